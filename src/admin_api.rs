@@ -0,0 +1,286 @@
+//! # Admin API Module
+//!
+//! Server-reported spend for organizations on API keys, from Anthropic's Admin
+//! Usage & Cost API: today's cost from the cost report, and the window's
+//! tokens from the messages usage report, priced like transcript entries.
+//! Needs an admin key (`sk-ant-admin...`). Results are cached in the db and
+//! refetched behind the expired figures, so only a render with nothing
+//! cached waits on the API.
+
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::usage_api::{CachedApi, cached_fetch};
+use crate::utils::key_fingerprint;
+use crate::window::{Efficiency, WindowMetrics};
+
+const COST_ENDPOINT: &str = "https://api.anthropic.com/v1/organizations/cost_report";
+const USAGE_ENDPOINT: &str = "https://api.anthropic.com/v1/organizations/usage_report/messages";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const CACHE_PREFIX: &str = "admin_api:";
+/// The reports trail live usage by a few minutes anyway
+const CACHE_TTL_SECONDS: i64 = 300;
+/// A failed call is retried no sooner than this
+const RETRY_SECONDS: i64 = 600;
+/// Hourly buckets per usage report page; a window spans at most six
+const USAGE_PAGE_LIMIT: &str = "24";
+/// Pages followed per report before giving up on the rest
+const MAX_PAGES: usize = 5;
+
+/// Organization-wide figures from the Admin API
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AdminUsage {
+    /// Cost so far in the current UTC day, the cost report's bucket, in USD
+    pub today_cost: f64,
+    /// The window's tokens priced at list rates, in USD
+    pub window_cost: f64,
+    pub tokens_input: u64,
+    pub tokens_output: u64,
+    pub tokens_cache_create: u64,
+    pub tokens_cache_read: u64,
+}
+
+impl AdminUsage {
+    /// Replace the log-derived window totals with the server-reported ones.
+    /// Burn rates stay log-derived; the usage report is hourly.
+    pub fn apply_to_window(&self, metrics: &mut WindowMetrics) {
+        metrics.total_cost = self.window_cost;
+        metrics.tokens_input = self.tokens_input;
+        metrics.tokens_output = self.tokens_output;
+        metrics.tokens_cache_create = self.tokens_cache_create;
+        metrics.tokens_cache_read = self.tokens_cache_read;
+        metrics.total_tokens = (self.tokens_input
+            + self.tokens_output
+            + self.tokens_cache_create
+            + self.tokens_cache_read) as f64;
+        metrics.noncache_tokens = (self.tokens_input + self.tokens_output) as f64;
+        metrics.efficiency = Efficiency::from_totals(
+            self.tokens_input,
+            self.tokens_output,
+            self.tokens_cache_create,
+            self.tokens_cache_read,
+            self.window_cost,
+        );
+    }
+}
+
+/// Today's cost and the usage since `window_start` (floored to the hour), from
+/// the db cache or the Admin API. `None` when the API failed with nothing
+/// cached for this day and window.
+pub fn get_admin_usage(
+    admin_key: &str,
+    window_start: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<AdminUsage> {
+    let window_start = window_start
+        .duration_trunc(TimeDelta::hours(1))
+        .unwrap_or(window_start);
+    let key = format!(
+        "{CACHE_PREFIX}{}:{}:{}",
+        key_fingerprint(admin_key),
+        now.date_naive(),
+        window_start.timestamp()
+    );
+    let api = CachedApi {
+        name: "admin_api",
+        key,
+        ttl_seconds: CACHE_TTL_SECONDS,
+        retry_seconds: RETRY_SECONDS,
+    };
+    let admin_key = admin_key.to_string();
+    cached_fetch(api, move || {
+        fetch_admin_usage(&admin_key, window_start, now)
+    })
+}
+
+fn fetch_admin_usage(
+    admin_key: &str,
+    window_start: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<AdminUsage> {
+    let agent = crate::usage_api::api_agent();
+    let day_start = now.duration_trunc(TimeDelta::days(1)).ok()?;
+    let costs: Vec<Bucket<CostResult>> = fetch_report(
+        &agent,
+        admin_key,
+        COST_ENDPOINT,
+        &[
+            ("starting_at", day_start.to_rfc3339()),
+            ("bucket_width", "1d".to_string()),
+        ],
+    )?;
+    let usage: Vec<Bucket<UsageResult>> = fetch_report(
+        &agent,
+        admin_key,
+        USAGE_ENDPOINT,
+        &[
+            ("starting_at", window_start.to_rfc3339()),
+            ("bucket_width", "1h".to_string()),
+            ("group_by[]", "model".to_string()),
+            ("limit", USAGE_PAGE_LIMIT.to_string()),
+        ],
+    )?;
+    let mut admin = window_usage(&usage);
+    admin.today_cost = total_cost(&costs);
+    Some(admin)
+}
+
+/// Every bucket of a paginated report, or `None` on any failed page
+fn fetch_report<T: DeserializeOwned>(
+    agent: &ureq::Agent,
+    admin_key: &str,
+    endpoint: &str,
+    query: &[(&str, String)],
+) -> Option<Vec<Bucket<T>>> {
+    let mut buckets = Vec::new();
+    let mut page: Option<String> = None;
+    for _ in 0..MAX_PAGES {
+        let mut request = agent
+            .get(endpoint)
+            .header("x-api-key", admin_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .query_pairs(query.iter().map(|(k, v)| (*k, v.as_str())));
+        if let Some(page) = page.as_deref() {
+            request = request.query("page", page);
+        }
+        let mut response = match request.call() {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("Admin API error: {}", e);
+                return None;
+            }
+        };
+        let report: Report<T> = response.body_mut().read_json().ok()?;
+        buckets.extend(report.data);
+        match report.next_page {
+            Some(next) if report.has_more => page = Some(next),
+            _ => return Some(buckets),
+        }
+    }
+    Some(buckets)
+}
+
+#[derive(Deserialize)]
+struct Report<T> {
+    data: Vec<Bucket<T>>,
+    #[serde(default)]
+    has_more: bool,
+    next_page: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Bucket<T> {
+    results: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct CostResult {
+    /// Decimal string in cents
+    amount: String,
+}
+
+#[derive(Deserialize)]
+struct UsageResult {
+    #[serde(default)]
+    uncached_input_tokens: u64,
+    #[serde(default)]
+    cache_creation: CacheCreation,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    model: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct CacheCreation {
+    #[serde(default)]
+    ephemeral_5m_input_tokens: u64,
+    #[serde(default)]
+    ephemeral_1h_input_tokens: u64,
+}
+
+/// Sum of the cost report in USD
+fn total_cost(buckets: &[Bucket<CostResult>]) -> f64 {
+    buckets
+        .iter()
+        .flat_map(|bucket| &bucket.results)
+        .filter_map(|result| result.amount.parse::<f64>().ok())
+        .sum::<f64>()
+        / 100.0
+}
+
+/// Token totals of the usage report, each model's share priced at its rates
+fn window_usage(buckets: &[Bucket<UsageResult>]) -> AdminUsage {
+    let mut admin = AdminUsage::default();
+    for result in buckets.iter().flat_map(|bucket| &bucket.results) {
+        let cache_create = result.cache_creation.ephemeral_5m_input_tokens
+            + result.cache_creation.ephemeral_1h_input_tokens;
+        admin.tokens_input += result.uncached_input_tokens;
+        admin.tokens_output += result.output_tokens;
+        admin.tokens_cache_create += cache_create;
+        admin.tokens_cache_read += result.cache_read_input_tokens;
+        let usage = serde_json::json!({
+            "input_tokens": result.uncached_input_tokens,
+            "output_tokens": result.output_tokens,
+            "cache_creation_input_tokens": cache_create,
+            "cache_read_input_tokens": result.cache_read_input_tokens,
+            "cache_creation": {
+                "ephemeral_5m_input_tokens": result.cache_creation.ephemeral_5m_input_tokens,
+                "ephemeral_1h_input_tokens": result.cache_creation.ephemeral_1h_input_tokens,
+            },
+        });
+        admin.window_cost += crate::pricing::calculate_cost_for_usage(
+            result.model.as_deref().unwrap_or_default(),
+            &usage,
+        );
+    }
+    admin
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cost_report_sums_cents_to_dollars() {
+        let report: Report<CostResult> = serde_json::from_str(
+            r#"{"data": [{"starting_at": "2026-10-15T00:00:00Z", "ending_at": "2026-10-16T00:00:00Z",
+            "results": [{"currency": "USD", "amount": "1234.5", "workspace_id": null,
+            "description": "Claude Sonnet 4 Usage - Input Tokens", "cost_type": "tokens"},
+            {"currency": "USD", "amount": "65.5", "workspace_id": null, "cost_type": "web_search"}]}],
+            "has_more": false, "next_page": null}"#,
+        )
+        .unwrap();
+        assert!((total_cost(&report.data) - 13.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn usage_report_totals_tokens_across_models() {
+        let report: Report<UsageResult> = serde_json::from_str(
+            r#"{"data": [
+            {"starting_at": "2026-10-15T10:00:00Z", "ending_at": "2026-10-15T11:00:00Z", "results": [
+                {"uncached_input_tokens": 1000, "cache_creation": {"ephemeral_1h_input_tokens": 0,
+                "ephemeral_5m_input_tokens": 200}, "cache_read_input_tokens": 5000,
+                "output_tokens": 300, "server_tool_use": {"web_search_requests": 0},
+                "model": "claude-sonnet-4-6", "api_key_id": null}]},
+            {"starting_at": "2026-10-15T11:00:00Z", "ending_at": "2026-10-15T12:00:00Z", "results": []}],
+            "has_more": true, "next_page": "page_2"}"#,
+        )
+        .unwrap();
+        assert_eq!(report.next_page.as_deref(), Some("page_2"));
+        let admin = window_usage(&report.data);
+        assert_eq!(
+            (
+                admin.tokens_input,
+                admin.tokens_output,
+                admin.tokens_cache_create,
+                admin.tokens_cache_read
+            ),
+            (1000, 300, 200, 5000)
+        );
+        assert!(admin.window_cost > 0.0);
+        assert_eq!(admin.today_cost, 0.0);
+    }
+}
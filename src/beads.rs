@@ -6,13 +6,21 @@
 //! Beads is a distributed, git-backed issue tracker for AI agents.
 //! See: https://github.com/steveyegge/beads
 
-use crate::models::{Bead, BeadStatus, BeadsCounts, BeadsInfo, PriorityCounts, TypeCounts};
+use crate::models::{
+    Bead, BeadStatus, BeadsCounts, BeadsInfo, PriorityCounts, StaleBead, TypeCounts,
+};
+#[cfg(feature = "db")]
 use rusqlite::{Connection, OptionalExtension};
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 /// Canonical database filename for beads
 const BEADS_DB_NAME: &str = "beads.db";
 
+/// Git-tracked JSONL export, present before the database is hydrated
+const BEADS_JSONL_NAME: &str = "issues.jsonl";
+
 /// Redirect filename that points to another .beads directory
 const REDIRECT_FILE: &str = "redirect";
 
@@ -67,19 +75,61 @@ fn find_beads_dir(start_path: &Path) -> Option<PathBuf> {
 
 /// Query beads information from the database
 ///
+/// Hooked and in-progress issues not updated for `stale_after_hours`
+/// (0 disables the check) are listed as stale.
+///
+/// With `labels` set, only issues carrying a label matching one of the
+/// globs (e.g. `agent:*`) are counted.
+///
+/// Falls back to the issues JSONL export when the database is missing or
+/// unreadable (including builds without the `db` feature).
+///
 /// Returns None if:
 /// - No .beads directory is found
-/// - The database doesn't exist
-/// - Any query errors occur
-pub fn get_beads_info(project_dir: &Path) -> Option<BeadsInfo> {
-    let beads_dir = find_beads_dir(project_dir)?;
-    let db_path = beads_dir.join(BEADS_DB_NAME);
-
-    if !db_path.is_file() {
+/// - Neither the database nor the JSONL export exists
+/// - Both fail to read
+pub fn get_beads_info(
+    project_dir: &Path,
+    stale_after_hours: u32,
+    labels: &[String],
+) -> Option<BeadsInfo> {
+    if crate::utils::known_absent("beads", project_dir) {
         return None;
     }
+    let sources = find_beads_dir(project_dir)
+        .map(|dir| (dir.join(BEADS_DB_NAME), dir.join(BEADS_JSONL_NAME), dir))
+        .filter(|(db_path, jsonl_path, _)| db_path.is_file() || jsonl_path.is_file());
+    let Some((db_path, jsonl_path, beads_dir)) = sources else {
+        crate::utils::remember_absent("beads", project_dir);
+        return None;
+    };
+    db_path
+        .is_file()
+        .then(|| read_beads_db(&beads_dir, &db_path, stale_after_hours, labels))
+        .flatten()
+        .or_else(|| read_beads_jsonl(&beads_dir, &jsonl_path, stale_after_hours, labels))
+}
+
+/// Reading the beads database needs the SQLite the `db` feature brings in.
+#[cfg(not(feature = "db"))]
+fn read_beads_db(
+    _beads_dir: &Path,
+    _db_path: &Path,
+    _stale_after_hours: u32,
+    _labels: &[String],
+) -> Option<BeadsInfo> {
+    None
+}
 
-    let conn = Connection::open(&db_path).ok()?;
+#[cfg(feature = "db")]
+fn read_beads_db(
+    beads_dir: &Path,
+    db_path: &Path,
+    stale_after_hours: u32,
+    labels: &[String],
+) -> Option<BeadsInfo> {
+    let conn = Connection::open(db_path).ok()?;
+    create_scoped_view(&conn, labels).ok()?;
 
     // Get status counts
     let counts = query_status_counts(&conn)?;
@@ -99,6 +149,24 @@ pub fn get_beads_info(project_dir: &Path) -> Option<BeadsInfo> {
     // Get top labels
     let top_labels = query_top_labels(&conn, 5).unwrap_or_default();
 
+    // Get issues held up by open blockers
+    let blocked_by_deps = query_blocked_by_deps(&conn).unwrap_or(0);
+
+    // Get work ready to pull
+    let ready = query_ready_count(&conn).unwrap_or(0);
+
+    // Get hooked/in-progress work nobody has touched lately
+    let stale = if stale_after_hours > 0 {
+        query_stale_work(&conn, i64::from(stale_after_hours) * 3600).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    // Get today's burndown
+    let (opened_today, closed_today) =
+        query_burndown(&conn, start_of_day(crate::utils::now_utc().timestamp()))
+            .unwrap_or_default();
+
     let total_open = counts.open + counts.in_progress + counts.blocked + counts.hooked;
 
     Some(BeadsInfo {
@@ -110,16 +178,51 @@ pub fn get_beads_info(project_dir: &Path) -> Option<BeadsInfo> {
         total_open,
         epic_count,
         top_labels,
+        blocked_by_deps,
+        ready,
+        stale,
+        opened_today,
+        closed_today,
     })
 }
 
+/// Create the `scoped_issues` view the queries read: every issue, or with
+/// `labels` set only issues carrying a label that matches one of the globs
+/// (SQLite `GLOB`, so `agent:*`). Blockers are still looked up in `issues`,
+/// since work outside the scope can hold up work inside it.
+#[cfg(feature = "db")]
+fn create_scoped_view(conn: &Connection, labels: &[String]) -> rusqlite::Result<()> {
+    if labels.is_empty() {
+        return conn.execute_batch("CREATE TEMP VIEW scoped_issues AS SELECT * FROM main.issues");
+    }
+    conn.execute_batch("CREATE TEMP TABLE scope_labels (pattern TEXT NOT NULL)")?;
+    let mut insert = conn.prepare("INSERT INTO temp.scope_labels (pattern) VALUES (?1)")?;
+    for label in labels {
+        insert.execute([label])?;
+    }
+    conn.execute_batch(
+        r#"
+        CREATE TEMP VIEW scoped_issues AS
+        SELECT *
+        FROM main.issues i
+        WHERE EXISTS (
+            SELECT 1
+            FROM main.labels l
+            JOIN temp.scope_labels s ON l.label GLOB s.pattern
+            WHERE l.issue_id = i.id
+        )
+        "#,
+    )
+}
+
 /// Query status counts for non-closed issues
+#[cfg(feature = "db")]
 fn query_status_counts(conn: &Connection) -> Option<BeadsCounts> {
     let mut stmt = conn
         .prepare(
             r#"
             SELECT status, COUNT(*) as count
-            FROM issues
+            FROM scoped_issues
             WHERE status NOT IN ('closed', 'tombstone')
               AND (deleted_at IS NULL OR deleted_at = '')
             GROUP BY status
@@ -154,6 +257,7 @@ fn query_status_counts(conn: &Connection) -> Option<BeadsCounts> {
 }
 
 /// Query the current work item (hooked or in_progress, highest priority first)
+#[cfg(feature = "db")]
 fn query_current_work(conn: &Connection) -> Option<Bead> {
     // Priority: hooked first (agent actively working), then in_progress
     // Within each status, sort by priority (lower = more critical)
@@ -161,7 +265,7 @@ fn query_current_work(conn: &Connection) -> Option<Bead> {
         .prepare(
             r#"
             SELECT id, title, status, priority, issue_type, assignee, estimated_minutes
-            FROM issues
+            FROM scoped_issues
             WHERE status IN ('hooked', 'in_progress')
               AND (deleted_at IS NULL OR deleted_at = '')
             ORDER BY
@@ -194,12 +298,13 @@ fn query_current_work(conn: &Connection) -> Option<Bead> {
 }
 
 /// Query priority counts for open issues (non-closed, non-tombstone)
+#[cfg(feature = "db")]
 fn query_priority_counts(conn: &Connection) -> Option<PriorityCounts> {
     let mut stmt = conn
         .prepare(
             r#"
             SELECT priority, COUNT(*) as count
-            FROM issues
+            FROM scoped_issues
             WHERE status NOT IN ('closed', 'tombstone')
               AND (deleted_at IS NULL OR deleted_at = '')
             GROUP BY priority
@@ -231,12 +336,13 @@ fn query_priority_counts(conn: &Connection) -> Option<PriorityCounts> {
 }
 
 /// Query issue type counts for open issues
+#[cfg(feature = "db")]
 fn query_type_counts(conn: &Connection) -> Option<TypeCounts> {
     let mut stmt = conn
         .prepare(
             r#"
             SELECT LOWER(COALESCE(issue_type, 'task')) as type, COUNT(*) as count
-            FROM issues
+            FROM scoped_issues
             WHERE status NOT IN ('closed', 'tombstone')
               AND (deleted_at IS NULL OR deleted_at = '')
             GROUP BY type
@@ -271,14 +377,15 @@ fn query_type_counts(conn: &Connection) -> Option<TypeCounts> {
 /// Count epics (issues with hierarchical IDs that have children)
 /// Epics are detected by looking for issues whose IDs are prefixes of other issues
 /// e.g., bd-abc is an epic if bd-abc.1 or bd-abc.2 exists
+#[cfg(feature = "db")]
 fn query_epic_count(conn: &Connection) -> Option<usize> {
     // Count issues that have children (their ID is a prefix of another issue's ID)
     let count: i64 = conn
         .query_row(
             r#"
             SELECT COUNT(DISTINCT parent.id)
-            FROM issues parent
-            JOIN issues child ON child.id LIKE parent.id || '.%'
+            FROM scoped_issues parent
+            JOIN scoped_issues child ON child.id LIKE parent.id || '.%'
             WHERE parent.status NOT IN ('closed', 'tombstone')
               AND (parent.deleted_at IS NULL OR parent.deleted_at = '')
               AND child.status NOT IN ('closed', 'tombstone')
@@ -292,14 +399,181 @@ fn query_epic_count(conn: &Connection) -> Option<usize> {
     Some(count as usize)
 }
 
+/// Count open issues blocked by unresolved dependencies
+/// An issue is blocked while any issue it has a `blocks` dependency on is
+/// neither closed nor deleted, whatever its own status says
+#[cfg(feature = "db")]
+fn query_blocked_by_deps(conn: &Connection) -> Option<usize> {
+    let count: i64 = conn
+        .query_row(
+            r#"
+            SELECT COUNT(DISTINCT i.id)
+            FROM scoped_issues i
+            JOIN dependencies d ON d.issue_id = i.id
+            JOIN issues blocker ON blocker.id = d.depends_on_id
+            WHERE d.type = 'blocks'
+              AND i.status NOT IN ('closed', 'tombstone')
+              AND (i.deleted_at IS NULL OR i.deleted_at = '')
+              AND blocker.status NOT IN ('closed', 'tombstone')
+              AND (blocker.deleted_at IS NULL OR blocker.deleted_at = '')
+            "#,
+            [],
+            |row| row.get(0),
+        )
+        .ok()?;
+
+    Some(count as usize)
+}
+
+/// Count ready work: open, unassigned issues with no unresolved `blocks`
+/// dependency (the same blockers as [`query_blocked_by_deps`])
+#[cfg(feature = "db")]
+fn query_ready_count(conn: &Connection) -> Option<usize> {
+    let count: i64 = conn
+        .query_row(
+            r#"
+            SELECT COUNT(*)
+            FROM scoped_issues i
+            WHERE i.status = 'open'
+              AND (i.assignee IS NULL OR i.assignee = '')
+              AND (i.deleted_at IS NULL OR i.deleted_at = '')
+              AND NOT EXISTS (
+                  SELECT 1
+                  FROM dependencies d
+                  JOIN issues blocker ON blocker.id = d.depends_on_id
+                  WHERE d.issue_id = i.id
+                    AND d.type = 'blocks'
+                    AND blocker.status NOT IN ('closed', 'tombstone')
+                    AND (blocker.deleted_at IS NULL OR blocker.deleted_at = '')
+              )
+            "#,
+            [],
+            |row| row.get(0),
+        )
+        .ok()?;
+
+    Some(count as usize)
+}
+
+/// Query hooked/in-progress issues whose updated_at is older than
+/// `threshold_secs`, oldest first
+#[cfg(feature = "db")]
+fn query_stale_work(conn: &Connection, threshold_secs: i64) -> Option<Vec<StaleBead>> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, title, status, updated_at
+            FROM scoped_issues
+            WHERE status IN ('hooked', 'in_progress')
+              AND (deleted_at IS NULL OR deleted_at = '')
+            "#,
+        )
+        .ok()?;
+
+    let now = crate::utils::now_utc().timestamp();
+    let rows = stmt
+        .query_map([], |row| {
+            let status: String = row.get(2)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                status,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })
+        .ok()?;
+
+    let mut stale: Vec<StaleBead> = rows
+        .flatten()
+        .filter_map(|(id, title, status, updated_at)| {
+            let idle_secs = now - parse_beads_timestamp(updated_at.as_deref()?)?;
+            (idle_secs > threshold_secs).then(|| StaleBead {
+                id,
+                title,
+                status: BeadStatus::parse(&status).unwrap_or(BeadStatus::InProgress),
+                idle_secs,
+            })
+        })
+        .collect();
+    stale.sort_by_key(|b| std::cmp::Reverse(b.idle_secs));
+
+    Some(stale)
+}
+
+/// Parse a beads timestamp (RFC 3339, or SQLite's `YYYY-MM-DD HH:MM:SS` in UTC)
+/// Count issues (`opened`, `closed`) since `since`. The SQL bound is a date a
+/// day early so any timestamp format or offset passes; the exact cut is made
+/// on the parsed timestamps.
+#[cfg(feature = "db")]
+fn query_burndown(conn: &Connection, since: i64) -> Option<(usize, usize)> {
+    let bound = chrono::DateTime::from_timestamp(since - 86_400, 0)?
+        .format("%Y-%m-%d")
+        .to_string();
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT created_at, closed_at
+            FROM scoped_issues
+            WHERE (created_at >= ?1 OR closed_at >= ?1)
+              AND (deleted_at IS NULL OR deleted_at = '')
+            "#,
+        )
+        .ok()?;
+
+    let rows = stmt
+        .query_map([bound], |row| {
+            let created_at: Option<String> = row.get(0)?;
+            let closed_at: Option<String> = row.get(1)?;
+            Ok((created_at, closed_at))
+        })
+        .ok()?;
+
+    let after = |ts: Option<&str>| {
+        ts.and_then(parse_beads_timestamp)
+            .is_some_and(|t| t >= since)
+    };
+    let (mut opened, mut closed) = (0, 0);
+    for (created_at, closed_at) in rows.flatten() {
+        opened += usize::from(after(created_at.as_deref()));
+        closed += usize::from(after(closed_at.as_deref()));
+    }
+    Some((opened, closed))
+}
+
+/// Unix timestamp of the local midnight starting the day containing `now`
+fn start_of_day(now: i64) -> i64 {
+    use chrono::TimeZone;
+    chrono::Local
+        .timestamp_opt(now, 0)
+        .single()
+        .and_then(|dt| {
+            dt.date_naive()
+                .and_hms_opt(0, 0, 0)?
+                .and_local_timezone(chrono::Local)
+                .earliest()
+        })
+        .map_or(now, |midnight| midnight.timestamp())
+}
+
+pub(crate) fn parse_beads_timestamp(ts: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(ts)
+        .map(|dt| dt.timestamp())
+        .or_else(|_| {
+            chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S")
+                .map(|dt| dt.and_utc().timestamp())
+        })
+        .ok()
+}
+
 /// Query top labels by usage count
+#[cfg(feature = "db")]
 fn query_top_labels(conn: &Connection, limit: usize) -> Option<Vec<(String, usize)>> {
     let mut stmt = conn
         .prepare(
             r#"
             SELECT l.label, COUNT(*) as count
             FROM labels l
-            JOIN issues i ON l.issue_id = i.id
+            JOIN scoped_issues i ON l.issue_id = i.id
             WHERE i.status NOT IN ('closed', 'tombstone')
               AND (i.deleted_at IS NULL OR i.deleted_at = '')
             GROUP BY l.label
@@ -320,6 +594,240 @@ fn query_top_labels(conn: &Connection, limit: usize) -> Option<Vec<(String, usiz
     Some(rows.flatten().collect())
 }
 
+/// One issue line of the JSONL export
+#[derive(Debug, Deserialize)]
+struct JsonlIssue {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    status: String,
+    /// Beads defaults new issues to P2
+    #[serde(default = "default_priority")]
+    priority: i32,
+    issue_type: Option<String>,
+    assignee: Option<String>,
+    estimated_minutes: Option<i32>,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    closed_at: Option<String>,
+    deleted_at: Option<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    dependencies: Vec<JsonlDependency>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonlDependency {
+    depends_on_id: String,
+    #[serde(rename = "type", default)]
+    dep_type: String,
+}
+
+fn default_priority() -> i32 {
+    2
+}
+
+impl JsonlIssue {
+    /// Not closed, tombstoned, or soft-deleted
+    fn is_live(&self) -> bool {
+        !matches!(self.status.as_str(), "closed" | "tombstone")
+            && self.deleted_at.as_deref().is_none_or(str::is_empty)
+    }
+}
+
+/// Compute beads information from the JSONL export, mirroring the queries
+/// [`read_beads_db`] runs against the database
+fn read_beads_jsonl(
+    beads_dir: &Path,
+    jsonl_path: &Path,
+    stale_after_hours: u32,
+    labels: &[String],
+) -> Option<BeadsInfo> {
+    let content = std::fs::read_to_string(jsonl_path).ok()?;
+    let now = crate::utils::now_utc().timestamp();
+    let mut info = parse_beads_jsonl(&content, stale_after_hours, labels, now);
+    info.beads_dir = beads_dir.to_string_lossy().to_string();
+    Some(info)
+}
+
+fn parse_beads_jsonl(
+    content: &str,
+    stale_after_hours: u32,
+    labels: &[String],
+    now: i64,
+) -> BeadsInfo {
+    // Later lines for the same ID supersede earlier ones
+    let mut by_id: HashMap<String, JsonlIssue> = HashMap::new();
+    for issue in content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str::<JsonlIssue>(line).ok())
+    {
+        by_id.insert(issue.id.clone(), issue);
+    }
+    let in_scope = |issue: &JsonlIssue| {
+        labels.is_empty()
+            || issue.labels.iter().any(|label| {
+                labels
+                    .iter()
+                    .any(|pattern| crate::utils::glob_match(pattern, label))
+            })
+    };
+    let live: Vec<&JsonlIssue> = by_id
+        .values()
+        .filter(|i| i.is_live() && in_scope(i))
+        .collect();
+
+    let mut counts = BeadsCounts::default();
+    let mut priorities = PriorityCounts::default();
+    let mut types = TypeCounts::default();
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    for issue in &live {
+        match issue.status.as_str() {
+            "open" => counts.open += 1,
+            "in_progress" => counts.in_progress += 1,
+            "blocked" => counts.blocked += 1,
+            "hooked" => counts.hooked += 1,
+            "deferred" => counts.deferred += 1,
+            "pinned" => counts.pinned += 1,
+            _ => {}
+        }
+        match issue.priority {
+            0 => priorities.p0_critical += 1,
+            1 => priorities.p1_high += 1,
+            2 => priorities.p2_medium += 1,
+            _ => priorities.p3_p4_low += 1,
+        }
+        match issue
+            .issue_type
+            .as_deref()
+            .unwrap_or("task")
+            .to_lowercase()
+            .as_str()
+        {
+            "task" => types.task += 1,
+            "bug" => types.bug += 1,
+            "feature" => types.feature += 1,
+            "epic" => types.epic += 1,
+            _ => types.other += 1,
+        }
+        for label in &issue.labels {
+            *labels.entry(label.as_str()).or_default() += 1;
+        }
+    }
+
+    // Hooked first, then in_progress; most critical, then most recently updated
+    let current_work = live
+        .iter()
+        .filter(|i| matches!(i.status.as_str(), "hooked" | "in_progress"))
+        .min_by_key(|i| {
+            (
+                i.status != "hooked",
+                i.priority,
+                std::cmp::Reverse(i.updated_at.as_deref().and_then(parse_beads_timestamp)),
+            )
+        })
+        .map(|i| Bead {
+            id: i.id.clone(),
+            title: i.title.clone(),
+            status: BeadStatus::parse(&i.status).unwrap_or(BeadStatus::Open),
+            priority: i.priority,
+            issue_type: i.issue_type.clone(),
+            assignee: i.assignee.clone(),
+            estimated_minutes: i.estimated_minutes,
+            labels: i.labels.clone(),
+        });
+
+    let epic_count = live
+        .iter()
+        .filter(|parent| {
+            let prefix = format!("{}.", parent.id);
+            live.iter().any(|child| child.id.starts_with(&prefix))
+        })
+        .count();
+
+    let mut top_labels: Vec<(String, usize)> = labels
+        .into_iter()
+        .map(|(label, count)| (label.to_string(), count))
+        .collect();
+    top_labels.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top_labels.truncate(5);
+
+    // A `blocks` dependency holds while its target is live, in scope or not
+    let has_open_blocker = |issue: &JsonlIssue| {
+        issue.dependencies.iter().any(|dep| {
+            dep.dep_type == "blocks"
+                && by_id
+                    .get(&dep.depends_on_id)
+                    .is_some_and(|blocker| blocker.is_live())
+        })
+    };
+    let blocked_by_deps = live.iter().filter(|i| has_open_blocker(i)).count();
+    let ready = live
+        .iter()
+        .filter(|i| {
+            i.status == "open"
+                && i.assignee.as_deref().is_none_or(str::is_empty)
+                && !has_open_blocker(i)
+        })
+        .count();
+
+    let mut stale: Vec<StaleBead> = if stale_after_hours > 0 {
+        let threshold_secs = i64::from(stale_after_hours) * 3600;
+        live.iter()
+            .filter(|i| matches!(i.status.as_str(), "hooked" | "in_progress"))
+            .filter_map(|i| {
+                let idle_secs = now - parse_beads_timestamp(i.updated_at.as_deref()?)?;
+                (idle_secs > threshold_secs).then(|| StaleBead {
+                    id: i.id.clone(),
+                    title: i.title.clone(),
+                    status: BeadStatus::parse(&i.status).unwrap_or(BeadStatus::InProgress),
+                    idle_secs,
+                })
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    stale.sort_by_key(|b| std::cmp::Reverse(b.idle_secs));
+
+    // Burndown counts closed issues too, so it walks every non-deleted issue
+    let since = start_of_day(now);
+    let after = |ts: &Option<String>| {
+        ts.as_deref()
+            .and_then(parse_beads_timestamp)
+            .is_some_and(|t| t >= since)
+    };
+    let (mut opened_today, mut closed_today) = (0, 0);
+    for issue in by_id
+        .values()
+        .filter(|i| i.deleted_at.as_deref().is_none_or(str::is_empty) && in_scope(i))
+    {
+        opened_today += usize::from(after(&issue.created_at));
+        closed_today += usize::from(after(&issue.closed_at));
+    }
+
+    let total_open = counts.open + counts.in_progress + counts.blocked + counts.hooked;
+
+    BeadsInfo {
+        beads_dir: String::new(),
+        current_work,
+        counts,
+        priorities,
+        types,
+        total_open,
+        epic_count,
+        top_labels,
+        blocked_by_deps,
+        ready,
+        stale,
+        opened_today,
+        closed_today,
+    }
+}
+
 /// Format a bead for display in the statusline
 ///
 /// Returns something like "🪝 bd-a1b2: Fix the auth bug" for hooked,
@@ -346,6 +854,226 @@ pub fn format_bead_display(bead: &Bead, max_len: usize) -> String {
 mod tests {
     use super::*;
 
+    #[cfg(feature = "db")]
+    #[test]
+    fn test_query_blocked_by_deps_ignores_resolved_blockers() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE issues (id TEXT PRIMARY KEY, status TEXT, deleted_at TEXT);
+            CREATE TABLE dependencies (issue_id TEXT, depends_on_id TEXT, type TEXT);
+            INSERT INTO issues VALUES
+                ('bd-1', 'open', NULL), ('bd-2', 'in_progress', NULL), ('bd-3', 'open', NULL),
+                ('bd-4', 'closed', NULL), ('bd-5', 'open', NULL), ('bd-6', 'closed', NULL);
+            INSERT INTO dependencies VALUES
+                ('bd-1', 'bd-2', 'blocks'), ('bd-1', 'bd-5', 'blocks'),
+                ('bd-3', 'bd-4', 'blocks'),
+                ('bd-5', 'bd-2', 'related'),
+                ('bd-6', 'bd-2', 'blocks');
+            "#,
+        )
+        .unwrap();
+        create_scoped_view(&conn, &[]).unwrap();
+        assert_eq!(query_blocked_by_deps(&conn), Some(1));
+
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(query_blocked_by_deps(&conn), None);
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn test_query_ready_count_skips_assigned_and_blocked() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE issues (id TEXT PRIMARY KEY, status TEXT, assignee TEXT, deleted_at TEXT);
+            CREATE TABLE dependencies (issue_id TEXT, depends_on_id TEXT, type TEXT);
+            INSERT INTO issues VALUES
+                ('bd-1', 'open', NULL, NULL), ('bd-2', 'open', '', NULL),
+                ('bd-3', 'open', 'max', NULL), ('bd-4', 'in_progress', NULL, NULL),
+                ('bd-5', 'open', NULL, NULL), ('bd-6', 'open', NULL, NULL),
+                ('bd-7', 'closed', NULL, NULL), ('bd-8', 'open', NULL, '2024-01-01');
+            INSERT INTO dependencies VALUES
+                ('bd-5', 'bd-4', 'blocks'), ('bd-6', 'bd-7', 'blocks'), ('bd-1', 'bd-4', 'related');
+            "#,
+        )
+        .unwrap();
+        create_scoped_view(&conn, &[]).unwrap();
+        // bd-1, bd-2, and bd-6 (its only blocker is closed)
+        assert_eq!(query_ready_count(&conn), Some(3));
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn test_query_stale_work_lists_idle_hooked_and_in_progress() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE issues (id TEXT PRIMARY KEY, title TEXT, status TEXT, updated_at TEXT,
+                deleted_at TEXT);
+            INSERT INTO issues VALUES
+                ('bd-1', 'Old hook', 'hooked', '2020-01-01T00:00:00Z', NULL),
+                ('bd-2', 'Older work', 'in_progress', '2019-01-01 00:00:00', NULL),
+                ('bd-3', 'Fresh work', 'in_progress', '2999-01-01T00:00:00Z', NULL),
+                ('bd-4', 'Old but open', 'open', '2019-01-01T00:00:00Z', NULL);
+            "#,
+        )
+        .unwrap();
+        create_scoped_view(&conn, &[]).unwrap();
+        let stale = query_stale_work(&conn, 24 * 3600).unwrap();
+        let ids: Vec<_> = stale.iter().map(|b| (b.id.as_str(), b.status)).collect();
+        assert_eq!(
+            ids,
+            [
+                ("bd-2", BeadStatus::InProgress),
+                ("bd-1", BeadStatus::Hooked)
+            ]
+        );
+        assert!(stale[0].idle_secs > stale[1].idle_secs);
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn test_query_burndown_counts_today_only() {
+        let now = chrono::Utc::now();
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE issues (id TEXT PRIMARY KEY, created_at TEXT, closed_at TEXT,
+                deleted_at TEXT);
+            "#,
+        )
+        .unwrap();
+        let today = now.to_rfc3339();
+        let sql_today = now.format("%Y-%m-%d %H:%M:%S").to_string();
+        for (id, created, closed, deleted) in [
+            ("bd-1", today.as_str(), None, None),
+            ("bd-2", sql_today.as_str(), Some(today.as_str()), None),
+            (
+                "bd-3",
+                "2020-01-01T00:00:00Z",
+                Some(sql_today.as_str()),
+                None,
+            ),
+            (
+                "bd-4",
+                "2020-01-01T00:00:00Z",
+                Some("2020-01-02T00:00:00Z"),
+                None,
+            ),
+            ("bd-5", today.as_str(), None, Some(today.as_str())),
+        ] {
+            conn.execute(
+                "INSERT INTO issues VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![id, created, closed, deleted],
+            )
+            .unwrap();
+        }
+        create_scoped_view(&conn, &[]).unwrap();
+        let since = start_of_day(now.timestamp());
+        assert_eq!(query_burndown(&conn, since), Some((2, 2)));
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn test_scoped_view_keeps_outside_blockers() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE issues (id TEXT PRIMARY KEY, status TEXT, assignee TEXT, deleted_at TEXT);
+            CREATE TABLE labels (issue_id TEXT, label TEXT);
+            CREATE TABLE dependencies (issue_id TEXT, depends_on_id TEXT, type TEXT);
+            INSERT INTO issues VALUES
+                ('bd-1', 'open', NULL, NULL), ('bd-2', 'open', NULL, NULL),
+                ('bd-3', 'open', NULL, NULL), ('bd-4', 'open', NULL, NULL);
+            INSERT INTO labels VALUES
+                ('bd-1', 'agent:max'), ('bd-1', 'api'), ('bd-2', 'agent:sam'), ('bd-3', 'ui');
+            INSERT INTO dependencies VALUES ('bd-2', 'bd-3', 'blocks');
+            "#,
+        )
+        .unwrap();
+        create_scoped_view(&conn, &["agent:*".to_string()]).unwrap();
+        let counts = query_status_counts(&conn).unwrap();
+        assert_eq!(counts.open, 2);
+        // bd-2 is held up by bd-3, which is outside the scope
+        assert_eq!(query_blocked_by_deps(&conn), Some(1));
+        assert_eq!(query_ready_count(&conn), Some(1));
+        let labels = query_top_labels(&conn, 5).unwrap();
+        assert!(!labels.iter().any(|(label, _)| label == "ui"));
+    }
+
+    #[test]
+    fn test_parse_beads_jsonl_matches_db_queries() {
+        let jsonl = r#"
+{"id":"bd-1","title":"Epic","status":"open","priority":1,"issue_type":"epic","labels":["api"]}
+{"id":"bd-1.1","title":"Child","status":"in_progress","priority":2,"updated_at":"2020-01-01T00:00:00Z","labels":["api","ui"]}
+{"id":"bd-2","title":"Hooked","status":"hooked","priority":3,"updated_at":"2999-01-01T00:00:00Z"}
+{"id":"bd-3","title":"Waiting","status":"open","dependencies":[{"issue_id":"bd-3","depends_on_id":"bd-2","type":"blocks"}]}
+{"id":"bd-4","title":"Unblocked","status":"open","dependencies":[{"issue_id":"bd-4","depends_on_id":"bd-5","type":"blocks"}]}
+{"id":"bd-5","title":"Done","status":"closed"}
+{"id":"bd-6","title":"Taken","status":"open","assignee":"max","priority":0}
+{"id":"bd-7","title":"Gone","status":"open","deleted_at":"2024-01-01T00:00:00Z"}
+not json
+{"id":"bd-2","title":"Hooked","status":"hooked","priority":0,"updated_at":"2999-01-01T00:00:00Z"}
+"#;
+        let now = chrono::Utc::now().timestamp();
+        let info = parse_beads_jsonl(jsonl, 24, &[], now);
+        assert_eq!(
+            (
+                info.counts.open,
+                info.counts.in_progress,
+                info.counts.hooked
+            ),
+            (4, 1, 1)
+        );
+        assert_eq!(info.total_open, 6);
+        assert_eq!(info.priorities.p0_critical, 2);
+        assert_eq!((info.types.epic, info.types.task), (1, 5));
+        assert_eq!(info.epic_count, 1);
+        assert_eq!(info.top_labels[0], ("api".to_string(), 2));
+        let work = info.current_work.expect("current work");
+        assert_eq!(
+            (work.id.as_str(), work.status),
+            ("bd-2", BeadStatus::Hooked)
+        );
+        assert_eq!(info.blocked_by_deps, 1);
+        // bd-1 and bd-4 (its blocker is closed)
+        assert_eq!(info.ready, 2);
+        assert_eq!(info.stale.len(), 1);
+        assert_eq!(info.stale[0].id, "bd-1.1");
+        assert_eq!((info.opened_today, info.closed_today), (0, 0));
+
+        let info = parse_beads_jsonl(jsonl, 24, &["ui".to_string()], now);
+        assert_eq!(info.total_open, 1);
+        assert_eq!(info.top_labels.len(), 2);
+
+        let today = chrono::Utc::now().to_rfc3339();
+        let jsonl = format!(
+            r#"
+{{"id":"bd-1","status":"open","created_at":"{today}"}}
+{{"id":"bd-2","status":"closed","created_at":"{today}","closed_at":"{today}"}}
+{{"id":"bd-3","status":"closed","closed_at":"2020-01-01T00:00:00Z"}}
+"#
+        );
+        let burndown = parse_beads_jsonl(&jsonl, 24, &[], now);
+        assert_eq!((burndown.opened_today, burndown.closed_today), (2, 1));
+    }
+
+    #[test]
+    fn test_get_beads_info_falls_back_to_jsonl() {
+        let project = tempfile::TempDir::new().unwrap();
+        let beads_dir = project.path().join(".beads");
+        std::fs::create_dir(&beads_dir).unwrap();
+        std::fs::write(
+            beads_dir.join(BEADS_JSONL_NAME),
+            r#"{"id":"bd-1","title":"Only line","status":"open"}"#,
+        )
+        .unwrap();
+        let info = get_beads_info(project.path(), 24, &[]).expect("beads info");
+        assert_eq!(info.total_open, 1);
+        assert!(info.beads_dir.ends_with(".beads"));
+    }
+
     #[test]
     fn test_format_bead_display() {
         let bead = Bead {
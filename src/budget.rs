@@ -0,0 +1,341 @@
+use crate::cli::Args;
+use crate::db::DailyCost;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike};
+use serde::Serialize;
+
+/// Calendar period a budget limit applies to (local time). `Monthly` follows
+/// the billing period when `--billing-day` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl BudgetPeriod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BudgetPeriod::Daily => "daily",
+            BudgetPeriod::Weekly => "weekly",
+            BudgetPeriod::Monthly => "monthly",
+        }
+    }
+}
+
+/// Global spend per period, as aggregated by the db ledger. `None` means the
+/// period total is unknown (e.g. db cache disabled) and its budget is skipped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BudgetSpend {
+    pub today: Option<f64>,
+    /// Since Monday, including today
+    pub week: Option<f64>,
+    /// Since the start of the current billing period, including today
+    pub month: Option<f64>,
+}
+
+impl BudgetSpend {
+    /// Sum the week and billing period from the recorded daily ledger.
+    pub fn from_history(history: &[DailyCost], today: NaiveDate, billing_day: u32) -> Self {
+        let week_start = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+        let (period_start, _) = billing_period(today, billing_day);
+        let sum_since = |start: NaiveDate| {
+            history
+                .iter()
+                .filter(|d| d.date >= start && d.date <= today)
+                .fold(0.0, |sum, d| sum + d.cost)
+        };
+        Self {
+            today: None,
+            week: Some(sum_since(week_start)),
+            month: Some(sum_since(period_start)),
+        }
+    }
+}
+
+/// Billing period `[start, end)` containing `today`, anchored on `billing_day`
+/// of each month. Anchors past a month's last day fall on that last day, so a
+/// 31st anchor renews on Feb 28/29. `billing_day = 1` is the calendar month.
+pub fn billing_period(today: NaiveDate, billing_day: u32) -> (NaiveDate, NaiveDate) {
+    let anchor_in = |year: i32, month: u32| {
+        let day = billing_day.clamp(1, 31);
+        (1..=day)
+            .rev()
+            .find_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+            .unwrap_or(today)
+    };
+    let shift = |year: i32, month: u32, delta: i32| {
+        let index = year * 12 + month as i32 - 1 + delta;
+        (index.div_euclid(12), index.rem_euclid(12) as u32 + 1)
+    };
+
+    let this_anchor = anchor_in(today.year(), today.month());
+    let (start_year, start_month) = if today >= this_anchor {
+        (today.year(), today.month())
+    } else {
+        shift(today.year(), today.month(), -1)
+    };
+    let (end_year, end_month) = shift(start_year, start_month, 1);
+    (
+        anchor_in(start_year, start_month),
+        anchor_in(end_year, end_month),
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetLine {
+    pub period: BudgetPeriod,
+    pub limit: f64,
+    pub spent: f64,
+}
+
+impl BudgetLine {
+    pub fn percent(&self) -> f64 {
+        self.spent / self.limit * 100.0
+    }
+}
+
+/// Spend checked against every configured budget that has a known total.
+#[derive(Debug, Clone, Default)]
+pub struct BudgetStatus {
+    pub lines: Vec<BudgetLine>,
+}
+
+impl BudgetStatus {
+    /// Returns `None` when no budget is configured or none has a known spend.
+    pub fn evaluate(args: &Args, spend: &BudgetSpend) -> Option<Self> {
+        let candidates = [
+            (BudgetPeriod::Daily, args.budget_daily, spend.today),
+            (BudgetPeriod::Weekly, args.budget_weekly, spend.week),
+            (BudgetPeriod::Monthly, args.budget_monthly, spend.month),
+        ];
+        let lines: Vec<BudgetLine> = candidates
+            .into_iter()
+            .filter_map(|(period, limit, spent)| {
+                let limit = limit.filter(|l| *l > 0.0)?;
+                Some(BudgetLine {
+                    period,
+                    limit,
+                    spent: spent?,
+                })
+            })
+            .collect();
+        (!lines.is_empty()).then_some(Self { lines })
+    }
+
+    /// The budget closest to (or furthest past) its limit.
+    pub fn tightest(&self) -> Option<&BudgetLine> {
+        self.lines
+            .iter()
+            .max_by(|a, b| a.percent().total_cmp(&b.percent()))
+    }
+}
+
+/// Project end-of-period spend from the period-to-date total.
+///
+/// The base is the run-rate over the elapsed part of the billing period. Each
+/// remaining day is scaled by how its weekday compared to the average over the
+/// complete days in `history`, so quiet weekends don't get billed like busy
+/// weekdays.
+pub fn forecast_month(
+    month_cost: f64,
+    history: &[DailyCost],
+    now: DateTime<Local>,
+    billing_day: u32,
+) -> f64 {
+    let today = now.date_naive();
+    let (period_start, period_end) = billing_period(today, billing_day);
+    let day_fraction = f64::from(now.num_seconds_from_midnight()) / 86_400.0;
+    let elapsed_days = ((today - period_start).num_days() as f64 + day_fraction).max(1.0);
+    let run_rate = month_cost / elapsed_days;
+    let factors = weekday_factors(history, today);
+
+    let mut projected =
+        month_cost + run_rate * factors[weekday_index(today)] * (1.0 - day_fraction);
+    let mut day = today + Duration::days(1);
+    while day < period_end {
+        projected += run_rate * factors[weekday_index(day)];
+        day += Duration::days(1);
+    }
+    projected
+}
+
+/// Average daily spend over the `days` complete days before `today`.
+///
+/// Days without a row count as zero, but the window never reaches back past
+/// the first recorded day, so a fresh ledger isn't diluted by empty days.
+/// `None` when the window holds no recorded spend.
+pub fn trailing_average(history: &[DailyCost], today: NaiveDate, days: i64) -> Option<f64> {
+    let window_start = today - Duration::days(days);
+    let first = history
+        .iter()
+        .map(|d| d.date)
+        .filter(|d| *d < today)
+        .min()?
+        .max(window_start);
+    let total: f64 = history
+        .iter()
+        .filter(|d| d.date >= first && d.date < today)
+        .map(|d| d.cost)
+        .sum();
+    let counted = (today - first).num_days();
+    (total > 0.0 && counted > 0).then(|| total / counted as f64)
+}
+
+fn weekday_index(date: NaiveDate) -> usize {
+    date.weekday().num_days_from_monday() as usize
+}
+
+/// Per-weekday spend relative to the overall daily average (1.0 = average).
+/// Only complete days before `today` count; days without a row count as zero.
+fn weekday_factors(history: &[DailyCost], today: NaiveDate) -> [f64; 7] {
+    let Some(first) = history.iter().map(|d| d.date).min() else {
+        return [1.0; 7];
+    };
+    let mut totals = [0.0; 7];
+    let mut counts = [0u32; 7];
+    let mut day = first;
+    while day < today {
+        counts[weekday_index(day)] += 1;
+        day += Duration::days(1);
+    }
+    for entry in history.iter().filter(|d| d.date < today) {
+        totals[weekday_index(entry.date)] += entry.cost;
+    }
+
+    let days: u32 = counts.iter().sum();
+    let overall = totals.iter().sum::<f64>() / f64::from(days.max(1));
+    if overall <= 0.0 {
+        return [1.0; 7];
+    }
+    std::array::from_fn(|i| {
+        if counts[i] == 0 {
+            1.0
+        } else {
+            totals[i] / f64::from(counts[i]) / overall
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use clap::Parser;
+
+    #[test]
+    fn evaluate_skips_unset_limits_and_unknown_spend() {
+        let mut args = Args::parse_from(["claude_statusline"]);
+        args.budget_daily = Some(25.0);
+        args.budget_weekly = Some(100.0);
+        args.budget_monthly = Some(300.0);
+        let spend = BudgetSpend {
+            today: Some(5.0),
+            week: None,
+            month: Some(192.0),
+        };
+
+        let status = BudgetStatus::evaluate(&args, &spend).expect("budgets configured");
+        let periods: Vec<BudgetPeriod> = status.lines.iter().map(|l| l.period).collect();
+        assert_eq!(periods, vec![BudgetPeriod::Daily, BudgetPeriod::Monthly]);
+
+        let tightest = status.tightest().unwrap();
+        assert_eq!(tightest.period, BudgetPeriod::Monthly);
+        assert!((tightest.percent() - 64.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn evaluate_returns_none_without_budgets() {
+        let args = Args::parse_from(["claude_statusline"]);
+        let spend = BudgetSpend {
+            today: Some(5.0),
+            ..BudgetSpend::default()
+        };
+        assert!(BudgetStatus::evaluate(&args, &spend).is_none());
+    }
+
+    #[test]
+    fn forecast_uses_run_rate_and_weekday_weighting() {
+        // Wednesday 2025-10-15 at midnight: 14 complete days elapsed.
+        let now = Local.with_ymd_and_hms(2025, 10, 15, 0, 0, 0).unwrap();
+
+        // Flat history: pure run-rate, $10/day over 31 days.
+        let flat: Vec<DailyCost> = (1..=14)
+            .map(|d| DailyCost {
+                date: NaiveDate::from_ymd_opt(2025, 10, d).unwrap(),
+                cost: 10.0,
+            })
+            .collect();
+        assert!((forecast_month(140.0, &flat, now, 1) - 310.0).abs() < 1e-6);
+
+        // Weekday-only spend: remaining weekends project to zero.
+        let weekdays: Vec<DailyCost> = flat
+            .iter()
+            .filter(|d| d.date.weekday().num_days_from_monday() < 5)
+            .copied()
+            .collect();
+        let weighted = forecast_month(100.0, &weekdays, now, 1);
+        let run_rate = 100.0 / 14.0;
+        // 13 remaining weekdays (15th-31st) weighted by 14/10, no weekend spend.
+        let expected = 100.0 + run_rate * (14.0 / 10.0) * 13.0;
+        assert!((weighted - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn billing_period_follows_anchor_day() {
+        let day = |y: i32, m: u32, d: u32| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(
+            billing_period(day(2025, 10, 15), 1),
+            (day(2025, 10, 1), day(2025, 11, 1))
+        );
+        assert_eq!(
+            billing_period(day(2025, 10, 15), 14),
+            (day(2025, 10, 14), day(2025, 11, 14))
+        );
+        assert_eq!(
+            billing_period(day(2025, 10, 13), 14),
+            (day(2025, 9, 14), day(2025, 10, 14))
+        );
+        assert_eq!(
+            billing_period(day(2026, 1, 5), 14),
+            (day(2025, 12, 14), day(2026, 1, 14))
+        );
+        // A 31st anchor clamps to short months.
+        assert_eq!(
+            billing_period(day(2025, 3, 1), 31),
+            (day(2025, 2, 28), day(2025, 3, 31))
+        );
+    }
+
+    #[test]
+    fn spend_from_history_uses_week_and_billing_period() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 10, d).unwrap();
+        let history: Vec<DailyCost> = [(10, 1.0), (13, 2.0), (14, 4.0), (15, 8.0)]
+            .into_iter()
+            .map(|(d, cost)| DailyCost { date: day(d), cost })
+            .collect();
+
+        let calendar = BudgetSpend::from_history(&history, day(15), 1);
+        let anchored = BudgetSpend::from_history(&history, day(15), 14);
+
+        assert_eq!(calendar.week, Some(14.0));
+        assert_eq!(calendar.month, Some(15.0));
+        assert_eq!(anchored.month, Some(12.0));
+    }
+
+    #[test]
+    fn trailing_average_zero_fills_but_skips_days_before_first_row() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 10, d).unwrap();
+        let history: Vec<DailyCost> = [(1, 50.0), (9, 7.0), (12, 14.0), (15, 99.0)]
+            .into_iter()
+            .map(|(d, cost)| DailyCost { date: day(d), cost })
+            .collect();
+
+        // 8th-14th: 7 + 14 over seven days; today and older rows excluded.
+        assert_eq!(trailing_average(&history, day(15), 7), Some(3.0));
+        // Ledger starting on the 9th only averages the days it covers.
+        assert_eq!(trailing_average(&history[1..], day(11), 7), Some(3.5));
+        assert_eq!(trailing_average(&history[3..], day(15), 7), None);
+    }
+}
@@ -1,5 +1,32 @@
+use chrono::{DateTime, NaiveDate, Utc};
 use std::path::PathBuf;
 
+fn parse_rfc3339(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|err| format!("expected an RFC 3339 timestamp: {err}"))
+}
+
+fn parse_issue_pattern(value: &str) -> Result<String, String> {
+    regex::Regex::new(value)
+        .map(|_| value.to_string())
+        .map_err(|err| format!("invalid issue pattern: {err}"))
+}
+
+/// A finite number no lower than zero, such as a USD amount or a percentage.
+fn parse_non_negative(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(n) if n.is_finite() && n >= 0.0 => Ok(n),
+        _ => Err(format!("expected a non-negative number, got {value:?}")),
+    }
+}
+
+/// `YYYY-MM` as the first day of that month.
+fn parse_month(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", value.trim()), "%Y-%m-%d")
+        .map_err(|_| format!("expected a month as YYYY-MM, got {value:?}"))
+}
+
 #[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TimeFormatArg {
     Auto,
@@ -19,10 +46,56 @@ pub enum GitArg {
     Verbose,
 }
 
+/// Parts the Gas Town header segment can show, for `--gastown-fields`
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GastownFieldArg {
+    /// Agent role icon
+    Role,
+    /// Polecat (and crew) counts
+    Polecats,
+    /// Rig status LEDs
+    Rigs,
+    /// In-flight convoys
+    Convoys,
+    /// Refinery merge queue
+    Queue,
+    /// Hooked issue
+    Hook,
+    /// Unread mail
+    Mail,
+}
+
+/// Facts the git header segments can show, for `--git-fields`
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitFieldArg {
+    Branch,
+    Commit,
+    Dirty,
+    Untracked,
+    Conflicts,
+    Operation,
+    Upstream,
+    #[value(name = "ahead_behind", alias = "ahead-behind")]
+    AheadBehind,
+    Stash,
+    #[value(name = "head_age", alias = "head-age")]
+    HeadAge,
+    Ci,
+    Describe,
+    Pr,
+    Subproject,
+    Issues,
+    Sparse,
+    Lfs,
+    Health,
+}
+
 #[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BurnScopeArg {
     /// Per-minute burn for this session only (input+output tokens)
     Session,
+    /// Per-minute burn for the current project in window (input+output tokens)
+    Project,
     /// Per-minute burn across all projects in window (input+output tokens)
     Global,
 }
@@ -33,6 +106,8 @@ pub enum WindowScopeArg {
     Global,
     /// Restrict window to current project only
     Project,
+    /// Restrict window to the current session only
+    Session,
 }
 
 #[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
@@ -43,6 +118,38 @@ pub enum WindowAnchorArg {
     Log,
 }
 
+/// Claude subscription plan whose price API-equivalent spend is compared against
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionArg {
+    /// Claude Pro ($20/month)
+    Pro,
+    /// Claude Max 5x ($100/month)
+    #[value(name = "max5x")]
+    Max5x,
+    /// Claude Max 20x ($200/month)
+    #[value(name = "max20x")]
+    Max20x,
+}
+
+impl SubscriptionArg {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SubscriptionArg::Pro => "pro",
+            SubscriptionArg::Max5x => "max5x",
+            SubscriptionArg::Max20x => "max20x",
+        }
+    }
+
+    /// List monthly price in USD
+    pub fn monthly_price_usd(self) -> f64 {
+        match self {
+            SubscriptionArg::Pro => 20.0,
+            SubscriptionArg::Max5x => 100.0,
+            SubscriptionArg::Max20x => 200.0,
+        }
+    }
+}
+
 /// Built-in presets that pre-configure display.* atomic toggles.
 /// CLI / env / TOML atomic flags still win over the preset.
 #[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
@@ -61,6 +168,144 @@ pub enum Command {
     Doctor,
     /// Install or update Claude Code statusLine settings
     Init(InitArgs),
+    /// Summarize today's spend from local transcripts without reading hook stdin
+    Report(ReportArgs),
+    /// Show lifetime totals (cost, tokens, first-seen and busiest day) from the db ledger
+    Stats,
+    /// Inspect model pricing
+    Pricing(PricingArgs),
+    /// Maintain the SQLite cache database
+    Db(DbArgs),
+    /// Share usage history between machines through a directory (e.g. a git repo)
+    Sync(SyncArgs),
+    /// Manage cached scan, usage API, and session parse state
+    Cache(CacheArgs),
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub command: CacheCommand,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum CacheCommand {
+    /// Drop cached state so the next render recomputes it. Without flags the
+    /// session is read from hook JSON on stdin (SessionEnd, PreCompact).
+    Invalidate {
+        /// Session whose cached state is dropped
+        #[arg(long, conflicts_with = "all")]
+        session: Option<String>,
+        /// Drop cached state for every session, including cached API responses
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct SyncArgs {
+    #[command(subcommand)]
+    pub command: SyncCommand,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum SyncCommand {
+    /// Append history changed since the last export to this machine's snapshot
+    Export,
+    /// Merge snapshot lines other machines appended since the last import
+    Import,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct DbArgs {
+    #[command(subcommand)]
+    pub command: DbCommand,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum DbCommand {
+    /// Apply pending schema migrations (normally run on first use)
+    Migrate {
+        /// List pending migrations without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Delete history past the retention policy now (otherwise done daily)
+    Prune,
+    /// Checkpoint the WAL and reclaim free pages now (otherwise done by threshold)
+    Maintain,
+    /// Run SQLite's integrity check; exits non-zero when the database is damaged
+    Check,
+    /// Write usage history (entries and daily ledgers) for backup or another machine
+    Export {
+        #[arg(long, value_enum, default_value = "json")]
+        format: ExportFormatArg,
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Merge a `db export` file into this database; the newer copy of a row wins
+    Import {
+        /// Export file, or `-` for stdin
+        path: PathBuf,
+    },
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormatArg {
+    Json,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct PricingArgs {
+    #[command(subcommand)]
+    pub command: PricingCommand,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum PricingCommand {
+    /// Print a model's resolved per-token rates and where they came from
+    Show {
+        /// Model id as it appears in transcripts (e.g. claude-sonnet-4-6)
+        model: String,
+    },
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ReportArgs {
+    /// Break today's cost down by project directory
+    #[arg(long)]
+    pub by_project: bool,
+
+    /// Break today's cost and tokens down by model
+    #[arg(long)]
+    pub by_model: bool,
+
+    /// Total today's and this billing period's cost per client tag (`[tags]`)
+    #[arg(long)]
+    pub by_tag: bool,
+
+    /// Daily peak 5-hour and weekly utilization from recorded usage API fetches
+    #[arg(long)]
+    pub limits: bool,
+
+    /// Monthly statement for the billing period starting in this month (YYYY-MM)
+    #[arg(long, value_parser = parse_month)]
+    pub month: Option<NaiveDate>,
+
+    /// Write the monthly statement as a markdown invoice to this file
+    /// (defaults to the current billing period without `--month`)
+    #[arg(long, value_name = "PATH")]
+    pub invoice: Option<PathBuf>,
+
+    /// Seat-level report over other users' statusline databases or
+    /// `--seat-export` files (`.jsonl`); repeat per seat as `[NAME=]PATH`
+    #[arg(long, value_name = "[NAME=]PATH")]
+    pub team: Vec<crate::team::SeatSource>,
+
+    /// Print this machine's billing-period ledger as JSONL for a teammate's `--team`
+    #[arg(long, conflicts_with = "team")]
+    pub seat_export: bool,
 }
 
 #[derive(clap::Args, Debug, Clone)]
@@ -116,6 +361,10 @@ pub struct Args {
     #[arg(long = "time", value_enum, default_value_t = TimeFormatArg::Auto)]
     pub time_fmt: TimeFormatArg,
 
+    /// IANA timezone (e.g. Europe/Berlin) used for reset clocks, daily totals, and window math
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_TIMEZONE")]
+    pub timezone: Option<String>,
+
     /// Enable truecolor accents (or set CLAUDE_TRUECOLOR=1)
     #[arg(long)]
     pub truecolor: bool,
@@ -144,6 +393,13 @@ pub struct Args {
         env = "CLAUDE_STATUSLINE_COST_NO_TODAY"
     )]
     pub no_cost_today: bool,
+    /// Hide the `(+38%)` today-vs-7-day-average suffix on the today cost
+    #[arg(
+        long = "no-cost-today-trend",
+        global = true,
+        env = "CLAUDE_STATUSLINE_COST_NO_TODAY_TREND"
+    )]
+    pub no_cost_today_trend: bool,
     /// Hide the window cost token (`window:$X`)
     #[arg(
         long = "no-cost-window",
@@ -165,6 +421,30 @@ pub struct Args {
         env = "CLAUDE_STATUSLINE_COST_PROVENANCE"
     )]
     pub cost_provenance: bool,
+    /// Show the month-to-date cost segment across all sessions (opt-in)
+    #[arg(long = "cost-mtd", global = true, env = "CLAUDE_STATUSLINE_COST_MTD")]
+    pub cost_mtd: bool,
+    /// Show the projected end-of-month cost segment (opt-in)
+    #[arg(
+        long = "cost-forecast",
+        global = true,
+        env = "CLAUDE_STATUSLINE_COST_FORECAST"
+    )]
+    pub cost_forecast: bool,
+    /// Show the estimated energy/CO2e segment for this session (opt-in)
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_ENVIRONMENT")]
+    pub environment: bool,
+    /// Grid carbon intensity in gCO2e/kWh for the energy estimate (default 400)
+    #[arg(
+        long,
+        global = true,
+        env = "CLAUDE_STATUSLINE_CARBON_INTENSITY",
+        value_parser = parse_non_negative
+    )]
+    pub carbon_intensity: Option<f64>,
+    /// Per-family energy coefficients, with `[environment.<family>]` overrides applied
+    #[arg(skip)]
+    pub energy_model: crate::environment::EnergyModel,
     /// Hide the lines-delta segment in the header
     #[arg(
         long = "no-cost-lines-delta",
@@ -173,6 +453,122 @@ pub struct Args {
     )]
     pub no_cost_lines_delta: bool,
 
+    /// Per-session cost cap in USD; the session cost turns red and JSON sets
+    /// `session.over_cap` once it is exceeded
+    #[arg(
+        long,
+        global = true,
+        env = "CLAUDE_STATUSLINE_SESSION_COST_CAP",
+        value_parser = parse_non_negative
+    )]
+    pub session_cost_cap: Option<f64>,
+
+    /// Daily spend budget in USD across all sessions (local calendar day)
+    #[arg(
+        long,
+        global = true,
+        env = "CLAUDE_STATUSLINE_BUDGET_DAILY",
+        value_parser = parse_non_negative
+    )]
+    pub budget_daily: Option<f64>,
+    /// Weekly spend budget in USD across all sessions (Monday-start local week)
+    #[arg(
+        long,
+        global = true,
+        env = "CLAUDE_STATUSLINE_BUDGET_WEEKLY",
+        value_parser = parse_non_negative
+    )]
+    pub budget_weekly: Option<f64>,
+    /// Monthly spend budget in USD across all sessions (local calendar month)
+    #[arg(
+        long,
+        global = true,
+        env = "CLAUDE_STATUSLINE_BUDGET_MONTHLY",
+        value_parser = parse_non_negative
+    )]
+    pub budget_monthly: Option<f64>,
+    /// Day of month the billing period starts (1-31); monthly budget, MTD,
+    /// forecast, and subscription value follow this period
+    #[arg(
+        long,
+        global = true,
+        env = "CLAUDE_STATUSLINE_BILLING_DAY",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..=31)
+    )]
+    pub billing_day: u32,
+
+    /// Subscription plan; shows `value:Nx`, the month's API-equivalent cost
+    /// as a multiple of the plan price
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_SUBSCRIPTION")]
+    pub subscription: Option<SubscriptionArg>,
+    /// Monthly subscription price in USD; overrides the `--subscription` list price
+    #[arg(
+        long,
+        global = true,
+        env = "CLAUDE_STATUSLINE_SUBSCRIPTION_PRICE",
+        value_parser = parse_non_negative
+    )]
+    pub subscription_price: Option<f64>,
+
+    /// Display costs in this currency (ISO code, e.g. EUR); JSON keeps the USD
+    /// fields and adds converted siblings
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_CURRENCY")]
+    pub currency: Option<String>,
+    /// Units of `--currency` per USD; takes precedence over a fetched rate
+    #[arg(
+        long,
+        global = true,
+        env = "CLAUDE_STATUSLINE_CURRENCY_RATE",
+        value_parser = parse_non_negative
+    )]
+    pub currency_rate: Option<f64>,
+    /// Fetch the USD exchange rate online (cached for a day) when no static rate is set
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_CURRENCY_FETCH")]
+    pub currency_fetch: bool,
+
+    /// Model price table overriding the built-in one, in the pricing.json schema
+    /// (default: `~/.claude/statusline-pricing.json` when present)
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_PRICING_FILE")]
+    pub pricing_file: Option<PathBuf>,
+    /// Price models missing from the built-in table with LiteLLM's price
+    /// database (downloaded at most daily, cached in the db)
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_PRICING_FETCH")]
+    pub pricing_fetch: bool,
+    /// Model id glob priced at $0 (local models, internal fine-tunes); tokens
+    /// still count. Repeat or comma-separate
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        env = "CLAUDE_STATUSLINE_FREE_MODELS"
+    )]
+    pub free_model: Vec<String>,
+    /// Percentage a paid proxy adds on top of model pricing (e.g. 5 for 5%)
+    #[arg(
+        long,
+        global = true,
+        env = "CLAUDE_STATUSLINE_PROXY_SURCHARGE_PERCENT",
+        value_parser = parse_non_negative
+    )]
+    pub proxy_surcharge_percent: Option<f64>,
+    /// Flat USD per million tokens a paid proxy adds on top of model pricing
+    #[arg(
+        long,
+        global = true,
+        env = "CLAUDE_STATUSLINE_PROXY_SURCHARGE_PER_MTOK",
+        value_parser = parse_non_negative
+    )]
+    pub proxy_surcharge_per_mtok: Option<f64>,
+    /// Warn in `doctor` when the pricing table is older than this many days
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 120,
+        env = "CLAUDE_STATUSLINE_PRICING_STALE_DAYS"
+    )]
+    pub pricing_stale_days: u32,
+
     // ---- display.usage.* ----
     /// Hide the 5-hour usage percent + inline reset token
     #[arg(
@@ -234,6 +630,16 @@ pub struct Args {
     pub no_context_compact_hint: bool,
 
     // ---- display.git.* ----
+    /// Exactly which git facts render, replacing the individual git toggles
+    /// (e.g. `branch,dirty,ahead_behind,stash`)
+    #[arg(
+        long = "git-fields",
+        global = true,
+        value_enum,
+        value_delimiter = ',',
+        env = "CLAUDE_STATUSLINE_GIT_FIELDS"
+    )]
+    pub git_fields: Vec<GitFieldArg>,
     /// Hide the branch name inside the git header segment
     #[arg(
         long = "no-git-branch",
@@ -255,6 +661,84 @@ pub struct Args {
         env = "CLAUDE_STATUSLINE_GIT_NO_AHEAD_BEHIND"
     )]
     pub no_git_ahead_behind: bool,
+    /// Show the upstream tracking branch (e.g. `origin/main`) next to ahead / behind on wide terminals (opt-in)
+    #[arg(
+        long = "git-upstream",
+        global = true,
+        env = "CLAUDE_STATUSLINE_GIT_UPSTREAM"
+    )]
+    pub git_upstream: bool,
+    /// Show the nearest tag and distance (`v1.4.2-12-gabc1234`) as a header segment (opt-in)
+    #[arg(
+        long = "git-describe",
+        global = true,
+        env = "CLAUDE_STATUSLINE_GIT_DESCRIBE"
+    )]
+    pub git_describe: bool,
+    /// Show the age of the HEAD commit (`3d`) inside the git header segment (opt-in)
+    #[arg(
+        long = "git-head-age",
+        global = true,
+        env = "CLAUDE_STATUSLINE_GIT_HEAD_AGE"
+    )]
+    pub git_head_age: bool,
+    /// Color the HEAD commit age as a warning once it is older than this many hours
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 48,
+        env = "CLAUDE_STATUSLINE_GIT_HEAD_AGE_WARN_HOURS"
+    )]
+    pub git_head_age_warn_hours: u32,
+    /// Show the current branch's pull request, review state, and mergeability via `gh` (opt-in; needs db_cache)
+    #[arg(long = "git-pr", global = true, env = "CLAUDE_STATUSLINE_GIT_PR")]
+    pub git_pr: bool,
+    /// Show CI check status for HEAD (✓/✗/●) next to the short commit via `gh` (opt-in; needs db_cache)
+    #[arg(long = "git-ci", global = true, env = "CLAUDE_STATUSLINE_GIT_CI")]
+    pub git_ci: bool,
+    /// Show issue keys parsed from the branch name (`PROJ-123`, `eng-456`, `#789`) (opt-in)
+    #[arg(
+        long = "git-issues",
+        global = true,
+        env = "CLAUDE_STATUSLINE_GIT_ISSUES"
+    )]
+    pub git_issues: bool,
+    /// Regex matching an issue key in the branch name; repeat for several trackers (replaces the built-in Jira/Linear/GitHub patterns)
+    #[arg(long, global = true, value_parser = parse_issue_pattern)]
+    pub git_issue_pattern: Vec<String>,
+    /// Link issue keys with OSC 8 hyperlinks; `{key}` expands to the key, without `#` for GitHub-style keys
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_GIT_ISSUE_URL")]
+    pub git_issue_url: Option<String>,
+    /// Warn (⚠) about huge untracked directories, an oversized git dir, and unignored build output; checked at most hourly (opt-in; needs db_cache)
+    #[arg(
+        long = "git-health",
+        global = true,
+        env = "CLAUDE_STATUSLINE_GIT_HEALTH"
+    )]
+    pub git_health: bool,
+    /// Git dir size in GB above which `--git-health` warns
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 2,
+        env = "CLAUDE_STATUSLINE_GIT_HEALTH_MAX_GIT_GB"
+    )]
+    pub git_health_max_git_gb: u32,
+    /// Wait at most this long for git inspection, then show the previous render's cached result (0 = no cap; needs db_cache)
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 0,
+        env = "CLAUDE_STATUSLINE_GIT_TIMEOUT_MS"
+    )]
+    pub git_timeout_ms: u32,
+    /// Hide the untracked file count (`?3`) and skip the worktree walk behind it
+    #[arg(
+        long = "no-git-untracked",
+        global = true,
+        env = "CLAUDE_STATUSLINE_GIT_NO_UNTRACKED"
+    )]
+    pub no_git_untracked: bool,
     /// Hide worktree segment (Claude internal worktrees + hook-provided linked worktree)
     #[arg(
         long = "no-git-worktree",
@@ -262,6 +746,28 @@ pub struct Args {
         env = "CLAUDE_STATUSLINE_GIT_NO_WORKTREE"
     )]
     pub no_git_worktree: bool,
+    /// Hide the monorepo subproject segment (`repo:packages/api`)
+    #[arg(
+        long = "no-git-subproject",
+        global = true,
+        env = "CLAUDE_STATUSLINE_GIT_NO_SUBPROJECT"
+    )]
+    pub no_git_subproject: bool,
+    /// Don't color the branch as a warning on the default branch or a protected one
+    #[arg(
+        long = "no-git-protected-warning",
+        global = true,
+        env = "CLAUDE_STATUSLINE_GIT_NO_PROTECTED_WARNING"
+    )]
+    pub no_git_protected_warning: bool,
+    /// Branch glob treated as protected, besides the remote default; repeat or comma-separate (default: main, master)
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        env = "CLAUDE_STATUSLINE_GIT_PROTECTED_BRANCHES"
+    )]
+    pub git_protected_branch: Vec<String>,
 
     // ---- display.workspace.* ----
     /// Hide the cwd / directory header segment
@@ -329,6 +835,22 @@ pub struct Args {
         env = "CLAUDE_STATUSLINE_INTEGRATIONS_NO_BEADS_ALERTS"
     )]
     pub no_integrations_beads_alerts: bool,
+    /// Flag hooked / in-progress beads not updated for this many hours as stale (`stale:2`; 0 = off)
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 24,
+        env = "CLAUDE_STATUSLINE_BEADS_STALE_HOURS"
+    )]
+    pub beads_stale_hours: u32,
+    /// Only count beads issues with a label matching this glob (e.g. `agent:*`); repeat or comma-separate
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        env = "CLAUDE_STATUSLINE_BEADS_LABELS"
+    )]
+    pub beads_label: Vec<String>,
     /// Hide the gastown header segment (does NOT skip the work; use --no-subsystem-gastown for that)
     #[arg(
         long = "no-integrations-gastown",
@@ -336,6 +858,30 @@ pub struct Args {
         env = "CLAUDE_STATUSLINE_INTEGRATIONS_NO_GASTOWN"
     )]
     pub no_integrations_gastown: bool,
+    /// Exactly which Gas Town parts render (e.g. `role,mail,queue`); parts outside the agent's role never show
+    #[arg(
+        long = "gastown-fields",
+        global = true,
+        value_enum,
+        value_delimiter = ',',
+        env = "CLAUDE_STATUSLINE_GASTOWN_FIELDS"
+    )]
+    pub gastown_fields: Vec<GastownFieldArg>,
+    /// Truncate the Gas Town segment to this many columns (0 = 30/45/60 by terminal width)
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 0,
+        env = "CLAUDE_STATUSLINE_GASTOWN_MAX_WIDTH"
+    )]
+    pub gastown_max_width: u32,
+    /// Spell out Gas Town roles and counters instead of emoji (`mayor | 3 polecats`)
+    #[arg(
+        long = "no-gastown-emoji",
+        global = true,
+        env = "CLAUDE_STATUSLINE_GASTOWN_NO_EMOJI"
+    )]
+    pub no_gastown_emoji: bool,
     /// Hide the prompt-cache countdown token in the status line
     #[arg(
         long = "no-integrations-prompt-cache",
@@ -396,14 +942,26 @@ pub struct Args {
         env = "CLAUDE_STATUSLINE_JSON_NO_USAGE_LIMITS"
     )]
     pub no_json_usage_limits: bool,
-    /// Burn scope: session|global (default: session)
+    /// Omit the top-level blocks timeline (recent 5-hour blocks and gaps)
+    #[arg(
+        long = "no-json-blocks",
+        global = true,
+        env = "CLAUDE_STATUSLINE_JSON_NO_BLOCKS"
+    )]
+    pub no_json_blocks: bool,
+    /// Burn scope: session|project|global (default: session)
     #[arg(long, value_enum, default_value_t = BurnScopeArg::Session)]
     pub burn_scope: BurnScopeArg,
 
-    /// Window scope: global|project (default: global)
+    /// Window cost/token scope: global|project|session (default: global)
     #[arg(long, value_enum, default_value_t = WindowScopeArg::Global)]
     pub window_scope: WindowScopeArg,
 
+    /// Pin the current time (RFC 3339) to replay window math deterministically.
+    /// Skips the SQLite ledger so a replay cannot prune today's rows.
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_NOW", value_parser = parse_rfc3339)]
+    pub now: Option<DateTime<Utc>>,
+
     /// Debug mode: show detailed calculation information
     #[arg(long, env = "CLAUDE_DEBUG")]
     pub debug: bool,
@@ -455,6 +1013,113 @@ pub struct Args {
     )]
     pub no_subsystem_usage_api: bool,
 
+    /// Directory holding sync snapshots, one subdirectory per machine
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_SYNC_DIR")]
+    pub sync_dir: Option<PathBuf>,
+    /// This machine's name in the sync directory (default: hostname)
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_SYNC_MACHINE")]
+    pub sync_machine: Option<String>,
+
+    /// Days of per-entry usage history kept in the db (0 keeps it forever)
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 400,
+        env = "CLAUDE_STATUSLINE_ENTRIES_RETENTION_DAYS"
+    )]
+    pub entries_retention_days: u32,
+    /// Days cached API responses are kept after they were fetched (0 keeps them forever)
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 30,
+        env = "CLAUDE_STATUSLINE_API_CACHE_RETENTION_DAYS"
+    )]
+    pub api_cache_retention_days: u32,
+
+    /// Seconds today's cross-session cost sum is reused before re-querying (0 disables)
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 5,
+        env = "CLAUDE_STATUSLINE_GLOBAL_SUM_TTL_SECONDS"
+    )]
+    pub global_sum_ttl_seconds: u32,
+    /// Seconds a transcript scan is reused by later refreshes (0 always rescans)
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 3,
+        env = "CLAUDE_STATUSLINE_SCAN_TTL_SECONDS"
+    )]
+    pub scan_ttl_seconds: u32,
+    /// Seconds an OAuth usage API response is served before refetching
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 300,
+        env = "CLAUDE_STATUSLINE_USAGE_API_TTL_SECONDS"
+    )]
+    pub usage_api_ttl_seconds: u32,
+    /// Proxy for the OAuth usage API call (`http://`, `https://`, `socks4://`,
+    /// `socks5://`, `socks5h://`), overriding `ALL_PROXY`/`HTTPS_PROXY`/`HTTP_PROXY`
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_USAGE_API_PROXY")]
+    pub usage_api_proxy: Option<String>,
+    /// `[accounts]` label whose usage limits are fetched (default: the account
+    /// whose dir is the Claude data path in use)
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_ACCOUNT")]
+    pub account: Option<String>,
+    /// Admin API key (`sk-ant-admin...`); today's cost and the window totals
+    /// then come from the organization's Usage & Cost reports
+    #[arg(
+        long,
+        global = true,
+        env = "ANTHROPIC_ADMIN_KEY",
+        hide_env_values = true
+    )]
+    pub admin_api_key: Option<String>,
+    /// Treat the session as routed through OpenRouter even when neither
+    /// `ANTHROPIC_BASE_URL` nor the model id says so
+    #[arg(long, global = true, env = "CLAUDE_STATUSLINE_OPENROUTER")]
+    pub openrouter: bool,
+    /// OpenRouter API key whose credits and rate limit are shown (default: the
+    /// token Claude Code sends when `ANTHROPIC_BASE_URL` points at OpenRouter)
+    #[arg(
+        long,
+        global = true,
+        env = "OPENROUTER_API_KEY",
+        hide_env_values = true
+    )]
+    pub openrouter_key: Option<String>,
+    /// Seconds a missing credentials file, `.beads` directory, or Gas Town
+    /// marker is remembered before probing again (0 probes every render)
+    #[arg(
+        long,
+        global = true,
+        default_value_t = 30,
+        env = "CLAUDE_STATUSLINE_ABSENT_TTL_SECONDS"
+    )]
+    pub absent_ttl_seconds: u32,
+
+    /// Markup applied to report costs to get client-billable numbers (e.g. 1.25)
+    #[arg(
+        long,
+        global = true,
+        env = "CLAUDE_STATUSLINE_CHARGEBACK_MULTIPLIER",
+        value_parser = parse_non_negative
+    )]
+    pub chargeback_multiplier: Option<f64>,
+
+    /// Project directory -> client tag pairs from the config `[tags]` section
+    #[arg(skip)]
+    pub tags: Vec<(String, String)>,
+    /// Project directory -> pricing override pairs from `[project_pricing]`
+    #[arg(skip)]
+    pub project_pricing: Vec<(String, crate::pricing::ProjectRates)>,
+    /// Account label -> Claude config dir pairs from `[accounts]`
+    #[arg(skip)]
+    pub accounts: Vec<(String, PathBuf)>,
+
     #[arg(skip)]
     pub config_loaded: Option<PathBuf>,
 
@@ -463,6 +1128,41 @@ pub struct Args {
 }
 
 impl Args {
+    /// Whether a git fact renders: `--git-fields` when set, otherwise the
+    /// individual default-on and opt-in git toggles.
+    pub fn git_field(&self, field: GitFieldArg) -> bool {
+        if !self.git_fields.is_empty() {
+            return self.git_fields.contains(&field);
+        }
+        match field {
+            GitFieldArg::Branch => !self.no_git_branch,
+            GitFieldArg::Dirty => !self.no_git_dirty,
+            GitFieldArg::Untracked => !self.no_git_untracked,
+            GitFieldArg::AheadBehind => !self.no_git_ahead_behind,
+            GitFieldArg::Upstream => self.git_upstream,
+            GitFieldArg::HeadAge => self.git_head_age,
+            GitFieldArg::Ci => self.git_ci,
+            GitFieldArg::Describe => self.git_describe,
+            GitFieldArg::Pr => self.git_pr,
+            GitFieldArg::Subproject => !self.no_git_subproject,
+            GitFieldArg::Issues => self.git_issues,
+            GitFieldArg::Health => self.git_health,
+            GitFieldArg::Commit
+            | GitFieldArg::Conflicts
+            | GitFieldArg::Operation
+            | GitFieldArg::Stash
+            | GitFieldArg::Sparse
+            | GitFieldArg::Lfs => true,
+        }
+    }
+
+    /// Effective monthly subscription price, if a plan or price is configured
+    pub fn subscription_price_usd(&self) -> Option<f64> {
+        self.subscription_price
+            .or_else(|| self.subscription.map(SubscriptionArg::monthly_price_usd))
+            .filter(|price| *price > 0.0)
+    }
+
     pub fn parse() -> Self {
         Self::parse_effective_from(std::env::args_os())
     }
@@ -478,9 +1178,30 @@ impl Args {
 
 #[cfg(test)]
 mod tests {
-    use super::Args;
+    use super::{Args, GitFieldArg};
     use clap::{CommandFactory, Parser, error::ErrorKind};
 
+    #[test]
+    fn git_fields_replace_individual_toggles() {
+        let args = Args::try_parse_from(["claude_statusline", "--no-git-dirty"]).unwrap();
+        assert!(args.git_field(GitFieldArg::Branch));
+        assert!(!args.git_field(GitFieldArg::Dirty));
+        assert!(!args.git_field(GitFieldArg::Upstream));
+
+        let args = Args::try_parse_from([
+            "claude_statusline",
+            "--no-git-dirty",
+            "--git-fields",
+            "dirty,ahead_behind,upstream",
+        ])
+        .unwrap();
+        assert!(!args.git_field(GitFieldArg::Branch));
+        assert!(args.git_field(GitFieldArg::Dirty));
+        assert!(args.git_field(GitFieldArg::AheadBehind));
+        assert!(args.git_field(GitFieldArg::Upstream));
+        assert!(!args.git_field(GitFieldArg::Stash));
+    }
+
     #[test]
     fn version_metadata_uses_cargo_package_version() {
         let command = Args::command();
@@ -495,4 +1216,46 @@ mod tests {
         assert_eq!(err.kind(), ErrorKind::DisplayVersion);
         assert!(err.to_string().contains(env!("CARGO_PKG_VERSION")));
     }
+
+    #[test]
+    fn now_flag_parses_rfc3339_into_utc() {
+        let args =
+            Args::try_parse_from(["claude_statusline", "--now", "2025-03-30T01:30:00+01:00"])
+                .unwrap();
+
+        assert_eq!(
+            args.now.map(|dt| dt.to_rfc3339()).as_deref(),
+            Some("2025-03-30T00:30:00+00:00")
+        );
+        assert!(Args::try_parse_from(["claude_statusline", "--now", "yesterday"]).is_err());
+    }
+
+    #[test]
+    fn amount_flags_reject_negative_numbers() {
+        let args = Args::try_parse_from(["claude_statusline", "--session-cost-cap", "15"]).unwrap();
+
+        assert_eq!(args.session_cost_cap, Some(15.0));
+        assert!(Args::try_parse_from(["claude_statusline", "--session-cost-cap=-5"]).is_err());
+        assert!(Args::try_parse_from(["claude_statusline", "--session-cost-cap", "inf"]).is_err());
+        assert!(
+            Args::try_parse_from(["claude_statusline", "--proxy-surcharge-percent=-5"]).is_err()
+        );
+        assert!(
+            Args::try_parse_from(["claude_statusline", "--proxy-surcharge-per-mtok", "x"]).is_err()
+        );
+        for flag in [
+            "--budget-daily=-1",
+            "--budget-weekly=-1",
+            "--budget-monthly=-1",
+            "--subscription-price=-20",
+            "--currency-rate=-0.9",
+            "--carbon-intensity=-400",
+            "--chargeback-multiplier=-1.25",
+        ] {
+            assert!(
+                Args::try_parse_from(["claude_statusline", flag]).is_err(),
+                "{flag}"
+            );
+        }
+    }
 }
@@ -12,9 +12,10 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::cli::{
-    Args, BurnScopeArg, GitArg, LabelsArg, PresetArg, TimeFormatArg, WindowAnchorArg,
-    WindowScopeArg,
+    Args, BurnScopeArg, GastownFieldArg, GitArg, GitFieldArg, LabelsArg, PresetArg,
+    SubscriptionArg, TimeFormatArg, WindowAnchorArg, WindowScopeArg,
 };
+use crate::pricing::ProjectRates;
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct FileConfig {
@@ -22,8 +23,60 @@ pub struct FileConfig {
     pub labels: Option<LabelsArg>,
     pub git: Option<GitArg>,
     pub time_fmt: Option<TimeFormatArg>,
+    pub timezone: Option<String>,
     pub truecolor: Option<bool>,
     pub prompt_cache_ttl_seconds: Option<u64>,
+    pub session_cost_cap: Option<f64>,
+    pub budget_daily: Option<f64>,
+    pub budget_weekly: Option<f64>,
+    pub budget_monthly: Option<f64>,
+    pub billing_day: Option<u32>,
+    pub chargeback_multiplier: Option<f64>,
+    pub environment: Option<bool>,
+    pub carbon_intensity: Option<f64>,
+    /// `[environment.<family>]` coefficient overrides: (family, name, Wh per 1k tokens)
+    pub energy_overrides: Vec<(String, String, f64)>,
+    pub currency: Option<String>,
+    pub currency_rate: Option<f64>,
+    pub currency_fetch: Option<bool>,
+    pub pricing_file: Option<String>,
+    pub pricing_fetch: Option<bool>,
+    pub free_models: Option<Vec<String>>,
+    pub proxy_surcharge_percent: Option<f64>,
+    pub entries_retention_days: Option<u32>,
+    pub sync_dir: Option<String>,
+    pub sync_machine: Option<String>,
+    pub api_cache_retention_days: Option<u32>,
+    pub global_sum_ttl_seconds: Option<u32>,
+    pub scan_ttl_seconds: Option<u32>,
+    pub usage_api_ttl_seconds: Option<u32>,
+    pub usage_api_proxy: Option<String>,
+    pub account: Option<String>,
+    pub admin_api_key: Option<String>,
+    pub openrouter: Option<bool>,
+    pub openrouter_key: Option<String>,
+    pub absent_ttl_seconds: Option<u32>,
+    pub proxy_surcharge_per_mtok: Option<f64>,
+    pub pricing_stale_days: Option<u32>,
+    pub git_head_age_warn_hours: Option<u32>,
+    pub git_timeout_ms: Option<u32>,
+    pub git_health_max_git_gb: Option<u32>,
+    pub git_fields: Option<Vec<GitFieldArg>>,
+    pub gastown_fields: Option<Vec<GastownFieldArg>>,
+    pub beads_stale_hours: Option<u32>,
+    pub beads_labels: Option<Vec<String>>,
+    pub gastown_max_width: Option<u32>,
+    pub git_issue_patterns: Option<Vec<String>>,
+    pub git_issue_url: Option<String>,
+    pub git_protected_branches: Option<Vec<String>>,
+    /// `[tags]` entries in file order: (project directory, tag)
+    pub tags: Vec<(String, String)>,
+    /// `[project_pricing]` entries in file order: (project directory, override)
+    pub project_pricing: Vec<(String, ProjectRates)>,
+    /// `[accounts]` entries in file order: (label, Claude config dir)
+    pub accounts: Vec<(String, PathBuf)>,
+    pub subscription: Option<SubscriptionArg>,
+    pub subscription_price: Option<f64>,
     pub burn_scope: Option<BurnScopeArg>,
     pub window_scope: Option<WindowScopeArg>,
     pub window_anchor: Option<WindowAnchorArg>,
@@ -42,6 +95,7 @@ pub struct JsonFileConfig {
     pub duration: Option<bool>,
     pub rate_limit: Option<bool>,
     pub usage_limits: Option<bool>,
+    pub blocks: Option<bool>,
 }
 
 /// Display.* atomic toggles. All values use positive semantics:
@@ -56,7 +110,10 @@ pub struct DisplayFileConfig {
     pub cost_window: Option<bool>,
     pub cost_breakdown: Option<bool>,
     pub cost_provenance: Option<bool>,
+    pub cost_mtd: Option<bool>,
+    pub cost_forecast: Option<bool>,
     pub cost_lines_delta: Option<bool>,
+    pub cost_today_trend: Option<bool>,
     // usage.*
     pub usage_five_hour: Option<bool>,
     pub usage_weekly: Option<bool>,
@@ -71,7 +128,17 @@ pub struct DisplayFileConfig {
     pub git_branch: Option<bool>,
     pub git_dirty: Option<bool>,
     pub git_ahead_behind: Option<bool>,
+    pub git_upstream: Option<bool>,
+    pub git_describe: Option<bool>,
+    pub git_head_age: Option<bool>,
+    pub git_pr: Option<bool>,
+    pub git_ci: Option<bool>,
+    pub git_issues: Option<bool>,
+    pub git_health: Option<bool>,
+    pub git_untracked: Option<bool>,
     pub git_worktree: Option<bool>,
+    pub git_subproject: Option<bool>,
+    pub git_protected_warning: Option<bool>,
     // workspace.*
     pub workspace_cwd: Option<bool>,
     pub workspace_added_dirs: Option<bool>,
@@ -84,6 +151,7 @@ pub struct DisplayFileConfig {
     pub integrations_beads: Option<bool>,
     pub integrations_beads_alerts: Option<bool>,
     pub integrations_gastown: Option<bool>,
+    pub integrations_gastown_emoji: Option<bool>,
     pub integrations_prompt_cache: Option<bool>,
     // provider.*
     pub provider_key_source: Option<bool>,
@@ -140,6 +208,11 @@ where
     if let Some(config) = loaded_config {
         apply_config(&mut args, &matches, &config);
     }
+    // Without the `db` feature there is no cache to use, whatever the toggles
+    // say; this also keeps rate and price-table fetches off every render.
+    if !cfg!(feature = "db") {
+        args.no_subsystem_db_cache = true;
+    }
 
     args
 }
@@ -202,6 +275,11 @@ fn apply_config(args: &mut Args, matches: &clap::ArgMatches, config: &FileConfig
             args.time_fmt = value;
         }
     }
+    if !arg_was_user_set(matches, "timezone") {
+        if let Some(value) = &config.timezone {
+            args.timezone = Some(value.clone());
+        }
+    }
     if !arg_was_user_set(matches, "truecolor") && std::env::var("CLAUDE_TRUECOLOR").is_err() {
         if let Some(value) = config.truecolor {
             args.truecolor = value;
@@ -212,6 +290,238 @@ fn apply_config(args: &mut Args, matches: &clap::ArgMatches, config: &FileConfig
             args.prompt_cache_ttl_seconds = Some(value);
         }
     }
+    if !arg_was_user_set(matches, "session_cost_cap") {
+        if let Some(value) = config.session_cost_cap {
+            args.session_cost_cap = Some(value);
+        }
+    }
+    if !arg_was_user_set(matches, "budget_daily") {
+        if let Some(value) = config.budget_daily {
+            args.budget_daily = Some(value);
+        }
+    }
+    if !arg_was_user_set(matches, "budget_weekly") {
+        if let Some(value) = config.budget_weekly {
+            args.budget_weekly = Some(value);
+        }
+    }
+    if !arg_was_user_set(matches, "budget_monthly") {
+        if let Some(value) = config.budget_monthly {
+            args.budget_monthly = Some(value);
+        }
+    }
+    if !arg_was_user_set(matches, "billing_day") {
+        if let Some(value) = config.billing_day {
+            args.billing_day = value;
+        }
+    }
+    apply_display_opt_in(
+        matches,
+        "environment",
+        config.environment,
+        &mut args.environment,
+    );
+    if !arg_was_user_set(matches, "carbon_intensity") {
+        if let Some(value) = config.carbon_intensity {
+            args.carbon_intensity = Some(value);
+        }
+    }
+    for (family, name, wh_per_1k) in &config.energy_overrides {
+        if let Some(coefficients) = args.energy_model.family_mut(family)
+            && coefficients.set(name, *wh_per_1k)
+        {
+            args.energy_model.customized = true;
+        }
+    }
+    if !arg_was_user_set(matches, "currency") {
+        if let Some(ref value) = config.currency {
+            args.currency = Some(value.clone());
+        }
+    }
+    if !arg_was_user_set(matches, "currency_rate") {
+        if let Some(value) = config.currency_rate {
+            args.currency_rate = Some(value);
+        }
+    }
+    if !arg_was_user_set(matches, "pricing_file") {
+        if let Some(ref value) = config.pricing_file {
+            args.pricing_file = Some(PathBuf::from(crate::utils::expand_home(value)));
+        }
+    }
+    if !arg_was_user_set(matches, "pricing_stale_days") {
+        if let Some(value) = config.pricing_stale_days {
+            args.pricing_stale_days = value;
+        }
+    }
+    if !arg_was_user_set(matches, "git_fields") {
+        if let Some(ref value) = config.git_fields {
+            args.git_fields = value.clone();
+        }
+    }
+    if !arg_was_user_set(matches, "beads_stale_hours") {
+        if let Some(value) = config.beads_stale_hours {
+            args.beads_stale_hours = value;
+        }
+    }
+    if !arg_was_user_set(matches, "beads_label") {
+        if let Some(ref value) = config.beads_labels {
+            args.beads_label = value.clone();
+        }
+    }
+    if !arg_was_user_set(matches, "gastown_fields") {
+        if let Some(ref value) = config.gastown_fields {
+            args.gastown_fields = value.clone();
+        }
+    }
+    if !arg_was_user_set(matches, "gastown_max_width") {
+        if let Some(value) = config.gastown_max_width {
+            args.gastown_max_width = value;
+        }
+    }
+    if !arg_was_user_set(matches, "git_issue_pattern") {
+        if let Some(ref value) = config.git_issue_patterns {
+            args.git_issue_pattern = value.clone();
+        }
+    }
+    if !arg_was_user_set(matches, "git_protected_branch") {
+        if let Some(ref value) = config.git_protected_branches {
+            args.git_protected_branch = value.clone();
+        }
+    }
+    if !arg_was_user_set(matches, "git_issue_url") {
+        if let Some(ref value) = config.git_issue_url {
+            args.git_issue_url = Some(value.clone());
+        }
+    }
+    if !arg_was_user_set(matches, "git_head_age_warn_hours") {
+        if let Some(value) = config.git_head_age_warn_hours {
+            args.git_head_age_warn_hours = value;
+        }
+    }
+    if !arg_was_user_set(matches, "git_health_max_git_gb") {
+        if let Some(value) = config.git_health_max_git_gb {
+            args.git_health_max_git_gb = value;
+        }
+    }
+    if !arg_was_user_set(matches, "git_timeout_ms") {
+        if let Some(value) = config.git_timeout_ms {
+            args.git_timeout_ms = value;
+        }
+    }
+    if !arg_was_user_set(matches, "free_model") {
+        if let Some(ref value) = config.free_models {
+            args.free_model = value.clone();
+        }
+    }
+    if !arg_was_user_set(matches, "sync_dir") {
+        if let Some(ref value) = config.sync_dir {
+            args.sync_dir = Some(PathBuf::from(crate::utils::expand_home(value)));
+        }
+    }
+    if !arg_was_user_set(matches, "sync_machine") {
+        if let Some(ref value) = config.sync_machine {
+            args.sync_machine = Some(value.clone());
+        }
+    }
+    if !arg_was_user_set(matches, "entries_retention_days") {
+        if let Some(value) = config.entries_retention_days {
+            args.entries_retention_days = value;
+        }
+    }
+    if !arg_was_user_set(matches, "api_cache_retention_days") {
+        if let Some(value) = config.api_cache_retention_days {
+            args.api_cache_retention_days = value;
+        }
+    }
+    if !arg_was_user_set(matches, "global_sum_ttl_seconds") {
+        if let Some(value) = config.global_sum_ttl_seconds {
+            args.global_sum_ttl_seconds = value;
+        }
+    }
+    if !arg_was_user_set(matches, "scan_ttl_seconds") {
+        if let Some(value) = config.scan_ttl_seconds {
+            args.scan_ttl_seconds = value;
+        }
+    }
+    if !arg_was_user_set(matches, "usage_api_ttl_seconds") {
+        if let Some(value) = config.usage_api_ttl_seconds {
+            args.usage_api_ttl_seconds = value;
+        }
+    }
+    if !arg_was_user_set(matches, "account") {
+        if let Some(ref value) = config.account {
+            args.account = Some(value.clone());
+        }
+    }
+    if !arg_was_user_set(matches, "openrouter") {
+        if let Some(value) = config.openrouter {
+            args.openrouter = value;
+        }
+    }
+    if !arg_was_user_set(matches, "openrouter_key") {
+        if let Some(ref value) = config.openrouter_key {
+            args.openrouter_key = Some(value.clone());
+        }
+    }
+    if !arg_was_user_set(matches, "admin_api_key") {
+        if let Some(ref value) = config.admin_api_key {
+            args.admin_api_key = Some(value.clone());
+        }
+    }
+    if !arg_was_user_set(matches, "usage_api_proxy") {
+        if let Some(ref value) = config.usage_api_proxy {
+            args.usage_api_proxy = Some(value.clone());
+        }
+    }
+    if !arg_was_user_set(matches, "absent_ttl_seconds") {
+        if let Some(value) = config.absent_ttl_seconds {
+            args.absent_ttl_seconds = value;
+        }
+    }
+    if !arg_was_user_set(matches, "proxy_surcharge_percent") {
+        if let Some(value) = config.proxy_surcharge_percent {
+            args.proxy_surcharge_percent = Some(value);
+        }
+    }
+    if !arg_was_user_set(matches, "proxy_surcharge_per_mtok") {
+        if let Some(value) = config.proxy_surcharge_per_mtok {
+            args.proxy_surcharge_per_mtok = Some(value);
+        }
+    }
+    if !arg_was_user_set(matches, "pricing_fetch") {
+        if let Some(value) = config.pricing_fetch {
+            args.pricing_fetch = value;
+        }
+    }
+    if !arg_was_user_set(matches, "currency_fetch") {
+        if let Some(value) = config.currency_fetch {
+            args.currency_fetch = value;
+        }
+    }
+    if !arg_was_user_set(matches, "chargeback_multiplier") {
+        if let Some(value) = config.chargeback_multiplier {
+            args.chargeback_multiplier = Some(value);
+        }
+    }
+    if !config.project_pricing.is_empty() {
+        args.project_pricing = config.project_pricing.clone();
+    }
+    if !config.tags.is_empty() {
+        args.tags = config.tags.clone();
+    }
+    if !config.accounts.is_empty() {
+        args.accounts = config.accounts.clone();
+    }
+    if !arg_was_user_set(matches, "subscription") {
+        if let Some(value) = config.subscription {
+            args.subscription = Some(value);
+        }
+    }
+    if !arg_was_user_set(matches, "subscription_price") {
+        if let Some(value) = config.subscription_price {
+            args.subscription_price = Some(value);
+        }
+    }
 
     // display.* atomic toggles. TOML positive (true = visible),
     // Args negative (no_<section>_<element>: true = hidden).
@@ -239,6 +549,12 @@ fn apply_config(args: &mut Args, matches: &clap::ArgMatches, config: &FileConfig
         config.display.cost_lines_delta,
         &mut args.no_cost_lines_delta,
     );
+    apply_display_toggle(
+        matches,
+        "no_cost_today_trend",
+        config.display.cost_today_trend,
+        &mut args.no_cost_today_trend,
+    );
     apply_display_opt_in(
         matches,
         "cost_breakdown",
@@ -251,6 +567,18 @@ fn apply_config(args: &mut Args, matches: &clap::ArgMatches, config: &FileConfig
         config.display.cost_provenance,
         &mut args.cost_provenance,
     );
+    apply_display_opt_in(
+        matches,
+        "cost_mtd",
+        config.display.cost_mtd,
+        &mut args.cost_mtd,
+    );
+    apply_display_opt_in(
+        matches,
+        "cost_forecast",
+        config.display.cost_forecast,
+        &mut args.cost_forecast,
+    );
 
     apply_display_toggle(
         matches,
@@ -320,12 +648,62 @@ fn apply_config(args: &mut Args, matches: &clap::ArgMatches, config: &FileConfig
         config.display.git_ahead_behind,
         &mut args.no_git_ahead_behind,
     );
+    apply_display_opt_in(
+        matches,
+        "git_upstream",
+        config.display.git_upstream,
+        &mut args.git_upstream,
+    );
+    apply_display_opt_in(
+        matches,
+        "git_describe",
+        config.display.git_describe,
+        &mut args.git_describe,
+    );
+    apply_display_opt_in(
+        matches,
+        "git_head_age",
+        config.display.git_head_age,
+        &mut args.git_head_age,
+    );
+    apply_display_opt_in(matches, "git_pr", config.display.git_pr, &mut args.git_pr);
+    apply_display_opt_in(matches, "git_ci", config.display.git_ci, &mut args.git_ci);
+    apply_display_opt_in(
+        matches,
+        "git_issues",
+        config.display.git_issues,
+        &mut args.git_issues,
+    );
+    apply_display_opt_in(
+        matches,
+        "git_health",
+        config.display.git_health,
+        &mut args.git_health,
+    );
+    apply_display_toggle(
+        matches,
+        "no_git_untracked",
+        config.display.git_untracked,
+        &mut args.no_git_untracked,
+    );
     apply_display_toggle(
         matches,
         "no_git_worktree",
         config.display.git_worktree,
         &mut args.no_git_worktree,
     );
+    apply_display_toggle(
+        matches,
+        "no_git_subproject",
+        config.display.git_subproject,
+        &mut args.no_git_subproject,
+    );
+    apply_display_toggle(
+        matches,
+        "no_git_protected_warning",
+        config.display.git_protected_warning,
+        &mut args.no_git_protected_warning,
+    );
 
     apply_display_toggle(
         matches,
@@ -388,6 +766,12 @@ fn apply_config(args: &mut Args, matches: &clap::ArgMatches, config: &FileConfig
         config.display.integrations_gastown,
         &mut args.no_integrations_gastown,
     );
+    apply_display_toggle(
+        matches,
+        "no_gastown_emoji",
+        config.display.integrations_gastown_emoji,
+        &mut args.no_gastown_emoji,
+    );
     apply_display_toggle(
         matches,
         "no_integrations_prompt_cache",
@@ -439,6 +823,12 @@ fn apply_config(args: &mut Args, matches: &clap::ArgMatches, config: &FileConfig
         config.json_settings.usage_limits,
         &mut args.no_json_usage_limits,
     );
+    apply_display_toggle(
+        matches,
+        "no_json_blocks",
+        config.json_settings.blocks,
+        &mut args.no_json_blocks,
+    );
     if !arg_was_user_set(matches, "burn_scope") {
         if let Some(value) = config.burn_scope {
             args.burn_scope = value;
@@ -539,7 +929,19 @@ fn apply_preset_minimal(args: &mut Args, matches: &clap::ArgMatches) {
         &mut args.no_git_ahead_behind,
         true,
     );
+    set_if_unset_neg(
+        matches,
+        "no_git_untracked",
+        &mut args.no_git_untracked,
+        true,
+    );
     set_if_unset_neg(matches, "no_git_worktree", &mut args.no_git_worktree, true);
+    set_if_unset_neg(
+        matches,
+        "no_git_subproject",
+        &mut args.no_git_subproject,
+        true,
+    );
     // Workspace: keep cwd + model + fast_mode_indicator, hide rest
     set_if_unset_neg(
         matches,
@@ -614,6 +1016,9 @@ fn apply_preset_minimal(args: &mut Args, matches: &clap::ArgMatches) {
 fn apply_preset_full(args: &mut Args, matches: &clap::ArgMatches) {
     set_if_unset_pos(matches, "cost_breakdown", &mut args.cost_breakdown, true);
     set_if_unset_pos(matches, "cost_provenance", &mut args.cost_provenance, true);
+    set_if_unset_pos(matches, "cost_mtd", &mut args.cost_mtd, true);
+    set_if_unset_pos(matches, "cost_forecast", &mut args.cost_forecast, true);
+    set_if_unset_pos(matches, "environment", &mut args.environment, true);
     set_if_unset_pos(
         matches,
         "provider_key_source",
@@ -621,6 +1026,9 @@ fn apply_preset_full(args: &mut Args, matches: &clap::ArgMatches) {
         true,
     );
     set_if_unset_pos(matches, "provider_name", &mut args.provider_name, true);
+    set_if_unset_pos(matches, "git_upstream", &mut args.git_upstream, true);
+    set_if_unset_pos(matches, "git_describe", &mut args.git_describe, true);
+    set_if_unset_pos(matches, "git_head_age", &mut args.git_head_age, true);
 }
 
 /// For default-on toggles (`no_<section>_<element>`): TOML true keeps it visible (args.no_* = false).
@@ -677,6 +1085,43 @@ fn parse_config_str(input: &str) -> Result<FileConfig> {
         let (raw_key, raw_value) = line
             .split_once('=')
             .ok_or_else(|| anyhow!("line {}: expected key = value", line_no + 1))?;
+        // [tags] keys are paths; keep their case and punctuation.
+        if section == "tags" {
+            config
+                .tags
+                .push((parse_string(raw_key)?, parse_string(raw_value)?));
+            continue;
+        }
+        // [accounts] keys are labels; values are Claude config dirs.
+        if section == "accounts" {
+            let dir = crate::utils::expand_home(&parse_string(raw_value)?);
+            config
+                .accounts
+                .push((parse_string(raw_key)?, PathBuf::from(dir)));
+            continue;
+        }
+        // [project_pricing] values are a cost multiplier or a quoted pricing file path.
+        if section == "project_pricing" {
+            let value = raw_value.trim();
+            let rates = if value.starts_with(['"', '\'']) {
+                let path = value[1..].strip_suffix(&value[..1]).ok_or_else(|| {
+                    anyhow!("line {}: unterminated project_pricing path", line_no + 1)
+                })?;
+                ProjectRates::File(PathBuf::from(crate::utils::expand_home(path)))
+            } else {
+                match parse_f64(value) {
+                    Ok(multiplier) if multiplier > 0.0 => ProjectRates::Multiplier(multiplier),
+                    _ => {
+                        return Err(anyhow!(
+                            "line {}: project_pricing value must be a positive multiplier or a quoted path",
+                            line_no + 1
+                        ));
+                    }
+                }
+            };
+            config.project_pricing.push((parse_string(raw_key)?, rates));
+            continue;
+        }
         let key = normalize_key(&section, raw_key.trim());
         let value = raw_value.trim();
 
@@ -687,8 +1132,66 @@ fn parse_config_str(input: &str) -> Result<FileConfig> {
             "git" => config.git = Some(parse_git(value)?),
             "git.verbosity" => config.git = Some(parse_git(value)?),
             "time" | "time_fmt" => config.time_fmt = Some(parse_time(value)?),
+            "timezone" => config.timezone = Some(parse_string(value)?),
             "truecolor" => config.truecolor = Some(parse_bool(value)?),
             "prompt_cache_ttl_seconds" => config.prompt_cache_ttl_seconds = Some(parse_u64(value)?),
+            "session_cost_cap" => config.session_cost_cap = Some(parse_f64(value)?),
+            "budget.daily" => config.budget_daily = Some(parse_f64(value)?),
+            "budget.weekly" => config.budget_weekly = Some(parse_f64(value)?),
+            "budget.monthly" => config.budget_monthly = Some(parse_f64(value)?),
+            "billing_day" | "billing.start_day" => {
+                config.billing_day = Some(parse_billing_day(value)?)
+            }
+            "currency" | "currency.code" => config.currency = Some(parse_string(value)?),
+            "currency_rate" | "currency.rate" => config.currency_rate = Some(parse_f64(value)?),
+            "currency_fetch" | "currency.fetch" => config.currency_fetch = Some(parse_bool(value)?),
+            "pricing_file" | "pricing.file" => config.pricing_file = Some(parse_string(value)?),
+            "pricing_fetch" | "pricing.fetch" => config.pricing_fetch = Some(parse_bool(value)?),
+            "pricing_stale_days" | "pricing.stale_days" => {
+                config.pricing_stale_days = Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
+            "free_models" | "pricing.free_models" => {
+                config.free_models = Some(parse_string_list(value)?)
+            }
+            "pricing.surcharge_percent" | "proxy.surcharge_percent" => {
+                config.proxy_surcharge_percent = Some(parse_f64(value)?)
+            }
+            "pricing.surcharge_per_mtok" | "proxy.surcharge_per_mtok" => {
+                config.proxy_surcharge_per_mtok = Some(parse_f64(value)?)
+            }
+            "usage_api.proxy" => config.usage_api_proxy = Some(parse_string(value)?),
+            "usage_api.account" => config.account = Some(parse_string(value)?),
+            "admin_api.key" => config.admin_api_key = Some(parse_string(value)?),
+            "openrouter.enabled" => config.openrouter = Some(parse_bool(value)?),
+            "openrouter.key" => config.openrouter_key = Some(parse_string(value)?),
+            "sync.dir" => config.sync_dir = Some(parse_string(value)?),
+            "sync.machine" => config.sync_machine = Some(parse_string(value)?),
+            "db.entries_retention_days" => {
+                config.entries_retention_days = Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
+            "db.api_cache_retention_days" => {
+                config.api_cache_retention_days =
+                    Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
+            "cache.global_sum_ttl_seconds" => {
+                config.global_sum_ttl_seconds = Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
+            "cache.scan_ttl_seconds" => {
+                config.scan_ttl_seconds = Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
+            "cache.usage_api_ttl_seconds" => {
+                config.usage_api_ttl_seconds = Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
+            "cache.absent_ttl_seconds" => {
+                config.absent_ttl_seconds = Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
+            "chargeback.multiplier" => config.chargeback_multiplier = Some(parse_f64(value)?),
+            "subscription" | "subscription.plan" => {
+                config.subscription = Some(parse_subscription(value)?)
+            }
+            "subscription_price" | "subscription.price" => {
+                config.subscription_price = Some(parse_f64(value)?)
+            }
             "burn_scope" => config.burn_scope = Some(parse_burn_scope(value)?),
             "window_scope" => config.window_scope = Some(parse_window_scope(value)?),
             "window_anchor" => config.window_anchor = Some(parse_window_anchor(value)?),
@@ -703,7 +1206,10 @@ fn parse_config_str(input: &str) -> Result<FileConfig> {
             "cost.window" => config.display.cost_window = Some(parse_bool(value)?),
             "cost.breakdown" => config.display.cost_breakdown = Some(parse_bool(value)?),
             "cost.provenance" => config.display.cost_provenance = Some(parse_bool(value)?),
+            "cost.mtd" => config.display.cost_mtd = Some(parse_bool(value)?),
+            "cost.forecast" => config.display.cost_forecast = Some(parse_bool(value)?),
             "cost.lines_delta" => config.display.cost_lines_delta = Some(parse_bool(value)?),
+            "cost.today_trend" => config.display.cost_today_trend = Some(parse_bool(value)?),
             // display.usage.*
             "usage.five_hour" => config.display.usage_five_hour = Some(parse_bool(value)?),
             "usage.weekly" => config.display.usage_weekly = Some(parse_bool(value)?),
@@ -720,7 +1226,34 @@ fn parse_config_str(input: &str) -> Result<FileConfig> {
             "git.branch" => config.display.git_branch = Some(parse_bool(value)?),
             "git.dirty" => config.display.git_dirty = Some(parse_bool(value)?),
             "git.ahead_behind" => config.display.git_ahead_behind = Some(parse_bool(value)?),
+            "git.upstream" => config.display.git_upstream = Some(parse_bool(value)?),
+            "git.describe" => config.display.git_describe = Some(parse_bool(value)?),
+            "git.head_age" => config.display.git_head_age = Some(parse_bool(value)?),
+            "git.pr" => config.display.git_pr = Some(parse_bool(value)?),
+            "git.ci" => config.display.git_ci = Some(parse_bool(value)?),
+            "git.issues" => config.display.git_issues = Some(parse_bool(value)?),
+            "git.health" => config.display.git_health = Some(parse_bool(value)?),
+            "git.health_max_git_gb" => {
+                config.git_health_max_git_gb = Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
+            "git.issue_patterns" => config.git_issue_patterns = Some(parse_pattern_list(value)?),
+            "git.issue_url" => config.git_issue_url = Some(parse_string(value)?),
+            "git.fields" => config.git_fields = Some(parse_git_fields(value)?),
+            "git.head_age_warn_hours" => {
+                config.git_head_age_warn_hours = Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
+            "git.timeout_ms" => {
+                config.git_timeout_ms = Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
+            "git.untracked" => config.display.git_untracked = Some(parse_bool(value)?),
             "git.worktree" => config.display.git_worktree = Some(parse_bool(value)?),
+            "git.subproject" => config.display.git_subproject = Some(parse_bool(value)?),
+            "git.protected_warning" => {
+                config.display.git_protected_warning = Some(parse_bool(value)?)
+            }
+            "git.protected_branches" => {
+                config.git_protected_branches = Some(parse_string_list(value)?)
+            }
             // display.workspace.*
             "workspace.cwd" => config.display.workspace_cwd = Some(parse_bool(value)?),
             "workspace.added_dirs" => {
@@ -740,9 +1273,22 @@ fn parse_config_str(input: &str) -> Result<FileConfig> {
             "integrations.beads_alerts" => {
                 config.display.integrations_beads_alerts = Some(parse_bool(value)?)
             }
+            "integrations.beads_stale_hours" => {
+                config.beads_stale_hours = Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
+            "integrations.beads_labels" => config.beads_labels = Some(parse_string_list(value)?),
             "integrations.gastown" => {
                 config.display.integrations_gastown = Some(parse_bool(value)?)
             }
+            "integrations.gastown_emoji" => {
+                config.display.integrations_gastown_emoji = Some(parse_bool(value)?)
+            }
+            "integrations.gastown_fields" => {
+                config.gastown_fields = Some(parse_gastown_fields(value)?)
+            }
+            "integrations.gastown_max_width" => {
+                config.gastown_max_width = Some(parse_u64(value)?.min(u32::MAX as u64) as u32)
+            }
             "integrations.prompt_cache" => {
                 config.display.integrations_prompt_cache = Some(parse_bool(value)?)
             }
@@ -757,7 +1303,26 @@ fn parse_config_str(input: &str) -> Result<FileConfig> {
             "json.duration" => config.json_settings.duration = Some(parse_bool(value)?),
             "json.rate_limit" => config.json_settings.rate_limit = Some(parse_bool(value)?),
             "json.usage_limits" => config.json_settings.usage_limits = Some(parse_bool(value)?),
-            _ => {}
+            "json.blocks" => config.json_settings.blocks = Some(parse_bool(value)?),
+            "environment" | "environment.enabled" => config.environment = Some(parse_bool(value)?),
+            "environment.grid_intensity" => config.carbon_intensity = Some(parse_f64(value)?),
+            other => {
+                // [environment.<family>] input/output/cache_write/cache_read
+                if let Some((family, name)) = other
+                    .strip_prefix("environment.")
+                    .and_then(|rest| rest.split_once('.'))
+                    && crate::environment::FAMILIES.contains(&family)
+                {
+                    if !crate::environment::COEFFICIENTS.contains(&name) {
+                        return Err(anyhow!("invalid environment coefficient: {other}"));
+                    }
+                    config.energy_overrides.push((
+                        family.to_string(),
+                        name.to_string(),
+                        parse_f64(value)?,
+                    ));
+                }
+            }
         }
     }
 
@@ -782,10 +1347,21 @@ fn normalize_key(section: &str, key: &str) -> String {
 
 fn strip_comment(line: &str) -> String {
     let mut in_string = false;
+    let mut in_literal = false;
     let mut escaped = false;
     let mut out = String::new();
 
     for ch in line.chars() {
+        // TOML literal strings ('...') have no escapes
+        if ch == '\'' && !in_string {
+            in_literal = !in_literal;
+            out.push(ch);
+            continue;
+        }
+        if in_literal {
+            out.push(ch);
+            continue;
+        }
         if escaped {
             out.push(ch);
             escaped = false;
@@ -819,6 +1395,61 @@ fn parse_string(value: &str) -> Result<String> {
     }
 }
 
+/// `["a", "b"]` (single line) or a bare comma-separated string.
+fn parse_string_list(value: &str) -> Result<Vec<String>> {
+    let trimmed = value.trim();
+    let inner = match trimmed.strip_prefix('[') {
+        Some(rest) => rest
+            .strip_suffix(']')
+            .ok_or_else(|| anyhow!("unterminated array: {trimmed}"))?,
+        None => &parse_string(trimmed)?,
+    };
+    inner
+        .split(',')
+        .map(parse_string)
+        .filter(|item| !matches!(item, Ok(s) if s.trim().is_empty()))
+        .map(|item| item.map(|s| s.trim().to_string()))
+        .collect()
+}
+
+/// Single-line array of regexes. Items may contain commas, double-quoted
+/// items unescape `\\` and `\"`, and single-quoted items are literal.
+fn parse_pattern_list(value: &str) -> Result<Vec<String>> {
+    let trimmed = value.trim();
+    let inner = trimmed
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or_else(|| anyhow!("expected an array of patterns: {trimmed}"))?;
+    let mut patterns = Vec::new();
+    let mut chars = inner.chars();
+    while let Some(ch) = chars.next() {
+        let quote = match ch {
+            '"' | '\'' => ch,
+            ',' | ' ' | '\t' => continue,
+            other => return Err(anyhow!("expected a quoted pattern, found {other:?}")),
+        };
+        let mut pattern = String::new();
+        loop {
+            match chars.next() {
+                Some(c) if c == quote => break,
+                Some('\\') if quote == '"' => match chars.next() {
+                    Some(c @ ('\\' | '"')) => pattern.push(c),
+                    Some(c) => {
+                        pattern.push('\\');
+                        pattern.push(c);
+                    }
+                    None => return Err(anyhow!("unterminated pattern: {trimmed}")),
+                },
+                Some(c) => pattern.push(c),
+                None => return Err(anyhow!("unterminated pattern: {trimmed}")),
+            }
+        }
+        regex::Regex::new(&pattern).with_context(|| format!("invalid pattern: {pattern}"))?;
+        patterns.push(pattern);
+    }
+    Ok(patterns)
+}
+
 fn parse_bool(value: &str) -> Result<bool> {
     match parse_string(value)?.trim().to_ascii_lowercase().as_str() {
         "true" | "1" | "yes" | "on" => Ok(true),
@@ -834,6 +1465,18 @@ fn parse_u64(value: &str) -> Result<u64> {
         .context("invalid unsigned integer")
 }
 
+fn parse_f64(value: &str) -> Result<f64> {
+    let parsed = parse_string(value)?
+        .trim()
+        .parse::<f64>()
+        .context("invalid number")?;
+    if parsed.is_finite() && parsed >= 0.0 {
+        Ok(parsed)
+    } else {
+        Err(anyhow!("invalid non-negative number: {parsed}"))
+    }
+}
+
 fn parse_labels(value: &str) -> Result<LabelsArg> {
     match parse_string(value)?.trim().to_ascii_lowercase().as_str() {
         "short" => Ok(LabelsArg::Short),
@@ -850,6 +1493,26 @@ fn parse_git(value: &str) -> Result<GitArg> {
     }
 }
 
+fn parse_git_fields(value: &str) -> Result<Vec<GitFieldArg>> {
+    parse_string_list(value)?
+        .iter()
+        .map(|field| {
+            <GitFieldArg as clap::ValueEnum>::from_str(field.trim(), true)
+                .map_err(|_| anyhow!("invalid git field: {field}"))
+        })
+        .collect()
+}
+
+fn parse_gastown_fields(value: &str) -> Result<Vec<GastownFieldArg>> {
+    parse_string_list(value)?
+        .iter()
+        .map(|field| {
+            <GastownFieldArg as clap::ValueEnum>::from_str(field.trim(), true)
+                .map_err(|_| anyhow!("invalid gastown field: {field}"))
+        })
+        .collect()
+}
+
 fn parse_time(value: &str) -> Result<TimeFormatArg> {
     match parse_string(value)?.trim().to_ascii_lowercase().as_str() {
         "auto" => Ok(TimeFormatArg::Auto),
@@ -862,6 +1525,7 @@ fn parse_time(value: &str) -> Result<TimeFormatArg> {
 fn parse_burn_scope(value: &str) -> Result<BurnScopeArg> {
     match parse_string(value)?.trim().to_ascii_lowercase().as_str() {
         "session" => Ok(BurnScopeArg::Session),
+        "project" => Ok(BurnScopeArg::Project),
         "global" => Ok(BurnScopeArg::Global),
         other => Err(anyhow!("invalid burn_scope value: {other}")),
     }
@@ -871,6 +1535,7 @@ fn parse_window_scope(value: &str) -> Result<WindowScopeArg> {
     match parse_string(value)?.trim().to_ascii_lowercase().as_str() {
         "global" => Ok(WindowScopeArg::Global),
         "project" => Ok(WindowScopeArg::Project),
+        "session" => Ok(WindowScopeArg::Session),
         other => Err(anyhow!("invalid window_scope value: {other}")),
     }
 }
@@ -884,6 +1549,24 @@ fn parse_preset(value: &str) -> Result<PresetArg> {
     }
 }
 
+fn parse_billing_day(value: &str) -> Result<u32> {
+    match parse_u64(value)? {
+        day @ 1..=31 => Ok(day as u32),
+        other => Err(anyhow!(
+            "invalid billing_day value: {other} (expected 1-31)"
+        )),
+    }
+}
+
+fn parse_subscription(value: &str) -> Result<SubscriptionArg> {
+    match parse_string(value)?.trim().to_ascii_lowercase().as_str() {
+        "pro" => Ok(SubscriptionArg::Pro),
+        "max5x" | "max_5x" => Ok(SubscriptionArg::Max5x),
+        "max20x" | "max_20x" => Ok(SubscriptionArg::Max20x),
+        other => Err(anyhow!("invalid subscription value: {other}")),
+    }
+}
+
 fn parse_window_anchor(value: &str) -> Result<WindowAnchorArg> {
     match parse_string(value)?.trim().to_ascii_lowercase().as_str() {
         "provider" => Ok(WindowAnchorArg::Provider),
@@ -904,22 +1587,197 @@ mod tests {
             labels = "long"
             git = "verbose"
             prompt_cache_ttl_seconds = 3600
+            session_cost_cap = 15
+            timezone = "Europe/Berlin"
 
             [display.cost]
             provenance = true
             today = false
+            mtd = true
+
+            [display.git]
+            fields = ["branch", "dirty", "ahead_behind", "stash"]
+            timeout_ms = 150
+            health_max_git_gb = 5
+            issue_patterns = ['#[0-9]+', "[A-Z]{2,5}-\\d+"]
 
             [display.integrations]
             prompt_cache = false
+            gastown_fields = ["role", "mail"]
+            gastown_max_width = 40
+            beads_stale_hours = 8
+            beads_labels = ["agent:*"]
+
+            [budget]
+            daily = 25.0
+            monthly = 300
+
+            [subscription]
+            plan = "max5x"
+
+            [billing]
+            start_day = 14
+
+            [chargeback]
+            multiplier = 1.25
+
+            [currency]
+            code = "EUR"
+            rate = 0.92
+
+            [pricing]
+            file = "~/prices.json"
+            fetch = true
+            free_models = ["llama-*", "qwen2.5-coder:*"]
+            surcharge_percent = 5
+            surcharge_per_mtok = 0.25
+
+            [db]
+            entries_retention_days = 0
+            api_cache_retention_days = 14
+
+            [cache]
+            scan_ttl_seconds = 0
+            usage_api_ttl_seconds = 900
+            absent_ttl_seconds = 120
+
+            [usage_api]
+            proxy = "socks5://127.0.0.1:1080"
+            account = "work"
+
+            [admin_api]
+            key = "sk-ant-admin01-test"
+
+            [openrouter]
+            enabled = true
+
+            [accounts]
+            work = "/home/u/.claude-work"
+
+            [sync]
+            dir = "~/usage-sync"
+            machine = "laptop"
+
+            [environment]
+            enabled = true
+            grid_intensity = 250
+
+            [environment.opus]
+            output = 1.5
+
+            [tags]
+            "~/work/Acme_API" = "acme"
+
+            [project_pricing]
+            "~/work/acme*" = 1.15
+            "/srv/client" = "/srv/client/pricing.json"
+            "/srv/other" = '/srv/other/pricing.json'
             "#,
         )
         .expect("config should parse");
 
         assert_eq!(config.labels, Some(LabelsArg::Long));
         assert_eq!(config.git, Some(GitArg::Verbose));
+        assert_eq!(
+            config.git_fields,
+            Some(vec![
+                GitFieldArg::Branch,
+                GitFieldArg::Dirty,
+                GitFieldArg::AheadBehind,
+                GitFieldArg::Stash
+            ])
+        );
+        assert_eq!(config.git_timeout_ms, Some(150));
+        assert_eq!(config.git_health_max_git_gb, Some(5));
+        assert_eq!(
+            config.gastown_fields,
+            Some(vec![GastownFieldArg::Role, GastownFieldArg::Mail])
+        );
+        assert_eq!(config.gastown_max_width, Some(40));
+        assert_eq!(config.beads_stale_hours, Some(8));
+        assert_eq!(config.beads_labels, Some(vec!["agent:*".to_string()]));
+        assert_eq!(
+            config.git_issue_patterns,
+            Some(vec!["#[0-9]+".to_string(), r"[A-Z]{2,5}-\d+".to_string()])
+        );
         assert_eq!(config.prompt_cache_ttl_seconds, Some(3600));
+        assert_eq!(config.timezone.as_deref(), Some("Europe/Berlin"));
         assert_eq!(config.display.cost_provenance, Some(true));
         assert_eq!(config.display.cost_today, Some(false));
+        assert_eq!(config.display.cost_mtd, Some(true));
+        assert_eq!(config.session_cost_cap, Some(15.0));
         assert_eq!(config.display.integrations_prompt_cache, Some(false));
+        assert_eq!(config.budget_daily, Some(25.0));
+        assert_eq!(config.budget_weekly, None);
+        assert_eq!(config.budget_monthly, Some(300.0));
+        assert_eq!(config.subscription, Some(SubscriptionArg::Max5x));
+        assert_eq!(config.subscription_price, None);
+        assert_eq!(config.billing_day, Some(14));
+        assert_eq!(config.chargeback_multiplier, Some(1.25));
+        assert_eq!(config.currency.as_deref(), Some("EUR"));
+        assert_eq!(config.currency_rate, Some(0.92));
+        assert_eq!(config.currency_fetch, None);
+        assert_eq!(config.pricing_file.as_deref(), Some("~/prices.json"));
+        assert_eq!(config.pricing_fetch, Some(true));
+        assert_eq!(
+            config.free_models,
+            Some(vec!["llama-*".to_string(), "qwen2.5-coder:*".to_string()])
+        );
+        assert_eq!(
+            parse_string_list("\"a, b\"").unwrap(),
+            vec!["a".to_string(), "b".to_string()]
+        );
+        assert_eq!(config.proxy_surcharge_percent, Some(5.0));
+        assert_eq!(config.proxy_surcharge_per_mtok, Some(0.25));
+        assert_eq!(config.entries_retention_days, Some(0));
+        assert_eq!(config.api_cache_retention_days, Some(14));
+        assert_eq!(config.global_sum_ttl_seconds, None);
+        assert_eq!(config.scan_ttl_seconds, Some(0));
+        assert_eq!(config.usage_api_ttl_seconds, Some(900));
+        assert_eq!(
+            config.usage_api_proxy.as_deref(),
+            Some("socks5://127.0.0.1:1080")
+        );
+        assert_eq!(config.account.as_deref(), Some("work"));
+        assert_eq!(config.admin_api_key.as_deref(), Some("sk-ant-admin01-test"));
+        assert_eq!(config.openrouter, Some(true));
+        assert_eq!(
+            config.accounts,
+            vec![("work".to_string(), PathBuf::from("/home/u/.claude-work"))]
+        );
+        assert_eq!(config.absent_ttl_seconds, Some(120));
+        assert_eq!(config.sync_dir.as_deref(), Some("~/usage-sync"));
+        assert_eq!(config.sync_machine.as_deref(), Some("laptop"));
+        assert_eq!(config.environment, Some(true));
+        assert_eq!(config.carbon_intensity, Some(250.0));
+        assert_eq!(
+            config.energy_overrides,
+            vec![("opus".to_string(), "output".to_string(), 1.5)]
+        );
+        assert_eq!(
+            config.tags,
+            vec![("~/work/Acme_API".to_string(), "acme".to_string())]
+        );
+        assert_eq!(
+            config.project_pricing,
+            vec![
+                ("~/work/acme*".to_string(), ProjectRates::Multiplier(1.15)),
+                (
+                    "/srv/client".to_string(),
+                    ProjectRates::File(PathBuf::from("/srv/client/pricing.json"))
+                ),
+                (
+                    "/srv/other".to_string(),
+                    ProjectRates::File(PathBuf::from("/srv/other/pricing.json"))
+                ),
+            ]
+        );
+        assert!(parse_config_str("[project_pricing]\n\"/a\" = -1").is_err());
+        assert!(parse_config_str("[project_pricing]\n\"/a\" = '/a/pricing.json").is_err());
+        assert!(parse_config_str("billing_day = 32").is_err());
+        assert!(parse_config_str("[pricing]\nsurcharge_percent = -5").is_err());
+        assert!(parse_config_str("[display]\nsession_cost_cap = -5").is_err());
+        assert!(parse_config_str("[environment.opus]\noutptu = 1.5").is_err());
+        assert!(parse_config_str("[environment.opus]\noutput = -1").is_err());
     }
 }
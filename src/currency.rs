@@ -0,0 +1,280 @@
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::cli::Args;
+
+/// Free, keyless USD-based rate table (updated daily upstream).
+const RATES_ENDPOINT: &str = "https://open.er-api.com/v6/latest/USD";
+const RATE_CACHE_TTL_SECONDS: i64 = 24 * 3600;
+const NEGATIVE_CACHE_TTL_SECONDS: i64 = 3600;
+const RATE_CACHE_PREFIX: &str = "fx_rate_usd_";
+const NEGATIVE_CACHE_KEY: &str = "fx_rate_negative";
+
+/// Where the active exchange rate came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateSource {
+    /// `--currency-rate` / `[currency] rate`
+    Static,
+    /// Fetched rate, possibly served from the cache
+    Fetched,
+    /// Expired cached rate, used after a failed fetch
+    Stale,
+}
+
+impl RateSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RateSource::Static => "static",
+            RateSource::Fetched => "fetched",
+            RateSource::Stale => "stale",
+        }
+    }
+}
+
+/// Currency costs are displayed in. All figures are computed in USD and
+/// converted only when formatted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayCurrency {
+    /// ISO 4217 code, uppercase
+    pub code: String,
+    /// Units of `code` per USD
+    pub rate: f64,
+    pub source: RateSource,
+}
+
+static DISPLAY_CURRENCY: OnceCell<DisplayCurrency> = OnceCell::new();
+
+/// Set the process-wide display currency. Only the first call takes effect.
+pub fn set_display_currency(currency: DisplayCurrency) {
+    let _ = DISPLAY_CURRENCY.set(currency);
+}
+
+pub fn display_currency() -> Option<&'static DisplayCurrency> {
+    DISPLAY_CURRENCY.get()
+}
+
+/// Convert a USD amount into `currency` (identity for `None`, i.e. USD).
+pub fn convert(usd: f64, currency: Option<&DisplayCurrency>) -> f64 {
+    currency.map_or(usd, |c| usd * c.rate)
+}
+
+/// Symbol for a currency code; mirrors the Claude Code formatter's symbol
+/// map and falls back to the ISO code for the rest.
+pub fn symbol(code: &str) -> String {
+    match code {
+        "USD" => "$".to_string(),
+        "EUR" => "€".to_string(),
+        "GBP" => "£".to_string(),
+        "JPY" => "¥".to_string(),
+        "BRL" => "R$".to_string(),
+        "CAD" => "CA$".to_string(),
+        "AUD" => "AU$".to_string(),
+        "NZD" => "NZ$".to_string(),
+        "SGD" => "SG$".to_string(),
+        other => format!("{other} "),
+    }
+}
+
+/// Symbol prefixed to costs converted into `currency`.
+pub fn cost_symbol(currency: Option<&DisplayCurrency>) -> String {
+    symbol(currency.map_or("USD", |c| c.code.as_str()))
+}
+
+/// A USD amount converted into `currency` with its symbol, e.g. `€3.92`.
+pub fn money(usd: f64, currency: Option<&DisplayCurrency>) -> String {
+    format!(
+        "{}{}",
+        cost_symbol(currency),
+        crate::utils::format_currency(convert(usd, currency))
+    )
+}
+
+/// Resolve `--currency` into a rate. `None` when unset, USD, or no rate is
+/// available (no static rate and fetching disabled or failed).
+pub fn resolve(args: &Args) -> Option<DisplayCurrency> {
+    let code = args.currency.as_deref()?.trim().to_ascii_uppercase();
+    if code == "USD" || code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    if let Some(rate) = args.currency_rate.filter(|r| *r > 0.0) {
+        return Some(DisplayCurrency {
+            code,
+            rate,
+            source: RateSource::Static,
+        });
+    }
+    if !args.currency_fetch || args.no_subsystem_db_cache {
+        return None;
+    }
+    let (rate, source) = cached_rate(&code)?;
+    Some(DisplayCurrency { code, rate, source })
+}
+
+fn cached_rate(code: &str) -> Option<(f64, RateSource)> {
+    let key = format!("{RATE_CACHE_PREFIX}{code}");
+    let parse = |s: String| s.parse::<f64>().ok().filter(|r| *r > 0.0);
+    if let Ok(Some(rate)) = crate::db::get_api_cache(&key).map(|v| v.and_then(parse)) {
+        return Some((rate, RateSource::Fetched));
+    }
+    let stale = || {
+        crate::db::get_stale_api_cache(&key)
+            .ok()
+            .flatten()
+            .and_then(parse)
+            .map(|rate| (rate, RateSource::Stale))
+    };
+    if let Ok(Some(_)) = crate::db::get_api_cache(NEGATIVE_CACHE_KEY) {
+        return stale();
+    }
+
+    match fetch_rate(code) {
+        Some(rate) => {
+            let _ = crate::db::set_api_cache(&key, &rate.to_string(), RATE_CACHE_TTL_SECONDS);
+            Some((rate, RateSource::Fetched))
+        }
+        None => {
+            let _ = crate::db::set_api_cache(NEGATIVE_CACHE_KEY, "1", NEGATIVE_CACHE_TTL_SECONDS);
+            stale()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RatesResponse {
+    rates: std::collections::HashMap<String, f64>,
+}
+
+fn fetch_rate(code: &str) -> Option<f64> {
+    let config = ureq::Agent::config_builder().timeout_global(Some(Duration::from_secs(3)));
+    let agent: ureq::Agent = config.build().into();
+    let mut response = agent.get(RATES_ENDPOINT).call().ok()?;
+    let body: RatesResponse = response.body_mut().read_json().ok()?;
+    body.rates.get(code).copied().filter(|r| *r > 0.0)
+}
+
+/// Add a converted sibling next to every numeric `*_usd` field
+/// (`cost_usd` -> `cost_eur`) and a top-level `currency` descriptor, so JSON
+/// consumers get both values. No-op without a display currency.
+pub fn add_converted_fields(json: &mut serde_json::Value) {
+    let Some(currency) = display_currency() else {
+        return;
+    };
+    let suffix = format!("_{}", currency.code.to_ascii_lowercase());
+    convert_fields(json, currency.rate, &suffix);
+    if let Some(obj) = json.as_object_mut() {
+        obj.insert(
+            "currency".to_string(),
+            serde_json::json!({
+                "code": currency.code,
+                "rate": currency.rate,
+                "source": currency.source.as_str(),
+            }),
+        );
+    }
+}
+
+fn convert_fields(value: &mut serde_json::Value, rate: f64, suffix: &str) {
+    match value {
+        serde_json::Value::Object(obj) => {
+            let converted: Vec<(String, f64)> = obj
+                .iter()
+                .filter_map(|(key, v)| {
+                    let base = key.strip_suffix("_usd")?;
+                    let usd = v.as_f64()?;
+                    // Per-unit prices stay sub-cent; keep their precision.
+                    let scale = if base.starts_with("cost_per_") {
+                        10_000.0
+                    } else {
+                        100.0
+                    };
+                    Some((
+                        format!("{base}{suffix}"),
+                        (usd * rate * scale).round() / scale,
+                    ))
+                })
+                .collect();
+            for v in obj.values_mut() {
+                convert_fields(v, rate, suffix);
+            }
+            for (key, amount) in converted {
+                obj.insert(key, serde_json::json!(amount));
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                convert_fields(item, rate, suffix);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn resolve_prefers_static_rate_and_skips_usd() {
+        let args = Args::parse_from([
+            "claude_statusline",
+            "--currency",
+            "eur",
+            "--currency-rate",
+            "0.92",
+        ]);
+        let currency = resolve(&args).expect("static rate");
+        assert_eq!(currency.code, "EUR");
+        assert_eq!(currency.rate, 0.92);
+        assert_eq!(currency.source, RateSource::Static);
+
+        let usd = Args::parse_from(["claude_statusline", "--currency", "USD"]);
+        assert!(resolve(&usd).is_none());
+
+        // No static rate and fetching off: stay in USD.
+        let unrated = Args::parse_from(["claude_statusline", "--currency", "GBP"]);
+        assert!(resolve(&unrated).is_none());
+    }
+
+    #[test]
+    fn money_converts_at_the_given_rate() {
+        let eur = DisplayCurrency {
+            code: "EUR".to_string(),
+            rate: 0.92,
+            source: RateSource::Static,
+        };
+        assert_eq!(money(4.26, None), "$4.26");
+        assert_eq!(money(10.0, Some(&eur)), "€9.20");
+        assert_eq!(
+            money(
+                1.0,
+                Some(&DisplayCurrency {
+                    code: "CHF".to_string(),
+                    ..eur
+                })
+            ),
+            "CHF 0.92"
+        );
+    }
+
+    #[test]
+    fn converted_fields_sit_next_to_usd_fields() {
+        let mut json = serde_json::json!({
+            "session": { "cost_usd": 10.0 },
+            "today": { "top_projects": [{ "name": "-repo", "cost_usd": 4.0 }] },
+            "window": { "efficiency": { "cost_per_1k_output_usd": 0.0153 } },
+            "sessions_count": 3,
+        });
+        convert_fields(&mut json, 0.9, "_eur");
+
+        assert_eq!(json["session"]["cost_usd"], 10.0);
+        assert_eq!(json["session"]["cost_eur"], 9.0);
+        assert_eq!(json["today"]["top_projects"][0]["cost_eur"], 3.6);
+        assert_eq!(
+            json["window"]["efficiency"]["cost_per_1k_output_eur"],
+            0.0138
+        );
+        assert_eq!(json["sessions_count"], 3);
+    }
+}
@@ -7,28 +7,58 @@
 //! - Global usage aggregation across all active sessions
 //! - Concurrent access support via WAL mode
 
-use crate::models::Entry;
+pub use crate::models::ledger::{
+    CacheTtls, CostHistory, DailyCost, DbHealth, GlobalUsage, HistoryRow, ImportReport,
+    IntegrityReport, InvalidateReport, LifetimeStats, MaintenanceReport, MetadataEntry,
+    MigrationReport, MigrationStep, ModelTotals, PruneReport, RenderTiming, Retention, SeatLedger,
+    UsageSnapshot,
+};
+use crate::models::{Entry, TokenCounts};
+use crate::provenance::{CacheLayer, record_cache};
 use anyhow::{Context, Result, bail};
-use chrono::{Local, Utc};
+use chrono::{Local, NaiveDate, Utc};
+use once_cell::sync::OnceCell;
 use rusqlite::{Connection, OptionalExtension, params};
-use serde::Serialize;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-const SCHEMA_VERSION: i64 = 4;
-const SCHEMA_VERSION_STR: &str = "4";
+const SCHEMA_VERSION: i64 = 14;
 const USAGE_CACHE_VERSION: &str = "3";
 const METADATA_KEY_SCHEMA_VERSION: &str = "schema_version";
 const METADATA_KEY_USAGE_CACHE_VERSION: &str = "usage_cache_version";
+const METADATA_KEY_LAST_PRUNE: &str = "last_prune";
+const METADATA_KEY_WAL_CHECKPOINT_BYTES: &str = "wal_checkpoint_bytes";
+const METADATA_KEY_VACUUM_FREE_PERCENT: &str = "vacuum_free_percent";
+const METADATA_KEY_LAST_CHECKPOINT: &str = "last_checkpoint";
+const METADATA_KEY_LAST_VACUUM: &str = "last_vacuum";
+const METADATA_KEY_RECOVERED_FROM: &str = "recovered_from";
+const DEFAULT_WAL_CHECKPOINT_BYTES: u64 = 8 * 1024 * 1024;
+const DEFAULT_VACUUM_FREE_PERCENT: f64 = 20.0;
+/// Free pages below this never warrant a vacuum, whatever the ratio.
+const VACUUM_MIN_FREE_PAGES: i64 = 256;
+/// Ring-buffer size of `render_timings`; enough for p95s across a few versions.
+const RENDER_TIMINGS_KEPT: i64 = 1000;
 const GLOBAL_SUM_CACHE_PREFIX: &str = "global_sum:";
-const GLOBAL_SUM_CACHE_TTL_SECONDS: i64 = 5;
 const OAUTH_USAGE_SUMMARY_CACHE_KEY: &str = "oauth_usage_summary";
+const OAUTH_PROFILE_CACHE_KEY: &str = "oauth_profile";
+/// Expired API cache entries are kept this long as the stale fallback served
+/// while a refetch runs or backs off
+const API_CACHE_STALE_KEEP_SECONDS: i64 = 7 * 24 * 3600;
 const COST_EPSILON: f64 = 1e-9;
+const DAILY_COST_RETENTION_DAYS: i64 = 400;
+/// How long [`lock_db_file`] waits for another process before giving up
+const FILE_LOCK_WAIT: Duration = Duration::from_secs(10);
+/// A lock file this old was left by a process that died holding it
+const FILE_LOCK_STALE_AFTER: Duration = Duration::from_secs(60);
+/// Days of per-day totals [`GlobalUsage::daily_history`] carries, including today
+pub const DAILY_HISTORY_DAYS: i64 = 28;
 
 mod sql {
     pub const INIT_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS sessions (
@@ -77,7 +107,45 @@ mod sql {
         CREATE INDEX IF NOT EXISTS idx_usage_events_today_session
             ON usage_events(today_date, session_id);
         CREATE INDEX IF NOT EXISTS idx_usage_events_session_date
-            ON usage_events(session_id, today_date);";
+            ON usage_events(session_id, today_date);
+        CREATE TABLE IF NOT EXISTS daily_costs (
+            date TEXT PRIMARY KEY CHECK (length(date) = 10),
+            cost REAL NOT NULL CHECK (cost >= 0.0),
+            updated_at INTEGER NOT NULL CHECK (updated_at >= 0)
+        );
+        CREATE TABLE IF NOT EXISTS session_costs (
+            date TEXT NOT NULL CHECK (length(date) = 10),
+            session_id TEXT NOT NULL,
+            cost REAL NOT NULL CHECK (cost >= 0.0),
+            updated_at INTEGER NOT NULL CHECK (updated_at >= 0),
+            transcript_path TEXT,
+            PRIMARY KEY (date, session_id)
+        );
+        CREATE TABLE IF NOT EXISTS model_costs (
+            date TEXT NOT NULL CHECK (length(date) = 10),
+            model TEXT NOT NULL,
+            cost REAL NOT NULL CHECK (cost >= 0.0),
+            input_tokens INTEGER NOT NULL CHECK (input_tokens >= 0),
+            output_tokens INTEGER NOT NULL CHECK (output_tokens >= 0),
+            cache_create_tokens INTEGER NOT NULL CHECK (cache_create_tokens >= 0),
+            cache_read_tokens INTEGER NOT NULL CHECK (cache_read_tokens >= 0),
+            updated_at INTEGER NOT NULL CHECK (updated_at >= 0),
+            PRIMARY KEY (date, model)
+        );
+        CREATE TABLE IF NOT EXISTS lifetime_totals (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            through_date TEXT NOT NULL CHECK (length(through_date) = 10),
+            first_seen TEXT,
+            cost REAL NOT NULL CHECK (cost >= 0.0),
+            input_tokens INTEGER NOT NULL CHECK (input_tokens >= 0),
+            output_tokens INTEGER NOT NULL CHECK (output_tokens >= 0),
+            cache_create_tokens INTEGER NOT NULL CHECK (cache_create_tokens >= 0),
+            cache_read_tokens INTEGER NOT NULL CHECK (cache_read_tokens >= 0),
+            active_days INTEGER NOT NULL CHECK (active_days >= 0),
+            busiest_date TEXT,
+            busiest_cost REAL NOT NULL CHECK (busiest_cost >= 0.0),
+            updated_at INTEGER NOT NULL CHECK (updated_at >= 0)
+        );";
 
     pub const ADD_METADATA_UPDATED_AT: &str = "ALTER TABLE metadata ADD COLUMN updated_at INTEGER";
     pub const CREATE_SESSIONS_TODAY_DATE_INDEX: &str =
@@ -168,6 +236,12 @@ mod sql {
     pub const DELETE_ALL_SESSIONS: &str = "DELETE FROM sessions";
     pub const DELETE_ALL_USAGE_EVENTS: &str = "DELETE FROM usage_events";
     pub const DELETE_GLOBAL_SUM_CACHE: &str = "DELETE FROM metadata WHERE key LIKE 'global_sum:%'";
+    pub const DELETE_SESSION_STATE: &str = "DELETE FROM sessions WHERE session_id = ?";
+    pub const DELETE_ALL_SESSION_STATE: &str = "DELETE FROM sessions";
+    /// Scan cache keys are `scan:<date>:<session>:<options>`
+    pub const DELETE_SESSION_SCAN_CACHE: &str = "DELETE FROM api_cache
+         WHERE cache_key LIKE 'scan:%' AND instr(cache_key, ':' || ?1 || ':') > 0";
+    pub const DELETE_ALL_API_CACHE: &str = "DELETE FROM api_cache";
     pub const CREATE_USAGE_EVENTS: &str = "CREATE TABLE IF NOT EXISTS usage_events (
             event_key TEXT PRIMARY KEY,
             session_id TEXT NOT NULL,
@@ -222,6 +296,7 @@ mod sql {
             updated_at
         FROM sessions
         WHERE session_id != '' AND today_cost >= 0.0";
+    pub const GET_METADATA_VALUE: &str = "SELECT value FROM metadata WHERE key = ?1";
     pub const GET_METADATA: &str = "SELECT value, updated_at FROM metadata WHERE key = ?1";
     pub const SET_METADATA: &str = "INSERT INTO metadata (key, value, updated_at)
          VALUES (?1, ?2, ?3)
@@ -277,6 +352,93 @@ mod sql {
             cost = excluded.cost,
             source = excluded.source,
             updated_at = excluded.updated_at";
+    pub const CREATE_ENTRIES: &str = "CREATE TABLE IF NOT EXISTS entries (
+            event_key TEXT PRIMARY KEY,
+            ts INTEGER NOT NULL,
+            date TEXT NOT NULL CHECK (length(date) = 10),
+            session_id TEXT NOT NULL,
+            project TEXT NOT NULL,
+            model TEXT,
+            input_tokens INTEGER NOT NULL CHECK (input_tokens >= 0),
+            output_tokens INTEGER NOT NULL CHECK (output_tokens >= 0),
+            cache_create_tokens INTEGER NOT NULL CHECK (cache_create_tokens >= 0),
+            cache_read_tokens INTEGER NOT NULL CHECK (cache_read_tokens >= 0),
+            web_search_requests INTEGER NOT NULL CHECK (web_search_requests >= 0),
+            cost REAL NOT NULL CHECK (cost >= 0.0),
+            updated_at INTEGER NOT NULL CHECK (updated_at >= 0)
+        );
+        CREATE INDEX IF NOT EXISTS idx_entries_date ON entries(date);
+        CREATE INDEX IF NOT EXISTS idx_entries_session_date ON entries(session_id, date);";
+    pub const SELECT_USAGE_EVENTS_FOR_ENTRIES: &str =
+        "SELECT event_key, ts, today_date, session_id,
+            transcript_path, model, input_tokens, output_tokens, cache_create_tokens,
+            cache_read_tokens, web_search_requests, cost
+        FROM usage_events";
+    pub const DELETE_ENTRIES_FOR_SESSION_DATE: &str =
+        "DELETE FROM entries WHERE session_id = ? AND date = ?";
+    pub const UPSERT_ENTRY: &str = "INSERT INTO entries (
+            event_key,
+            ts,
+            date,
+            session_id,
+            project,
+            model,
+            input_tokens,
+            output_tokens,
+            cache_create_tokens,
+            cache_read_tokens,
+            web_search_requests,
+            cost,
+            updated_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(event_key) DO UPDATE SET
+            ts = excluded.ts,
+            date = excluded.date,
+            session_id = excluded.session_id,
+            project = excluded.project,
+            model = excluded.model,
+            input_tokens = excluded.input_tokens,
+            output_tokens = excluded.output_tokens,
+            cache_create_tokens = excluded.cache_create_tokens,
+            cache_read_tokens = excluded.cache_read_tokens,
+            web_search_requests = excluded.web_search_requests,
+            cost = excluded.cost,
+            updated_at = excluded.updated_at";
+    #[cfg(test)]
+    pub const SELECT_ENTRIES_BETWEEN: &str = "SELECT session_id, project, model, input_tokens, cost
+        FROM entries WHERE date >= ? AND date <= ? ORDER BY ts";
+    pub const CREATE_TOTALS: &str = "CREATE TABLE IF NOT EXISTS daily_totals (
+            date TEXT NOT NULL CHECK (length(date) = 10),
+            project TEXT NOT NULL,
+            model TEXT NOT NULL,
+            cost REAL NOT NULL CHECK (cost >= 0.0),
+            input_tokens INTEGER NOT NULL CHECK (input_tokens >= 0),
+            output_tokens INTEGER NOT NULL CHECK (output_tokens >= 0),
+            cache_create_tokens INTEGER NOT NULL CHECK (cache_create_tokens >= 0),
+            cache_read_tokens INTEGER NOT NULL CHECK (cache_read_tokens >= 0),
+            entry_count INTEGER NOT NULL CHECK (entry_count >= 0),
+            updated_at INTEGER NOT NULL CHECK (updated_at >= 0),
+            PRIMARY KEY (date, project, model)
+        );";
+    pub const DELETE_DAILY_TOTALS_FOR_DATE: &str = "DELETE FROM daily_totals WHERE date = ?";
+    pub const INSERT_DAILY_TOTALS_FOR_DATE: &str = "INSERT INTO daily_totals (
+            date, project, model, cost, input_tokens, output_tokens,
+            cache_create_tokens, cache_read_tokens, entry_count, updated_at
+        )
+         SELECT date, project, COALESCE(model, '(unknown)'), MAX(SUM(cost), 0.0),
+                SUM(input_tokens), SUM(output_tokens),
+                SUM(cache_create_tokens), SUM(cache_read_tokens), COUNT(*), ?1
+         FROM entries
+         WHERE date = ?2
+         GROUP BY project, COALESCE(model, '(unknown)')";
+    pub const SELECT_ENTRY_DATES: &str = "SELECT DISTINCT date FROM entries";
+    pub const SELECT_DAILY_TOTALS_START: &str = "SELECT MIN(date) FROM daily_totals";
+    pub const SELECT_PROJECT_TOTALS_BETWEEN: &str = "SELECT project, SUM(cost)
+         FROM daily_totals
+         WHERE date >= ? AND date <= ?
+         GROUP BY project";
+    pub const DELETE_EXPIRED_DAILY_TOTALS: &str = "DELETE FROM daily_totals WHERE date < ?";
     pub const SELECT_GLOBAL_TODAY: &str = "WITH session_totals AS (
             SELECT session_id, SUM(cost) AS today_cost
             FROM usage_events
@@ -284,9 +446,134 @@ mod sql {
             GROUP BY session_id
         )
         SELECT COALESCE(SUM(today_cost), 0.0), COUNT(*) FROM session_totals";
+    pub const CREATE_DAILY_COSTS: &str = "CREATE TABLE IF NOT EXISTS daily_costs (
+            date TEXT PRIMARY KEY CHECK (length(date) = 10),
+            cost REAL NOT NULL CHECK (cost >= 0.0),
+            updated_at INTEGER NOT NULL CHECK (updated_at >= 0)
+        )";
+    pub const UPSERT_DAILY_COST: &str = "INSERT INTO daily_costs (date, cost, updated_at)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(date) DO UPDATE SET
+             cost = excluded.cost,
+             updated_at = excluded.updated_at";
+    pub const DELETE_EXPIRED_DAILY_COSTS: &str = "DELETE FROM daily_costs WHERE date < ?";
+    pub const CREATE_SESSION_COSTS: &str = "CREATE TABLE IF NOT EXISTS session_costs (
+            date TEXT NOT NULL CHECK (length(date) = 10),
+            session_id TEXT NOT NULL,
+            cost REAL NOT NULL CHECK (cost >= 0.0),
+            updated_at INTEGER NOT NULL CHECK (updated_at >= 0),
+            transcript_path TEXT,
+            PRIMARY KEY (date, session_id)
+        )";
+    pub const ADD_SESSION_COSTS_TRANSCRIPT_PATH: &str =
+        "ALTER TABLE session_costs ADD COLUMN transcript_path TEXT";
+    pub const UPSERT_SESSION_COSTS_FOR_DATE: &str =
+        "INSERT INTO session_costs (date, session_id, cost, updated_at, transcript_path)
+         SELECT today_date, session_id, MAX(SUM(cost), 0.0), ?1, MAX(transcript_path)
+         FROM usage_events
+         WHERE today_date = ?2
+         GROUP BY session_id
+         ON CONFLICT(date, session_id) DO UPDATE SET
+             cost = excluded.cost,
+             updated_at = excluded.updated_at,
+             transcript_path = excluded.transcript_path";
+    pub const DELETE_EXPIRED_SESSION_COSTS: &str = "DELETE FROM session_costs WHERE date < ?";
+    pub const SELECT_SESSION_TOTALS_SINCE: &str =
+        "SELECT SUM(cost) FROM session_costs WHERE date >= ? GROUP BY session_id";
+    pub const CREATE_MODEL_COSTS: &str = "CREATE TABLE IF NOT EXISTS model_costs (
+            date TEXT NOT NULL CHECK (length(date) = 10),
+            model TEXT NOT NULL,
+            cost REAL NOT NULL CHECK (cost >= 0.0),
+            input_tokens INTEGER NOT NULL CHECK (input_tokens >= 0),
+            output_tokens INTEGER NOT NULL CHECK (output_tokens >= 0),
+            cache_create_tokens INTEGER NOT NULL CHECK (cache_create_tokens >= 0),
+            cache_read_tokens INTEGER NOT NULL CHECK (cache_read_tokens >= 0),
+            updated_at INTEGER NOT NULL CHECK (updated_at >= 0),
+            PRIMARY KEY (date, model)
+        )";
+    pub const UPSERT_MODEL_COSTS_FOR_DATE: &str = "INSERT INTO model_costs (
+            date, model, cost, input_tokens, output_tokens,
+            cache_create_tokens, cache_read_tokens, updated_at
+        )
+         SELECT today_date, COALESCE(model, '(unknown)'), MAX(SUM(cost), 0.0),
+                SUM(input_tokens), SUM(output_tokens),
+                SUM(cache_create_tokens), SUM(cache_read_tokens), ?1
+         FROM usage_events
+         WHERE today_date = ?2
+         GROUP BY COALESCE(model, '(unknown)')
+         ON CONFLICT(date, model) DO UPDATE SET
+             cost = excluded.cost,
+             input_tokens = excluded.input_tokens,
+             output_tokens = excluded.output_tokens,
+             cache_create_tokens = excluded.cache_create_tokens,
+             cache_read_tokens = excluded.cache_read_tokens,
+             updated_at = excluded.updated_at";
+    pub const DELETE_EXPIRED_MODEL_COSTS: &str = "DELETE FROM model_costs WHERE date < ?";
+    pub const SELECT_MODEL_COSTS_BETWEEN: &str = "SELECT model, SUM(cost), SUM(input_tokens),
+                SUM(output_tokens), SUM(cache_create_tokens), SUM(cache_read_tokens)
+         FROM model_costs
+         WHERE date >= ? AND date <= ?
+         GROUP BY model";
+    pub const CREATE_LIFETIME_TOTALS: &str = "CREATE TABLE IF NOT EXISTS lifetime_totals (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            through_date TEXT NOT NULL CHECK (length(through_date) = 10),
+            first_seen TEXT,
+            cost REAL NOT NULL CHECK (cost >= 0.0),
+            input_tokens INTEGER NOT NULL CHECK (input_tokens >= 0),
+            output_tokens INTEGER NOT NULL CHECK (output_tokens >= 0),
+            cache_create_tokens INTEGER NOT NULL CHECK (cache_create_tokens >= 0),
+            cache_read_tokens INTEGER NOT NULL CHECK (cache_read_tokens >= 0),
+            active_days INTEGER NOT NULL CHECK (active_days >= 0),
+            busiest_date TEXT,
+            busiest_cost REAL NOT NULL CHECK (busiest_cost >= 0.0),
+            updated_at INTEGER NOT NULL CHECK (updated_at >= 0)
+        )";
+    pub const SELECT_LIFETIME_TOTALS: &str = "SELECT through_date, first_seen, cost,
+                input_tokens, output_tokens, cache_create_tokens, cache_read_tokens,
+                active_days, busiest_date, busiest_cost
+         FROM lifetime_totals WHERE id = 1";
+    pub const UPSERT_LIFETIME_TOTALS: &str = "INSERT INTO lifetime_totals (
+            id, through_date, first_seen, cost, input_tokens, output_tokens,
+            cache_create_tokens, cache_read_tokens, active_days, busiest_date,
+            busiest_cost, updated_at
+        )
+         VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+         ON CONFLICT(id) DO UPDATE SET
+             through_date = excluded.through_date,
+             first_seen = excluded.first_seen,
+             cost = excluded.cost,
+             input_tokens = excluded.input_tokens,
+             output_tokens = excluded.output_tokens,
+             cache_create_tokens = excluded.cache_create_tokens,
+             cache_read_tokens = excluded.cache_read_tokens,
+             active_days = excluded.active_days,
+             busiest_date = excluded.busiest_date,
+             busiest_cost = excluded.busiest_cost,
+             updated_at = excluded.updated_at";
+    pub const SELECT_DAILY_COSTS_IN_WINDOW: &str = "SELECT date, cost FROM daily_costs
+         WHERE date > ?1 AND date < ?2
+         ORDER BY date";
+    pub const SELECT_DAILY_TOKENS_IN_WINDOW: &str = "SELECT SUM(input_tokens), SUM(output_tokens),
+                SUM(cache_create_tokens), SUM(cache_read_tokens)
+         FROM model_costs
+         WHERE date > ?1 AND date < ?2";
+    pub const SELECT_TOKENS_BY_DATE_BETWEEN: &str = "SELECT date, SUM(input_tokens),
+                SUM(output_tokens), SUM(cache_create_tokens), SUM(cache_read_tokens)
+         FROM model_costs
+         WHERE date >= ? AND date <= ?
+         GROUP BY date";
+    pub const SELECT_API_CACHE_WITH_FETCHED_AT: &str =
+        "SELECT data, fetched_at FROM api_cache WHERE cache_key = ?";
+    pub const SELECT_TRANSCRIPT_COSTS_BETWEEN: &str = "SELECT transcript_path, SUM(cost)
+         FROM session_costs
+         WHERE date >= ? AND date <= ? AND transcript_path IS NOT NULL
+         GROUP BY transcript_path";
+    pub const SELECT_DAILY_COSTS: &str =
+        "SELECT date, cost FROM daily_costs WHERE date >= ? AND date <= ? ORDER BY date";
     pub const GET_FRESH_API_CACHE: &str =
-        "SELECT data FROM api_cache WHERE cache_key = ? AND expires_at > ?";
-    pub const GET_STALE_API_CACHE: &str = "SELECT data FROM api_cache WHERE cache_key = ?";
+        "SELECT data, fetched_at FROM api_cache WHERE cache_key = ? AND expires_at > ?";
+    pub const GET_STALE_API_CACHE: &str =
+        "SELECT data, fetched_at FROM api_cache WHERE cache_key = ?";
     pub const DELETE_EXPIRED_API_CACHE_KEY: &str =
         "DELETE FROM api_cache WHERE cache_key = ? AND expires_at <= ?";
     pub const TRY_INSERT_API_CACHE: &str =
@@ -300,26 +587,100 @@ mod sql {
              data = excluded.data,
              fetched_at = excluded.fetched_at,
              expires_at = excluded.expires_at";
-    pub const DELETE_EXPIRED_API_CACHE: &str =
-        "DELETE FROM api_cache WHERE expires_at <= ? AND cache_key != ?";
-}
-
-/// Global usage result containing both session-specific and global costs
-#[derive(Debug, Clone)]
-pub struct GlobalUsage {
-    /// Cost for the current session only
-    pub session_cost: f64,
-    /// Total cost across all sessions for today
-    pub global_today: f64,
-    /// Number of sessions contributing to global total
-    pub sessions_count: usize,
-}
-
-/// Metadata value with optional timestamp
-#[derive(Debug, Clone)]
-pub struct MetadataEntry {
-    pub value: String,
-    pub updated_at: Option<i64>,
+    pub const RECONCILE_DAILY_COST_FROM_SESSIONS: &str =
+        "INSERT INTO daily_costs (date, cost, updated_at)
+         SELECT date, SUM(cost), ?1 FROM session_costs WHERE date = ?2 GROUP BY date
+         ON CONFLICT(date) DO UPDATE SET
+             cost = excluded.cost,
+             updated_at = excluded.updated_at
+         WHERE excluded.cost > daily_costs.cost";
+    pub const RECONCILE_MODEL_COSTS_FROM_ENTRIES: &str = "INSERT INTO model_costs (
+            date, model, cost, input_tokens, output_tokens,
+            cache_create_tokens, cache_read_tokens, updated_at
+        )
+         SELECT date, COALESCE(model, '(unknown)'), MAX(SUM(cost), 0.0),
+                SUM(input_tokens), SUM(output_tokens),
+                SUM(cache_create_tokens), SUM(cache_read_tokens), ?1
+         FROM entries
+         WHERE date = ?2
+         GROUP BY COALESCE(model, '(unknown)')
+         ON CONFLICT(date, model) DO UPDATE SET
+             cost = excluded.cost,
+             input_tokens = excluded.input_tokens,
+             output_tokens = excluded.output_tokens,
+             cache_create_tokens = excluded.cache_create_tokens,
+             cache_read_tokens = excluded.cache_read_tokens,
+             updated_at = excluded.updated_at
+         WHERE excluded.cost > model_costs.cost";
+    pub const DELETE_ENTRIES_BEFORE: &str = "DELETE FROM entries WHERE date < ?";
+    pub const CREATE_USAGE_SNAPSHOTS: &str = "CREATE TABLE IF NOT EXISTS usage_snapshots (
+            fetched_at INTEGER PRIMARY KEY,
+            five_hour REAL,
+            five_hour_resets_at INTEGER,
+            seven_day REAL,
+            seven_day_resets_at INTEGER,
+            seven_day_opus REAL,
+            seven_day_sonnet REAL
+        )";
+    pub const DROP_USAGE_SNAPSHOTS_V14: &str = "DROP TABLE IF EXISTS usage_snapshots_v14";
+    pub const CREATE_USAGE_SNAPSHOTS_V14: &str = "CREATE TABLE usage_snapshots_v14 (
+            account TEXT NOT NULL DEFAULT '',
+            fetched_at INTEGER NOT NULL,
+            five_hour REAL,
+            five_hour_resets_at INTEGER,
+            seven_day REAL,
+            seven_day_resets_at INTEGER,
+            seven_day_opus REAL,
+            seven_day_sonnet REAL,
+            PRIMARY KEY (account, fetched_at)
+        )";
+    pub const COPY_USAGE_SNAPSHOTS_V14: &str = "INSERT INTO usage_snapshots_v14 (
+            fetched_at, five_hour, five_hour_resets_at, seven_day,
+            seven_day_resets_at, seven_day_opus, seven_day_sonnet
+        )
+        SELECT fetched_at, five_hour, five_hour_resets_at, seven_day,
+            seven_day_resets_at, seven_day_opus, seven_day_sonnet
+        FROM usage_snapshots";
+    pub const DROP_USAGE_SNAPSHOTS: &str = "DROP TABLE usage_snapshots";
+    pub const RENAME_USAGE_SNAPSHOTS_V14: &str =
+        "ALTER TABLE usage_snapshots_v14 RENAME TO usage_snapshots";
+    pub const INSERT_USAGE_SNAPSHOT: &str = "INSERT OR REPLACE INTO usage_snapshots (
+            account, fetched_at, five_hour, five_hour_resets_at, seven_day,
+            seven_day_resets_at, seven_day_opus, seven_day_sonnet
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)";
+    pub const SELECT_USAGE_SNAPSHOTS_SINCE: &str =
+        "SELECT account, fetched_at, five_hour, five_hour_resets_at,
+                seven_day, seven_day_resets_at, seven_day_opus, seven_day_sonnet
+         FROM usage_snapshots
+         WHERE account = ? AND fetched_at >= ?
+         ORDER BY fetched_at";
+    pub const DELETE_USAGE_SNAPSHOTS_BEFORE: &str =
+        "DELETE FROM usage_snapshots WHERE fetched_at < ?";
+    pub const CREATE_RENDER_TIMINGS: &str = "CREATE TABLE IF NOT EXISTS render_timings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            recorded_at INTEGER NOT NULL,
+            version TEXT NOT NULL,
+            scan_ms REAL NOT NULL,
+            db_ms REAL NOT NULL,
+            git_ms REAL NOT NULL,
+            api_ms REAL NOT NULL,
+            render_ms REAL NOT NULL,
+            total_ms REAL NOT NULL
+        )";
+    pub const INSERT_RENDER_TIMING: &str = "INSERT INTO render_timings (
+            recorded_at, version, scan_ms, db_ms, git_ms, api_ms, render_ms, total_ms
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)";
+    pub const TRIM_RENDER_TIMINGS: &str = "DELETE FROM render_timings
+         WHERE id <= (SELECT MAX(id) FROM render_timings) - ?";
+    pub const SELECT_RENDER_TIMINGS: &str =
+        "SELECT recorded_at, version, scan_ms, db_ms, git_ms, api_ms, render_ms, total_ms
+         FROM render_timings
+         ORDER BY id";
+    pub const DELETE_API_CACHE_FETCHED_BEFORE: &str = "DELETE FROM api_cache WHERE fetched_at < ?";
+    /// Keeps `?2` and `?3`, and their per-account `<key>:<label>` entries
+    pub const DELETE_EXPIRED_API_CACHE: &str = "DELETE FROM api_cache WHERE expires_at <= ?1
+         AND cache_key NOT IN (?2, ?3)
+         AND cache_key NOT GLOB ?2 || ':*' AND cache_key NOT GLOB ?3 || ':*'";
 }
 
 impl MetadataEntry {
@@ -387,20 +748,6 @@ struct UsageEvent {
     source: &'static str,
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct DbHealth {
-    pub path: String,
-    pub exists: bool,
-    pub parent_exists: bool,
-    pub writable: bool,
-    pub journal_mode: Option<String>,
-    pub schema_version: Option<String>,
-    pub user_version: Option<i64>,
-    pub usage_cache_version: Option<String>,
-    pub ok: bool,
-    pub error: Option<String>,
-}
-
 /// Get the database file path
 ///
 /// Checks `CLAUDE_STATUSLINE_DB_PATH` environment variable first,
@@ -421,22 +768,361 @@ fn get_db_path() -> Result<PathBuf> {
     Ok(claude_dir.join("statusline.db"))
 }
 
+/// SQLCipher key handling for `--features encrypted-db` builds.
+///
+/// The key is `CLAUDE_STATUSLINE_DB_KEY` when set, otherwise a random 256-bit
+/// key kept in the OS keychain (macOS Keychain via `security`, or the Secret
+/// Service via `secret-tool` elsewhere), created on first use.
+#[cfg(feature = "encrypted-db")]
+mod keychain {
+    use anyhow::{Context, Result, bail};
+    use once_cell::sync::OnceCell;
+    use rusqlite::Connection;
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+    use std::time::Duration;
+
+    use crate::utils::{run_with_input, run_with_timeout};
+
+    const SERVICE: &str = "claude-statusline-db";
+    /// macOS may show a keychain access prompt, which needs time to answer
+    const KEYCHAIN_TIMEOUT: Duration = if cfg!(target_os = "macos") {
+        Duration::from_secs(30)
+    } else {
+        Duration::from_secs(2)
+    };
+
+    /// Looked up once per process; every db call opens a connection
+    static KEY: OnceCell<String> = OnceCell::new();
+
+    pub(super) fn apply_key(conn: &Connection) -> Result<()> {
+        let key = KEY.get_or_try_init(db_key)?;
+        // Raw keys skip SQLCipher's passphrase KDF: x'<64 hex digits>'
+        let pragma = if key.len() == 64 && key.chars().all(|c| c.is_ascii_hexdigit()) {
+            format!("PRAGMA key = \"x'{key}'\";")
+        } else {
+            format!("PRAGMA key = '{}';", key.replace('\'', "''"))
+        };
+        conn.execute_batch(&pragma)?;
+        // Fails here, not on some later query, when the key is wrong or the
+        // file is an unencrypted database from a default build.
+        conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))
+            .context("cannot decrypt the statusline database (wrong key, or created unencrypted)")
+    }
+
+    fn db_key() -> Result<String> {
+        if let Ok(key) = std::env::var("CLAUDE_STATUSLINE_DB_KEY")
+            && !key.trim().is_empty()
+        {
+            return Ok(key.trim().to_string());
+        }
+        if let Some(key) = read_keychain() {
+            return Ok(key);
+        }
+        // Two first renders must not each store a key of their own: the
+        // keychain keeps the last one, and a file encrypted under the other
+        // could never be opened again.
+        let _lock = super::lock_db_file(&super::get_db_path()?)?;
+        if let Some(key) = read_keychain() {
+            return Ok(key);
+        }
+        store_keychain(&random_key()?)?;
+        read_keychain().context("failed to store the database key in the OS keychain")
+    }
+
+    fn account() -> String {
+        std::env::var("USER").unwrap_or_else(|_| "statusline".to_string())
+    }
+
+    fn read_keychain() -> Option<String> {
+        let mut command;
+        if cfg!(target_os = "macos") {
+            command = Command::new("security");
+            command.args([
+                "find-generic-password",
+                "-a",
+                &account(),
+                "-s",
+                SERVICE,
+                "-w",
+            ]);
+        } else {
+            command = Command::new("secret-tool");
+            command.args(["lookup", "service", SERVICE, "account", &account()]);
+        }
+        let output = run_with_timeout(command, KEYCHAIN_TIMEOUT)?;
+        let key = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !key.is_empty()).then_some(key)
+    }
+
+    /// The key goes over stdin, never argv, where `ps` would show it
+    fn store_keychain(key: &str) -> Result<()> {
+        let (mut command, input) = if cfg!(target_os = "macos") {
+            // `security -i` reads its command line from stdin
+            let mut command = Command::new("security");
+            command.arg("-i");
+            let line = format!(
+                "add-generic-password -a \"{}\" -s {SERVICE} -w {key}\n",
+                account().replace(['"', '\\'], "")
+            );
+            (command, line)
+        } else {
+            let mut command = Command::new("secret-tool");
+            command
+                .args(["store", "--label", "Claude statusline database key"])
+                .args(["service", SERVICE, "account", &account()]);
+            (command, key.to_string())
+        };
+        command.stderr(Stdio::null());
+        let output = run_with_input(command, Some(input.as_bytes()), KEYCHAIN_TIMEOUT)
+            .context("no OS keychain available; set CLAUDE_STATUSLINE_DB_KEY")?;
+        // `security -i` exits 0 even when the command inside fails, so the
+        // caller reads the key back
+        if !output.status.success() {
+            bail!("failed to store the database key in the OS keychain");
+        }
+        Ok(())
+    }
+
+    fn random_key() -> Result<String> {
+        let mut bytes = [0u8; 32];
+        std::fs::File::open("/dev/urandom")
+            .and_then(|mut f| f.read_exact(&mut bytes))
+            .context("no random source for a database key; set CLAUDE_STATUSLINE_DB_KEY")?;
+        Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+    }
+}
+
+/// Connection kept open while a [`HeldDb`] is alive.
+struct HeldConnection {
+    path: PathBuf,
+    conn: Connection,
+}
+
+static HELD: Mutex<Option<HeldConnection>> = Mutex::new(None);
+
+/// Whether `err` means the database file itself is damaged, as opposed to
+/// locked, read-only, or missing. "Not a database" does not count: that is
+/// also what a wrong key, or an encrypted file in a default build, reports.
+fn is_corruption(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<rusqlite::Error>(),
+            Some(rusqlite::Error::SqliteFailure(e, _))
+                if e.code == rusqlite::ErrorCode::DatabaseCorrupt
+        )
+    })
+}
+
+/// Cross-process lock next to the database, `<db>.lock`, held until dropped.
+/// The file is created exclusively, so this works the same on every platform;
+/// one older than [`FILE_LOCK_STALE_AFTER`] was left by a crashed process and
+/// is taken over.
+struct DbFileLock {
+    path: PathBuf,
+}
+
+impl Drop for DbFileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn lock_db_file(db_path: &Path) -> Result<DbFileLock> {
+    let mut name = db_path.as_os_str().to_owned();
+    name.push(".lock");
+    let path = PathBuf::from(name);
+    let started = Instant::now();
+    loop {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(_) => return Ok(DbFileLock { path }),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                let stale = fs::metadata(&path)
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .and_then(|modified| modified.elapsed().ok())
+                    .is_some_and(|age| age > FILE_LOCK_STALE_AFTER);
+                if stale {
+                    let _ = fs::remove_file(&path);
+                } else if started.elapsed() >= FILE_LOCK_WAIT {
+                    bail!("timed out waiting for {}", path.display());
+                } else {
+                    thread::sleep(Duration::from_millis(20));
+                }
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("failed to create {}", path.display()));
+            }
+        }
+    }
+}
+
+/// Move a corrupt database (and its WAL and shared-memory files) aside as
+/// `<name>.corrupt-<unix seconds>-<pid>` and create a fresh one in its place.
+/// Returns where the old file went, or `None` when another process recovered
+/// it first.
+fn recover_corrupt_db() -> Result<Option<PathBuf>> {
+    let path = get_db_path()?;
+    let _lock = lock_db_file(&path)?;
+    if !check().is_ok_and(|report| !report.ok) {
+        return Ok(None);
+    }
+    let mut held = lock_held();
+    let was_held = held.as_ref().is_some_and(|h| h.path == path);
+    if was_held {
+        // The open handle would keep writing to the moved file
+        held.take();
+    }
+    let suffix = format!(".corrupt-{}-{}", Utc::now().timestamp(), std::process::id());
+    let with_suffix = |base: &Path, extra: &str| {
+        let mut name = base.as_os_str().to_owned();
+        name.push(extra);
+        PathBuf::from(name)
+    };
+    let moved = with_suffix(&path, &suffix);
+    fs::rename(&path, &moved)
+        .with_context(|| format!("failed to move corrupt database {}", path.display()))?;
+    for extra in ["-wal", "-shm"] {
+        let side = with_suffix(&path, extra);
+        if side.exists() {
+            let _ = fs::rename(&side, with_suffix(&moved, extra));
+        }
+    }
+
+    let conn = connect(&path)?;
+    set_metadata(
+        &conn,
+        METADATA_KEY_RECOVERED_FROM,
+        &moved.display().to_string(),
+    )?;
+    if was_held {
+        *held = Some(HeldConnection { path, conn });
+    }
+    Ok(Some(moved))
+}
+
+/// Run `PRAGMA integrity_check` without touching the schema, so a damaged
+/// file is reported rather than half-migrated.
+pub fn check() -> Result<IntegrityReport> {
+    let path = get_db_path()?;
+    let mut report = IntegrityReport {
+        path: path.display().to_string(),
+        ..IntegrityReport::default()
+    };
+    if !path.exists() {
+        report.ok = true;
+        return Ok(report);
+    }
+    let conn = Connection::open_with_flags(&path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    #[cfg(feature = "encrypted-db")]
+    keychain::apply_key(&conn)?;
+    let problems = conn.prepare("PRAGMA integrity_check").and_then(|mut stmt| {
+        stmt.query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+    });
+    match problems {
+        Ok(rows) => {
+            report.ok = rows.len() == 1 && rows[0] == "ok";
+            if !report.ok {
+                report.problems = rows;
+            }
+        }
+        // Damage bad enough that the check itself cannot run
+        Err(err) => report.problems = vec![err.to_string()],
+    }
+    report.recovered_from = get_metadata(&conn, METADATA_KEY_RECOVERED_FROM)
+        .ok()
+        .flatten()
+        .map(|entry| entry.value);
+    Ok(report)
+}
+
+/// Keeps one database connection open for reuse by every db call until
+/// dropped, so long-running modes (watch, serve) skip reopening and schema
+/// init on each refresh. One-shot renders don't hold one and open a
+/// short-lived connection per call.
+#[must_use = "the connection is released when the handle is dropped"]
+pub struct HeldDb {
+    _private: (),
+}
+
+/// Open the database and keep the connection for reuse until the returned
+/// handle is dropped.
+pub fn hold() -> Result<HeldDb> {
+    let path = get_db_path()?;
+    let conn = connect(&path)?;
+    *lock_held() = Some(HeldConnection { path, conn });
+    Ok(HeldDb { _private: () })
+}
+
+impl Drop for HeldDb {
+    fn drop(&mut self) {
+        lock_held().take();
+    }
+}
+
+fn lock_held() -> MutexGuard<'static, Option<HeldConnection>> {
+    // A panic mid-query leaves the connection itself usable
+    HELD.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Connection returned by [`open_db`]: the held one, or a fresh one closed on drop.
+enum DbConn {
+    Owned(Connection),
+    Held(MutexGuard<'static, Option<HeldConnection>>),
+}
+
+impl Deref for DbConn {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        match self {
+            DbConn::Owned(conn) => conn,
+            DbConn::Held(guard) => &guard.as_ref().expect("held connection present").conn,
+        }
+    }
+}
+
+/// Get a connection: the held one when a [`HeldDb`] is alive for the same
+/// path, otherwise a newly opened one.
+fn open_db() -> Result<DbConn> {
+    let db_path = get_db_path()?;
+    let held = lock_held();
+    if held.as_ref().is_some_and(|h| h.path == db_path) {
+        // Reopening used to prune; a held connection checks on each use instead
+        if let Some(h) = held.as_ref() {
+            housekeeping(&h.conn, &h.path);
+        }
+        return Ok(DbConn::Held(held));
+    }
+    drop(held);
+    connect(&db_path).map(DbConn::Owned)
+}
+
 /// Open database connection with WAL mode and retry logic
 ///
 /// Implements retry logic for "database locked" errors with exponential backoff.
 /// Configures WAL mode for concurrent access and sets busy timeout.
-fn open_db() -> Result<Connection> {
-    let db_path = get_db_path()?;
-
+fn connect(db_path: &Path) -> Result<Connection> {
     let mut attempts = 0;
     let max_attempts = 3;
 
     loop {
-        match Connection::open(&db_path) {
+        match Connection::open(db_path) {
             Ok(conn) => {
+                // The key must be set before the first read of an encrypted file
+                #[cfg(feature = "encrypted-db")]
+                keychain::apply_key(&conn)?;
+                // Only takes effect on a new file; older ones switch on `db maintain`
+                conn.pragma_update(None, "auto_vacuum", "INCREMENTAL")?;
                 conn.pragma_update(None, "journal_mode", "WAL")?;
                 conn.pragma_update(None, "busy_timeout", 5000)?;
                 init_schema(&conn)?;
+                housekeeping(&conn, db_path);
                 return Ok(conn);
             }
             Err(e) if e.to_string().contains("locked") && attempts < max_attempts => {
@@ -461,6 +1147,7 @@ pub fn inspect_health() -> DbHealth {
                 schema_version: None,
                 user_version: None,
                 usage_cache_version: None,
+                encrypted: cfg!(feature = "encrypted-db"),
                 ok: false,
                 error: Some(err.to_string()),
             };
@@ -498,6 +1185,7 @@ pub fn inspect_health() -> DbHealth {
                 schema_version,
                 user_version,
                 usage_cache_version,
+                encrypted: cfg!(feature = "encrypted-db"),
                 ok: writable,
                 error: None,
             }
@@ -511,100 +1199,666 @@ pub fn inspect_health() -> DbHealth {
             schema_version: None,
             user_version: None,
             usage_cache_version: None,
+            encrypted: cfg!(feature = "encrypted-db"),
             ok: false,
             error: Some(err.to_string()),
         },
     }
 }
 
-/// Fetch metadata value by key (opens a short-lived connection)
-pub fn load_metadata(key: &str) -> Result<Option<MetadataEntry>> {
-    let conn = open_db()?;
-    get_metadata(&conn, key)
-}
+static RETENTION: OnceCell<Retention> = OnceCell::new();
 
-/// Persist metadata value by key (opens a short-lived connection)
-pub fn store_metadata(key: &str, value: &str) -> Result<()> {
-    let conn = open_db()?;
-    set_metadata(&conn, key, value)
+/// Install the retention policy for this process.
+pub fn set_retention(retention: Retention) {
+    let _ = RETENTION.set(retention);
 }
 
-/// Initialize database schema
-///
-/// Creates tables and indexes if they don't exist.
-/// Handles schema versioning via metadata table.
-fn init_schema(conn: &Connection) -> Result<()> {
-    conn.execute_batch(sql::INIT_SCHEMA)?;
-
-    migrate_schema(conn)?;
-
-    match get_metadata(conn, METADATA_KEY_USAGE_CACHE_VERSION)? {
-        Some(cache_version) if cache_version.value == USAGE_CACHE_VERSION => {}
-        Some(_) => {
-            conn.execute(sql::DELETE_ALL_SESSIONS, [])?;
-            conn.execute(sql::DELETE_ALL_USAGE_EVENTS, [])?;
-            clear_global_sum_cache(conn)?;
-            set_metadata(conn, METADATA_KEY_USAGE_CACHE_VERSION, USAGE_CACHE_VERSION)?;
-        }
-        None => {
-            clear_global_sum_cache(conn)?;
-            set_metadata(conn, METADATA_KEY_USAGE_CACHE_VERSION, USAGE_CACHE_VERSION)?;
-        }
-    }
-
-    Ok(())
+fn retention() -> Retention {
+    RETENTION.get().copied().unwrap_or_default()
 }
 
-fn migrate_schema(conn: &Connection) -> Result<()> {
-    let user_version = sqlite_user_version(conn)?;
-    let mut schema_changed = false;
-
-    if user_version > SCHEMA_VERSION {
-        bail!(
-            "SQLite schema version {} is newer than supported version {}",
-            user_version,
-            SCHEMA_VERSION
-        );
-    }
-
-    if !table_has_column(conn, "metadata", "updated_at")? {
-        conn.execute(sql::ADD_METADATA_UPDATED_AT, [])?;
-        schema_changed = true;
-    }
+static CACHE_TTLS: OnceCell<CacheTtls> = OnceCell::new();
 
-    if user_version < 2 || !table_has_column(conn, "sessions", "session_id")? {
-        migrate_sessions_session_id(conn)?;
-        schema_changed = true;
-    }
+/// Install the cache freshness windows for this process.
+pub fn set_cache_ttls(ttls: CacheTtls) {
+    let _ = CACHE_TTLS.set(ttls);
+}
 
-    if !table_column_is_primary_key(conn, "sessions", "session_id")? {
-        migrate_sessions_session_id_primary_key(conn)?;
-        schema_changed = true;
-    }
+pub fn cache_ttls() -> CacheTtls {
+    CACHE_TTLS.get().copied().unwrap_or_default()
+}
 
-    create_session_indexes(conn)?;
+/// Apply the retention policy now (`db prune`).
+pub fn prune() -> Result<PruneReport> {
+    let conn = open_db()?;
+    let mut report = prune_with(&conn, retention(), Utc::now())?;
+    report.path = get_db_path()?.display().to_string();
+    Ok(report)
+}
 
-    create_usage_events_schema(conn)?;
-    if user_version < 4 {
-        conn.execute(sql::BACKFILL_USAGE_EVENTS_FROM_SESSIONS, [])?;
-        schema_changed = true;
-    }
+/// Drop cached state so the next render recomputes it (`cache invalidate`).
+/// With a session, only that session's parse state and scan results go, plus
+/// the global sums it fed; without one, every session row and `api_cache`
+/// row does. Usage history and ledgers are kept either way.
+pub fn invalidate_cache(session_id: Option<&str>) -> Result<InvalidateReport> {
+    let conn = open_db()?;
+    let (sessions, api_cache) = match session_id {
+        Some(id) => (
+            conn.execute(sql::DELETE_SESSION_STATE, params![id])?,
+            conn.execute(sql::DELETE_SESSION_SCAN_CACHE, params![id])?,
+        ),
+        None => (
+            conn.execute(sql::DELETE_ALL_SESSION_STATE, [])?,
+            conn.execute(sql::DELETE_ALL_API_CACHE, [])?,
+        ),
+    };
+    Ok(InvalidateReport {
+        path: get_db_path()?.display().to_string(),
+        session_id: session_id.map(str::to_string),
+        sessions,
+        api_cache,
+        metadata: clear_global_sum_cache(&conn)?,
+    })
+}
 
-    let metadata_version = get_metadata(conn, METADATA_KEY_SCHEMA_VERSION)?;
-    if metadata_version.as_ref().map(|m| m.value.as_str()) != Some(SCHEMA_VERSION_STR) {
-        set_metadata(conn, METADATA_KEY_SCHEMA_VERSION, SCHEMA_VERSION_STR)?;
-        schema_changed = true;
+fn prune_with(
+    conn: &Connection,
+    retention: Retention,
+    now: chrono::DateTime<Utc>,
+) -> Result<PruneReport> {
+    let mut report = PruneReport::default();
+    if let Some(days) = retention.entries_days {
+        let cutoff = (now.with_timezone(&Local).date_naive()
+            - chrono::Duration::days(i64::from(days)))
+        .format("%Y-%m-%d")
+        .to_string();
+        report.entries = conn.execute(sql::DELETE_ENTRIES_BEFORE, params![cutoff])?;
+        let cutoff = now.timestamp() - i64::from(days) * 86_400;
+        report.usage_snapshots =
+            conn.execute(sql::DELETE_USAGE_SNAPSHOTS_BEFORE, params![cutoff])?;
     }
-
-    if user_version != SCHEMA_VERSION {
-        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
-        schema_changed = true;
+    if let Some(days) = retention.api_cache_days {
+        let cutoff = now.timestamp() - i64::from(days) * 86_400;
+        report.api_cache = conn.execute(sql::DELETE_API_CACHE_FETCHED_BEFORE, params![cutoff])?;
     }
+    Ok(report)
+}
 
-    if schema_changed {
-        clear_global_sum_cache(conn)?;
+/// Prune at most once per local day, on whichever open gets there first.
+fn prune_if_due(conn: &Connection) -> Result<()> {
+    let today = crate::utils::now_local().date_naive().to_string();
+    if get_metadata(conn, METADATA_KEY_LAST_PRUNE)?.is_some_and(|m| m.value == today) {
+        return Ok(());
+    }
+    prune_with(conn, retention(), Utc::now())?;
+    set_metadata(conn, METADATA_KEY_LAST_PRUNE, &today)?;
+    // Pruning is what frees pages, so this is when reclaiming them pays off
+    vacuum_if_due(conn, false)?;
+    Ok(())
+}
+
+/// Per-connection upkeep: the daily prune and a WAL checkpoint once the
+/// `-wal` file outgrows its threshold. Best effort: a locked or read-only db
+/// still serves reads.
+fn housekeeping(conn: &Connection, db_path: &Path) {
+    let _ = prune_if_due(conn);
+    let _ = checkpoint_if_due(conn, db_path);
+}
+
+/// Thresholds for [`checkpoint_if_due`] and [`vacuum_if_due`]. Defaults are
+/// written to metadata on first use so they can be tuned per database.
+fn maintenance_thresholds(conn: &Connection) -> Result<(u64, f64)> {
+    let wal_bytes = match get_metadata(conn, METADATA_KEY_WAL_CHECKPOINT_BYTES)? {
+        Some(entry) => entry.value.parse().unwrap_or(DEFAULT_WAL_CHECKPOINT_BYTES),
+        None => {
+            set_metadata(
+                conn,
+                METADATA_KEY_WAL_CHECKPOINT_BYTES,
+                &DEFAULT_WAL_CHECKPOINT_BYTES.to_string(),
+            )?;
+            DEFAULT_WAL_CHECKPOINT_BYTES
+        }
+    };
+    let free_percent = match get_metadata(conn, METADATA_KEY_VACUUM_FREE_PERCENT)? {
+        Some(entry) => entry.value.parse().unwrap_or(DEFAULT_VACUUM_FREE_PERCENT),
+        None => {
+            set_metadata(
+                conn,
+                METADATA_KEY_VACUUM_FREE_PERCENT,
+                &DEFAULT_VACUUM_FREE_PERCENT.to_string(),
+            )?;
+            DEFAULT_VACUUM_FREE_PERCENT
+        }
+    };
+    Ok((wal_bytes, free_percent))
+}
+
+fn wal_size(db_path: &Path) -> u64 {
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    fs::metadata(wal).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Fold the WAL back into the database and truncate it. SQLite's automatic
+/// checkpoints never shrink the file, so with many concurrent sessions it
+/// keeps growing.
+fn checkpoint_if_due(conn: &Connection, db_path: &Path) -> Result<bool> {
+    let (threshold, _) = maintenance_thresholds(conn)?;
+    if wal_size(db_path) < threshold {
+        return Ok(false);
+    }
+    checkpoint(conn)
+}
+
+/// Returns false when another connection kept the checkpoint from finishing.
+fn checkpoint(conn: &Connection) -> Result<bool> {
+    let busy: i64 = conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))?;
+    set_metadata(
+        conn,
+        METADATA_KEY_LAST_CHECKPOINT,
+        &Utc::now().timestamp().to_string(),
+    )?;
+    Ok(busy == 0)
+}
+
+fn page_counts(conn: &Connection) -> Result<(i64, i64)> {
+    let pages = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    let free = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+    Ok((pages, free))
+}
+
+/// Return free pages to the filesystem once they pass the threshold share of
+/// the file. Only incremental (cheap) vacuums run here; a database created
+/// before auto_vacuum was enabled needs one full `VACUUM` via `db maintain`.
+/// With `force`, any free pages are reclaimed, converting the file if needed.
+fn vacuum_if_due(conn: &Connection, force: bool) -> Result<Option<&'static str>> {
+    let (_, free_percent) = maintenance_thresholds(conn)?;
+    let (pages, free) = page_counts(conn)?;
+    let due = free >= VACUUM_MIN_FREE_PAGES && free as f64 * 100.0 >= pages as f64 * free_percent;
+    if !(due || force && free > 0) {
+        return Ok(None);
+    }
+    let auto_vacuum: i64 = conn.query_row("PRAGMA auto_vacuum", [], |row| row.get(0))?;
+    let kind = if auto_vacuum == 2 {
+        // Frees one page per step, so it has to be run to completion
+        let mut stmt = conn.prepare("PRAGMA incremental_vacuum")?;
+        let mut rows = stmt.query([])?;
+        while rows.next()?.is_some() {}
+        "incremental"
+    } else if force {
+        conn.pragma_update(None, "auto_vacuum", "INCREMENTAL")?;
+        conn.execute_batch("VACUUM")?;
+        "full"
+    } else {
+        return Ok(None);
+    };
+    set_metadata(
+        conn,
+        METADATA_KEY_LAST_VACUUM,
+        &Utc::now().timestamp().to_string(),
+    )?;
+    Ok(Some(kind))
+}
+
+/// Checkpoint and truncate the WAL and reclaim all free pages now, instead of
+/// waiting for the thresholds.
+pub fn maintain() -> Result<MaintenanceReport> {
+    let path = get_db_path()?;
+    let conn = open_db()?;
+    let wal_bytes_before = wal_size(&path);
+    let (pages_before, free_pages_before) = page_counts(&conn)?;
+    let vacuum = vacuum_if_due(&conn, true)?;
+    let checkpointed = checkpoint(&conn)?;
+    let (pages_after, _) = page_counts(&conn)?;
+    Ok(MaintenanceReport {
+        path: path.display().to_string(),
+        wal_bytes_before,
+        checkpointed,
+        pages_before,
+        free_pages_before,
+        pages_after,
+        vacuum,
+    })
+}
+
+/// `format` marker of a `db export` document.
+const EXPORT_FORMAT: &str = "claude-statusline-db";
+
+/// History tables carried by `db export`. `entries` and `session_costs` have
+/// natural row identities and merge row by row; the per-day ledgers are
+/// reconciled from them on import. Aggregates are rebuilt rather than copied.
+const EXPORT_TABLES: &[&str] = &["entries", "session_costs", "daily_costs", "model_costs"];
+
+/// Usage history as a JSON document: `{"format", "schema_version",
+/// "exported_at", "tables": {"<table>": [{column: value}, ...]}}`.
+pub fn export_json() -> Result<serde_json::Value> {
+    let conn = open_db()?;
+    let mut tables = serde_json::Map::new();
+    for table in EXPORT_TABLES {
+        tables.insert(table.to_string(), serde_json::Value::Array(Vec::new()));
+    }
+    for row in history_rows(&conn, 0)? {
+        if let Some(serde_json::Value::Array(rows)) = tables.get_mut(&row.table) {
+            rows.push(serde_json::Value::Object(row.row));
+        }
+    }
+    Ok(serde_json::json!({
+        "format": EXPORT_FORMAT,
+        "schema_version": SCHEMA_VERSION,
+        "exported_at": Utc::now().to_rfc3339(),
+        "tables": tables,
+    }))
+}
+
+/// History rows written or updated at or after `since` (unix seconds).
+pub fn history_rows_since(since: i64) -> Result<Vec<HistoryRow>> {
+    let conn = open_db()?;
+    history_rows(&conn, since)
+}
+
+fn history_rows(conn: &Connection, since: i64) -> Result<Vec<HistoryRow>> {
+    let mut out = Vec::new();
+    for table in EXPORT_TABLES {
+        let mut stmt = conn.prepare(&format!("SELECT * FROM {table} WHERE updated_at >= ?"))?;
+        let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+        let mut rows = stmt.query(params![since])?;
+        while let Some(row) = rows.next()? {
+            let mut object = serde_json::Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                object.insert(column.clone(), sql_to_json(row.get_ref(i)?));
+            }
+            out.push(HistoryRow {
+                table: table.to_string(),
+                row: object,
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// Merge history rows (from `db export` or sync snapshots) into this database
+/// with the same newest-copy-wins rules as `db import`.
+pub fn merge_history_rows(rows: &[HistoryRow]) -> Result<ImportReport> {
+    let conn = open_db()?;
+    let mut report = merge_rows_into(&conn, rows)?;
+    report.path = get_db_path()?.display().to_string();
+    Ok(report)
+}
+
+fn merge_rows_into(conn: &Connection, rows: &[HistoryRow]) -> Result<ImportReport> {
+    run_schema_change(conn, |conn| {
+        let mut report = ImportReport::default();
+        let mut dates = std::collections::BTreeSet::new();
+        for table in EXPORT_TABLES {
+            let known = table_columns(conn, table)?;
+            let mut merged = 0;
+            for row in rows.iter().filter(|r| r.table == *table) {
+                if let Some(date) = row.row.get("date").and_then(|d| d.as_str()) {
+                    dates.insert(date.to_string());
+                }
+                merged += merge_row(conn, table, &known, &row.row)?;
+            }
+            report.tables.insert(table, merged);
+        }
+        for date in &dates {
+            reconcile_imported_date(conn, date)?;
+        }
+        report.dates = dates.len();
+        Ok(report)
+    })
+}
+
+fn sql_to_json(value: rusqlite::types::ValueRef<'_>) -> serde_json::Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null | ValueRef::Blob(_) => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+    }
+}
+
+fn json_to_sql(value: &serde_json::Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value as Sql;
+    match value {
+        serde_json::Value::Bool(b) => Sql::Integer(i64::from(*b)),
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .map(Sql::Integer)
+            .or_else(|| n.as_f64().map(Sql::Real))
+            .unwrap_or(Sql::Null),
+        serde_json::Value::String(s) => Sql::Text(s.clone()),
+        _ => Sql::Null,
+    }
+}
+
+/// Merge a `db export` document into this database. Rows with the same
+/// identity keep whichever copy was updated last, so importing the same
+/// export twice changes nothing and histories from several machines combine.
+pub fn import_json(doc: &serde_json::Value) -> Result<ImportReport> {
+    let conn = open_db()?;
+    let mut report = import_into(&conn, doc)?;
+    report.path = get_db_path()?.display().to_string();
+    Ok(report)
+}
+
+fn import_into(conn: &Connection, doc: &serde_json::Value) -> Result<ImportReport> {
+    if doc.get("format").and_then(|f| f.as_str()) != Some(EXPORT_FORMAT) {
+        bail!("not a claude-statusline db export");
+    }
+    let version = doc
+        .get("schema_version")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    if version > SCHEMA_VERSION {
+        bail!("export is from schema version {version}, newer than supported {SCHEMA_VERSION}");
+    }
+    let tables = doc
+        .get("tables")
+        .and_then(|t| t.as_object())
+        .context("export has no tables")?;
+
+    let rows: Vec<HistoryRow> = EXPORT_TABLES
+        .iter()
+        .flat_map(|table| {
+            tables
+                .get(*table)
+                .and_then(|r| r.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|row| row.as_object())
+                .map(|row| HistoryRow {
+                    table: table.to_string(),
+                    row: row.clone(),
+                })
+        })
+        .collect();
+    merge_rows_into(conn, &rows)
+}
+
+/// Upsert one exported row, keeping the copy with the newer `updated_at`.
+/// Ledger rows without a row identity of their own keep the larger cost,
+/// and are then reconciled from the merged entries and sessions.
+fn merge_row(
+    conn: &Connection,
+    table: &str,
+    known: &[String],
+    row: &serde_json::Map<String, serde_json::Value>,
+) -> Result<usize> {
+    let (key, resolve) = match table {
+        "entries" => ("event_key", "excluded.updated_at > entries.updated_at"),
+        "session_costs" => (
+            "date, session_id",
+            "excluded.updated_at > session_costs.updated_at",
+        ),
+        "daily_costs" => ("date", "excluded.cost > daily_costs.cost"),
+        "model_costs" => ("date, model", "excluded.cost > model_costs.cost"),
+        _ => bail!("unsupported table {table}"),
+    };
+    // Only columns this schema has; they also come from PRAGMA, not the file
+    let columns: Vec<&str> = known
+        .iter()
+        .map(String::as_str)
+        .filter(|c| row.contains_key(*c))
+        .collect();
+    if columns.is_empty() {
+        return Ok(0);
+    }
+    let updates: Vec<String> = columns
+        .iter()
+        .map(|c| format!("{c} = excluded.{c}"))
+        .collect();
+    let statement = format!(
+        "INSERT INTO {table} ({}) VALUES ({}) ON CONFLICT({key}) DO UPDATE SET {} WHERE {resolve}",
+        columns.join(", "),
+        vec!["?"; columns.len()].join(", "),
+        updates.join(", "),
+    );
+    let values: Vec<rusqlite::types::Value> =
+        columns.iter().map(|c| json_to_sql(&row[*c])).collect();
+    Ok(conn.execute(&statement, rusqlite::params_from_iter(values))?)
+}
+
+/// Bring a day's ledgers up to the merged history: the global total is at
+/// least the sum of its sessions, per-model rows at least the entries' sums,
+/// and the per-project/model aggregates are rebuilt. Rows already there keep
+/// their `updated_at`, so the next sync export doesn't send them back.
+fn reconcile_imported_date(conn: &Connection, date: &str) -> Result<()> {
+    let now = Utc::now().timestamp();
+    conn.execute(sql::RECONCILE_DAILY_COST_FROM_SESSIONS, params![now, date])?;
+    conn.execute(sql::RECONCILE_MODEL_COSTS_FROM_ENTRIES, params![now, date])?;
+    refresh_totals(conn, date)
+}
+
+/// Fetch metadata value by key (opens a short-lived connection)
+pub fn load_metadata(key: &str) -> Result<Option<MetadataEntry>> {
+    let conn = open_db()?;
+    get_metadata(&conn, key)
+}
+
+/// Persist metadata value by key (opens a short-lived connection)
+pub fn store_metadata(key: &str, value: &str) -> Result<()> {
+    let conn = open_db()?;
+    set_metadata(&conn, key, value)
+}
+
+/// Initialize database schema
+///
+/// Creates tables and indexes if they don't exist.
+/// Handles schema versioning via metadata table.
+fn init_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(sql::INIT_SCHEMA)?;
+
+    migrate_schema(conn)?;
+
+    match get_metadata(conn, METADATA_KEY_USAGE_CACHE_VERSION)? {
+        Some(cache_version) if cache_version.value == USAGE_CACHE_VERSION => {}
+        Some(_) => {
+            conn.execute(sql::DELETE_ALL_SESSIONS, [])?;
+            conn.execute(sql::DELETE_ALL_USAGE_EVENTS, [])?;
+            clear_global_sum_cache(conn)?;
+            set_metadata(conn, METADATA_KEY_USAGE_CACHE_VERSION, USAGE_CACHE_VERSION)?;
+        }
+        None => {
+            clear_global_sum_cache(conn)?;
+            set_metadata(conn, METADATA_KEY_USAGE_CACHE_VERSION, USAGE_CACHE_VERSION)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A forward-only schema step taking the database from `version - 1` to
+/// `version`. Steps check the live schema before changing it, because
+/// databases created before versioning was enforced may already have some of
+/// them applied.
+struct Migration {
+    version: i64,
+    description: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+/// Numbered migrations, in order. Append new steps here and bump
+/// `SCHEMA_VERSION`; never edit or reorder a released step.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "metadata.updated_at",
+        apply: migrate_metadata_updated_at,
+    },
+    Migration {
+        version: 2,
+        description: "sessions.session_id",
+        apply: migrate_sessions_session_id,
+    },
+    Migration {
+        version: 3,
+        description: "sessions keyed by session_id",
+        apply: migrate_sessions_session_id_primary_key,
+    },
+    Migration {
+        version: 4,
+        description: "usage_events backfilled from sessions",
+        apply: migrate_usage_events,
+    },
+    Migration {
+        version: 5,
+        description: "daily_costs ledger",
+        apply: |conn| Ok(conn.execute(sql::CREATE_DAILY_COSTS, []).map(drop)?),
+    },
+    Migration {
+        version: 6,
+        description: "session_costs ledger",
+        apply: |conn| Ok(conn.execute(sql::CREATE_SESSION_COSTS, []).map(drop)?),
+    },
+    Migration {
+        version: 7,
+        description: "session_costs.transcript_path",
+        apply: migrate_session_costs_transcript_path,
+    },
+    Migration {
+        version: 8,
+        description: "model_costs ledger",
+        apply: |conn| Ok(conn.execute(sql::CREATE_MODEL_COSTS, []).map(drop)?),
+    },
+    Migration {
+        version: 9,
+        description: "lifetime_totals",
+        apply: |conn| Ok(conn.execute(sql::CREATE_LIFETIME_TOTALS, []).map(drop)?),
+    },
+    Migration {
+        version: 10,
+        description: "entries history backfilled from usage_events",
+        apply: migrate_entries,
+    },
+    Migration {
+        version: 11,
+        description: "daily_totals aggregates",
+        apply: migrate_totals,
+    },
+    Migration {
+        version: 12,
+        description: "usage_snapshots history of OAuth utilization",
+        apply: migrate_usage_snapshots,
+    },
+    Migration {
+        version: 13,
+        description: "render_timings ring buffer of per-phase render durations",
+        apply: migrate_render_timings,
+    },
+    Migration {
+        version: 14,
+        description: "usage_snapshots.account",
+        apply: migrate_usage_snapshots_account,
+    },
+];
+
+/// Schema version a database is at: the lower of `PRAGMA user_version` and the
+/// `schema_version` metadata row, so a database where either lags is brought
+/// forward. Fails when either is newer than this build understands.
+fn current_schema_version(conn: &Connection) -> Result<i64> {
+    let user_version = sqlite_user_version(conn)?;
+    // Read the value alone: legacy metadata tables predate updated_at
+    let metadata_version = if table_exists(conn, "metadata")? {
+        conn.query_row(
+            sql::GET_METADATA_VALUE,
+            params![METADATA_KEY_SCHEMA_VERSION],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?
+    } else {
+        None
+    };
+    let metadata_version = match metadata_version {
+        Some(value) => value
+            .parse::<i64>()
+            .with_context(|| format!("invalid schema_version metadata {value:?}"))?,
+        None => 0,
+    };
+    let newest = user_version.max(metadata_version);
+    if newest > SCHEMA_VERSION {
+        bail!(
+            "SQLite schema version {} is newer than supported version {}",
+            newest,
+            SCHEMA_VERSION
+        );
+    }
+    Ok(user_version.min(metadata_version))
+}
+
+/// Migrations a database at `version` still needs.
+fn pending_migrations(version: i64) -> impl Iterator<Item = &'static Migration> {
+    MIGRATIONS.iter().filter(move |m| m.version > version)
+}
+
+/// Apply pending migrations, each in its own transaction that also records the
+/// new version. Returns the versions applied.
+fn migrate_schema(conn: &Connection) -> Result<Vec<i64>> {
+    let version = current_schema_version(conn)?;
+    let mut applied = Vec::new();
+    for migration in pending_migrations(version) {
+        run_schema_change(conn, |conn| {
+            (migration.apply)(conn).with_context(|| {
+                format!(
+                    "schema migration {} ({}) failed",
+                    migration.version, migration.description
+                )
+            })?;
+            set_metadata(
+                conn,
+                METADATA_KEY_SCHEMA_VERSION,
+                &migration.version.to_string(),
+            )?;
+            conn.pragma_update(None, "user_version", migration.version)?;
+            Ok(())
+        })?;
+        applied.push(migration.version);
+    }
+
+    create_session_indexes(conn)?;
+    create_usage_events_schema(conn)?;
+
+    if !applied.is_empty() {
+        clear_global_sum_cache(conn)?;
+    }
+
+    Ok(applied)
+}
+
+/// Bring the database to the current schema, or with `dry_run` only list the
+/// migrations that would run.
+pub fn migrate(dry_run: bool) -> Result<MigrationReport> {
+    let path = get_db_path()?;
+    let from_version = if path.exists() {
+        let conn = Connection::open_with_flags(&path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        #[cfg(feature = "encrypted-db")]
+        keychain::apply_key(&conn)?;
+        current_schema_version(&conn)?
+    } else {
+        0
+    };
+    let migrations = pending_migrations(from_version)
+        .map(|m| MigrationStep {
+            version: m.version,
+            description: m.description,
+        })
+        .collect();
+    if !dry_run {
+        open_db()?;
+    }
+    Ok(MigrationReport {
+        path: path.display().to_string(),
+        from_version,
+        to_version: SCHEMA_VERSION,
+        dry_run,
+        migrations,
+    })
+}
+
+fn migrate_metadata_updated_at(conn: &Connection) -> Result<()> {
+    if !table_has_column(conn, "metadata", "updated_at")? {
+        conn.execute(sql::ADD_METADATA_UPDATED_AT, [])?;
     }
-
     Ok(())
 }
 
@@ -619,14 +1873,96 @@ fn migrate_sessions_session_id(conn: &Connection) -> Result<()> {
 }
 
 fn migrate_sessions_session_id_primary_key(conn: &Connection) -> Result<()> {
-    run_schema_change(conn, |conn| {
-        conn.execute(sql::DROP_SESSIONS_V3, [])?;
-        conn.execute(sql::CREATE_SESSIONS_V3, [])?;
-        conn.execute(sql::COPY_SESSIONS_V3, [])?;
-        conn.execute(sql::DROP_SESSIONS, [])?;
-        conn.execute(sql::RENAME_SESSIONS_V3, [])?;
-        Ok(())
-    })
+    if table_column_is_primary_key(conn, "sessions", "session_id")? {
+        return Ok(());
+    }
+    conn.execute(sql::DROP_SESSIONS_V3, [])?;
+    conn.execute(sql::CREATE_SESSIONS_V3, [])?;
+    conn.execute(sql::COPY_SESSIONS_V3, [])?;
+    conn.execute(sql::DROP_SESSIONS, [])?;
+    conn.execute(sql::RENAME_SESSIONS_V3, [])?;
+    Ok(())
+}
+
+fn migrate_usage_events(conn: &Connection) -> Result<()> {
+    create_usage_events_schema(conn)?;
+    conn.execute(sql::BACKFILL_USAGE_EVENTS_FROM_SESSIONS, [])?;
+    Ok(())
+}
+
+fn migrate_entries(conn: &Connection) -> Result<()> {
+    conn.execute_batch(sql::CREATE_ENTRIES)?;
+    let now = Utc::now().timestamp();
+    let mut select = conn.prepare(sql::SELECT_USAGE_EVENTS_FOR_ENTRIES)?;
+    let mut insert = conn.prepare(sql::UPSERT_ENTRY)?;
+    let mut rows = select.query([])?;
+    while let Some(row) = rows.next()? {
+        let transcript_path: String = row.get(4)?;
+        insert.execute(params![
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            project_from_transcript_path(Path::new(&transcript_path)),
+            row.get::<_, Option<String>>(5)?,
+            row.get::<_, i64>(6)?,
+            row.get::<_, i64>(7)?,
+            row.get::<_, i64>(8)?,
+            row.get::<_, i64>(9)?,
+            row.get::<_, i64>(10)?,
+            row.get::<_, f64>(11)?,
+            now
+        ])?;
+    }
+    Ok(())
+}
+
+fn migrate_usage_snapshots(conn: &Connection) -> Result<()> {
+    conn.execute_batch(sql::CREATE_USAGE_SNAPSHOTS)?;
+    Ok(())
+}
+
+/// Rebuild `usage_snapshots` keyed by account as well, since two accounts can
+/// be fetched in the same second. Existing rows predate `[accounts]` scoping
+/// and keep the empty label.
+fn migrate_usage_snapshots_account(conn: &Connection) -> Result<()> {
+    if table_columns(conn, "usage_snapshots")?
+        .iter()
+        .any(|c| c == "account")
+    {
+        return Ok(());
+    }
+    conn.execute(sql::DROP_USAGE_SNAPSHOTS_V14, [])?;
+    conn.execute(sql::CREATE_USAGE_SNAPSHOTS_V14, [])?;
+    conn.execute(sql::COPY_USAGE_SNAPSHOTS_V14, [])?;
+    conn.execute(sql::DROP_USAGE_SNAPSHOTS, [])?;
+    conn.execute(sql::RENAME_USAGE_SNAPSHOTS_V14, [])?;
+    Ok(())
+}
+
+fn migrate_render_timings(conn: &Connection) -> Result<()> {
+    conn.execute_batch(sql::CREATE_RENDER_TIMINGS)?;
+    Ok(())
+}
+
+fn migrate_totals(conn: &Connection) -> Result<()> {
+    conn.execute_batch(sql::CREATE_TOTALS)?;
+    let mut stmt = conn.prepare(sql::SELECT_ENTRY_DATES)?;
+    let dates = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for date in dates {
+        refresh_totals(conn, &date)?;
+    }
+    Ok(())
+}
+
+fn migrate_session_costs_transcript_path(conn: &Connection) -> Result<()> {
+    conn.execute(sql::CREATE_SESSION_COSTS, [])?;
+    if !table_has_column(conn, "session_costs", "transcript_path")? {
+        conn.execute(sql::ADD_SESSION_COSTS_TRANSCRIPT_PATH, [])?;
+    }
+    Ok(())
 }
 
 fn create_session_indexes(conn: &Connection) -> Result<()> {
@@ -643,16 +1979,25 @@ fn create_usage_events_schema(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
-fn run_schema_change(
+fn run_schema_change<T>(
     conn: &Connection,
-    change: impl FnOnce(&Connection) -> Result<()>,
-) -> Result<()> {
+    change: impl FnOnce(&Connection) -> Result<T>,
+) -> Result<T> {
+    in_write_transaction(conn, change)
+}
+
+/// Run `write` holding the db write lock (`BEGIN IMMEDIATE`), so concurrent
+/// writers queue behind it instead of interleaving; rolled back on error.
+fn in_write_transaction<T>(
+    conn: &Connection,
+    write: impl FnOnce(&Connection) -> Result<T>,
+) -> Result<T> {
     conn.execute_batch("BEGIN IMMEDIATE")?;
-    let result = change(conn);
+    let result = write(conn);
     match result {
-        Ok(()) => {
+        Ok(value) => {
             conn.execute_batch("COMMIT")?;
-            Ok(())
+            Ok(value)
         }
         Err(err) => {
             let _ = conn.execute_batch("ROLLBACK");
@@ -666,6 +2011,23 @@ fn sqlite_user_version(conn: &Connection) -> Result<i64> {
         .map_err(Into::into)
 }
 
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        params![table],
+        |row| row.get(0),
+    )
+    .map_err(Into::into)
+}
+
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    debug_assert!(table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    stmt.query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(Into::into)
+}
+
 fn table_has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
     debug_assert!(table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
     let sql = format!("PRAGMA table_info({table})");
@@ -695,46 +2057,559 @@ fn table_column_is_primary_key(conn: &Connection, table: &str, column: &str) ->
     Ok(false)
 }
 
-fn clear_global_sum_cache(conn: &Connection) -> Result<usize> {
-    conn.execute(sql::DELETE_GLOBAL_SUM_CACHE, [])
-        .map_err(Into::into)
+/// Record today's global and per-session totals in the per-day ledgers and
+/// prune rows past retention.
+///
+/// `sessions`/`usage_events` only hold today, so these ledgers are what week-
+/// and month-level totals and cross-session statistics are computed from.
+fn record_daily_cost(conn: &Connection, today: NaiveDate, cost: f64) -> Result<()> {
+    let date = today.format("%Y-%m-%d").to_string();
+    let now = crate::utils::now_utc().timestamp();
+    conn.execute(sql::UPSERT_DAILY_COST, params![date, cost.max(0.0), now])?;
+    conn.execute(sql::UPSERT_SESSION_COSTS_FOR_DATE, params![now, date])?;
+    conn.execute(sql::UPSERT_MODEL_COSTS_FOR_DATE, params![now, date])?;
+    in_write_transaction(conn, |conn| refresh_totals(conn, &date))?;
+    // Fold finished days into the lifetime totals before they can be pruned.
+    fold_lifetime_totals(conn, today)?;
+    let cutoff = (today - chrono::Duration::days(DAILY_COST_RETENTION_DAYS))
+        .format("%Y-%m-%d")
+        .to_string();
+    conn.execute(sql::DELETE_EXPIRED_DAILY_COSTS, params![cutoff])?;
+    conn.execute(sql::DELETE_EXPIRED_SESSION_COSTS, params![cutoff])?;
+    conn.execute(sql::DELETE_EXPIRED_MODEL_COSTS, params![cutoff])?;
+    conn.execute(sql::DELETE_EXPIRED_DAILY_TOTALS, params![cutoff])?;
+    Ok(())
 }
 
-fn global_sum_cache_key(today: &str) -> String {
-    format!("{GLOBAL_SUM_CACHE_PREFIX}{today}")
+/// Rebuild the per-project/model aggregates for `date` (`YYYY-MM-DD`) from
+/// `entries`. Callers outside a migration run this in a transaction so
+/// concurrent refreshes of the same day don't interleave.
+fn refresh_totals(conn: &Connection, date: &str) -> Result<()> {
+    let now = crate::utils::now_utc().timestamp();
+    conn.execute(sql::DELETE_DAILY_TOTALS_FOR_DATE, params![date])?;
+    conn.execute(sql::INSERT_DAILY_TOTALS_FOR_DATE, params![now, date])?;
+    Ok(())
 }
 
-fn encode_global_sum_cache(total_cost: f64, sessions_count: usize) -> String {
-    format!("{total_cost}:{sessions_count}")
+/// Load the last `days` local days of the daily and per-session ledgers.
+pub fn load_cost_history(days: i64) -> Result<CostHistory> {
+    let conn = open_db()?;
+    cost_history(&conn, days)
 }
 
-fn decode_global_sum_cache(value: &str) -> Option<GlobalTodayRow> {
-    let (sum_str, count_str) = value.split_once(':')?;
-    Some(GlobalTodayRow {
-        total_cost: sum_str.parse().ok()?,
-        sessions_count: count_str.parse().ok()?,
-    })
+/// [`load_cost_history`] and [`load_lifetime_stats`] over one connection, for
+/// renders that report both.
+pub fn load_history_and_lifetime(days: i64) -> Result<(CostHistory, LifetimeStats)> {
+    let conn = open_db()?;
+    Ok((cost_history(&conn, days)?, lifetime_stats(&conn)?))
 }
 
-/// Fetch metadata value and optional timestamp
-pub fn get_metadata(conn: &Connection, key: &str) -> Result<Option<MetadataEntry>> {
-    let mut stmt = conn.prepare(sql::GET_METADATA)?;
-    let result = stmt
-        .query_row(params![key], MetadataEntry::from_row)
-        .optional()?;
-    Ok(result)
+fn cost_history(conn: &Connection, days: i64) -> Result<CostHistory> {
+    let today = crate::utils::now_local().date_naive();
+    let start = today - chrono::Duration::days(days.max(1) - 1);
+    let daily = daily_costs_between(conn, start, today)?;
+    let mut stmt = conn.prepare(sql::SELECT_SESSION_TOTALS_SINCE)?;
+    let session_costs = stmt
+        .query_map(params![start.format("%Y-%m-%d").to_string()], |row| {
+            row.get::<_, f64>(0)
+        })?
+        .collect::<rusqlite::Result<Vec<f64>>>()?;
+    Ok(CostHistory {
+        daily,
+        session_costs,
+    })
 }
 
-/// Set metadata value with current timestamp
-pub fn set_metadata(conn: &Connection, key: &str, value: &str) -> Result<()> {
-    let now = Utc::now().timestamp();
-    conn.execute(sql::SET_METADATA, params![key, value, now])?;
-    Ok(())
+impl LifetimeStats {
+    /// Fold one recorded day's global cost into the totals.
+    fn add_day(&mut self, day: DailyCost) {
+        if day.cost <= 0.0 {
+            return;
+        }
+        self.first_seen = Some(self.first_seen.map_or(day.date, |d| d.min(day.date)));
+        self.cost += day.cost;
+        self.active_days += 1;
+        if self.busiest_day.is_none_or(|b| day.cost > b.cost) {
+            self.busiest_day = Some(day);
+        }
+    }
 }
 
-/// Insert or update session entry
-///
-/// Uses UPSERT to handle both new sessions and updates to existing ones.
+/// Persisted lifetime totals and the last day folded into them.
+fn read_lifetime_totals(conn: &Connection) -> Result<(LifetimeStats, Option<String>)> {
+    let parse_date =
+        |d: Option<String>| d.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+    let row = conn
+        .query_row(sql::SELECT_LIFETIME_TOTALS, [], |row| {
+            let busiest_date: Option<String> = row.get(8)?;
+            let busiest_cost: f64 = row.get(9)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                LifetimeStats {
+                    first_seen: parse_date(row.get(1)?),
+                    cost: row.get(2)?,
+                    input_tokens: row.get::<_, i64>(3)?.max(0) as u64,
+                    output_tokens: row.get::<_, i64>(4)?.max(0) as u64,
+                    cache_create_tokens: row.get::<_, i64>(5)?.max(0) as u64,
+                    cache_read_tokens: row.get::<_, i64>(6)?.max(0) as u64,
+                    active_days: row.get::<_, i64>(7)?.max(0) as u32,
+                    busiest_day: parse_date(busiest_date).map(|date| DailyCost {
+                        date,
+                        cost: busiest_cost,
+                    }),
+                },
+            ))
+        })
+        .optional()?;
+    Ok(match row {
+        Some((through, stats)) => (stats, Some(through)),
+        None => (LifetimeStats::default(), None),
+    })
+}
+
+/// Add ledger days strictly between `after` and `before` (`YYYY-MM-DD`) to `stats`.
+fn add_ledger_days(
+    conn: &Connection,
+    stats: &mut LifetimeStats,
+    after: &str,
+    before: &str,
+) -> Result<usize> {
+    let mut stmt = conn.prepare(sql::SELECT_DAILY_COSTS_IN_WINDOW)?;
+    let rows = stmt.query_map(params![after, before], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+    })?;
+    let mut days = 0;
+    for row in rows {
+        let (date, cost) = row?;
+        if let Ok(date) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            stats.add_day(DailyCost { date, cost });
+            days += 1;
+        }
+    }
+    let (input, output, cache_create, cache_read) = conn.query_row(
+        sql::SELECT_DAILY_TOKENS_IN_WINDOW,
+        params![after, before],
+        |row| {
+            let tokens = |i: usize| -> rusqlite::Result<u64> {
+                Ok(row.get::<_, Option<i64>>(i)?.unwrap_or(0).max(0) as u64)
+            };
+            Ok((tokens(0)?, tokens(1)?, tokens(2)?, tokens(3)?))
+        },
+    )?;
+    stats.input_tokens += input;
+    stats.output_tokens += output;
+    stats.cache_create_tokens += cache_create;
+    stats.cache_read_tokens += cache_read;
+    Ok(days)
+}
+
+/// Fold every ledger day before `today` not yet counted into `lifetime_totals`.
+/// Each day is folded exactly once, after it can no longer change.
+fn fold_lifetime_totals(conn: &Connection, today: NaiveDate) -> Result<()> {
+    let (mut stats, through) = read_lifetime_totals(conn)?;
+    let yesterday = (today - chrono::Duration::days(1))
+        .format("%Y-%m-%d")
+        .to_string();
+    let after = through.unwrap_or_default();
+    if after >= yesterday {
+        return Ok(());
+    }
+    let today_str = today.format("%Y-%m-%d").to_string();
+    add_ledger_days(conn, &mut stats, &after, &today_str)?;
+    let date_str = |d: NaiveDate| d.format("%Y-%m-%d").to_string();
+    conn.execute(
+        sql::UPSERT_LIFETIME_TOTALS,
+        params![
+            yesterday,
+            stats.first_seen.map(date_str),
+            stats.cost,
+            stats.input_tokens as i64,
+            stats.output_tokens as i64,
+            stats.cache_create_tokens as i64,
+            stats.cache_read_tokens as i64,
+            i64::from(stats.active_days),
+            stats.busiest_day.map(|d| date_str(d.date)),
+            stats.busiest_day.map_or(0.0, |d| d.cost),
+            Utc::now().timestamp(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Lifetime totals: every folded day plus the ledger days since, including
+/// today's running total.
+pub fn load_lifetime_stats() -> Result<LifetimeStats> {
+    let conn = open_db()?;
+    lifetime_stats(&conn)
+}
+
+fn lifetime_stats(conn: &Connection) -> Result<LifetimeStats> {
+    let (mut stats, through) = read_lifetime_totals(conn)?;
+    add_ledger_days(conn, &mut stats, &through.unwrap_or_default(), "9999-12-31")?;
+    Ok(stats)
+}
+
+/// One deduplicated usage row from the `entries` history. Unlike
+/// `usage_events`, entries outlive the day and the transcript they came from.
+#[cfg(test)]
+#[derive(Debug)]
+struct UsageEntry {
+    session_id: String,
+    project: String,
+    model: Option<String>,
+    input_tokens: u64,
+    cost: f64,
+}
+
+/// Recorded usage entries over the inclusive local date range `[from, to]`, oldest first.
+#[cfg(test)]
+fn entries_between(conn: &Connection, from: NaiveDate, to: NaiveDate) -> Result<Vec<UsageEntry>> {
+    let mut stmt = conn.prepare(sql::SELECT_ENTRIES_BETWEEN)?;
+    let rows = stmt.query_map(params![from.to_string(), to.to_string()], |row| {
+        Ok(UsageEntry {
+            session_id: row.get(0)?,
+            project: row.get(1)?,
+            model: row.get(2)?,
+            input_tokens: row.get::<_, i64>(3)?.max(0) as u64,
+            cost: row.get(4)?,
+        })
+    })?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(Into::into)
+}
+
+/// Keep a fetched utilization reading; the live API only reports the present.
+pub fn record_usage_snapshot(snapshot: &UsageSnapshot) -> Result<()> {
+    let conn = open_db()?;
+    conn.execute(
+        sql::INSERT_USAGE_SNAPSHOT,
+        params![
+            snapshot.account,
+            snapshot.fetched_at,
+            snapshot.five_hour,
+            snapshot.five_hour_resets_at,
+            snapshot.seven_day,
+            snapshot.seven_day_resets_at,
+            snapshot.seven_day_opus,
+            snapshot.seven_day_sonnet
+        ],
+    )?;
+    Ok(())
+}
+
+/// `account`'s recorded snapshots fetched at or after `since` (unix seconds),
+/// oldest first; `""` selects readings taken without `[accounts]`.
+pub fn load_usage_snapshots(account: &str, since: i64) -> Result<Vec<UsageSnapshot>> {
+    let conn = open_db()?;
+    let mut stmt = conn.prepare(sql::SELECT_USAGE_SNAPSHOTS_SINCE)?;
+    let rows = stmt.query_map(params![account, since], |row| {
+        Ok(UsageSnapshot {
+            account: row.get(0)?,
+            fetched_at: row.get(1)?,
+            five_hour: row.get(2)?,
+            five_hour_resets_at: row.get(3)?,
+            seven_day: row.get(4)?,
+            seven_day_resets_at: row.get(5)?,
+            seven_day_opus: row.get(6)?,
+            seven_day_sonnet: row.get(7)?,
+        })
+    })?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(Into::into)
+}
+
+/// Keep one render's phase durations, dropping all but the newest
+/// `RENDER_TIMINGS_KEPT` rows.
+pub fn record_render_timing(timing: &RenderTiming) -> Result<()> {
+    let conn = open_db()?;
+    conn.execute(
+        sql::INSERT_RENDER_TIMING,
+        params![
+            timing.recorded_at,
+            timing.version,
+            timing.scan_ms,
+            timing.db_ms,
+            timing.git_ms,
+            timing.api_ms,
+            timing.render_ms,
+            timing.total_ms
+        ],
+    )?;
+    conn.execute(sql::TRIM_RENDER_TIMINGS, params![RENDER_TIMINGS_KEPT])?;
+    Ok(())
+}
+
+/// Recorded render timings, oldest first.
+pub fn load_render_timings() -> Result<Vec<RenderTiming>> {
+    let conn = open_db()?;
+    let mut stmt = conn.prepare(sql::SELECT_RENDER_TIMINGS)?;
+    let rows = stmt.query_map([], |row| {
+        Ok(RenderTiming {
+            recorded_at: row.get(0)?,
+            version: row.get(1)?,
+            scan_ms: row.get(2)?,
+            db_ms: row.get(3)?,
+            git_ms: row.get(4)?,
+            api_ms: row.get(5)?,
+            render_ms: row.get(6)?,
+            total_ms: row.get(7)?,
+        })
+    })?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(Into::into)
+}
+
+/// Recorded daily totals over the inclusive local date range `[from, to]`.
+pub fn load_daily_costs(from: NaiveDate, to: NaiveDate) -> Result<Vec<DailyCost>> {
+    let conn = open_db()?;
+    daily_costs_between(&conn, from, to)
+}
+
+/// Read the ledgers of another seat's statusline database (opened read-only),
+/// or of this machine's database when `path` is `None`, over the inclusive
+/// local date range `[from, to]`.
+pub fn load_seat_ledger(path: Option<&Path>, from: NaiveDate, to: NaiveDate) -> Result<SeatLedger> {
+    let conn = match path {
+        Some(path) => {
+            let conn =
+                Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+                    .with_context(|| format!("failed to open seat database {}", path.display()))?;
+            // Seat databases share the team's key
+            #[cfg(feature = "encrypted-db")]
+            keychain::apply_key(&conn)
+                .with_context(|| format!("failed to open seat database {}", path.display()))?;
+            DbConn::Owned(conn)
+        }
+        None => open_db()?,
+    };
+    let daily = daily_costs_between(&conn, from, to)?;
+    // Databases from before the model ledger existed simply report no tokens.
+    let tokens = tokens_by_date_between(&conn, from, to).unwrap_or_default();
+    let usage_summary = conn
+        .query_row(
+            sql::SELECT_API_CACHE_WITH_FETCHED_AT,
+            params![OAUTH_USAGE_SUMMARY_CACHE_KEY],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+        )
+        .optional()
+        .unwrap_or(None);
+    Ok(SeatLedger {
+        daily,
+        tokens,
+        usage_summary,
+    })
+}
+
+fn tokens_by_date_between(
+    conn: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<(NaiveDate, TokenCounts)>> {
+    let mut stmt = conn.prepare(sql::SELECT_TOKENS_BY_DATE_BETWEEN)?;
+    let rows = stmt.query_map(
+        params![
+            from.format("%Y-%m-%d").to_string(),
+            to.format("%Y-%m-%d").to_string()
+        ],
+        |row| {
+            let tokens = |i: usize| -> rusqlite::Result<u64> {
+                Ok(row.get::<_, Option<i64>>(i)?.unwrap_or(0).max(0) as u64)
+            };
+            Ok((
+                row.get::<_, String>(0)?,
+                TokenCounts {
+                    input: tokens(1)?,
+                    output: tokens(2)?,
+                    cache_create: tokens(3)?,
+                    cache_read: tokens(4)?,
+                },
+            ))
+        },
+    )?;
+    let mut days = Vec::new();
+    for row in rows {
+        let (date, tokens) = row?;
+        if let Ok(date) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            days.push((date, tokens));
+        }
+    }
+    Ok(days)
+}
+
+/// Per-model totals over the inclusive local date range `[from, to]`, from the
+/// model ledger.
+pub fn load_model_costs(from: NaiveDate, to: NaiveDate) -> Result<Vec<ModelTotals>> {
+    let conn = open_db()?;
+    model_costs_between(&conn, from, to)
+}
+
+fn model_costs_between(
+    conn: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<ModelTotals>> {
+    let mut stmt = conn.prepare(sql::SELECT_MODEL_COSTS_BETWEEN)?;
+    let rows = stmt.query_map(
+        params![
+            from.format("%Y-%m-%d").to_string(),
+            to.format("%Y-%m-%d").to_string()
+        ],
+        |row| {
+            Ok(ModelTotals {
+                model: row.get(0)?,
+                cost: row.get(1)?,
+                input_tokens: row.get::<_, i64>(2)?.max(0) as u64,
+                output_tokens: row.get::<_, i64>(3)?.max(0) as u64,
+                cache_create_tokens: row.get::<_, i64>(4)?.max(0) as u64,
+                cache_read_tokens: row.get::<_, i64>(5)?.max(0) as u64,
+            })
+        },
+    )?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(Into::into)
+}
+
+/// Per-project spend over the inclusive local date range `[from, to]`, from the
+/// `daily_totals` aggregate and, for days before it was kept, the session
+/// ledger. Session rows recorded before transcript paths were kept are skipped.
+pub fn load_project_costs(from: NaiveDate, to: NaiveDate) -> Result<Vec<(String, f64)>> {
+    let conn = open_db()?;
+    project_costs_between(&conn, from, to)
+}
+
+fn project_costs_between(
+    conn: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<(String, f64)>> {
+    let totals_start = conn
+        .query_row(sql::SELECT_DAILY_TOTALS_START, [], |row| {
+            row.get::<_, Option<String>>(0)
+        })?
+        .and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok())
+        .unwrap_or(to + chrono::Duration::days(1));
+    let mut by_project: HashMap<String, f64> = HashMap::new();
+
+    let ledger_to = to.min(totals_start - chrono::Duration::days(1));
+    if from <= ledger_to {
+        let mut stmt = conn.prepare(sql::SELECT_TRANSCRIPT_COSTS_BETWEEN)?;
+        let rows = stmt.query_map(
+            params![
+                from.format("%Y-%m-%d").to_string(),
+                ledger_to.format("%Y-%m-%d").to_string()
+            ],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)),
+        )?;
+        for row in rows {
+            let (path, cost) = row?;
+            *by_project
+                .entry(project_from_transcript_path(Path::new(&path)))
+                .or_insert(0.0) += cost;
+        }
+    }
+
+    let totals_from = from.max(totals_start);
+    if totals_from <= to {
+        let mut stmt = conn.prepare(sql::SELECT_PROJECT_TOTALS_BETWEEN)?;
+        let rows = stmt.query_map(
+            params![
+                totals_from.format("%Y-%m-%d").to_string(),
+                to.format("%Y-%m-%d").to_string()
+            ],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)),
+        )?;
+        for row in rows {
+            let (project, cost) = row?;
+            *by_project.entry(project).or_insert(0.0) += cost;
+        }
+    }
+    Ok(by_project.into_iter().collect())
+}
+
+/// Project directory name of a transcript (`.../projects/<project>/...`),
+/// matching the name the usage scan attributes entries to.
+fn project_from_transcript_path(path: &Path) -> String {
+    let names: Vec<_> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    // Needs `projects/<project>/<file...>`, not a file directly under `projects/`.
+    if let Some(i) = names.iter().position(|c| c == "projects")
+        && let Some(project) = names.get(i + 2).and(names.get(i + 1))
+    {
+        return project.to_string();
+    }
+    path.parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "(unknown)".to_string())
+}
+
+/// Recorded daily totals over the inclusive local date range `[from, to]`, oldest first.
+/// Days without activity have no row.
+fn daily_costs_between(
+    conn: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<DailyCost>> {
+    let mut stmt = conn.prepare(sql::SELECT_DAILY_COSTS)?;
+    let rows = stmt.query_map(
+        params![
+            from.format("%Y-%m-%d").to_string(),
+            to.format("%Y-%m-%d").to_string()
+        ],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)),
+    )?;
+    let mut days = Vec::new();
+    for row in rows {
+        let (date, cost) = row?;
+        if let Ok(date) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            days.push(DailyCost { date, cost });
+        }
+    }
+    Ok(days)
+}
+
+fn clear_global_sum_cache(conn: &Connection) -> Result<usize> {
+    conn.execute(sql::DELETE_GLOBAL_SUM_CACHE, [])
+        .map_err(Into::into)
+}
+
+fn global_sum_cache_key(today: &str) -> String {
+    format!("{GLOBAL_SUM_CACHE_PREFIX}{today}")
+}
+
+fn encode_global_sum_cache(total_cost: f64, sessions_count: usize) -> String {
+    format!("{total_cost}:{sessions_count}")
+}
+
+fn decode_global_sum_cache(value: &str) -> Option<GlobalTodayRow> {
+    let (sum_str, count_str) = value.split_once(':')?;
+    Some(GlobalTodayRow {
+        total_cost: sum_str.parse().ok()?,
+        sessions_count: count_str.parse().ok()?,
+    })
+}
+
+/// Fetch metadata value and optional timestamp
+pub fn get_metadata(conn: &Connection, key: &str) -> Result<Option<MetadataEntry>> {
+    let mut stmt = conn.prepare(sql::GET_METADATA)?;
+    let result = stmt
+        .query_row(params![key], MetadataEntry::from_row)
+        .optional()?;
+    Ok(result)
+}
+
+/// Set metadata value with current timestamp
+pub fn set_metadata(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    let now = Utc::now().timestamp();
+    conn.execute(sql::SET_METADATA, params![key, value, now])?;
+    Ok(())
+}
+
+/// Insert or update session entry
+///
+/// Uses UPSERT to handle both new sessions and updates to existing ones.
 fn upsert_session(
     conn: &Connection,
     session_key: &str,
@@ -871,7 +2746,7 @@ fn usage_event_from_entry(
         entry.output,
         entry.cache_create,
         entry.cache_read,
-        entry.web_search_requests,
+        entry.server_tools.requests("web_search"),
         entry.agent_id.as_deref().unwrap_or_default()
     );
 
@@ -886,7 +2761,7 @@ fn usage_event_from_entry(
         output_tokens: entry.output,
         cache_create_tokens: entry.cache_create,
         cache_read_tokens: entry.cache_read,
-        web_search_requests: entry.web_search_requests,
+        web_search_requests: entry.server_tools.requests("web_search"),
         cost: entry.cost.max(0.0),
         source: "scan_entry",
     })
@@ -996,9 +2871,29 @@ fn replace_usage_events_for_session_date_in_transaction(
         sql::DELETE_USAGE_EVENTS_FOR_SESSION_DATE,
         params![session_id, today],
     )?;
+    conn.execute(
+        sql::DELETE_ENTRIES_FOR_SESSION_DATE,
+        params![session_id, today],
+    )?;
     let now = Utc::now().timestamp();
     let mut stmt = conn.prepare(sql::UPSERT_USAGE_EVENT)?;
+    let mut entry_stmt = conn.prepare(sql::UPSERT_ENTRY)?;
     for event in events {
+        entry_stmt.execute(params![
+            &event.event_key,
+            event.ts,
+            &event.today_date,
+            &event.session_id,
+            project_from_transcript_path(Path::new(&event.transcript_path)),
+            event.model.as_deref(),
+            i64_from_u64(event.input_tokens),
+            i64_from_u64(event.output_tokens),
+            i64_from_u64(event.cache_create_tokens),
+            i64_from_u64(event.cache_read_tokens),
+            i64_from_u64(event.web_search_requests),
+            event.cost,
+            now
+        ])?;
         stmt.execute(params![
             &event.event_key,
             &event.session_id,
@@ -1134,6 +3029,7 @@ fn parse_transcript_today_events(
         .to_str()
         .context("Invalid transcript path")?;
 
+    let project = project_from_transcript_path(transcript_path);
     let mut aggregated_events: HashMap<String, UsageEvent> = HashMap::new();
     let mut last_seen_raw: HashMap<String, (u64, u64, u64, u64)> = HashMap::new();
     let mut force_delta_mode: HashMap<String, bool> = HashMap::new();
@@ -1187,6 +3083,22 @@ fn parse_transcript_today_events(
                 .as_f64()
                 .or_else(|| cost_val.as_str().and_then(|s| s.parse::<f64>().ok()))
         {
+            let free = v
+                .get("model")
+                .or_else(|| message.and_then(|m| m.get("model")))
+                .and_then(|m| m.as_str())
+                .is_some_and(crate::pricing::is_free_model);
+            let cost = if free {
+                0.0
+            } else {
+                let tokens = message
+                    .and_then(|m| m.get("usage"))
+                    .map_or(0, crate::pricing::usage_tokens);
+                crate::pricing::apply_proxy_surcharge(
+                    cost * crate::pricing::project_cost_multiplier(Some(&project)),
+                    tokens,
+                )
+            };
             let agg_key = if let Some(ref r) = rid {
                 format!("R:{}", r)
             } else if let Some(ref m) = mid {
@@ -1253,7 +3165,8 @@ fn parse_transcript_today_events(
                 .or_else(|| message.get("model"))
                 .and_then(|m| m.as_str())
                 .unwrap_or("claude-sonnet-4-6");
-            let cost = crate::pricing::calculate_cost_for_usage_with_speed(model_id, usage, speed);
+            let cost =
+                crate::pricing::calculate_project_cost(Some(&project), model_id, usage, speed);
 
             if cost > 0.0
                 || input > 0
@@ -1432,7 +3345,42 @@ fn session_usage_for_today(
 ///
 /// If `session_today_cost` is provided, it will be used instead of re-parsing
 /// the transcript file (optimization to avoid double-parsing).
+///
+/// A corrupted database (common after crashes on network home directories)
+/// is moved aside and rebuilt once, rather than failing every render.
 pub fn get_global_usage(
+    session_id: &str,
+    project_dir: &str,
+    transcript_path: &Path,
+    session_today_cost: Option<f64>,
+    session_entries: Option<&[Entry]>,
+) -> Result<GlobalUsage> {
+    let usage = || {
+        global_usage(
+            session_id,
+            project_dir,
+            transcript_path,
+            session_today_cost,
+            session_entries,
+        )
+    };
+    match usage() {
+        // Only once the integrity check agrees; one that cannot even open the
+        // file (say, after a key failure) leaves it in place
+        Err(err) if is_corruption(&err) && check().is_ok_and(|report| !report.ok) => {
+            if let Some(moved) = recover_corrupt_db()? {
+                eprintln!(
+                    "claude-statusline: database was corrupt; moved it to {} and started a new one",
+                    moved.display()
+                );
+            }
+            usage()
+        }
+        result => result,
+    }
+}
+
+fn global_usage(
     session_id: &str,
     _project_dir: &str,
     transcript_path: &Path,
@@ -1443,7 +3391,8 @@ pub fn get_global_usage(
     // No env-var check here; the caller is the single point of truth.
     let conn = open_db()?;
     let session_key = session_id.to_string();
-    let today = Local::now().format("%Y-%m-%d").to_string();
+    let today_date = crate::utils::now_local().date_naive();
+    let today = today_date.format("%Y-%m-%d").to_string();
 
     let metadata = fs::metadata(transcript_path)?;
     let current_mtime = metadata
@@ -1561,16 +3510,15 @@ pub fn get_global_usage(
         db_was_modified = true;
     }
 
-    // Check cache for global sum (5s TTL to reduce redundant SUM queries across concurrent sessions)
+    // Check cache for global sum (short TTL to reduce redundant SUM queries across concurrent sessions)
     // Skip cache if we just modified the DB (invalidates cache)
     let cache_key = global_sum_cache_key(&today);
     let now = Utc::now().timestamp();
-    let cached_sum: Option<GlobalTodayRow> = if !db_was_modified {
+    let cached_sum: Option<(GlobalTodayRow, i64)> = if !db_was_modified {
         if let Ok(Some(entry)) = get_metadata(&conn, &cache_key) {
             if let Some(updated_at) = entry.updated_at {
-                if now - updated_at < GLOBAL_SUM_CACHE_TTL_SECONDS {
-                    // Cache is fresh (< 5 seconds old)
-                    decode_global_sum_cache(&entry.value)
+                if now - updated_at < cache_ttls().global_sum_seconds {
+                    decode_global_sum_cache(&entry.value).map(|row| (row, now - updated_at))
                 } else {
                     None
                 }
@@ -1584,11 +3532,13 @@ pub fn get_global_usage(
         None // DB was modified, so cache is invalid
     };
 
-    let (global_today, sessions_count) = if let Some(cached_sum) = cached_sum {
+    let (global_today, sessions_count) = if let Some((cached_sum, age)) = cached_sum {
         // Use cached value
+        record_cache("global_sum", CacheLayer::Sqlite, Some(age));
         (cached_sum.total_cost, cached_sum.sessions_count)
     } else {
         // Cache miss or expired - run the query
+        record_cache("global_sum", CacheLayer::FreshQuery, None);
         let row = conn.query_row(
             sql::SELECT_GLOBAL_TODAY,
             params![today],
@@ -1602,10 +3552,15 @@ pub fn get_global_usage(
         (row.total_cost, row.sessions_count)
     };
 
+    record_daily_cost(&conn, today_date, global_today)?;
+    let history_start = today_date - chrono::Duration::days(DAILY_HISTORY_DAYS - 1);
+    let daily_history = daily_costs_between(&conn, history_start, today_date)?;
+
     Ok(GlobalUsage {
         session_cost: current_session_cost,
         global_today,
         sessions_count,
+        daily_history,
     })
 }
 
@@ -1613,12 +3568,17 @@ pub fn get_global_usage(
 ///
 /// Returns cached data if it exists and hasn't expired.
 pub fn get_api_cache(cache_key: &str) -> Result<Option<String>> {
+    Ok(get_api_cache_entry(cache_key)?.map(|(data, _)| data))
+}
+
+/// Like [`get_api_cache`], with the unix time the data was fetched.
+pub fn get_api_cache_entry(cache_key: &str) -> Result<Option<(String, i64)>> {
     let conn = open_db()?;
     let now = Utc::now().timestamp();
 
     let result = conn
         .query_row(sql::GET_FRESH_API_CACHE, params![cache_key, now], |row| {
-            row.get::<_, String>(0)
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
         })
         .optional()?;
 
@@ -1627,11 +3587,16 @@ pub fn get_api_cache(cache_key: &str) -> Result<Option<String>> {
 
 /// Get cached API response, ignoring expiration (for stale fallback)
 pub fn get_stale_api_cache(cache_key: &str) -> Result<Option<String>> {
+    Ok(get_stale_api_cache_entry(cache_key)?.map(|(data, _)| data))
+}
+
+/// Like [`get_stale_api_cache`], with the unix time the data was fetched.
+pub fn get_stale_api_cache_entry(cache_key: &str) -> Result<Option<(String, i64)>> {
     let conn = open_db()?;
 
     let result = conn
         .query_row(sql::GET_STALE_API_CACHE, params![cache_key], |row| {
-            row.get::<_, String>(0)
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
         })
         .optional()?;
 
@@ -1673,10 +3638,15 @@ pub fn set_api_cache(cache_key: &str, data: &str, ttl_seconds: i64) -> Result<()
         params![cache_key, data, now, expires_at],
     )?;
 
-    // Clean up expired entries, but keep the main usage cache for stale fallback
+    // Clean up entries past their stale fallback, but keep the OAuth usage
+    // and profile caches for good
     conn.execute(
         sql::DELETE_EXPIRED_API_CACHE,
-        params![now, OAUTH_USAGE_SUMMARY_CACHE_KEY],
+        params![
+            now - API_CACHE_STALE_KEEP_SECONDS,
+            OAUTH_USAGE_SUMMARY_CACHE_KEY,
+            OAUTH_PROFILE_CACHE_KEY
+        ],
     )?;
 
     Ok(())
@@ -1687,6 +3657,15 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    /// Raw connection for hand-built legacy databases, keyed like `open_db`
+    /// in encrypted builds.
+    fn open_fixture(path: &Path) -> Connection {
+        let conn = Connection::open(path).unwrap();
+        #[cfg(feature = "encrypted-db")]
+        keychain::apply_key(&conn).unwrap();
+        conn
+    }
+
     #[test]
     #[serial_test::serial]
     fn test_db_init() {
@@ -1704,7 +3683,7 @@ mod tests {
             )
             .unwrap();
 
-        assert_eq!(version, SCHEMA_VERSION_STR);
+        assert_eq!(version, SCHEMA_VERSION.to_string());
         assert_eq!(sqlite_user_version(&conn).unwrap(), SCHEMA_VERSION);
         assert!(table_has_column(&conn, "sessions", "session_id").unwrap());
         assert!(table_column_is_primary_key(&conn, "sessions", "session_id").unwrap());
@@ -1717,7 +3696,7 @@ mod tests {
     fn test_schema_migration_backfills_session_id() {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("legacy.db");
-        let legacy_conn = Connection::open(&db_path).unwrap();
+        let legacy_conn = open_fixture(&db_path);
         legacy_conn
             .execute_batch(
                 "CREATE TABLE sessions (
@@ -1792,7 +3771,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(session_id, "legacy-session");
-        assert_eq!(schema_version.value, SCHEMA_VERSION_STR);
+        assert_eq!(schema_version.value, SCHEMA_VERSION.to_string());
         assert_eq!(sqlite_user_version(&conn).unwrap(), SCHEMA_VERSION);
         assert!(metadata_has_updated_at);
         assert!(table_column_is_primary_key(&conn, "sessions", "session_id").unwrap());
@@ -1805,7 +3784,7 @@ mod tests {
     fn test_schema_migration_collapses_duplicate_session_rows() {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("duplicate-legacy.db");
-        let legacy_conn = Connection::open(&db_path).unwrap();
+        let legacy_conn = open_fixture(&db_path);
         legacy_conn
             .execute_batch(
                 "CREATE TABLE sessions (
@@ -1930,7 +3909,7 @@ mod tests {
     fn test_missing_usage_cache_version_does_not_delete_sessions() {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("missing-cache-version.db");
-        let legacy_conn = Connection::open(&db_path).unwrap();
+        let legacy_conn = open_fixture(&db_path);
         legacy_conn
             .execute_batch(
                 "CREATE TABLE sessions (
@@ -2257,7 +4236,7 @@ mod tests {
                 output: 200,
                 cache_create: 50,
                 cache_read: 25,
-                web_search_requests: 1,
+                server_tools: crate::models::ServerToolUse::single("web_search", 1),
                 speed: None,
                 service_tier: None,
                 cost: 1.0,
@@ -2274,7 +4253,7 @@ mod tests {
                 output: 100,
                 cache_create: 25,
                 cache_read: 10,
-                web_search_requests: 2,
+                server_tools: crate::models::ServerToolUse::single("web_search", 2),
                 speed: None,
                 service_tier: None,
                 cost: 0.5,
@@ -2291,7 +4270,7 @@ mod tests {
                 output: 1,
                 cache_create: 1,
                 cache_read: 1,
-                web_search_requests: 1,
+                server_tools: crate::models::ServerToolUse::single("web_search", 1),
                 speed: None,
                 service_tier: None,
                 cost: 9.0,
@@ -2686,52 +4665,916 @@ mod tests {
         let expired = get_api_cache("expired_key").unwrap();
         assert_eq!(expired, None);
 
+        // Expired entries stay for stale fallback until the keep period ends,
+        // OAuth entries for good, per account too
+        let long_expired = -API_CACHE_STALE_KEEP_SECONDS - 1;
+        set_api_cache("expired_key", "expired", long_expired).unwrap();
+        set_api_cache("recently_expired_key", "expired", -1).unwrap();
+        set_api_cache("oauth_usage_summary:work", "{}", long_expired).unwrap();
+        set_api_cache(OAUTH_PROFILE_CACHE_KEY, "{}", long_expired).unwrap();
+        set_api_cache("test_key_unique", test_data, 300).unwrap();
+        assert!(
+            get_stale_api_cache_entry("oauth_usage_summary:work")
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            get_stale_api_cache_entry(OAUTH_PROFILE_CACHE_KEY)
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(get_stale_api_cache_entry("expired_key").unwrap(), None);
+        assert!(
+            get_stale_api_cache_entry("recently_expired_key")
+                .unwrap()
+                .is_some()
+        );
+
         unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
     }
 
     #[test]
     #[serial_test::serial]
-    fn test_stale_cleanup() {
+    fn test_daily_cost_ledger_ranges_and_prunes() {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.db");
         // SAFETY: Test runs serially, no concurrent env access
         unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
 
         let conn = open_db().unwrap();
-        let transcript_path = PathBuf::from("/tmp/test.jsonl");
-
-        upsert_session(
-            &conn,
-            "sess1:/path",
-            &transcript_path,
-            12345,
-            "2025-10-17",
-            1.0,
-            10,
-        )
-        .unwrap();
-        upsert_session(
-            &conn,
-            "sess2:/path",
-            &transcript_path,
-            12345,
-            "2025-10-18",
-            2.0,
-            10,
-        )
-        .unwrap();
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
 
-        conn.execute(
-            "DELETE FROM sessions WHERE today_date != ?",
-            params!["2025-10-18"],
-        )
-        .unwrap();
+        record_daily_cost(&conn, day("2024-09-01"), 1.0).unwrap();
+        record_daily_cost(&conn, day("2025-10-06"), 4.0).unwrap();
+        record_daily_cost(&conn, day("2025-10-13"), 2.0).unwrap();
+        // Re-recording a day replaces its total rather than adding to it.
+        record_daily_cost(&conn, day("2025-10-15"), 1.0).unwrap();
+        record_daily_cost(&conn, day("2025-10-15"), 3.5).unwrap();
 
-        let count: i64 = conn
-            .query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))
+        let week: f64 = daily_costs_between(&conn, day("2025-10-13"), day("2025-10-15"))
+            .unwrap()
+            .iter()
+            .map(|d| d.cost)
+            .sum();
+        let pruned: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM daily_costs WHERE date = '2024-09-01'",
+                [],
+                |row| row.get(0),
+            )
             .unwrap();
 
-        assert_eq!(count, 1);
+        let history = daily_costs_between(&conn, day("2025-10-01"), day("2025-10-15")).unwrap();
+        let dates: Vec<NaiveDate> = history.iter().map(|d| d.date).collect();
+
+        assert_eq!(
+            dates,
+            vec![day("2025-10-06"), day("2025-10-13"), day("2025-10-15")]
+        );
+        assert!((week - 5.5).abs() < 1e-10);
+        assert_eq!(pruned, 0);
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_seat_ledger_reads_another_database_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let seat_path = temp_dir.path().join("seat.db");
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", seat_path.to_str().unwrap()) };
+        record_daily_cost(&open_db().unwrap(), day("2025-10-14"), 2.5).unwrap();
+        unsafe {
+            env::set_var(
+                "CLAUDE_STATUSLINE_DB_PATH",
+                temp_dir.path().join("own.db").to_str().unwrap(),
+            )
+        };
+
+        let ledger =
+            load_seat_ledger(Some(&seat_path), day("2025-10-01"), day("2025-10-31")).unwrap();
+        assert_eq!(ledger.daily.len(), 1);
+        assert!((ledger.daily[0].cost - 2.5).abs() < 1e-10);
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_session_cost_ledger_totals_sessions_across_days() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        let conn = open_db().unwrap();
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let insert_event = |key: &str, session: &str, date: &str, cost: f64| {
+            conn.execute(
+                "INSERT INTO usage_events (
+                    event_key, session_id, transcript_path, ts, today_date, model,
+                    input_tokens, output_tokens, cache_create_tokens, cache_read_tokens,
+                    web_search_requests, cost, source, created_at, updated_at
+                )
+                VALUES (?1, ?2, '/tmp/t.jsonl', 0, ?3, NULL, 0, 0, 0, 0, 0, ?4, 'test', 0, 0)",
+                params![key, session, date, cost],
+            )
+            .unwrap();
+        };
+
+        insert_event("a1", "sess-a", "2025-10-14", 1.5);
+        insert_event("a2", "sess-a", "2025-10-14", 0.5);
+        insert_event("b1", "sess-b", "2025-10-14", 4.0);
+        record_daily_cost(&conn, day("2025-10-14"), 6.0).unwrap();
+        // The next day only holds that day's events; yesterday's ledger rows stay.
+        conn.execute("DELETE FROM usage_events", []).unwrap();
+        insert_event("a3", "sess-a", "2025-10-15", 3.0);
+        record_daily_cost(&conn, day("2025-10-15"), 3.0).unwrap();
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT session_id, SUM(cost) FROM session_costs GROUP BY session_id ORDER BY session_id",
+            )
+            .unwrap();
+        let totals: Vec<(String, f64)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+
+        assert_eq!(
+            totals,
+            vec![("sess-a".to_string(), 5.0), ("sess-b".to_string(), 4.0)]
+        );
+
+        let models = model_costs_between(&conn, day("2025-10-14"), day("2025-10-15")).unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].model, "(unknown)");
+        assert!((models[0].cost - 9.0).abs() < 1e-10);
+
+        let mut projects =
+            project_costs_between(&conn, day("2025-10-14"), day("2025-10-15")).unwrap();
+        projects.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(projects, vec![("tmp".to_string(), 9.0)]);
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_lifetime_totals_fold_finished_days_and_survive_pruning() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        let conn = open_db().unwrap();
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        conn.execute(
+            "INSERT INTO usage_events (
+                event_key, session_id, transcript_path, ts, today_date, model,
+                input_tokens, output_tokens, cache_create_tokens, cache_read_tokens,
+                web_search_requests, cost, source, created_at, updated_at
+            )
+            VALUES ('e1', 's', '/tmp/t.jsonl', 0, '2024-06-01', 'm', 100, 20, 5, 1000, 0, 9.0, 'test', 0, 0)",
+            [],
+        )
+        .unwrap();
+        record_daily_cost(&conn, day("2024-06-01"), 9.0).unwrap();
+        record_daily_cost(&conn, day("2024-06-01"), 12.0).unwrap();
+        conn.execute("DELETE FROM usage_events", []).unwrap();
+        // A year later: June 1st is folded in before retention prunes it.
+        record_daily_cost(&conn, day("2025-10-14"), 4.0).unwrap();
+        record_daily_cost(&conn, day("2025-10-15"), 20.0).unwrap();
+        record_daily_cost(&conn, day("2025-10-15"), 30.0).unwrap();
+
+        let stats = load_lifetime_stats().unwrap();
+        assert_eq!(load_history_and_lifetime(90).unwrap().1, stats);
+        assert_eq!(stats.first_seen, Some(day("2024-06-01")));
+        assert!((stats.cost - 46.0).abs() < 1e-10);
+        assert_eq!(stats.active_days, 3);
+        assert_eq!(stats.total_tokens(), 1125);
+        assert_eq!(
+            stats.busiest_day,
+            Some(DailyCost {
+                date: day("2025-10-15"),
+                cost: 30.0
+            })
+        );
+        let json = stats.to_json();
+        assert_eq!(json["busiest_day"]["date"], "2025-10-15");
+        assert_eq!(json["cost_usd"], 46.0);
+
+        let (folded, through) = read_lifetime_totals(&conn).unwrap();
+        assert_eq!(through.as_deref(), Some("2025-10-14"));
+        assert!((folded.cost - 16.0).abs() < 1e-10);
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    fn project_from_transcript_path_uses_projects_dir() {
+        let project = |p: &str| project_from_transcript_path(Path::new(p));
+        assert_eq!(
+            project("/home/u/.claude/projects/-home-u-work-acme/abc.jsonl"),
+            "-home-u-work-acme"
+        );
+        assert_eq!(
+            project("/home/u/.claude/projects/-home-u-work-acme/abc/subagents/agent-1.jsonl"),
+            "-home-u-work-acme"
+        );
+        assert_eq!(project("/tmp/scratch/t.jsonl"), "scratch");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_stale_cleanup() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        let conn = open_db().unwrap();
+        let transcript_path = PathBuf::from("/tmp/test.jsonl");
+
+        upsert_session(
+            &conn,
+            "sess1:/path",
+            &transcript_path,
+            12345,
+            "2025-10-17",
+            1.0,
+            10,
+        )
+        .unwrap();
+        upsert_session(
+            &conn,
+            "sess2:/path",
+            &transcript_path,
+            12345,
+            "2025-10-18",
+            2.0,
+            10,
+        )
+        .unwrap();
+
+        conn.execute(
+            "DELETE FROM sessions WHERE today_date != ?",
+            params!["2025-10-18"],
+        )
+        .unwrap();
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(count, 1);
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    fn test_migrations_are_numbered_up_to_schema_version() {
+        let versions: Vec<i64> = MIGRATIONS.iter().map(|m| m.version).collect();
+        assert_eq!(versions, (1..=SCHEMA_VERSION).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_usage_snapshots_gain_an_account_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let conn = open_fixture(&temp_dir.path().join("snapshots.db"));
+        conn.execute_batch(sql::CREATE_USAGE_SNAPSHOTS).unwrap();
+        conn.execute(
+            "INSERT INTO usage_snapshots (fetched_at, seven_day) VALUES (100, 42.0)",
+            [],
+        )
+        .unwrap();
+
+        migrate_usage_snapshots_account(&conn).unwrap();
+        migrate_usage_snapshots_account(&conn).unwrap();
+        let (account, seven_day): (String, f64) = conn
+            .query_row(
+                "SELECT account, seven_day FROM usage_snapshots WHERE fetched_at = 100",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((account.as_str(), seven_day), ("", 42.0));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_migrate_applies_pending_steps_and_rejects_newer_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("partial.db");
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        let conn = open_db().unwrap();
+        conn.execute_batch("DROP TABLE lifetime_totals; DROP TABLE model_costs;")
+            .unwrap();
+        conn.pragma_update(None, "user_version", 7).unwrap();
+        drop(conn);
+
+        // The lagging user_version wins over the metadata row
+        let dry = migrate(true).unwrap();
+        assert_eq!(dry.from_version, 7);
+        let pending: Vec<i64> = dry.migrations.iter().map(|m| m.version).collect();
+        assert_eq!(pending, (8..=SCHEMA_VERSION).collect::<Vec<_>>());
+
+        let applied = migrate(false).unwrap();
+        assert_eq!(applied.migrations.len(), pending.len());
+        let conn = open_db().unwrap();
+        assert!(table_exists(&conn, "model_costs").unwrap());
+        assert!(table_exists(&conn, "lifetime_totals").unwrap());
+        assert_eq!(current_schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        assert!(migrate(false).unwrap().migrations.is_empty());
+
+        set_metadata(
+            &conn,
+            METADATA_KEY_SCHEMA_VERSION,
+            &(SCHEMA_VERSION + 1).to_string(),
+        )
+        .unwrap();
+        drop(conn);
+        assert!(open_db().is_err());
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_entries_outlive_daily_usage_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("entries.db");
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        let conn = open_db().unwrap();
+        let transcript = "/home/u/.claude/projects/-work-api/s1.jsonl";
+        let event = |key: &str, cost: f64| {
+            transcript_usage_event(TranscriptUsageEventInput {
+                session_id: "s1",
+                transcript_path: transcript,
+                today: "2025-03-02",
+                agg_key: key,
+                fingerprint: key,
+                ts: 1_740_900_000,
+                model_id: "claude-sonnet-4-6",
+                input: 100,
+                output: 50,
+                cache_create: 0,
+                cache_read: 10,
+                web_search_requests: 0,
+                cost,
+            })
+        };
+        replace_usage_events_for_session_date(
+            &conn,
+            "s1",
+            "2025-03-02",
+            &[event("a", 1.0), event("b", 2.0)],
+        )
+        .unwrap();
+        // A rescan replaces the day's entries instead of adding to them
+        replace_usage_events_for_session_date(&conn, "s1", "2025-03-02", &[event("a", 1.5)])
+            .unwrap();
+        conn.execute(sql::DELETE_STALE_USAGE_EVENTS, params!["2025-03-03"])
+            .unwrap();
+
+        let day = NaiveDate::from_ymd_opt(2025, 3, 2).unwrap();
+        let entries = entries_between(&conn, day, day).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].project, "-work-api");
+        assert_eq!(entries[0].model.as_deref(), Some("claude-sonnet-4-6"));
+        assert_eq!(entries[0].input_tokens, 100);
+        assert!((entries[0].cost - 1.5).abs() < 1e-9);
+        assert!(
+            entries_between(&conn, day.succ_opt().unwrap(), day.succ_opt().unwrap())
+                .unwrap()
+                .is_empty()
+        );
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_totals_aggregate_entries_by_project_and_model() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("totals.db");
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        let conn = open_db().unwrap();
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let event = |session: &str, project: &str, date: &str, key: &str, model: &str, cost| {
+            transcript_usage_event(TranscriptUsageEventInput {
+                session_id: session,
+                transcript_path: &format!("/h/.claude/projects/{project}/{session}.jsonl"),
+                today: date,
+                agg_key: key,
+                fingerprint: key,
+                ts: 0,
+                model_id: model,
+                input: 10,
+                output: 5,
+                cache_create: 0,
+                cache_read: 0,
+                web_search_requests: 0,
+                cost,
+            })
+        };
+        // Session ledger row from before the aggregates existed
+        conn.execute(
+            "INSERT INTO session_costs (date, session_id, cost, updated_at, transcript_path)
+             VALUES ('2025-10-01', 'old', 7.0, 0, '/h/.claude/projects/api/old.jsonl')",
+            [],
+        )
+        .unwrap();
+        for (date, events) in [
+            (
+                "2025-10-14",
+                vec![
+                    event("s1", "api", "2025-10-14", "a", "claude-sonnet-4-6", 1.0),
+                    event("s1", "api", "2025-10-14", "b", "claude-opus-4-6", 2.0),
+                ],
+            ),
+            (
+                "2025-10-15",
+                vec![event(
+                    "s1",
+                    "api",
+                    "2025-10-15",
+                    "c",
+                    "claude-sonnet-4-6",
+                    3.0,
+                )],
+            ),
+        ] {
+            replace_usage_events_for_session_date(&conn, "s1", date, &events).unwrap();
+            replace_usage_events_for_session_date(
+                &conn,
+                "s2",
+                date,
+                &[event("s2", "web", date, "d", "claude-sonnet-4-6", 0.5)],
+            )
+            .unwrap();
+            record_daily_cost(&conn, day(date), 0.0).unwrap();
+        }
+
+        let (rows, cost, entry_count, input_tokens): (i64, f64, i64, i64) = conn
+            .query_row(
+                "SELECT COUNT(*), SUM(cost), SUM(entry_count), SUM(input_tokens)
+                 FROM daily_totals WHERE project = 'api' AND model = 'claude-sonnet-4-6'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(rows, 2);
+        assert!((cost - 4.0).abs() < 1e-9);
+        assert_eq!(entry_count, 2);
+        assert_eq!(input_tokens, 20);
+
+        let mut projects =
+            project_costs_between(&conn, day("2025-10-01"), day("2025-10-15")).unwrap();
+        projects.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            projects,
+            vec![("api".to_string(), 13.0), ("web".to_string(), 1.0)]
+        );
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_prune_applies_retention() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("prune.db");
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        let conn = open_db().unwrap();
+        let now = chrono::DateTime::parse_from_rfc3339("2025-06-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for (key, date) in [("old", "2025-01-01"), ("new", "2025-06-10")] {
+            conn.execute(
+                "INSERT INTO entries (event_key, ts, date, session_id, project, model,
+                    input_tokens, output_tokens, cache_create_tokens, cache_read_tokens,
+                    web_search_requests, cost, updated_at)
+                 VALUES (?1, 0, ?2, 's', 'p', NULL, 0, 0, 0, 0, 0, 1.0, 0)",
+                params![key, date],
+            )
+            .unwrap();
+        }
+        for (key, fetched_at) in [
+            (OAUTH_USAGE_SUMMARY_CACHE_KEY, now.timestamp() - 40 * 86_400),
+            ("fresh", now.timestamp() - 60),
+        ] {
+            conn.execute(
+                "INSERT INTO api_cache (cache_key, data, fetched_at, expires_at) VALUES (?1, '', ?2, ?2)",
+                params![key, fetched_at],
+            )
+            .unwrap();
+        }
+
+        let keep_all = Retention {
+            entries_days: None,
+            api_cache_days: None,
+        };
+        let report = prune_with(&conn, keep_all, now).unwrap();
+        assert_eq!((report.entries, report.api_cache), (0, 0));
+
+        let report = prune_with(&conn, Retention::default(), now).unwrap();
+        assert_eq!((report.entries, report.api_cache), (0, 1));
+
+        let week = Retention {
+            entries_days: Some(7),
+            ..Retention::default()
+        };
+        drop(conn);
+        for days_ago in [30, 1] {
+            record_usage_snapshot(&UsageSnapshot {
+                fetched_at: now.timestamp() - days_ago * 86_400,
+                seven_day: Some(days_ago as f64),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+        let conn = open_db().unwrap();
+        let report = prune_with(&conn, week, now).unwrap();
+        assert_eq!((report.entries, report.usage_snapshots), (1, 1));
+        let left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(left, 1);
+        let snapshots = load_usage_snapshots("", 0).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].seven_day, Some(1.0));
+
+        // Accounts fetched in the same second keep separate histories
+        let at = now.timestamp();
+        for (account, seven_day) in [("work", 60.0), ("personal", 5.0)] {
+            record_usage_snapshot(&UsageSnapshot {
+                account: account.to_string(),
+                fetched_at: at,
+                seven_day: Some(seven_day),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+        let work = load_usage_snapshots("work", 0).unwrap();
+        assert_eq!(work.len(), 1);
+        assert_eq!(work[0].seven_day, Some(60.0));
+        assert_eq!(load_usage_snapshots("", 0).unwrap().len(), 1);
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_reimported_rows_are_not_exported_again() {
+        let temp_dir = TempDir::new().unwrap();
+        let use_db = |name: &str| {
+            // SAFETY: Test runs serially, no concurrent env access
+            unsafe {
+                env::set_var(
+                    "CLAUDE_STATUSLINE_DB_PATH",
+                    temp_dir.path().join(name).to_str().unwrap(),
+                )
+            };
+            open_db().unwrap()
+        };
+
+        let laptop = use_db("laptop.db");
+        laptop
+            .execute(
+                "INSERT INTO entries (event_key, ts, date, session_id, project, model,
+                    input_tokens, output_tokens, cache_create_tokens, cache_read_tokens,
+                    web_search_requests, cost, updated_at)
+                 VALUES ('l1', 0, '2025-10-14', 'laptop', 'api', 'claude-sonnet-4-6',
+                    10, 5, 0, 0, 0, 2.0, 10)",
+                [],
+            )
+            .unwrap();
+        laptop
+            .execute(
+                "INSERT INTO session_costs (date, session_id, cost, updated_at, transcript_path)
+                 VALUES ('2025-10-14', 'laptop', 2.0, 10, NULL)",
+                [],
+            )
+            .unwrap();
+        reconcile_imported_date(&laptop, "2025-10-14").unwrap();
+        // Everything was exported at 10; rows updated since then go out next
+        for table in EXPORT_TABLES {
+            laptop
+                .execute(&format!("UPDATE {table} SET updated_at = 10"), [])
+                .unwrap();
+        }
+        let from_laptop = history_rows(&laptop, 0).unwrap();
+        drop(laptop);
+
+        let desktop = use_db("desktop.db");
+        merge_rows_into(&desktop, &from_laptop).unwrap();
+        assert!(history_rows(&desktop, 11).unwrap().is_empty());
+        let from_desktop = history_rows(&desktop, 0).unwrap();
+        drop(desktop);
+
+        let laptop = use_db("laptop.db");
+        merge_rows_into(&laptop, &from_desktop).unwrap();
+        assert!(history_rows(&laptop, 11).unwrap().is_empty());
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_export_import_merges_histories() {
+        let temp_dir = TempDir::new().unwrap();
+        let insert = |conn: &Connection, session: &str, key: &str, cost: f64, updated_at: i64| {
+            conn.execute(
+                "INSERT INTO entries (event_key, ts, date, session_id, project, model,
+                    input_tokens, output_tokens, cache_create_tokens, cache_read_tokens,
+                    web_search_requests, cost, updated_at)
+                 VALUES (?1, 0, '2025-10-14', ?2, 'api', 'claude-sonnet-4-6', 10, 5, 0, 0, 0, ?3, ?4)
+                 ON CONFLICT(event_key) DO UPDATE SET cost = excluded.cost,
+                    updated_at = excluded.updated_at",
+                params![key, session, cost, updated_at],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO session_costs (date, session_id, cost, updated_at, transcript_path)
+                 VALUES ('2025-10-14', ?1, ?2, ?3, NULL)
+                 ON CONFLICT(date, session_id) DO UPDATE SET cost = excluded.cost,
+                    updated_at = excluded.updated_at",
+                params![session, cost, updated_at],
+            )
+            .unwrap();
+        };
+
+        // Laptop history
+        unsafe {
+            env::set_var(
+                "CLAUDE_STATUSLINE_DB_PATH",
+                temp_dir.path().join("laptop.db").to_str().unwrap(),
+            )
+        };
+        let laptop = open_db().unwrap();
+        insert(&laptop, "laptop", "l1", 2.0, 10);
+        insert(&laptop, "shared", "s1", 1.0, 10);
+        let export = export_json().unwrap();
+        drop(laptop);
+
+        // Desktop has a newer copy of the shared session
+        unsafe {
+            env::set_var(
+                "CLAUDE_STATUSLINE_DB_PATH",
+                temp_dir.path().join("desktop.db").to_str().unwrap(),
+            )
+        };
+        let desktop = open_db().unwrap();
+        insert(&desktop, "desktop", "d1", 3.0, 10);
+        insert(&desktop, "shared", "s1", 1.5, 20);
+        record_daily_cost(
+            &desktop,
+            NaiveDate::from_ymd_opt(2025, 10, 14).unwrap(),
+            4.5,
+        )
+        .unwrap();
+
+        let report = import_into(&desktop, &export).unwrap();
+        assert_eq!(report.tables["entries"], 1);
+        assert_eq!(report.dates, 1);
+        // Importing the same export again is a no-op
+        let again = import_into(&desktop, &export).unwrap();
+        assert_eq!(again.tables["entries"], 0);
+
+        let day = NaiveDate::from_ymd_opt(2025, 10, 14).unwrap();
+        let daily = daily_costs_between(&desktop, day, day).unwrap();
+        assert!((daily[0].cost - 6.5).abs() < 1e-9);
+        let entries = entries_between(&desktop, day, day).unwrap();
+        assert_eq!(entries.len(), 3);
+        let shared = entries.iter().find(|e| e.session_id == "shared").unwrap();
+        assert!((shared.cost - 1.5).abs() < 1e-9);
+        let projects = project_costs_between(&desktop, day, day).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert!((projects[0].1 - 6.5).abs() < 1e-9);
+
+        assert!(import_into(&desktop, &serde_json::json!({"format": "other"})).is_err());
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_held_connection_is_reused_until_dropped() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        // Temp tables live only as long as their connection
+        let probe = "SELECT count(*) FROM temp.sqlite_master WHERE name = 'held_probe'";
+        let seen = || -> i64 {
+            open_db()
+                .unwrap()
+                .query_row(probe, [], |row| row.get(0))
+                .unwrap()
+        };
+
+        let held = hold().unwrap();
+        open_db()
+            .unwrap()
+            .execute_batch("CREATE TEMP TABLE held_probe (id INTEGER)")
+            .unwrap();
+        assert_eq!(seen(), 1);
+        set_api_cache("held", "{}", 60).unwrap();
+        assert_eq!(get_api_cache("held").unwrap().as_deref(), Some("{}"));
+
+        drop(held);
+        assert_eq!(seen(), 0);
+        assert_eq!(get_api_cache("held").unwrap().as_deref(), Some("{}"));
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_maintain_reclaims_free_pages() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        // A database from before auto_vacuum was enabled
+        open_fixture(&db_path)
+            .execute_batch("CREATE TABLE legacy (id INTEGER)")
+            .unwrap();
+        let fill = |conn: &Connection| {
+            let blob = "x".repeat(4096);
+            for i in 0..600 {
+                conn.execute(
+                    sql::UPSERT_API_CACHE,
+                    params![format!("k{i}"), blob, 0, i64::MAX],
+                )
+                .unwrap();
+            }
+            conn.execute_batch("DELETE FROM api_cache").unwrap();
+        };
+        let conn = open_db().unwrap();
+        fill(&conn);
+        let (_, free) = page_counts(&conn).unwrap();
+        assert!(free >= VACUUM_MIN_FREE_PAGES);
+        // Over threshold, but only `db maintain` may run a full VACUUM
+        assert_eq!(vacuum_if_due(&conn, false).unwrap(), None);
+        drop(conn);
+
+        let report = maintain().unwrap();
+        assert_eq!(report.vacuum, Some("full"));
+        assert!(report.checkpointed);
+        assert!(report.pages_after < report.pages_before);
+        let conn = open_db().unwrap();
+        let auto_vacuum: i64 = conn
+            .query_row("PRAGMA auto_vacuum", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(auto_vacuum, 2);
+
+        // Converted, so the opportunistic path can now reclaim pages itself
+        fill(&conn);
+        assert_eq!(vacuum_if_due(&conn, false).unwrap(), Some("incremental"));
+        assert_eq!(page_counts(&conn).unwrap().1, 0);
+        assert!(
+            get_metadata(&conn, METADATA_KEY_LAST_VACUUM)
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(
+            maintenance_thresholds(&conn).unwrap(),
+            (DEFAULT_WAL_CHECKPOINT_BYTES, DEFAULT_VACUUM_FREE_PERCENT)
+        );
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_unreadable_db_is_left_in_place() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("foreign.db");
+        let transcript_path = temp_dir.path().join("transcript.jsonl");
+        std::fs::write(&transcript_path, "{}\n").unwrap();
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        // Indistinguishable from a database encrypted under another key
+        let foreign = vec![0xA5u8; 8192];
+        std::fs::write(&db_path, &foreign).unwrap();
+        assert!(get_global_usage("sess", "/project", &transcript_path, Some(1.5), None).is_err());
+        assert_eq!(std::fs::read(&db_path).unwrap(), foreign);
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    // SQLCipher reports damaged pages as "not a database", which never recovers
+    #[test]
+    #[serial_test::serial]
+    #[cfg(not(feature = "encrypted-db"))]
+    fn test_corrupt_db_is_moved_aside_and_rebuilt() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("corrupt.db");
+        let transcript_path = temp_dir.path().join("transcript.jsonl");
+        std::fs::write(&transcript_path, "{}\n").unwrap();
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        drop(open_db().unwrap());
+        assert!(check().unwrap().ok);
+        // What a torn write on a network home directory can leave behind:
+        // an intact header over a garbled schema page
+        let mut bytes = std::fs::read(&db_path).unwrap();
+        bytes[100..4096].fill(0xA5);
+        std::fs::write(&db_path, bytes).unwrap();
+        assert!(!check().unwrap().ok);
+
+        let usage =
+            get_global_usage("sess", "/project", &transcript_path, Some(1.5), None).unwrap();
+        assert!((usage.session_cost - 1.5).abs() < 1e-10);
+
+        let report = check().unwrap();
+        assert!(report.ok);
+        let moved = PathBuf::from(report.recovered_from.unwrap());
+        assert!(moved.exists());
+        assert!(
+            moved
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("corrupt.db.corrupt-")
+        );
+        // Same-second recoveries in two processes get different names
+        assert!(
+            moved
+                .to_string_lossy()
+                .ends_with(&format!("-{}", std::process::id()))
+        );
+        // A process that lost the race leaves the fresh database alone
+        assert_eq!(recover_corrupt_db().unwrap(), None);
+        assert!(!temp_dir.path().join("corrupt.db.lock").exists());
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_render_timings_keep_newest_rows() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("timings.db");
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        for i in 0..RENDER_TIMINGS_KEPT + 5 {
+            record_render_timing(&RenderTiming {
+                recorded_at: i,
+                version: "2.5.0".to_string(),
+                total_ms: i as f64,
+                ..RenderTiming::default()
+            })
+            .unwrap();
+        }
+        let timings = load_render_timings().unwrap();
+        assert_eq!(timings.len() as i64, RENDER_TIMINGS_KEPT);
+        assert_eq!(timings[0].recorded_at, 5);
+        assert_eq!(timings.last().unwrap().recorded_at, RENDER_TIMINGS_KEPT + 4);
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_invalidate_cache_scopes_to_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("invalidate.db");
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_path.to_str().unwrap()) };
+
+        let conn = open_db().unwrap();
+        for session in ["s1", "s2"] {
+            conn.execute(
+                "INSERT INTO sessions (session_id, session_key, transcript_path, transcript_mtime,
+                     today_date, today_cost, entry_count, last_parsed_at, created_at, updated_at)
+                 VALUES (?1, ?1, '/t.jsonl', 0, '2025-10-14', 1.0, 1, 0, 0, 0)",
+                params![session],
+            )
+            .unwrap();
+        }
+        set_metadata(&conn, &global_sum_cache_key("2025-10-14"), "2:2").unwrap();
+        drop(conn);
+        set_api_cache("scan:2025-10-14:s1:abcd", "{}", 60).unwrap();
+        set_api_cache("scan:2025-10-14:s2:abcd", "{}", 60).unwrap();
+        set_api_cache(OAUTH_USAGE_SUMMARY_CACHE_KEY, "{}", 60).unwrap();
+
+        let report = invalidate_cache(Some("s1")).unwrap();
+        assert_eq!(
+            (report.sessions, report.api_cache, report.metadata),
+            (1, 1, 1)
+        );
+        assert!(get_api_cache("scan:2025-10-14:s2:abcd").unwrap().is_some());
+        assert!(
+            get_api_cache(OAUTH_USAGE_SUMMARY_CACHE_KEY)
+                .unwrap()
+                .is_some()
+        );
+
+        let report = invalidate_cache(None).unwrap();
+        assert_eq!((report.sessions, report.api_cache), (1, 2));
         unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
     }
 }
@@ -0,0 +1,181 @@
+//! Stand-in for `db` in builds without the `db` feature.
+//!
+//! Keeps the same API so callers compile unchanged. Caches always miss,
+//! writes are dropped, and history queries fail with [`DISABLED`], which
+//! callers already treat like an unavailable database: renders fall back to
+//! the transcript scan, and `report`/`db` commands print the error.
+
+use crate::models::Entry;
+pub use crate::models::ledger::{
+    CacheTtls, CostHistory, DailyCost, DbHealth, GlobalUsage, HistoryRow, ImportReport,
+    IntegrityReport, InvalidateReport, LifetimeStats, MaintenanceReport, MetadataEntry,
+    MigrationReport, ModelTotals, PruneReport, RenderTiming, Retention, SeatLedger, UsageSnapshot,
+};
+use anyhow::{Result, bail};
+use chrono::NaiveDate;
+use std::path::Path;
+
+pub const DISABLED: &str = "built without the `db` feature";
+pub const DAILY_HISTORY_DAYS: i64 = 28;
+
+/// No connection to hold; kept so long-running modes compile unchanged.
+#[must_use = "the connection is released when the handle is dropped"]
+pub struct HeldDb {
+    _private: (),
+}
+
+pub fn hold() -> Result<HeldDb> {
+    Ok(HeldDb { _private: () })
+}
+
+pub fn inspect_health() -> DbHealth {
+    DbHealth {
+        path: format!("({DISABLED})"),
+        exists: false,
+        parent_exists: false,
+        writable: false,
+        journal_mode: None,
+        schema_version: None,
+        user_version: None,
+        usage_cache_version: None,
+        encrypted: false,
+        ok: false,
+        error: Some(DISABLED.to_string()),
+    }
+}
+
+pub fn set_retention(_retention: Retention) {}
+
+pub fn set_cache_ttls(_ttls: CacheTtls) {}
+
+pub fn cache_ttls() -> CacheTtls {
+    CacheTtls::default()
+}
+
+pub fn prune() -> Result<PruneReport> {
+    bail!(DISABLED)
+}
+
+pub fn invalidate_cache(_session_id: Option<&str>) -> Result<InvalidateReport> {
+    bail!(DISABLED)
+}
+
+pub fn maintain() -> Result<MaintenanceReport> {
+    bail!(DISABLED)
+}
+
+pub fn check() -> Result<IntegrityReport> {
+    bail!(DISABLED)
+}
+
+pub fn export_json() -> Result<serde_json::Value> {
+    bail!(DISABLED)
+}
+
+pub fn history_rows_since(_since: i64) -> Result<Vec<HistoryRow>> {
+    bail!(DISABLED)
+}
+
+pub fn merge_history_rows(_rows: &[HistoryRow]) -> Result<ImportReport> {
+    bail!(DISABLED)
+}
+
+pub fn import_json(_doc: &serde_json::Value) -> Result<ImportReport> {
+    bail!(DISABLED)
+}
+
+pub fn load_metadata(_key: &str) -> Result<Option<MetadataEntry>> {
+    bail!(DISABLED)
+}
+
+pub fn store_metadata(_key: &str, _value: &str) -> Result<()> {
+    bail!(DISABLED)
+}
+
+pub fn migrate(_dry_run: bool) -> Result<MigrationReport> {
+    bail!(DISABLED)
+}
+
+pub fn load_cost_history(_days: i64) -> Result<CostHistory> {
+    bail!(DISABLED)
+}
+
+pub fn load_lifetime_stats() -> Result<LifetimeStats> {
+    bail!(DISABLED)
+}
+
+pub fn load_history_and_lifetime(_days: i64) -> Result<(CostHistory, LifetimeStats)> {
+    bail!(DISABLED)
+}
+
+pub fn record_usage_snapshot(_snapshot: &UsageSnapshot) -> Result<()> {
+    Ok(())
+}
+
+pub fn load_usage_snapshots(_account: &str, _since: i64) -> Result<Vec<UsageSnapshot>> {
+    bail!(DISABLED)
+}
+
+pub fn record_render_timing(_timing: &RenderTiming) -> Result<()> {
+    Ok(())
+}
+
+pub fn load_render_timings() -> Result<Vec<RenderTiming>> {
+    bail!(DISABLED)
+}
+
+pub fn load_daily_costs(_from: NaiveDate, _to: NaiveDate) -> Result<Vec<DailyCost>> {
+    bail!(DISABLED)
+}
+
+pub fn load_seat_ledger(
+    _path: Option<&Path>,
+    _from: NaiveDate,
+    _to: NaiveDate,
+) -> Result<SeatLedger> {
+    bail!(DISABLED)
+}
+
+pub fn load_model_costs(_from: NaiveDate, _to: NaiveDate) -> Result<Vec<ModelTotals>> {
+    bail!(DISABLED)
+}
+
+pub fn load_project_costs(_from: NaiveDate, _to: NaiveDate) -> Result<Vec<(String, f64)>> {
+    bail!(DISABLED)
+}
+
+pub fn get_global_usage(
+    _session_id: &str,
+    _project_dir: &str,
+    _transcript_path: &Path,
+    _session_today_cost: Option<f64>,
+    _session_entries: Option<&[Entry]>,
+) -> Result<GlobalUsage> {
+    bail!(DISABLED)
+}
+
+pub fn get_api_cache(_cache_key: &str) -> Result<Option<String>> {
+    Ok(None)
+}
+
+pub fn get_api_cache_entry(_cache_key: &str) -> Result<Option<(String, i64)>> {
+    Ok(None)
+}
+
+pub fn get_stale_api_cache(_cache_key: &str) -> Result<Option<String>> {
+    Ok(None)
+}
+
+pub fn get_stale_api_cache_entry(_cache_key: &str) -> Result<Option<(String, i64)>> {
+    Ok(None)
+}
+
+/// Never grants the fetch lock: without a shared cache every render would
+/// call the API, so lock-guarded fetches are skipped instead.
+pub fn try_set_api_cache(_cache_key: &str, _data: &str, _ttl_seconds: i64) -> Result<bool> {
+    Ok(false)
+}
+
+pub fn set_api_cache(_cache_key: &str, _data: &str, _ttl_seconds: i64) -> Result<()> {
+    Ok(())
+}
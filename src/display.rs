@@ -1,12 +1,19 @@
 use chrono::{DateTime, Local, Timelike};
 
 use crate::beads::format_bead_display;
-use crate::gastown::format_gastown_display;
+use crate::budget::BudgetStatus;
+use crate::currency::{DisplayCurrency, convert, cost_symbol, money};
+use crate::db::{DailyCost, LifetimeStats};
+use crate::environment::{EnvironmentEstimate, Footprint};
+use crate::gastown::{GastownFormat, format_gastown_display};
 use crate::models::{BeadsInfo, GasTownInfo};
 use crate::models::{PromptCacheBucketKind, PromptCacheInfo};
-use crate::provenance::CostProvenance;
+use crate::openrouter::OpenRouterCredits;
+use crate::pricing::ServerToolCost;
+use crate::provenance::{CacheReport, CostProvenance};
+use crate::report::{CostStats, ModelCost, ProjectCost, TagCost};
 use crate::tokens;
-use crate::usage_api::is_direct_claude_api;
+use crate::usage_api::{UsageApiError, is_direct_claude_api};
 use std::env;
 use std::fmt::Write as _;
 use std::path::Path;
@@ -21,7 +28,12 @@ const SYM_DOT: &str = "·"; // Dot separator (compact)
 const SYM_ARROW_RIGHT: &str = "→"; // Projection arrow
 const SYM_ARROW_UP: &str = "↑"; // Ahead indicator
 const SYM_ARROW_DOWN: &str = "↓"; // Behind indicator
-const SYM_DOLLAR: &str = "$"; // Cost indicator
+const SYM_STASH: &str = "⚑"; // Stash entries
+const SYM_CONFLICT: &str = "✖"; // Unresolved merge conflicts
+const SYM_HEALTH: &str = "⚠"; // Repository health warnings
+const SYM_CI_PASS: &str = "✓"; // CI checks passed
+const SYM_CI_FAIL: &str = "✗"; // CI checks failed
+const SYM_CI_PENDING: &str = "●"; // CI checks running
 
 // Terminal width thresholds for responsive formatting
 const WIDTH_NARROW: u16 = 140;
@@ -86,7 +98,8 @@ pub mod color_shim {
     }
 }
 
-use crate::cli::{Args, LabelsArg, TimeFormatArg};
+use crate::cli::{Args, GitFieldArg, LabelsArg, TimeFormatArg};
+use crate::models::git::{GitCiState, GitIssue, Vcs};
 use crate::models::{Block, GitInfo, HookJson, RateLimitInfo};
 use crate::usage_api::{UsageLimit, UsageSummary};
 use crate::utils::{
@@ -94,7 +107,7 @@ use crate::utils::{
     deduce_provider_from_model, format_currency, format_path, format_tokens,
     reserved_output_tokens_for_model, system_overhead_tokens,
 };
-use crate::window::window_bounds;
+use crate::window::{Efficiency, window_bounds};
 
 fn format_pct(pct: f64) -> String {
     let rounded = pct.round();
@@ -155,6 +168,73 @@ fn active_effort_level(hook: &HookJson) -> Option<String> {
         })
 }
 
+/// The `git` object. Every key is present, null outside a repository, so the
+/// schema stays stable.
+fn git_json(git_info: Option<GitInfo>) -> serde_json::Value {
+    let present = git_info.is_some();
+    let gi = git_info.unwrap_or_default();
+    serde_json::json!({
+        "vcs": present.then(|| gi.vcs.as_str()),
+        "branch": gi.branch,
+        "root": gi.root,
+        "subproject": gi.subproject,
+        "short_commit": gi.short_commit,
+        "commit": gi.commit,
+        "head_age_seconds": gi.head_age_secs,
+        "is_clean": gi.is_clean,
+        "ahead": gi.ahead,
+        "behind": gi.behind,
+        "upstream": gi.upstream,
+        "default_branch": gi.default_branch,
+        "protected": present.then_some(gi.protected),
+        "default_ahead": gi.default_ahead,
+        "default_behind": gi.default_behind,
+        "is_head_on_remote": gi.is_head_on_remote,
+        "remote_url": gi.remote_url,
+        "worktree_count": gi.worktree_count,
+        "is_linked_worktree": gi.is_linked_worktree,
+        "worktrees": present.then_some(gi.worktrees),
+        "health": present.then_some(gi.health),
+        "stash_count": gi.stash_count,
+        "untracked": gi.untracked,
+        "lines_added": gi.lines_added,
+        "lines_removed": gi.lines_removed,
+        "conflicts": gi.conflicts,
+        "sparse_checkout": gi.sparse_checkout,
+        "lfs_pending": gi.lfs_pending,
+        "submodules": gi.submodules,
+        "submodules_dirty": gi.submodules_dirty,
+        "operation": gi.operation.map(|op| serde_json::json!({
+            "kind": op.kind.as_str(),
+            "step": op.step,
+            "total": op.total,
+        })),
+        "describe": gi.describe.map(|d| serde_json::json!({
+            "tag": d.tag,
+            "distance": d.distance,
+            "text": d.text,
+        })),
+        "pr": gi.pull_request.map(|pr| serde_json::json!({
+            "number": pr.number,
+            "url": pr.url,
+            "title": pr.title,
+            "state": pr.state,
+            "is_draft": pr.is_draft,
+            "review_decision": pr.review_decision,
+            "mergeable": pr.mergeable,
+        })),
+        "ci": gi.ci.map(|ci| serde_json::json!({
+            "state": ci.state.as_str(),
+            "total": ci.total,
+            "failed": ci.failed,
+            "pending": ci.pending,
+        })),
+        "issues": present.then_some(gi.issues),
+        "stale": gi.stale_age_secs.is_some(),
+        "stale_age_seconds": gi.stale_age_secs
+    })
+}
+
 fn prompt_cache_json(info: Option<&PromptCacheInfo>) -> serde_json::Value {
     info.map(|info| {
         let primary = info.primary_bucket();
@@ -349,6 +429,15 @@ fn strip_ansi(text: &str) -> String {
             }
             continue;
         }
+        // OSC sequences (hyperlinks) end at BEL or ESC \
+        if ch == '\x1b' && chars.peek().is_some_and(|next| *next == ']') {
+            while let Some(code) = chars.next() {
+                if code == '\x07' || (code == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                    break;
+                }
+            }
+            continue;
+        }
 
         stripped.push(ch);
     }
@@ -795,16 +884,166 @@ fn cost_segment_variants(
     short_label: &str,
     value: f64,
     gradient_max: Option<f64>,
+    currency: Option<&DisplayCurrency>,
     tc: bool,
     priority: u8,
 ) -> StatusSegment {
-    let cost_str = format_currency(value);
+    let cost_str = format_currency(convert(value, currency));
     let cost_value = if let Some(max) = gradient_max {
         tokens::gradient(value, max).paint(&cost_str, tc)
     } else {
         tokens::PRIMARY.bold(&cost_str, tc)
     };
-    let dollar = tokens::MUTED.paint(SYM_DOLLAR, tc);
+    labeled_cost_segment(long_label, short_label, cost_value, currency, tc, priority)
+}
+
+/// Session cost segment; the value turns red once `--session-cost-cap` is exceeded.
+fn session_cost_segment(
+    long_label: &str,
+    short_label: &str,
+    session_cost: f64,
+    args: &Args,
+    currency: Option<&DisplayCurrency>,
+    tc: bool,
+) -> StatusSegment {
+    if session_over_cap(session_cost, args) {
+        let cost_value = tokens::ERROR.bold(&format_currency(convert(session_cost, currency)), tc);
+        labeled_cost_segment(long_label, short_label, cost_value, currency, tc, 80)
+    } else {
+        cost_segment_variants(
+            long_label,
+            short_label,
+            session_cost,
+            None,
+            currency,
+            tc,
+            80,
+        )
+    }
+}
+
+fn session_over_cap(session_cost: f64, args: &Args) -> bool {
+    args.session_cost_cap
+        .is_some_and(|cap| cap > 0.0 && session_cost > cap)
+}
+
+/// Month totals outgrow cents quickly; drop them past $100 to save width.
+fn format_month_cost(value: f64, currency: Option<&DisplayCurrency>) -> String {
+    let converted = convert(value, currency);
+    if converted >= 100.0 {
+        format!("{converted:.0}")
+    } else {
+        format_currency(converted)
+    }
+}
+
+/// Tightest budget as a whole percent; red once any budget is exceeded.
+fn budget_segment(status: &BudgetStatus, tc: bool) -> Option<StatusSegment> {
+    let line = status.tightest()?;
+    let pct = line.percent();
+    let formatted = format!("{:.0}%", pct);
+    let value = if line.spent > line.limit {
+        tokens::ERROR.bold(&formatted, tc)
+    } else if pct >= 80.0 {
+        tokens::gradient(pct, 100.0).bold(&formatted, tc)
+    } else {
+        tokens::gradient(pct, 100.0).paint(&formatted, tc)
+    };
+    Some(adaptive_segment(
+        vec![
+            format!("{}{}", muted_label("budget:", tc), value),
+            format!("{}{}", muted_label("b:", tc), value),
+        ],
+        35,
+    ))
+}
+
+/// Session energy and CO2e estimate (`env:3.2Wh/1.3g`), dropping energy first.
+fn environment_segment(footprint: &Footprint, tc: bool) -> StatusSegment {
+    let co2 = tokens::PRIMARY_DIM.paint(&footprint.co2e_label(), tc);
+    let energy = tokens::PRIMARY_DIM.paint(&footprint.energy_label(), tc);
+    adaptive_segment(
+        vec![
+            format!(
+                "{}{}{}{}",
+                muted_label("env:", tc),
+                energy,
+                muted_label("/", tc),
+                co2
+            ),
+            format!("{}{}", muted_label("co2:", tc), co2),
+        ],
+        15,
+    )
+}
+
+/// `auth⚠` while the usage endpoint rejects the token; the limits would
+/// otherwise look like a plan without any.
+fn usage_auth_segment(extras: &OutputExtras, tc: bool) -> Option<StatusSegment> {
+    extras.usage_limits_error.as_ref()?;
+    Some(status_segment(
+        format!(
+            "{}{}",
+            muted_label("auth", tc),
+            tokens::WARNING.paint("⚠", tc)
+        ),
+        16,
+    ))
+}
+
+/// OpenRouter credits left (`or:$7.50 10/10s`): a gradient against the key's
+/// cap when it has one, a warning under a dollar otherwise.
+fn openrouter_segment(credits: &OpenRouterCredits, tc: bool) -> Option<StatusSegment> {
+    let value = match credits.remaining {
+        Some(remaining) => {
+            let formatted = format!("${remaining:.2}");
+            match credits.limit.filter(|limit| *limit > 0.0) {
+                Some(limit) => {
+                    tokens::gradient(credits.usage / limit * 100.0, 100.0).paint(&formatted, tc)
+                }
+                None if remaining < 1.0 => tokens::WARNING.paint(&formatted, tc),
+                None => tokens::PRIMARY_DIM.paint(&formatted, tc),
+            }
+        }
+        None if credits.is_free_tier => tokens::PRIMARY_DIM.paint("free", tc),
+        None => return None,
+    };
+    let short = format!("{}{}", muted_label("or:", tc), value);
+    let Some(ref rate) = credits.rate_limit else {
+        return Some(status_segment(short, 12));
+    };
+    let rate = muted_label(&format!("{}/{}", rate.requests, rate.interval), tc);
+    Some(adaptive_segment(vec![format!("{short} {rate}"), short], 12))
+}
+
+/// Percent change of today's cost against the trailing daily average.
+fn today_vs_average_percent(today_cost: f64, average: f64) -> f64 {
+    (today_cost / average - 1.0) * 100.0
+}
+
+/// `(+38%)` next to the today cost: warning when above the 7-day average,
+/// success below it, muted within +/-5%.
+fn today_trend_suffix(today_cost: f64, average: f64, tc: bool) -> String {
+    let change = today_vs_average_percent(today_cost, average);
+    let text = format!("({change:+.0}%)");
+    if change >= 5.0 {
+        tokens::WARNING.paint(&text, tc)
+    } else if change <= -5.0 {
+        tokens::SUCCESS.paint(&text, tc)
+    } else {
+        tokens::MUTED.paint(&text, tc)
+    }
+}
+
+fn labeled_cost_segment(
+    long_label: &str,
+    short_label: &str,
+    cost_value: String,
+    currency: Option<&DisplayCurrency>,
+    tc: bool,
+    priority: u8,
+) -> StatusSegment {
+    let dollar = tokens::MUTED.paint(&cost_symbol(currency), tc);
     adaptive_segment(
         vec![
             format!("{}{}{}", muted_label(long_label, tc), dollar, cost_value),
@@ -861,8 +1100,7 @@ fn render_reset_clock(
     let window_end_local = if let Some(block) = active_block {
         block.end.with_timezone(&Local)
     } else {
-        let now_utc = chrono::Utc::now();
-        let (_, end) = window_bounds(now_utc, latest_reset);
+        let (_, end) = window_bounds(crate::utils::now_utc(), latest_reset);
         end.with_timezone(&Local)
     };
 
@@ -901,13 +1139,25 @@ fn render_reset_inline(
 
 fn build_git_status_segment(
     git_info: Option<&GitInfo>,
+    args: &Args,
     tc: bool,
     width: TerminalWidth,
     lines_delta: Option<(i64, i64)>,
     include_lines_delta: bool,
 ) -> Option<String> {
     let git_info = git_info?;
+    let field = |f: GitFieldArg| args.git_field(f);
     let mut git_seg = String::new();
+    if field(GitFieldArg::Operation)
+        && let Some(op) = git_info.operation
+    {
+        let label = match (op.step, op.total) {
+            (Some(step), Some(total)) => format!("{} {step}/{total}", op.kind.label()),
+            _ => op.kind.label().to_string(),
+        };
+        git_seg.push_str(&tokens::WARNING.bold(&label, tc));
+        git_seg.push(' ');
+    }
     let branch_max_len = match (width, include_lines_delta) {
         (TerminalWidth::Narrow, true) => 12,
         (TerminalWidth::Medium, true) => 20,
@@ -917,23 +1167,103 @@ fn build_git_status_segment(
         (TerminalWidth::Wide, false) => 24,
     };
 
-    if let Some(branch) = git_info.branch.as_ref() {
-        let branch_name = truncate_label(branch, branch_max_len);
-        git_seg.push_str(&tokens::PRIMARY.paint(&branch_name, tc));
-        if let Some(short_commit) = git_info.short_commit.as_ref() {
-            git_seg.push_str(&muted_label("@", tc));
+    let short_commit = git_info
+        .short_commit
+        .as_ref()
+        .filter(|_| field(GitFieldArg::Commit));
+    match (git_info.branch.as_ref(), short_commit) {
+        (Some(branch), commit) if field(GitFieldArg::Branch) => {
+            let branch_name = truncate_label(branch, branch_max_len);
+            if git_info.protected && !args.no_git_protected_warning {
+                git_seg.push_str(&tokens::WARNING.bold(&branch_name, tc));
+            } else {
+                git_seg.push_str(&tokens::PRIMARY.paint(&branch_name, tc));
+            }
+            if let Some(short_commit) = commit {
+                git_seg.push_str(&muted_label("@", tc));
+                git_seg.push_str(&tokens::PRIMARY.paint(short_commit, tc));
+            }
+        }
+        (Some(_), Some(short_commit)) => {
+            git_seg.push_str(&tokens::PRIMARY.paint(short_commit, tc));
+        }
+        // Sapling has no named branches; a commit without a bookmark is usual
+        (None, Some(short_commit)) => {
+            if git_info.vcs != Vcs::Sapling {
+                git_seg.push_str(&muted_label("detached@", tc));
+            }
             git_seg.push_str(&tokens::PRIMARY.paint(short_commit, tc));
         }
-    } else if let Some(short_commit) = git_info.short_commit.as_ref() {
-        git_seg.push_str(&muted_label("detached@", tc));
-        git_seg.push_str(&tokens::PRIMARY.paint(short_commit, tc));
+        _ => {}
+    }
+    if field(GitFieldArg::Ci)
+        && let Some(ci) = git_info.ci.as_ref()
+    {
+        git_seg.push_str(&match ci.state {
+            GitCiState::Success => tokens::SUCCESS.paint(SYM_CI_PASS, tc),
+            GitCiState::Failure => tokens::ERROR.paint(SYM_CI_FAIL, tc),
+            GitCiState::Pending => tokens::WARNING.paint(SYM_CI_PENDING, tc),
+        });
     }
 
-    if git_info.is_clean == Some(false) {
+    if field(GitFieldArg::Dirty) && git_info.is_clean == Some(false) {
         git_seg.push_str(&tokens::WARNING.paint("*", tc));
     }
+    if field(GitFieldArg::Untracked)
+        && let Some(untracked) = git_info.untracked.filter(|n| *n > 0)
+    {
+        git_seg.push_str(&tokens::WARNING.paint(&format!("?{untracked}"), tc));
+    }
+    if field(GitFieldArg::HeadAge)
+        && let Some(age) = git_info.head_age_secs
+    {
+        if !git_seg.is_empty() {
+            git_seg.push(' ');
+        }
+        git_seg.push_str(&head_age_label(age, args.git_head_age_warn_hours, tc));
+    }
+    if field(GitFieldArg::Conflicts)
+        && let Some(conflicts) = git_info.conflicts.filter(|n| *n > 0)
+    {
+        if !git_seg.is_empty() {
+            git_seg.push(' ');
+        }
+        git_seg.push_str(&tokens::ERROR.paint(&format!("{SYM_CONFLICT}{conflicts}"), tc));
+    }
+    if field(GitFieldArg::Sparse) && git_info.sparse_checkout == Some(true) {
+        if !git_seg.is_empty() {
+            git_seg.push(' ');
+        }
+        git_seg.push_str(&muted_label("sparse", tc));
+    }
+    if field(GitFieldArg::Lfs)
+        && let Some(pending) = git_info.lfs_pending.filter(|n| *n > 0)
+    {
+        if !git_seg.is_empty() {
+            git_seg.push(' ');
+        }
+        git_seg.push_str(&tokens::WARNING.paint(&format!("lfs:{pending}"), tc));
+    }
+    if field(GitFieldArg::Health) && !git_info.health.is_empty() {
+        if !git_seg.is_empty() {
+            git_seg.push(' ');
+        }
+        git_seg.push_str(&tokens::WARNING.paint(SYM_HEALTH, tc));
+    }
+
+    if field(GitFieldArg::Upstream)
+        && width == TerminalWidth::Wide
+        && let Some(upstream) = git_info.upstream.as_ref()
+    {
+        if !git_seg.is_empty() {
+            git_seg.push(' ');
+        }
+        git_seg.push_str(&muted_label(upstream, tc));
+    }
 
-    if let (Some(ahead), Some(behind)) = (git_info.ahead, git_info.behind) {
+    if field(GitFieldArg::AheadBehind)
+        && let (Some(ahead), Some(behind)) = (git_info.ahead, git_info.behind)
+    {
         if ahead > 0 {
             if !git_seg.is_empty() {
                 git_seg.push(' ');
@@ -948,6 +1278,15 @@ fn build_git_status_segment(
         }
     }
 
+    if field(GitFieldArg::Stash)
+        && let Some(stashes) = git_info.stash_count.filter(|n| *n > 0)
+    {
+        if !git_seg.is_empty() {
+            git_seg.push(' ');
+        }
+        git_seg.push_str(&muted_label(&format!("{SYM_STASH}{stashes}"), tc));
+    }
+
     if include_lines_delta
         && let Some((added, removed)) = lines_delta
         && (added != 0 || removed != 0)
@@ -966,6 +1305,165 @@ fn build_git_status_segment(
     }
 }
 
+/// `PR#42 approved conflicts`, shortened to `PR#42`. Review and merge state
+/// only matter while the PR is open; merged or closed PRs show that instead.
+fn pull_request_segment(
+    git_info: Option<&GitInfo>,
+    tc: bool,
+    priority: u8,
+) -> Option<StatusSegment> {
+    let pr = git_info?.pull_request.as_ref()?;
+    let number = format!("PR#{}", pr.number);
+    let mut full = if pr.is_draft {
+        muted_label(&number, tc)
+    } else {
+        tokens::ACCENT.paint(&number, tc)
+    };
+    let mut push = |part: String| {
+        full.push(' ');
+        full.push_str(&part);
+    };
+    match pr.state.as_str() {
+        "MERGED" => push(muted_label("merged", tc)),
+        "CLOSED" => push(muted_label("closed", tc)),
+        _ => {
+            if pr.is_draft {
+                push(muted_label("draft", tc));
+            }
+            match pr.review_decision.as_deref() {
+                Some("APPROVED") => push(tokens::SUCCESS.paint("approved", tc)),
+                Some("CHANGES_REQUESTED") => push(tokens::ERROR.paint("changes", tc)),
+                Some("REVIEW_REQUIRED") => push(tokens::WARNING.paint("review", tc)),
+                _ => {}
+            }
+            if pr.mergeable.as_deref() == Some("CONFLICTING") {
+                push(tokens::ERROR.paint("conflicts", tc));
+            }
+        }
+    }
+    let short = if pr.is_draft {
+        muted_label(&number, tc)
+    } else {
+        tokens::ACCENT.paint(&number, tc)
+    };
+    Some(adaptive_segment(vec![full, short], priority))
+}
+
+/// Coarse HEAD commit age (`45m`, `5h`, `3d`), in warning color past `warn_hours`.
+fn head_age_label(age_secs: i64, warn_hours: u32, tc: bool) -> String {
+    let minutes = age_secs / 60;
+    let text = if minutes < 60 {
+        format!("{minutes}m")
+    } else if minutes < 24 * 60 {
+        format!("{}h", minutes / 60)
+    } else {
+        format!("{}d", minutes / (24 * 60))
+    };
+    if age_secs > i64::from(warn_hours) * 3600 {
+        tokens::WARNING.paint(&text, tc)
+    } else {
+        muted_label(&text, tc)
+    }
+}
+
+/// ` ready:7` when beads has unassigned, unblocked work to pull, else empty.
+fn beads_ready(beads: &BeadsInfo, tc: bool) -> String {
+    if beads.ready == 0 {
+        return String::new();
+    }
+    format!(
+        " {}{}",
+        muted_label("ready:", tc),
+        tokens::SUCCESS.paint(&beads.ready.to_string(), tc)
+    )
+}
+
+/// ` +3/-5` when beads issues were opened or closed today, else empty.
+/// Green when closing outpaces opening, yellow when the backlog grows.
+fn beads_burndown(beads: &BeadsInfo, tc: bool) -> String {
+    if beads.opened_today == 0 && beads.closed_today == 0 {
+        return String::new();
+    }
+    let text = format!("+{}/-{}", beads.opened_today, beads.closed_today);
+    let colored = match beads.closed_today.cmp(&beads.opened_today) {
+        std::cmp::Ordering::Greater => tokens::SUCCESS.paint(&text, tc),
+        std::cmp::Ordering::Less => tokens::WARNING.paint(&text, tc),
+        std::cmp::Ordering::Equal => tokens::MUTED.dim(&text, tc),
+    };
+    format!(" {colored}")
+}
+
+/// `repo:packages/api` when the project dir sits in a monorepo package,
+/// narrowing to the package path alone.
+fn subproject_segment(
+    hook: &HookJson,
+    git_info: Option<&GitInfo>,
+    tc: bool,
+    priority: u8,
+) -> Option<StatusSegment> {
+    let git_info = git_info?;
+    let subproject = git_info.subproject.as_deref()?;
+    let repo = hook
+        .workspace
+        .repo
+        .as_ref()
+        .map(|repo| repo.name.as_str())
+        .or_else(|| path_basename(git_info.root.as_deref()?));
+    let mut variants = Vec::new();
+    if let Some(repo) = repo {
+        variants.push(format!(
+            "{}{}",
+            muted_label(&format!("{repo}:"), tc),
+            tokens::ACCENT.paint(subproject, tc)
+        ));
+    }
+    variants.push(tokens::ACCENT.paint(subproject, tc));
+    Some(adaptive_segment(variants, priority))
+}
+
+/// `text` as an OSC 8 terminal hyperlink to `url`. Terminals not detected as
+/// truecolor may print the escape literally, so they get plain `text`.
+fn hyperlink(text: &str, url: &str, tc: bool) -> String {
+    if !tc {
+        return text.to_string();
+    }
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// Issue keys from the branch name, hyperlinked when a URL template is set,
+/// narrowing to the first key.
+fn issues_segment(git_info: Option<&GitInfo>, tc: bool, priority: u8) -> Option<StatusSegment> {
+    let issues = &git_info?.issues;
+    let render = |issue: &GitIssue| {
+        let key = tokens::PRIMARY.paint(&issue.key, tc);
+        match issue.url.as_deref() {
+            Some(url) => hyperlink(&key, url, tc),
+            None => key,
+        }
+    };
+    let first = render(issues.first()?);
+    let mut variants = Vec::new();
+    if issues.len() > 1 {
+        variants.push(issues.iter().map(render).collect::<Vec<_>>().join(" "));
+    }
+    variants.push(first);
+    Some(adaptive_segment(variants, priority))
+}
+
+/// `git describe` text, falling back to `tag+N` when space is tight.
+fn describe_segment(git_info: Option<&GitInfo>, tc: bool, priority: u8) -> Option<StatusSegment> {
+    let describe = git_info?.describe.as_ref()?;
+    let short = if describe.distance == 0 {
+        describe.tag.clone()
+    } else {
+        format!("{}+{}", describe.tag, describe.distance)
+    };
+    Some(adaptive_segment(
+        vec![muted_label(&describe.text, tc), muted_label(&short, tc)],
+        priority,
+    ))
+}
+
 struct UsageSegmentTiming<'a> {
     remaining_minutes: f64,
     active_block: Option<&'a Block>,
@@ -1217,6 +1715,7 @@ fn render_header_line(
     git_info: Option<&GitInfo>,
     args: &Args,
     api_key_source: Option<&str>,
+    oauth_plan: Option<&str>,
     lines_delta: Option<(i64, i64)>,
     beads_info: Option<&BeadsInfo>,
     gastown_info: Option<&GasTownInfo>,
@@ -1246,9 +1745,10 @@ fn render_header_line(
             header_parts.push(status_segment(tokens::ACCENT.paint(&dir_fmt, tc), 90));
         }
     }
-    if let Some(git_seg) = build_git_status_segment(git_info, tc, profile.width, lines_delta, true)
+    if let Some(git_seg) =
+        build_git_status_segment(git_info, args, tc, profile.width, lines_delta, true)
     {
-        let compact_git = build_git_status_segment(git_info, tc, profile.width, None, false);
+        let compact_git = build_git_status_segment(git_info, args, tc, profile.width, None, false);
         let mut variants = vec![git_seg];
         if let Some(compact_git) = compact_git {
             variants.push(compact_git);
@@ -1258,6 +1758,26 @@ fn render_header_line(
             tc,
         ));
     }
+    if args.git_field(GitFieldArg::Subproject)
+        && let Some(subproject_seg) = subproject_segment(hook, git_info, tc, 60)
+    {
+        header_parts.push(wrap_header_segment_variants(subproject_seg, tc));
+    }
+    if args.git_field(GitFieldArg::Issues)
+        && let Some(issues_seg) = issues_segment(git_info, tc, 50)
+    {
+        header_parts.push(wrap_header_segment_variants(issues_seg, tc));
+    }
+    if args.git_field(GitFieldArg::Describe)
+        && let Some(describe_seg) = describe_segment(git_info, tc, 35)
+    {
+        header_parts.push(wrap_header_segment_variants(describe_seg, tc));
+    }
+    if args.git_field(GitFieldArg::Pr)
+        && let Some(pr_seg) = pull_request_segment(git_info, tc, 45)
+    {
+        header_parts.push(wrap_header_segment_variants(pr_seg, tc));
+    }
     if !args.no_git_worktree
         && should_show_header_worktree(hook)
         && let Some(wt_seg) = worktree_segment(hook, git_info, tc, profile.width)
@@ -1324,19 +1844,39 @@ fn render_header_line(
                 tokens::ACCENT.paint(&work_display, tc)
             };
 
-            header_parts.push(status_segment(wrap_header_segment(work_colored, tc), 20));
+            header_parts.push(status_segment(
+                wrap_header_segment(
+                    format!(
+                        "{}{}{}",
+                        work_colored,
+                        beads_ready(beads, tc),
+                        beads_burndown(beads, tc)
+                    ),
+                    tc,
+                ),
+                20,
+            ));
         } else if beads.total_open > 0 {
             // No current work but there are open issues - show count
             let count_text = format!("{} open", beads.total_open);
             let count_colored = tokens::MUTED.dim(&count_text, tc);
             header_parts.push(status_segment(
-                wrap_header_segment(format!("{}{}", muted_label("bd:", tc), count_colored), tc),
+                wrap_header_segment(
+                    format!(
+                        "{}{}{}{}",
+                        muted_label("bd:", tc),
+                        count_colored,
+                        beads_ready(beads, tc),
+                        beads_burndown(beads, tc)
+                    ),
+                    tc,
+                ),
                 20,
             ));
         }
     }
 
-    // Beads alert segment (P0 + blocked); independently gated from the work segment.
+    // Beads alert segment (P0, blocked, dependency-blocked, stale); independently gated from the work segment.
     if !args.no_integrations_beads_alerts
         && let Some(beads) = beads_info
     {
@@ -1349,6 +1889,14 @@ fn render_header_line(
             let blocked_text = format!("⚠{}", beads.counts.blocked);
             alerts.push(tokens::WARNING.paint(&blocked_text, tc));
         }
+        if beads.blocked_by_deps > 0 {
+            let deps_text = format!("⛔{}", beads.blocked_by_deps);
+            alerts.push(tokens::WARNING.paint(&deps_text, tc));
+        }
+        if !beads.stale.is_empty() {
+            let stale_text = format!("stale:{}", beads.stale.len());
+            alerts.push(tokens::WARNING.paint(&stale_text, tc));
+        }
         if !alerts.is_empty() {
             header_parts.push(status_segment(
                 wrap_header_segment(alerts.join(" "), tc),
@@ -1361,12 +1909,13 @@ fn render_header_line(
     if !args.no_integrations_gastown
         && let Some(gt) = gastown_info
     {
-        let max_len = match profile.width {
-            TerminalWidth::Narrow => 30,
-            TerminalWidth::Medium => 45,
-            TerminalWidth::Wide => 60,
+        let max_len = match (args.gastown_max_width, profile.width) {
+            (0, TerminalWidth::Narrow) => 30,
+            (0, TerminalWidth::Medium) => 45,
+            (0, TerminalWidth::Wide) => 60,
+            (width, _) => width as usize,
         };
-        let gt_display = format_gastown_display(gt, max_len);
+        let gt_display = format_gastown_display(gt, max_len, &GastownFormat::from_args(args));
         if !gt_display.is_empty() {
             // Color based on context - warning for unread mail, accent otherwise
             let gt_token = if gt.mail.as_ref().is_some_and(|m| m.unread_count > 0) {
@@ -1470,6 +2019,13 @@ fn render_header_line(
                 muted_label("prov:", tc),
                 tokens::PRIMARY_DIM.paint(&prov_disp, tc)
             ));
+            if let Some(plan) = oauth_plan {
+                prov_hint_parts.push(format!(
+                    "{}{}",
+                    muted_label("plan:", tc),
+                    tokens::PRIMARY_DIM.paint(plan, tc)
+                ));
+            }
         }
         if !prov_hint_parts.is_empty() {
             header_parts.push(status_segment(
@@ -1494,6 +2050,7 @@ pub fn print_header(
     git_info: Option<&GitInfo>,
     args: &Args,
     api_key_source: Option<&str>,
+    oauth_plan: Option<&str>,
     lines_delta: Option<(i64, i64)>,
     beads_info: Option<&BeadsInfo>,
     gastown_info: Option<&GasTownInfo>,
@@ -1505,6 +2062,7 @@ pub fn print_header(
         git_info,
         args,
         api_key_source,
+        oauth_plan,
         lines_delta,
         beads_info,
         gastown_info,
@@ -1530,9 +2088,11 @@ fn render_compact_text_output(
     lines_delta: Option<(i64, i64)>,
     usage_limits: Option<&UsageSummary>,
     context_limit_override: Option<u64>,
+    extras: &OutputExtras,
 ) -> String {
     let profile = render_profile();
     let tc = is_truecolor_enabled(args);
+    let currency = extras.currency.as_ref();
     let prompt = tokens::ACCENT.paint(SYM_PROMPT, tc);
     let mut segments = Vec::new();
 
@@ -1542,10 +2102,31 @@ fn render_compact_text_output(
         segments.push(cwd_seg);
     }
 
-    if let Some(git_seg) = build_git_status_segment(git_info, tc, profile.width, lines_delta, false)
+    if let Some(git_seg) =
+        build_git_status_segment(git_info, args, tc, profile.width, lines_delta, false)
     {
         segments.push(status_segment(git_seg, 30));
     }
+    if args.git_field(GitFieldArg::Subproject)
+        && let Some(subproject_seg) = subproject_segment(hook, git_info, tc, 22)
+    {
+        segments.push(subproject_seg);
+    }
+    if args.git_field(GitFieldArg::Issues)
+        && let Some(issues_seg) = issues_segment(git_info, tc, 19)
+    {
+        segments.push(issues_seg);
+    }
+    if args.git_field(GitFieldArg::Describe)
+        && let Some(describe_seg) = describe_segment(git_info, tc, 15)
+    {
+        segments.push(describe_seg);
+    }
+    if args.git_field(GitFieldArg::Pr)
+        && let Some(pr_seg) = pull_request_segment(git_info, tc, 18)
+    {
+        segments.push(pr_seg);
+    }
     if !args.no_git_worktree
         && let Some(wt_seg) = worktree_segment(hook, git_info, tc, profile.width)
     {
@@ -1574,16 +2155,18 @@ fn render_compact_text_output(
     }
 
     if !args.no_cost_session {
-        segments.push(cost_segment_variants(
+        segments.push(session_cost_segment(
             "session:",
             "s:",
             session_cost,
-            None,
+            args,
+            currency,
             tc,
-            80,
         ));
     }
 
+    segments.extend(spend_segments(args, extras, tc));
+
     if !args.no_usage_five_hour
         && let Some(usage_seg) = render_usage_segment_variants(
             &hook.model.id,
@@ -1605,6 +2188,19 @@ fn render_compact_text_output(
         segments.push(usage_seg);
     }
 
+    if let Some(auth_seg) = usage_auth_segment(extras, tc) {
+        segments.push(auth_seg);
+    }
+
+    // OpenRouter sessions have no usage limits; credits take their place
+    if let Some(or_seg) = extras
+        .openrouter
+        .as_ref()
+        .and_then(|credits| openrouter_segment(credits, tc))
+    {
+        segments.push(or_seg);
+    }
+
     if !args.no_context_tokens || !args.no_context_percent {
         segments.push(render_context_segment_variants(
             &hook.model.id,
@@ -1621,33 +2217,91 @@ fn render_compact_text_output(
     fit_status_segments(&prompt, segments, &separator, profile.safe_width)
 }
 
-/// Currency symbol for the extra-usage token; mirrors the Claude Code
-/// formatter's symbol map and falls back to the ISO code for the rest.
-fn extra_usage_symbol(currency: Option<&str>) -> String {
-    match currency.unwrap_or("USD") {
-        "USD" => SYM_DOLLAR.to_string(),
-        "EUR" => "€".to_string(),
-        "GBP" => "£".to_string(),
-        "JPY" => "¥".to_string(),
-        "BRL" => "R$".to_string(),
-        "CAD" => "CA$".to_string(),
-        "AUD" => "AU$".to_string(),
-        "NZD" => "NZ$".to_string(),
-        "SGD" => "SG$".to_string(),
-        other => format!("{other} "),
+/// Month-to-date, forecast, subscription value, environment, and budget
+/// segments, shared by the rich and compact renderers.
+fn spend_segments(args: &Args, extras: &OutputExtras, tc: bool) -> Vec<StatusSegment> {
+    let currency = extras.currency.as_ref();
+    let mut segments = Vec::new();
+
+    if args.cost_mtd
+        && let Some(mtd) = extras.mtd_cost
+    {
+        segments.push(labeled_cost_segment(
+            "mtd:",
+            "m:",
+            tokens::PRIMARY_DIM.paint(&format_month_cost(mtd, currency), tc),
+            currency,
+            tc,
+            25,
+        ));
     }
-}
 
-#[allow(clippy::too_many_arguments)]
-fn render_rich_text_output(
-    args: &Args,
-    model_id: &str,
-    model_display_name: &str,
-    session_cost: f64,
-    today_cost: f64,
-    total_cost: f64,
-    usage_percent: Option<f64>,
-    projected_percent: Option<f64>,
+    if args.cost_forecast
+        && let Some(forecast) = extras.forecast_month
+    {
+        let forecast_str = format_month_cost(forecast, currency);
+        let over_budget = args
+            .budget_monthly
+            .is_some_and(|limit| limit > 0.0 && forecast > limit);
+        let value = if over_budget {
+            tokens::WARNING.bold(&forecast_str, tc)
+        } else {
+            tokens::PRIMARY_DIM.paint(&forecast_str, tc)
+        };
+        segments.push(labeled_cost_segment(
+            "forecast:",
+            "fc:",
+            value,
+            currency,
+            tc,
+            22,
+        ));
+    }
+
+    if let (Some(price), Some(mtd)) = (args.subscription_price_usd(), extras.mtd_cost) {
+        let multiple = mtd / price;
+        let formatted = format!("{multiple:.1}x");
+        let value = if multiple >= 1.0 {
+            tokens::SUCCESS.paint(&formatted, tc)
+        } else {
+            tokens::PRIMARY_DIM.paint(&formatted, tc)
+        };
+        segments.push(adaptive_segment(
+            vec![
+                format!("{}{}", muted_label("value:", tc), value),
+                format!("{}{}", muted_label("v:", tc), value),
+            ],
+            20,
+        ));
+    }
+
+    if let Some(ref estimate) = extras.environment {
+        segments.push(environment_segment(&estimate.session, tc));
+    }
+
+    if let Some(budget_seg) = extras.budget.as_ref().and_then(|b| budget_segment(b, tc)) {
+        segments.push(budget_seg);
+    }
+
+    segments
+}
+
+/// Currency symbol for the extra-usage token. Extra usage is billed in the
+/// account's own currency, so it is never converted.
+fn extra_usage_symbol(currency: Option<&str>) -> String {
+    crate::currency::symbol(currency.unwrap_or("USD"))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_rich_text_output(
+    args: &Args,
+    model_id: &str,
+    model_display_name: &str,
+    session_cost: f64,
+    today_cost: f64,
+    total_cost: f64,
+    usage_percent: Option<f64>,
+    projected_percent: Option<f64>,
     remaining_minutes: f64,
     active_block: Option<&Block>,
     latest_reset: Option<DateTime<chrono::Utc>>,
@@ -1661,10 +2315,12 @@ fn render_rich_text_output(
     usage_limits: Option<&UsageSummary>,
     context_limit_override: Option<u64>,
     prompt_cache: Option<&PromptCacheInfo>,
+    extras: &OutputExtras,
 ) -> String {
     let profile = render_profile();
     let term_width = profile.width;
     let tc = is_truecolor_enabled(args);
+    let currency = extras.currency.as_ref();
     let prompt = tokens::ACCENT.paint(SYM_PROMPT, tc);
     let long_labels = matches!(args.labels, LabelsArg::Long);
     let is_claude = is_direct_claude_api(Some(model_id));
@@ -1677,13 +2333,13 @@ fn render_rich_text_output(
             TerminalWidth::Medium => "sess:",
             TerminalWidth::Wide => "session:",
         };
-        segments.push(cost_segment_variants(
+        segments.push(session_cost_segment(
             session_label,
             "s:",
             session_cost,
-            None,
+            args,
+            currency,
             tc,
-            80,
         ));
     }
 
@@ -1692,14 +2348,47 @@ fn render_rich_text_output(
             TerminalWidth::Narrow => "t:",
             _ => "today:",
         };
-        segments.push(cost_segment_variants(
-            today_label,
-            "t:",
-            today_cost,
-            Some(10.0),
-            tc,
-            30,
-        ));
+        let trend = extras
+            .today_avg_7d
+            .filter(|_| !args.no_cost_today_trend)
+            .map(|avg| today_trend_suffix(today_cost, avg, tc));
+        match trend {
+            Some(trend) => {
+                let cost_str = format_currency(convert(today_cost, currency));
+                let cost_value = tokens::gradient(today_cost, 10.0).paint(&cost_str, tc);
+                segments.push(labeled_cost_segment(
+                    today_label,
+                    "t:",
+                    format!("{cost_value} {trend}"),
+                    currency,
+                    tc,
+                    30,
+                ));
+            }
+            None => segments.push(cost_segment_variants(
+                today_label,
+                "t:",
+                today_cost,
+                Some(10.0),
+                currency,
+                tc,
+                30,
+            )),
+        }
+    }
+
+    segments.extend(spend_segments(args, extras, tc));
+
+    if let Some(auth_seg) = usage_auth_segment(extras, tc) {
+        segments.push(auth_seg);
+    }
+
+    if let Some(or_seg) = extras
+        .openrouter
+        .as_ref()
+        .and_then(|credits| openrouter_segment(credits, tc))
+    {
+        segments.push(or_seg);
     }
 
     if is_claude && !args.no_cost_window {
@@ -1714,6 +2403,7 @@ fn render_rich_text_output(
             "w:",
             total_cost,
             Some(5.0),
+            currency,
             tc,
             40,
         ));
@@ -1754,8 +2444,7 @@ fn render_rich_text_output(
                 let mut text = format!("{}{}", muted_label(label, tc), colorize_percent(pct, args));
                 if let Some(reset) = summary.seven_day.resets_at {
                     let local_reset = reset.with_timezone(&Local);
-                    let now = Local::now();
-                    let hours_until = (reset - now.with_timezone(&chrono::Utc)).num_hours();
+                    let hours_until = (reset - crate::utils::now_utc()).num_hours();
                     let reset_fmt = if hours_until < 24 {
                         if use_12h {
                             if local_reset.minute() == 0 {
@@ -1839,18 +2528,35 @@ fn render_rich_text_output(
         let to = format_tokens(tokens_output);
         let tcc = format_tokens(tokens_cache_create);
         let tcr = format_tokens(tokens_cache_read);
-        segments.push(status_segment(
-            format!(
-                "{}{} {}{} {}{}",
-                muted_label("tok:", tc),
-                tokens::PRIMARY_DIM.paint(&format!("{}/{}", ti, to), tc),
-                muted_label("cache:", tc),
-                tokens::PRIMARY_DIM.paint(&format!("{}/{}", tcc, tcr), tc),
-                muted_label("ws:", tc),
-                tokens::PRIMARY_DIM.paint(&web_search_requests.to_string(), tc)
-            ),
-            5,
-        ));
+        let mut breakdown = format!(
+            "{}{} {}{} {}{}",
+            muted_label("tok:", tc),
+            tokens::PRIMARY_DIM.paint(&format!("{}/{}", ti, to), tc),
+            muted_label("cache:", tc),
+            tokens::PRIMARY_DIM.paint(&format!("{}/{}", tcc, tcr), tc),
+            muted_label("ws:", tc),
+            tokens::PRIMARY_DIM.paint(&web_search_requests.to_string(), tc)
+        );
+        if extras.batch_cost > 0.0 {
+            breakdown.push_str(&format!(
+                " {}{}",
+                muted_label("batch:", tc),
+                tokens::PRIMARY_DIM.paint(&money(extras.batch_cost, currency), tc)
+            ));
+        }
+        // Batch spend is already itemized above.
+        if let Some((ref tier, adjustment)) = extras.tier_adjustment
+            && tier != "batch"
+        {
+            let sign = if adjustment < 0.0 { "-" } else { "+" };
+            breakdown.push_str(&format!(
+                " {}{}",
+                muted_label(&format!("{tier}:"), tc),
+                tokens::PRIMARY_DIM
+                    .paint(&format!("{sign}{}", money(adjustment.abs(), currency)), tc)
+            ));
+        }
+        segments.push(status_segment(breakdown, 5));
     }
 
     if !args.no_integrations_prompt_cache
@@ -1909,6 +2615,7 @@ pub fn print_text_output(
     context_limit_override: Option<u64>,
     cost_provenance: Option<&CostProvenance>,
     prompt_cache: Option<&PromptCacheInfo>,
+    extras: &OutputExtras,
 ) {
     let profile = render_profile();
     let mut line = if profile.mode == RenderMode::Compact {
@@ -1926,6 +2633,7 @@ pub fn print_text_output(
             lines_delta,
             usage_limits,
             context_limit_override,
+            extras,
         )
     } else {
         let _ = lines_delta;
@@ -1951,6 +2659,7 @@ pub fn print_text_output(
             usage_limits,
             context_limit_override,
             prompt_cache,
+            extras,
         )
     };
 
@@ -1987,6 +2696,9 @@ mod tests {
     use serial_test::serial;
 
     use crate::models::PromptCacheBucketInfo;
+    use crate::models::git::{
+        GitCi, GitDescribe, GitOperation, GitOperationKind, GitPullRequest, Vcs,
+    };
     use crate::models::hook::{
         HookContextWindow, HookCost, HookJson, HookModel, HookThinking, HookWorkspace, OutputStyle,
     };
@@ -2022,8 +2734,8 @@ mod tests {
                 total_cost_usd: 0.0,
                 total_duration_ms: 0,
                 total_api_duration_ms: 0,
-                total_lines_added: 0,
-                total_lines_removed: 0,
+                total_lines_added: Some(0),
+                total_lines_removed: Some(0),
             },
             context_window: HookContextWindow {
                 total_input_tokens: 0,
@@ -2105,6 +2817,60 @@ mod tests {
         ])
     }
 
+    /// Rich line at 320 columns for a $3 session on a $5 day; only `args` and
+    /// `extras` vary between the tests that use it.
+    fn rich_line(args: &Args, extras: &OutputExtras) -> String {
+        let env = terminal_env_guard();
+        env.force_dimensions("320", "32");
+        render_rich_text_output(
+            args,
+            "claude-opus-4-7",
+            "Opus 4.7",
+            3.0,
+            5.0,
+            5.0,
+            None,
+            None,
+            274.0,
+            None,
+            None,
+            0.0,
+            None,
+            0,
+            0,
+            0,
+            0,
+            0,
+            None,
+            None,
+            None,
+            extras,
+        )
+    }
+
+    /// The same session as [`rich_line`], rendered compact at 120 columns.
+    fn compact_line(args: &Args, extras: &OutputExtras) -> String {
+        let env = terminal_env_guard();
+        env.force_dimensions("120", "32");
+        assert_eq!(render_profile().mode, RenderMode::Compact);
+        render_compact_text_output(
+            &test_hook(vec![], None),
+            None,
+            args,
+            false,
+            3.0,
+            None,
+            274.0,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            extras,
+        )
+    }
+
     #[test]
     fn render_profile_prefers_compact_for_claude_safe_fit() {
         assert_eq!(
@@ -2162,15 +2928,42 @@ mod tests {
 
         let hook = test_hook(vec!["/tmp/project/docs"], Some("hook-wt"));
         let git_info = GitInfo {
+            vcs: Vcs::Git,
+            root: None,
+            subproject: None,
             branch: Some("feature/footer".to_string()),
             short_commit: Some("abc1234".to_string()),
+            head_age_secs: None,
+            commit: None,
             is_clean: Some(false),
             ahead: Some(1),
             behind: Some(0),
+            upstream: None,
             remote_url: None,
             is_head_on_remote: None,
             worktree_count: Some(2),
             is_linked_worktree: Some(true),
+            worktrees: Vec::new(),
+            health: Vec::new(),
+            stash_count: None,
+            untracked: None,
+            lines_added: None,
+            lines_removed: None,
+            conflicts: None,
+            sparse_checkout: None,
+            lfs_pending: None,
+            submodules: None,
+            submodules_dirty: None,
+            operation: None,
+            describe: None,
+            pull_request: None,
+            ci: None,
+            issues: Vec::new(),
+            default_branch: None,
+            default_ahead: None,
+            default_behind: None,
+            protected: false,
+            stale_age_secs: None,
         };
 
         let line = render_compact_text_output(
@@ -2187,6 +2980,7 @@ mod tests {
             Some((8, 3)),
             None,
             Some(200_000),
+            &OutputExtras::default(),
         );
 
         assert!(!line.contains('\n'));
@@ -2198,6 +2992,371 @@ mod tests {
         assert!(line.contains("fast"));
     }
 
+    #[test]
+    fn git_segment_marks_sparse_checkout_and_pending_lfs() {
+        let git_info = GitInfo {
+            branch: Some("main".to_string()),
+            short_commit: Some("abc1234".to_string()),
+            is_clean: Some(true),
+            sparse_checkout: Some(true),
+            lfs_pending: Some(4),
+            ..Default::default()
+        };
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &test_args(),
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert_eq!(strip_ansi(&seg), "main@abc1234 sparse lfs:4");
+
+        let mut args = test_args();
+        args.git_fields = vec![GitFieldArg::Branch, GitFieldArg::Sparse];
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &args,
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert_eq!(strip_ansi(&seg), "main sparse");
+    }
+
+    #[test]
+    fn git_segment_flags_health_issues_when_enabled() {
+        use crate::models::git::{GitHealthIssue, GitHealthKind};
+        let git_info = GitInfo {
+            branch: Some("main".to_string()),
+            is_clean: Some(true),
+            health: vec![GitHealthIssue {
+                kind: GitHealthKind::UnignoredBuildDir,
+                path: "target".to_string(),
+                size: 12,
+            }],
+            ..Default::default()
+        };
+        let render = |args: &Args| {
+            let seg = build_git_status_segment(
+                Some(&git_info),
+                args,
+                false,
+                TerminalWidth::Wide,
+                None,
+                false,
+            )
+            .expect("git segment");
+            strip_ansi(&seg)
+        };
+        let mut args = test_args();
+        assert_eq!(render(&args), "main");
+        args.git_health = true;
+        assert_eq!(render(&args), "main ⚠");
+    }
+
+    #[test]
+    fn git_segment_shows_sapling_commits_without_detached_label() {
+        let mut git_info = GitInfo {
+            vcs: Vcs::Sapling,
+            short_commit: Some("abc1234".to_string()),
+            ..Default::default()
+        };
+        let render = |git_info: &GitInfo| {
+            let seg = build_git_status_segment(
+                Some(git_info),
+                &test_args(),
+                false,
+                TerminalWidth::Wide,
+                None,
+                false,
+            )
+            .expect("git segment");
+            strip_ansi(&seg)
+        };
+        assert_eq!(render(&git_info), "abc1234");
+        git_info.vcs = Vcs::Git;
+        assert_eq!(render(&git_info), "detached@abc1234");
+    }
+
+    #[test]
+    fn git_segment_shows_stash_count_when_nonzero() {
+        let mut git_info = GitInfo {
+            branch: Some("main".to_string()),
+            short_commit: Some("abc1234".to_string()),
+            is_clean: Some(true),
+            ahead: Some(0),
+            behind: Some(0),
+            upstream: Some("origin/main".to_string()),
+            stash_count: Some(2),
+            ..Default::default()
+        };
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &test_args(),
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert!(strip_ansi(&seg).ends_with(" ⚑2"), "{seg}");
+
+        git_info.untracked = Some(3);
+        git_info.is_clean = Some(false);
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &test_args(),
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert!(strip_ansi(&seg).starts_with("main@abc1234*?3 "), "{seg}");
+
+        git_info.operation = Some(GitOperation {
+            kind: GitOperationKind::Rebase,
+            step: Some(2),
+            total: Some(7),
+        });
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &test_args(),
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert!(strip_ansi(&seg).starts_with("REBASE 2/7 main@"), "{seg}");
+        git_info.operation = None;
+
+        git_info.ci = Some(GitCi {
+            state: GitCiState::Failure,
+            total: 3,
+            failed: 1,
+            pending: 0,
+        });
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &Args::parse_from(["claude_statusline", "--git-ci"]),
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert!(strip_ansi(&seg).starts_with("main@abc1234✗*?3"), "{seg}");
+        git_info.ci = None;
+
+        let fields = Args::parse_from(["claude_statusline", "--git-fields", "branch,dirty,stash"]);
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &fields,
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert_eq!(strip_ansi(&seg), "main* ⚑2");
+
+        git_info.conflicts = Some(3);
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &test_args(),
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert!(strip_ansi(&seg).starts_with("main@abc1234*?3 ✖3 "), "{seg}");
+        git_info.conflicts = Some(0);
+
+        git_info.stash_count = Some(0);
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &test_args(),
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert!(!seg.contains(SYM_STASH), "{seg}");
+        assert!(!seg.contains("origin/main"), "{seg}");
+
+        git_info.ahead = Some(2);
+        let upstream_args = Args::parse_from(["claude_statusline", "--git-upstream"]);
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &upstream_args,
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert!(strip_ansi(&seg).ends_with(" origin/main ↑2"), "{seg}");
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &upstream_args,
+            false,
+            TerminalWidth::Medium,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert!(!seg.contains("origin/main"), "{seg}");
+    }
+
+    #[test]
+    fn head_age_warns_past_threshold() {
+        assert_eq!(strip_ansi(&head_age_label(45 * 60, 48, false)), "45m");
+        assert_eq!(strip_ansi(&head_age_label(5 * 3600 + 59, 48, false)), "5h");
+        assert_eq!(strip_ansi(&head_age_label(3 * 86_400, 48, false)), "3d");
+        assert_eq!(
+            head_age_label(3 * 86_400, 48, true),
+            tokens::WARNING.paint("3d", true)
+        );
+        assert_eq!(
+            head_age_label(3 * 86_400, 96, true),
+            muted_label("3d", true)
+        );
+    }
+
+    #[test]
+    fn pull_request_segment_shows_review_and_merge_state() {
+        let mut git_info = GitInfo::default();
+        assert!(pull_request_segment(Some(&git_info), false, 45).is_none());
+
+        let mut pr = GitPullRequest {
+            number: 42,
+            url: "https://github.com/o/r/pull/42".to_string(),
+            title: "Add thing".to_string(),
+            state: "OPEN".to_string(),
+            is_draft: false,
+            review_decision: Some("APPROVED".to_string()),
+            mergeable: Some("CONFLICTING".to_string()),
+        };
+        git_info.pull_request = Some(pr.clone());
+        let seg = pull_request_segment(Some(&git_info), false, 45).expect("pr segment");
+        let variants: Vec<String> = seg.variants.iter().map(|v| strip_ansi(v)).collect();
+        assert_eq!(variants, ["PR#42 approved conflicts", "PR#42"]);
+
+        pr.state = "MERGED".to_string();
+        git_info.pull_request = Some(pr);
+        let seg = pull_request_segment(Some(&git_info), false, 45).expect("pr segment");
+        assert_eq!(strip_ansi(&seg.variants[0]), "PR#42 merged");
+    }
+
+    #[test]
+    #[cfg(feature = "colors")]
+    fn protected_branch_renders_as_warning() {
+        let mut git_info = GitInfo {
+            branch: Some("main".to_string()),
+            protected: true,
+            ..Default::default()
+        };
+        let args = test_args();
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &args,
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert!(seg.contains(&tokens::WARNING.bold("main", false)));
+
+        let quiet = Args::parse_from(["claude_statusline", "--no-git-protected-warning"]);
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &quiet,
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert!(seg.contains(&tokens::PRIMARY.paint("main", false)));
+
+        git_info.protected = false;
+        let seg = build_git_status_segment(
+            Some(&git_info),
+            &args,
+            false,
+            TerminalWidth::Wide,
+            None,
+            false,
+        )
+        .expect("git segment");
+        assert!(!seg.contains(&tokens::WARNING.bold("main", false)));
+    }
+
+    #[test]
+    fn issues_segment_links_keys_and_narrows_to_first() {
+        let mut git_info = GitInfo::default();
+        assert!(issues_segment(Some(&git_info), false, 50).is_none());
+
+        git_info.issues = vec![
+            GitIssue {
+                key: "ENG-456".to_string(),
+                url: Some("https://linear.app/acme/issue/ENG-456".to_string()),
+            },
+            GitIssue {
+                key: "#789".to_string(),
+                url: None,
+            },
+        ];
+        let seg = issues_segment(Some(&git_info), true, 50).expect("issues segment");
+        assert!(seg.variants[0].contains("\x1b]8;;https://linear.app/acme/issue/ENG-456\x1b\\"));
+        let variants: Vec<String> = seg.variants.iter().map(|v| strip_ansi(v)).collect();
+        assert_eq!(variants, ["ENG-456 #789", "ENG-456"]);
+        assert_eq!(visible_width(&seg.variants[1]), "ENG-456".len());
+
+        let plain = issues_segment(Some(&git_info), false, 50).expect("issues segment");
+        assert!(plain.variants.iter().all(|v| !v.contains("\x1b]8")));
+        let variants: Vec<String> = plain.variants.iter().map(|v| strip_ansi(v)).collect();
+        assert_eq!(variants, ["ENG-456 #789", "ENG-456"]);
+    }
+
+    #[test]
+    fn subproject_segment_prefixes_repo_name() {
+        let hook = test_hook(Vec::new(), None);
+        let mut git_info = GitInfo {
+            root: Some("/work/monorepo".to_string()),
+            ..Default::default()
+        };
+        assert!(subproject_segment(&hook, Some(&git_info), false, 60).is_none());
+
+        git_info.subproject = Some("packages/api".to_string());
+        let seg = subproject_segment(&hook, Some(&git_info), false, 60).expect("subproject");
+        let variants: Vec<String> = seg.variants.iter().map(|v| strip_ansi(v)).collect();
+        assert_eq!(variants, ["monorepo:packages/api", "packages/api"]);
+    }
+
+    #[test]
+    fn describe_segment_shortens_to_tag_and_distance() {
+        let mut git_info = GitInfo::default();
+        assert!(describe_segment(Some(&git_info), false, 35).is_none());
+
+        git_info.describe = Some(GitDescribe {
+            tag: "v1.4.2".to_string(),
+            distance: 12,
+            text: "v1.4.2-12-gabc1234".to_string(),
+        });
+        let seg = describe_segment(Some(&git_info), false, 35).expect("describe segment");
+        let variants: Vec<String> = seg.variants.iter().map(|v| strip_ansi(v)).collect();
+        assert_eq!(variants, ["v1.4.2-12-gabc1234", "v1.4.2+12"]);
+    }
+
     #[test]
     #[serial]
     fn compact_line_fits_safe_width_from_columns() {
@@ -2213,15 +3372,42 @@ mod tests {
             Some("very-long-worktree-name-that-would-overflow"),
         );
         let git_info = GitInfo {
+            vcs: Vcs::Git,
+            root: None,
+            subproject: None,
             branch: Some("feature/very-long-responsive-statusline-branch".to_string()),
             short_commit: Some("abc1234".to_string()),
+            head_age_secs: None,
+            commit: None,
             is_clean: Some(false),
             ahead: Some(4),
             behind: Some(1),
+            upstream: None,
             remote_url: None,
             is_head_on_remote: None,
             worktree_count: Some(2),
             is_linked_worktree: Some(true),
+            worktrees: Vec::new(),
+            health: Vec::new(),
+            stash_count: None,
+            untracked: None,
+            lines_added: None,
+            lines_removed: None,
+            conflicts: None,
+            sparse_checkout: None,
+            lfs_pending: None,
+            submodules: None,
+            submodules_dirty: None,
+            operation: None,
+            describe: None,
+            pull_request: None,
+            ci: None,
+            issues: Vec::new(),
+            default_branch: None,
+            default_ahead: None,
+            default_behind: None,
+            protected: false,
+            stale_age_secs: None,
         };
 
         let line = render_compact_text_output(
@@ -2238,6 +3424,7 @@ mod tests {
             Some((8, 3)),
             None,
             Some(200_000),
+            &OutputExtras::default(),
         );
         let profile = render_profile();
         let plain = strip_ansi(&line);
@@ -2312,6 +3499,7 @@ mod tests {
             None,
             None,
             Some(200_000),
+            &OutputExtras::default(),
         );
         let profile = render_profile();
         let plain = strip_ansi(&line);
@@ -2361,6 +3549,7 @@ mod tests {
             None,
             None,
             Some(200_000),
+            &OutputExtras::default(),
         );
         let plain = strip_ansi(&line);
 
@@ -2379,15 +3568,42 @@ mod tests {
             Some("hook-wt"),
         );
         let git_info = GitInfo {
+            vcs: Vcs::Git,
+            root: None,
+            subproject: None,
             branch: Some("feature/footer".to_string()),
             short_commit: Some("abc1234".to_string()),
+            head_age_secs: None,
+            commit: None,
             is_clean: Some(true),
             ahead: Some(0),
             behind: Some(0),
+            upstream: None,
             remote_url: None,
             is_head_on_remote: None,
             worktree_count: Some(2),
             is_linked_worktree: Some(true),
+            worktrees: Vec::new(),
+            health: Vec::new(),
+            stash_count: None,
+            untracked: None,
+            lines_added: None,
+            lines_removed: None,
+            conflicts: None,
+            sparse_checkout: None,
+            lfs_pending: None,
+            submodules: None,
+            submodules_dirty: None,
+            operation: None,
+            describe: None,
+            pull_request: None,
+            ci: None,
+            issues: Vec::new(),
+            default_branch: None,
+            default_ahead: None,
+            default_behind: None,
+            protected: false,
+            stale_age_secs: None,
         };
 
         let line = render_header_line(
@@ -2398,6 +3614,7 @@ mod tests {
             None,
             None,
             None,
+            None,
             Some(200_000),
             false,
         )
@@ -2420,15 +3637,42 @@ mod tests {
             "/tmp/project/.claude/worktrees/topic+sample-worktree".to_string();
 
         let git_info = GitInfo {
+            vcs: Vcs::Git,
+            root: None,
+            subproject: None,
             branch: Some("topic/sample-worktree".to_string()),
             short_commit: Some("abc1234".to_string()),
+            head_age_secs: None,
+            commit: None,
             is_clean: Some(false),
             ahead: Some(3),
             behind: Some(0),
+            upstream: None,
             remote_url: None,
             is_head_on_remote: None,
             worktree_count: Some(2),
             is_linked_worktree: Some(true),
+            worktrees: Vec::new(),
+            health: Vec::new(),
+            stash_count: None,
+            untracked: None,
+            lines_added: None,
+            lines_removed: None,
+            conflicts: None,
+            sparse_checkout: None,
+            lfs_pending: None,
+            submodules: None,
+            submodules_dirty: None,
+            operation: None,
+            describe: None,
+            pull_request: None,
+            ci: None,
+            issues: Vec::new(),
+            default_branch: None,
+            default_ahead: None,
+            default_behind: None,
+            protected: false,
+            stale_age_secs: None,
         };
 
         let line = render_header_line(
@@ -2436,6 +3680,7 @@ mod tests {
             Some(&git_info),
             &test_args(),
             None,
+            None,
             Some((12, 4)),
             None,
             None,
@@ -2496,6 +3741,7 @@ mod tests {
             Some(&summary),
             Some(1_000_000),
             None,
+            &OutputExtras::default(),
         );
 
         assert!(line.contains("session:"));
@@ -2507,6 +3753,399 @@ mod tests {
         assert!(line.contains("context:"));
         assert!(line.contains("1M"));
         assert!(line.contains("13%"));
+        assert!(!line.contains("budget:"));
+    }
+
+    #[test]
+    #[serial]
+    fn rich_output_shows_tightest_budget_percent() {
+        use crate::budget::{BudgetLine, BudgetPeriod};
+
+        let extras = OutputExtras {
+            budget: Some(BudgetStatus {
+                lines: vec![
+                    BudgetLine {
+                        period: BudgetPeriod::Daily,
+                        limit: 25.0,
+                        spent: 5.0,
+                    },
+                    BudgetLine {
+                        period: BudgetPeriod::Monthly,
+                        limit: 300.0,
+                        spent: 192.0,
+                    },
+                ],
+            }),
+            ..OutputExtras::default()
+        };
+
+        let line = rich_line(&test_args(), &extras);
+        assert!(strip_ansi(&line).contains("budget:64%"));
+        assert!(!strip_ansi(&line).contains("mtd:"));
+        assert!(strip_ansi(&compact_line(&test_args(), &extras)).contains("budget:64%"));
+
+        let mut json = serde_json::json!({});
+        apply_json_extras(&mut json, &extras);
+        assert_eq!(json["budget"]["percent"], 64.0);
+        assert_eq!(json["budget"]["tightest"], "monthly");
+        assert_eq!(json["budget"]["daily"]["spent_usd"], 5.0);
+        assert_eq!(json["budget"]["monthly"]["over"], false);
+    }
+
+    #[test]
+    #[serial]
+    fn rejected_token_shows_auth_hint() {
+        let extras = OutputExtras {
+            usage_limits_error: Some(UsageApiError {
+                kind: "token_invalid",
+                status: 401,
+                retry_at: None,
+            }),
+            ..OutputExtras::default()
+        };
+
+        let line = rich_line(&test_args(), &extras);
+        assert!(strip_ansi(&line).contains("auth⚠"));
+        let compact = compact_line(&test_args(), &extras);
+        assert!(strip_ansi(&compact).contains("auth⚠"));
+
+        let mut json = serde_json::json!({});
+        apply_json_extras(&mut json, &extras);
+        assert_eq!(json["usage_limits_error"]["kind"], "token_invalid");
+        assert_eq!(json["usage_limits_error"]["status"], 401);
+    }
+
+    #[test]
+    #[serial]
+    fn mtd_segment_is_opt_in_and_always_in_json() {
+        let extras = OutputExtras {
+            mtd_cost: Some(142.37),
+            ..OutputExtras::default()
+        };
+
+        assert!(!strip_ansi(&rich_line(&test_args(), &extras)).contains("mtd:"));
+        assert!(!strip_ansi(&compact_line(&test_args(), &extras)).contains("mtd:"));
+        let opted_in = Args::parse_from(["claude_statusline", "--cost-mtd"]);
+        assert!(strip_ansi(&rich_line(&opted_in, &extras)).contains("mtd:$142"));
+        assert!(strip_ansi(&compact_line(&opted_in, &extras)).contains("mtd:$142"));
+
+        let mut json = serde_json::json!({});
+        apply_json_extras(&mut json, &extras);
+        assert_eq!(json["mtd_cost_usd"], 142.37);
+    }
+
+    #[test]
+    #[serial]
+    fn forecast_segment_warns_above_monthly_budget() {
+        let extras = OutputExtras {
+            forecast_month: Some(312.4),
+            ..OutputExtras::default()
+        };
+        let mut args = Args::parse_from(["claude_statusline", "--cost-forecast"]);
+        args.truecolor = true;
+        let under = rich_line(&args, &extras);
+        args.budget_monthly = Some(300.0);
+        let over = rich_line(&args, &extras);
+
+        assert!(strip_ansi(&under).contains("forecast:$312"));
+        assert!(strip_ansi(&compact_line(&args, &extras)).contains("fc:$312"));
+        // Plain builds render both alike
+        if cfg!(feature = "colors") {
+            assert!(!under.contains(&tokens::WARNING.bold("312", true)));
+            assert!(over.contains(&tokens::WARNING.bold("312", true)));
+        }
+
+        let mut json = serde_json::json!({});
+        apply_json_extras(&mut json, &extras);
+        assert_eq!(json["forecast_month_usd"], 312.4);
+    }
+
+    #[test]
+    #[serial]
+    fn environment_json_and_segment() {
+        let footprint = Footprint {
+            energy_wh: 3.214,
+            co2e_g: 1.2856,
+        };
+        let extras = OutputExtras {
+            environment: Some(EnvironmentEstimate {
+                session: footprint,
+                today: footprint,
+                grid_intensity: 400.0,
+                customized: false,
+            }),
+            ..OutputExtras::default()
+        };
+        let mut json = serde_json::json!({});
+        apply_json_extras(&mut json, &extras);
+
+        assert_eq!(json["environment"]["session"]["energy_wh"], 3.21);
+        assert_eq!(json["environment"]["today"]["co2e_g"], 1.29);
+        assert_eq!(json["environment"]["coefficients"], "default");
+
+        let segment = environment_segment(&footprint, false);
+        assert_eq!(strip_ansi(&segment.variants[0]), "env:3.2Wh/1.3g");
+        assert_eq!(strip_ansi(&segment.variants[1]), "co2:1.3g");
+        assert!(strip_ansi(&rich_line(&test_args(), &extras)).contains("env:3.2Wh/1.3g"));
+        assert!(strip_ansi(&compact_line(&test_args(), &extras)).contains("co2:1.3g"));
+    }
+
+    #[test]
+    fn openrouter_credits_render_balance_and_rate_limit() {
+        let credits = OpenRouterCredits {
+            label: Some("work".to_string()),
+            usage: 12.5,
+            limit: Some(20.0),
+            remaining: Some(7.5),
+            is_free_tier: false,
+            rate_limit: Some(crate::openrouter::OpenRouterRateLimit {
+                requests: 10,
+                interval: "10s".to_string(),
+            }),
+        };
+        let segment = openrouter_segment(&credits, false).expect("segment");
+        assert_eq!(strip_ansi(&segment.variants[0]), "or:$7.50 10/10s");
+        assert_eq!(strip_ansi(&segment.variants[1]), "or:$7.50");
+
+        let extras = OutputExtras {
+            openrouter: Some(credits),
+            ..OutputExtras::default()
+        };
+        let mut json = serde_json::json!({});
+        apply_json_extras(&mut json, &extras);
+        assert_eq!(json["openrouter"]["remaining_usd"], 7.5);
+        assert_eq!(json["openrouter"]["rate_limit"]["requests"], 10);
+
+        let unknown = OpenRouterCredits::default();
+        assert!(openrouter_segment(&unknown, false).is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn compact_line_shows_openrouter_credits() {
+        let extras = OutputExtras {
+            openrouter: Some(OpenRouterCredits {
+                remaining: Some(7.5),
+                ..OpenRouterCredits::default()
+            }),
+            ..OutputExtras::default()
+        };
+        let line = compact_line(&test_args(), &extras);
+        assert!(strip_ansi(&line).contains("or:$7.50"));
+    }
+
+    #[test]
+    fn tag_costs_nest_under_today_and_period() {
+        let tag = |name: &str, cost: f64| TagCost {
+            tag: name.to_string(),
+            cost_usd: cost,
+            share: 0.5,
+            projects: 2,
+            billable_usd: Some(cost * 1.25),
+        };
+        let extras = OutputExtras {
+            tag_costs: vec![tag("acme", 3.0)],
+            period_tag_costs: Some(vec![tag("acme", 120.0)]),
+            ..OutputExtras::default()
+        };
+        let mut json = serde_json::json!({ "today": { "cost_usd": 6.0 } });
+        apply_json_extras(&mut json, &extras);
+
+        assert_eq!(json["today"]["by_tag"][0]["tag"], "acme");
+        assert_eq!(json["today"]["by_tag"][0]["billable_usd"], 3.75);
+        assert_eq!(json["mtd_by_tag"][0]["cost_usd"], 120.0);
+        assert_eq!(json["mtd_by_tag"][0]["projects"], 2);
+    }
+
+    #[test]
+    fn today_trend_compares_against_seven_day_average() {
+        assert_eq!(strip_ansi(&today_trend_suffix(13.8, 10.0, true)), "(+38%)");
+        assert_eq!(strip_ansi(&today_trend_suffix(4.0, 10.0, false)), "(-60%)");
+        assert_eq!(
+            today_trend_suffix(13.8, 10.0, true),
+            tokens::WARNING.paint("(+38%)", true)
+        );
+        assert_eq!(
+            today_trend_suffix(10.2, 10.0, true),
+            tokens::MUTED.paint("(+2%)", true)
+        );
+
+        let extras = OutputExtras {
+            today_avg_7d: Some(4.0),
+            ..OutputExtras::default()
+        };
+        let mut json = serde_json::json!({ "today": { "cost_usd": 6.0 } });
+        apply_json_extras(&mut json, &extras);
+        assert_eq!(json["today"]["avg_7d_usd"], 4.0);
+        assert_eq!(json["today"]["vs_avg_7d_percent"], 50.0);
+    }
+
+    #[test]
+    fn cache_report_is_emitted_under_cache() {
+        use crate::provenance::{CacheLayer, CacheLookup};
+        let extras = OutputExtras {
+            cache: Some(CacheReport {
+                hits: 1,
+                misses: 1,
+                layers: vec![
+                    CacheLookup {
+                        cache: "scan",
+                        layer: CacheLayer::Sqlite,
+                        age_seconds: Some(2),
+                    },
+                    CacheLookup {
+                        cache: "usage_api",
+                        layer: CacheLayer::Api,
+                        age_seconds: None,
+                    },
+                ],
+            }),
+            ..OutputExtras::default()
+        };
+        let mut json = serde_json::json!({});
+        apply_json_extras(&mut json, &extras);
+
+        assert_eq!(json["cache"]["hits"], 1);
+        assert_eq!(json["cache"]["misses"], 1);
+        assert_eq!(json["cache"]["layers"][0]["layer"], "sqlite");
+        assert_eq!(json["cache"]["layers"][0]["age_seconds"], 2);
+        assert!(json["cache"]["layers"][1]["age_seconds"].is_null());
+    }
+
+    #[test]
+    fn service_tier_costs_nest_under_window() {
+        let extras = OutputExtras {
+            batch_cost: 1.234,
+            tier_adjustment: Some(("priority".to_string(), 2.5)),
+            ..OutputExtras::default()
+        };
+        let mut json = serde_json::json!({ "window": { "service_tier": "priority" } });
+        apply_json_extras(&mut json, &extras);
+
+        assert_eq!(json["window"]["batch_cost_usd"], 1.234);
+        assert_eq!(json["window"]["tier_adjustment_usd"], 2.5);
+    }
+
+    #[test]
+    fn server_tool_line_items_nest_under_window() {
+        let extras = OutputExtras {
+            server_tool_costs: vec![
+                ServerToolCost {
+                    tool: "web_fetch".to_string(),
+                    requests: 4,
+                    cost_usd: 0.0,
+                },
+                ServerToolCost {
+                    tool: "web_search".to_string(),
+                    requests: 3,
+                    cost_usd: 0.03,
+                },
+            ],
+            ..OutputExtras::default()
+        };
+        let mut json = serde_json::json!({ "window": { "web_search_requests": 3 } });
+        apply_json_extras(&mut json, &extras);
+
+        assert_eq!(json["window"]["server_tools"][1]["tool"], "web_search");
+        assert_eq!(json["window"]["server_tools"][1]["cost_usd"], 0.03);
+        assert_eq!(json["window"]["server_tools"][0]["requests"], 4);
+        assert!(json["window"].get("batch_cost_usd").is_none());
+        assert!(json["window"].get("tier_adjustment_usd").is_none());
+        assert_eq!(json["window"]["server_tool_cost_usd"], 0.03);
+    }
+
+    #[test]
+    fn top_projects_nest_under_today() {
+        let extras = OutputExtras {
+            top_projects: vec![ProjectCost {
+                name: "-root-crate".to_string(),
+                cost_usd: 4.256,
+                share: 0.6667,
+                tag: None,
+                billable_usd: None,
+            }],
+            ..OutputExtras::default()
+        };
+        let mut json = serde_json::json!({ "today": { "cost_usd": 6.38 } });
+        apply_json_extras(&mut json, &extras);
+
+        assert_eq!(json["today"]["top_projects"][0]["name"], "-root-crate");
+        assert_eq!(json["today"]["top_projects"][0]["cost_usd"], 4.26);
+        assert_eq!(json["today"]["top_projects"][0]["share"], 0.667);
+        assert!(json["today"].get("by_model").is_none());
+    }
+
+    #[test]
+    fn git_json_keeps_every_key_outside_a_repository() {
+        let absent = git_json(None);
+        let present = git_json(Some(GitInfo {
+            branch: Some("main".to_string()),
+            protected: true,
+            ..Default::default()
+        }));
+
+        let keys =
+            |v: &serde_json::Value| v.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+        assert_eq!(keys(&absent), keys(&present));
+        assert!(absent["vcs"].is_null() && absent["protected"].is_null());
+        assert_eq!(absent["stale"], false);
+        assert_eq!(present["vcs"], "git");
+        assert_eq!(present["branch"], "main");
+        assert_eq!(present["protected"], true);
+    }
+
+    #[test]
+    fn daily_costs_json_is_dense_and_ends_today() {
+        let day = |d: u32| chrono::NaiveDate::from_ymd_opt(2025, 10, d).unwrap();
+        let history = vec![
+            DailyCost {
+                date: day(1),
+                cost: 9.0,
+            },
+            DailyCost {
+                date: day(10),
+                cost: 4.256,
+            },
+            DailyCost {
+                date: day(15),
+                cost: 1.5,
+            },
+        ];
+
+        let json = daily_costs_json(&history, day(15));
+        let days = json.as_array().unwrap();
+
+        assert_eq!(days.len(), 14);
+        assert_eq!(days[0]["date"], "2025-10-02");
+        assert_eq!(days[8]["date"], "2025-10-10");
+        assert_eq!(days[8]["cost_usd"], 4.26);
+        assert_eq!(days[9]["cost_usd"], 0.0);
+        assert_eq!(days[13]["date"], "2025-10-15");
+        assert_eq!(days[13]["cost_usd"], 1.5);
+    }
+
+    #[test]
+    #[serial]
+    fn subscription_value_compares_mtd_against_plan_price() {
+        let extras = OutputExtras {
+            mtd_cost: Some(620.0),
+            ..OutputExtras::default()
+        };
+        let args = Args::parse_from(["claude_statusline", "--subscription", "max5x"]);
+
+        let line = rich_line(&args, &extras);
+        assert!(strip_ansi(&line).contains("value:6.2x"));
+        assert!(strip_ansi(&compact_line(&args, &extras)).contains("value:6.2x"));
+
+        let mut json = serde_json::json!({});
+        apply_subscription_value(&mut json, &extras, &args);
+        assert_eq!(json["subscription"]["plan"], "max5x");
+        assert_eq!(json["subscription"]["price_usd"], 100.0);
+        assert_eq!(json["subscription"]["value_multiple"], 6.2);
+
+        let mut without_plan = serde_json::json!({});
+        apply_subscription_value(&mut without_plan, &extras, &test_args());
+        assert!(without_plan.get("subscription").is_none());
     }
 
     #[test]
@@ -2565,6 +4204,25 @@ mod tests {
         assert!(!segment.contains("hit:"));
         assert!(!segment.contains("age:"));
     }
+
+    #[test]
+    fn session_cost_cap_sets_over_cap_in_json() {
+        let args = Args::parse_from(["claude_statusline", "--session-cost-cap", "15"]);
+        let mut json = serde_json::json!({ "session": { "cost_usd": 16.5 } });
+
+        apply_session_cost_cap(&mut json, 16.5, &args);
+
+        assert_eq!(json["session"]["cost_cap_usd"], 15.0);
+        assert_eq!(json["session"]["over_cap"], true);
+
+        let mut under = serde_json::json!({ "session": { "cost_usd": 4.0 } });
+        apply_session_cost_cap(&mut under, 4.0, &args);
+        assert_eq!(under["session"]["over_cap"], false);
+
+        let mut uncapped = serde_json::json!({ "session": { "cost_usd": 40.0 } });
+        apply_session_cost_cap(&mut uncapped, 40.0, &test_args());
+        assert!(uncapped["session"].get("over_cap").is_none());
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -2674,33 +4332,6 @@ pub fn build_json_output(
         (None, None)
     };
 
-    // Git json fields (present even if nulls to keep schema stable)
-    let (
-        git_branch,
-        git_short,
-        git_clean,
-        git_ahead,
-        git_behind,
-        git_on_remote,
-        git_remote_url,
-        git_wt_count,
-        git_is_wt,
-    ) = if let Some(gi) = git_info {
-        (
-            gi.branch,
-            gi.short_commit,
-            gi.is_clean,
-            gi.ahead,
-            gi.behind,
-            gi.is_head_on_remote,
-            gi.remote_url,
-            gi.worktree_count,
-            gi.is_linked_worktree,
-        )
-    } else {
-        (None, None, None, None, None, None, None, None, None)
-    };
-
     let block_json = serde_json::json!({
         "cost_usd": (total_cost * 100.0).round() / 100.0,
         "total_tokens": (total_tokens as u64),
@@ -2844,17 +4475,7 @@ pub fn build_json_output(
             "overage_resets_at": rl.overage_resets_at.map(|d| d.to_rfc3339()),
             "is_using_overage": rl.is_using_overage,
         })),
-        "git": {
-            "branch": git_branch,
-            "short_commit": git_short,
-            "is_clean": git_clean,
-            "ahead": git_ahead,
-            "behind": git_behind,
-            "is_head_on_remote": git_on_remote,
-            "remote_url": git_remote_url,
-            "worktree_count": git_wt_count,
-            "is_linked_worktree": git_is_wt
-        },
+        "git": git_json(git_info),
         "session_name": hook.session_name.clone(),
         "exceeds_200k_tokens": hook.exceeds_200k_tokens,
         "vim": hook.vim.as_ref().map(|v| serde_json::json!({"mode": v.mode.clone()})),
@@ -2911,6 +4532,16 @@ pub fn build_json_output(
             },
             "total_open": b.total_open,
             "epic_count": b.epic_count,
+            "blocked_by_deps": b.blocked_by_deps,
+            "ready": b.ready,
+            "stale": b.stale.iter().map(|s| serde_json::json!({
+                "id": s.id.clone(),
+                "title": s.title.clone(),
+                "status": s.status.as_str(),
+                "idle_seconds": s.idle_secs
+            })).collect::<Vec<_>>(),
+            "opened_today": b.opened_today,
+            "closed_today": b.closed_today,
             "top_labels": b.top_labels.clone()
         })),
         "gastown": gastown_info.map(|gt| serde_json::json!({
@@ -2925,16 +4556,13 @@ pub fn build_json_output(
             })),
             "mail": gt.mail.as_ref().map(|m| serde_json::json!({
                 "unread_count": m.unread_count,
-                "preview": m.preview.clone()
+                "preview": m.preview.clone(),
+                "oldest_age_seconds": m.oldest_age_secs
             })),
             "hooked_issue": gt.hooked_issue.clone(),
             "rigs": gt.rigs.iter().map(|r| serde_json::json!({
                 "name": r.name.clone(),
-                "status": match r.status {
-                    crate::models::RigStatus::Active => "active",
-                    crate::models::RigStatus::Partial => "partial",
-                    crate::models::RigStatus::Inactive => "inactive",
-                },
+                "status": r.status.as_str(),
                 "led": r.status.led(),
                 "polecat_count": r.polecat_count,
                 "crew_count": r.crew_count,
@@ -2945,6 +4573,10 @@ pub fn build_json_output(
             "refinery_queue": gt.refinery_queue.as_ref().map(|q| serde_json::json!({
                 "current": q.current.clone(),
                 "pending": q.pending
+            })),
+            "convoys": gt.convoys.as_ref().map(|c| serde_json::json!({
+                "active": c.active,
+                "oldest_age_seconds": c.oldest_age_secs
             }))
         }))
     });
@@ -2959,6 +4591,373 @@ pub fn build_json_output(
     json
 }
 
+/// Sections computed by the caller from scanned entries and the db ledger.
+/// Rendered as extra text segments and merged into the JSON payload after
+/// `build_json_output`.
+#[derive(Default)]
+pub struct OutputExtras {
+    /// Recent 5-hour blocks, including inactivity gaps, oldest first
+    pub blocks: Vec<Block>,
+    /// Spend against configured budgets; `None` when no budget applies
+    pub budget: Option<BudgetStatus>,
+    /// Month-to-date cost across all sessions; `None` without the db ledger
+    pub mtd_cost: Option<f64>,
+    /// Projected end-of-month cost across all sessions; `None` without the db ledger
+    pub forecast_month: Option<f64>,
+    /// Recorded per-day global costs, oldest first; empty without the db ledger
+    pub daily_history: Vec<DailyCost>,
+    /// Most expensive projects today from the scanned entries
+    pub top_projects: Vec<ProjectCost>,
+    /// Today's cost and tokens per model from the scanned entries
+    pub by_model: Vec<ModelCost>,
+    /// Today's cost per client tag; empty when no `[tags]` are configured
+    pub tag_costs: Vec<TagCost>,
+    /// Billing-period-to-date cost per client tag from the db ledger
+    pub period_tag_costs: Option<Vec<TagCost>>,
+    /// Today and session costs against the persisted history
+    pub cost_stats: Option<CostStats>,
+    /// Ratios derived from the window's token totals and cost
+    pub window_efficiency: Option<Efficiency>,
+    /// Per-tool server tool charges within the window, already in its cost
+    pub server_tool_costs: Vec<ServerToolCost>,
+    /// Window cost billed at batch rates, already in its cost
+    pub batch_cost: f64,
+    /// Active service tier and its cost difference from list price within
+    /// the window; `None` on list-price tiers
+    pub tier_adjustment: Option<(String, f64)>,
+    /// Energy/CO2e estimate; `None` unless `--environment` is set
+    pub environment: Option<EnvironmentEstimate>,
+    /// Average daily cost over the 7 days before today; `None` without the
+    /// db ledger or recorded spend
+    pub today_avg_7d: Option<f64>,
+    /// Cumulative totals from the db; `None` without the db ledger
+    pub lifetime: Option<LifetimeStats>,
+    /// Cache layers that served this render; `None` outside `--json`
+    pub cache: Option<CacheReport>,
+    /// `[accounts]` label the usage limits were fetched for
+    pub account: Option<String>,
+    /// Credit balance and rate limit when the session goes through OpenRouter
+    pub openrouter: Option<OpenRouterCredits>,
+    /// Why the usage limits are missing or stale, e.g. a rejected token
+    pub usage_limits_error: Option<UsageApiError>,
+    /// Currency costs are shown in; `None` for USD
+    pub currency: Option<DisplayCurrency>,
+}
+
+fn tag_costs_json(tags: &[TagCost]) -> serde_json::Value {
+    tags.iter()
+        .map(|t| {
+            let mut entry = serde_json::json!({
+                "tag": t.tag,
+                "cost_usd": (t.cost_usd * 100.0).round() / 100.0,
+                "share": (t.share * 1000.0).round() / 1000.0,
+                "projects": t.projects,
+            });
+            if let Some(billable) = t.billable_usd {
+                entry["billable_usd"] = serde_json::json!((billable * 100.0).round() / 100.0);
+            }
+            entry
+        })
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// Days emitted in `history.daily_costs`.
+const HISTORY_DAYS: i64 = 14;
+
+/// Dense per-day costs ending today (local), zero-filling days without activity
+/// so consumers can index the array directly for sparklines.
+fn daily_costs_json(history: &[DailyCost], today: chrono::NaiveDate) -> serde_json::Value {
+    (0..HISTORY_DAYS)
+        .rev()
+        .map(|offset| {
+            let date = today - chrono::Duration::days(offset);
+            let cost = history
+                .iter()
+                .find(|d| d.date == date)
+                .map_or(0.0, |d| d.cost);
+            serde_json::json!({
+                "date": date.format("%Y-%m-%d").to_string(),
+                "cost_usd": (cost * 100.0).round() / 100.0,
+            })
+        })
+        .collect()
+}
+
+fn block_json(block: &Block) -> serde_json::Value {
+    serde_json::json!({
+        "start": block.start.to_rfc3339(),
+        "end": block.end.to_rfc3339(),
+        "actual_end": block.actual_end.to_rfc3339(),
+        "is_active": block.is_active,
+        "is_gap": block.is_gap,
+        "entries": block.entries.len(),
+        "cost_usd": (block.cost * 100.0).round() / 100.0,
+        "tokens": {
+            "input": block.tokens.input,
+            "output": block.tokens.output,
+            "cache_create": block.tokens.cache_create,
+            "cache_read": block.tokens.cache_read,
+        },
+    })
+}
+
+fn budget_json(status: &BudgetStatus) -> serde_json::Value {
+    let mut budget = serde_json::Map::new();
+    for line in &status.lines {
+        budget.insert(
+            line.period.as_str().to_string(),
+            serde_json::json!({
+                "limit_usd": line.limit,
+                "spent_usd": (line.spent * 100.0).round() / 100.0,
+                "percent": (line.percent() * 10.0).round() / 10.0,
+                "over": line.spent > line.limit,
+            }),
+        );
+    }
+    if let Some(tightest) = status.tightest() {
+        budget.insert(
+            "tightest".to_string(),
+            serde_json::json!(tightest.period.as_str()),
+        );
+        budget.insert(
+            "percent".to_string(),
+            serde_json::json!((tightest.percent() * 10.0).round() / 10.0),
+        );
+    }
+    serde_json::Value::Object(budget)
+}
+
+fn apply_json_extras(json: &mut serde_json::Value, extras: &OutputExtras) {
+    let Some(obj) = json.as_object_mut() else {
+        return;
+    };
+    if !extras.blocks.is_empty() {
+        obj.insert(
+            "blocks".to_string(),
+            extras.blocks.iter().map(block_json).collect(),
+        );
+    }
+    if let Some(ref budget) = extras.budget {
+        obj.insert("budget".to_string(), budget_json(budget));
+    }
+    if let Some(mtd) = extras.mtd_cost {
+        obj.insert(
+            "mtd_cost_usd".to_string(),
+            serde_json::json!((mtd * 100.0).round() / 100.0),
+        );
+    }
+    if let Some(forecast) = extras.forecast_month {
+        obj.insert(
+            "forecast_month_usd".to_string(),
+            serde_json::json!((forecast * 100.0).round() / 100.0),
+        );
+    }
+    if !extras.top_projects.is_empty() {
+        if let Some(today) = obj.get_mut("today").and_then(|v| v.as_object_mut()) {
+            let projects: Vec<serde_json::Value> = extras
+                .top_projects
+                .iter()
+                .map(|p| {
+                    serde_json::json!({
+                        "name": p.name,
+                        "cost_usd": (p.cost_usd * 100.0).round() / 100.0,
+                        "share": (p.share * 1000.0).round() / 1000.0,
+                    })
+                })
+                .collect();
+            today.insert("top_projects".to_string(), projects.into());
+        }
+    }
+    if !extras.by_model.is_empty() {
+        if let Some(today) = obj.get_mut("today").and_then(|v| v.as_object_mut()) {
+            let models: Vec<serde_json::Value> = extras
+                .by_model
+                .iter()
+                .map(|m| {
+                    serde_json::json!({
+                        "model": m.model,
+                        "cost_usd": (m.cost_usd * 100.0).round() / 100.0,
+                        "share": (m.share * 1000.0).round() / 1000.0,
+                        "input_tokens": m.input_tokens,
+                        "output_tokens": m.output_tokens,
+                        "cache_creation_input_tokens": m.cache_creation_input_tokens,
+                        "cache_read_input_tokens": m.cache_read_input_tokens,
+                    })
+                })
+                .collect();
+            today.insert("by_model".to_string(), models.into());
+        }
+    }
+    if let Some(ref estimate) = extras.environment {
+        let footprint = |f: &Footprint| {
+            serde_json::json!({
+                "energy_wh": (f.energy_wh * 100.0).round() / 100.0,
+                "co2e_g": (f.co2e_g * 100.0).round() / 100.0,
+            })
+        };
+        obj.insert(
+            "environment".to_string(),
+            serde_json::json!({
+                "session": footprint(&estimate.session),
+                "today": footprint(&estimate.today),
+                "grid_gco2e_per_kwh": estimate.grid_intensity,
+                "coefficients": if estimate.customized { "custom" } else { "default" },
+            }),
+        );
+    }
+    if let Some(avg) = extras.today_avg_7d
+        && let Some(today) = obj.get_mut("today").and_then(|v| v.as_object_mut())
+    {
+        let today_cost = today
+            .get("cost_usd")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0);
+        today.insert(
+            "avg_7d_usd".to_string(),
+            serde_json::json!((avg * 100.0).round() / 100.0),
+        );
+        today.insert(
+            "vs_avg_7d_percent".to_string(),
+            serde_json::json!((today_vs_average_percent(today_cost, avg) * 10.0).round() / 10.0),
+        );
+    }
+    if !extras.tag_costs.is_empty() {
+        if let Some(today) = obj.get_mut("today").and_then(|v| v.as_object_mut()) {
+            today.insert("by_tag".to_string(), tag_costs_json(&extras.tag_costs));
+        }
+    }
+    if let Some(ref tags) = extras.period_tag_costs {
+        obj.insert("mtd_by_tag".to_string(), tag_costs_json(tags));
+    }
+    if let Some(efficiency) = extras.window_efficiency
+        && let Some(window) = obj.get_mut("window").and_then(|v| v.as_object_mut())
+    {
+        let round = |v: Option<f64>, scale: f64| v.map(|v| (v * scale).round() / scale);
+        window.insert(
+            "efficiency".to_string(),
+            serde_json::json!({
+                "output_input_ratio": round(efficiency.output_input_ratio, 100.0),
+                "cost_per_1k_output_usd": round(efficiency.cost_per_1k_output, 10000.0),
+                "cache_read_ratio": round(efficiency.cache_read_ratio, 1000.0),
+                "tokens_per_dollar": round(efficiency.tokens_per_dollar, 1.0),
+            }),
+        );
+    }
+    if let Some(window) = obj.get_mut("window").and_then(|v| v.as_object_mut()) {
+        let round = |v: f64| (v * 10000.0).round() / 10000.0;
+        let items: Vec<serde_json::Value> = extras
+            .server_tool_costs
+            .iter()
+            .map(|item| {
+                serde_json::json!({
+                    "tool": item.tool,
+                    "requests": item.requests,
+                    "cost_usd": round(item.cost_usd),
+                })
+            })
+            .collect();
+        let total = extras.server_tool_costs.iter().map(|i| i.cost_usd).sum();
+        window.insert("server_tools".to_string(), serde_json::json!(items));
+        window.insert(
+            "server_tool_cost_usd".to_string(),
+            serde_json::json!(round(total)),
+        );
+        if extras.batch_cost > 0.0 {
+            window.insert(
+                "batch_cost_usd".to_string(),
+                serde_json::json!(round(extras.batch_cost)),
+            );
+        }
+        if let Some((_, adjustment)) = extras.tier_adjustment {
+            window.insert(
+                "tier_adjustment_usd".to_string(),
+                serde_json::json!(round(adjustment)),
+            );
+        }
+    }
+    if let Some(ref stats) = extras.cost_stats {
+        let round = |v: Option<f64>| v.map(|v| (v * 100.0).round() / 100.0);
+        obj.insert(
+            "stats".to_string(),
+            serde_json::json!({
+                "days": stats.days,
+                "today_rank": stats.today_rank,
+                "today_percentile": (stats.today_percentile * 10.0).round() / 10.0,
+                "today_label": stats.rank_label(),
+                "sessions": stats.sessions,
+                "session_cost_median_usd": round(stats.session_cost_median_usd),
+                "session_cost_p90_usd": round(stats.session_cost_p90_usd),
+            }),
+        );
+    }
+    if let Some(ref lifetime) = extras.lifetime {
+        obj.insert("lifetime".to_string(), lifetime.to_json());
+    }
+    if let Some(ref cache) = extras.cache {
+        obj.insert("cache".to_string(), serde_json::json!(cache));
+    }
+    if let Some(ref account) = extras.account {
+        obj.insert("account".to_string(), serde_json::json!(account));
+    }
+    if let Some(ref error) = extras.usage_limits_error {
+        obj.insert("usage_limits_error".to_string(), serde_json::json!(error));
+    }
+    if let Some(ref credits) = extras.openrouter {
+        let round = |v: f64| (v * 10000.0).round() / 10000.0;
+        obj.insert(
+            "openrouter".to_string(),
+            serde_json::json!({
+                "label": credits.label,
+                "usage_usd": round(credits.usage),
+                "limit_usd": credits.limit.map(round),
+                "remaining_usd": credits.remaining.map(round),
+                "is_free_tier": credits.is_free_tier,
+                "rate_limit": credits.rate_limit,
+            }),
+        );
+    }
+    if !extras.daily_history.is_empty() {
+        let today = crate::utils::now_local().date_naive();
+        obj.insert(
+            "history".to_string(),
+            serde_json::json!({ "daily_costs": daily_costs_json(&extras.daily_history, today) }),
+        );
+    }
+}
+
+/// Annotate the payload with the subscription value multiple when a plan is configured.
+fn apply_subscription_value(json: &mut serde_json::Value, extras: &OutputExtras, args: &Args) {
+    let (Some(price), Some(mtd)) = (args.subscription_price_usd(), extras.mtd_cost) else {
+        return;
+    };
+    if let Some(obj) = json.as_object_mut() {
+        obj.insert(
+            "subscription".to_string(),
+            serde_json::json!({
+                "plan": args.subscription.map(|plan| plan.as_str()),
+                "price_usd": price,
+                "api_equivalent_usd": (mtd * 100.0).round() / 100.0,
+                "value_multiple": (mtd / price * 10.0).round() / 10.0,
+            }),
+        );
+    }
+}
+
+/// Annotate `session` with the configured cost cap and whether it is exceeded.
+fn apply_session_cost_cap(json: &mut serde_json::Value, session_cost: f64, args: &Args) {
+    let Some(cap) = args.session_cost_cap.filter(|cap| *cap > 0.0) else {
+        return;
+    };
+    if let Some(session) = json.get_mut("session").and_then(|v| v.as_object_mut()) {
+        session.insert("cost_cap_usd".to_string(), serde_json::json!(cap));
+        session.insert(
+            "over_cap".to_string(),
+            serde_json::json!(session_over_cap(session_cost, args)),
+        );
+    }
+}
+
 /// Remove JSON fields gated by `--no-json-*` toggles. Runs after `build_json_output`
 /// so the omission policy is enforced in one place.
 fn apply_json_toggles(json: &mut serde_json::Value, args: &Args) {
@@ -2971,6 +4970,9 @@ fn apply_json_toggles(json: &mut serde_json::Value, args: &Args) {
     if args.no_json_usage_limits {
         obj.remove("usage_limits");
     }
+    if args.no_json_blocks {
+        obj.remove("blocks");
+    }
     if args.no_json_subagents {
         if let Some(session) = obj.get_mut("session").and_then(|v| v.as_object_mut()) {
             session.remove("subagents");
@@ -3043,6 +5045,7 @@ pub fn print_json_output(
     subagent_breakdown: Option<serde_json::Value>,
     cost_provenance: Option<&CostProvenance>,
     prompt_cache: Option<&PromptCacheInfo>,
+    extras: &OutputExtras,
 ) -> anyhow::Result<()> {
     let mut json = build_json_output(
         hook,
@@ -3088,7 +5091,11 @@ pub fn print_json_output(
         cost_provenance,
         prompt_cache,
     );
+    apply_session_cost_cap(&mut json, session_cost, args);
+    apply_json_extras(&mut json, extras);
+    apply_subscription_value(&mut json, extras, args);
     apply_json_toggles(&mut json, args);
+    crate::currency::add_converted_fields(&mut json);
     println!("{}", serde_json::to_string(&json)?);
     Ok(())
 }
@@ -4,7 +4,10 @@ use serde_json::{Map, Value, json};
 use std::fs;
 use std::path::PathBuf;
 
-use crate::cli::{Args, Command, InitArgs};
+use crate::cli::{
+    Args, CacheArgs, CacheCommand, Command, DbArgs, DbCommand, ExportFormatArg, InitArgs, SyncArgs,
+    SyncCommand,
+};
 use crate::provenance::PricingSource;
 
 #[derive(Debug, Serialize)]
@@ -35,6 +38,19 @@ struct PricingHealth {
     probe_model: String,
     source: PricingSource,
     available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_error: Option<String>,
+    /// Models in the fetched LiteLLM table, with `--pricing-fetch`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    litellm_models: Option<usize>,
+    /// `_meta.updated` of the newest loaded price table
+    table_updated: Option<chrono::NaiveDate>,
+    table_age_days: Option<i64>,
+    /// Proxy markup added after base pricing, when configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proxy_surcharge: Option<crate::pricing::ProxySurcharge>,
 }
 
 #[derive(Debug, Serialize)]
@@ -55,6 +71,9 @@ struct PresetHealth {
 struct DisplayToggleHealth {
     cost_breakdown: bool,
     cost_provenance: bool,
+    cost_mtd: bool,
+    cost_forecast: bool,
+    environment: bool,
     provider_key_source: bool,
     provider_name: bool,
     context_compact_hint_enabled: bool,
@@ -68,6 +87,7 @@ struct JsonToggleHealth {
     duration: bool,
     rate_limit: bool,
     usage_limits: bool,
+    blocks: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -79,6 +99,7 @@ struct DoctorReport {
     settings: SettingsHealth,
     db: crate::db::DbHealth,
     usage_api: crate::usage_api::UsageApiHealth,
+    render_timings: Vec<crate::timing::TimingSummary>,
     pricing: PricingHealth,
     subsystems: SubsystemHealth,
     preset: PresetHealth,
@@ -90,6 +111,199 @@ pub fn run_command(args: &Args, command: &Command) -> Result<()> {
     match command {
         Command::Doctor => run_doctor(args),
         Command::Init(init) => run_init(args, init),
+        Command::Report(report) => crate::report::run_report(args, report),
+        Command::Stats => crate::report::run_stats(args),
+        Command::Pricing(pricing) => crate::report::run_pricing(args, pricing),
+        Command::Db(db) => run_db(args, db),
+        Command::Sync(sync) => run_sync(args, sync),
+        Command::Cache(cache) => run_cache(args, cache),
+    }
+}
+
+fn run_cache(args: &Args, cache: &CacheArgs) -> Result<()> {
+    let CacheCommand::Invalidate { session, all } = &cache.command;
+    let session = match (session, all) {
+        (_, true) => None,
+        (Some(id), false) => Some(id.clone()),
+        (None, false) => Some(hook_session_id()?),
+    };
+    let report = crate::db::invalidate_cache(session.as_deref())?;
+    if args.json {
+        println!("{}", serde_json::to_string(&report)?);
+    } else {
+        println!("db: {}", report.path);
+        println!(
+            "invalidated {}: {} session rows, {} api_cache rows, {} global sums",
+            report.session_id.as_deref().unwrap_or("all sessions"),
+            report.sessions,
+            report.api_cache,
+            report.metadata
+        );
+    }
+    Ok(())
+}
+
+/// `session_id` from the hook JSON Claude Code pipes to hook commands.
+fn hook_session_id() -> Result<String> {
+    let stdin = crate::utils::read_stdin()?;
+    let hook: Value = serde_json::from_slice(&stdin)
+        .context("pass --session or --all, or pipe hook JSON with a session_id")?;
+    hook.get("session_id")
+        .and_then(Value::as_str)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .context("hook JSON has no session_id; pass --session or --all")
+}
+
+fn run_sync(args: &Args, sync: &SyncArgs) -> Result<()> {
+    let dir = args
+        .sync_dir
+        .as_deref()
+        .context("no sync directory; pass --sync-dir or set [sync] dir")?;
+    let machine = args
+        .sync_machine
+        .clone()
+        .unwrap_or_else(crate::sync::default_machine_name);
+    match sync.command {
+        SyncCommand::Export => {
+            let export = crate::sync::export(dir, &machine)?;
+            if args.json {
+                println!("{}", serde_json::to_string(&export)?);
+            } else if export.rows == 0 {
+                println!("sync: nothing new to export");
+            } else {
+                println!(
+                    "sync: appended {} rows to {}",
+                    export.rows,
+                    export.path.display()
+                );
+            }
+        }
+        SyncCommand::Import => {
+            let import = crate::sync::import(dir, &machine)?;
+            if args.json {
+                println!("{}", serde_json::to_string(&import)?);
+            } else {
+                println!(
+                    "sync: merged {} new rows from {} files ({} days reconciled)",
+                    import.rows, import.files, import.merged.dates
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_db(args: &Args, db: &DbArgs) -> Result<()> {
+    match db.command {
+        DbCommand::Migrate { dry_run } => {
+            let report = crate::db::migrate(dry_run)?;
+            if args.json {
+                println!("{}", serde_json::to_string(&report)?);
+                return Ok(());
+            }
+            println!("db: {}", report.path);
+            if report.migrations.is_empty() {
+                println!("schema: v{} (up to date)", report.from_version);
+                return Ok(());
+            }
+            let verb = if dry_run { "pending" } else { "applied" };
+            println!("schema: v{} -> v{}", report.from_version, report.to_version);
+            for step in &report.migrations {
+                println!("{verb} v{}: {}", step.version, step.description);
+            }
+            Ok(())
+        }
+        DbCommand::Export { format, ref output } => {
+            let ExportFormatArg::Json = format;
+            let json = serde_json::to_string(&crate::db::export_json()?)?;
+            match output {
+                Some(path) => fs::write(path, json + "\n")
+                    .with_context(|| format!("failed to write {}", path.display()))?,
+                None => println!("{json}"),
+            }
+            Ok(())
+        }
+        DbCommand::Import { ref path } => {
+            let text = if path.as_os_str() == "-" {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                fs::read_to_string(path)
+                    .with_context(|| format!("failed to read {}", path.display()))?
+            };
+            let doc: serde_json::Value =
+                serde_json::from_str(&text).context("export file is not valid JSON")?;
+            let report = crate::db::import_json(&doc)?;
+            if args.json {
+                println!("{}", serde_json::to_string(&report)?);
+            } else {
+                println!("db: {}", report.path);
+                for (table, rows) in &report.tables {
+                    println!("merged {table}: {rows} rows");
+                }
+                println!("reconciled {} days", report.dates);
+            }
+            Ok(())
+        }
+        DbCommand::Prune => {
+            let report = crate::db::prune()?;
+            if args.json {
+                println!("{}", serde_json::to_string(&report)?);
+            } else {
+                println!("db: {}", report.path);
+                println!(
+                    "pruned: {} entries, {} usage snapshots, {} api_cache rows",
+                    report.entries, report.usage_snapshots, report.api_cache
+                );
+            }
+            Ok(())
+        }
+        DbCommand::Check => {
+            let report = crate::db::check()?;
+            if args.json {
+                println!("{}", serde_json::to_string(&report)?);
+            } else {
+                println!("db: {}", report.path);
+                println!("integrity: {}", if report.ok { "ok" } else { "damaged" });
+                for problem in &report.problems {
+                    println!("  {problem}");
+                }
+                if let Some(ref moved) = report.recovered_from {
+                    println!("rebuilt after corruption; old file kept at {moved}");
+                }
+            }
+            if !report.ok {
+                return Err(anyhow!(
+                    "database failed the integrity check; move it aside to start a new one"
+                ));
+            }
+            Ok(())
+        }
+        DbCommand::Maintain => {
+            let report = crate::db::maintain()?;
+            if args.json {
+                println!("{}", serde_json::to_string(&report)?);
+            } else {
+                println!("db: {}", report.path);
+                println!(
+                    "wal: {} bytes, {}",
+                    report.wal_bytes_before,
+                    if report.checkpointed {
+                        "checkpointed and truncated"
+                    } else {
+                        "checkpoint incomplete (database busy)"
+                    }
+                );
+                println!(
+                    "vacuum: {} ({} -> {} pages, {} were free)",
+                    report.vacuum.unwrap_or("none"),
+                    report.pages_before,
+                    report.pages_after,
+                    report.free_pages_before
+                );
+            }
+            Ok(())
+        }
     }
 }
 
@@ -117,18 +331,47 @@ fn build_report(args: &Args) -> Result<DoctorReport> {
     let active_paths = crate::utils::claude_paths(args.claude_config_dir.as_deref());
     let settings = inspect_settings(args)?;
     let db = crate::db::inspect_health();
-    let usage_api = crate::usage_api::inspect_usage_api(&active_paths, Some("claude-sonnet-4-5"));
+    let account =
+        crate::usage_api::resolve_account(&args.accounts, args.account.as_deref(), &active_paths);
+    let usage_api = crate::usage_api::inspect_usage_api(
+        &active_paths,
+        Some("claude-sonnet-4-5"),
+        account.as_ref(),
+    );
+    let render_timings = crate::db::load_render_timings()
+        .map(|timings| crate::timing::summarize(&timings))
+        .unwrap_or_default();
     let pricing_source = crate::pricing::pricing_source_for_model("claude-sonnet-4-5");
+    let (file, file_error) = match crate::pricing::pricing_file_status() {
+        Some(Ok(path)) => (Some(path.display().to_string()), None),
+        Some(Err(e)) => (None, Some(e.to_string())),
+        None => (None, None),
+    };
     let pricing = PricingHealth {
         probe_model: "claude-sonnet-4-5".to_string(),
         source: pricing_source,
         available: !matches!(pricing_source, PricingSource::Unavailable),
+        file,
+        file_error,
+        litellm_models: args
+            .pricing_fetch
+            .then(crate::pricing::load_litellm_pricing)
+            .flatten(),
+        table_updated: crate::pricing::pricing_table_date(),
+        table_age_days: crate::pricing::pricing_table_date()
+            .map(|date| (crate::utils::now_local().date_naive() - date).num_days()),
+        proxy_surcharge: crate::pricing::proxy_surcharge(),
     };
 
     let mut warnings = Vec::new();
     if args.config_error.is_some() {
         warnings.push("config file could not be loaded".to_string());
     }
+    if let Some(tz) = args.timezone.as_deref() {
+        if crate::utils::resolve_timezone(tz).is_none() {
+            warnings.push(format!("unknown timezone {tz}; using system local time"));
+        }
+    }
     if active_paths.is_empty() {
         warnings.push("no Claude projects directories were found".to_string());
     }
@@ -140,6 +383,20 @@ fn build_report(args: &Args) -> Result<DoctorReport> {
     if !db.ok {
         warnings.push("SQLite cache is not healthy".to_string());
     }
+    if args.pricing_fetch && pricing.litellm_models.is_none() {
+        warnings
+            .push("LiteLLM pricing unavailable (download failed or db cache disabled)".to_string());
+    }
+    if let (Some(updated), Some(age)) = (pricing.table_updated, pricing.table_age_days)
+        && age > i64::from(args.pricing_stale_days)
+    {
+        warnings.push(format!(
+            "pricing table is {age} days old (updated {updated}); update pricing.json or pass --pricing-file/--pricing-fetch"
+        ));
+    }
+    if let Some(ref e) = pricing.file_error {
+        warnings.push(format!("pricing file ignored: {e}"));
+    }
     if !pricing.available {
         warnings.push("pricing lookup failed for probe model".to_string());
     }
@@ -163,6 +420,9 @@ fn build_report(args: &Args) -> Result<DoctorReport> {
     let display_opt_in = DisplayToggleHealth {
         cost_breakdown: args.cost_breakdown,
         cost_provenance: args.cost_provenance,
+        cost_mtd: args.cost_mtd,
+        cost_forecast: args.cost_forecast,
+        environment: args.environment,
         provider_key_source: args.provider_key_source,
         provider_name: args.provider_name,
         context_compact_hint_enabled: !args.no_context_compact_hint,
@@ -175,6 +435,7 @@ fn build_report(args: &Args) -> Result<DoctorReport> {
         duration: !args.no_json_duration,
         rate_limit: !args.no_json_rate_limit,
         usage_limits: !args.no_json_usage_limits,
+        blocks: !args.no_json_blocks,
     };
 
     Ok(DoctorReport {
@@ -191,6 +452,7 @@ fn build_report(args: &Args) -> Result<DoctorReport> {
         settings,
         db,
         usage_api,
+        render_timings,
         pricing,
         subsystems,
         preset,
@@ -234,7 +496,7 @@ fn print_report(report: &DoctorReport) {
             .unwrap_or_else(|| "n/a".to_string())
     );
     println!(
-        "db: {} ok={} wal={} schema={} user_version={} cache_version={}",
+        "db: {} ok={} wal={} schema={} user_version={} cache_version={} encrypted={}",
         report.db.path,
         report.db.ok,
         report.db.journal_mode.as_deref().unwrap_or("unknown"),
@@ -248,15 +510,22 @@ fn print_report(report: &DoctorReport) {
             .db
             .usage_cache_version
             .as_deref()
-            .unwrap_or("unknown")
+            .unwrap_or("unknown"),
+        report.db.encrypted
     );
     println!(
-        "usage_api: direct={} token={} cache={} stale_cache={} negative_cache={}",
+        "usage_api: account={} direct={} token={} cache={} stale_cache={} negative_cache={} failures={} auth={}",
+        report.usage_api.account.as_deref().unwrap_or("default"),
         report.usage_api.direct_claude_api,
         report.usage_api.oauth_token_present,
         report.usage_api.fresh_cache_present,
         report.usage_api.stale_cache_present,
-        report.usage_api.negative_cache_active
+        report.usage_api.negative_cache_active,
+        report.usage_api.consecutive_failures,
+        match report.usage_api.token_rejected {
+            Some(status) => format!("rejected({status})"),
+            None => "ok".to_string(),
+        }
     );
     println!(
         "usage_api egress: {}{}",
@@ -266,11 +535,31 @@ fn print_report(report: &DoctorReport) {
             None => String::new(),
         }
     );
+    for summary in &report.render_timings {
+        println!("render timing: {}", crate::timing::format_summary(summary));
+    }
     println!(
         "pricing: model={} source={}",
         report.pricing.probe_model,
         report.pricing.source.as_str()
     );
+    if let Some(ref file) = report.pricing.file {
+        println!("pricing file: {file}");
+    }
+    if let (Some(updated), Some(age)) =
+        (report.pricing.table_updated, report.pricing.table_age_days)
+    {
+        println!("pricing table: updated {updated} ({age} days ago)");
+    }
+    if let Some(models) = report.pricing.litellm_models {
+        println!("pricing fetch: {models} models from LiteLLM");
+    }
+    if let Some(surcharge) = report.pricing.proxy_surcharge {
+        println!(
+            "proxy surcharge: {}% + ${}/MTok",
+            surcharge.percent, surcharge.per_mtok
+        );
+    }
     println!(
         "subsystems: git={} beads={} gastown={} db_cache={} usage_api={}",
         report.subsystems.git,
@@ -281,21 +570,25 @@ fn print_report(report: &DoctorReport) {
     );
     println!("preset: {}", report.preset.selected.unwrap_or("(none)"));
     println!(
-        "display opt-ins: breakdown={} provenance={} provider_key={} provider_name={} compact_hint={} prompt_cache={}",
+        "display opt-ins: breakdown={} provenance={} mtd={} forecast={} environment={} provider_key={} provider_name={} compact_hint={} prompt_cache={}",
         report.display_opt_in.cost_breakdown,
         report.display_opt_in.cost_provenance,
+        report.display_opt_in.cost_mtd,
+        report.display_opt_in.cost_forecast,
+        report.display_opt_in.environment,
         report.display_opt_in.provider_key_source,
         report.display_opt_in.provider_name,
         report.display_opt_in.context_compact_hint_enabled,
         report.display_opt_in.integrations_prompt_cache_enabled
     );
     println!(
-        "json: subagents={} tokens_breakdown={} duration={} rate_limit={} usage_limits={}",
+        "json: subagents={} tokens_breakdown={} duration={} rate_limit={} usage_limits={} blocks={}",
         report.json_settings.subagents,
         report.json_settings.tokens_breakdown,
         report.json_settings.duration,
         report.json_settings.rate_limit,
-        report.json_settings.usage_limits
+        report.json_settings.usage_limits,
+        report.json_settings.blocks
     );
 }
 
@@ -0,0 +1,228 @@
+use serde::Serialize;
+
+use crate::models::Entry;
+
+/// Default grid carbon intensity (gCO2e per kWh), roughly the global average.
+pub const DEFAULT_GRID_INTENSITY: f64 = 400.0;
+
+/// Model families with separate energy coefficients; `default` covers the rest.
+pub const FAMILIES: [&str; 4] = ["opus", "sonnet", "haiku", "default"];
+
+/// Coefficient names accepted under `[environment.<family>]`.
+pub const COEFFICIENTS: [&str; 4] = ["input", "output", "cache_write", "cache_read"];
+
+/// Energy per 1k tokens (Wh) for one model family.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EnergyCoefficients {
+    pub input_wh_per_1k: f64,
+    pub output_wh_per_1k: f64,
+    pub cache_write_wh_per_1k: f64,
+    pub cache_read_wh_per_1k: f64,
+}
+
+impl EnergyCoefficients {
+    /// Rough public estimates; output generation dominates, cache reads are
+    /// a small fraction of fresh input.
+    pub fn default_for(family: &str) -> Self {
+        let (input, output) = match family {
+            "opus" => (0.06, 1.2),
+            "haiku" => (0.01, 0.2),
+            _ => (0.03, 0.6),
+        };
+        Self {
+            input_wh_per_1k: input,
+            output_wh_per_1k: output,
+            cache_write_wh_per_1k: input,
+            cache_read_wh_per_1k: input * 0.1,
+        }
+    }
+
+    /// Override one coefficient by its config name (see [`COEFFICIENTS`]).
+    /// Returns false, leaving it unchanged, for unknown names or values
+    /// that are negative or not finite.
+    pub fn set(&mut self, name: &str, wh_per_1k: f64) -> bool {
+        if !wh_per_1k.is_finite() || wh_per_1k < 0.0 {
+            return false;
+        }
+        let slot = match name {
+            "input" => &mut self.input_wh_per_1k,
+            "output" => &mut self.output_wh_per_1k,
+            "cache_write" => &mut self.cache_write_wh_per_1k,
+            "cache_read" => &mut self.cache_read_wh_per_1k,
+            _ => return false,
+        };
+        *slot = wh_per_1k;
+        true
+    }
+
+    fn energy_wh(&self, entry: &Entry) -> f64 {
+        (entry.input as f64 * self.input_wh_per_1k
+            + entry.output as f64 * self.output_wh_per_1k
+            + entry.cache_create as f64 * self.cache_write_wh_per_1k
+            + entry.cache_read as f64 * self.cache_read_wh_per_1k)
+            / 1000.0
+    }
+}
+
+/// Coefficients per family plus grid intensity used to turn energy into CO2e.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnergyModel {
+    pub families: Vec<(String, EnergyCoefficients)>,
+    pub grid_intensity: f64,
+    /// True once any value came from config or CLI instead of the defaults
+    pub customized: bool,
+}
+
+impl Default for EnergyModel {
+    fn default() -> Self {
+        Self {
+            families: FAMILIES
+                .iter()
+                .map(|f| (f.to_string(), EnergyCoefficients::default_for(f)))
+                .collect(),
+            grid_intensity: DEFAULT_GRID_INTENSITY,
+            customized: false,
+        }
+    }
+}
+
+impl EnergyModel {
+    /// Configured model with the `--carbon-intensity` override applied.
+    pub fn from_args(args: &crate::cli::Args) -> Self {
+        let mut model = args.energy_model.clone();
+        if let Some(intensity) = args.carbon_intensity.filter(|v| *v >= 0.0) {
+            model.grid_intensity = intensity;
+            model.customized = true;
+        }
+        model
+    }
+
+    pub fn family_mut(&mut self, family: &str) -> Option<&mut EnergyCoefficients> {
+        self.families
+            .iter_mut()
+            .find(|(name, _)| name == family)
+            .map(|(_, coefficients)| coefficients)
+    }
+
+    fn coefficients_for(&self, model: Option<&str>) -> EnergyCoefficients {
+        let model = model.unwrap_or_default().to_ascii_lowercase();
+        let family = FAMILIES[..3]
+            .iter()
+            .find(|f| model.contains(*f))
+            .copied()
+            .unwrap_or("default");
+        self.families
+            .iter()
+            .find(|(name, _)| name == family)
+            .map_or_else(|| EnergyCoefficients::default_for(family), |(_, c)| *c)
+    }
+
+    /// Estimated footprint of the given entries.
+    pub fn estimate<'a>(&self, entries: impl IntoIterator<Item = &'a Entry>) -> Footprint {
+        let energy_wh = entries.into_iter().fold(0.0, |sum, entry| {
+            sum + self
+                .coefficients_for(entry.model.as_deref())
+                .energy_wh(entry)
+        });
+        Footprint {
+            energy_wh,
+            co2e_g: energy_wh / 1000.0 * self.grid_intensity,
+        }
+    }
+}
+
+/// Estimated energy use and emissions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Footprint {
+    pub energy_wh: f64,
+    pub co2e_g: f64,
+}
+
+impl Footprint {
+    /// Compact CO2e amount: grams below 1 kg.
+    pub fn co2e_label(&self) -> String {
+        if self.co2e_g >= 1000.0 {
+            format!("{:.1}kg", self.co2e_g / 1000.0)
+        } else if self.co2e_g >= 10.0 {
+            format!("{:.0}g", self.co2e_g)
+        } else {
+            format!("{:.1}g", self.co2e_g)
+        }
+    }
+
+    /// Compact energy amount: Wh below 1 kWh.
+    pub fn energy_label(&self) -> String {
+        if self.energy_wh >= 1000.0 {
+            format!("{:.1}kWh", self.energy_wh / 1000.0)
+        } else if self.energy_wh >= 10.0 {
+            format!("{:.0}Wh", self.energy_wh)
+        } else {
+            format!("{:.1}Wh", self.energy_wh)
+        }
+    }
+}
+
+/// Session and today footprints surfaced by `--environment`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnvironmentEstimate {
+    pub session: Footprint,
+    pub today: Footprint,
+    pub grid_intensity: f64,
+    pub customized: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn entry(model: &str, input: u64, output: u64, cache_read: u64) -> Entry {
+        Entry {
+            ts: Utc::now(),
+            input,
+            output,
+            cache_create: 0,
+            cache_read,
+            server_tools: Default::default(),
+            speed: None,
+            service_tier: None,
+            cost: 0.0,
+            model: Some(model.to_string()),
+            session_id: None,
+            msg_id: None,
+            req_id: None,
+            project: None,
+            agent_id: None,
+        }
+    }
+
+    #[test]
+    fn estimate_uses_family_coefficients_and_grid_intensity() {
+        let model = EnergyModel::default();
+        let entries = [
+            entry("claude-opus-4-1", 1_000, 1_000, 0),
+            entry("claude-haiku-4-5", 0, 10_000, 0),
+            entry("some-proxy-model", 0, 0, 100_000),
+        ];
+
+        let footprint = model.estimate(&entries);
+        // opus 0.06 + 1.2, haiku 2.0, default cache read 0.003 * 100
+        let expected_wh = 0.06 + 1.2 + 2.0 + 0.3;
+        assert!((footprint.energy_wh - expected_wh).abs() < 1e-9);
+        assert!((footprint.co2e_g - expected_wh * 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn overrides_replace_single_coefficients() {
+        let mut model = EnergyModel::default();
+        assert!(model.family_mut("sonnet").unwrap().set("output", 2.0));
+        assert!(!model.family_mut("sonnet").unwrap().set("bogus", 1.0));
+        model.grid_intensity = 100.0;
+
+        let footprint = model.estimate(&[entry("claude-sonnet-4-5", 0, 1_000, 0)]);
+        assert!((footprint.energy_wh - 2.0).abs() < 1e-9);
+        assert!((footprint.co2e_g - 0.2).abs() < 1e-9);
+        assert_eq!(footprint.co2e_label(), "0.2g");
+        assert_eq!(footprint.energy_label(), "2.0Wh");
+    }
+}
@@ -6,9 +6,14 @@
 //! Gas Town is a multi-agent orchestration system for Claude agents.
 //! See: https://github.com/steveyegge/gastown
 
+#[cfg(feature = "db")]
+use crate::beads::parse_beads_timestamp;
+use crate::cli::GastownFieldArg;
 use crate::models::{
-    AgentIdentity, AgentType, GasTownInfo, MailPreview, RefineryQueue, RigInfo, RigStatus,
+    AgentIdentity, AgentType, ConvoyStatus, GasTownInfo, MailPreview, RefineryQueue, RigInfo,
+    RigStatus,
 };
+#[cfg(feature = "db")]
 use rusqlite::Connection;
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -23,8 +28,12 @@ const PRIMARY_MARKER: &str = "mayor/town.json";
 const SECONDARY_MARKER: &str = "mayor";
 
 /// Beads database filename
+#[cfg(feature = "db")]
 const BEADS_DB_NAME: &str = "beads.db";
 
+/// Refinery state files, relative to the rig directory (current layout first)
+const REFINERY_STATE_FILES: &[&str] = &[".runtime/refinery.json", "refinery/state.json"];
+
 /// Minimal town.json structure for name extraction
 #[derive(Debug, Deserialize)]
 struct TownConfig {
@@ -153,7 +162,8 @@ fn get_hooked_issue() -> Option<String> {
 
 /// Query mail inbox from beads database
 ///
-/// Returns unread count and preview of first unread message.
+/// Returns unread count, preview of the newest unread message, and the
+/// oldest unread message's age.
 /// All gastown mail uses town-level beads ({townRoot}/.beads).
 fn query_mail_inbox(town_root: &Path, identity: &str) -> Option<MailPreview> {
     // All mail uses town-level beads (rig-level beads are for project issues only)
@@ -165,6 +175,12 @@ fn query_mail_inbox(town_root: &Path, identity: &str) -> Option<MailPreview> {
 ///
 /// Gastown mail uses issue_type='message' with assignee = recipient identity.
 /// All mail goes through town-level beads ({townRoot}/.beads).
+#[cfg(not(feature = "db"))]
+fn query_mail_from_beads(_beads_dir: &Path, _identity: &str) -> Option<MailPreview> {
+    None
+}
+
+#[cfg(feature = "db")]
 fn query_mail_from_beads(beads_dir: &Path, identity: &str) -> Option<MailPreview> {
     let db_path = beads_dir.join(BEADS_DB_NAME);
     if !db_path.is_file() {
@@ -178,7 +194,7 @@ fn query_mail_from_beads(beads_dir: &Path, identity: &str) -> Option<MailPreview
     let mut stmt = conn
         .prepare(
             r#"
-            SELECT id, title
+            SELECT id, title, created_at
             FROM issues
             WHERE assignee = ?1
               AND status IN ('open', 'hooked')
@@ -189,8 +205,10 @@ fn query_mail_from_beads(beads_dir: &Path, identity: &str) -> Option<MailPreview
         )
         .ok()?;
 
-    let rows: Vec<(String, String)> = stmt
-        .query_map([identity], |row| Ok((row.get(0)?, row.get(1)?)))
+    let rows: Vec<(String, String, Option<String>)> = stmt
+        .query_map([identity], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
         .ok()?
         .flatten()
         .collect();
@@ -199,7 +217,7 @@ fn query_mail_from_beads(beads_dir: &Path, identity: &str) -> Option<MailPreview
         return None;
     }
 
-    let preview = rows.first().map(|(_, title)| {
+    let preview = rows.first().map(|(_, title, _)| {
         if title.len() > 45 {
             format!("{}…", &title[..44])
         } else {
@@ -207,12 +225,81 @@ fn query_mail_from_beads(beads_dir: &Path, identity: &str) -> Option<MailPreview
         }
     });
 
+    let now = crate::utils::now_utc().timestamp();
+    let oldest_age_secs = rows
+        .iter()
+        .filter_map(|(_, _, created)| parse_beads_timestamp(created.as_deref()?))
+        .min()
+        .map(|oldest| (now - oldest).max(0));
+
     Some(MailPreview {
         unread_count: rows.len(),
         preview,
+        oldest_age_secs,
+    })
+}
+
+/// Query in-flight convoys from the town-level beads database
+///
+/// Convoys are beads issues with issue_type='convoy'; any not yet closed is
+/// still in flight. Returns None when the town has no beads database.
+#[cfg(not(feature = "db"))]
+fn query_convoys(_town_root: &Path) -> Option<ConvoyStatus> {
+    None
+}
+
+#[cfg(feature = "db")]
+fn query_convoys(town_root: &Path) -> Option<ConvoyStatus> {
+    let db_path = town_root.join(".beads").join(BEADS_DB_NAME);
+    if !db_path.is_file() {
+        return None;
+    }
+
+    let conn = Connection::open(&db_path).ok()?;
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT created_at
+            FROM issues
+            WHERE issue_type = 'convoy'
+              AND status NOT IN ('closed', 'tombstone')
+              AND (deleted_at IS NULL OR deleted_at = '')
+            "#,
+        )
+        .ok()?;
+
+    let created: Vec<Option<String>> = stmt
+        .query_map([], |row| row.get(0))
+        .ok()?
+        .flatten()
+        .collect();
+
+    let now = crate::utils::now_utc().timestamp();
+    let oldest_age_secs = created
+        .iter()
+        .flatten()
+        .filter_map(|ts| parse_beads_timestamp(ts))
+        .min()
+        .map(|oldest| (now - oldest).max(0));
+
+    Some(ConvoyStatus {
+        active: created.len(),
+        oldest_age_secs,
     })
 }
 
+/// Compact age for convoy and mail summaries (e.g. "45m", "3h", "2d")
+fn format_age(age_secs: i64) -> String {
+    let minutes = age_secs / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else if minutes < 24 * 60 {
+        format!("{}h", minutes / 60)
+    } else {
+        format!("{}d", minutes / (24 * 60))
+    }
+}
+
 /// Get rig status by querying tmux sessions
 ///
 /// Returns a list of rigs with their status (active/partial/inactive)
@@ -354,12 +441,70 @@ fn parse_session_name(name: &str) -> Option<(String, AgentType, Option<String>)>
     }
 }
 
-/// Get refinery queue status (stub - would need to query refinery state)
-fn get_refinery_queue(_town_root: &Path, _rig: &str) -> Option<RefineryQueue> {
-    // Refinery state would need to be queried from refinery's MQ
-    // This is complex and requires accessing refinery's internal state files
-    // For now, return None - can be enhanced later
-    None
+/// Subset of the refinery's persisted state
+#[derive(Debug, Deserialize)]
+struct RefineryState {
+    current_mr: Option<MergeRequest>,
+    /// Keyed by merge request ID, or a plain list in older releases
+    pending_mrs: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MergeRequest {
+    id: Option<String>,
+    issue_id: Option<String>,
+    branch: Option<String>,
+}
+
+impl MergeRequest {
+    /// Issue being merged, falling back to the MR id and then the branch
+    fn label(&self) -> Option<&str> {
+        self.issue_id
+            .as_deref()
+            .or(self.id.as_deref())
+            .or(self.branch.as_deref())
+            .filter(|s| !s.is_empty())
+    }
+}
+
+/// Get refinery queue status from the refinery's state file in the rig
+///
+/// Returns None when the refinery has never written its state.
+fn get_refinery_queue(town_root: &Path, rig: &str) -> Option<RefineryQueue> {
+    let rig_dir = town_root.join(rig);
+    let content = REFINERY_STATE_FILES
+        .iter()
+        .find_map(|file| std::fs::read_to_string(rig_dir.join(file)).ok())?;
+    parse_refinery_state(&content)
+}
+
+/// Parse refinery state JSON into the merge queue shown in the statusline
+fn parse_refinery_state(content: &str) -> Option<RefineryQueue> {
+    let state: RefineryState = serde_json::from_str(content).ok()?;
+    let current_id = state.current_mr.as_ref().and_then(|mr| mr.id.clone());
+    // The MR being merged may still be listed as pending
+    let pending = match state.pending_mrs {
+        Some(serde_json::Value::Object(map)) => map
+            .keys()
+            .filter(|id| Some(*id) != current_id.as_ref())
+            .count(),
+        Some(serde_json::Value::Array(list)) => list
+            .iter()
+            .filter(|mr| {
+                current_id.is_none()
+                    || mr.get("id").and_then(|id| id.as_str()) != current_id.as_deref()
+            })
+            .count(),
+        _ => 0,
+    };
+    Some(RefineryQueue {
+        current: state
+            .current_mr
+            .as_ref()
+            .and_then(MergeRequest::label)
+            .map(str::to_string),
+        pending,
+    })
 }
 
 /// Get complete Gas Town information for the current project
@@ -368,7 +513,13 @@ fn get_refinery_queue(_town_root: &Path, _rig: &str) -> Option<RefineryQueue> {
 /// - Not in a Gas Town workspace
 /// - No agent identity can be determined
 pub fn get_gastown_info(project_dir: &Path) -> Option<GasTownInfo> {
-    let town_root = find_town_root(project_dir)?;
+    if crate::utils::known_absent("gastown", project_dir) {
+        return None;
+    }
+    let Some(town_root) = find_town_root(project_dir) else {
+        crate::utils::remember_absent("gastown", project_dir);
+        return None;
+    };
 
     // Load town name from config
     let town_name = load_town_name(&town_root);
@@ -384,14 +535,14 @@ pub fn get_gastown_info(project_dir: &Path) -> Option<GasTownInfo> {
         .as_ref()
         .and_then(|a| query_mail_inbox(&town_root, &a.identity));
 
-    // Get rig status (useful for mayor/deacon views)
-    let rigs = if agent
+    // Get rig status and in-flight convoys (useful for mayor/deacon views)
+    let (rigs, convoys) = if agent
         .as_ref()
         .is_some_and(|a| matches!(a.agent_type, AgentType::Mayor | AgentType::Deacon))
     {
-        get_rig_status()
+        (get_rig_status(), query_convoys(&town_root))
     } else {
-        Vec::new()
+        (Vec::new(), None)
     };
 
     // Calculate total polecats
@@ -423,75 +574,168 @@ pub fn get_gastown_info(project_dir: &Path) -> Option<GasTownInfo> {
         rigs,
         total_polecats,
         refinery_queue,
+        convoys,
     })
 }
 
+/// Which parts of the Gas Town segment render, and how
+#[derive(Debug, Clone)]
+pub struct GastownFormat {
+    /// Parts to show; empty shows everything the agent's role has
+    pub fields: Vec<GastownFieldArg>,
+    /// Emoji icons, or plain words when off
+    pub emoji: bool,
+}
+
+impl Default for GastownFormat {
+    fn default() -> Self {
+        Self {
+            fields: Vec::new(),
+            emoji: true,
+        }
+    }
+}
+
+impl GastownFormat {
+    pub fn from_args(args: &crate::cli::Args) -> Self {
+        Self {
+            fields: args.gastown_fields.clone(),
+            emoji: !args.no_gastown_emoji,
+        }
+    }
+
+    fn shows(&self, field: GastownFieldArg) -> bool {
+        self.fields.is_empty() || self.fields.contains(&field)
+    }
+
+    /// `emoji` when enabled, else the plain-word fallback
+    fn icon<'a>(&self, emoji: &'a str, word: &'a str) -> &'a str {
+        if self.emoji { emoji } else { word }
+    }
+}
+
+/// Format the inbox summary: "📬 3 (oldest 2h) <newest subject>"
+fn format_mail(mail: &MailPreview, format: &GastownFormat) -> String {
+    let mut text = format!("{} {}", format.icon("📬", "mail"), mail.unread_count);
+    if let Some(age) = mail.oldest_age_secs {
+        text.push_str(&format!(" (oldest {})", format_age(age)));
+    }
+    if let Some(ref preview) = mail.preview {
+        text.push(' ');
+        text.push_str(preview);
+    }
+    text
+}
+
 /// Format Gas Town status for display in statusline header
 ///
 /// Returns a compact status string based on agent role:
-/// - Mayor: "3 😺 🟢rig1 🟡rig2"
+/// - Mayor: "3 😺 🟢rig1 🟡rig2 2 🚚 3h"
 /// - Witness: "2 😺 1 crew"
 /// - Polecat/Crew: "😺 <hooked work or mail>"
-pub fn format_gastown_display(info: &GasTownInfo, max_len: usize) -> String {
+///
+/// `format` narrows the parts shown and swaps emoji for words
+/// ("mayor | 3 polecats | rig1:active").
+pub fn format_gastown_display(
+    info: &GasTownInfo,
+    max_len: usize,
+    format: &GastownFormat,
+) -> String {
     let mut parts: Vec<String> = Vec::new();
+    let polecats = format.icon("😺", "polecats");
 
     if let Some(ref agent) = info.agent {
         // Add agent icon
-        parts.push(agent.agent_type.emoji().to_string());
+        if format.shows(GastownFieldArg::Role) {
+            parts.push(
+                format
+                    .icon(agent.agent_type.emoji(), agent.agent_type.as_str())
+                    .to_string(),
+            );
+        }
 
         match agent.agent_type {
             AgentType::Mayor | AgentType::Deacon => {
                 // Show polecat count and rig LEDs
-                if let Some(total) = info.total_polecats {
-                    parts.push(format!("{} 😺", total));
+                if format.shows(GastownFieldArg::Polecats) {
+                    if let Some(total) = info.total_polecats {
+                        parts.push(format!("{} {}", total, polecats));
+                    }
                 }
                 // Rig LEDs
                 let rig_leds: Vec<String> = info
                     .rigs
                     .iter()
-                    .map(|r| format!("{}{}", r.status.led(), r.name))
+                    .map(|r| {
+                        if format.emoji {
+                            format!("{}{}", r.status.led(), r.name)
+                        } else {
+                            format!("{}:{}", r.name, r.status.as_str())
+                        }
+                    })
                     .collect();
-                if !rig_leds.is_empty() {
+                if format.shows(GastownFieldArg::Rigs) && !rig_leds.is_empty() {
                     parts.push(rig_leds.join(" "));
                 }
+                // Convoys in flight, with the oldest one's age
+                if format.shows(GastownFieldArg::Convoys) {
+                    if let Some(ref convoys) = info.convoys {
+                        if convoys.active > 0 {
+                            let icon = format.icon("🚚", "convoys");
+                            match convoys.oldest_age_secs {
+                                Some(age) => parts.push(format!(
+                                    "{} {} {}",
+                                    convoys.active,
+                                    icon,
+                                    format_age(age)
+                                )),
+                                None => parts.push(format!("{} {}", convoys.active, icon)),
+                            }
+                        }
+                    }
+                }
             }
             AgentType::Witness => {
                 // Show polecat/crew counts for this rig
-                if let Some(ref rig_name) = agent.rig {
-                    if let Some(rig) = info.rigs.iter().find(|r| &r.name == rig_name) {
-                        parts.push(format!("{} 😺", rig.polecat_count));
-                        if rig.crew_count > 0 {
-                            parts.push(format!("{} crew", rig.crew_count));
+                if format.shows(GastownFieldArg::Polecats) {
+                    if let Some(ref rig_name) = agent.rig {
+                        if let Some(rig) = info.rigs.iter().find(|r| &r.name == rig_name) {
+                            parts.push(format!("{} {}", rig.polecat_count, polecats));
+                            if rig.crew_count > 0 {
+                                parts.push(format!("{} crew", rig.crew_count));
+                            }
                         }
                     }
                 }
             }
             AgentType::Refinery => {
                 // Show merge queue status
-                if let Some(ref queue) = info.refinery_queue {
-                    if let Some(ref current) = queue.current {
-                        parts.push(format!("merging {}", current));
-                        if queue.pending > 0 {
-                            parts.push(format!("+{} queued", queue.pending));
+                if format.shows(GastownFieldArg::Queue) {
+                    if let Some(ref queue) = info.refinery_queue {
+                        if let Some(ref current) = queue.current {
+                            parts.push(format!("merging {}", current));
+                            if queue.pending > 0 {
+                                parts.push(format!("+{} queued", queue.pending));
+                            }
+                        } else if queue.pending > 0 {
+                            parts.push(format!("{} queued", queue.pending));
+                        } else {
+                            parts.push("idle".to_string());
                         }
-                    } else if queue.pending > 0 {
-                        parts.push(format!("{} queued", queue.pending));
-                    } else {
-                        parts.push("idle".to_string());
                     }
                 }
             }
             AgentType::Crew | AgentType::Polecat => {
                 // Show hooked work or mail preview
-                if let Some(ref issue) = info.hooked_issue {
-                    parts.push(format!("🪝 {}", issue));
+                let hooked = info
+                    .hooked_issue
+                    .as_ref()
+                    .filter(|_| format.shows(GastownFieldArg::Hook));
+                if let Some(issue) = hooked {
+                    parts.push(format!("{} {}", format.icon("🪝", "hook:"), issue));
                 } else if let Some(ref mail) = info.mail {
-                    if mail.unread_count > 0 {
-                        if let Some(ref preview) = mail.preview {
-                            parts.push(format!("📬 {}", preview));
-                        } else {
-                            parts.push(format!("📬 {}", mail.unread_count));
-                        }
+                    if format.shows(GastownFieldArg::Mail) && mail.unread_count > 0 {
+                        parts.push(format_mail(mail, format));
                     }
                 }
             }
@@ -499,7 +743,7 @@ pub fn format_gastown_display(info: &GasTownInfo, max_len: usize) -> String {
     }
 
     let result = parts.join(" | ");
-    if result.chars().count() > max_len {
+    if max_len > 0 && result.chars().count() > max_len {
         format!("{}…", result.chars().take(max_len - 1).collect::<String>())
     } else {
         result
@@ -550,6 +794,154 @@ mod tests {
         assert_eq!(name, None);
     }
 
+    #[test]
+    fn test_parse_refinery_state() {
+        let queue = parse_refinery_state(
+            r#"{"state":"running","current_mr":{"id":"mr-1","branch":"polecat/slit","issue_id":"gt-42"},
+            "pending_mrs":{"mr-1":{"id":"mr-1"},"mr-2":{"id":"mr-2"},"mr-3":{"id":"mr-3"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(queue.current.as_deref(), Some("gt-42"));
+        assert_eq!(queue.pending, 2);
+
+        let queue =
+            parse_refinery_state(r#"{"current_mr":null,"pending_mrs":[{"id":"mr-4"}]}"#).unwrap();
+        assert_eq!((queue.current, queue.pending), (None, 1));
+
+        let queue = parse_refinery_state(r#"{"state":"stopped"}"#).unwrap();
+        assert_eq!((queue.current, queue.pending), (None, 0));
+        assert!(parse_refinery_state("not json").is_none());
+    }
+
+    #[test]
+    fn test_get_refinery_queue_reads_rig_state_file() {
+        let town = tempfile::TempDir::new().unwrap();
+        assert!(get_refinery_queue(town.path(), "gastown").is_none());
+
+        let runtime = town.path().join("gastown/.runtime");
+        std::fs::create_dir_all(&runtime).unwrap();
+        std::fs::write(
+            runtime.join("refinery.json"),
+            r#"{"pending_mrs":{"mr-2":{"id":"mr-2"}}}"#,
+        )
+        .unwrap();
+        let queue = get_refinery_queue(town.path(), "gastown").unwrap();
+        assert_eq!((queue.current, queue.pending), (None, 1));
+    }
+
+    #[test]
+    fn test_format_gastown_display_mayor_convoys() {
+        let info = GasTownInfo {
+            town_root: "/town".to_string(),
+            town_name: None,
+            agent: Some(AgentIdentity {
+                agent_type: AgentType::Mayor,
+                rig: None,
+                name: None,
+                identity: "mayor".to_string(),
+            }),
+            mail: None,
+            hooked_issue: None,
+            rigs: Vec::new(),
+            total_polecats: Some(3),
+            refinery_queue: None,
+            convoys: Some(ConvoyStatus {
+                active: 2,
+                oldest_age_secs: Some(3 * 3600 + 120),
+            }),
+        };
+        assert_eq!(
+            format_gastown_display(&info, 80, &GastownFormat::default()),
+            "🎩 | 3 😺 | 2 🚚 3h"
+        );
+
+        let format = GastownFormat {
+            fields: vec![GastownFieldArg::Role, GastownFieldArg::Convoys],
+            emoji: false,
+        };
+        assert_eq!(
+            format_gastown_display(&info, 80, &format),
+            "mayor | 2 convoys 3h"
+        );
+        assert_eq!(format_gastown_display(&info, 8, &format), "mayor |…");
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn test_query_convoys_counts_open_convoys() {
+        let town = tempfile::TempDir::new().unwrap();
+        assert!(query_convoys(town.path()).is_none());
+
+        std::fs::create_dir(town.path().join(".beads")).unwrap();
+        let conn = Connection::open(town.path().join(".beads").join(BEADS_DB_NAME)).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE issues (id TEXT, issue_type TEXT, status TEXT, created_at TEXT, deleted_at TEXT);
+            INSERT INTO issues VALUES ('hq-cv-1', 'convoy', 'open', '2020-01-01T00:00:00Z', NULL);
+            INSERT INTO issues VALUES ('hq-cv-2', 'convoy', 'in_progress', '2999-01-01 00:00:00', NULL);
+            INSERT INTO issues VALUES ('hq-cv-3', 'convoy', 'closed', '2019-01-01T00:00:00Z', NULL);
+            INSERT INTO issues VALUES ('hq-1', 'task', 'open', '2018-01-01T00:00:00Z', NULL);
+            "#,
+        )
+        .unwrap();
+        let convoys = query_convoys(town.path()).unwrap();
+        assert_eq!(convoys.active, 2);
+        let expected = chrono::Utc::now().timestamp() - 1_577_836_800;
+        assert!(
+            convoys
+                .oldest_age_secs
+                .is_some_and(|age| (age - expected).abs() < 60)
+        );
+    }
+
+    #[test]
+    fn test_format_mail_shows_oldest_age() {
+        let mut mail = MailPreview {
+            unread_count: 3,
+            preview: Some("Review merge".to_string()),
+            oldest_age_secs: Some(2 * 3600 + 59),
+        };
+        assert_eq!(
+            format_mail(&mail, &GastownFormat::default()),
+            "📬 3 (oldest 2h) Review merge"
+        );
+        mail.oldest_age_secs = None;
+        mail.preview = None;
+        let plain = GastownFormat {
+            emoji: false,
+            ..Default::default()
+        };
+        assert_eq!(format_mail(&mail, &plain), "mail 3");
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn test_query_mail_reports_oldest_unread() {
+        let beads = tempfile::TempDir::new().unwrap();
+        let conn = Connection::open(beads.path().join(BEADS_DB_NAME)).unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE issues (id TEXT, title TEXT, assignee TEXT, status TEXT, issue_type TEXT,
+                created_at TEXT, updated_at TEXT, deleted_at TEXT);
+            INSERT INTO issues VALUES ('hq-1', 'Old', 'mayor', 'open', 'message',
+                '2020-01-01T00:00:00Z', '2020-01-01T00:00:00Z', NULL);
+            INSERT INTO issues VALUES ('hq-2', 'New', 'mayor', 'hooked', 'message',
+                '2999-01-01T00:00:00Z', '2999-01-01T00:00:00Z', NULL);
+            INSERT INTO issues VALUES ('hq-3', 'Read', 'mayor', 'closed', 'message',
+                '2010-01-01T00:00:00Z', '2010-01-01T00:00:00Z', NULL);
+            "#,
+        )
+        .unwrap();
+        let mail = query_mail_from_beads(beads.path(), "mayor").unwrap();
+        assert_eq!(mail.unread_count, 2);
+        assert_eq!(mail.preview.as_deref(), Some("New"));
+        let expected = chrono::Utc::now().timestamp() - 1_577_836_800;
+        assert!(
+            mail.oldest_age_secs
+                .is_some_and(|age| (age - expected).abs() < 60)
+        );
+    }
+
     #[test]
     fn test_agent_type_emoji() {
         assert_eq!(AgentType::Mayor.emoji(), "🎩");
@@ -4,20 +4,172 @@
 //!
 //! ## Features
 //!
-//! - Branch and commit information
+//! - Branch, commit, and HEAD commit age
 //! - Clean/dirty status detection
-//! - Ahead/behind calculation against upstream
-//! - Worktree detection
+//! - Untracked file count
+//! - Added/removed line counts against HEAD
+//! - Unresolved conflict count
+//! - Sparse checkout and pending Git LFS pointers
+//! - In-progress merge / rebase / cherry-pick detection
+//! - Upstream tracking branch and ahead/behind calculation against it
+//! - Remote default branch (`origin/HEAD`) and ahead/behind against it
+//! - Nearest tag (`git describe --tags`)
+//! - Submodule checkout and drift counts
+//! - Worktree detection and listing
+//! - Monorepo subproject (nearest package root)
+//! - Repository health checks (`--git-health`)
 //! - Remote URL extraction
+//! - Time-capped inspection with the previous render's result as fallback
 
-use crate::models::git::GitInfo;
+use crate::models::git::{
+    GitDescribe, GitHealthIssue, GitHealthKind, GitInfo, GitOperation, GitOperationKind,
+    GitWorktree,
+};
+use crate::provenance::{CacheLayer, record_cache};
 use std::path::Path;
+use std::sync::Mutex;
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
 
 /// Maximum number of commits to walk when calculating ahead/behind
 /// This prevents excessive CPU usage on large repositories
 const MAX_ANCESTOR_WALK: usize = 10_000;
 
-pub fn read_git_info(start_dir: &Path) -> Option<GitInfo> {
+const GIT_INFO_CACHE_PREFIX: &str = "git_info:";
+/// Only ever read as a fallback, so it outlives any gap between renders
+const GIT_INFO_CACHE_TTL_SECONDS: i64 = 7 * 24 * 3600;
+const GIT_INFO_LOCK_PREFIX: &str = "git_info_lock:";
+/// Held while one process inspects a checkout; expires on its own if that
+/// process dies mid-inspection
+const GIT_INFO_LOCK_TTL_SECONDS: i64 = 60;
+
+const HEALTH_CACHE_PREFIX: &str = "git_health:";
+const HEALTH_CACHE_TTL_SECONDS: i64 = 3600;
+/// Files bigger than this on either side are left out of the line stats
+const LINE_STATS_MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Untracked files below one top-level directory before it is reported
+const LARGE_UNTRACKED_DIR_FILES: u64 = 1000;
+/// Build output directories that belong in a gitignore
+const BUILD_ARTIFACT_DIRS: &[&str] = &[
+    "target",
+    "node_modules",
+    "dist",
+    "build",
+    ".venv",
+    "venv",
+    "__pycache__",
+    ".next",
+    ".gradle",
+];
+
+/// Inspection still running after its budget ran out
+static BACKGROUND: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+/// Run `inspect` for `project_dir`, waiting at most `budget`. Every finished
+/// inspection is cached in the db under `options`, which decide what it
+/// holds; past the budget the previous render's result is served with
+/// [`GitInfo::stale_age_secs`] set, and the running inspection is left to
+/// refresh the cache (see [`finish_background`]). While another process is
+/// inspecting the same checkout its last result is served instead of
+/// starting a second inspection.
+pub fn read_git_info_within<F>(
+    project_dir: &Path,
+    options: GitInspectOptions,
+    budget: Duration,
+    inspect: F,
+) -> Option<GitInfo>
+where
+    F: FnOnce() -> Option<GitInfo> + Send + 'static,
+{
+    let key = git_info_cache_key(project_dir, options);
+    let lock_key = format!("{GIT_INFO_LOCK_PREFIX}{}", project_dir.display());
+    let got_lock =
+        crate::db::try_set_api_cache(&lock_key, "1", GIT_INFO_LOCK_TTL_SECONDS).unwrap_or(false);
+    if !got_lock && let Some(info) = stale_git_info(&key) {
+        return Some(info);
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let worker_key = key.clone();
+    let handle = std::thread::spawn(move || {
+        let info = inspect();
+        if let Some(ref info) = info
+            && let Ok(json) = serde_json::to_string(info)
+        {
+            let _ = crate::db::set_api_cache(&worker_key, &json, GIT_INFO_CACHE_TTL_SECONDS);
+        }
+        if got_lock {
+            let _ = crate::db::set_api_cache(&lock_key, "", 0);
+        }
+        let _ = tx.send(info);
+    });
+
+    match rx.recv_timeout(budget) {
+        Ok(info) => {
+            let _ = handle.join();
+            info
+        }
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            *BACKGROUND.lock().unwrap_or_else(|e| e.into_inner()) = Some(handle);
+            stale_git_info(&key)
+        }
+        // The worker panicked
+        Err(mpsc::RecvTimeoutError::Disconnected) => None,
+    }
+}
+
+/// Cache key for an inspection of `project_dir` taken with `options`
+fn git_info_cache_key(project_dir: &Path, options: GitInspectOptions) -> String {
+    let GitInspectOptions {
+        untracked,
+        describe,
+        line_stats,
+        lfs,
+        default_divergence,
+    } = options;
+    let flags: String = [untracked, describe, line_stats, lfs, default_divergence]
+        .iter()
+        .map(|&on| if on { '1' } else { '0' })
+        .collect();
+    format!("{GIT_INFO_CACHE_PREFIX}{}:{flags}", project_dir.display())
+}
+
+/// The last cached inspection, even if expired, with its age set
+fn stale_git_info(key: &str) -> Option<GitInfo> {
+    let (cached, fetched_at) = crate::db::get_stale_api_cache_entry(key).ok()??;
+    let mut info: GitInfo = serde_json::from_str(&cached).ok()?;
+    let age = (crate::utils::now_utc().timestamp() - fetched_at).max(0);
+    info.stale_age_secs = Some(age);
+    record_cache("git", CacheLayer::SqliteStale, Some(age));
+    Some(info)
+}
+
+/// Wait for an inspection that outran its budget so its result reaches the
+/// cache for the next render. Call after the statusline has been printed and
+/// stdout released ([`crate::utils::release_stdout`]).
+pub fn finish_background() {
+    let handle = BACKGROUND.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(handle) = handle {
+        let _ = handle.join();
+    }
+}
+
+/// Optional parts of [`read_git_info`]. The untracked count needs a worktree
+/// walk, describe and default-branch divergence a history walk, line stats a
+/// read of every changed file, and LFS a peek into LFS-tracked files, so each
+/// is only taken when set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GitInspectOptions {
+    pub untracked: bool,
+    pub describe: bool,
+    pub line_stats: bool,
+    pub lfs: bool,
+    pub default_divergence: bool,
+}
+
+/// Read repository state for the header
+pub fn read_git_info(start_dir: &Path, options: GitInspectOptions) -> Option<GitInfo> {
     let repo = gix::discover(start_dir).ok()?;
     let mut info = GitInfo::default();
 
@@ -30,6 +182,17 @@ pub fn read_git_info(start_dir: &Path) -> Option<GitInfo> {
     if let Ok(Some(id)) = head.try_peel_to_id() {
         let hex = id.to_hex().to_string();
         info.short_commit = Some(hex.chars().take(7).collect());
+        info.commit = Some(hex);
+    }
+    if let Ok(commit) = repo.head_commit()
+        && let Ok(time) = commit.time()
+    {
+        info.head_age_secs = Some((crate::utils::now_utc().timestamp() - time.seconds).max(0));
+    }
+
+    if let Some(workdir) = repo.workdir() {
+        info.root = Some(workdir.display().to_string());
+        info.subproject = crate::utils::subproject_dir(workdir, start_dir);
     }
 
     // Dirty status via index vs worktree (untracked files do not affect it)
@@ -38,16 +201,57 @@ pub fn read_git_info(start_dir: &Path) -> Option<GitInfo> {
         Err(_) => info.is_clean = None,
     }
 
+    // One status walk serves both the untracked count and the line stats
+    let diff_lines = options.line_stats && info.is_clean != Some(true);
+    let status = if options.untracked || diff_lines {
+        status_summary(&repo, options.untracked, diff_lines)
+    } else {
+        None
+    };
+    if options.untracked {
+        info.untracked = status.as_ref().and_then(|s| s.untracked);
+    }
+
+    if options.describe {
+        info.describe = describe_head(&repo);
+    }
+    if options.line_stats {
+        let stats = match (info.is_clean, status) {
+            (Some(true), _) => Some((0, 0)),
+            (_, Some(status)) => worktree_line_stats(&repo, &status.changed),
+            (_, None) => None,
+        };
+        if let Some((added, removed)) = stats {
+            info.lines_added = Some(added);
+            info.lines_removed = Some(removed);
+        }
+    }
+    info.conflicts = conflict_count(&repo);
+    info.sparse_checkout = sparse_checkout(&repo);
+    if options.lfs {
+        info.lfs_pending = lfs_pending(&repo);
+    }
+    if let Some((initialized, dirty)) = submodule_counts(&repo) {
+        info.submodules = Some(initialized);
+        info.submodules_dirty = Some(dirty);
+    }
+    info.stash_count = Some(stash_count(&repo));
+    info.operation = operation_in_progress(&repo);
+
     // Sanitized remote URL from config
     let cfg = repo.config_snapshot();
     if let Some(url) = cfg.string("remote.origin.url") {
         info.remote_url = sanitize_remote_url(&url.to_string());
     }
+    info.default_branch = default_branch(&repo);
 
     // Worktree count (primary + linked) and detect if current is a linked worktree
     let mut count = 1usize;
     if let Ok(wts) = repo.worktrees() {
         count += wts.len();
+        if !wts.is_empty() {
+            info.worktrees = list_worktrees(&repo, &wts);
+        }
     }
     info.worktree_count = Some(count);
     // Determine if current working dir is a linked worktree by checking if .git is a file
@@ -74,41 +278,579 @@ pub fn read_git_info(start_dir: &Path) -> Option<GitInfo> {
             let merge_short = merge_s
                 .strip_prefix("refs/heads/")
                 .unwrap_or(merge_s.as_str());
+            // A `.` remote tracks a local branch
+            info.upstream = Some(if remote_s == "." {
+                merge_short.to_string()
+            } else {
+                format!("{}/{}", remote_s, merge_short)
+            });
             let upstream_ref = format!("refs/remotes/{}/{}", remote_s, merge_short);
-            if let Ok(mut up_ref) = repo.find_reference(upstream_ref.as_str()) {
-                if let Ok(up_id) = up_ref.peel_to_id() {
-                    if let Ok(Some(head_id)) = repo.head().ok()?.try_peel_to_id() {
-                        let mut head_set = std::collections::HashSet::<String>::new();
-                        if let Ok(iter) = head_id.ancestors().all() {
-                            for item in iter.flatten() {
-                                head_set.insert(item.id.to_string());
-                                if head_set.len() >= MAX_ANCESTOR_WALK {
-                                    break;
-                                }
-                            }
-                        }
-                        let mut up_set = std::collections::HashSet::<String>::new();
-                        if let Ok(iter) = up_id.ancestors().all() {
-                            for item in iter.flatten() {
-                                up_set.insert(item.id.to_string());
-                                if up_set.len() >= MAX_ANCESTOR_WALK {
-                                    break;
-                                }
-                            }
-                        }
-                        let ahead = head_set.difference(&up_set).count();
-                        let behind = up_set.difference(&head_set).count();
-                        info.ahead = Some(ahead);
-                        info.behind = Some(behind);
-                        info.is_head_on_remote = Some(ahead == 0 && behind == 0);
-                    }
-                }
+            if let Ok(mut up_ref) = repo.find_reference(upstream_ref.as_str())
+                && let Ok(up_id) = up_ref.peel_to_id()
+                && let Ok(head_id) = repo.head_id()
+            {
+                let (ahead, behind) = ahead_behind(head_id, up_id);
+                info.ahead = Some(ahead);
+                info.behind = Some(behind);
+                info.is_head_on_remote = Some(ahead == 0 && behind == 0);
             }
         }
     }
+
+    // Divergence from the remote default branch, which an up-to-date
+    // feature branch's own upstream hides
+    if options.default_divergence
+        && let Some(default) = info.default_branch.as_deref()
+        && info.branch.as_deref() != Some(default)
+        && let Ok(mut default_ref) =
+            repo.find_reference(format!("refs/remotes/origin/{default}").as_str())
+        && let Ok(default_id) = default_ref.peel_to_id()
+        && let Ok(head_id) = repo.head_id()
+    {
+        let (ahead, behind) = ahead_behind(head_id, default_id);
+        info.default_ahead = Some(ahead);
+        info.default_behind = Some(behind);
+    }
     Some(info)
 }
 
+/// Repository conditions that slow agents down in the worktree at `root`,
+/// served from the db cache so the walk runs at most once per hour.
+pub fn repo_health(root: &Path, max_git_dir_gb: u32) -> Vec<GitHealthIssue> {
+    let key = format!("{HEALTH_CACHE_PREFIX}{}:{max_git_dir_gb}", root.display());
+    if let Ok(Some((cached, fetched_at))) = crate::db::get_api_cache_entry(&key)
+        && let Ok(issues) = serde_json::from_str(&cached)
+    {
+        let age = crate::utils::now_utc().timestamp() - fetched_at;
+        record_cache("git_health", CacheLayer::Sqlite, Some(age));
+        return issues;
+    }
+    let Ok(repo) = gix::open(root) else {
+        return Vec::new();
+    };
+    let issues = check_health(&repo, u64::from(max_git_dir_gb) << 30);
+    if let Ok(json) = serde_json::to_string(&issues) {
+        let _ = crate::db::set_api_cache(&key, &json, HEALTH_CACHE_TTL_SECONDS);
+    }
+    record_cache("git_health", CacheLayer::FreshScan, None);
+    issues
+}
+
+/// One untracked walk finds both unignored build output and top-level
+/// directories with at least [`LARGE_UNTRACKED_DIR_FILES`] files.
+fn check_health(repo: &gix::Repository, max_git_dir_bytes: u64) -> Vec<GitHealthIssue> {
+    use gix::status::index_worktree::Item;
+    use std::collections::BTreeMap;
+    let mut issues = Vec::new();
+
+    let mut per_dir = BTreeMap::<String, u64>::new();
+    let mut build_dirs = BTreeMap::<String, u64>::new();
+    if let Ok(iter) = repo
+        .status(gix::progress::Discard)
+        .map_err(drop)
+        .and_then(|status| {
+            status
+                .untracked_files(gix::status::UntrackedFiles::Files)
+                .into_index_worktree_iter(Vec::new())
+                .map_err(drop)
+        })
+    {
+        for item in iter.flatten() {
+            let Item::DirectoryContents { entry, .. } = item else {
+                continue;
+            };
+            if !matches!(entry.status, gix::dir::entry::Status::Untracked) {
+                continue;
+            }
+            let path = entry.rela_path.to_string();
+            let mut components: Vec<&str> = path.split('/').collect();
+            // The file name itself is not a directory
+            components.pop();
+            if let Some(top) = components.first() {
+                *per_dir.entry((*top).to_string()).or_default() += 1;
+            }
+            if let Some(at) = components
+                .iter()
+                .position(|c| BUILD_ARTIFACT_DIRS.contains(c))
+            {
+                *build_dirs.entry(components[..=at].join("/")).or_default() += 1;
+            }
+        }
+    }
+    issues.extend(build_dirs.into_iter().map(|(path, size)| GitHealthIssue {
+        kind: GitHealthKind::UnignoredBuildDir,
+        path,
+        size,
+    }));
+    issues.extend(
+        per_dir
+            .into_iter()
+            .filter(|(_, files)| *files >= LARGE_UNTRACKED_DIR_FILES)
+            .map(|(path, size)| GitHealthIssue {
+                kind: GitHealthKind::LargeUntrackedDir,
+                path,
+                size,
+            }),
+    );
+
+    let git_dir_bytes = dir_size(repo.common_dir());
+    if git_dir_bytes > max_git_dir_bytes {
+        issues.push(GitHealthIssue {
+            kind: GitHealthKind::LargeGitDir,
+            path: ".git".to_string(),
+            size: git_dir_bytes,
+        });
+    }
+    issues
+}
+
+/// Total size of the files below `dir`, without following symlinks.
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Commits reachable from `head` but not `other`, and the reverse, each
+/// side walked at most [`MAX_ANCESTOR_WALK`] commits deep.
+fn ahead_behind(head: gix::Id<'_>, other: gix::Id<'_>) -> (usize, usize) {
+    if head == other {
+        return (0, 0);
+    }
+    let ancestors = |id: gix::Id<'_>| {
+        let mut set = std::collections::HashSet::new();
+        if let Ok(iter) = id.ancestors().all() {
+            for item in iter.flatten() {
+                set.insert(item.id);
+                if set.len() >= MAX_ANCESTOR_WALK {
+                    break;
+                }
+            }
+        }
+        set
+    };
+    let (head_set, other_set) = (ancestors(head), ancestors(other));
+    (
+        head_set.difference(&other_set).count(),
+        other_set.difference(&head_set).count(),
+    )
+}
+
+/// What one status walk turns up
+#[derive(Default)]
+struct StatusSummary {
+    /// Untracked files as `git status -uall` lists them, ignored files
+    /// excluded; only when counted
+    untracked: Option<usize>,
+    /// Tracked paths that differ between HEAD and the worktree; only when
+    /// collected
+    changed: Vec<gix::bstr::BString>,
+}
+
+/// Walk the status once, counting untracked files and collecting changed
+/// paths as asked. Without changed paths the HEAD-to-index diff is skipped.
+fn status_summary(
+    repo: &gix::Repository,
+    count_untracked: bool,
+    collect_changed: bool,
+) -> Option<StatusSummary> {
+    use gix::status::index_worktree::Item as WorktreeItem;
+    let untracked_files = if count_untracked {
+        gix::status::UntrackedFiles::Files
+    } else {
+        gix::status::UntrackedFiles::None
+    };
+    let status = repo
+        .status(gix::progress::Discard)
+        .ok()?
+        .untracked_files(untracked_files);
+    let mut summary = StatusSummary {
+        untracked: count_untracked.then_some(0),
+        ..Default::default()
+    };
+    // Directory walk results are untracked (or ignored), never changes
+    let mut count = |item: &WorktreeItem| {
+        if let WorktreeItem::DirectoryContents { entry, .. } = item
+            && matches!(entry.status, gix::dir::entry::Status::Untracked)
+            && let Some(untracked) = summary.untracked.as_mut()
+        {
+            *untracked += 1;
+        }
+    };
+    if collect_changed {
+        let mut changed = std::collections::BTreeSet::new();
+        for item in status.into_iter(None).ok()? {
+            match item.ok()? {
+                gix::status::Item::IndexWorktree(item @ WorktreeItem::DirectoryContents { .. }) => {
+                    count(&item)
+                }
+                item => {
+                    changed.insert(item.location().to_owned());
+                }
+            }
+        }
+        summary.changed = changed.into_iter().collect();
+    } else {
+        for item in status.into_index_worktree_iter(Vec::new()).ok()? {
+            count(&item.ok()?);
+        }
+    }
+    Some(summary)
+}
+
+/// Lines added and removed in `changed` between HEAD and the worktree, as
+/// `git diff HEAD --numstat` would sum them. Worktree files go through the
+/// repository's to-git conversion (line endings, `ident`, encoding) first,
+/// so a CRLF checkout is compared as it would be committed. Binary files and
+/// files over [`LINE_STATS_MAX_FILE_BYTES`] on either side are skipped.
+fn worktree_line_stats(
+    repo: &gix::Repository,
+    changed: &[gix::bstr::BString],
+) -> Option<(usize, usize)> {
+    use gix::diff::blob::{Algorithm, Diff, InternedInput};
+    use std::io::Read;
+    let workdir = repo.workdir()?;
+    // Unborn HEAD: everything staged counts as added
+    let head_tree = repo.head_tree().ok();
+    let (pipeline, index) = repo.filter_pipeline(None).ok()?;
+    let (mut filters, mut attributes) = pipeline.into_parts();
+    // External clean filters (Git LFS and the like) would spawn a process
+    // per file; the built-in conversions are enough for a line count
+    filters.options_mut().drivers.clear();
+
+    let (mut added, mut removed) = (0, 0);
+    for path in changed {
+        let path = gix::path::from_bstr(path);
+        let old_entry = head_tree
+            .as_ref()
+            .and_then(|tree| tree.lookup_entry_by_path(&path).ok().flatten())
+            .filter(|entry| entry.mode().is_blob());
+        if old_entry.as_ref().is_some_and(|entry| {
+            repo.find_header(entry.object_id())
+                .map_or(true, |header| header.size() > LINE_STATS_MAX_FILE_BYTES)
+        }) {
+            continue;
+        }
+        let old = old_entry
+            .and_then(|entry| entry.object().ok())
+            .map(|object| object.detach().data)
+            .unwrap_or_default();
+
+        // Deleted from the worktree, or a submodule directory
+        let file = workdir.join(&path);
+        let new = match std::fs::metadata(&file) {
+            Ok(meta) if meta.is_file() && meta.len() > LINE_STATS_MAX_FILE_BYTES => continue,
+            Ok(meta) if meta.is_file() => std::fs::read(&file).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        if is_binary(&old) || is_binary(&new) {
+            continue;
+        }
+        let new = filters
+            .convert_to_git(
+                new.as_slice(),
+                &path,
+                &mut |_, outcome| {
+                    if let Ok(platform) = attributes.at_path(path.as_ref(), None, &repo.objects) {
+                        platform.matching_attributes(outcome);
+                    }
+                },
+                // The index version decides whether `core.autocrlf` applies
+                &mut |buf| {
+                    use gix::objs::Find;
+                    let Some(entry) = index.entry_by_path(gix::path::into_bstr(&*path).as_ref())
+                    else {
+                        return Ok(None);
+                    };
+                    let object = repo.objects.try_find(&entry.id, buf)?;
+                    Ok(object
+                        .filter(|object| object.kind == gix::object::Kind::Blob)
+                        .map(drop))
+                },
+            )
+            .ok()
+            .and_then(|mut converted| {
+                let mut buf = Vec::new();
+                converted.read_to_end(&mut buf).ok()?;
+                Some(buf)
+            })
+            .unwrap_or(new);
+        let input = InternedInput::new(old.as_slice(), new.as_slice());
+        let diff = Diff::compute(Algorithm::Histogram, &input);
+        added += diff.count_additions() as usize;
+        removed += diff.count_removals() as usize;
+    }
+    Some((added, removed))
+}
+
+/// Git's heuristic: a NUL byte in the first 8000 bytes means binary.
+fn is_binary(data: &[u8]) -> bool {
+    data.iter().take(8000).any(|&b| b == 0)
+}
+
+/// Nearest tag, lightweight or annotated, as `git describe --tags` finds it.
+fn describe_head(repo: &gix::Repository) -> Option<GitDescribe> {
+    use gix::commit::describe::SelectRef;
+    let commit = repo.head_commit().ok()?;
+    let resolution = commit
+        .describe()
+        .names(SelectRef::AllTags)
+        .try_resolve()
+        .ok()??;
+    let tag = resolution.outcome.name.as_ref()?.to_string();
+    let distance = resolution.outcome.depth;
+    let text = resolution.format().ok()?.to_string();
+    Some(GitDescribe {
+        tag,
+        distance,
+        text,
+    })
+}
+
+/// Initialized submodules and how many are dirty, i.e. checked out at a
+/// different commit than the superproject's index records or carrying
+/// worktree changes. `None` when the repository has no `.gitmodules`.
+fn submodule_counts(repo: &gix::Repository) -> Option<(usize, usize)> {
+    let submodules = repo.submodules().ok()??;
+    let (mut initialized, mut dirty) = (0, 0);
+    for submodule in submodules {
+        let Ok(state) = submodule.state() else {
+            continue;
+        };
+        if !(state.repository_exists && state.worktree_checkout) {
+            continue;
+        }
+        initialized += 1;
+        let ignore = submodule.ignore().ok().flatten().unwrap_or_default();
+        let status = submodule.status(ignore, true).ok();
+        if status.and_then(|s| s.is_dirty()) == Some(true) {
+            dirty += 1;
+        }
+    }
+    Some((initialized, dirty))
+}
+
+/// Paths with entries in a non-zero index stage. Git keeps the base, ours,
+/// and theirs versions side by side, sorted by path, until resolution.
+fn conflict_count(repo: &gix::Repository) -> Option<usize> {
+    let index = repo.index_or_empty().ok()?;
+    let mut count = 0;
+    let mut last: Option<&gix::bstr::BStr> = None;
+    for entry in index.entries() {
+        if entry.stage_raw() == 0 {
+            continue;
+        }
+        let path = entry.path(&index);
+        if last != Some(path) {
+            count += 1;
+            last = Some(path);
+        }
+    }
+    Some(count)
+}
+
+/// `core.sparseCheckout`, or a sparse index with collapsed directories.
+fn sparse_checkout(repo: &gix::Repository) -> Option<bool> {
+    if repo.config_snapshot().boolean("core.sparseCheckout") == Some(true) {
+        return Some(true);
+    }
+    Some(repo.index_or_empty().ok()?.is_sparse())
+}
+
+/// First line of every Git LFS pointer file
+const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs.github.com/spec/";
+/// Pointer files are ~130 bytes; anything bigger holds real content
+const LFS_POINTER_MAX_BYTES: u64 = 1024;
+
+/// Worktree files that are still LFS pointers, i.e. waiting on
+/// `git lfs pull`. Only pointer-sized files among the paths the root
+/// `.gitattributes` routes through `filter=lfs` are opened, and only their
+/// first line is read. `None` when no path is routed there.
+fn lfs_pending(repo: &gix::Repository) -> Option<usize> {
+    use gix::index::entry::{Flags, Mode};
+    use std::io::Read;
+    let workdir = repo.workdir()?;
+    let patterns = lfs_patterns(workdir);
+    if patterns.is_empty() {
+        return None;
+    }
+    let index = repo.index_or_empty().ok()?;
+    let mut pending = 0;
+    for entry in index.entries() {
+        if entry.stage_raw() != 0
+            || !matches!(entry.mode, Mode::FILE | Mode::FILE_EXECUTABLE)
+            || entry.flags.contains(Flags::SKIP_WORKTREE)
+        {
+            continue;
+        }
+        let rela_path = entry.path(&index).to_string();
+        if !is_lfs_tracked(&patterns, &rela_path) {
+            continue;
+        }
+        let path = workdir.join(&rela_path);
+        // A freshly built index records no stat data
+        let size = match entry.stat.size {
+            0 => std::fs::metadata(&path).map_or(0, |m| m.len()),
+            size => u64::from(size),
+        };
+        if size == 0 || size > LFS_POINTER_MAX_BYTES {
+            continue;
+        }
+        let mut head = Vec::with_capacity(LFS_POINTER_PREFIX.len());
+        let read = std::fs::File::open(&path).and_then(|file| {
+            file.take(LFS_POINTER_PREFIX.len() as u64)
+                .read_to_end(&mut head)
+        });
+        if read.is_ok() && head == LFS_POINTER_PREFIX {
+            pending += 1;
+        }
+    }
+    Some(pending)
+}
+
+/// Patterns in the root `.gitattributes` that set `filter=lfs`
+fn lfs_patterns(workdir: &Path) -> Vec<String> {
+    let Ok(attrs) = std::fs::read_to_string(workdir.join(".gitattributes")) else {
+        return Vec::new();
+    };
+    attrs
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next().filter(|p| !p.starts_with('#'))?;
+            fields
+                .any(|attr| attr == "filter=lfs")
+                .then(|| pattern.to_string())
+        })
+        .collect()
+}
+
+/// Gitattributes matching: patterns without a slash match the file name at
+/// any depth, the rest the path from the root.
+fn is_lfs_tracked(patterns: &[String], rela_path: &str) -> bool {
+    let file_name = rela_path.rsplit('/').next().unwrap_or(rela_path);
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        if pattern.contains('/') {
+            crate::utils::glob_match(pattern, rela_path)
+        } else {
+            crate::utils::glob_match(pattern, file_name)
+        }
+    })
+}
+
+/// Detect an unfinished operation from the state files git leaves in the
+/// (per-worktree) git dir, with rebase progress from its step counters.
+fn operation_in_progress(repo: &gix::Repository) -> Option<GitOperation> {
+    use gix::state::InProgress;
+    let kind = match repo.state()? {
+        InProgress::Merge => GitOperationKind::Merge,
+        InProgress::Rebase | InProgress::RebaseInteractive | InProgress::ApplyMailboxRebase => {
+            GitOperationKind::Rebase
+        }
+        InProgress::CherryPick | InProgress::CherryPickSequence => GitOperationKind::CherryPick,
+        InProgress::Revert | InProgress::RevertSequence => GitOperationKind::Revert,
+        InProgress::ApplyMailbox => GitOperationKind::Am,
+        InProgress::Bisect => GitOperationKind::Bisect,
+    };
+    let git_dir = repo.path();
+    let read_count = |rel: &str| -> Option<usize> {
+        std::fs::read_to_string(git_dir.join(rel))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    let (step, total) = if git_dir.join("rebase-merge").is_dir() {
+        (
+            read_count("rebase-merge/msgnum"),
+            read_count("rebase-merge/end"),
+        )
+    } else if git_dir.join("rebase-apply").is_dir() {
+        (
+            read_count("rebase-apply/next"),
+            read_count("rebase-apply/last"),
+        )
+    } else {
+        (None, None)
+    };
+    Some(GitOperation { kind, step, total })
+}
+
+/// Main worktree first, then linked ones, flagging the one `repo` was
+/// opened from. Branches come straight from each worktree's `HEAD` file.
+fn list_worktrees(repo: &gix::Repository, linked: &[gix::worktree::Proxy<'_>]) -> Vec<GitWorktree> {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let current = repo.workdir().map(canonical);
+    let head_branch = |git_dir: &Path| {
+        let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+        head.trim()
+            .strip_prefix("ref: refs/heads/")
+            .map(str::to_string)
+    };
+    let entry = |name: String, path: std::path::PathBuf, git_dir: &Path, is_main: bool| {
+        let path = canonical(&path);
+        GitWorktree {
+            name,
+            branch: head_branch(git_dir),
+            is_main,
+            is_current: current.as_deref() == Some(path.as_path()),
+            path: path.display().to_string(),
+        }
+    };
+
+    let mut worktrees = Vec::new();
+    if let Ok(main) = repo.main_repo()
+        && let Some(workdir) = main.workdir()
+    {
+        let name = workdir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        worktrees.push(entry(name, workdir.to_path_buf(), main.git_dir(), true));
+    }
+    for proxy in linked {
+        if let Ok(base) = proxy.base() {
+            worktrees.push(entry(proxy.id().to_string(), base, proxy.git_dir(), false));
+        }
+    }
+    worktrees
+}
+
+/// Branch `origin/HEAD` points at, as `git clone` or
+/// `git remote set-head` recorded it.
+fn default_branch(repo: &gix::Repository) -> Option<String> {
+    let reference = repo.find_reference("refs/remotes/origin/HEAD").ok()?;
+    match reference.target() {
+        gix::refs::TargetRef::Symbolic(name) => name
+            .as_bstr()
+            .to_string()
+            .strip_prefix("refs/remotes/origin/")
+            .map(str::to_string),
+        gix::refs::TargetRef::Object(_) => None,
+    }
+}
+
+/// Stash entries live in the reflog of `refs/stash`, newest first.
+fn stash_count(repo: &gix::Repository) -> usize {
+    let Ok(stash) = repo.find_reference("refs/stash") else {
+        return 0;
+    };
+    let mut log = stash.log_iter();
+    match log.all() {
+        Ok(Some(entries)) => entries.count(),
+        _ => 0,
+    }
+}
+
 fn sanitize_remote_url(raw: &str) -> Option<String> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
@@ -135,7 +877,20 @@ fn sanitize_remote_url(raw: &str) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::sanitize_remote_url;
+    use super::{
+        GitInfo, GitInspectOptions, GitOperation, GitOperationKind, read_git_info,
+        sanitize_remote_url,
+    };
+    use gix::refs::transaction::PreviousValue;
+
+    fn init_with_identity(path: &std::path::Path) -> gix::Repository {
+        gix::init(path).unwrap();
+        let config = path.join(".git/config");
+        let mut text = std::fs::read_to_string(&config).unwrap();
+        text.push_str("[user]\n\tname = Test\n\temail = test@example.com\n");
+        std::fs::write(&config, text).unwrap();
+        gix::open(path).unwrap()
+    }
 
     #[test]
     fn remote_url_sanitizer_removes_userinfo() {
@@ -161,4 +916,587 @@ mod tests {
             None
         );
     }
+
+    #[test]
+    fn untracked_files_are_counted_only_when_requested() {
+        let dir = tempfile::TempDir::new().unwrap();
+        gix::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("new.txt"), "a").unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        std::fs::write(dir.path().join("nested/other.txt"), "b").unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "ignored.log\n").unwrap();
+        std::fs::write(dir.path().join("ignored.log"), "c").unwrap();
+
+        let info = read_git_info(
+            dir.path(),
+            GitInspectOptions {
+                untracked: true,
+                ..Default::default()
+            },
+        )
+        .expect("git info");
+        assert_eq!(info.untracked, Some(3));
+
+        let info = read_git_info(dir.path(), GitInspectOptions::default()).expect("git info");
+        assert_eq!(info.untracked, None);
+    }
+
+    #[test]
+    fn upstream_comes_from_branch_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        gix::init(dir.path()).unwrap();
+        let info = read_git_info(dir.path(), GitInspectOptions::default()).unwrap();
+        assert_eq!(info.upstream, None);
+        assert_eq!(info.head_age_secs, None);
+
+        let branch = info.branch.expect("unborn branch name");
+        let config = dir.path().join(".git/config");
+        let mut text = std::fs::read_to_string(&config).unwrap();
+        text.push_str(&format!(
+            "[branch \"{branch}\"]\n\tremote = upstream\n\tmerge = refs/heads/trunk\n"
+        ));
+        std::fs::write(&config, text).unwrap();
+
+        let info = read_git_info(dir.path(), GitInspectOptions::default()).unwrap();
+        assert_eq!(info.upstream.as_deref(), Some("upstream/trunk"));
+    }
+
+    #[test]
+    fn worktrees_list_main_and_linked_with_current() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let main = dir.path().join("main");
+        let linked = dir.path().join("agent-1");
+        std::fs::create_dir_all(&linked).unwrap();
+        let repo = init_with_identity(&main);
+        repo.commit(
+            "HEAD",
+            "first",
+            repo.empty_tree().id,
+            gix::commit::NO_PARENT_IDS,
+        )
+        .unwrap();
+
+        // What `git worktree add ../agent-1 -b feature` leaves behind
+        let admin = main.join(".git/worktrees/agent-1");
+        std::fs::create_dir_all(&admin).unwrap();
+        std::fs::write(admin.join("HEAD"), "ref: refs/heads/feature\n").unwrap();
+        std::fs::write(admin.join("commondir"), "../..\n").unwrap();
+        std::fs::write(
+            admin.join("gitdir"),
+            format!("{}\n", linked.join(".git").display()),
+        )
+        .unwrap();
+        std::fs::write(
+            linked.join(".git"),
+            format!("gitdir: {}\n", admin.display()),
+        )
+        .unwrap();
+
+        let info = read_git_info(&linked, GitInspectOptions::default()).unwrap();
+        assert_eq!(info.worktree_count, Some(2));
+        let names: Vec<_> = info
+            .worktrees
+            .iter()
+            .map(|wt| (wt.name.as_str(), wt.is_main, wt.is_current))
+            .collect();
+        assert_eq!(names, [("main", true, false), ("agent-1", false, true)]);
+        assert_eq!(info.worktrees[1].branch.as_deref(), Some("feature"));
+    }
+
+    #[test]
+    fn default_branch_follows_origin_head() {
+        let dir = tempfile::TempDir::new().unwrap();
+        gix::init(dir.path()).unwrap();
+        let info = read_git_info(dir.path(), GitInspectOptions::default()).unwrap();
+        assert_eq!(info.default_branch, None);
+
+        let remotes = dir.path().join(".git/refs/remotes/origin");
+        std::fs::create_dir_all(&remotes).unwrap();
+        std::fs::write(remotes.join("HEAD"), "ref: refs/remotes/origin/trunk\n").unwrap();
+        let info = read_git_info(dir.path(), GitInspectOptions::default()).unwrap();
+        assert_eq!(info.default_branch.as_deref(), Some("trunk"));
+    }
+
+    #[test]
+    fn health_reports_build_output_large_untracked_dirs_and_git_dir_size() {
+        use crate::models::git::GitHealthKind;
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = init_with_identity(dir.path());
+        std::fs::write(dir.path().join(".gitignore"), "/dist\n").unwrap();
+        for sub in ["target/debug", "dist", "crates/app/node_modules/pkg"] {
+            std::fs::create_dir_all(dir.path().join(sub)).unwrap();
+            std::fs::write(dir.path().join(sub).join("out"), "x").unwrap();
+        }
+        let data = dir.path().join("data");
+        std::fs::create_dir(&data).unwrap();
+        for n in 0..super::LARGE_UNTRACKED_DIR_FILES {
+            std::fs::write(data.join(format!("{n}.csv")), "").unwrap();
+        }
+
+        let issues = super::check_health(&repo, u64::MAX);
+        let found: Vec<_> = issues
+            .iter()
+            .map(|issue| (issue.kind, issue.path.as_str(), issue.size))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    GitHealthKind::UnignoredBuildDir,
+                    "crates/app/node_modules",
+                    1
+                ),
+                (GitHealthKind::UnignoredBuildDir, "target", 1),
+                (GitHealthKind::LargeUntrackedDir, "data", 1000),
+            ]
+        );
+
+        let issues = super::check_health(&repo, 0);
+        let git_dir = issues.last().unwrap();
+        assert_eq!(
+            (git_dir.kind, git_dir.path.as_str()),
+            (GitHealthKind::LargeGitDir, ".git")
+        );
+        assert!(git_dir.size > 0);
+    }
+
+    #[test]
+    fn divergence_from_default_branch_ignores_own_upstream() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = init_with_identity(dir.path());
+        let tree = repo.empty_tree().id;
+        let base = repo
+            .commit("HEAD", "base", tree, gix::commit::NO_PARENT_IDS)
+            .unwrap()
+            .detach();
+        repo.reference(
+            "refs/remotes/origin/main",
+            base,
+            PreviousValue::MustNotExist,
+            "fetch",
+        )
+        .unwrap();
+        repo.commit("refs/remotes/origin/main", "upstream", tree, [base])
+            .unwrap();
+        repo.reference(
+            "refs/heads/feature",
+            base,
+            PreviousValue::MustNotExist,
+            "branch",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join(".git/HEAD"), "ref: refs/heads/feature\n").unwrap();
+        let first = repo.commit("HEAD", "feature 1", tree, [base]).unwrap();
+        repo.commit("HEAD", "feature 2", tree, [first.detach()])
+            .unwrap();
+
+        let divergence = GitInspectOptions {
+            default_divergence: true,
+            ..Default::default()
+        };
+        let info = read_git_info(dir.path(), divergence).unwrap();
+        assert_eq!(info.default_ahead, None);
+
+        std::fs::write(
+            dir.path().join(".git/refs/remotes/origin/HEAD"),
+            "ref: refs/remotes/origin/main\n",
+        )
+        .unwrap();
+        let info = read_git_info(dir.path(), divergence).unwrap();
+        assert_eq!(
+            (info.default_ahead, info.default_behind),
+            (Some(2), Some(1))
+        );
+        assert_eq!(info.ahead, None);
+        let info = read_git_info(dir.path(), GitInspectOptions::default()).unwrap();
+        assert_eq!(info.default_ahead, None);
+
+        // On the default branch itself there is nothing to compare
+        std::fs::write(dir.path().join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        let info = read_git_info(dir.path(), divergence).unwrap();
+        assert_eq!(info.branch.as_deref(), Some("main"));
+        assert_eq!(info.default_ahead, None);
+    }
+
+    #[test]
+    fn describe_reports_nearest_tag_when_requested() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = init_with_identity(dir.path());
+        let tree = repo.empty_tree().id;
+        let first = repo
+            .commit("HEAD", "first", tree, gix::commit::NO_PARENT_IDS)
+            .unwrap()
+            .detach();
+        repo.tag_reference("v1.4.2", first, PreviousValue::MustNotExist)
+            .unwrap();
+        let second = repo.commit("HEAD", "second", tree, [first]).unwrap();
+        repo.commit("HEAD", "third", tree, [second.detach()])
+            .unwrap();
+
+        let info = read_git_info(
+            dir.path(),
+            GitInspectOptions {
+                describe: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(info.head_age_secs.is_some_and(|age| age < 60));
+        let describe = info.describe.expect("describe");
+        assert_eq!(describe.tag, "v1.4.2");
+        assert_eq!(describe.distance, 2);
+        assert!(describe.text.starts_with("v1.4.2-2-g"), "{}", describe.text);
+
+        assert_eq!(
+            read_git_info(dir.path(), GitInspectOptions::default())
+                .unwrap()
+                .describe,
+            None
+        );
+    }
+
+    #[test]
+    fn submodules_off_the_recorded_commit_count_as_dirty() {
+        use gix::index::entry::{Flags, Mode, Stat};
+        let dir = tempfile::TempDir::new().unwrap();
+        gix::init(dir.path()).unwrap();
+        assert_eq!(
+            read_git_info(dir.path(), GitInspectOptions::default())
+                .unwrap()
+                .submodules,
+            None
+        );
+
+        let sub = init_with_identity(&dir.path().join("sub"));
+        let tree = sub.empty_tree().id;
+        let recorded = sub
+            .commit("HEAD", "first", tree, gix::commit::NO_PARENT_IDS)
+            .unwrap()
+            .detach();
+        std::fs::write(
+            dir.path().join(".gitmodules"),
+            "[submodule \"sub\"]\n\tpath = sub\n\turl = ./sub\n",
+        )
+        .unwrap();
+        let mut state = gix::index::State::new(gix::hash::Kind::Sha1);
+        state.dangerously_push_entry(
+            Stat::default(),
+            recorded,
+            Flags::empty(),
+            Mode::COMMIT,
+            "sub".into(),
+        );
+        let mut index = gix::index::File::from_state(state, dir.path().join(".git/index"));
+        index.write(Default::default()).unwrap();
+
+        let info = read_git_info(dir.path(), GitInspectOptions::default()).unwrap();
+        assert_eq!((info.submodules, info.submodules_dirty), (Some(1), Some(0)));
+
+        sub.commit("HEAD", "second", tree, [recorded]).unwrap();
+        let info = read_git_info(dir.path(), GitInspectOptions::default()).unwrap();
+        assert_eq!((info.submodules, info.submodules_dirty), (Some(1), Some(1)));
+    }
+
+    #[test]
+    fn line_stats_diff_worktree_against_head() {
+        use gix::objs::tree::{Entry, EntryKind};
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = init_with_identity(dir.path());
+        let blob = repo.write_blob("one\ntwo\nthree\n").unwrap().detach();
+        let tree = repo
+            .write_object(gix::objs::Tree {
+                entries: vec![Entry {
+                    mode: EntryKind::Blob.into(),
+                    filename: "a.txt".into(),
+                    oid: blob,
+                }],
+            })
+            .unwrap()
+            .detach();
+        repo.commit("HEAD", "first", tree, gix::commit::NO_PARENT_IDS)
+            .unwrap();
+        let mut index = repo.index_from_tree(&tree).unwrap();
+        index.write(Default::default()).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\nTWO\nthree\nfour\n").unwrap();
+
+        let info = read_git_info(
+            dir.path(),
+            GitInspectOptions {
+                line_stats: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!((info.lines_added, info.lines_removed), (Some(2), Some(1)));
+        let info = read_git_info(dir.path(), GitInspectOptions::default()).unwrap();
+        assert_eq!(info.lines_added, None);
+    }
+
+    #[test]
+    fn line_stats_convert_line_endings_and_skip_large_files() {
+        use gix::objs::tree::{Entry, EntryKind};
+        let dir = tempfile::TempDir::new().unwrap();
+        init_with_identity(dir.path());
+        let config = dir.path().join(".git/config");
+        let mut text = std::fs::read_to_string(&config).unwrap();
+        text.push_str("[core]\n\tautocrlf = true\n");
+        std::fs::write(&config, text).unwrap();
+        let repo = gix::open(dir.path()).unwrap();
+        let large = "line\n".repeat(300_000);
+        let entries = [("a.txt", "one\ntwo\nthree\n"), ("big.txt", large.as_str())]
+            .map(|(name, data)| Entry {
+                mode: EntryKind::Blob.into(),
+                filename: name.into(),
+                oid: repo.write_blob(data).unwrap().detach(),
+            })
+            .to_vec();
+        let tree = repo
+            .write_object(gix::objs::Tree { entries })
+            .unwrap()
+            .detach();
+        repo.commit("HEAD", "first", tree, gix::commit::NO_PARENT_IDS)
+            .unwrap();
+        let mut index = repo.index_from_tree(&tree).unwrap();
+        index.write(Default::default()).unwrap();
+        // A CRLF checkout with one line changed
+        std::fs::write(dir.path().join("a.txt"), "one\r\nTWO\r\nthree\r\n").unwrap();
+        std::fs::write(dir.path().join("big.txt"), format!("{large}more\n")).unwrap();
+
+        let info = read_git_info(
+            dir.path(),
+            GitInspectOptions {
+                line_stats: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!((info.lines_added, info.lines_removed), (Some(1), Some(1)));
+    }
+
+    #[test]
+    fn sparse_checkout_and_lfs_pointers_are_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = init_with_identity(dir.path());
+        let info = read_git_info(
+            dir.path(),
+            GitInspectOptions {
+                lfs: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            (info.sparse_checkout, info.lfs_pending),
+            (Some(false), None)
+        );
+
+        let pointer = "version https://git-lfs.github.com/spec/v1\n\
+            oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\
+            size 12345\n";
+        std::fs::write(
+            dir.path().join(".gitattributes"),
+            "*.bin filter=lfs diff=lfs merge=lfs -text\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("model.bin"), pointer).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "plain\n").unwrap();
+        // Pointer text outside the LFS patterns is ordinary content
+        std::fs::write(dir.path().join("pointer.txt"), pointer).unwrap();
+        let mut state = gix::index::State::new(gix::hash::Kind::Sha1);
+        for (path, data) in [
+            ("model.bin", pointer),
+            ("notes.txt", "plain\n"),
+            ("pointer.txt", pointer),
+        ] {
+            let id = repo.write_blob(data).unwrap().detach();
+            state.dangerously_push_entry(
+                Default::default(),
+                id,
+                gix::index::entry::Flags::empty(),
+                gix::index::entry::Mode::FILE,
+                path.into(),
+            );
+        }
+        state.sort_entries();
+        gix::index::File::from_state(state, repo.index_path())
+            .write(Default::default())
+            .unwrap();
+        let mut config = std::fs::OpenOptions::new()
+            .append(true)
+            .open(repo.git_dir().join("config"))
+            .unwrap();
+        std::io::Write::write_all(&mut config, b"[core]\n\tsparseCheckout = true\n").unwrap();
+
+        let info = read_git_info(
+            dir.path(),
+            GitInspectOptions {
+                lfs: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            (info.sparse_checkout, info.lfs_pending),
+            (Some(true), Some(1))
+        );
+        let info = read_git_info(dir.path(), GitInspectOptions::default()).unwrap();
+        assert_eq!(info.lfs_pending, None);
+    }
+
+    #[test]
+    fn conflicted_paths_are_counted_once() {
+        use gix::index::entry::{Flags, Mode, Stage, Stat};
+        let dir = tempfile::TempDir::new().unwrap();
+        gix::init(dir.path()).unwrap();
+        assert_eq!(
+            read_git_info(dir.path(), GitInspectOptions::default())
+                .unwrap()
+                .conflicts,
+            Some(0)
+        );
+
+        let mut state = gix::index::State::new(gix::hash::Kind::Sha1);
+        let null = gix::hash::ObjectId::null(gix::hash::Kind::Sha1);
+        for (path, stages) in [
+            ("a.txt", &[Stage::Base, Stage::Ours, Stage::Theirs][..]),
+            ("b.txt", &[Stage::Ours, Stage::Theirs][..]),
+            ("c.txt", &[Stage::Unconflicted][..]),
+        ] {
+            for stage in stages {
+                state.dangerously_push_entry(
+                    Stat::default(),
+                    null,
+                    Flags::from_stage(*stage),
+                    Mode::FILE,
+                    path.into(),
+                );
+            }
+        }
+        state.sort_entries();
+        let mut index = gix::index::File::from_state(state, dir.path().join(".git/index"));
+        index.write(Default::default()).unwrap();
+
+        assert_eq!(
+            read_git_info(dir.path(), GitInspectOptions::default())
+                .unwrap()
+                .conflicts,
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn rebase_in_progress_reports_step_and_total() {
+        let dir = tempfile::TempDir::new().unwrap();
+        gix::init(dir.path()).unwrap();
+        let git_dir = dir.path().join(".git");
+        assert_eq!(
+            read_git_info(dir.path(), GitInspectOptions::default())
+                .unwrap()
+                .operation,
+            None
+        );
+
+        std::fs::write(
+            git_dir.join("MERGE_HEAD"),
+            "0000000000000000000000000000000000000000\n",
+        )
+        .unwrap();
+        let op = read_git_info(dir.path(), GitInspectOptions::default())
+            .unwrap()
+            .operation
+            .unwrap();
+        assert_eq!(op.kind, GitOperationKind::Merge);
+        assert_eq!((op.step, op.total), (None, None));
+        std::fs::remove_file(git_dir.join("MERGE_HEAD")).unwrap();
+
+        let rebase = git_dir.join("rebase-merge");
+        std::fs::create_dir(&rebase).unwrap();
+        std::fs::write(rebase.join("interactive"), "").unwrap();
+        std::fs::write(rebase.join("msgnum"), "2\n").unwrap();
+        std::fs::write(rebase.join("end"), "7\n").unwrap();
+        let op = read_git_info(dir.path(), GitInspectOptions::default())
+            .unwrap()
+            .operation
+            .unwrap();
+        assert_eq!(op.kind, GitOperationKind::Rebase);
+        assert_eq!((op.step, op.total), (Some(2), Some(7)));
+    }
+
+    #[test]
+    fn cached_git_info_drops_the_stale_marker() {
+        let info = GitInfo {
+            branch: Some("main".into()),
+            operation: Some(GitOperation {
+                kind: GitOperationKind::Rebase,
+                step: Some(2),
+                total: Some(7),
+            }),
+            stale_age_secs: Some(30),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&info).unwrap();
+        let cached: GitInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(cached.branch.as_deref(), Some("main"));
+        assert_eq!(cached.operation, info.operation);
+        assert_eq!(cached.stale_age_secs, None);
+    }
+
+    #[test]
+    #[cfg(feature = "db")]
+    #[serial_test::serial]
+    fn slow_inspection_serves_the_previous_result() {
+        use super::{finish_background, read_git_info_within};
+        use std::time::Duration;
+
+        let db_dir = tempfile::tempdir().unwrap();
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { std::env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_dir.path().join("git.db")) };
+        let project = db_dir.path().join("project");
+        let options = GitInspectOptions::default();
+        let with_branch = |branch: &str| GitInfo {
+            branch: Some(branch.into()),
+            ..Default::default()
+        };
+
+        let first = with_branch("main");
+        let info = read_git_info_within(&project, options, Duration::from_secs(5), move || {
+            Some(first)
+        })
+        .unwrap();
+        assert_eq!(info.stale_age_secs, None);
+
+        let slow = with_branch("feature");
+        let info = read_git_info_within(&project, options, Duration::from_millis(20), move || {
+            std::thread::sleep(Duration::from_millis(300));
+            Some(slow)
+        })
+        .expect("previous result");
+        assert_eq!(info.branch.as_deref(), Some("main"));
+        assert!(info.stale_age_secs.is_some());
+
+        // A second render does not start another inspection meanwhile
+        let info = read_git_info_within(&project, options, Duration::from_secs(5), || {
+            panic!("inspected while another inspection holds the lock")
+        })
+        .unwrap();
+        assert_eq!(info.branch.as_deref(), Some("main"));
+
+        finish_background();
+        let info = read_git_info_within(&project, options, Duration::from_secs(5), || None);
+        assert!(info.is_none(), "lock released once the inspection finished");
+
+        // Other options never see this cache entry
+        let lfs = GitInspectOptions {
+            lfs: true,
+            ..Default::default()
+        };
+        let info = read_git_info_within(&project, lfs, Duration::from_millis(20), || {
+            std::thread::sleep(Duration::from_millis(300));
+            None
+        });
+        assert!(info.is_none());
+        finish_background();
+
+        unsafe { std::env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
 }
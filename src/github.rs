@@ -0,0 +1,245 @@
+//! # GitHub Module
+//!
+//! Opt-in pull request and CI check lookups through the `gh` CLI. Results are
+//! cached in the db per project and branch or commit, so at most one render
+//! per TTL waits on GitHub.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::models::git::{GitCi, GitCiState, GitPullRequest};
+use crate::provenance::{CacheLayer, record_cache};
+use crate::utils::run_with_timeout;
+
+const PR_CACHE_PREFIX: &str = "gh_pr:";
+const PR_CACHE_TTL_SECONDS: i64 = 300;
+const CI_CACHE_PREFIX: &str = "gh_ci:";
+/// Checks still running are polled sooner than settled ones.
+const CI_PENDING_TTL_SECONDS: i64 = 60;
+const CI_SETTLED_TTL_SECONDS: i64 = 600;
+/// Suffix for the per-lookup key set after `gh` fails, e.g. no GitHub remote
+const NEGATIVE_CACHE_SUFFIX: &str = ":negative";
+const NEGATIVE_CACHE_TTL_SECONDS: i64 = 600;
+/// `gh` goes to the network; a render never waits longer than this.
+const GH_TIMEOUT: Duration = Duration::from_secs(3);
+
+const PR_FIELDS: &str = "number,url,title,state,isDraft,reviewDecision,mergeable";
+
+/// Pull request for `branch`, from the db cache or `gh pr status`. `None`
+/// when the branch has no PR, or `gh` is missing, unauthenticated, or slow
+/// and nothing was cached before.
+pub fn pull_request(project_dir: &Path, branch: &str) -> Option<GitPullRequest> {
+    let key = format!("{PR_CACHE_PREFIX}{}:{branch}", project_dir.display());
+    cached(
+        "github_pr",
+        &key,
+        |_| PR_CACHE_TTL_SECONDS,
+        || fetch_pull_request(project_dir),
+    )
+}
+
+/// Combined check-run state for `commit`, from the db cache or the GitHub
+/// checks API through `gh api`. `None` when the commit has no checks or the
+/// lookup failed with nothing cached.
+pub fn ci_status(project_dir: &Path, commit: &str) -> Option<GitCi> {
+    let key = format!("{CI_CACHE_PREFIX}{}:{commit}", project_dir.display());
+    let ttl = |ci: &Option<GitCi>| match ci {
+        Some(ci) if ci.state == GitCiState::Pending => CI_PENDING_TTL_SECONDS,
+        _ => CI_SETTLED_TTL_SECONDS,
+    };
+    cached("github_ci", &key, ttl, || {
+        fetch_ci_status(project_dir, commit)
+    })
+}
+
+/// Serve `key` from the db cache, else run `fetch` and store its answer for
+/// `ttl` seconds. A failed fetch sets a negative entry so `gh` is not retried
+/// on every render, and falls back to the expired value.
+fn cached<T>(
+    cache: &'static str,
+    key: &str,
+    ttl: impl Fn(&Option<T>) -> i64,
+    fetch: impl FnOnce() -> Option<Option<T>>,
+) -> Option<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let negative_key = format!("{key}{NEGATIVE_CACHE_SUFFIX}");
+    let parse = |s: &str| serde_json::from_str::<Option<T>>(s).ok();
+    if let Ok(Some((cached, fetched_at))) = crate::db::get_api_cache_entry(key)
+        && let Some(value) = parse(&cached)
+    {
+        record_cache(cache, CacheLayer::Sqlite, Some(now() - fetched_at));
+        return value;
+    }
+    let stale = || {
+        let (cached, fetched_at) = crate::db::get_stale_api_cache_entry(key).ok()??;
+        let value = parse(&cached)?;
+        record_cache(cache, CacheLayer::SqliteStale, Some(now() - fetched_at));
+        value
+    };
+    if let Ok(Some(_)) = crate::db::get_api_cache(&negative_key) {
+        return stale();
+    }
+
+    match fetch() {
+        Some(value) => {
+            if let Ok(json) = serde_json::to_string(&value) {
+                let _ = crate::db::set_api_cache(key, &json, ttl(&value));
+            }
+            record_cache(cache, CacheLayer::Api, None);
+            value
+        }
+        None => {
+            let _ = crate::db::set_api_cache(&negative_key, "1", NEGATIVE_CACHE_TTL_SECONDS);
+            stale()
+        }
+    }
+}
+
+/// `Some(None)` when `gh` answered and the branch has no PR.
+fn fetch_pull_request(project_dir: &Path) -> Option<Option<GitPullRequest>> {
+    let mut cmd = Command::new("gh");
+    cmd.args(["pr", "status", "--json", PR_FIELDS])
+        .current_dir(project_dir);
+    let output = run_with_timeout(cmd, GH_TIMEOUT)?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_pr_status(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Pull the current branch's PR out of `gh pr status --json` output.
+fn parse_pr_status(stdout: &str) -> Option<Option<GitPullRequest>> {
+    let value: serde_json::Value = serde_json::from_str(stdout).ok()?;
+    match value.get("currentBranch") {
+        None | Some(serde_json::Value::Null) => Some(None),
+        Some(current) => serde_json::from_value(current.clone()).ok().map(Some),
+    }
+}
+
+/// `Some(None)` when `gh` answered and the commit has no check runs.
+fn fetch_ci_status(project_dir: &Path, commit: &str) -> Option<Option<GitCi>> {
+    let mut cmd = Command::new("gh");
+    cmd.args([
+        "api",
+        &format!("repos/{{owner}}/{{repo}}/commits/{commit}/check-runs?per_page=100"),
+        // Only the fields read below; full runs can top 64 KB on busy repos
+        "--jq",
+        "{check_runs: [.check_runs[] | {status, conclusion}]}",
+    ])
+    .current_dir(project_dir);
+    let output = run_with_timeout(cmd, GH_TIMEOUT)?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_check_runs(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[derive(Deserialize)]
+struct CheckRuns {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Deserialize)]
+struct CheckRun {
+    status: String,
+    conclusion: Option<String>,
+}
+
+/// Fold check runs into one state: any failure wins, then anything still
+/// running; success, neutral, and skipped runs all count as passing.
+fn parse_check_runs(stdout: &str) -> Option<Option<GitCi>> {
+    let runs: CheckRuns = serde_json::from_str(stdout).ok()?;
+    if runs.check_runs.is_empty() {
+        return Some(None);
+    }
+    let mut ci = GitCi {
+        state: GitCiState::Success,
+        total: runs.check_runs.len(),
+        failed: 0,
+        pending: 0,
+    };
+    for run in &runs.check_runs {
+        if run.status != "completed" {
+            ci.pending += 1;
+        } else if !matches!(
+            run.conclusion.as_deref(),
+            Some("success" | "neutral" | "skipped")
+        ) {
+            ci.failed += 1;
+        }
+    }
+    ci.state = if ci.failed > 0 {
+        GitCiState::Failure
+    } else if ci.pending > 0 {
+        GitCiState::Pending
+    } else {
+        GitCiState::Success
+    };
+    Some(Some(ci))
+}
+
+fn now() -> i64 {
+    crate::utils::now_utc().timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pr_status_reads_the_current_branch() {
+        let pr = parse_pr_status(
+            r#"{"createdBy":[],"needsReview":[],"currentBranch":{"number":42,
+            "url":"https://github.com/o/r/pull/42","title":"Add thing","state":"OPEN",
+            "isDraft":false,"reviewDecision":"","mergeable":"CONFLICTING"}}"#,
+        )
+        .expect("parsed")
+        .expect("pr");
+        assert_eq!(pr.number, 42);
+        assert_eq!(pr.review_decision, None);
+        assert_eq!(pr.mergeable.as_deref(), Some("CONFLICTING"));
+
+        assert_eq!(
+            parse_pr_status(r#"{"createdBy":[],"needsReview":[]}"#),
+            Some(None)
+        );
+        assert_eq!(parse_pr_status("not json"), None);
+    }
+
+    #[test]
+    fn check_runs_fold_into_one_state() {
+        let run = |status: &str, conclusion: Option<&str>| serde_json::json!({ "status": status, "conclusion": conclusion });
+        let parse = |runs: Vec<serde_json::Value>| {
+            let body = serde_json::json!({ "total_count": runs.len(), "check_runs": runs });
+            parse_check_runs(&body.to_string()).expect("parsed")
+        };
+
+        let ci = parse(vec![
+            run("completed", Some("success")),
+            run("completed", Some("skipped")),
+        ])
+        .expect("ci");
+        assert_eq!((ci.state, ci.total), (GitCiState::Success, 2));
+
+        let ci = parse(vec![
+            run("completed", Some("success")),
+            run("in_progress", None),
+        ])
+        .expect("ci");
+        assert_eq!((ci.state, ci.pending), (GitCiState::Pending, 1));
+
+        let ci = parse(vec![
+            run("in_progress", None),
+            run("completed", Some("failure")),
+        ])
+        .expect("ci");
+        assert_eq!((ci.state, ci.failed), (GitCiState::Failure, 1));
+
+        assert_eq!(parse(Vec::new()), None);
+    }
+}
@@ -0,0 +1,160 @@
+//! # Mercurial Module
+//!
+//! Repository inspection for Mercurial and Sapling working copies through the
+//! `hg` and `sl` CLIs, filling the same [`GitInfo`] the git header renders.
+//! Only branch or bookmark, commit, commit age, dirty state, the untracked
+//! count, and the subproject are read; git-only fields stay `None`.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::models::git::{GitInfo, Vcs};
+use crate::utils::run_with_timeout;
+
+/// Both commands read local state only.
+const HG_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Commit id `hg` reports for `.` in a repository with no commits
+const NULL_NODE: &str = "0000000000000000000000000000000000000000";
+
+/// Read the working copy containing `start_dir`, or `None` when it is not
+/// inside a Mercurial or Sapling checkout or the CLI is unavailable.
+pub fn read_hg_info(start_dir: &Path, count_untracked: bool) -> Option<GitInfo> {
+    let (vcs, root) = find_root(start_dir)?;
+    let program = match vcs {
+        Vcs::Sapling => "sl",
+        _ => "hg",
+    };
+    // Sapling dropped named branches, so only the bookmark names the line
+    let template = match vcs {
+        Vcs::Sapling => "{node}\\n{node|short}\\n\\n{activebookmark}\\n{date|hgdate}\\n",
+        _ => "{node}\\n{node|short}\\n{branch}\\n{activebookmark}\\n{date|hgdate}\\n",
+    };
+    let log = run(program, root, &["log", "-r", ".", "-T", template])?;
+    let mut info = parse_log(&log, vcs)?;
+    info.root = Some(root.display().to_string());
+    info.subproject = crate::utils::subproject_dir(root, start_dir);
+
+    // A slow or failed status still leaves the branch and commit to show
+    let status_flags = if count_untracked { "-mardu" } else { "-mard" };
+    if let Some(status) = run(program, root, &["status", status_flags]) {
+        let (changed, untracked) = parse_status(&status);
+        info.is_clean = Some(changed == 0);
+        if count_untracked {
+            info.untracked = Some(untracked);
+        }
+    }
+    Some(info)
+}
+
+/// Nearest ancestor holding `.sl` (Sapling) or `.hg` (Mercurial, and
+/// Sapling checkouts made before `.sl` existed).
+fn find_root(start_dir: &Path) -> Option<(Vcs, &Path)> {
+    start_dir.ancestors().find_map(|dir| {
+        if dir.join(".sl").is_dir() {
+            Some((Vcs::Sapling, dir))
+        } else if dir.join(".hg").is_dir() {
+            Some((Vcs::Mercurial, dir))
+        } else {
+            None
+        }
+    })
+}
+
+fn run(program: &str, root: &Path, args: &[&str]) -> Option<String> {
+    let mut cmd = Command::new(program);
+    // HGPLAIN keeps user aliases and config out of the output format
+    cmd.args(args).current_dir(root).env("HGPLAIN", "1");
+    let output = run_with_timeout(cmd, HG_TIMEOUT)?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse the `log -r .` template: node, short node, branch, active
+/// bookmark, and `hgdate` (`<unix seconds> <offset>`), one per line.
+fn parse_log(stdout: &str, vcs: Vcs) -> Option<GitInfo> {
+    let mut lines = stdout.lines();
+    let node = lines.next()?.trim();
+    let short = lines.next()?.trim();
+    let branch = lines.next().unwrap_or("").trim();
+    let bookmark = lines.next().unwrap_or("").trim();
+    let date = lines.next().unwrap_or("");
+
+    let mut info = GitInfo {
+        vcs,
+        ..Default::default()
+    };
+    info.branch = [bookmark, branch]
+        .into_iter()
+        .find(|name| !name.is_empty())
+        .map(str::to_string);
+    if !node.is_empty() && node != NULL_NODE {
+        info.commit = Some(node.to_string());
+        info.short_commit = Some(short.to_string());
+        if let Some(seconds) = date
+            .split_whitespace()
+            .next()
+            .and_then(|s| s.parse::<i64>().ok())
+        {
+            info.head_age_secs = Some((crate::utils::now_utc().timestamp() - seconds).max(0));
+        }
+    }
+    Some(info)
+}
+
+/// Changed (`M`, `A`, `R`, `!`) and untracked (`?`) paths in `status`.
+fn parse_status(stdout: &str) -> (usize, usize) {
+    let (mut changed, mut untracked) = (0, 0);
+    for line in stdout.lines() {
+        match line.chars().next() {
+            Some('?') => untracked += 1,
+            Some('M' | 'A' | 'R' | '!') => changed += 1,
+            _ => {}
+        }
+    }
+    (changed, untracked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_prefers_the_active_bookmark() {
+        let info = parse_log(
+            "1f0e2d3c4b5a69788796a5b4c3d2e1f0a9b8c7d6\n1f0e2d3c4b5a\ndefault\nfeature-x\n1700000000 0\n",
+            Vcs::Mercurial,
+        )
+        .unwrap();
+        assert_eq!(info.vcs, Vcs::Mercurial);
+        assert_eq!(info.branch.as_deref(), Some("feature-x"));
+        assert_eq!(info.short_commit.as_deref(), Some("1f0e2d3c4b5a"));
+        assert!(info.head_age_secs.is_some());
+
+        let info = parse_log(
+            "1f0e2d3c4b5a69788796a5b4c3d2e1f0a9b8c7d6\n1f0e2d3c4b5a\ndefault\n\n1700000000 0\n",
+            Vcs::Mercurial,
+        )
+        .unwrap();
+        assert_eq!(info.branch.as_deref(), Some("default"));
+
+        let info = parse_log(
+            &format!("{NULL_NODE}\n000000000000\n\n\n0 0\n"),
+            Vcs::Sapling,
+        )
+        .unwrap();
+        assert_eq!((info.branch, info.commit), (None, None));
+    }
+
+    #[test]
+    fn status_splits_changes_from_untracked() {
+        assert_eq!(
+            parse_status("M src/a.rs\n! gone.txt\n? new.txt\n? other.txt\n"),
+            (2, 2)
+        );
+        assert_eq!(parse_status(""), (0, 0));
+    }
+}
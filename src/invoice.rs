@@ -0,0 +1,336 @@
+use anyhow::{Context, Result, bail};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::budget::billing_period;
+use crate::cli::Args;
+use crate::currency::{DisplayCurrency, add_converted_fields, display_currency, money};
+use crate::db::{DailyCost, ModelTotals, load_daily_costs, load_model_costs, load_project_costs};
+use crate::report::{ModelCost, TagCost, costs_by_tag};
+use crate::utils::{format_tokens, now_local};
+
+/// One billing period of spend, built from the db ledgers.
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthlyStatement {
+    pub period_start: NaiveDate,
+    /// Last day of the period (inclusive)
+    pub period_end: NaiveDate,
+    pub cost_usd: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chargeback_multiplier: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub billable_usd: Option<f64>,
+    pub by_tag: Vec<TagCost>,
+    pub by_model: Vec<ModelCost>,
+    /// Every day of the period up to today, zero-filled
+    pub daily: Vec<DayCost>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DayCost {
+    pub date: NaiveDate,
+    pub cost_usd: f64,
+}
+
+impl MonthlyStatement {
+    /// Assemble the statement for the billing period that starts in `month`
+    /// (any day of it), or the current period when `None`.
+    pub fn load(args: &Args, month: Option<NaiveDate>) -> Result<Self> {
+        if args.no_subsystem_db_cache {
+            bail!("monthly statements read the SQLite cost ledger; enable the db_cache subsystem");
+        }
+        let today = now_local().date_naive();
+        let (start, end) = statement_period(args, month, today);
+        let through = end.min(today);
+
+        let daily = load_daily_costs(start, through)?;
+        let projects = load_project_costs(start, through)?;
+        let models = load_model_costs(start, through)?;
+        Ok(Self::from_ledgers(
+            args, start, end, through, &daily, &projects, models,
+        ))
+    }
+
+    fn from_ledgers(
+        args: &Args,
+        start: NaiveDate,
+        end: NaiveDate,
+        through: NaiveDate,
+        daily: &[DailyCost],
+        projects: &[(String, f64)],
+        models: Vec<ModelTotals>,
+    ) -> Self {
+        let days: Vec<DayCost> = start
+            .iter_days()
+            .take_while(|d| *d <= through)
+            .map(|date| DayCost {
+                date,
+                cost_usd: daily
+                    .iter()
+                    .find(|d| d.date == date)
+                    .map_or(0.0, |d| d.cost),
+            })
+            .collect();
+        let cost_usd = days.iter().fold(0.0, |sum, d| sum + d.cost_usd);
+
+        let model_total = models.iter().fold(0.0, |sum, m| sum + m.cost);
+        let mut by_model: Vec<ModelCost> = models
+            .into_iter()
+            .map(|m| ModelCost {
+                share: if model_total > 0.0 {
+                    m.cost / model_total
+                } else {
+                    0.0
+                },
+                model: m.model,
+                cost_usd: m.cost,
+                input_tokens: m.input_tokens,
+                output_tokens: m.output_tokens,
+                cache_creation_input_tokens: m.cache_create_tokens,
+                cache_read_input_tokens: m.cache_read_tokens,
+            })
+            .collect();
+        by_model.sort_by(|a, b| {
+            b.cost_usd
+                .total_cmp(&a.cost_usd)
+                .then_with(|| a.model.cmp(&b.model))
+        });
+
+        Self {
+            period_start: start,
+            period_end: end,
+            cost_usd,
+            chargeback_multiplier: args.chargeback_multiplier,
+            billable_usd: args.chargeback_multiplier.map(|m| cost_usd * m),
+            by_tag: costs_by_tag(
+                projects.iter().map(|(name, cost)| (name.as_str(), *cost)),
+                args,
+            ),
+            by_model,
+            daily: days,
+        }
+    }
+
+    /// Markdown invoice in `currency`: summary, per-client totals, model
+    /// breakdown, and the daily table.
+    pub fn to_markdown(&self, currency: Option<&DisplayCurrency>) -> String {
+        let mut md = String::new();
+        let _ = writeln!(
+            md,
+            "# Claude usage statement: {} to {}\n",
+            self.period_start, self.period_end
+        );
+
+        let _ = writeln!(md, "## Summary\n");
+        let _ = writeln!(md, "| Item | Amount |");
+        let _ = writeln!(md, "| --- | ---: |");
+        let _ = writeln!(
+            md,
+            "| API-equivalent cost | {} |",
+            money(self.cost_usd, currency)
+        );
+        if let (Some(multiplier), Some(billable)) = (self.chargeback_multiplier, self.billable_usd)
+        {
+            let _ = writeln!(md, "| Chargeback multiplier | x{multiplier} |");
+            let _ = writeln!(
+                md,
+                "| **Billable total** | **{}** |",
+                money(billable, currency)
+            );
+        }
+        if let Some(currency) = currency {
+            let _ = writeln!(
+                md,
+                "| Exchange rate | 1 USD = {} {} |",
+                currency.rate, currency.code
+            );
+        }
+
+        let billable = self.chargeback_multiplier.is_some();
+        let _ = writeln!(md, "\n## By client\n");
+        if billable {
+            let _ = writeln!(md, "| Client | Projects | Cost | Share | Billable |");
+            let _ = writeln!(md, "| --- | ---: | ---: | ---: | ---: |");
+        } else {
+            let _ = writeln!(md, "| Client | Projects | Cost | Share |");
+            let _ = writeln!(md, "| --- | ---: | ---: | ---: |");
+        }
+        for tag in &self.by_tag {
+            let _ = write!(
+                md,
+                "| {} | {} | {} | {:.1}% |",
+                tag.tag,
+                tag.projects,
+                money(tag.cost_usd, currency),
+                tag.share * 100.0
+            );
+            if let Some(amount) = tag.billable_usd {
+                let _ = write!(md, " {} |", money(amount, currency));
+            }
+            md.push('\n');
+        }
+
+        let _ = writeln!(md, "\n## By model\n");
+        let _ = writeln!(
+            md,
+            "| Model | Cost | Share | Input | Output | Cache write | Cache read |"
+        );
+        let _ = writeln!(md, "| --- | ---: | ---: | ---: | ---: | ---: | ---: |");
+        for model in &self.by_model {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {:.1}% | {} | {} | {} | {} |",
+                model.model,
+                money(model.cost_usd, currency),
+                model.share * 100.0,
+                format_tokens(model.input_tokens),
+                format_tokens(model.output_tokens),
+                format_tokens(model.cache_creation_input_tokens),
+                format_tokens(model.cache_read_input_tokens)
+            );
+        }
+
+        let _ = writeln!(md, "\n## Daily\n");
+        let _ = writeln!(md, "| Date | Cost |");
+        let _ = writeln!(md, "| --- | ---: |");
+        for day in &self.daily {
+            let _ = writeln!(md, "| {} | {} |", day.date, money(day.cost_usd, currency));
+        }
+        let _ = writeln!(
+            md,
+            "\n_Generated by claude_statusline {} on {}._",
+            env!("CARGO_PKG_VERSION"),
+            now_local().format("%Y-%m-%d")
+        );
+        md
+    }
+
+    /// Statement JSON with converted siblings for the display currency.
+    pub fn to_json(&self) -> Result<serde_json::Value> {
+        let mut json = serde_json::to_value(self)?;
+        add_converted_fields(&mut json);
+        Ok(json)
+    }
+}
+
+/// `report --month` / `--invoice`: print the statement, or write the markdown
+/// invoice to `invoice` when given.
+pub fn run_statement(args: &Args, month: Option<NaiveDate>, invoice: Option<&Path>) -> Result<()> {
+    let statement = MonthlyStatement::load(args, month)?;
+    match invoice {
+        Some(path) => {
+            std::fs::write(path, statement.to_markdown(display_currency()))
+                .with_context(|| format!("failed to write invoice {}", path.display()))?;
+            println!(
+                "wrote {} ({} to {})",
+                path.display(),
+                statement.period_start,
+                statement.period_end
+            );
+        }
+        None if args.json => println!("{}", serde_json::to_string(&statement.to_json()?)?),
+        None => print!("{}", statement.to_markdown(display_currency())),
+    }
+    Ok(())
+}
+
+/// First and last day (inclusive) of the billing period that starts in
+/// `month`, or of the period containing `today` when `None`.
+pub(crate) fn statement_period(
+    args: &Args,
+    month: Option<NaiveDate>,
+    today: NaiveDate,
+) -> (NaiveDate, NaiveDate) {
+    let (start, end_exclusive) = match month {
+        // The anchor day in that month lies inside the period starting there.
+        Some(month) => billing_period(
+            month
+                .with_day(args.billing_day)
+                .unwrap_or_else(|| last_day_of_month(month)),
+            args.billing_day,
+        ),
+        None => billing_period(today, args.billing_day),
+    };
+    (start, end_exclusive - Duration::days(1))
+}
+
+fn last_day_of_month(date: NaiveDate) -> NaiveDate {
+    (28..=31)
+        .rev()
+        .find_map(|d| date.with_day(d))
+        .unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, d).unwrap()
+    }
+
+    #[test]
+    fn statement_zero_fills_days_and_applies_chargeback() {
+        let mut args = Args::parse_from(["claude_statusline"]);
+        args.chargeback_multiplier = Some(1.5);
+        args.tags = vec![("/work/acme*".to_string(), "acme".to_string())];
+        let daily = [
+            DailyCost {
+                date: day(2),
+                cost: 4.0,
+            },
+            DailyCost {
+                date: day(4),
+                cost: 6.0,
+            },
+        ];
+        let projects = [
+            ("-work-acme-api".to_string(), 8.0),
+            ("-tmp".to_string(), 2.0),
+        ];
+        let models = vec![ModelTotals {
+            model: "claude-sonnet-4-5".to_string(),
+            cost: 10.0,
+            input_tokens: 1_000,
+            output_tokens: 2_000,
+            cache_create_tokens: 0,
+            cache_read_tokens: 50_000,
+        }];
+
+        let statement = MonthlyStatement::from_ledgers(
+            &args,
+            day(1),
+            day(31),
+            day(5),
+            &daily,
+            &projects,
+            models,
+        );
+
+        let costs: Vec<f64> = statement.daily.iter().map(|d| d.cost_usd).collect();
+        assert_eq!(costs, vec![0.0, 4.0, 0.0, 6.0, 0.0]);
+        assert_eq!(statement.cost_usd, 10.0);
+        assert_eq!(statement.billable_usd, Some(15.0));
+        assert_eq!(statement.by_tag[0].tag, "acme");
+        assert_eq!(statement.by_model[0].share, 1.0);
+
+        let md = statement.to_markdown(None);
+        assert!(md.starts_with("# Claude usage statement: 2025-01-01 to 2025-01-31"));
+        assert!(md.contains("| **Billable total** | **$15.00** |"));
+        assert!(md.contains("| acme | 1 | $8.00 | 80.0% | $12.00 |"));
+        assert!(md.contains("| claude-sonnet-4-5 | $10.00 | 100.0% |"));
+        assert!(md.contains("| 2025-01-04 | $6.00 |"));
+    }
+
+    #[test]
+    fn last_day_of_month_handles_short_months() {
+        assert_eq!(
+            last_day_of_month(NaiveDate::from_ymd_opt(2025, 2, 1).unwrap()),
+            NaiveDate::from_ymd_opt(2025, 2, 28).unwrap()
+        );
+        assert_eq!(last_day_of_month(day(1)), day(31));
+    }
+}
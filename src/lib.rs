@@ -20,7 +20,14 @@
 //! ## Features
 //!
 //! - `git` (default): Enables repository inspection via gix
+//! - `hg`: Falls back to Mercurial and Sapling checkouts via the `hg` / `sl` CLIs
 //! - `colors` (default): Enables terminal color output via owo-colors
+//! - `db` (default): Enables the SQLite cache and history via rusqlite; without
+//!   it renders fall back to the transcript scan
+//! - `encrypted-db`: Encrypts the SQLite cache with SQLCipher, keyed from the OS keychain
+
+/// Daily, weekly, and monthly spend budgets
+pub mod budget;
 
 /// Beads issue tracker integration
 pub mod beads;
@@ -28,7 +35,16 @@ pub mod beads;
 /// Gas Town multi-agent orchestration integration
 pub mod gastown;
 
+/// Display currency conversion
+pub mod currency;
+
 /// SQLite-based persistent caching for global usage tracking
+#[cfg(feature = "db")]
+pub mod db;
+
+/// Storage-free stand-in for `db` (feature-gated)
+#[cfg(not(feature = "db"))]
+#[path = "db_disabled.rs"]
 pub mod db;
 
 /// Command-line argument parsing and configuration
@@ -37,31 +53,62 @@ pub mod cli;
 /// File-backed configuration loading
 pub mod config;
 
+/// Energy and CO2e estimates from token counts
+pub mod environment;
+
 /// Display formatting for text and JSON output
 pub mod display;
 
 /// Diagnostics and setup commands
 pub mod doctor;
 
+/// Pull request lookups through the `gh` CLI
+pub mod github;
+
 /// Git repository inspection (feature-gated)
 #[cfg(feature = "git")]
 pub mod git;
 
+/// Mercurial and Sapling working copy inspection (feature-gated)
+#[cfg(feature = "hg")]
+pub mod hg;
+
 /// Data models for hooks, entries, blocks, and Git info
 pub mod models;
 
+/// Monthly statements and markdown invoices (`report --month`)
+pub mod invoice;
+
 /// Model-specific pricing calculations
 pub mod pricing;
 
 /// Source metadata for costs, pricing, and context values
 pub mod provenance;
 
+/// Daily spend reports (`report` subcommand)
+pub mod report;
+
+/// Cross-machine usage sync through append-only JSONL snapshots
+pub mod sync;
+
+/// Per-render phase timings (`doctor`/`stats` performance history)
+pub mod timing;
+
+/// Seat-level team aggregation across statusline databases (`report --team`)
+pub mod team;
+
 /// Usage tracking and block identification
 pub mod usage;
 
 /// Online usage limits retrieved from the Claude OAuth API
 pub mod usage_api;
 
+/// Server-reported organization spend from the Anthropic Admin API
+pub mod admin_api;
+
+/// Credit balance and rate limit for sessions routed through OpenRouter
+pub mod openrouter;
+
 /// Utility functions for paths, formatting, and time
 pub mod utils;
 
@@ -2,25 +2,52 @@
 #![allow(clippy::collapsible_if)]
 
 use anyhow::{Context, Result};
-use chrono::{Local, Utc};
+use chrono::Local;
 #[cfg(feature = "colors")]
 use owo_colors::OwoColorize;
 use std::path::Path;
+use std::time::Instant;
 
+use claude_statusline::admin_api::get_admin_usage;
 use claude_statusline::beads::get_beads_info;
+use claude_statusline::budget::{
+    BudgetSpend, BudgetStatus, billing_period, forecast_month, trailing_average,
+};
 use claude_statusline::cli::{Args, BurnScopeArg, WindowAnchorArg, WindowScopeArg};
 #[cfg(not(feature = "colors"))]
 use claude_statusline::display::color_shim::ColorizeShim;
-use claude_statusline::display::{print_header, print_json_output, print_text_output};
+use claude_statusline::display::{
+    OutputExtras, print_header, print_json_output, print_text_output,
+};
+use claude_statusline::environment::{EnergyModel, EnvironmentEstimate};
 use claude_statusline::gastown::get_gastown_info;
 use claude_statusline::models::{Entry, HookJson};
-use claude_statusline::provenance::{CostProvenance, SessionCostSource, TodayCostSource};
+use claude_statusline::openrouter::is_openrouter;
+use claude_statusline::pricing::server_tool_costs;
+use claude_statusline::provenance::{
+    CostProvenance, SessionCostSource, TodayCostSource, cache_report,
+};
+use claude_statusline::report::{
+    CostStats, STATS_HISTORY_DAYS, TOP_PROJECTS_LIMIT, costs_by_tag, models_for_day,
+    period_costs_by_tag, projects_for_day,
+};
+use claude_statusline::timing::{Phase, PhaseTimer};
 use claude_statusline::usage::{
-    calc_context_from_entries, calc_context_from_transcript, parse_session_state, scan_usage,
+    calc_context_from_entries, calc_context_from_transcript, get_cached_usage, parse_session_state,
+    scan_usage,
+};
+use claude_statusline::usage_api::{
+    TokenSource, UsageSummary, get_oauth_profile, get_usage_summary, resolve_account,
+    resolve_usage_egress, usage_api_error,
 };
-use claude_statusline::usage_api::{UsageSummary, get_usage_summary, resolve_usage_egress};
-use claude_statusline::utils::{claude_paths, friendly_model_name, read_stdin};
-use claude_statusline::window::{BurnScope, WindowScope, calculate_window_metrics};
+use claude_statusline::utils::{
+    apply_timezone_override, claude_paths, friendly_model_name, now_local, now_utc, read_stdin,
+    set_now_override,
+};
+use claude_statusline::window::{BurnScope, WindowScope, calculate_window_metrics, recent_blocks};
+
+/// Blocks (active + gaps) emitted in the JSON timeline.
+const RECENT_BLOCKS_LIMIT: usize = 8;
 
 fn session_today_cost_for_db(
     session_id: &str,
@@ -33,7 +60,7 @@ fn session_today_cost_for_db(
         return scan_session_today_cost;
     }
 
-    let today = Local::now().date_naive();
+    let today = now_local().date_naive();
     let has_non_today_session_entries = entries.iter().any(|entry| {
         entry.session_id.as_deref() == Some(session_id)
             && entry.ts.with_timezone(&Local).date_naive() != today
@@ -49,7 +76,60 @@ fn session_today_cost_for_db(
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let mut timer = PhaseTimer::start();
+    let mut args = Args::parse();
+    // Apply before anything converts to Local so reset clocks, daily totals,
+    // and window anchors all follow the requested zone.
+    let timezone_error = args
+        .timezone
+        .as_deref()
+        .and_then(|tz| apply_timezone_override(tz).err());
+    if let Some(now) = args.now {
+        set_now_override(now);
+    }
+    let keep_days = |days: u32| (days > 0).then_some(days);
+    claude_statusline::db::set_retention(claude_statusline::db::Retention {
+        entries_days: keep_days(args.entries_retention_days),
+        api_cache_days: keep_days(args.api_cache_retention_days),
+    });
+    claude_statusline::db::set_cache_ttls(claude_statusline::db::CacheTtls {
+        global_sum_seconds: i64::from(args.global_sum_ttl_seconds),
+        scan_seconds: i64::from(args.scan_ttl_seconds),
+        usage_api_seconds: i64::from(args.usage_api_ttl_seconds),
+    });
+    // Replays must not skip probes on the strength of live markers
+    if !args.no_subsystem_db_cache && args.now.is_none() {
+        claude_statusline::utils::set_absent_ttl(i64::from(args.absent_ttl_seconds));
+    }
+    if let Some(currency) = claude_statusline::currency::resolve(&args) {
+        claude_statusline::currency::set_display_currency(currency);
+    }
+    // A rejected file is left to `doctor` to report rather than repeated on
+    // every render
+    let _ = claude_statusline::pricing::load_pricing_file(args.pricing_file.as_deref());
+    claude_statusline::pricing::set_free_models(&args.free_model);
+    if let Some(proxy) = args.usage_api_proxy.as_deref()
+        && let Err(e) = claude_statusline::usage_api::set_proxy(proxy)
+    {
+        eprintln!("claude-statusline: ignoring usage API proxy: {e}");
+    }
+    let surcharge = claude_statusline::pricing::ProxySurcharge {
+        percent: args.proxy_surcharge_percent.unwrap_or(0.0),
+        per_mtok: args.proxy_surcharge_per_mtok.unwrap_or(0.0),
+    };
+    claude_statusline::pricing::set_proxy_surcharge(surcharge)
+        .context("invalid proxy surcharge")?;
+    if let Err(e) = claude_statusline::pricing::set_project_pricing(&args.project_pricing) {
+        eprintln!("claude-statusline: ignoring project pricing: {e:#}");
+    }
+    // Commands can wait on a LiteLLM download; renders refresh it in the background
+    if args.pricing_fetch && !args.no_subsystem_db_cache {
+        if args.command.is_some() {
+            claude_statusline::pricing::load_litellm_pricing();
+        } else {
+            claude_statusline::pricing::enable_litellm_pricing();
+        }
+    }
     if let Some(ref command) = args.command {
         return claude_statusline::doctor::run_command(&args, command);
     }
@@ -73,6 +153,13 @@ fn main() -> Result<()> {
 
     // Compute metrics (from logs)
     let paths = claude_paths(args.claude_config_dir.as_deref());
+    let scan_started = Instant::now();
+    // Pinned --now replays must rescan; the cache holds live results only
+    let scan = if !args.no_subsystem_db_cache && args.now.is_none() {
+        get_cached_usage
+    } else {
+        scan_usage
+    };
     let (
         mut session_cost,
         session_today_cost,
@@ -81,7 +168,7 @@ fn main() -> Result<()> {
         latest_reset,
         api_key_source,
         rate_limit_info,
-    ) = scan_usage(
+    ) = scan(
         &paths,
         &hook.session_id,
         Some(hook.workspace.project_dir.as_str()),
@@ -96,9 +183,10 @@ fn main() -> Result<()> {
     // - session cost (from SDK result messages)
     let transcript_path = Path::new(&hook.transcript_path);
     let session_state = parse_session_state(transcript_path);
+    timer.add(Phase::Scan, scan_started);
     let prompt_cache_info = if !args.no_integrations_prompt_cache {
         session_state.prompt_cache.clone().map(|mut info| {
-            info.now = Utc::now();
+            info.now = now_utc();
             info.set_unknown_ttl_seconds(args.prompt_cache_ttl_seconds.unwrap_or(300));
             info
         })
@@ -129,18 +217,39 @@ fn main() -> Result<()> {
     // lag behind Claude Code's live hook when transcript usage is sparse.
     let mut sessions_count = 1;
     let mut today_cost_source = TodayCostSource::ScanFallback;
-    if !args.no_subsystem_db_cache {
-        match claude_statusline::db::get_global_usage(
-            &hook.session_id,
-            &hook.workspace.project_dir,
-            transcript_path,
-            Some(db_session_today_cost),
-            Some(&entries),
-        ) {
+    let mut budget_spend = BudgetSpend::default();
+    let mut daily_history = Vec::new();
+    // A pinned --now replays a past moment; keep it away from the live ledger,
+    // which prunes every row not dated "today".
+    if !args.no_subsystem_db_cache && args.now.is_none() {
+        let global_usage = timer.time(Phase::Db, || {
+            claude_statusline::db::get_global_usage(
+                &hook.session_id,
+                &hook.workspace.project_dir,
+                transcript_path,
+                Some(db_session_today_cost),
+                Some(&entries),
+            )
+        });
+        match global_usage {
             Ok(global_usage) => {
                 today_cost = global_usage.global_today;
                 sessions_count = global_usage.sessions_count;
                 today_cost_source = TodayCostSource::DbGlobalUsage;
+                let today = now_local().date_naive();
+                let (period_start, _) = billing_period(today, args.billing_day);
+                let history_start =
+                    today - chrono::Duration::days(claude_statusline::db::DAILY_HISTORY_DAYS - 1);
+                // A billing period reaching back past the four-week history
+                // reads its earlier days from the ledger.
+                budget_spend = if period_start < history_start {
+                    let history = claude_statusline::db::load_daily_costs(period_start, today)
+                        .unwrap_or_else(|_| global_usage.daily_history.clone());
+                    BudgetSpend::from_history(&history, today, args.billing_day)
+                } else {
+                    BudgetSpend::from_history(&global_usage.daily_history, today, args.billing_day)
+                };
+                daily_history = global_usage.daily_history;
             }
             Err(e) => {
                 eprintln!("DB cache error (using scan_usage fallback): {}", e);
@@ -231,22 +340,106 @@ fn main() -> Result<()> {
             if args.no_subsystem_git {
                 None
             } else {
-                let git_dir = hook.workspace.project_dir.as_str();
-                claude_statusline::git::read_git_info(Path::new(git_dir))
+                use claude_statusline::cli::GitFieldArg;
+                let git_dir = std::path::PathBuf::from(hook.workspace.project_dir.as_str());
+                let options = claude_statusline::git::GitInspectOptions {
+                    untracked: args.git_field(GitFieldArg::Untracked),
+                    describe: args.git_field(GitFieldArg::Describe),
+                    // Older hooks carry no line counts; diff the worktree instead
+                    line_stats: !args.no_cost_lines_delta
+                        && hook.cost.total_lines_added.is_none()
+                        && hook.cost.total_lines_removed.is_none(),
+                    lfs: args.git_field(GitFieldArg::Lfs),
+                    // Only the JSON output carries it
+                    default_divergence: args.json && args.git_field(GitFieldArg::AheadBehind),
+                };
+                // `gh` results only ever come from or go through the db cache
+                let with_pr = args.git_field(GitFieldArg::Pr) && !args.no_subsystem_db_cache;
+                let with_ci = args.git_field(GitFieldArg::Ci) && !args.no_subsystem_db_cache;
+                // Hourly at most, which only the db cache can enforce
+                let with_health =
+                    args.git_field(GitFieldArg::Health) && !args.no_subsystem_db_cache;
+                let max_git_gb = args.git_health_max_git_gb;
+                let inspect_dir = git_dir.clone();
+                let inspect = move || {
+                    let git_dir = inspect_dir.as_path();
+                    let mut info = claude_statusline::git::read_git_info(git_dir, options)?;
+                    if with_pr && let Some(branch) = info.branch.as_deref() {
+                        info.pull_request =
+                            claude_statusline::github::pull_request(git_dir, branch);
+                    }
+                    if with_ci && let Some(commit) = info.commit.as_deref() {
+                        info.ci = claude_statusline::github::ci_status(git_dir, commit);
+                    }
+                    if with_health && let Some(root) = info.root.clone() {
+                        info.health =
+                            claude_statusline::git::repo_health(Path::new(&root), max_git_gb);
+                    }
+                    Some(info)
+                };
+                timer.time(Phase::Git, || {
+                    // The fallback is the previous render's result in the db
+                    if args.git_timeout_ms > 0 && !args.no_subsystem_db_cache {
+                        claude_statusline::git::read_git_info_within(
+                            &git_dir,
+                            options,
+                            std::time::Duration::from_millis(u64::from(args.git_timeout_ms)),
+                            inspect,
+                        )
+                    } else {
+                        inspect()
+                    }
+                })
             }
         }
         #[cfg(not(feature = "git"))]
         {
-            None
+            None::<claude_statusline::models::GitInfo>
         }
     };
+    // Not a git repository: try a Mercurial or Sapling checkout
+    #[cfg(feature = "hg")]
+    let git_info = match git_info {
+        None if !args.no_subsystem_git => timer.time(Phase::Git, || {
+            claude_statusline::hg::read_hg_info(
+                Path::new(hook.workspace.project_dir.as_str()),
+                args.git_field(claude_statusline::cli::GitFieldArg::Untracked),
+            )
+        }),
+        git_info => git_info,
+    };
+
+    let git_info = git_info.map(|mut info| {
+        if let Some(branch) = info.branch.as_deref() {
+            info.protected = claude_statusline::utils::is_protected_branch(
+                branch,
+                info.default_branch.as_deref(),
+                &args.git_protected_branch,
+            );
+            info.issues = claude_statusline::utils::issue_keys(branch, &args.git_issue_pattern)
+                .into_iter()
+                .map(|key| claude_statusline::models::git::GitIssue {
+                    url: args
+                        .git_issue_url
+                        .as_deref()
+                        .map(|template| claude_statusline::utils::issue_url(template, &key)),
+                    key,
+                })
+                .collect();
+        }
+        info
+    });
 
     // Beads issue tracker info (unless --no-subsystem-beads is set)
     let beads_info = if args.no_subsystem_beads {
         None
     } else {
         let beads_dir = hook.workspace.project_dir.as_str();
-        get_beads_info(Path::new(beads_dir))
+        get_beads_info(
+            Path::new(beads_dir),
+            args.beads_stale_hours,
+            &args.beads_label,
+        )
     };
 
     // Gas Town multi-agent info (unless --no-subsystem-gastown is set)
@@ -261,20 +454,56 @@ fn main() -> Result<()> {
     let context_limit_override =
         Some(hook.context_window.context_window_size).filter(|&size| size > 0);
 
-    let la = hook.cost.total_lines_added;
-    let lr = hook.cost.total_lines_removed;
+    let (la, lr) = match (hook.cost.total_lines_added, hook.cost.total_lines_removed) {
+        (None, None) => git_info
+            .as_ref()
+            .map(|git| {
+                let lines = |n: Option<usize>| n.map_or(0, |n| n as i64);
+                (lines(git.lines_added), lines(git.lines_removed))
+            })
+            .unwrap_or((0, 0)),
+        (added, removed) => (added.unwrap_or(0), removed.unwrap_or(0)),
+    };
     let lines_delta = if la != 0 || lr != 0 {
         Some((la, lr))
     } else {
         None
     };
 
+    let usage_account = resolve_account(&args.accounts, args.account.as_deref(), &paths);
+    if usage_account.is_none()
+        && let Some(label) = args.account.as_deref()
+    {
+        eprintln!("claude-statusline: no [accounts] entry named {label}");
+    }
+    // One per render, so the token is read once for the profile, the limits,
+    // and the rejection check
+    let usage_source = TokenSource::new(&paths, usage_account.as_ref());
+    // Remote lookups lean on the db cache between renders; without it (or in
+    // no-db builds) every render would wait on the network
+    let usage_api_enabled = !args.no_subsystem_usage_api && !args.no_subsystem_db_cache;
+    // OpenRouter sessions have no Anthropic OAuth limits; its credits stand in
+    let openrouter_routed = args.openrouter || is_openrouter(&hook.model.id);
+    let oauth_usage_api = usage_api_enabled && !openrouter_routed;
+    // Organization plan behind the OAuth token; fills in --subscription
+    let oauth_profile = if oauth_usage_api {
+        timer.time(Phase::Api, || {
+            get_oauth_profile(&usage_source, Some(&hook.model.id))
+        })
+    } else {
+        None
+    };
+    if args.subscription.is_none() {
+        args.subscription = oauth_profile.as_ref().and_then(|p| p.subscription());
+    }
+
     if !args.json {
         print_header(
             &hook,
             git_info.as_ref(),
             &args,
             api_key_source.as_deref(),
+            oauth_profile.as_ref().and_then(|p| p.plan_label()),
             lines_delta,
             beads_info.as_ref(),
             gastown_info.as_ref(),
@@ -283,22 +512,20 @@ fn main() -> Result<()> {
         );
     }
 
-    let oauth_org_type: Option<String> = None;
-    let oauth_rate_tier: Option<String> = None;
-    let cost_provenance = CostProvenance {
-        session_cost: session_cost_source,
-        today_cost: today_cost_source,
-        pricing: claude_statusline::pricing::pricing_source_for_model(&hook.model.id),
-    };
+    let (oauth_org_type, oauth_rate_tier) = oauth_profile
+        .map(|p| (p.organization_type, p.rate_limit_tier))
+        .unwrap_or_default();
 
     // Calculate window metrics
-    let now_utc = Utc::now();
+    let now_utc = now_utc();
     let window_scope = match args.window_scope {
         WindowScopeArg::Global => WindowScope::Global,
         WindowScopeArg::Project => WindowScope::Project,
+        WindowScopeArg::Session => WindowScope::Session,
     };
     let burn_scope = match args.burn_scope {
         BurnScopeArg::Session => BurnScope::Session,
+        BurnScopeArg::Project => BurnScope::Project,
         BurnScopeArg::Global => BurnScope::Global,
     };
     let anchor_strategy = match args.window_anchor {
@@ -379,9 +606,11 @@ fn main() -> Result<()> {
     // Priority 2: OAuth API
     // When hook provided rate_limits, we still call the API to get extra_usage
     // and model-specific breakdowns that the hook doesn't include.
-    if usage_summary.is_none() {
+    if usage_summary.is_none() && oauth_usage_api {
         // No hook data at all; API is the primary source
-        usage_summary = get_usage_summary(&paths, Some(&hook.model.id));
+        usage_summary = timer.time(Phase::Api, || {
+            get_usage_summary(&usage_source, Some(&hook.model.id))
+        });
         if let Some(summary) = usage_summary.as_ref() {
             usage_percent_display = summary.window.utilization;
             if let Some(reset) = summary.window.resets_at {
@@ -394,7 +623,11 @@ fn main() -> Result<()> {
                 );
             }
         }
-    } else if let Some(api_summary) = get_usage_summary(&paths, Some(&hook.model.id)) {
+    } else if oauth_usage_api
+        && let Some(api_summary) = timer.time(Phase::Api, || {
+            get_usage_summary(&usage_source, Some(&hook.model.id))
+        })
+    {
         // Hook provided utilization/reset; enrich with API-only fields
         if let Some(ref mut summary) = usage_summary {
             if summary.extra_usage.is_none() {
@@ -421,7 +654,7 @@ fn main() -> Result<()> {
         }
     }
 
-    let metrics = calculate_window_metrics(
+    let mut metrics = calculate_window_metrics(
         &entries,
         &hook.session_id,
         Some(hook.workspace.project_dir.as_str()),
@@ -431,6 +664,26 @@ fn main() -> Result<()> {
         burn_scope,
         anchor_strategy,
     );
+    // Ledger-backed extras need the db global-usage pass, whatever the source
+    let db_ledger = today_cost_source == TodayCostSource::DbGlobalUsage;
+    // Replays (`--now`) keep their log-derived window; live org figures would
+    // overwrite it
+    if usage_api_enabled
+        && args.now.is_none()
+        && let Some(admin_key) = args.admin_api_key.as_deref()
+        && let Some(admin) = timer.time(Phase::Api, || {
+            get_admin_usage(admin_key, metrics.start, now_utc)
+        })
+    {
+        today_cost = admin.today_cost;
+        today_cost_source = TodayCostSource::AdminApi;
+        admin.apply_to_window(&mut metrics);
+    }
+    let cost_provenance = CostProvenance {
+        session_cost: session_cost_source,
+        today_cost: today_cost_source,
+        pricing: claude_statusline::pricing::pricing_source_for_model(&hook.model.id),
+    };
     let remaining_minutes_display =
         authoritative_remaining_minutes.unwrap_or(metrics.remaining_minutes);
     let active_block = claude_statusline::models::Block {
@@ -457,6 +710,112 @@ fn main() -> Result<()> {
             context_source = Some("entries");
         }
     }
+    budget_spend.today = Some(today_cost);
+    // Only worth a db read when the limits are missing or stale
+    let usage_limits_error = if oauth_usage_api && usage_summary.as_ref().is_none_or(|s| s.stale) {
+        usage_api_error(&usage_source, Some(&hook.model.id))
+    } else {
+        None
+    };
+    let openrouter = if openrouter_routed && usage_api_enabled && args.now.is_none() {
+        claude_statusline::openrouter::resolve_key(args.openrouter_key.as_deref()).and_then(|key| {
+            timer.time(Phase::Api, || {
+                claude_statusline::openrouter::get_credits(&key)
+            })
+        })
+    } else {
+        None
+    };
+    let (cost_history, lifetime) = if args.json && db_ledger {
+        claude_statusline::db::load_history_and_lifetime(STATS_HISTORY_DAYS)
+            .ok()
+            .unzip()
+    } else {
+        (None, None)
+    };
+    let extras = OutputExtras {
+        blocks: if args.json {
+            recent_blocks(&entries, now_utc, RECENT_BLOCKS_LIMIT)
+        } else {
+            Vec::new()
+        },
+        budget: BudgetStatus::evaluate(&args, &budget_spend),
+        mtd_cost: budget_spend.month,
+        forecast_month: budget_spend.month.map(|month_cost| {
+            forecast_month(month_cost, &daily_history, now_local(), args.billing_day)
+        }),
+        today_avg_7d: trailing_average(&daily_history, now_local().date_naive(), 7),
+        daily_history,
+        top_projects: if args.json {
+            let mut projects = projects_for_day(&entries, now_local().date_naive());
+            projects.truncate(TOP_PROJECTS_LIMIT);
+            projects
+        } else {
+            Vec::new()
+        },
+        by_model: if args.json {
+            models_for_day(&entries, now_local().date_naive())
+        } else {
+            Vec::new()
+        },
+        tag_costs: if args.json && !args.tags.is_empty() {
+            let projects = projects_for_day(&entries, now_local().date_naive());
+            costs_by_tag(
+                projects.iter().map(|p| (p.name.as_str(), p.cost_usd)),
+                &args,
+            )
+        } else {
+            Vec::new()
+        },
+        // Ledger rows for today are written by the db global-usage pass.
+        period_tag_costs: if args.json && !args.tags.is_empty() && db_ledger {
+            period_costs_by_tag(&args, now_local().date_naive())
+        } else {
+            None
+        },
+        window_efficiency: args.json.then_some(metrics.efficiency),
+        server_tool_costs: if args.json {
+            server_tool_costs(&metrics.server_tools)
+        } else {
+            Vec::new()
+        },
+        batch_cost: metrics.batch_cost,
+        tier_adjustment: metrics
+            .service_tier
+            .clone()
+            .filter(|_| metrics.tier_adjustment.abs() >= 0.005)
+            .map(|tier| (tier, metrics.tier_adjustment)),
+        environment: args.environment.then(|| {
+            let model = EnergyModel::from_args(&args);
+            let today = now_local().date_naive();
+            EnvironmentEstimate {
+                session: model.estimate(
+                    entries
+                        .iter()
+                        .filter(|e| e.session_id.as_deref() == Some(hook.session_id.as_str())),
+                ),
+                today: model.estimate(
+                    entries
+                        .iter()
+                        .filter(|e| e.ts.with_timezone(&Local).date_naive() == today),
+                ),
+                grid_intensity: model.grid_intensity,
+                customized: model.customized,
+            }
+        }),
+        lifetime,
+        // Only meaningful once the ledger holds today's row, i.e. after the
+        // db global-usage pass above succeeded.
+        cost_stats: cost_history
+            .map(|history| CostStats::from_history(&history, now_local().date_naive(), today_cost)),
+        cache: args.json.then(cache_report),
+        account: usage_account.map(|account| account.label),
+        openrouter,
+        usage_limits_error,
+        currency: claude_statusline::currency::display_currency().cloned(),
+    };
+
+    let render_started = Instant::now();
     if args.json {
         // Machine-readable output for statusline consumption
         // Compute per-subagent cost breakdown for this session
@@ -508,7 +867,7 @@ fn main() -> Result<()> {
             metrics.session_tokens_output,
             metrics.session_tokens_cache_create,
             metrics.session_tokens_cache_read,
-            metrics.web_search_requests,
+            metrics.server_tools.requests("web_search"),
             metrics.service_tier,
             usage_percent_display,
             projected_percent_display,
@@ -535,6 +894,7 @@ fn main() -> Result<()> {
             subagent_breakdown,
             Some(&cost_provenance),
             prompt_cache_info.as_ref(),
+            &extras,
         )?;
     } else {
         // Compute session-level cost per hour from Claude's provided cost
@@ -577,7 +937,7 @@ fn main() -> Result<()> {
             metrics.session_tokens_output,
             metrics.session_tokens_cache_create,
             metrics.session_tokens_cache_read,
-            metrics.web_search_requests,
+            metrics.server_tools.requests("web_search"),
             session_cph_opt,
             lines_delta,
             rate_limit_info.as_ref(),
@@ -585,6 +945,7 @@ fn main() -> Result<()> {
             context_limit_override,
             Some(&cost_provenance),
             prompt_cache_info.as_ref(),
+            &extras,
         );
 
         // Debug output if requested
@@ -623,6 +984,17 @@ fn main() -> Result<()> {
                 metrics.session_nc_tpm, metrics.global_nc_tpm
             );
             eprintln!("Files scanned: cutoff=48h (env: CLAUDE_SCAN_LOOKBACK_HOURS)");
+            let cache = cache_report();
+            eprintln!(
+                "Cache: {} hits, {} misses ({})",
+                cache.hits,
+                cache.misses,
+                if cache.layers.is_empty() {
+                    "no lookups".to_string()
+                } else {
+                    cache.summary()
+                }
+            );
             #[cfg(feature = "git")]
             if let Some(ref git) = git_info {
                 eprintln!(
@@ -639,6 +1011,11 @@ fn main() -> Result<()> {
                 "Window scope: {:?}, Burn scope: {:?}",
                 args.window_scope, args.burn_scope
             );
+            match (&args.timezone, &timezone_error) {
+                (Some(tz), None) => eprintln!("Timezone: {} (override)", tz),
+                (Some(_), Some(err)) => eprintln!("Timezone: {} (using system local)", err),
+                (None, _) => {}
+            }
             let usage_egress = resolve_usage_egress();
             match &usage_egress.extra_ca {
                 Some(path) => {
@@ -652,12 +1029,24 @@ fn main() -> Result<()> {
             eprintln!("{}", "========================".bright_black());
         }
     }
+    timer.add(Phase::Render, render_started);
+    // Same guard as the global usage lookup: replays never touch the live db
+    if !args.no_subsystem_db_cache && args.now.is_none() {
+        let _ = claude_statusline::db::record_render_timing(&timer.finish());
+    }
+    // Callers reading to EOF are done once the line is out
+    claude_statusline::utils::release_stdout();
+    #[cfg(feature = "git")]
+    claude_statusline::git::finish_background();
+    claude_statusline::usage_api::finish_background();
+    claude_statusline::pricing::finish_background();
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Utc;
 
     fn test_entry(session_id: &str, ts: chrono::DateTime<Utc>) -> Entry {
         Entry {
@@ -666,7 +1055,7 @@ mod tests {
             output: 0,
             cache_create: 0,
             cache_read: 0,
-            web_search_requests: 0,
+            server_tools: Default::default(),
             speed: None,
             service_tier: None,
             cost: 0.0,
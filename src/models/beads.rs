@@ -74,6 +74,19 @@ pub struct Bead {
     pub labels: Vec<String>,
 }
 
+/// Hooked or in-progress issue nobody has updated for a while
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleBead {
+    /// Issue ID
+    pub id: String,
+    /// Issue title
+    pub title: String,
+    /// Current status (hooked or in_progress)
+    pub status: BeadStatus,
+    /// Seconds since the issue was last updated
+    pub idle_secs: i64,
+}
+
 /// Summary of beads status counts
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BeadsCounts {
@@ -126,6 +139,21 @@ pub struct BeadsInfo {
     pub total_open: usize,
     /// Number of epics (parent issues with children)
     pub epic_count: usize,
+    /// Open issues waiting on at least one unresolved `blocks` dependency
+    #[serde(default)]
+    pub blocked_by_deps: usize,
+    /// Open, unassigned issues with no unresolved blockers, free to pick up
+    #[serde(default)]
+    pub ready: usize,
+    /// Hooked or in-progress issues untouched past the stale threshold (oldest first)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub stale: Vec<StaleBead>,
+    /// Issues created since local midnight
+    #[serde(default)]
+    pub opened_today: usize,
+    /// Issues closed since local midnight
+    #[serde(default)]
+    pub closed_today: usize,
     /// Top labels with counts (most common first)
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub top_labels: Vec<(String, usize)>,
@@ -1,7 +1,7 @@
 use crate::models::entry::Entry;
 use chrono::{DateTime, Utc};
 
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct TokenCounts {
     pub input: u64,
     pub output: u64,
@@ -9,7 +9,7 @@ pub struct TokenCounts {
     pub cache_read: u64,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Block {
     pub start: DateTime<Utc>,
     pub end: DateTime<Utc>,
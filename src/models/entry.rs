@@ -1,13 +1,95 @@
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-#[derive(Clone, Debug)]
+/// Server-side tool invocations from `usage.server_tool_use`, keyed by tool
+/// (`web_search`, `web_fetch`, ...). Every `<tool>_requests` counter is kept,
+/// so tools added upstream show up without code changes.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerToolUse {
+    /// `(tool, requests)` sorted by tool name, zero counts omitted
+    tools: Vec<(String, u64)>,
+}
+
+impl ServerToolUse {
+    /// Counters from a transcript `usage` object.
+    pub fn from_usage(usage: &Value) -> Self {
+        let mut tools = Self::default();
+        if let Some(counters) = usage.get("server_tool_use").and_then(|v| v.as_object()) {
+            for (key, value) in counters {
+                if let (Some(tool), Some(requests)) =
+                    (key.strip_suffix("_requests"), value.as_u64())
+                {
+                    tools.add(tool, requests);
+                }
+            }
+        }
+        tools
+    }
+
+    /// Single-tool counter, e.g. web searches from an SDK `modelUsage` total.
+    pub fn single(tool: &str, requests: u64) -> Self {
+        let mut tools = Self::default();
+        tools.add(tool, requests);
+        tools
+    }
+
+    pub fn requests(&self, tool: &str) -> u64 {
+        self.tools
+            .iter()
+            .find(|(name, _)| name == tool)
+            .map_or(0, |(_, n)| *n)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.tools.iter().map(|(name, n)| (name.as_str(), *n))
+    }
+
+    /// Add `requests` to one tool's counter.
+    pub fn add(&mut self, tool: &str, requests: u64) {
+        if requests == 0 {
+            return;
+        }
+        match self
+            .tools
+            .binary_search_by(|(name, _)| name.as_str().cmp(tool))
+        {
+            Ok(i) => self.tools[i].1 = self.tools[i].1.saturating_add(requests),
+            Err(i) => self.tools.insert(i, (tool.to_string(), requests)),
+        }
+    }
+
+    /// Sum per-chunk deltas into these counters.
+    pub fn accumulate(&mut self, other: &Self) {
+        for (tool, requests) in other.iter() {
+            self.add(tool, requests);
+        }
+    }
+
+    /// Keep the larger counter per tool, for cumulative snapshots of one message.
+    pub fn merge_max(&mut self, other: &Self) {
+        for (tool, requests) in other.iter() {
+            let current = self.requests(tool);
+            if requests > current {
+                self.add(tool, requests - current);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Entry {
     pub ts: DateTime<Utc>,
     pub input: u64,
     pub output: u64,
     pub cache_create: u64,
     pub cache_read: u64,
-    pub web_search_requests: u64,
+    /// Server tool invocations, priced as separate line items
+    pub server_tools: ServerToolUse,
     pub speed: Option<String>,
     pub service_tier: Option<String>,
     pub cost: f64,
@@ -23,3 +105,11 @@ pub struct Entry {
     /// `None` for main-thread entries.
     pub agent_id: Option<String>,
 }
+
+impl Entry {
+    /// Whether this entry ran through the Message Batches API and was billed
+    /// at batch rates.
+    pub fn is_batch(&self) -> bool {
+        self.service_tier.as_deref() == Some("batch")
+    }
+}
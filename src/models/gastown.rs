@@ -83,6 +83,15 @@ impl RigStatus {
             Self::Inactive => "⚫",
         }
     }
+
+    /// Get the status name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Partial => "partial",
+            Self::Inactive => "inactive",
+        }
+    }
 }
 
 /// Information about a single rig
@@ -110,6 +119,9 @@ pub struct MailPreview {
     /// Subject of the most recent unread message (truncated)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub preview: Option<String>,
+    /// Seconds since the oldest unread message was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_age_secs: Option<i64>,
 }
 
 /// Current agent's identity in the Gas Town hierarchy
@@ -153,6 +165,19 @@ pub struct GasTownInfo {
     /// Refinery merge queue info (for refinery view)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub refinery_queue: Option<RefineryQueue>,
+    /// In-flight convoys (for mayor/deacon view)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub convoys: Option<ConvoyStatus>,
+}
+
+/// Convoys still in flight in the town
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConvoyStatus {
+    /// Number of convoys not yet closed
+    pub active: usize,
+    /// Seconds since the oldest in-flight convoy was created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_age_secs: Option<i64>,
 }
 
 /// Refinery merge queue status
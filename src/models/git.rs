@@ -1,12 +1,244 @@
-#[derive(Default, Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct GitInfo {
+    /// Repository kind the fields were read from
+    pub vcs: Vcs,
+    /// Branch, or the active bookmark in Mercurial and Sapling
     pub branch: Option<String>,
+    /// Top of the working tree
+    pub root: Option<String>,
+    /// Nearest package root (`Cargo.toml`, `package.json`, `BUILD`, ...)
+    /// below `root` containing the project dir, relative to `root`
+    pub subproject: Option<String>,
     pub short_commit: Option<String>,
+    /// Full HEAD commit id
+    pub commit: Option<String>,
+    /// Seconds since the HEAD commit was made (committer time)
+    pub head_age_secs: Option<i64>,
     pub is_clean: Option<bool>,
     pub ahead: Option<usize>,
     pub behind: Option<usize>,
+    /// Configured tracking branch, e.g. `origin/main`
+    pub upstream: Option<String>,
+    /// Branch `origin/HEAD` points at, e.g. `main`
+    pub default_branch: Option<String>,
+    /// Commits on HEAD missing from `origin/<default_branch>`, and the reverse
+    pub default_ahead: Option<usize>,
+    pub default_behind: Option<usize>,
+    /// Current branch is the default branch or matches `--git-protected-branch`
+    pub protected: bool,
     pub remote_url: Option<String>,
     pub is_head_on_remote: Option<bool>,
     pub worktree_count: Option<usize>,
     pub is_linked_worktree: Option<bool>,
+    /// Main and linked worktrees; empty when there are no linked ones
+    pub worktrees: Vec<GitWorktree>,
+    /// Repository conditions that slow agents down, with `--git-health`
+    pub health: Vec<GitHealthIssue>,
+    /// Entries in `refs/stash`
+    pub stash_count: Option<usize>,
+    /// Untracked, non-ignored files in the worktree
+    pub untracked: Option<usize>,
+    /// Lines added and removed in tracked files since HEAD; only read when
+    /// the hook carries no line counts of its own
+    pub lines_added: Option<usize>,
+    pub lines_removed: Option<usize>,
+    /// Paths with unmerged (conflicted) index entries
+    pub conflicts: Option<usize>,
+    /// Sparse checkout is enabled, so part of the tree is not materialized
+    pub sparse_checkout: Option<bool>,
+    /// Tracked files still holding a Git LFS pointer instead of their
+    /// content; `None` when the repository does not use LFS
+    pub lfs_pending: Option<usize>,
+    /// Submodules with a clone and worktree checkout
+    pub submodules: Option<usize>,
+    /// Of those, ones off the recorded commit or with local changes
+    pub submodules_dirty: Option<usize>,
+    /// Merge, rebase, or similar left in progress
+    pub operation: Option<GitOperation>,
+    /// Nearest tag reachable from HEAD
+    pub describe: Option<GitDescribe>,
+    /// Pull request for the current branch, from the `gh` CLI
+    pub pull_request: Option<GitPullRequest>,
+    /// Check runs on HEAD, from the `gh` CLI
+    pub ci: Option<GitCi>,
+    /// Issue keys found in the branch name
+    pub issues: Vec<GitIssue>,
+    /// Set when inspection ran past `--git-timeout-ms` and this is an earlier
+    /// render's result: seconds since that result was cached
+    #[serde(skip)]
+    pub stale_age_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Vcs {
+    #[default]
+    Git,
+    Mercurial,
+    Sapling,
+}
+
+impl Vcs {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Vcs::Git => "git",
+            Vcs::Mercurial => "hg",
+            Vcs::Sapling => "sapling",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitCiState {
+    Success,
+    Failure,
+    Pending,
+}
+
+impl GitCiState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GitCiState::Success => "success",
+            GitCiState::Failure => "failure",
+            GitCiState::Pending => "pending",
+        }
+    }
+}
+
+/// One checkout of the repository, as `git worktree list` shows it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitWorktree {
+    /// Directory name for the main worktree, the worktree id for linked ones
+    pub name: String,
+    pub path: String,
+    /// `None` when detached
+    pub branch: Option<String>,
+    pub is_main: bool,
+    /// The worktree this session runs in
+    pub is_current: bool,
+}
+
+/// Repository condition reported by `--git-health`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitHealthIssue {
+    pub kind: GitHealthKind,
+    /// Directory concerned, relative to the worktree root; `.git` for the git dir
+    pub path: String,
+    /// Untracked files below `path`, or bytes for the git dir
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitHealthKind {
+    /// Untracked directory holding thousands of files
+    LargeUntrackedDir,
+    /// Git dir over `--git-health-max-git-gb`
+    LargeGitDir,
+    /// Build output (`target`, `node_modules`, ...) not covered by a gitignore
+    UnignoredBuildDir,
+}
+
+/// Issue key parsed out of the branch name, e.g. `PROJ-123` or `#789`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitIssue {
+    pub key: String,
+    /// Tracker link from `--git-issue-url`
+    pub url: Option<String>,
+}
+
+/// Check runs reported for one commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitCi {
+    pub state: GitCiState,
+    pub total: usize,
+    pub failed: usize,
+    pub pending: usize,
+}
+
+/// Pull request fields as `gh pr status --json` reports them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitPullRequest {
+    pub number: u64,
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    /// `OPEN`, `CLOSED`, or `MERGED`
+    pub state: String,
+    #[serde(default)]
+    pub is_draft: bool,
+    /// `APPROVED`, `CHANGES_REQUESTED`, or `REVIEW_REQUIRED`; `None` when
+    /// the repository requires no review
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub review_decision: Option<String>,
+    /// `MERGEABLE`, `CONFLICTING`, or `UNKNOWN`
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub mergeable: Option<String>,
+}
+
+/// `gh` reports unset enum fields as `""`.
+fn empty_as_none<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.filter(|s| !s.is_empty()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitDescribe {
+    pub tag: String,
+    /// Commits on HEAD since the tag
+    pub distance: u32,
+    /// `git describe --tags` form, e.g. `v1.4.2-12-gabc1234`
+    pub text: String,
+}
+
+/// Multi-step git operations that leave the worktree mid-flight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitOperationKind {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+    Am,
+    Bisect,
+}
+
+impl GitOperationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GitOperationKind::Merge => "merge",
+            GitOperationKind::Rebase => "rebase",
+            GitOperationKind::CherryPick => "cherry_pick",
+            GitOperationKind::Revert => "revert",
+            GitOperationKind::Am => "am",
+            GitOperationKind::Bisect => "bisect",
+        }
+    }
+
+    /// Header label, as git's own prompt spells it.
+    pub fn label(self) -> &'static str {
+        match self {
+            GitOperationKind::Merge => "MERGING",
+            GitOperationKind::Rebase => "REBASE",
+            GitOperationKind::CherryPick => "CHERRY-PICKING",
+            GitOperationKind::Revert => "REVERTING",
+            GitOperationKind::Am => "AM",
+            GitOperationKind::Bisect => "BISECTING",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitOperation {
+    pub kind: GitOperationKind,
+    /// Current step and total for rebase and am, when git recorded them
+    pub step: Option<usize>,
+    pub total: Option<usize>,
 }
@@ -35,8 +35,9 @@ pub struct HookCost {
     pub total_cost_usd: f64,
     pub total_duration_ms: u64,
     pub total_api_duration_ms: u64,
-    pub total_lines_added: i64,
-    pub total_lines_removed: i64,
+    /// Missing from older Claude Code releases
+    pub total_lines_added: Option<i64>,
+    pub total_lines_removed: Option<i64>,
 }
 
 /// Current usage breakdown from the last API call
@@ -0,0 +1,274 @@
+//! Records read from and written to the statusline database.
+//!
+//! These live apart from `db` so builds without the `db` feature keep the
+//! same types while the storage itself is compiled out.
+
+use crate::models::TokenCounts;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Global usage result containing both session-specific and global costs
+#[derive(Debug, Clone)]
+pub struct GlobalUsage {
+    /// Cost for the current session only
+    pub session_cost: f64,
+    /// Total cost across all sessions for today
+    pub global_today: f64,
+    /// Number of sessions contributing to global total
+    pub sessions_count: usize,
+    /// Recorded per-day totals for the last four weeks, oldest first, including today
+    pub daily_history: Vec<DailyCost>,
+}
+
+/// Global cost recorded for one local calendar day
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailyCost {
+    pub date: NaiveDate,
+    pub cost: f64,
+}
+
+/// Metadata value with optional timestamp
+#[derive(Debug, Clone)]
+pub struct MetadataEntry {
+    pub value: String,
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DbHealth {
+    pub path: String,
+    pub exists: bool,
+    pub parent_exists: bool,
+    pub writable: bool,
+    pub journal_mode: Option<String>,
+    pub schema_version: Option<String>,
+    pub user_version: Option<i64>,
+    pub usage_cache_version: Option<String>,
+    /// Built with `encrypted-db` (SQLCipher)
+    pub encrypted: bool,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+/// How long history rows are kept. `None` keeps them forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    /// Days of `entries` history, by entry date
+    pub entries_days: Option<u32>,
+    /// Days since an `api_cache` row was fetched, including stale fallbacks
+    pub api_cache_days: Option<u32>,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            // Matches the daily ledger's own 400-day retention
+            entries_days: Some(400),
+            api_cache_days: Some(30),
+        }
+    }
+}
+
+/// Freshness windows of the db-backed caches, in seconds. 0 disables a cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheTtls {
+    /// Today's cross-session cost sum
+    pub global_sum_seconds: i64,
+    /// Transcript scan results (`get_cached_usage`)
+    pub scan_seconds: i64,
+    /// OAuth usage API responses
+    pub usage_api_seconds: i64,
+}
+
+impl Default for CacheTtls {
+    fn default() -> Self {
+        Self {
+            global_sum_seconds: 5,
+            scan_seconds: 3,
+            usage_api_seconds: 300,
+        }
+    }
+}
+
+/// Cache rows dropped by `cache invalidate`. Usage history is never touched.
+#[derive(Debug, Default, Serialize)]
+pub struct InvalidateReport {
+    pub path: String,
+    /// `None` for `--all`
+    pub session_id: Option<String>,
+    /// Per-session transcript parse state, rebuilt on the next render
+    pub sessions: usize,
+    pub api_cache: usize,
+    /// Cached global sums
+    pub metadata: usize,
+}
+
+/// Rows removed by a prune.
+#[derive(Debug, Default, Serialize)]
+pub struct PruneReport {
+    pub path: String,
+    pub entries: usize,
+    pub usage_snapshots: usize,
+    pub api_cache: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MaintenanceReport {
+    pub path: String,
+    pub wal_bytes_before: u64,
+    pub checkpointed: bool,
+    pub pages_before: i64,
+    pub free_pages_before: i64,
+    pub pages_after: i64,
+    /// `incremental`, `full`, or `None` when nothing was reclaimed.
+    pub vacuum: Option<&'static str>,
+}
+
+/// One history row tagged with its table, as written to sync snapshots.
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct HistoryRow {
+    pub table: String,
+    pub row: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Rows merged by `db import`, per table.
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub path: String,
+    /// Rows inserted or updated, by table
+    pub tables: BTreeMap<&'static str, usize>,
+    /// Days whose ledgers were reconciled
+    pub dates: usize,
+}
+
+/// Outcome of `db check`.
+#[derive(Debug, Default, Serialize)]
+pub struct IntegrityReport {
+    pub path: String,
+    pub ok: bool,
+    /// `PRAGMA integrity_check` findings, empty when `ok`
+    pub problems: Vec<String>,
+    /// Where a corrupt predecessor was moved when this database was rebuilt
+    pub recovered_from: Option<String>,
+}
+
+/// Outcome of `db migrate`.
+#[derive(Debug, Serialize)]
+pub struct MigrationReport {
+    pub path: String,
+    pub from_version: i64,
+    pub to_version: i64,
+    pub dry_run: bool,
+    /// Steps applied, or with `dry_run` the steps that would be
+    pub migrations: Vec<MigrationStep>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrationStep {
+    pub version: i64,
+    pub description: &'static str,
+}
+
+/// Persisted spend history used for cross-day and cross-session statistics
+#[derive(Debug, Clone, Default)]
+pub struct CostHistory {
+    /// Recorded per-day global totals, oldest first
+    pub daily: Vec<DailyCost>,
+    /// Total cost of each session active in the window (summed across its days)
+    pub session_costs: Vec<f64>,
+}
+
+/// Cumulative spend since the ledger started, kept in `lifetime_totals` so it
+/// survives ledger retention and never needs a transcript rescan.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LifetimeStats {
+    /// First day with recorded spend
+    pub first_seen: Option<NaiveDate>,
+    pub cost: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_create_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Days with non-zero recorded spend
+    pub active_days: u32,
+    /// Most expensive recorded day
+    pub busiest_day: Option<DailyCost>,
+}
+
+impl LifetimeStats {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_create_tokens + self.cache_read_tokens
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "first_seen": self.first_seen.map(|d| d.format("%Y-%m-%d").to_string()),
+            "active_days": self.active_days,
+            "cost_usd": (self.cost * 100.0).round() / 100.0,
+            "total_tokens": self.total_tokens(),
+            "input_tokens": self.input_tokens,
+            "output_tokens": self.output_tokens,
+            "cache_creation_input_tokens": self.cache_create_tokens,
+            "cache_read_input_tokens": self.cache_read_tokens,
+            "busiest_day": self.busiest_day.map(|d| serde_json::json!({
+                "date": d.date.format("%Y-%m-%d").to_string(),
+                "cost_usd": (d.cost * 100.0).round() / 100.0,
+            })),
+        })
+    }
+}
+
+/// Spend and token totals for one model over a date range
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelTotals {
+    pub model: String,
+    pub cost: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_create_tokens: u64,
+    pub cache_read_tokens: u64,
+}
+
+/// OAuth usage utilization (percent) as returned by one successful fetch.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageSnapshot {
+    /// `[accounts]` label the reading belongs to; empty without `[accounts]`
+    pub account: String,
+    /// Unix seconds
+    pub fetched_at: i64,
+    pub five_hour: Option<f64>,
+    pub five_hour_resets_at: Option<i64>,
+    pub seven_day: Option<f64>,
+    pub seven_day_resets_at: Option<i64>,
+    pub seven_day_opus: Option<f64>,
+    pub seven_day_sonnet: Option<f64>,
+}
+
+/// Phase durations of one statusline render, in milliseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RenderTiming {
+    /// Unix seconds
+    pub recorded_at: i64,
+    /// Binary version that rendered
+    pub version: String,
+    pub scan_ms: f64,
+    pub db_ms: f64,
+    pub git_ms: f64,
+    pub api_ms: f64,
+    pub render_ms: f64,
+    /// Whole invocation, including time outside the named phases
+    pub total_ms: f64,
+}
+
+/// One statusline database's ledgers over a date range, used for seat-level
+/// team aggregation.
+#[derive(Debug, Clone, Default)]
+pub struct SeatLedger {
+    /// Recorded per-day global totals, oldest first
+    pub daily: Vec<DailyCost>,
+    /// Per-day token totals from the model ledger
+    pub tokens: Vec<(NaiveDate, TokenCounts)>,
+    /// Last cached usage API response (raw JSON) and its fetch time (epoch seconds)
+    pub usage_summary: Option<(String, i64)>,
+}
@@ -4,15 +4,17 @@ pub mod entry;
 pub mod gastown;
 pub mod git;
 pub mod hook;
+pub mod ledger;
 pub mod message;
 pub mod prompt_cache;
 pub mod ratelimit;
 
-pub use beads::{Bead, BeadStatus, BeadsCounts, BeadsInfo, PriorityCounts, TypeCounts};
+pub use beads::{Bead, BeadStatus, BeadsCounts, BeadsInfo, PriorityCounts, StaleBead, TypeCounts};
 pub use block::{Block, TokenCounts};
-pub use entry::Entry;
+pub use entry::{Entry, ServerToolUse};
 pub use gastown::{
-    AgentIdentity, AgentType, GasTownInfo, MailPreview, RefineryQueue, RigInfo, RigStatus,
+    AgentIdentity, AgentType, ConvoyStatus, GasTownInfo, MailPreview, RefineryQueue, RigInfo,
+    RigStatus,
 };
 pub use git::GitInfo;
 pub use hook::HookJson;
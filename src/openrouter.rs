@@ -0,0 +1,210 @@
+//! # OpenRouter Module
+//!
+//! Credit balance and rate limit for sessions routed through OpenRouter, from
+//! its key and credits endpoints. They stand in for the Anthropic OAuth usage
+//! limits, which do not apply there. Results are cached in the db and
+//! refetched behind the expired credits, so only a render with nothing cached
+//! waits on OpenRouter.
+
+use serde::{Deserialize, Serialize};
+use std::env;
+
+use crate::usage_api::{CachedApi, cached_fetch};
+use crate::utils::key_fingerprint;
+
+const KEY_ENDPOINT: &str = "https://openrouter.ai/api/v1/key";
+const CREDITS_ENDPOINT: &str = "https://openrouter.ai/api/v1/credits";
+const OPENROUTER_HOST: &str = "openrouter.ai";
+const CACHE_PREFIX: &str = "openrouter:";
+const CACHE_TTL_SECONDS: i64 = 120;
+/// A failed call is retried no sooner than this
+const RETRY_SECONDS: i64 = 300;
+
+/// Credits and limits of the OpenRouter key in use
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpenRouterCredits {
+    pub label: Option<String>,
+    /// Credits spent through this key, in USD
+    pub usage: f64,
+    /// The key's spending cap in USD; `None` when uncapped
+    pub limit: Option<f64>,
+    /// Credits left under the key's cap, else the account balance; `None`
+    /// when neither was reported
+    pub remaining: Option<f64>,
+    pub is_free_tier: bool,
+    pub rate_limit: Option<OpenRouterRateLimit>,
+}
+
+/// Requests allowed per interval, e.g. 10 per `10s`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenRouterRateLimit {
+    pub requests: i64,
+    pub interval: String,
+}
+
+/// Whether the session goes through OpenRouter: `ANTHROPIC_BASE_URL` points at
+/// it, the model id carries its `openrouter/` prefix, or `CLAUDE_PROVIDER` says so.
+pub fn is_openrouter(model_id: &str) -> bool {
+    let base_url_is_openrouter = env::var("ANTHROPIC_BASE_URL")
+        .is_ok_and(|url| url.to_lowercase().contains(OPENROUTER_HOST));
+    base_url_is_openrouter
+        || model_id.to_lowercase().starts_with("openrouter/")
+        || env::var("CLAUDE_PROVIDER").is_ok_and(|p| p.eq_ignore_ascii_case("openrouter"))
+}
+
+/// The key to query: `configured` (`--openrouter-key`), else the token Claude
+/// Code itself sends when `ANTHROPIC_BASE_URL` points at OpenRouter.
+pub fn resolve_key(configured: Option<&str>) -> Option<String> {
+    if let Some(key) = configured.map(str::trim).filter(|k| !k.is_empty()) {
+        return Some(key.to_string());
+    }
+    env::var("ANTHROPIC_BASE_URL")
+        .ok()
+        .filter(|url| url.to_lowercase().contains(OPENROUTER_HOST))?;
+    ["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_API_KEY"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .map(|key| key.trim().to_string())
+        .find(|key| !key.is_empty())
+}
+
+/// Credits for `key`, from the db cache or OpenRouter. Expired credits are
+/// served while they are refetched; a failed fetch is retried after a
+/// back-off.
+pub fn get_credits(key: &str) -> Option<OpenRouterCredits> {
+    let api = CachedApi {
+        name: "openrouter",
+        key: format!("{CACHE_PREFIX}{}", key_fingerprint(key)),
+        ttl_seconds: CACHE_TTL_SECONDS,
+        retry_seconds: RETRY_SECONDS,
+    };
+    let key = key.to_string();
+    cached_fetch(api, move || fetch_credits(&key))
+}
+
+fn fetch_credits(key: &str) -> Option<OpenRouterCredits> {
+    let agent = crate::usage_api::api_agent();
+    let get = |url: &str| -> Option<String> {
+        match agent
+            .get(url)
+            .header("Authorization", &format!("Bearer {key}"))
+            .header("Accept", "application/json")
+            .call()
+        {
+            Ok(mut r) => r.body_mut().read_to_string().ok(),
+            Err(e) => {
+                eprintln!("OpenRouter API error: {}", e);
+                None
+            }
+        }
+    };
+    let mut credits = parse_key(&get(KEY_ENDPOINT)?)?;
+    // Uncapped keys draw on the account balance
+    if credits.remaining.is_none() {
+        credits.remaining = get(CREDITS_ENDPOINT).and_then(|body| parse_balance(&body));
+    }
+    Some(credits)
+}
+
+#[derive(Deserialize)]
+struct Envelope<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct KeyDto {
+    label: Option<String>,
+    #[serde(default)]
+    usage: f64,
+    limit: Option<f64>,
+    limit_remaining: Option<f64>,
+    #[serde(default)]
+    is_free_tier: bool,
+    rate_limit: Option<OpenRouterRateLimit>,
+}
+
+#[derive(Deserialize)]
+struct CreditsDto {
+    total_credits: f64,
+    total_usage: f64,
+}
+
+fn parse_key(body: &str) -> Option<OpenRouterCredits> {
+    let key = serde_json::from_str::<Envelope<KeyDto>>(body).ok()?.data;
+    Some(OpenRouterCredits {
+        label: key.label,
+        usage: key.usage,
+        limit: key.limit,
+        remaining: key.limit_remaining,
+        is_free_tier: key.is_free_tier,
+        // Newer keys report -1 requests: no fixed per-key limit
+        rate_limit: key.rate_limit.filter(|limit| limit.requests > 0),
+    })
+}
+
+fn parse_balance(body: &str) -> Option<f64> {
+    let credits = serde_json::from_str::<Envelope<CreditsDto>>(body)
+        .ok()?
+        .data;
+    Some(credits.total_credits - credits.total_usage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn key_and_credits_parse_into_remaining_balance() {
+        let credits = parse_key(
+            r#"{"data": {"label": "sk-or-v1-abc...xyz", "usage": 12.5, "limit": 20,
+            "limit_remaining": 7.5, "is_free_tier": false,
+            "rate_limit": {"requests": 10, "interval": "10s"}}}"#,
+        )
+        .expect("key");
+        assert_eq!(
+            (credits.usage, credits.limit, credits.remaining),
+            (12.5, Some(20.0), Some(7.5))
+        );
+        assert_eq!(credits.rate_limit.expect("rate limit").interval, "10s");
+
+        let uncapped = parse_key(
+            r#"{"data": {"label": "k", "usage": 3, "limit": null, "limit_remaining": null,
+            "is_free_tier": true, "rate_limit": {"requests": -1, "interval": "10s"}}}"#,
+        )
+        .expect("key");
+        assert_eq!((uncapped.remaining, uncapped.rate_limit), (None, None));
+        assert!(uncapped.is_free_tier);
+
+        assert_eq!(
+            parse_balance(r#"{"data": {"total_credits": 50, "total_usage": 42.25}}"#),
+            Some(7.75)
+        );
+        assert_eq!(parse_key("{}"), None);
+    }
+
+    #[test]
+    #[serial]
+    fn openrouter_is_detected_from_base_url_or_model() {
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe {
+            env::remove_var("ANTHROPIC_BASE_URL");
+            env::remove_var("CLAUDE_PROVIDER");
+        }
+        assert!(!is_openrouter("claude-sonnet-4-6"));
+        assert!(is_openrouter("openrouter/anthropic/claude-sonnet-4.5"));
+        assert_eq!(resolve_key(None), None);
+        assert_eq!(resolve_key(Some(" sk-or-1 ")).as_deref(), Some("sk-or-1"));
+
+        unsafe {
+            env::set_var("ANTHROPIC_BASE_URL", "https://openrouter.ai/api");
+            env::set_var("ANTHROPIC_AUTH_TOKEN", "sk-or-v1-test");
+        }
+        assert!(is_openrouter("claude-sonnet-4-6"));
+        assert_eq!(resolve_key(None).as_deref(), Some("sk-or-v1-test"));
+        unsafe {
+            env::remove_var("ANTHROPIC_BASE_URL");
+            env::remove_var("ANTHROPIC_AUTH_TOKEN");
+        }
+    }
+}
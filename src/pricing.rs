@@ -18,16 +18,38 @@
 //!    - `CLAUDE_PRICE_OUTPUT`
 //!    - `CLAUDE_PRICE_CACHE_CREATE`
 //!    - `CLAUDE_PRICE_CACHE_READ`
-//! 2. Compile-time embedded pricing.json (from `pricing.json` at build time)
-//! 3. Built-in static pricing fallback for active Claude models
-//! 4. Family heuristics for unknown future opus/sonnet/haiku IDs
+//! 2. Runtime pricing file (`--pricing-file`, or `~/.claude/statusline-pricing.json`)
+//! 3. Compile-time embedded pricing.json (from `pricing.json` at build time)
+//! 4. Built-in static pricing fallback for active Claude models
+//! 5. LiteLLM price database, when fetched (`--pricing-fetch`)
+//! 6. Family heuristics for unknown future opus/sonnet/haiku IDs, plus list
+//!    prices for major non-Anthropic families reached through proxies
+//!
+//! Bedrock and Vertex usage (2-6) is then adjusted by the `providers` section
+//! of the pricing file: provider-specific model prices and per-region
+//! multipliers.
+//!
+//! Models matching a `--free-model` glob are priced at $0 ahead of all of the
+//! above, so local or internal models only contribute tokens.
+//!
+//! Entries from projects listed in the config `[project_pricing]` section are
+//! priced from that project's pricing file first, and/or scaled by its
+//! multiplier (negotiated rates, proxy surcharges).
 
-use once_cell::sync::Lazy;
+use anyhow::{Context, Result, bail};
+use chrono::NaiveDate;
+use once_cell::sync::{Lazy, OnceCell};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
 
+use crate::models::ServerToolUse;
 use crate::provenance::PricingSource;
 
 #[derive(Clone, Copy, Debug)]
@@ -68,6 +90,16 @@ impl Pricing {
         )
     }
 
+    fn scaled(self, multiplier: f64) -> Self {
+        Self::new(
+            self.in_per_tok * multiplier,
+            self.out_per_tok * multiplier,
+            self.cache_create_per_tok * multiplier,
+            self.cache_create_1h_per_tok * multiplier,
+            self.cache_read_per_tok * multiplier,
+        )
+    }
+
     fn from_input_multipliers(in_per_tok: f64, out_per_tok: f64) -> Self {
         Self::new(
             in_per_tok,
@@ -102,39 +134,137 @@ struct PricingMultipliers {
     cache_read: f64,
 }
 
+/// Price multipliers once a request's input (including cache) exceeds
+/// `threshold` tokens, for models matching `applies_to`.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 struct PricingTier {
     name: String,
     #[serde(default)]
     description: Option<String>,
     threshold: u64,
+    /// Model ids, date-suffixed ids, or `*`/`?` globs such as `claude-sonnet-5*`
     applies_to: Vec<String>,
     multipliers: PricingMultipliers,
 }
 
+impl PricingTier {
+    fn applies_to_model(&self, model: &str) -> bool {
+        self.applies_to.iter().any(|pattern| {
+            let p = pattern.to_lowercase();
+            if p.contains(['*', '?']) {
+                return crate::utils::glob_match(&p, model);
+            }
+            if model == p {
+                return true;
+            }
+            // Only match date suffixes like -20250514 (8+ digits), not version suffixes like -5, -6
+            model
+                .strip_prefix(&p)
+                .and_then(|suffix| suffix.strip_prefix('-'))
+                .is_some_and(|rest| rest.len() >= 8 && rest.chars().all(|c| c.is_ascii_digit()))
+        })
+    }
+
+    fn apply(&self, base: Pricing) -> Pricing {
+        let m = &self.multipliers;
+        Pricing::new(
+            base.in_per_tok * m.input,
+            base.out_per_tok * m.output,
+            base.cache_create_per_tok * m.cache_create,
+            base.cache_create_1h_per_tok * m.cache_create_1h.unwrap_or(m.cache_create),
+            base.cache_read_per_tok * m.cache_read,
+        )
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Default)]
 struct TieredPricing {
+    /// Defaults to on when tiers are listed
     #[serde(default)]
-    enabled: bool,
+    enabled: Option<bool>,
     #[serde(default)]
     comment: Option<String>,
     #[serde(default)]
     tiers: Vec<PricingTier>,
 }
 
+impl TieredPricing {
+    /// The highest-threshold tier the request crosses for this model.
+    fn tier_for(&self, model: &str, total_input_tokens: u64) -> Option<&PricingTier> {
+        if !self.enabled.unwrap_or(!self.tiers.is_empty()) {
+            return None;
+        }
+        self.tiers
+            .iter()
+            .filter(|tier| total_input_tokens > tier.threshold && tier.applies_to_model(model))
+            .max_by_key(|tier| tier.threshold)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+struct PricingMeta {
+    /// Date the prices were last checked against the published rates
+    #[serde(default)]
+    updated: Option<NaiveDate>,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 struct PricingConfig {
+    #[serde(default, rename = "_meta")]
+    meta: PricingMeta,
     models: HashMap<String, ModelPricing>,
     #[serde(default)]
     additional_costs: AdditionalCosts,
     #[serde(default)]
     tiered_pricing: TieredPricing,
+    /// Token price multiplier per `usage.service_tier`
+    #[serde(default)]
+    service_tier_multipliers: HashMap<String, f64>,
+    /// Adjustments for `bedrock` / `vertex` usage
+    #[serde(default)]
+    providers: HashMap<String, ProviderPricing>,
 }
 
+/// Pricing adjustments for one cloud provider.
 #[derive(Deserialize, Serialize, Debug, Default)]
-struct AdditionalCosts {
+struct ProviderPricing {
+    /// Prices replacing the Anthropic table for this provider
+    #[serde(default)]
+    models: HashMap<String, ModelPricing>,
+    /// Multiplier for all usage through this provider
+    #[serde(default)]
+    multiplier: Option<f64>,
+    /// Multiplier for regional (non-`global`) endpoints not listed in `regions`
     #[serde(default)]
-    web_search_per_request: f64,
+    regional_multiplier: Option<f64>,
+    /// Multiplier per region (`eu-west-1`) or geography (`eu`)
+    #[serde(default)]
+    regions: HashMap<String, f64>,
+}
+
+impl ProviderPricing {
+    fn multiplier_for(&self, region: Option<&str>) -> f64 {
+        let regional = region.map_or(1.0, |r| {
+            self.regions
+                .get(r)
+                .or_else(|| r.split('-').next().and_then(|geo| self.regions.get(geo)))
+                .copied()
+                .unwrap_or(if r == "global" {
+                    1.0
+                } else {
+                    self.regional_multiplier.unwrap_or(1.0)
+                })
+        });
+        self.multiplier.unwrap_or(1.0) * regional
+    }
+}
+
+/// Flat charges outside token pricing: `<tool>_per_request` rates for server
+/// tools, plus informational entries such as `code_execution_per_hour`.
+#[derive(Deserialize, Serialize, Debug, Default)]
+struct AdditionalCosts {
+    #[serde(flatten)]
+    rates: HashMap<String, f64>,
 }
 
 /// Compile-time embedded pricing configuration
@@ -143,27 +273,552 @@ static PRICING_CONFIG: Lazy<Option<PricingConfig>> = Lazy::new(|| {
     serde_json::from_str::<PricingConfig>(EMBEDDED_PRICING).ok()
 });
 
+/// Prices per token above this are almost certainly per-million figures.
+const MAX_PRICE_PER_TOKEN: f64 = 0.01;
+
+/// Runtime pricing file: the loaded table, or why it was rejected.
+static PRICING_FILE: OnceCell<std::result::Result<(PathBuf, PricingConfig), String>> =
+    OnceCell::new();
+
+/// Default runtime pricing file, `~/.claude/statusline-pricing.json`.
+pub fn default_pricing_file() -> Option<PathBuf> {
+    directories::BaseDirs::new()
+        .map(|b| b.home_dir().join(".claude").join("statusline-pricing.json"))
+}
+
+/// Load and validate the runtime pricing file once per process: `explicit`
+/// (`--pricing-file`) or the default path when it exists. A rejected file is
+/// remembered for `doctor` and ignored for pricing.
+pub fn load_pricing_file(explicit: Option<&Path>) -> Result<Option<&'static Path>> {
+    let path = match explicit {
+        Some(path) => path.to_path_buf(),
+        None => match default_pricing_file() {
+            Some(path) if path.is_file() => path,
+            _ => return Ok(None),
+        },
+    };
+    let loaded = PRICING_FILE.get_or_init(|| {
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read pricing file {}", path.display()))
+            .and_then(|text| parse_pricing_file(&text))
+            .map(|config| (path.clone(), config))
+            .map_err(|e| format!("{e:#}"))
+    });
+    match loaded {
+        Ok((path, _)) => Ok(Some(path.as_path())),
+        Err(e) => bail!("{e}"),
+    }
+}
+
+/// Loaded pricing file path, or the reason it was rejected; `None` when no
+/// file was configured.
+pub fn pricing_file_status() -> Option<std::result::Result<&'static Path, &'static str>> {
+    PRICING_FILE.get().map(|loaded| match loaded {
+        Ok((path, _)) => Ok(path.as_path()),
+        Err(e) => Err(e.as_str()),
+    })
+}
+
+/// `_meta.updated` of the embedded pricing.json.
+pub fn embedded_pricing_date() -> Option<NaiveDate> {
+    PRICING_CONFIG.as_ref()?.meta.updated
+}
+
+/// Newest `_meta.updated` among the loaded price tables: the runtime pricing
+/// file, when it declares one, and the embedded pricing.json.
+pub fn pricing_table_date() -> Option<NaiveDate> {
+    file_config()
+        .and_then(|c| c.meta.updated)
+        .max(embedded_pricing_date())
+}
+
+fn file_config() -> Option<&'static PricingConfig> {
+    PRICING_FILE.get()?.as_ref().ok().map(|(_, config)| config)
+}
+
+fn validate_models(kind: &str, models: &HashMap<String, ModelPricing>) -> Result<()> {
+    for (key, model) in models {
+        let prices = [
+            ("input", Some(model.input)),
+            ("output", Some(model.output)),
+            ("cache_create", Some(model.cache_create)),
+            ("cache_create_1h", model.cache_create_1h),
+            ("cache_read", Some(model.cache_read)),
+        ];
+        for (field, price) in prices {
+            let Some(price) = price else { continue };
+            if !price.is_finite() || price < 0.0 {
+                bail!("{kind} {key}: {field} must be a non-negative number");
+            }
+            if price > MAX_PRICE_PER_TOKEN {
+                bail!(
+                    "{kind} {key}: {field} {price} looks like a per-million price; use USD per token"
+                );
+            }
+        }
+        if model
+            .fast_mode_multiplier
+            .is_some_and(|m| !m.is_finite() || m < 1.0)
+        {
+            bail!("{kind} {key}: fast_mode_multiplier must be at least 1");
+        }
+    }
+    Ok(())
+}
+
+/// Parse a pricing file with the embedded table's schema and check that every
+/// price is a finite, non-negative per-token amount.
+fn parse_pricing_file(text: &str) -> Result<PricingConfig> {
+    let mut config: PricingConfig = serde_json::from_str(text)
+        .context("pricing file does not match the pricing.json schema")?;
+    validate_models("model", &config.models)?;
+    if let Some((name, _)) = config
+        .additional_costs
+        .rates
+        .iter()
+        .find(|(_, rate)| !rate.is_finite() || **rate < 0.0)
+    {
+        bail!("additional_costs.{name} must be a non-negative number");
+    }
+    if let Some((tier, _)) = config
+        .service_tier_multipliers
+        .iter()
+        .find(|(_, m)| !m.is_finite() || **m <= 0.0)
+    {
+        bail!("service_tier_multipliers.{tier} must be a positive number");
+    }
+    for tier in &config.tiered_pricing.tiers {
+        let m = &tier.multipliers;
+        let multipliers = [m.input, m.output, m.cache_create, m.cache_read]
+            .into_iter()
+            .chain(m.cache_create_1h);
+        if multipliers.into_iter().any(|v| !v.is_finite() || v <= 0.0) {
+            bail!(
+                "tiered_pricing tier {}: multipliers must be positive numbers",
+                tier.name
+            );
+        }
+    }
+    for (name, provider) in &mut config.providers {
+        validate_models(&format!("providers.{name}.models"), &provider.models)?;
+        let multipliers = provider
+            .multiplier
+            .iter()
+            .chain(&provider.regional_multiplier)
+            .chain(provider.regions.values());
+        if multipliers.into_iter().any(|m| !m.is_finite() || *m <= 0.0) {
+            bail!("providers.{name}: multipliers must be positive numbers");
+        }
+        provider.models = std::mem::take(&mut provider.models)
+            .into_iter()
+            .map(|(key, model)| (key.to_lowercase(), model))
+            .collect();
+    }
+    // Model ids are matched lowercase.
+    config.models = config
+        .models
+        .into_iter()
+        .map(|(key, model)| (key.to_lowercase(), model))
+        .collect();
+    Ok(config)
+}
+
+/// Lowercased `--free-model` globs.
+static FREE_MODELS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Install the `--free-model` globs for this process, replacing any earlier set.
+pub fn set_free_models(patterns: &[String]) {
+    *FREE_MODELS.write().unwrap_or_else(|e| e.into_inner()) =
+        patterns.iter().map(|p| p.trim().to_lowercase()).collect();
+}
+
+/// Whether a model is on the zero-cost allowlist.
+pub fn is_free_model(model_id: &str) -> bool {
+    let patterns = FREE_MODELS.read().unwrap_or_else(|e| e.into_inner());
+    if patterns.is_empty() {
+        return false;
+    }
+    let m = model_id.to_lowercase();
+    patterns.iter().any(|p| crate::utils::glob_match(p, &m))
+}
+
+/// Markup a paid proxy bills on top of provider pricing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct ProxySurcharge {
+    /// Percentage added to every priced cost
+    pub percent: f64,
+    /// Flat USD per million tokens (input, output, and cache)
+    pub per_mtok: f64,
+}
+
+impl ProxySurcharge {
+    pub fn is_active(&self) -> bool {
+        self.percent != 0.0 || self.per_mtok != 0.0
+    }
+
+    /// `cost` with the markup for `tokens` added; unpriced (zero) costs stay zero.
+    pub fn apply(&self, cost: f64, tokens: u64) -> f64 {
+        if cost <= 0.0 {
+            return cost;
+        }
+        cost * (1.0 + self.percent / 100.0) + tokens as f64 * self.per_mtok / 1_000_000.0
+    }
+}
+
+static PROXY_SURCHARGE: OnceCell<ProxySurcharge> = OnceCell::new();
+
+/// Install the proxy surcharge for this process; negative markups are rejected.
+pub fn set_proxy_surcharge(surcharge: ProxySurcharge) -> Result<()> {
+    for (name, value) in [
+        ("percent", surcharge.percent),
+        ("per_mtok", surcharge.per_mtok),
+    ] {
+        if !value.is_finite() || value < 0.0 {
+            bail!("proxy surcharge {name} must be a non-negative number, got {value}");
+        }
+    }
+    let _ = PROXY_SURCHARGE.set(surcharge);
+    Ok(())
+}
+
+/// Configured proxy surcharge, when one is set.
+pub fn proxy_surcharge() -> Option<ProxySurcharge> {
+    PROXY_SURCHARGE
+        .get()
+        .copied()
+        .filter(ProxySurcharge::is_active)
+}
+
+/// The process-wide pricing inputs that change computed costs (pricing file,
+/// free models, proxy surcharge, project overrides, LiteLLM table), as one
+/// string. Costs cached across processes are keyed on it, so a render with
+/// other pricing flags never reuses them.
+pub fn pricing_fingerprint() -> String {
+    let file = match pricing_file_status() {
+        Some(Ok(path)) => path.display().to_string(),
+        _ => String::new(),
+    };
+    let free = FREE_MODELS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .join(",");
+    let surcharge = proxy_surcharge()
+        .map(|s| format!("{}+{}", s.percent, s.per_mtok))
+        .unwrap_or_default();
+    let projects = PROJECT_PRICING
+        .get()
+        .map(|overrides| {
+            overrides
+                .iter()
+                .map(|p| format!("{}={}:{}", p.pattern, p.multiplier, p.models.len()))
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .unwrap_or_default();
+    let litellm = LITELLM_ENABLED.load(Ordering::Relaxed);
+    format!("file={file};free={free};surcharge={surcharge};projects={projects};litellm={litellm}")
+}
+
+/// Add the proxy surcharge to an already-priced (or transcript-recorded) cost.
+pub fn apply_proxy_surcharge(cost: f64, tokens: u64) -> f64 {
+    proxy_surcharge().map_or(cost, |s| s.apply(cost, tokens))
+}
+
+/// Tokens billed for a usage object, including advisor iterations.
+pub fn usage_tokens(usage: &Value) -> u64 {
+    let cache_create = usage_u64(usage, "cache_creation_input_tokens").max(
+        usage_nested_u64(usage, "cache_creation", "ephemeral_1h_input_tokens")
+            + usage_nested_u64(usage, "cache_creation", "ephemeral_5m_input_tokens"),
+    );
+    let advisor = usage
+        .get("iterations")
+        .and_then(|v| v.as_array())
+        .map_or(0, |iterations| {
+            iterations
+                .iter()
+                .filter(|it| it.get("type").and_then(|s| s.as_str()) == Some("advisor_message"))
+                .map(usage_tokens)
+                .sum()
+        });
+    usage_u64(usage, "input_tokens")
+        + usage_u64(usage, "output_tokens")
+        + cache_create
+        + usage_u64(usage, "cache_read_input_tokens")
+        + advisor
+}
+
+/// Per-project pricing override from the config `[project_pricing]` section.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProjectRates {
+    /// Scale the project's costs (negotiated discount, proxy surcharge)
+    Multiplier(f64),
+    /// Pricing file in the pricing.json schema, consulted before global pricing
+    File(PathBuf),
+}
+
+/// Loaded override for projects matching `pattern`.
+struct ProjectPricing {
+    pattern: String,
+    multiplier: f64,
+    models: HashMap<String, ModelPricing>,
+}
+
+static PROJECT_PRICING: OnceCell<Vec<ProjectPricing>> = OnceCell::new();
+
+/// Install the `[project_pricing]` overrides for this process. Entries whose
+/// pricing file fails to load are skipped and reported in the error.
+pub fn set_project_pricing(overrides: &[(String, ProjectRates)]) -> Result<()> {
+    let mut loaded = Vec::new();
+    let mut errors = Vec::new();
+    for (dir, rates) in overrides {
+        let pattern = crate::utils::project_dir_pattern(dir);
+        match rates {
+            ProjectRates::Multiplier(multiplier) => loaded.push(ProjectPricing {
+                pattern,
+                multiplier: *multiplier,
+                models: HashMap::new(),
+            }),
+            ProjectRates::File(path) => {
+                match std::fs::read_to_string(path)
+                    .with_context(|| format!("failed to read {}", path.display()))
+                    .and_then(|text| parse_pricing_file(&text))
+                {
+                    Ok(config) => loaded.push(ProjectPricing {
+                        pattern,
+                        multiplier: 1.0,
+                        models: config.models,
+                    }),
+                    Err(e) => errors.push(format!("{dir}: {e:#}")),
+                }
+            }
+        }
+    }
+    let _ = PROJECT_PRICING.set(loaded);
+    if !errors.is_empty() {
+        bail!("{}", errors.join("; "));
+    }
+    Ok(())
+}
+
+/// First override matching a sanitized project name, like `[tags]`.
+fn project_pricing(project: &str) -> Option<&'static ProjectPricing> {
+    PROJECT_PRICING
+        .get()?
+        .iter()
+        .find(|p| crate::utils::glob_match(&p.pattern, project))
+}
+
+/// Multiplier for costs recorded in `project` (1.0 without an override).
+pub fn project_cost_multiplier(project: Option<&str>) -> f64 {
+    project
+        .and_then(project_pricing)
+        .map_or(1.0, |p| p.multiplier)
+}
+
+/// Cost of a usage object from `project`, using that project's pricing
+/// override when one is configured.
+pub fn calculate_project_cost(
+    project: Option<&str>,
+    model_id: &str,
+    usage: &Value,
+    speed_override: Option<&str>,
+) -> f64 {
+    match project.and_then(project_pricing) {
+        Some(over) => project_cost(over, model_id, usage, speed_override),
+        None => calculate_cost_for_usage_with_speed(model_id, usage, speed_override),
+    }
+}
+
+fn project_cost(
+    over: &ProjectPricing,
+    model_id: &str,
+    usage: &Value,
+    speed_override: Option<&str>,
+) -> f64 {
+    let cost = cost_with_models(Some(&over.models), model_id, usage, speed_override);
+    apply_proxy_surcharge(cost * over.multiplier, usage_tokens(usage))
+}
+
+/// Community-maintained price table covering far more models than pricing.json.
+const LITELLM_ENDPOINT: &str =
+    "https://raw.githubusercontent.com/BerriAI/litellm/main/model_prices_and_context_window.json";
+const LITELLM_CACHE_KEY: &str = "litellm_pricing";
+const LITELLM_NEGATIVE_CACHE_KEY: &str = "litellm_pricing_negative";
+const LITELLM_CACHE_TTL_SECONDS: i64 = 24 * 3600;
+const LITELLM_NEGATIVE_CACHE_TTL_SECONDS: i64 = 3600;
+
+/// LiteLLM per-token prices by lowercased model id:
+/// `[input, output, cache_create, cache_create_1h, cache_read]`.
+type LitellmTable = HashMap<String, [f64; 5]>;
+
+static LITELLM_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The cached table, parsed on the first lookup that reaches it
+static LITELLM_PRICING: OnceCell<Option<LitellmTable>> = OnceCell::new();
+
+/// Download still running when the statusline was printed
+static LITELLM_REFRESH: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+/// Price otherwise unknown models from the LiteLLM table cached in the db.
+/// A copy older than a day is re-downloaded on a background thread, so the
+/// render never waits on it; lookups meanwhile use the expired copy (see
+/// [`finish_background`]).
+pub fn enable_litellm_pricing() {
+    LITELLM_ENABLED.store(true, Ordering::Relaxed);
+    let handle = std::thread::spawn(refresh_litellm_table);
+    *LITELLM_REFRESH.lock().unwrap_or_else(|e| e.into_inner()) = Some(handle);
+}
+
+/// Wait for a LiteLLM download started by [`enable_litellm_pricing`] so the
+/// table reaches the cache for the next render. Call after the statusline has
+/// been printed and stdout released ([`crate::utils::release_stdout`]).
+pub fn finish_background() {
+    let handle = LITELLM_REFRESH
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+    if let Some(handle) = handle {
+        let _ = handle.join();
+    }
+}
+
+/// Load the LiteLLM price table, downloading it first when the cached copy is
+/// older than a day. Returns the number of priced models, or `None` when no
+/// table is available (a failed download falls back to the expired copy and
+/// is not retried for an hour).
+pub fn load_litellm_pricing() -> Option<usize> {
+    refresh_litellm_table();
+    LITELLM_ENABLED.store(true, Ordering::Relaxed);
+    litellm_table().map(HashMap::len)
+}
+
+fn litellm_table() -> Option<&'static LitellmTable> {
+    if !LITELLM_ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    LITELLM_PRICING
+        .get_or_init(|| {
+            crate::db::get_stale_api_cache(LITELLM_CACHE_KEY)
+                .ok()
+                .flatten()
+                .and_then(|s| serde_json::from_str::<LitellmTable>(&s).ok())
+        })
+        .as_ref()
+}
+
+/// Download the table into the db cache unless the cached copy is current or
+/// a download failed within the hour.
+fn refresh_litellm_table() {
+    if let Ok(Some(_)) = crate::db::get_api_cache(LITELLM_CACHE_KEY) {
+        return;
+    }
+    if let Ok(Some(_)) = crate::db::get_api_cache(LITELLM_NEGATIVE_CACHE_KEY) {
+        return;
+    }
+    match fetch_litellm_table().and_then(|table| serde_json::to_string(&table).ok()) {
+        Some(data) => {
+            let _ = crate::db::set_api_cache(LITELLM_CACHE_KEY, &data, LITELLM_CACHE_TTL_SECONDS);
+        }
+        None => {
+            let _ = crate::db::set_api_cache(
+                LITELLM_NEGATIVE_CACHE_KEY,
+                "1",
+                LITELLM_NEGATIVE_CACHE_TTL_SECONDS,
+            );
+        }
+    }
+}
+
+fn fetch_litellm_table() -> Option<LitellmTable> {
+    let config = ureq::Agent::config_builder().timeout_global(Some(Duration::from_secs(5)));
+    let agent: ureq::Agent = config.build().into();
+    let mut response = agent.get(LITELLM_ENDPOINT).call().ok()?;
+    let body: Value = response
+        .body_mut()
+        .with_config()
+        .limit(16 * 1024 * 1024)
+        .read_json()
+        .ok()?;
+    parse_litellm_table(&body)
+}
+
+/// Keep only models with sane per-token prices; cache prices default to the
+/// input price when LiteLLM doesn't list them.
+fn parse_litellm_table(body: &Value) -> Option<LitellmTable> {
+    let price = |entry: &Value, key: &str| {
+        entry
+            .get(key)
+            .and_then(Value::as_f64)
+            .filter(|p| p.is_finite() && (0.0..=MAX_PRICE_PER_TOKEN).contains(p))
+    };
+    let table: LitellmTable = body
+        .as_object()?
+        .iter()
+        .filter_map(|(model, entry)| {
+            let input = price(entry, "input_cost_per_token")?;
+            let output = price(entry, "output_cost_per_token")?;
+            let cache_create = price(entry, "cache_creation_input_token_cost").unwrap_or(input);
+            let cache_create_1h =
+                price(entry, "cache_creation_input_token_cost_above_1hr").unwrap_or(cache_create);
+            let cache_read = price(entry, "cache_read_input_token_cost").unwrap_or(input);
+            Some((
+                model.to_lowercase(),
+                [input, output, cache_create, cache_create_1h, cache_read],
+            ))
+        })
+        .collect();
+    (!table.is_empty()).then_some(table)
+}
+
+/// Get pricing from the LiteLLM table, also trying the id without a routing
+/// prefix (`openrouter/anthropic/claude-...` -> `claude-...`).
+fn pricing_from_litellm(model_id: &str) -> Option<Pricing> {
+    let table = litellm_table()?;
+    let [input, output, cache_create, cache_create_1h, cache_read] = table
+        .get(model_id)
+        .or_else(|| {
+            model_id
+                .rsplit_once('/')
+                .and_then(|(_, tail)| table.get(tail))
+        })
+        .copied()?;
+    Some(Pricing::new(
+        input,
+        output,
+        cache_create,
+        cache_create_1h,
+        cache_read,
+    ))
+}
+
+/// Get pricing from the runtime pricing file
+fn pricing_from_file(model_id: &str) -> Option<Pricing> {
+    pricing_from(&file_config()?.models, model_id)
+}
+
 /// Get pricing from embedded config
 fn pricing_from_config(model_id: &str) -> Option<Pricing> {
-    let config = PRICING_CONFIG.as_ref()?;
+    pricing_from(&PRICING_CONFIG.as_ref()?.models, model_id)
+}
+
+fn pricing_from(models: &HashMap<String, ModelPricing>, model_id: &str) -> Option<Pricing> {
     let m = model_id.to_lowercase();
 
     // Try exact match first
-    if let Some(model_pricing) = config.models.get(&m) {
+    if let Some(model_pricing) = models.get(&m) {
         return Some(Pricing::from_model_pricing(model_pricing));
     }
 
     // Try canonical model names before partial matching. Order matters: more
     // specific 4.x variants must win before their family prefix.
     if let Some(key) = canonical_pricing_key(&m) {
-        if let Some(model_pricing) = config.models.get(key) {
+        if let Some(model_pricing) = models.get(key) {
             return Some(Pricing::from_model_pricing(model_pricing));
         }
     }
 
     // Try provider/date suffix matches and prefer the most specific key.
-    if let Some((_, model_pricing)) = config
-        .models
+    if let Some((_, model_pricing)) = models
         .iter()
         .filter(|(key, _)| m.contains(key.as_str()))
         .max_by_key(|(key, _)| key.len())
@@ -172,7 +827,7 @@ fn pricing_from_config(model_id: &str) -> Option<Pricing> {
     }
 
     // Try short user-provided fragments such as "opus-4-6".
-    for (key, model_pricing) in &config.models {
+    for (key, model_pricing) in models {
         if key.contains(&m) {
             return Some(Pricing::from_model_pricing(model_pricing));
         }
@@ -210,6 +865,57 @@ pub(crate) fn static_pricing_lookup(model_id: &str) -> Option<Pricing> {
     None
 }
 
+/// List prices per MTok `(input, output, cached input)` for non-Anthropic
+/// families seen when Claude Code is routed through LiteLLM or OpenRouter.
+/// Matched as prefixes of the id after any `provider/` routing prefix, so
+/// more specific variants come first.
+const PROVIDER_FAMILY_PRICES: &[(&str, f64, f64, f64)] = &[
+    ("gpt-5-nano", 0.05, 0.4, 0.005),
+    ("gpt-5-mini", 0.25, 2.0, 0.025),
+    ("gpt-5-pro", 15.0, 120.0, 15.0),
+    ("gpt-5", 1.25, 10.0, 0.125),
+    ("gpt-4.1-nano", 0.1, 0.4, 0.025),
+    ("gpt-4.1-mini", 0.4, 1.6, 0.1),
+    ("gpt-4.1", 2.0, 8.0, 0.5),
+    ("gpt-4o-mini", 0.15, 0.6, 0.075),
+    ("gpt-4o", 2.5, 10.0, 1.25),
+    ("o4-mini", 1.1, 4.4, 0.275),
+    ("o3-mini", 1.1, 4.4, 0.55),
+    ("o3-pro", 20.0, 80.0, 20.0),
+    ("o3", 2.0, 8.0, 0.5),
+    ("o1-mini", 1.1, 4.4, 0.55),
+    ("o1-pro", 150.0, 600.0, 150.0),
+    ("o1", 15.0, 60.0, 7.5),
+    ("gemini-2.5-flash-lite", 0.1, 0.4, 0.025),
+    ("gemini-2.5-flash", 0.3, 2.5, 0.075),
+    ("gemini-2.5-pro", 1.25, 10.0, 0.3125),
+    ("gemini-2.0-flash", 0.1, 0.4, 0.025),
+    ("deepseek-reasoner", 0.55, 2.19, 0.14),
+    ("deepseek-r1", 0.55, 2.19, 0.14),
+    ("deepseek", 0.27, 1.1, 0.07),
+    ("grok-3-mini", 0.3, 0.5, 0.075),
+    ("grok-4", 3.0, 15.0, 0.75),
+    ("grok-3", 3.0, 15.0, 0.75),
+    ("mistral-large", 2.0, 6.0, 2.0),
+    ("codestral", 0.3, 0.9, 0.3),
+    ("kimi-k2", 0.6, 2.5, 0.15),
+    ("qwen3-coder", 0.45, 1.8, 0.45),
+    ("glm-4.5", 0.6, 2.2, 0.11),
+];
+
+/// Pricing for a non-Anthropic model family. These providers cache
+/// implicitly, so cache writes are billed at the input price.
+fn provider_family_pricing(model_id: &str) -> Option<Pricing> {
+    let name = model_id.rsplit('/').next().unwrap_or(model_id);
+    PROVIDER_FAMILY_PRICES
+        .iter()
+        .find(|(prefix, ..)| name.starts_with(prefix))
+        .map(|&(_, input, output, cached)| {
+            let (input, output, cached) = (input * 1e-6, output * 1e-6, cached * 1e-6);
+            Pricing::new(input, output, input, input, cached)
+        })
+}
+
 fn is_deprecated_or_retired_model(model_id: &str) -> bool {
     let m = model_id.to_lowercase();
     if m.contains("claude-instant") {
@@ -258,29 +964,105 @@ fn env_pricing_override() -> Option<Pricing> {
     None
 }
 
+/// Region Claude Code was configured for, from the provider SDK environment.
+static ENV_REGIONS: Lazy<(Option<String>, Option<String>)> = Lazy::new(|| {
+    let var = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+    (
+        var("AWS_REGION").or_else(|| var("AWS_DEFAULT_REGION")),
+        var("CLOUD_ML_REGION"),
+    )
+});
+
+/// Cloud provider serving a model id and the region it ran in, when known.
+/// Bedrock ids carry their inference profile geography (`eu.anthropic...`)
+/// or an ARN region; otherwise the SDK environment decides.
+fn model_provider_region(model_id: &str) -> Option<(&'static str, Option<String>)> {
+    let truthy = |name: &str| env::var(name).is_ok_and(|v| v == "1" || v == "true");
+    let provider = match crate::utils::deduce_provider_from_model(model_id) {
+        "anthropic" if truthy("CLAUDE_CODE_USE_BEDROCK") => "bedrock",
+        "anthropic" if truthy("CLAUDE_CODE_USE_VERTEX") => "vertex",
+        "anthropic" => return None,
+        other => other,
+    };
+    let region = match provider {
+        "bedrock" => {
+            if let Some(arn) = model_id.strip_prefix("arn:") {
+                arn.split(':').nth(2).map(str::to_string)
+            } else if let Some((geo, _)) = model_id.split_once(".anthropic.") {
+                Some(geo.to_string())
+            } else {
+                ENV_REGIONS.0.clone()
+            }
+        }
+        "vertex" => ENV_REGIONS.1.clone(),
+        _ => None,
+    };
+    Some((provider, region))
+}
+
+/// Provider adjustments from the pricing file, else pricing.json.
+fn provider_pricing(provider: &str) -> Option<(&'static ProviderPricing, PricingSource)> {
+    file_config()
+        .and_then(|c| c.providers.get(provider))
+        .map(|p| (p, PricingSource::File))
+        .or_else(|| {
+            PRICING_CONFIG
+                .as_ref()
+                .and_then(|c| c.providers.get(provider))
+                .map(|p| (p, PricingSource::Embedded))
+        })
+}
+
 pub fn pricing_for_model(model_id: &str) -> Option<Pricing> {
     let m = model_id.to_lowercase();
 
+    if is_free_model(&m) {
+        return Some(Pricing::new(0.0, 0.0, 0.0, 0.0, 0.0));
+    }
+
     // Priority 1: Environment variable overrides (when all four are provided)
     if let Some(p) = env_pricing_override() {
         return Some(p);
     }
 
-    // Priority 2: Embedded pricing.json config
-    if let Some(p) = pricing_from_config(&m) {
+    let Some((provider, region)) = model_provider_region(&m) else {
+        return list_pricing_for_model(&m);
+    };
+    let Some((adjustments, _)) = provider_pricing(provider) else {
+        return list_pricing_for_model(&m);
+    };
+    let base = pricing_from(&adjustments.models, &m).or_else(|| list_pricing_for_model(&m))?;
+    Some(base.scaled(adjustments.multiplier_for(region.as_deref())))
+}
+
+/// Anthropic list pricing (or a provider-family estimate) before provider
+/// adjustments.
+fn list_pricing_for_model(m: &str) -> Option<Pricing> {
+    // Priority 2: Runtime pricing file
+    if let Some(p) = pricing_from_file(m) {
         return Some(p);
     }
 
-    // Priority 3: Built-in static pricing
-    if let Some(p) = static_pricing_lookup(&m) {
+    // Priority 3: Embedded pricing.json config
+    if let Some(p) = pricing_from_config(m) {
         return Some(p);
     }
 
-    if is_deprecated_or_retired_model(&m) {
+    // Priority 4: Built-in static pricing
+    if let Some(p) = static_pricing_lookup(m) {
+        return Some(p);
+    }
+
+    // Priority 5: LiteLLM price database (opt-in)
+    if let Some(p) = pricing_from_litellm(m) {
+        return Some(p);
+    }
+
+    if is_deprecated_or_retired_model(m) {
         return None;
     }
 
-    // Priority 4: Family heuristics fallback
+    // Priority 6: Family heuristics fallback
     if m.contains("fable") || m.contains("mythos") {
         let in_pt = 10e-6; // $10 / 1M
         Some(Pricing::from_input_multipliers(in_pt, 50e-6))
@@ -294,22 +1076,37 @@ pub fn pricing_for_model(model_id: &str) -> Option<Pricing> {
         let in_pt = 1e-6; // $1 / 1M
         Some(Pricing::from_input_multipliers(in_pt, 5e-6))
     } else {
-        None
+        provider_family_pricing(m)
     }
 }
 
 pub fn pricing_source_for_model(model_id: &str) -> PricingSource {
     let m = model_id.to_lowercase();
 
+    if is_free_model(&m) {
+        return PricingSource::Free;
+    }
     if env_pricing_override().is_some() {
         return PricingSource::EnvOverride;
     }
+    if let Some((provider, _)) = model_provider_region(&m)
+        && let Some((adjustments, source)) = provider_pricing(provider)
+        && pricing_from(&adjustments.models, &m).is_some()
+    {
+        return source;
+    }
+    if pricing_from_file(&m).is_some() {
+        return PricingSource::File;
+    }
     if pricing_from_config(&m).is_some() {
         return PricingSource::Embedded;
     }
     if static_pricing_lookup(&m).is_some() {
         return PricingSource::StaticFallback;
     }
+    if pricing_from_litellm(&m).is_some() {
+        return PricingSource::Litellm;
+    }
     if is_deprecated_or_retired_model(&m) {
         return PricingSource::Unavailable;
     }
@@ -318,6 +1115,7 @@ pub fn pricing_source_for_model(model_id: &str) -> PricingSource {
         || m.contains("haiku")
         || m.contains("fable")
         || m.contains("mythos")
+        || provider_family_pricing(&m).is_some()
     {
         return PricingSource::FamilyHeuristic;
     }
@@ -327,11 +1125,20 @@ pub fn pricing_source_for_model(model_id: &str) -> PricingSource {
 /// Get the fast mode multiplier for a model (e.g. 6x for Opus 4.6).
 /// Returns 1.0 if the model has no fast mode pricing.
 pub fn fast_mode_multiplier(model_id: &str) -> f64 {
+    let m = model_id.to_lowercase();
+    if let Some(multiplier) = file_config().and_then(|config| {
+        config
+            .models
+            .get(&m)
+            .or_else(|| canonical_pricing_key(&m).and_then(|key| config.models.get(key)))
+            .and_then(|mp| mp.fast_mode_multiplier)
+    }) {
+        return multiplier;
+    }
     let config = match PRICING_CONFIG.as_ref() {
         Some(c) => c,
         None => return 1.0,
     };
-    let m = model_id.to_lowercase();
     // Exact match: return its multiplier (or 1.0 if model exists but has no fast mode)
     if let Some(mp) = config.models.get(&m) {
         return mp.fast_mode_multiplier.unwrap_or(1.0);
@@ -391,16 +1198,97 @@ fn usage_speed<'a>(usage: &'a Value, speed_override: Option<&'a str>) -> Option<
         .or(speed_override)
 }
 
-fn web_search_per_request() -> f64 {
-    PRICING_CONFIG
-        .as_ref()
-        .map(|c| c.additional_costs.web_search_per_request)
-        .filter(|v| *v > 0.0)
-        .unwrap_or(0.01)
+/// Per-request rate for a server tool. Web search falls back to its published
+/// $10/1k price; tools without a configured rate cost nothing beyond tokens.
+fn server_tool_rate(tool: &str) -> f64 {
+    let key = format!("{tool}_per_request");
+    file_config()
+        .and_then(|c| c.additional_costs.rates.get(&key))
+        .or_else(|| {
+            PRICING_CONFIG
+                .as_ref()
+                .and_then(|c| c.additional_costs.rates.get(&key))
+        })
+        .copied()
+        .filter(|v| *v >= 0.0)
+        .unwrap_or(if tool == "web_search" { 0.01 } else { 0.0 })
+}
+
+/// One server tool's charge within a usage total
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerToolCost {
+    pub tool: String,
+    pub requests: u64,
+    pub cost_usd: f64,
+}
+
+/// Line items for each server tool used, in tool-name order.
+pub fn server_tool_costs(tools: &ServerToolUse) -> Vec<ServerToolCost> {
+    tools
+        .iter()
+        .map(|(tool, requests)| ServerToolCost {
+            tool: tool.to_string(),
+            requests,
+            cost_usd: requests as f64 * server_tool_rate(tool),
+        })
+        .collect()
+}
+
+/// Combined server tool charge; never affected by fast mode.
+pub fn server_tool_cost(tools: &ServerToolUse) -> f64 {
+    server_tool_costs(tools).iter().map(|c| c.cost_usd).sum()
+}
+
+/// Token price multiplier for Message Batches API requests.
+pub const BATCH_DISCOUNT: f64 = 0.5;
+
+/// Token price multiplier for a usage entry's `service_tier`, from
+/// `service_tier_multipliers` in the pricing file or pricing.json. Unlisted
+/// tiers (and `standard`) are billed at list price.
+pub fn service_tier_multiplier(service_tier: Option<&str>) -> f64 {
+    let Some(tier) = service_tier else {
+        return 1.0;
+    };
+    file_config()
+        .and_then(|c| c.service_tier_multipliers.get(tier))
+        .or_else(|| {
+            PRICING_CONFIG
+                .as_ref()
+                .and_then(|c| c.service_tier_multipliers.get(tier))
+        })
+        .copied()
+        .unwrap_or(if tier == "batch" { BATCH_DISCOUNT } else { 1.0 })
 }
 
-fn flat_cost_for_usage(model_id: &str, usage: &Value, speed_override: Option<&str>) -> f64 {
-    let Some(base_p) = pricing_for_model(model_id) else {
+/// How much a tier premium or discount changed an entry's cost: `cost` minus
+/// what the same usage costs at list price. Server tool charges are not
+/// tier-adjusted.
+pub fn service_tier_adjustment(
+    cost: f64,
+    server_tools: &ServerToolUse,
+    service_tier: Option<&str>,
+) -> f64 {
+    let multiplier = service_tier_multiplier(service_tier);
+    if multiplier == 1.0 {
+        return 0.0;
+    }
+    let token_cost = (cost - server_tool_cost(server_tools)).max(0.0);
+    token_cost - token_cost / multiplier
+}
+
+fn flat_cost_for_usage(
+    models: Option<&HashMap<String, ModelPricing>>,
+    model_id: &str,
+    usage: &Value,
+    speed_override: Option<&str>,
+) -> f64 {
+    if is_free_model(model_id) {
+        return 0.0;
+    }
+    let Some(base_p) = models
+        .and_then(|models| pricing_from(models, model_id))
+        .or_else(|| pricing_for_model(model_id))
+    else {
         return 0.0;
     };
 
@@ -412,11 +1300,6 @@ fn flat_cost_for_usage(model_id: &str, usage: &Value, speed_override: Option<&st
             + usage_nested_u64(usage, "cache_creation", "ephemeral_5m_input_tokens");
     let cache_create_effective = cache_create.max(cache_create_nested);
     let cache_read = usage_u64(usage, "cache_read_input_tokens");
-    let web_search_requests = usage
-        .get("server_tool_use")
-        .and_then(|o| o.get("web_search_requests"))
-        .and_then(|n| n.as_u64())
-        .unwrap_or(0);
 
     let p = apply_tiered_pricing(
         base_p,
@@ -427,15 +1310,18 @@ fn flat_cost_for_usage(model_id: &str, usage: &Value, speed_override: Option<&st
         + (output as f64) * p.out_per_tok
         + (cache_create_effective as f64) * p.cache_create_per_tok
         + (cache_read as f64) * p.cache_read_per_tok;
-    let web_search_cost = (web_search_requests as f64) * web_search_per_request();
+    let tool_cost = server_tool_cost(&ServerToolUse::from_usage(usage));
 
-    let token_multiplier = if usage_speed(usage, speed_override) == Some("fast") {
+    let speed_multiplier = if usage_speed(usage, speed_override) == Some("fast") {
         fast_mode_multiplier(model_id)
     } else {
         1.0
     };
+    // Service tiers adjust tokens only; server tools stay per-request.
+    let token_multiplier = speed_multiplier
+        * service_tier_multiplier(usage.get("service_tier").and_then(|s| s.as_str()));
 
-    token_cost * token_multiplier + web_search_cost
+    token_cost * token_multiplier + tool_cost
 }
 
 /// Calculate Claude Code-compatible cost for a usage object.
@@ -443,18 +1329,32 @@ fn flat_cost_for_usage(model_id: &str, usage: &Value, speed_override: Option<&st
 /// Mirrors `calculateUSDCost` plus `addToTotalSessionCost` in Claude Code:
 /// token/cache costs are model-priced, cache creation is charged from aggregate
 /// `cache_creation_input_tokens`, Opus 4.6 fast mode affects token/cache costs
-/// only, web search remains a flat per-request charge, and advisor iteration
-/// usage is charged recursively under its own model.
+/// only, batch and other listed service tiers scale token prices by their
+/// multiplier, server tools (web search) remain flat per-request charges, and
+/// advisor iteration usage is charged recursively under its own model.
 pub fn calculate_cost_for_usage(model_id: &str, usage: &Value) -> f64 {
     calculate_cost_for_usage_with_speed(model_id, usage, None)
 }
 
 /// Calculate cost for usage, using `speed_override` when the transcript stores
-/// speed on the enclosing line instead of inside `message.usage`.
+/// speed on the enclosing line instead of inside `message.usage`. Any proxy
+/// surcharge is added on top.
 pub fn calculate_cost_for_usage_with_speed(
     model_id: &str,
     usage: &Value,
     speed_override: Option<&str>,
+) -> f64 {
+    let cost = cost_with_models(None, model_id, usage, speed_override);
+    apply_proxy_surcharge(cost, usage_tokens(usage))
+}
+
+/// Usage cost, pricing models from `models` (a project override) before the
+/// global resolution order.
+fn cost_with_models(
+    models: Option<&HashMap<String, ModelPricing>>,
+    model_id: &str,
+    usage: &Value,
+    speed_override: Option<&str>,
 ) -> f64 {
     let advisor_cost = usage
         .get("iterations")
@@ -465,71 +1365,54 @@ pub fn calculate_cost_for_usage_with_speed(
                 .filter(|it| it.get("type").and_then(|s| s.as_str()) == Some("advisor_message"))
                 .filter_map(|it| {
                     let model = it.get("model").and_then(|s| s.as_str())?;
-                    Some(calculate_cost_for_usage(model, it))
+                    Some(cost_with_models(models, model, it, None))
                 })
                 .sum::<f64>()
         })
         .unwrap_or(0.0);
 
-    flat_cost_for_usage(model_id, usage, speed_override) + advisor_cost
+    flat_cost_for_usage(models, model_id, usage, speed_override) + advisor_cost
 }
 
-/// Apply tiered pricing multipliers if applicable based on token count
-/// Returns modified pricing if a tier applies, otherwise returns the input pricing unchanged
+/// Apply tiered pricing multipliers if applicable based on token count.
+/// Tiers come from the runtime pricing file, then pricing.json; returns the
+/// input pricing unchanged when no tier applies.
 pub fn apply_tiered_pricing(
     base_pricing: Pricing,
     model_id: &str,
     total_input_tokens: u64,
 ) -> Pricing {
-    // Check if tiered pricing is enabled and configured
-    let config = match PRICING_CONFIG.as_ref() {
-        Some(c) if c.tiered_pricing.enabled => c,
-        _ => return base_pricing,
-    };
-
-    let model_lower = model_id.to_lowercase();
-
-    // Find applicable tier
-    for tier in &config.tiered_pricing.tiers {
-        // Check if this tier applies using exact or date-suffix matching.
-        let applies = tier.applies_to.iter().any(|pattern| {
-            let p = pattern.to_lowercase();
-            if model_lower == p {
-                return true;
-            }
-            // Only match date suffixes like -20250514 (8+ digits), not version suffixes like -5, -6
-            if let Some(suffix) = model_lower.strip_prefix(&p) {
-                if let Some(rest) = suffix.strip_prefix('-') {
-                    return rest.len() >= 8 && rest.chars().all(|c| c.is_ascii_digit());
-                }
-            }
-            false
-        });
+    let model = tier_model_name(model_id);
+    [file_config(), PRICING_CONFIG.as_ref()]
+        .into_iter()
+        .flatten()
+        .find_map(|config| config.tiered_pricing.tier_for(&model, total_input_tokens))
+        .map_or(base_pricing, |tier| tier.apply(base_pricing))
+}
 
-        if applies && total_input_tokens > tier.threshold {
-            // Apply multipliers
-            return Pricing {
-                in_per_tok: base_pricing.in_per_tok * tier.multipliers.input,
-                out_per_tok: base_pricing.out_per_tok * tier.multipliers.output,
-                cache_create_per_tok: base_pricing.cache_create_per_tok
-                    * tier.multipliers.cache_create,
-                cache_create_1h_per_tok: base_pricing.cache_create_1h_per_tok
-                    * tier
-                        .multipliers
-                        .cache_create_1h
-                        .unwrap_or(tier.multipliers.cache_create),
-                cache_read_per_tok: base_pricing.cache_read_per_tok * tier.multipliers.cache_read,
-            };
+/// Model id without provider decoration, so tiers written for Anthropic ids
+/// also match `us.anthropic.<id>-v1:0`, `<id>@<date>`, and `vendor/<id>`.
+fn tier_model_name(model_id: &str) -> String {
+    let m = model_id.to_lowercase();
+    let m = m.rsplit('/').next().unwrap_or(&m);
+    let m = m.rsplit_once("anthropic.").map_or(m, |(_, id)| id);
+    let m = m.split('@').next().unwrap_or(m);
+    // Bedrock version suffix: -v1 or -v1:0
+    let m = match m.rsplit_once("-v") {
+        Some((id, version))
+            if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit() || c == ':') =>
+        {
+            id
         }
-    }
-
-    // No tier applies, return base pricing
-    base_pricing
+        _ => m,
+    };
+    m.to_string()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serial_test::serial;
 
     #[test]
     fn test_pricing_for_known_models() {
@@ -641,13 +1524,14 @@ mod tests {
 
     #[test]
     fn test_provider_model_uses_specific_pricing() {
-        let p = pricing_for_model("us.anthropic.claude-opus-4-8").unwrap();
+        let p = pricing_for_model("global.anthropic.claude-opus-4-8").unwrap();
         assert!((p.in_per_tok - 5e-6).abs() < 1e-10);
         assert!((p.out_per_tok - 25e-6).abs() < 1e-10);
 
+        // Regional inference profiles pay the shipped 10% premium
         let p = pricing_for_model("us.anthropic.claude-opus-4-6-v1").unwrap();
-        assert!((p.in_per_tok - 5e-6).abs() < 1e-10);
-        assert!((p.out_per_tok - 25e-6).abs() < 1e-10);
+        assert!((p.in_per_tok - 5.5e-6).abs() < 1e-10);
+        assert!((p.out_per_tok - 27.5e-6).abs() < 1e-10);
     }
 
     #[test]
@@ -665,6 +1549,52 @@ mod tests {
         assert!((cost - 220.52).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_batch_tier_halves_token_cost_only() {
+        let usage = serde_json::json!({
+            "input_tokens": 1_000_000,
+            "output_tokens": 1_000_000,
+            "server_tool_use": { "web_search_requests": 2 },
+        });
+        let mut batch = usage.clone();
+        batch["service_tier"] = serde_json::json!("batch");
+
+        let standard = calculate_cost_for_usage("claude-sonnet-4-6", &usage);
+        let batched = calculate_cost_for_usage("claude-sonnet-4-6", &batch);
+        assert!((standard - 18.02).abs() < 1e-10);
+        assert!((batched - 9.02).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_server_tool_line_items_price_each_tool() {
+        let usage = serde_json::json!({
+            "server_tool_use": {
+                "web_search_requests": 3,
+                "web_fetch_requests": 2,
+                "code_execution_requests": 0
+            }
+        });
+        let items = server_tool_costs(&ServerToolUse::from_usage(&usage));
+
+        assert_eq!(
+            items,
+            vec![
+                ServerToolCost {
+                    tool: "web_fetch".to_string(),
+                    requests: 2,
+                    cost_usd: 0.0,
+                },
+                ServerToolCost {
+                    tool: "web_search".to_string(),
+                    requests: 3,
+                    cost_usd: 0.03,
+                },
+            ]
+        );
+        let cost = calculate_cost_for_usage("claude-sonnet-4-5", &usage);
+        assert!((cost - 0.03).abs() < 1e-12);
+    }
+
     #[test]
     fn test_cache_creation_1h_uses_cli_cache_write_price() {
         let usage = serde_json::json!({
@@ -748,4 +1678,299 @@ mod tests {
         assert!(pricing_for_model(&deprecated_sonnet).is_none());
         assert!(pricing_for_model(&deprecated_opus).is_none());
     }
+
+    #[test]
+    fn test_pricing_file_validation() {
+        let config = parse_pricing_file(
+            r#"{"models": {"Claude-Opus-9": {"name": "Opus 9", "input": 0.00002, "output": 0.0001,
+                "cache_create": 0.000025, "cache_read": 0.000002}},
+                "additional_costs": {"web_search_per_request": 0.02}}"#,
+        )
+        .unwrap();
+        let pricing = pricing_from(&config.models, "claude-opus-9").unwrap();
+        assert_eq!(pricing.in_per_tok, 0.00002);
+        assert_eq!(pricing.cache_read_per_tok, 0.000002);
+        assert_eq!(
+            config.additional_costs.rates["web_search_per_request"],
+            0.02
+        );
+
+        // Per-million prices are rejected rather than inflating costs a millionfold.
+        let err = parse_pricing_file(
+            r#"{"models": {"x": {"name": "x", "input": 15.0, "output": 75.0, "cache_create": 0, "cache_read": 0}}}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("per-million"));
+        assert!(parse_pricing_file(r#"{"models": {"x": {"name": "x", "input": -1e-6, "output": 0, "cache_create": 0, "cache_read": 0}}}"#).is_err());
+        assert!(parse_pricing_file(r#"{"models": {"x": {"input": 0.0}}}"#).is_err());
+        assert!(parse_pricing_file(r#"{"additional_costs": {}}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_litellm_table() {
+        let body: Value = serde_json::from_str(
+            r#"{
+                "sample_spec": {"input_cost_per_token": "0.0", "output_cost_per_token": "0.0"},
+                "gpt-4o": {"input_cost_per_token": 2.5e-6, "output_cost_per_token": 1e-5,
+                    "cache_read_input_token_cost": 1.25e-6},
+                "Claude-Opus-9": {"input_cost_per_token": 5e-6, "output_cost_per_token": 2.5e-5,
+                    "cache_creation_input_token_cost": 6.25e-6,
+                    "cache_creation_input_token_cost_above_1hr": 1e-5,
+                    "cache_read_input_token_cost": 5e-7},
+                "dall-e-3": {"output_cost_per_image": 0.04},
+                "broken": {"input_cost_per_token": 3.0, "output_cost_per_token": 15.0}
+            }"#,
+        )
+        .unwrap();
+        let table = parse_litellm_table(&body).unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table["gpt-4o"], [2.5e-6, 1e-5, 2.5e-6, 2.5e-6, 1.25e-6]);
+        assert_eq!(table["claude-opus-9"], [5e-6, 2.5e-5, 6.25e-6, 1e-5, 5e-7]);
+        assert!(parse_litellm_table(&serde_json::json!({"sample_spec": {}})).is_none());
+    }
+
+    #[test]
+    fn test_non_anthropic_families_behind_proxies() {
+        let gpt = pricing_for_model("openai/gpt-4o-2024-08-06").unwrap();
+        assert!((gpt.in_per_tok - 2.5e-6).abs() < 1e-15);
+        assert!((gpt.cache_read_per_tok - 1.25e-6).abs() < 1e-15);
+        assert_eq!(gpt.cache_create_per_tok, gpt.in_per_tok);
+
+        let mini = pricing_for_model("gpt-4o-mini").unwrap();
+        assert!((mini.out_per_tok - 0.6e-6).abs() < 1e-15);
+
+        let gemini = pricing_for_model("Gemini-2.5-Pro").unwrap();
+        assert!((gemini.out_per_tok - 10e-6).abs() < 1e-15);
+        assert_eq!(
+            pricing_source_for_model("openrouter/google/gemini-2.5-pro"),
+            PricingSource::FamilyHeuristic
+        );
+
+        let o1_mini = pricing_for_model("o1-mini-2024-09-12").unwrap();
+        assert!((o1_mini.in_per_tok - 1.1e-6).abs() < 1e-15);
+        let o3_pro = pricing_for_model("openai/o3-pro").unwrap();
+        assert!((o3_pro.out_per_tok - 80e-6).abs() < 1e-15);
+
+        assert!(pricing_for_model("llama-3.1-70b").is_none());
+    }
+
+    #[test]
+    fn test_provider_family_prefixes_are_most_specific_first() {
+        for (i, (earlier, ..)) in PROVIDER_FAMILY_PRICES.iter().enumerate() {
+            for (later, ..) in &PROVIDER_FAMILY_PRICES[i + 1..] {
+                assert!(
+                    !later.starts_with(earlier),
+                    "{earlier} shadows {later}; list the longer prefix first"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_service_tier_multipliers_and_adjustment() {
+        assert_eq!(service_tier_multiplier(None), 1.0);
+        assert_eq!(service_tier_multiplier(Some("standard")), 1.0);
+        assert_eq!(service_tier_multiplier(Some("batch")), 0.5);
+        assert_eq!(service_tier_multiplier(Some("priority")), 1.0);
+        assert_eq!(service_tier_multiplier(Some("flex")), 1.0);
+
+        let tools = ServerToolUse::single("web_search", 2);
+        assert_eq!(
+            service_tier_adjustment(10.02, &tools, Some("priority")),
+            0.0
+        );
+        // $10 of tokens at 0.5x plus $0.02 of web search.
+        let savings = service_tier_adjustment(5.02, &tools, Some("batch"));
+        assert!((savings + 5.0).abs() < 1e-10);
+        assert_eq!(
+            service_tier_adjustment(12.52, &tools, Some("standard")),
+            0.0
+        );
+
+        assert!(
+            parse_pricing_file(r#"{"models": {}, "service_tier_multipliers": {"priority": 0}}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_provider_regional_pricing() {
+        assert_eq!(
+            model_provider_region("eu.anthropic.claude-sonnet-4-5-20250929-v1:0"),
+            Some(("bedrock", Some("eu".to_string())))
+        );
+        assert_eq!(
+            model_provider_region("global.anthropic.claude-opus-4-6-v1"),
+            Some(("bedrock", Some("global".to_string())))
+        );
+        assert_eq!(
+            model_provider_region(
+                "arn:aws:bedrock:ap-northeast-1:123456789012:application-inference-profile/abc"
+            ),
+            Some(("bedrock", Some("ap-northeast-1".to_string())))
+        );
+
+        let config = parse_pricing_file(
+            r#"{"models": {}, "providers": {"bedrock": {
+                "regional_multiplier": 1.1,
+                "regions": {"eu": 1.2, "us-gov-west-1": 1.5},
+                "models": {"Claude-Sonnet-4-5": {"name": "Sonnet 4.5 (Bedrock)",
+                    "input": 3.3e-6, "output": 1.65e-5, "cache_create": 4.125e-6,
+                    "cache_read": 3.3e-7}}
+            }}}"#,
+        )
+        .unwrap();
+        let bedrock = &config.providers["bedrock"];
+        assert_eq!(bedrock.multiplier_for(Some("global")), 1.0);
+        assert_eq!(bedrock.multiplier_for(Some("eu")), 1.2);
+        assert_eq!(bedrock.multiplier_for(Some("eu-west-1")), 1.2);
+        assert_eq!(bedrock.multiplier_for(Some("us-gov-west-1")), 1.5);
+        assert_eq!(bedrock.multiplier_for(Some("us")), 1.1);
+        assert_eq!(bedrock.multiplier_for(None), 1.0);
+        let sonnet = pricing_from(
+            &bedrock.models,
+            "us.anthropic.claude-sonnet-4-5-20250929-v1:0",
+        )
+        .unwrap();
+        assert_eq!(sonnet.in_per_tok, 3.3e-6);
+
+        assert!(
+            parse_pricing_file(
+                r#"{"models": {}, "providers": {"vertex": {"regions": {"us-east5": -1}}}}"#
+            )
+            .is_err()
+        );
+
+        // Shipped defaults: regional endpoints carry the 10% premium
+        let embedded = PRICING_CONFIG.as_ref().expect("embedded pricing");
+        for provider in ["bedrock", "vertex"] {
+            let defaults = &embedded.providers[provider];
+            assert_eq!(defaults.multiplier_for(Some("global")), 1.0);
+            assert_eq!(defaults.multiplier_for(Some("us-east5")), 1.1);
+        }
+    }
+
+    #[test]
+    fn test_project_pricing_override() {
+        let usage = serde_json::json!({ "input_tokens": 1_000_000, "output_tokens": 1_000_000 });
+        let surcharge = ProjectPricing {
+            pattern: "-work-acme*".to_string(),
+            multiplier: 1.1,
+            models: HashMap::new(),
+        };
+        let cost = project_cost(&surcharge, "claude-sonnet-4-6", &usage, None);
+        assert!((cost - 19.8).abs() < 1e-10);
+
+        let negotiated = ProjectPricing {
+            pattern: "-work-client".to_string(),
+            multiplier: 1.0,
+            models: parse_pricing_file(
+                r#"{"models": {"claude-sonnet-4-6": {"name": "Sonnet 4.6 (contract)",
+                    "input": 2e-6, "output": 1e-5, "cache_create": 2.5e-6, "cache_read": 2e-7}}}"#,
+            )
+            .unwrap()
+            .models,
+        };
+        let cost = project_cost(&negotiated, "claude-sonnet-4-6", &usage, None);
+        assert!((cost - 12.0).abs() < 1e-10);
+        // Models missing from the project file fall back to global pricing.
+        let cost = project_cost(&negotiated, "claude-haiku-4-5", &usage, None);
+        assert!((cost - 6.0).abs() < 1e-10);
+    }
+
+    #[test]
+    #[serial]
+    fn test_free_models_cost_nothing() {
+        set_free_models(&["Ollama/*".to_string(), "acme-ft-*".to_string()]);
+        let usage = serde_json::json!({
+            "input_tokens": 1_000_000,
+            "output_tokens": 1_000_000,
+            "server_tool_use": { "web_search_requests": 2 },
+        });
+        assert!(is_free_model("ollama/llama-3.1-70b"));
+        assert_eq!(calculate_cost_for_usage("ollama/gpt-4o", &usage), 0.0);
+        assert_eq!(calculate_cost_for_usage("acme-ft-sonnet", &usage), 0.0);
+        assert_eq!(
+            pricing_source_for_model("acme-ft-sonnet"),
+            PricingSource::Free
+        );
+        assert!(calculate_cost_for_usage("claude-sonnet-4-6", &usage) > 0.0);
+
+        set_free_models(&[]);
+        assert!(!is_free_model("ollama/llama-3.1-70b"));
+    }
+
+    #[test]
+    fn test_pricing_table_dates() {
+        assert!(embedded_pricing_date().is_some());
+        let config =
+            parse_pricing_file(r#"{"_meta": {"updated": "2030-01-31"}, "models": {}}"#).unwrap();
+        assert_eq!(config.meta.updated, NaiveDate::from_ymd_opt(2030, 1, 31));
+        assert!(
+            parse_pricing_file(r#"{"_meta": {"updated": "last week"}, "models": {}}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_tiers_from_data() {
+        let config = parse_pricing_file(
+            r#"{"models": {}, "tiered_pricing": {"tiers": [
+                {"name": "long", "threshold": 200000, "applies_to": ["claude-sonnet-9*"],
+                 "multipliers": {"input": 2.0, "output": 1.5, "cache_create": 2.0, "cache_read": 2.0}},
+                {"name": "longer", "threshold": 500000, "applies_to": ["claude-sonnet-9*"],
+                 "multipliers": {"input": 3.0, "output": 2.0, "cache_create": 3.0, "cache_read": 3.0}}
+            ]}}"#,
+        )
+        .unwrap();
+        let tiers = &config.tiered_pricing;
+        let bedrock = tier_model_name("us.anthropic.claude-sonnet-9-20270101-v1:0");
+        assert_eq!(bedrock, "claude-sonnet-9-20270101");
+        assert_eq!(
+            tier_model_name("claude-sonnet-9@20270101"),
+            "claude-sonnet-9"
+        );
+        assert!(tiers.tier_for(&bedrock, 200_000).is_none());
+        assert_eq!(tiers.tier_for(&bedrock, 300_000).unwrap().name, "long");
+        assert_eq!(
+            tiers.tier_for("claude-sonnet-9", 600_000).unwrap().name,
+            "longer"
+        );
+        assert!(tiers.tier_for("claude-opus-9", 600_000).is_none());
+
+        let base = Pricing::new(3e-6, 15e-6, 3.75e-6, 6e-6, 0.3e-6);
+        let long = tiers
+            .tier_for("claude-sonnet-9", 300_000)
+            .unwrap()
+            .apply(base);
+        assert!((long.in_per_tok - 6e-6).abs() < 1e-15);
+        assert!((long.out_per_tok - 22.5e-6).abs() < 1e-15);
+        assert!((long.cache_create_1h_per_tok - 12e-6).abs() < 1e-15);
+
+        let disabled = TieredPricing {
+            enabled: Some(false),
+            ..TieredPricing::default()
+        };
+        assert!(disabled.tier_for("claude-sonnet-9", 600_000).is_none());
+    }
+
+    #[test]
+    fn test_proxy_surcharge() {
+        let usage = serde_json::json!({
+            "input_tokens": 600_000,
+            "output_tokens": 200_000,
+            "cache_read_input_tokens": 200_000,
+            "iterations": [{"type": "advisor_message", "input_tokens": 1_000}]
+        });
+        assert_eq!(usage_tokens(&usage), 1_001_000);
+
+        let surcharge = ProxySurcharge {
+            percent: 10.0,
+            per_mtok: 0.5,
+        };
+        assert!(surcharge.is_active());
+        assert!((surcharge.apply(2.0, 1_000_000) - 2.7).abs() < 1e-9);
+        // Unpriced and free usage stays at zero
+        assert_eq!(surcharge.apply(0.0, 1_000_000), 0.0);
+        assert!(!ProxySurcharge::default().is_active());
+    }
 }
@@ -1,4 +1,5 @@
 use serde::Serialize;
+use std::sync::Mutex;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -23,6 +24,8 @@ impl SessionCostSource {
 pub enum TodayCostSource {
     DbGlobalUsage,
     ScanFallback,
+    /// Admin API cost report, with `--admin-api-key`
+    AdminApi,
 }
 
 impl TodayCostSource {
@@ -30,6 +33,7 @@ impl TodayCostSource {
         match self {
             TodayCostSource::DbGlobalUsage => "db_global_usage",
             TodayCostSource::ScanFallback => "scan_fallback",
+            TodayCostSource::AdminApi => "admin_api",
         }
     }
 }
@@ -37,9 +41,15 @@ impl TodayCostSource {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum PricingSource {
+    /// `--free-model` allowlist
+    Free,
     EnvOverride,
+    /// Runtime pricing file (`--pricing-file`)
+    File,
     Embedded,
     StaticFallback,
+    /// LiteLLM price database (`--pricing-fetch`)
+    Litellm,
     FamilyHeuristic,
     Unavailable,
 }
@@ -47,9 +57,12 @@ pub enum PricingSource {
 impl PricingSource {
     pub fn as_str(self) -> &'static str {
         match self {
+            PricingSource::Free => "free",
             PricingSource::EnvOverride => "env_override",
+            PricingSource::File => "file",
             PricingSource::Embedded => "embedded",
             PricingSource::StaticFallback => "static_fallback",
+            PricingSource::Litellm => "litellm",
             PricingSource::FamilyHeuristic => "family_heuristic",
             PricingSource::Unavailable => "unavailable",
         }
@@ -62,3 +75,139 @@ pub struct CostProvenance {
     pub today_cost: TodayCostSource,
     pub pricing: PricingSource,
 }
+
+/// Where one cached value came from on this render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheLayer {
+    /// Unexpired row in the SQLite cache
+    Sqlite,
+    /// Expired SQLite row, served because a refresh failed or was backing off
+    SqliteStale,
+    /// Recomputed from the transcripts
+    FreshScan,
+    /// Recomputed from the db ledgers
+    FreshQuery,
+    /// Fetched from the remote API (or the `gh` CLI)
+    Api,
+}
+
+impl CacheLayer {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CacheLayer::Sqlite => "sqlite",
+            CacheLayer::SqliteStale => "sqlite_stale",
+            CacheLayer::FreshScan => "fresh_scan",
+            CacheLayer::FreshQuery => "fresh_query",
+            CacheLayer::Api => "api",
+        }
+    }
+
+    pub fn is_hit(self) -> bool {
+        matches!(self, CacheLayer::Sqlite | CacheLayer::SqliteStale)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CacheLookup {
+    /// `scan`, `global_sum`, `usage_api`, `oauth_profile`, `admin_api`, `openrouter`,
+    /// `github_pr`, `github_ci`, `git`, or `git_health`
+    pub cache: &'static str,
+    pub layer: CacheLayer,
+    /// Seconds since a cached value was written; `None` when freshly computed
+    pub age_seconds: Option<i64>,
+}
+
+/// Cache lookups made by this process, in order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheReport {
+    pub hits: usize,
+    pub misses: usize,
+    pub layers: Vec<CacheLookup>,
+}
+
+impl CacheReport {
+    /// `scan=sqlite(2s) global_sum=fresh_query usage_api=api`
+    pub fn summary(&self) -> String {
+        self.layers
+            .iter()
+            .map(|lookup| match lookup.age_seconds {
+                Some(age) => format!("{}={}({age}s)", lookup.cache, lookup.layer.as_str()),
+                None => format!("{}={}", lookup.cache, lookup.layer.as_str()),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Lookups kept per process; a long-running process renders many times, and
+/// only the latest render's lookups are worth reporting.
+const CACHE_LOOKUPS_KEPT: usize = 64;
+
+static CACHE_LOOKUPS: Mutex<Vec<CacheLookup>> = Mutex::new(Vec::new());
+
+/// Note which layer served `cache` on this render. Past
+/// [`CACHE_LOOKUPS_KEPT`] entries the oldest is dropped.
+pub fn record_cache(cache: &'static str, layer: CacheLayer, age_seconds: Option<i64>) {
+    let mut lookups = CACHE_LOOKUPS.lock().unwrap_or_else(|e| e.into_inner());
+    if lookups.len() >= CACHE_LOOKUPS_KEPT {
+        lookups.remove(0);
+    }
+    lookups.push(CacheLookup {
+        cache,
+        layer,
+        age_seconds: age_seconds.map(|age| age.max(0)),
+    });
+}
+
+pub fn cache_report() -> CacheReport {
+    let lookups = CACHE_LOOKUPS.lock().unwrap_or_else(|e| e.into_inner());
+    let hits = lookups.iter().filter(|l| l.layer.is_hit()).count();
+    CacheReport {
+        hits,
+        misses: lookups.len() - hits,
+        layers: lookups.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_report_counts_hits_and_misses() {
+        let report = CacheReport {
+            hits: 1,
+            misses: 1,
+            layers: vec![
+                CacheLookup {
+                    cache: "scan",
+                    layer: CacheLayer::Sqlite,
+                    age_seconds: Some(2),
+                },
+                CacheLookup {
+                    cache: "usage_api",
+                    layer: CacheLayer::Api,
+                    age_seconds: None,
+                },
+            ],
+        };
+        assert_eq!(report.summary(), "scan=sqlite(2s) usage_api=api");
+
+        record_cache("global_sum", CacheLayer::FreshQuery, None);
+        record_cache("usage_api", CacheLayer::SqliteStale, Some(-3));
+        let recorded = cache_report();
+        assert!(recorded.hits >= 1 && recorded.misses >= 1);
+        let stale = recorded
+            .layers
+            .iter()
+            .find(|l| l.layer == CacheLayer::SqliteStale)
+            .unwrap();
+        assert_eq!(stale.age_seconds, Some(0));
+
+        for _ in 0..CACHE_LOOKUPS_KEPT + 10 {
+            record_cache("scan", CacheLayer::FreshScan, None);
+        }
+        assert_eq!(cache_report().layers.len(), CACHE_LOOKUPS_KEPT);
+    }
+}
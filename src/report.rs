@@ -0,0 +1,918 @@
+use anyhow::{Result, bail};
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::budget::billing_period;
+use crate::cli::{Args, PricingArgs, PricingCommand, ReportArgs};
+use crate::currency::{DisplayCurrency, add_converted_fields, display_currency, money};
+use crate::db::{
+    CostHistory, LifetimeStats, UsageSnapshot, load_cost_history, load_lifetime_stats,
+    load_project_costs, load_usage_snapshots,
+};
+use crate::models::Entry;
+use crate::pricing::{
+    fast_mode_multiplier, pricing_for_model, pricing_source_for_model, pricing_table_date,
+};
+use crate::usage::scan_usage;
+use crate::utils::{claude_paths, format_tokens, glob_match, now_local, project_dir_pattern};
+
+/// Projects listed under `today.top_projects` in the statusline JSON.
+pub const TOP_PROJECTS_LIMIT: usize = 5;
+
+/// Local days of persisted history that cost statistics are computed over.
+pub const STATS_HISTORY_DAYS: i64 = 90;
+
+/// Local days of recorded usage API snapshots shown by `report --limits`.
+pub const LIMIT_HISTORY_DAYS: i64 = 30;
+
+/// Usage API utilization recorded over one local day, in percent
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LimitDay {
+    pub date: String,
+    pub five_hour_peak: Option<f64>,
+    pub seven_day_peak: Option<f64>,
+    /// Weekly utilization at the day's last fetch
+    pub seven_day_last: Option<f64>,
+    pub snapshots: usize,
+}
+
+/// Today's spend attributed to one project directory
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectCost {
+    pub name: String,
+    pub cost_usd: f64,
+    /// Fraction of today's scanned cost, 0.0..=1.0
+    pub share: f64,
+    /// Client tag from the config `[tags]` section
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// `cost_usd` with the chargeback multiplier applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub billable_usd: Option<f64>,
+}
+
+/// Projects without a matching `[tags]` pattern are totalled under this tag.
+pub const UNTAGGED: &str = "(untagged)";
+
+/// Spend attributed to one client tag
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagCost {
+    pub tag: String,
+    pub cost_usd: f64,
+    /// Fraction of the period's cost, 0.0..=1.0
+    pub share: f64,
+    /// Number of distinct projects contributing
+    pub projects: usize,
+    /// `cost_usd` with the chargeback multiplier applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub billable_usd: Option<f64>,
+}
+
+/// Today's spend and token volume for one model id
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelCost {
+    pub model: String,
+    pub cost_usd: f64,
+    /// Fraction of today's scanned cost, 0.0..=1.0
+    pub share: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+fn is_on_day(entry: &Entry, day: NaiveDate) -> bool {
+    entry.ts.with_timezone(&Local).date_naive() == day
+}
+
+/// Per-project cost for `day` (local), most expensive first.
+/// Entries without a project are grouped under `(unknown)`.
+pub fn projects_for_day(entries: &[Entry], day: NaiveDate) -> Vec<ProjectCost> {
+    let mut by_project: HashMap<&str, f64> = HashMap::new();
+    let mut total = 0.0;
+    for entry in entries.iter().filter(|e| is_on_day(e, day)) {
+        *by_project
+            .entry(entry.project.as_deref().unwrap_or("(unknown)"))
+            .or_insert(0.0) += entry.cost;
+        total += entry.cost;
+    }
+
+    let mut projects: Vec<ProjectCost> = by_project
+        .into_iter()
+        .map(|(name, cost)| ProjectCost {
+            name: name.to_string(),
+            cost_usd: cost,
+            share: if total > 0.0 { cost / total } else { 0.0 },
+            tag: None,
+            billable_usd: None,
+        })
+        .collect();
+    projects.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    projects
+}
+
+/// Client tag for a project. Tag keys are project directories (`~` allowed,
+/// `*`/`?` globs) matched against the sanitized project name the transcripts
+/// are stored under. The first matching entry wins.
+pub fn project_tag<'a>(project: &str, tags: &'a [(String, String)]) -> Option<&'a str> {
+    tags.iter()
+        .find(|(dir, _)| glob_match(&project_dir_pattern(dir), project))
+        .map(|(_, tag)| tag.as_str())
+}
+
+/// Total per-project costs by client tag, most expensive first.
+pub fn costs_by_tag<'a>(
+    projects: impl IntoIterator<Item = (&'a str, f64)>,
+    args: &Args,
+) -> Vec<TagCost> {
+    let mut by_tag: HashMap<&str, (f64, usize)> = HashMap::new();
+    let mut total = 0.0;
+    for (project, cost) in projects {
+        let tag = project_tag(project, &args.tags).unwrap_or(UNTAGGED);
+        let slot = by_tag.entry(tag).or_insert((0.0, 0));
+        slot.0 += cost;
+        slot.1 += 1;
+        total += cost;
+    }
+
+    let mut tags: Vec<TagCost> = by_tag
+        .into_iter()
+        .map(|(tag, (cost, projects))| TagCost {
+            tag: tag.to_string(),
+            cost_usd: cost,
+            share: if total > 0.0 { cost / total } else { 0.0 },
+            projects,
+            billable_usd: args.chargeback_multiplier.map(|m| cost * m),
+        })
+        .collect();
+    tags.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.tag.cmp(&b.tag))
+    });
+    tags
+}
+
+/// Per-tag totals for the billing period containing `today`, from the db ledger.
+pub fn period_costs_by_tag(args: &Args, today: NaiveDate) -> Option<Vec<TagCost>> {
+    if args.no_subsystem_db_cache {
+        return None;
+    }
+    let (start, _) = billing_period(today, args.billing_day);
+    let projects = load_project_costs(start, today).ok()?;
+    Some(costs_by_tag(
+        projects.iter().map(|(name, cost)| (name.as_str(), *cost)),
+        args,
+    ))
+}
+
+/// Fill in client tags and, when a chargeback multiplier is set, billable cost.
+pub fn apply_chargeback(projects: &mut [ProjectCost], args: &Args) {
+    for project in projects {
+        project.tag = project_tag(&project.name, &args.tags).map(str::to_string);
+        project.billable_usd = args.chargeback_multiplier.map(|m| project.cost_usd * m);
+    }
+}
+
+/// Fold snapshots (oldest first) into one row per local day, oldest first.
+pub fn limit_days(snapshots: &[UsageSnapshot]) -> Vec<LimitDay> {
+    let peak = |a: Option<f64>, b: Option<f64>| match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+    let mut days: Vec<LimitDay> = Vec::new();
+    for snapshot in snapshots {
+        let Some(at) = chrono::DateTime::from_timestamp(snapshot.fetched_at, 0) else {
+            continue;
+        };
+        let date = at.with_timezone(&Local).format("%Y-%m-%d").to_string();
+        match days.last_mut() {
+            Some(day) if day.date == date => {
+                day.five_hour_peak = peak(day.five_hour_peak, snapshot.five_hour);
+                day.seven_day_peak = peak(day.seven_day_peak, snapshot.seven_day);
+                day.seven_day_last = snapshot.seven_day.or(day.seven_day_last);
+                day.snapshots += 1;
+            }
+            _ => days.push(LimitDay {
+                date,
+                five_hour_peak: snapshot.five_hour,
+                seven_day_peak: snapshot.seven_day,
+                seven_day_last: snapshot.seven_day,
+                snapshots: 1,
+            }),
+        }
+    }
+    days
+}
+
+/// Per-model cost and tokens for `day` (local), most expensive first.
+/// Entries without a model are grouped under `(unknown)`.
+pub fn models_for_day(entries: &[Entry], day: NaiveDate) -> Vec<ModelCost> {
+    let mut by_model: HashMap<&str, ModelCost> = HashMap::new();
+    let mut total = 0.0;
+    for entry in entries.iter().filter(|e| is_on_day(e, day)) {
+        let name = entry.model.as_deref().unwrap_or("(unknown)");
+        let model = by_model.entry(name).or_insert_with(|| ModelCost {
+            model: name.to_string(),
+            cost_usd: 0.0,
+            share: 0.0,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        });
+        model.cost_usd += entry.cost;
+        model.input_tokens += entry.input;
+        model.output_tokens += entry.output;
+        model.cache_creation_input_tokens += entry.cache_create;
+        model.cache_read_input_tokens += entry.cache_read;
+        total += entry.cost;
+    }
+
+    let mut models: Vec<ModelCost> = by_model
+        .into_values()
+        .map(|mut model| {
+            model.share = if total > 0.0 {
+                model.cost_usd / total
+            } else {
+                0.0
+            };
+            model
+        })
+        .collect();
+    models.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    models
+}
+
+/// Where today and recent sessions sit in the persisted spend history
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostStats {
+    /// Days with recorded spend in the window, including today
+    pub days: usize,
+    /// Today's position by cost among those days; 1 is the most expensive
+    pub today_rank: usize,
+    /// Percent of recorded days that cost no more than today
+    pub today_percentile: f64,
+    /// Sessions with recorded spend in the window
+    pub sessions: usize,
+    pub session_cost_median_usd: Option<f64>,
+    pub session_cost_p90_usd: Option<f64>,
+}
+
+impl CostStats {
+    /// `today_cost` stands in for today's ledger row, which may lag the caller.
+    pub fn from_history(history: &CostHistory, today: NaiveDate, today_cost: f64) -> Self {
+        let earlier: Vec<f64> = history
+            .daily
+            .iter()
+            .filter(|d| d.date != today)
+            .map(|d| d.cost)
+            .collect();
+        let days = earlier.len() + 1;
+        let today_rank = 1 + earlier.iter().filter(|c| **c > today_cost).count();
+        let at_or_below = 1 + earlier.iter().filter(|c| **c <= today_cost).count();
+
+        let mut sessions = history.session_costs.clone();
+        sessions.sort_by(f64::total_cmp);
+        Self {
+            days,
+            today_rank,
+            today_percentile: at_or_below as f64 / days as f64 * 100.0,
+            sessions: sessions.len(),
+            session_cost_median_usd: percentile(&sessions, 50.0),
+            session_cost_p90_usd: percentile(&sessions, 90.0),
+        }
+    }
+
+    /// e.g. "today is your 2nd most expensive day of 37"
+    pub fn rank_label(&self) -> String {
+        if self.today_rank == 1 {
+            format!("today is your most expensive day of {}", self.days)
+        } else {
+            format!(
+                "today is your {} most expensive day of {}",
+                ordinal(self.today_rank),
+                self.days
+            )
+        }
+    }
+}
+
+/// Linearly interpolated percentile of an ascending slice.
+pub(crate) fn percentile(sorted: &[f64], pct: f64) -> Option<f64> {
+    let last = sorted.len().checked_sub(1)?;
+    let rank = pct / 100.0 * last as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    date: String,
+    cost_usd: f64,
+    entries: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chargeback_multiplier: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    billable_usd: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    by_project: Option<Vec<ProjectCost>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    by_model: Option<Vec<ModelCost>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    by_tag: Option<Vec<TagCost>>,
+    /// Per-tag totals since the start of the billing period
+    #[serde(skip_serializing_if = "Option::is_none")]
+    period_by_tag: Option<Vec<TagCost>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<CostStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limits: Option<Vec<LimitDay>>,
+}
+
+/// `report` subcommand: today's spend from the scanned transcripts.
+pub fn run_report(args: &Args, report_args: &ReportArgs) -> Result<()> {
+    if !report_args.team.is_empty() {
+        return crate::team::run_team(args, report_args.month, &report_args.team);
+    }
+    if report_args.seat_export {
+        return crate::team::run_seat_export(args, report_args.month);
+    }
+    if report_args.month.is_some() || report_args.invoice.is_some() {
+        return crate::invoice::run_statement(
+            args,
+            report_args.month,
+            report_args.invoice.as_deref(),
+        );
+    }
+    let paths = claude_paths(args.claude_config_dir.as_deref());
+    let (_, _, _, entries, _, _, _) = scan_usage(&paths, "", None, None)?;
+    let today = now_local().date_naive();
+    let todays: Vec<&Entry> = entries.iter().filter(|e| is_on_day(e, today)).collect();
+
+    let cost_usd = todays.iter().fold(0.0, |sum, e| sum + e.cost);
+    let stats = if args.no_subsystem_db_cache {
+        None
+    } else {
+        load_cost_history(STATS_HISTORY_DAYS)
+            .ok()
+            .map(|history| CostStats::from_history(&history, today, cost_usd))
+    };
+
+    let report = Report {
+        date: today.format("%Y-%m-%d").to_string(),
+        cost_usd,
+        entries: todays.len(),
+        chargeback_multiplier: args.chargeback_multiplier,
+        billable_usd: args.chargeback_multiplier.map(|m| cost_usd * m),
+        by_project: report_args.by_project.then(|| {
+            let mut projects = projects_for_day(&entries, today);
+            apply_chargeback(&mut projects, args);
+            projects
+        }),
+        by_model: report_args
+            .by_model
+            .then(|| models_for_day(&entries, today)),
+        by_tag: report_args.by_tag.then(|| {
+            costs_by_tag(
+                projects_for_day(&entries, today)
+                    .iter()
+                    .map(|p| (p.name.as_str(), p.cost_usd)),
+                args,
+            )
+        }),
+        period_by_tag: report_args
+            .by_tag
+            .then(|| period_costs_by_tag(args, today))
+            .flatten(),
+        stats,
+        limits: if report_args.limits {
+            if args.no_subsystem_db_cache {
+                bail!("limit history reads the SQLite cache; enable the db_cache subsystem");
+            }
+            let since = now_local().timestamp() - LIMIT_HISTORY_DAYS * 86_400;
+            // The account a render here would fetch limits for
+            let account =
+                crate::usage_api::resolve_account(&args.accounts, args.account.as_deref(), &paths)
+                    .map(|account| account.label)
+                    .unwrap_or_default();
+            Some(limit_days(&load_usage_snapshots(&account, since)?))
+        } else {
+            None
+        },
+    };
+
+    if args.json {
+        let mut json = serde_json::to_value(&report)?;
+        add_converted_fields(&mut json);
+        println!("{}", serde_json::to_string(&json)?);
+    } else {
+        print_report(&report, display_currency());
+    }
+    Ok(())
+}
+
+/// `stats`: lifetime totals maintained incrementally in the db.
+pub fn run_stats(args: &Args) -> Result<()> {
+    if args.no_subsystem_db_cache {
+        bail!("lifetime stats read the SQLite cost ledger; enable the db_cache subsystem");
+    }
+    let stats = load_lifetime_stats()?;
+    let timings = crate::timing::summarize(&crate::db::load_render_timings()?);
+    if args.json {
+        let mut json = serde_json::json!({
+            "lifetime": stats.to_json(),
+            "render_timings": timings,
+        });
+        add_converted_fields(&mut json);
+        println!("{}", serde_json::to_string(&json)?);
+    } else {
+        print_lifetime(&stats, display_currency());
+        if !timings.is_empty() {
+            println!("render timings:");
+            for summary in &timings {
+                println!("  {}", crate::timing::format_summary(summary));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn run_pricing(args: &Args, pricing: &PricingArgs) -> Result<()> {
+    match &pricing.command {
+        PricingCommand::Show { model } => run_pricing_show(args, model),
+    }
+}
+
+fn run_pricing_show(args: &Args, model: &str) -> Result<()> {
+    let source = pricing_source_for_model(model);
+    let Some(rates) = pricing_for_model(model) else {
+        bail!("no pricing for {model} (source: {})", source.as_str());
+    };
+    let fast = fast_mode_multiplier(model);
+    let updated = pricing_table_date();
+    let per_mtok = |per_tok: f64| (per_tok * 1e6 * 10_000.0).round() / 10_000.0;
+    if args.json {
+        let json = serde_json::json!({
+            "model": model,
+            "source": source.as_str(),
+            "per_mtok_usd": {
+                "input": per_mtok(rates.in_per_tok),
+                "output": per_mtok(rates.out_per_tok),
+                "cache_create": per_mtok(rates.cache_create_per_tok),
+                "cache_create_1h": per_mtok(rates.cache_create_1h_per_tok),
+                "cache_read": per_mtok(rates.cache_read_per_tok),
+            },
+            "fast_mode_multiplier": fast,
+            "table_updated": updated,
+        });
+        println!("{}", serde_json::to_string(&json)?);
+        return Ok(());
+    }
+    println!("model: {model}");
+    println!("source: {}", source.as_str());
+    let rows = [
+        ("input", rates.in_per_tok),
+        ("output", rates.out_per_tok),
+        ("cache write 5m", rates.cache_create_per_tok),
+        ("cache write 1h", rates.cache_create_1h_per_tok),
+        ("cache read", rates.cache_read_per_tok),
+    ];
+    for (label, per_tok) in rows {
+        let price = per_mtok(per_tok);
+        let decimals = if price > 0.0 && price < 0.1 { 4 } else { 2 };
+        println!("{label}: ${price:.decimals$}/MTok");
+    }
+    if fast != 1.0 {
+        println!("fast mode: {fast}x");
+    }
+    if let Some(updated) = updated {
+        let age = (now_local().date_naive() - updated).num_days();
+        println!("pricing table: updated {updated} ({age} days ago)");
+        if age > i64::from(args.pricing_stale_days) {
+            println!("warning: pricing table may be stale; check published prices");
+        }
+    }
+    Ok(())
+}
+
+fn print_lifetime(stats: &LifetimeStats, currency: Option<&DisplayCurrency>) {
+    println!("claude_statusline lifetime stats");
+    let Some(first_seen) = stats.first_seen else {
+        println!("no spend recorded yet");
+        return;
+    };
+    println!(
+        "since: {} ({} active days)",
+        first_seen.format("%Y-%m-%d"),
+        stats.active_days
+    );
+    println!("cost: {}", money(stats.cost, currency));
+    println!(
+        "tokens: {} (in {} out {} cache_w {} cache_r {})",
+        format_tokens(stats.total_tokens()),
+        format_tokens(stats.input_tokens),
+        format_tokens(stats.output_tokens),
+        format_tokens(stats.cache_create_tokens),
+        format_tokens(stats.cache_read_tokens)
+    );
+    if let Some(busiest) = stats.busiest_day {
+        println!(
+            "busiest day: {} ({})",
+            busiest.date.format("%Y-%m-%d"),
+            money(busiest.cost, currency)
+        );
+    }
+}
+
+fn print_report(report: &Report, currency: Option<&DisplayCurrency>) {
+    println!("claude_statusline report {}", report.date);
+    println!(
+        "today: {} ({} entries)",
+        money(report.cost_usd, currency),
+        report.entries
+    );
+    if let (Some(multiplier), Some(billable)) = (report.chargeback_multiplier, report.billable_usd)
+    {
+        println!("billable: {} (x{multiplier})", money(billable, currency));
+    }
+    if let Some(ref stats) = report.stats {
+        println!("{}", stats.rank_label());
+        if let (Some(median), Some(p90)) =
+            (stats.session_cost_median_usd, stats.session_cost_p90_usd)
+        {
+            println!(
+                "sessions: median {} p90 {} (n={}, last {} days)",
+                money(median, currency),
+                money(p90, currency),
+                stats.sessions,
+                STATS_HISTORY_DAYS
+            );
+        }
+    }
+    if let Some(ref projects) = report.by_project {
+        let width = projects
+            .iter()
+            .map(|p| p.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("project".len());
+        let tagged = projects.iter().any(|p| p.tag.is_some());
+        let billable = report.chargeback_multiplier.is_some();
+        println!();
+        let mut header = format!("{:<width$}  {:>10}  {:>6}", "project", "cost", "share");
+        if billable {
+            header.push_str(&format!("  {:>10}", "billable"));
+        }
+        if tagged {
+            header.push_str("  client");
+        }
+        println!("{header}");
+        for project in projects {
+            let mut row = format!(
+                "{:<width$}  {:>10}  {:>5.1}%",
+                project.name,
+                money(project.cost_usd, currency),
+                project.share * 100.0
+            );
+            if let Some(amount) = project.billable_usd {
+                row.push_str(&format!("  {:>10}", money(amount, currency)));
+            }
+            if tagged {
+                row.push_str(&format!("  {}", project.tag.as_deref().unwrap_or("-")));
+            }
+            println!("{row}");
+        }
+    }
+    if let Some(ref tags) = report.by_tag {
+        print_tag_table("today", tags, currency);
+    }
+    if let Some(ref tags) = report.period_by_tag {
+        print_tag_table("billing period", tags, currency);
+    }
+    if let Some(ref models) = report.by_model {
+        let width = models
+            .iter()
+            .map(|m| m.model.len())
+            .max()
+            .unwrap_or(0)
+            .max("model".len());
+        println!();
+        println!(
+            "{:<width$}  {:>10}  {:>6}  {:>8}  {:>8}  {:>8}  {:>8}",
+            "model", "cost", "share", "input", "output", "cache_w", "cache_r"
+        );
+        for model in models {
+            println!(
+                "{:<width$}  {:>10}  {:>5.1}%  {:>8}  {:>8}  {:>8}  {:>8}",
+                model.model,
+                money(model.cost_usd, currency),
+                model.share * 100.0,
+                format_tokens(model.input_tokens),
+                format_tokens(model.output_tokens),
+                format_tokens(model.cache_creation_input_tokens),
+                format_tokens(model.cache_read_input_tokens)
+            );
+        }
+    }
+    if let Some(ref days) = report.limits {
+        print_limit_table(days);
+    }
+}
+
+fn print_limit_table(days: &[LimitDay]) {
+    let percent =
+        |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.0}%"));
+    println!();
+    println!(
+        "{:<10}  {:>8}  {:>8}  {:>8}  {:>9}",
+        "date", "5h peak", "7d peak", "7d last", "snapshots"
+    );
+    if days.is_empty() {
+        println!("(no usage API fetches recorded)");
+    }
+    for day in days {
+        println!(
+            "{:<10}  {:>8}  {:>8}  {:>8}  {:>9}",
+            day.date,
+            percent(day.five_hour_peak),
+            percent(day.seven_day_peak),
+            percent(day.seven_day_last),
+            day.snapshots
+        );
+    }
+}
+
+fn print_tag_table(period: &str, tags: &[TagCost], currency: Option<&DisplayCurrency>) {
+    let width = tags
+        .iter()
+        .map(|t| t.tag.len())
+        .max()
+        .unwrap_or(0)
+        .max(period.len() + "tag ()".len());
+    println!();
+    let mut header = format!(
+        "{:<width$}  {:>10}  {:>6}  {:>8}",
+        format!("tag ({period})"),
+        "cost",
+        "share",
+        "projects"
+    );
+    if tags.iter().any(|t| t.billable_usd.is_some()) {
+        header.push_str(&format!("  {:>10}", "billable"));
+    }
+    println!("{header}");
+    if tags.is_empty() {
+        println!("(no recorded spend)");
+    }
+    for tag in tags {
+        let mut row = format!(
+            "{:<width$}  {:>10}  {:>5.1}%  {:>8}",
+            tag.tag,
+            money(tag.cost_usd, currency),
+            tag.share * 100.0,
+            tag.projects
+        );
+        if let Some(amount) = tag.billable_usd {
+            row.push_str(&format!("  {:>10}", money(amount, currency)));
+        }
+        println!("{row}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DailyCost;
+    use crate::utils::sanitized_project_name;
+    use chrono::TimeZone;
+
+    fn model_entry(model: &str, hour: u32, cost: f64, output: u64) -> Entry {
+        let mut e = entry(None, hour, cost);
+        e.model = Some(model.to_string());
+        e.input = 100;
+        e.output = output;
+        e
+    }
+
+    fn entry(project: Option<&str>, hour: u32, cost: f64) -> Entry {
+        Entry {
+            ts: Local
+                .with_ymd_and_hms(2025, 10, 15, hour, 0, 0)
+                .unwrap()
+                .with_timezone(&chrono::Utc),
+            input: 0,
+            output: 0,
+            cache_create: 0,
+            cache_read: 0,
+            server_tools: Default::default(),
+            speed: None,
+            service_tier: None,
+            cost,
+            model: None,
+            session_id: None,
+            msg_id: None,
+            req_id: None,
+            project: project.map(str::to_string),
+            agent_id: None,
+        }
+    }
+
+    #[test]
+    fn projects_for_day_sorts_by_cost_with_shares() {
+        let mut yesterday = entry(Some("-repo-a"), 12, 50.0);
+        yesterday.ts -= chrono::Duration::days(1);
+        let entries = vec![
+            entry(Some("-repo-a"), 9, 1.0),
+            entry(Some("-repo-b"), 10, 6.0),
+            entry(Some("-repo-a"), 11, 2.0),
+            entry(None, 12, 1.0),
+            yesterday,
+        ];
+
+        let day = NaiveDate::from_ymd_opt(2025, 10, 15).unwrap();
+        let projects = projects_for_day(&entries, day);
+        let names: Vec<&str> = projects.iter().map(|p| p.name.as_str()).collect();
+
+        assert_eq!(names, vec!["-repo-b", "-repo-a", "(unknown)"]);
+        assert!((projects[0].share - 0.6).abs() < 1e-9);
+        assert!((projects[1].cost_usd - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn costs_by_tag_groups_glob_matches() {
+        use clap::Parser;
+        let mut args = Args::parse_from(["claude_statusline"]);
+        args.tags = vec![
+            ("/work/acme*".to_string(), "acme".to_string()),
+            ("/work/globex-web".to_string(), "globex".to_string()),
+        ];
+        let projects = [
+            ("-work-acme-api", 4.0),
+            ("-work-acme-web", 2.0),
+            ("-work-globex-web", 3.0),
+            ("-tmp-scratch", 1.0),
+        ];
+
+        let tags = costs_by_tag(projects, &args);
+        let summary: Vec<(&str, f64, usize)> = tags
+            .iter()
+            .map(|t| (t.tag.as_str(), t.cost_usd, t.projects))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![("acme", 6.0, 2), ("globex", 3.0, 1), (UNTAGGED, 1.0, 1)]
+        );
+        assert!((tags[0].share - 0.6).abs() < 1e-9);
+        assert_eq!(tags[0].billable_usd, None);
+    }
+
+    #[test]
+    fn chargeback_tags_projects_and_applies_multiplier() {
+        use clap::Parser;
+        let home = directories::BaseDirs::new()
+            .unwrap()
+            .home_dir()
+            .to_string_lossy()
+            .into_owned();
+        let mut args = Args::parse_from(["claude_statusline"]);
+        args.chargeback_multiplier = Some(1.25);
+        args.tags = vec![
+            ("~/work/acme-api".to_string(), "acme".to_string()),
+            ("/srv/globex".to_string(), "globex".to_string()),
+        ];
+        let acme = sanitized_project_name(&format!("{home}/work/acme-api"));
+        let entries = vec![
+            entry(Some(&acme), 9, 4.0),
+            entry(Some("-srv-globex"), 10, 2.0),
+            entry(Some("-tmp-scratch"), 11, 1.0),
+        ];
+
+        let day = NaiveDate::from_ymd_opt(2025, 10, 15).unwrap();
+        let mut projects = projects_for_day(&entries, day);
+        apply_chargeback(&mut projects, &args);
+
+        let tags: Vec<Option<&str>> = projects.iter().map(|p| p.tag.as_deref()).collect();
+        assert_eq!(tags, vec![Some("acme"), Some("globex"), None]);
+        assert_eq!(projects[0].billable_usd, Some(5.0));
+        assert_eq!(projects[2].billable_usd, Some(1.25));
+    }
+
+    #[test]
+    fn models_for_day_sums_cost_and_tokens() {
+        let entries = vec![
+            model_entry("claude-sonnet-4-6", 9, 1.0, 500),
+            model_entry("claude-opus-4-7", 10, 3.0, 200),
+            model_entry("claude-sonnet-4-6", 11, 1.0, 300),
+        ];
+
+        let day = NaiveDate::from_ymd_opt(2025, 10, 15).unwrap();
+        let models = models_for_day(&entries, day);
+
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].model, "claude-opus-4-7");
+        assert!((models[0].share - 0.6).abs() < 1e-9);
+        assert_eq!(models[1].input_tokens, 200);
+        assert_eq!(models[1].output_tokens, 800);
+    }
+
+    #[test]
+    fn cost_stats_rank_today_and_session_percentiles() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 10, d).unwrap();
+        let history = CostHistory {
+            daily: vec![
+                DailyCost {
+                    date: day(12),
+                    cost: 30.0,
+                },
+                DailyCost {
+                    date: day(13),
+                    cost: 5.0,
+                },
+                DailyCost {
+                    date: day(14),
+                    cost: 8.0,
+                },
+                // Stale ledger row for today; the caller's total wins.
+                DailyCost {
+                    date: day(15),
+                    cost: 1.0,
+                },
+            ],
+            session_costs: vec![4.0, 1.0, 3.0, 2.0, 10.0],
+        };
+
+        let stats = CostStats::from_history(&history, day(15), 12.0);
+
+        assert_eq!(stats.days, 4);
+        assert_eq!(stats.today_rank, 2);
+        assert!((stats.today_percentile - 75.0).abs() < 1e-9);
+        assert_eq!(
+            stats.rank_label(),
+            "today is your 2nd most expensive day of 4"
+        );
+        assert_eq!(stats.sessions, 5);
+        assert_eq!(stats.session_cost_median_usd, Some(3.0));
+        assert!((stats.session_cost_p90_usd.unwrap() - 7.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ordinal_suffixes() {
+        assert_eq!(ordinal(1), "1st");
+        assert_eq!(ordinal(3), "3rd");
+        assert_eq!(ordinal(11), "11th");
+        assert_eq!(ordinal(22), "22nd");
+    }
+
+    #[test]
+    fn limit_days_keeps_daily_peaks_and_last_weekly_value() {
+        let at = |day: u32, hour: u32| {
+            Local
+                .with_ymd_and_hms(2026, 3, day, hour, 0, 0)
+                .unwrap()
+                .timestamp()
+        };
+        let snapshot = |fetched_at, five_hour, seven_day| UsageSnapshot {
+            fetched_at,
+            five_hour,
+            seven_day,
+            ..Default::default()
+        };
+        let days = limit_days(&[
+            snapshot(at(2, 9), Some(40.0), Some(12.0)),
+            snapshot(at(2, 14), Some(85.0), Some(30.0)),
+            snapshot(at(2, 20), Some(10.0), None),
+            snapshot(at(3, 9), None, Some(31.0)),
+        ]);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, "2026-03-02");
+        assert_eq!(days[0].five_hour_peak, Some(85.0));
+        assert_eq!(days[0].seven_day_peak, Some(30.0));
+        assert_eq!(days[0].seven_day_last, Some(30.0));
+        assert_eq!(days[0].snapshots, 3);
+        assert_eq!(days[1].five_hour_peak, None);
+        assert_eq!(days[1].seven_day_last, Some(31.0));
+    }
+}
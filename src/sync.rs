@@ -0,0 +1,227 @@
+//! Cross-machine usage sync through a shared directory, typically a private
+//! git repository.
+//!
+//! Each machine only appends to its own files, `<dir>/<machine>/<YYYY-MM>.jsonl`,
+//! so commits from different machines never touch the same file and git
+//! merges them without conflicts. Every line is a [`HistoryRow`]; importing
+//! merges rows with the newest-copy-wins rules of `db import`, so replaying a
+//! line is harmless.
+
+use crate::db::{HistoryRow, ImportReport};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Start of the last export, in unix seconds; rows updated since are exported next.
+const EXPORTED_AT_KEY: &str = "sync_exported_at";
+/// Bytes of `<machine>/<file>` already imported.
+const OFFSET_KEY_PREFIX: &str = "sync_offset:";
+
+#[derive(Debug, Serialize)]
+pub struct SyncExport {
+    pub path: PathBuf,
+    pub rows: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncImport {
+    pub files: usize,
+    pub rows: usize,
+    pub merged: ImportReport,
+}
+
+/// Machine name for snapshot directories: the hostname, reduced to
+/// filename-safe characters.
+pub fn default_machine_name() -> String {
+    let host = std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .unwrap_or_default();
+    let name = sanitize(&host);
+    if name.is_empty() {
+        "default".to_string()
+    } else {
+        name
+    }
+}
+
+fn sanitize(name: &str) -> String {
+    name.trim()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        .collect()
+}
+
+/// Append rows changed since the last export to this machine's snapshot.
+pub fn export(dir: &Path, machine: &str) -> Result<SyncExport> {
+    let since = crate::db::load_metadata(EXPORTED_AT_KEY)?
+        .and_then(|m| m.value.parse::<i64>().ok())
+        .unwrap_or(0);
+    let started = crate::utils::now_utc().timestamp();
+    let rows = crate::db::history_rows_since(since)?;
+
+    let machine_dir = dir.join(sanitize(machine));
+    let path = machine_dir.join(format!("{}.jsonl", crate::utils::now_utc().format("%Y-%m")));
+    if !rows.is_empty() {
+        fs::create_dir_all(&machine_dir)
+            .with_context(|| format!("failed to create {}", machine_dir.display()))?;
+        let mut text = String::new();
+        for row in &rows {
+            text.push_str(&serde_json::to_string(row)?);
+            text.push('\n');
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(text.as_bytes()))
+            .with_context(|| format!("failed to append to {}", path.display()))?;
+    }
+    crate::db::store_metadata(EXPORTED_AT_KEY, &started.to_string())?;
+    Ok(SyncExport {
+        path,
+        rows: rows.len(),
+    })
+}
+
+/// Merge lines other machines appended since the last import.
+pub fn import(dir: &Path, machine: &str) -> Result<SyncImport> {
+    let own = sanitize(machine);
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name == own || name.starts_with('.') || !entry.path().is_dir() {
+            continue;
+        }
+        for file in fs::read_dir(entry.path())? {
+            let path = file?.path();
+            if path.extension().is_some_and(|ext| ext == "jsonl") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+
+    let mut rows = Vec::new();
+    let mut offsets = Vec::new();
+    for path in &files {
+        let key = offset_key(dir, path);
+        let offset = crate::db::load_metadata(&key)?
+            .and_then(|m| m.value.parse::<u64>().ok())
+            .unwrap_or(0);
+        let (new_rows, consumed) = read_new_lines(path, offset)?;
+        if consumed != offset {
+            rows.extend(new_rows);
+            offsets.push((key, consumed));
+        }
+    }
+
+    let merged = crate::db::merge_history_rows(&rows)?;
+    // Only advance once the rows are safely merged
+    for (key, offset) in offsets {
+        crate::db::store_metadata(&key, &offset.to_string())?;
+    }
+    Ok(SyncImport {
+        files: files.len(),
+        rows: rows.len(),
+        merged,
+    })
+}
+
+fn offset_key(dir: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(dir).unwrap_or(path);
+    format!("{OFFSET_KEY_PREFIX}{}", relative.to_string_lossy())
+}
+
+/// Complete lines after `offset`, and the offset just past the last one. A
+/// file shorter than `offset` was rewritten, so it is read from the start.
+fn read_new_lines(path: &Path, offset: u64) -> Result<(Vec<HistoryRow>, u64)> {
+    let mut file =
+        fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let len = file.metadata()?.len();
+    let start = if len < offset { 0 } else { offset };
+    file.seek(SeekFrom::Start(start))?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    // A trailing partial line is still being written (or was cut off by a pull)
+    let complete = text.rfind('\n').map_or(0, |i| i + 1);
+    let rows = text[..complete]
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .with_context(|| format!("invalid sync line in {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((rows, start + complete as u64))
+}
+
+#[cfg(all(test, feature = "db"))]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use tempfile::TempDir;
+
+    fn use_db(path: &Path) {
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { std::env::set_var("CLAUDE_STATUSLINE_DB_PATH", path) };
+    }
+
+    fn seed(session: &str, cost: f64) {
+        let export = serde_json::json!({
+            "format": "claude-statusline-db",
+            "schema_version": 1,
+            "tables": {"entries": [{
+                "event_key": format!("{session}-1"), "ts": 0, "date": "2025-10-14",
+                "session_id": session, "project": "api", "model": "claude-sonnet-4-6",
+                "input_tokens": 1, "output_tokens": 1, "cache_create_tokens": 0,
+                "cache_read_tokens": 0, "web_search_requests": 0, "cost": cost,
+                "updated_at": chrono::Utc::now().timestamp()
+            }]}
+        });
+        crate::db::import_json(&export).unwrap();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_sync_round_trip_between_machines() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(&repo).unwrap();
+        let day = NaiveDate::from_ymd_opt(2025, 10, 14).unwrap();
+
+        use_db(&temp.path().join("laptop.db"));
+        seed("laptop", 2.0);
+        let first = export(&repo, "laptop").unwrap();
+        // The entry plus the ledger rows reconciled from it
+        assert!(first.rows >= 1);
+
+        use_db(&temp.path().join("desktop.db"));
+        seed("desktop", 3.0);
+        export(&repo, "desktop").unwrap();
+        let imported = import(&repo, "desktop").unwrap();
+        assert_eq!(imported.files, 1);
+        assert_eq!(imported.rows, first.rows);
+        assert_eq!(import(&repo, "desktop").unwrap().rows, 0);
+
+        // Both machines' entries reach the per-project aggregate
+        let projects = crate::db::load_project_costs(day, day).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert!((projects[0].1 - 5.0).abs() < 1e-9);
+
+        // A partial trailing line waits for the rest of the write
+        let file = repo.join("laptop").join(first.path.file_name().unwrap());
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&file)
+            .unwrap()
+            .write_all(b"{\"table\":")
+            .unwrap();
+        assert_eq!(import(&repo, "desktop").unwrap().rows, 0);
+        unsafe { std::env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+}
@@ -0,0 +1,370 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::cli::Args;
+use crate::currency::{DisplayCurrency, add_converted_fields, display_currency, money};
+use crate::db::{SeatLedger, load_seat_ledger};
+use crate::invoice::statement_period;
+use crate::usage_api::UsageSummary;
+use crate::utils::{expand_home, format_tokens, now_local};
+
+/// One seat passed to `report --team`: `[NAME=]PATH`. Without a name the
+/// file stem is used (`alice.db` -> `alice`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeatSource {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl FromStr for SeatSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, path) = match s.split_once('=') {
+            Some((name, path)) if !name.is_empty() && !name.contains('/') => (Some(name), path),
+            _ => (None, s),
+        };
+        if path.is_empty() {
+            return Err(format!("invalid seat value: {s}"));
+        }
+        let path = PathBuf::from(expand_home(path));
+        let name = name.map(str::to_string).unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string())
+        });
+        Ok(Self { name, path })
+    }
+}
+
+/// One day of a seat's spend; also the line format of `--seat-export`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SeatDay {
+    pub date: NaiveDate,
+    pub cost_usd: f64,
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default)]
+    pub cache_read_input_tokens: u64,
+}
+
+impl SeatDay {
+    fn new(date: NaiveDate, cost_usd: f64) -> Self {
+        Self {
+            date,
+            cost_usd,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        }
+    }
+}
+
+/// Latest subscription window utilization seen for a seat
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SeatWindow {
+    pub five_hour_percent: Option<f64>,
+    pub seven_day_percent: Option<f64>,
+    pub observed_at: Option<DateTime<Utc>>,
+}
+
+impl SeatWindow {
+    fn from_summary(summary: &UsageSummary, fetched_at: i64) -> Self {
+        Self {
+            five_hour_percent: summary.window.utilization,
+            seven_day_percent: summary.seven_day.utilization,
+            observed_at: DateTime::from_timestamp(fetched_at, 0),
+        }
+    }
+}
+
+/// A `--seat-export` line: a day of spend or the window snapshot.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExportLine {
+    Window { window: SeatWindow },
+    Day(SeatDay),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SeatReport {
+    pub seat: String,
+    pub cost_usd: f64,
+    /// Fraction of the team's cost
+    pub share: f64,
+    /// Days with recorded spend in the period
+    pub active_days: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<SeatWindow>,
+}
+
+impl SeatReport {
+    fn from_days(seat: &str, days: &[SeatDay], window: Option<SeatWindow>) -> Self {
+        let mut report = Self {
+            seat: seat.to_string(),
+            cost_usd: 0.0,
+            share: 0.0,
+            active_days: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+            window,
+        };
+        for day in days {
+            report.cost_usd += day.cost_usd;
+            report.active_days += u32::from(day.cost_usd > 0.0);
+            report.input_tokens += day.input_tokens;
+            report.output_tokens += day.output_tokens;
+            report.cache_creation_input_tokens += day.cache_creation_input_tokens;
+            report.cache_read_input_tokens += day.cache_read_input_tokens;
+        }
+        report
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+}
+
+/// Seat-level spend for one billing period, most expensive seat first.
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamReport {
+    pub period_start: NaiveDate,
+    /// Last day of the period (inclusive)
+    pub period_end: NaiveDate,
+    pub cost_usd: f64,
+    pub seats: Vec<SeatReport>,
+}
+
+impl TeamReport {
+    fn from_seats(start: NaiveDate, end: NaiveDate, mut seats: Vec<SeatReport>) -> Self {
+        let cost_usd = seats.iter().fold(0.0, |sum, s| sum + s.cost_usd);
+        for seat in &mut seats {
+            seat.share = if cost_usd > 0.0 {
+                seat.cost_usd / cost_usd
+            } else {
+                0.0
+            };
+        }
+        seats.sort_by(|a, b| {
+            b.cost_usd
+                .total_cmp(&a.cost_usd)
+                .then_with(|| a.seat.cmp(&b.seat))
+        });
+        Self {
+            period_start: start,
+            period_end: end,
+            cost_usd,
+            seats,
+        }
+    }
+}
+
+fn seat_days(ledger: &SeatLedger) -> Vec<SeatDay> {
+    let mut days: Vec<SeatDay> = ledger
+        .daily
+        .iter()
+        .map(|d| SeatDay::new(d.date, d.cost))
+        .collect();
+    for (date, tokens) in &ledger.tokens {
+        let day = match days.iter().position(|d| d.date == *date) {
+            Some(i) => &mut days[i],
+            None => {
+                days.push(SeatDay::new(*date, 0.0));
+                days.last_mut().expect("just pushed")
+            }
+        };
+        day.input_tokens = tokens.input;
+        day.output_tokens = tokens.output;
+        day.cache_creation_input_tokens = tokens.cache_create;
+        day.cache_read_input_tokens = tokens.cache_read;
+    }
+    days.sort_by_key(|d| d.date);
+    days
+}
+
+fn ledger_window(ledger: &SeatLedger) -> Option<SeatWindow> {
+    let (json, fetched_at) = ledger.usage_summary.as_ref()?;
+    let summary: UsageSummary = serde_json::from_str(json).ok()?;
+    Some(SeatWindow::from_summary(&summary, *fetched_at))
+}
+
+/// Parse a `--seat-export` file, keeping days in `[from, to]` and the last
+/// window snapshot.
+fn parse_export(
+    text: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<(Vec<SeatDay>, Option<SeatWindow>)> {
+    let mut days = Vec::new();
+    let mut window = None;
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parsed: ExportLine = serde_json::from_str(line)
+            .with_context(|| format!("invalid seat export line {}", i + 1))?;
+        match parsed {
+            ExportLine::Window { window: w } => window = Some(w),
+            ExportLine::Day(day) if day.date >= from && day.date <= to => days.push(day),
+            ExportLine::Day(_) => {}
+        }
+    }
+    Ok((days, window))
+}
+
+fn load_seat(seat: &SeatSource, from: NaiveDate, to: NaiveDate) -> Result<SeatReport> {
+    let is_export = seat
+        .path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"));
+    let (days, window) = if is_export {
+        let text = std::fs::read_to_string(&seat.path)
+            .with_context(|| format!("failed to read seat export {}", seat.path.display()))?;
+        parse_export(&text, from, to)?
+    } else {
+        let ledger = load_seat_ledger(Some(&seat.path), from, to)?;
+        (seat_days(&ledger), ledger_window(&ledger))
+    };
+    Ok(SeatReport::from_days(&seat.name, &days, window))
+}
+
+/// `report --team`: aggregate each seat's billing period (current, or the one
+/// starting in `month`).
+pub fn run_team(args: &Args, month: Option<NaiveDate>, seats: &[SeatSource]) -> Result<()> {
+    let (start, end) = statement_period(args, month, now_local().date_naive());
+    let reports = seats
+        .iter()
+        .map(|seat| load_seat(seat, start, end))
+        .collect::<Result<Vec<_>>>()?;
+    let report = TeamReport::from_seats(start, end, reports);
+
+    if args.json {
+        let mut json = serde_json::to_value(&report)?;
+        add_converted_fields(&mut json);
+        println!("{}", serde_json::to_string(&json)?);
+    } else {
+        print_team(&report, display_currency());
+    }
+    Ok(())
+}
+
+/// `report --seat-export`: this machine's billing period as seat JSONL, ready
+/// to be collected for a teammate's `report --team`.
+pub fn run_seat_export(args: &Args, month: Option<NaiveDate>) -> Result<()> {
+    if args.no_subsystem_db_cache {
+        bail!("seat exports read the SQLite cost ledger; enable the db_cache subsystem");
+    }
+    let (start, end) = statement_period(args, month, now_local().date_naive());
+    let ledger = load_seat_ledger(None, start, end)?;
+    for day in seat_days(&ledger) {
+        println!("{}", serde_json::to_string(&day)?);
+    }
+    if let Some(window) = ledger_window(&ledger) {
+        println!("{}", serde_json::json!({ "window": window }));
+    }
+    Ok(())
+}
+
+fn percent(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{v:.0}%"))
+}
+
+fn print_team(report: &TeamReport, currency: Option<&DisplayCurrency>) {
+    println!(
+        "claude_statusline team report {} to {} ({} seats)",
+        report.period_start,
+        report.period_end,
+        report.seats.len()
+    );
+    let width = report
+        .seats
+        .iter()
+        .map(|s| s.seat.len())
+        .max()
+        .unwrap_or(0)
+        .max("seat".len());
+    println!();
+    println!(
+        "{:<width$}  {:>10}  {:>6}  {:>4}  {:>8}  {:>4}  {:>4}",
+        "seat", "cost", "share", "days", "tokens", "5h", "7d"
+    );
+    for seat in &report.seats {
+        let window = seat.window.unwrap_or_default();
+        println!(
+            "{:<width$}  {:>10}  {:>5.1}%  {:>4}  {:>8}  {:>4}  {:>4}",
+            seat.seat,
+            money(seat.cost_usd, currency),
+            seat.share * 100.0,
+            seat.active_days,
+            format_tokens(seat.total_tokens()),
+            percent(window.five_hour_percent),
+            percent(window.seven_day_percent)
+        );
+    }
+    println!(
+        "{:<width$}  {:>10}",
+        "total",
+        money(report.cost_usd, currency)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 10, d).unwrap()
+    }
+
+    #[test]
+    fn seat_source_uses_name_or_file_stem() {
+        let named: SeatSource = "alice=/shared/a.db".parse().unwrap();
+        assert_eq!(named.name, "alice");
+        assert_eq!(named.path, PathBuf::from("/shared/a.db"));
+
+        let unnamed: SeatSource = "/shared/bob.jsonl".parse().unwrap();
+        assert_eq!(unnamed.name, "bob");
+        assert!("alice=".parse::<SeatSource>().is_err());
+    }
+
+    #[test]
+    fn export_lines_aggregate_into_seat_shares() {
+        let export = r#"{"date":"2025-09-30","cost_usd":50.0}
+{"date":"2025-10-02","cost_usd":6.0,"input_tokens":100,"output_tokens":50}
+
+{"date":"2025-10-03","cost_usd":0.0}
+{"window":{"five_hour_percent":42.0,"seven_day_percent":71.5,"observed_at":null}}
+"#;
+        let (days, window) = parse_export(export, day(1), day(31)).unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(window.unwrap().seven_day_percent, Some(71.5));
+
+        let alice = SeatReport::from_days("alice", &days, window);
+        let bob = SeatReport::from_days("bob", &[SeatDay::new(day(5), 18.0)], None);
+        let team = TeamReport::from_seats(day(1), day(31), vec![alice, bob]);
+
+        assert_eq!(team.cost_usd, 24.0);
+        assert_eq!(team.seats[0].seat, "bob");
+        assert_eq!(team.seats[0].share, 0.75);
+        assert_eq!(team.seats[1].active_days, 1);
+        assert_eq!(team.seats[1].total_tokens(), 150);
+        assert!(parse_export("not json", day(1), day(31)).is_err());
+    }
+}
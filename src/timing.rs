@@ -0,0 +1,190 @@
+//! Per-render phase timings, kept in a small ring buffer in the db so
+//! performance regressions between versions show up on real machines.
+
+use crate::db::RenderTiming;
+use serde::Serialize;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Transcript scan and session state
+    Scan,
+    /// Global usage aggregation in the SQLite cache
+    Db,
+    /// Repository inspection
+    Git,
+    /// OAuth usage API (cached or fetched)
+    Api,
+    /// Formatting and writing the statusline
+    Render,
+}
+
+/// Accumulates phase durations for one invocation.
+pub struct PhaseTimer {
+    started: Instant,
+    timing: RenderTiming,
+}
+
+impl Default for PhaseTimer {
+    fn default() -> Self {
+        Self::start()
+    }
+}
+
+impl PhaseTimer {
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            timing: RenderTiming {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                ..RenderTiming::default()
+            },
+        }
+    }
+
+    /// Add the time since `since` to `phase`.
+    pub fn add(&mut self, phase: Phase, since: Instant) {
+        let ms = since.elapsed().as_secs_f64() * 1000.0;
+        let slot = match phase {
+            Phase::Scan => &mut self.timing.scan_ms,
+            Phase::Db => &mut self.timing.db_ms,
+            Phase::Git => &mut self.timing.git_ms,
+            Phase::Api => &mut self.timing.api_ms,
+            Phase::Render => &mut self.timing.render_ms,
+        };
+        *slot += ms;
+    }
+
+    /// Run `f`, charging its duration to `phase`.
+    pub fn time<T>(&mut self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        let since = Instant::now();
+        let value = f();
+        self.add(phase, since);
+        value
+    }
+
+    pub fn finish(mut self) -> RenderTiming {
+        self.timing.recorded_at = chrono::Utc::now().timestamp();
+        self.timing.total_ms = self.started.elapsed().as_secs_f64() * 1000.0;
+        self.timing
+    }
+}
+
+/// Median and 95th percentile of one phase, in milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PhaseStats {
+    pub phase: &'static str,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+}
+
+/// Recorded renders of one binary version.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimingSummary {
+    pub version: String,
+    pub renders: usize,
+    pub phases: Vec<PhaseStats>,
+}
+
+/// Per-version percentiles, most recently recorded version first.
+pub fn summarize(timings: &[RenderTiming]) -> Vec<TimingSummary> {
+    let mut versions: Vec<&str> = Vec::new();
+    let mut newest = timings.iter().collect::<Vec<_>>();
+    newest.sort_by_key(|t| std::cmp::Reverse(t.recorded_at));
+    for timing in &newest {
+        if !versions.contains(&timing.version.as_str()) {
+            versions.push(&timing.version);
+        }
+    }
+    versions
+        .into_iter()
+        .map(|version| {
+            let runs: Vec<&RenderTiming> =
+                timings.iter().filter(|t| t.version == version).collect();
+            let stats = |phase: &'static str, ms: fn(&RenderTiming) -> f64| {
+                let mut values: Vec<f64> = runs.iter().map(|t| ms(t)).collect();
+                values.sort_by(f64::total_cmp);
+                PhaseStats {
+                    phase,
+                    p50_ms: crate::report::percentile(&values, 50.0).unwrap_or(0.0),
+                    p95_ms: crate::report::percentile(&values, 95.0).unwrap_or(0.0),
+                }
+            };
+            TimingSummary {
+                version: version.to_string(),
+                renders: runs.len(),
+                phases: vec![
+                    stats("scan", |t| t.scan_ms),
+                    stats("db", |t| t.db_ms),
+                    stats("git", |t| t.git_ms),
+                    stats("api", |t| t.api_ms),
+                    stats("render", |t| t.render_ms),
+                    stats("total", |t| t.total_ms),
+                ],
+            }
+        })
+        .collect()
+}
+
+/// One line per version, e.g. `v2.5.0 (120 renders, p50/p95) scan 12/30ms db 3/8ms ...`
+pub fn format_summary(summary: &TimingSummary) -> String {
+    let phases: Vec<String> = summary
+        .phases
+        .iter()
+        .map(|p| format!("{} {:.0}/{:.0}ms", p.phase, p.p50_ms, p.p95_ms))
+        .collect();
+    format!(
+        "v{} ({} renders, p50/p95) {}",
+        summary.version,
+        summary.renders,
+        phases.join(" ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(version: &str, recorded_at: i64, scan_ms: f64) -> RenderTiming {
+        RenderTiming {
+            recorded_at,
+            version: version.to_string(),
+            scan_ms,
+            total_ms: scan_ms + 1.0,
+            ..RenderTiming::default()
+        }
+    }
+
+    #[test]
+    fn summarize_groups_by_version_newest_first() {
+        let timings = vec![
+            timing("2.4.0", 1, 50.0),
+            timing("2.5.0", 2, 10.0),
+            timing("2.5.0", 3, 20.0),
+            timing("2.5.0", 4, 30.0),
+        ];
+        let summaries = summarize(&timings);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].version, "2.5.0");
+        assert_eq!(summaries[0].renders, 3);
+        let scan = &summaries[0].phases[0];
+        assert_eq!(scan.phase, "scan");
+        assert!((scan.p50_ms - 20.0).abs() < 1e-9);
+        assert!((scan.p95_ms - 29.0).abs() < 1e-9);
+        assert_eq!(summaries[1].renders, 1);
+        assert!(
+            format_summary(&summaries[0]).starts_with("v2.5.0 (3 renders, p50/p95) scan 20/29ms")
+        );
+    }
+
+    #[test]
+    fn timer_charges_phases() {
+        let mut timer = PhaseTimer::start();
+        let value = timer.time(Phase::Git, || 7);
+        assert_eq!(value, 7);
+        let timing = timer.finish();
+        assert!(timing.git_ms >= 0.0);
+        assert!(timing.total_ms >= timing.git_ms);
+        assert_eq!(timing.version, env!("CARGO_PKG_VERSION"));
+    }
+}
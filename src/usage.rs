@@ -5,6 +5,7 @@
 //! ## Key Functions
 //!
 //! - `scan_usage`: Scans Claude config directories for usage JSONL files
+//! - `get_cached_usage`: `scan_usage` behind a short-lived cache shared across processes
 //! - `identify_blocks`: Groups usage entries into 5-hour window blocks with gap detection
 //! - `calc_context_from_*`: Calculates context window usage from various sources
 
@@ -23,11 +24,17 @@ use walkdir::WalkDir;
 
 use crate::models::prompt_cache::{PROMPT_CACHE_1H_TTL_SECONDS, PROMPT_CACHE_5M_TTL_SECONDS};
 use crate::models::{
-    Entry, MessageUsage, PromptCacheBucketInfo, PromptCacheBucketKind, PromptCacheInfo,
-    RateLimitInfo, TranscriptLine,
+    Block, Entry, MessageUsage, PromptCacheBucketInfo, PromptCacheBucketKind, PromptCacheInfo,
+    RateLimitInfo, ServerToolUse, TokenCounts, TranscriptLine,
+};
+use crate::pricing::{
+    apply_proxy_surcharge, calculate_project_cost, is_free_model, project_cost_multiplier,
+};
+use crate::provenance::{CacheLayer, record_cache};
+use crate::utils::{
+    WINDOW_DURATION_HOURS, context_limit_for_model_display, floor_to_hour, now_local, now_utc,
+    parse_iso_date, system_overhead_tokens,
 };
-use crate::pricing::calculate_cost_for_usage_with_speed;
-use crate::utils::{context_limit_for_model_display, parse_iso_date, system_overhead_tokens};
 
 /// Session-specific state extracted from the session's own transcript file.
 /// Unlike the global scan, this reads only the target transcript for fast, authoritative data.
@@ -241,7 +248,7 @@ pub fn parse_session_state(transcript_path: &Path) -> SessionState {
             last_cache_read_at,
             cache_write_input_tokens: last_cache_write_tokens,
             cache_read_input_tokens: last_cache_read_tokens,
-            now: Utc::now(),
+            now: now_utc(),
         });
     }
 
@@ -715,13 +722,8 @@ fn find_recent_jsonl_files(root: &Path, cutoff: SystemTime) -> Vec<PathBuf> {
         .collect()
 }
 
-#[allow(clippy::type_complexity)]
-pub fn scan_usage(
-    paths: &[PathBuf],
-    session_id: &str,
-    _project_dir: Option<&str>,
-    _model_id_for_probe: Option<&str>,
-) -> Result<(
+/// Everything [`scan_usage`] extracts from one pass over the transcripts.
+pub type ScanResult = (
     f64, /*session*/
     f64, /*session_today*/
     f64, /*today*/
@@ -729,8 +731,87 @@ pub fn scan_usage(
     Option<DateTime<Utc>>,
     Option<String>,
     Option<RateLimitInfo>,
-)> {
-    let today = Local::now().date_naive();
+);
+
+/// `api_cache` key prefix for [`get_cached_usage`] results.
+const SCAN_CACHE_PREFIX: &str = "scan:";
+
+/// [`scan_usage`] behind the SQLite `api_cache`, so refreshes within a few
+/// seconds of each other (from any process) skip the transcript walk. The
+/// window is `CacheTtls::scan_seconds`; 0 always rescans.
+///
+/// The key covers the local date, session, project, scanned roots, lookback
+/// (`CLAUDE_SCAN_LOOKBACK_HOURS`), and pricing inputs, so a cached result never
+/// crosses midnight, another session's totals, or a render scanned or priced
+/// under different options. Window scope and anchoring are applied to the
+/// scanned entries afterwards and need no key of their own. Cache errors fall
+/// through to a fresh scan.
+pub fn get_cached_usage(
+    paths: &[PathBuf],
+    session_id: &str,
+    project_dir: Option<&str>,
+    model_id_for_probe: Option<&str>,
+) -> Result<ScanResult> {
+    let ttl = crate::db::cache_ttls().scan_seconds;
+    if ttl <= 0 {
+        return scan_usage(paths, session_id, project_dir, model_id_for_probe);
+    }
+    let key = scan_cache_key(paths, session_id, project_dir);
+    if let Ok(Some((cached, fetched_at))) = crate::db::get_api_cache_entry(&key)
+        && let Ok(result) = serde_json::from_str::<ScanResult>(&cached)
+    {
+        let age = Utc::now().timestamp() - fetched_at;
+        record_cache("scan", CacheLayer::Sqlite, Some(age));
+        return Ok(result);
+    }
+    let result = scan_usage(paths, session_id, project_dir, model_id_for_probe)?;
+    record_cache("scan", CacheLayer::FreshScan, None);
+    if let Ok(json) = serde_json::to_string(&result) {
+        let _ = crate::db::set_api_cache(&key, &json, ttl);
+    }
+    Ok(result)
+}
+
+fn scan_cache_key(paths: &[PathBuf], session_id: &str, project_dir: Option<&str>) -> String {
+    use sha2::{Digest, Sha256};
+
+    let roots: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    let mut hasher = Sha256::new();
+    hasher.update(project_dir.unwrap_or("").as_bytes());
+    hasher.update(b"\0");
+    hasher.update(roots.join("|").as_bytes());
+    hasher.update(b"\0");
+    hasher.update(scan_lookback_hours().to_string().as_bytes());
+    hasher.update(b"\0");
+    hasher.update(crate::pricing::pricing_fingerprint().as_bytes());
+    let options: String = hasher
+        .finalize()
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!(
+        "{SCAN_CACHE_PREFIX}{}:{session_id}:{options}",
+        now_local().date_naive()
+    )
+}
+
+/// Hours of transcript history scanned, from `CLAUDE_SCAN_LOOKBACK_HOURS`
+/// (default 48).
+fn scan_lookback_hours() -> i64 {
+    env::var("CLAUDE_SCAN_LOOKBACK_HOURS")
+        .ok()
+        .and_then(|hours| hours.parse::<i64>().ok())
+        .unwrap_or(48)
+}
+
+pub fn scan_usage(
+    paths: &[PathBuf],
+    session_id: &str,
+    _project_dir: Option<&str>,
+    _model_id_for_probe: Option<&str>,
+) -> Result<ScanResult> {
+    let today = now_local().date_naive();
     let mut session_cost = 0.0f64;
     // Prefer precise session cost from SDK result messages when available.
     // Track the maximum observed total_cost_usd for this session to avoid overcounting across retries.
@@ -758,15 +839,7 @@ pub fn scan_usage(
     let mut result_usage_by_session_model: HashMap<String, Entry> = HashMap::new();
 
     // Optimization: Skip files older than 48 hours by default
-    let cutoff_time = if let Ok(hours_str) = env::var("CLAUDE_SCAN_LOOKBACK_HOURS") {
-        if let Ok(hours) = hours_str.parse::<i64>() {
-            Utc::now() - Duration::hours(hours)
-        } else {
-            Utc::now() - Duration::hours(48)
-        }
-    } else {
-        Utc::now() - Duration::hours(48)
-    };
+    let cutoff_time = now_utc() - Duration::hours(scan_lookback_hours());
     // Convert to SystemTime for efficient walkdir filtering
     let cutoff_system = SystemTime::UNIX_EPOCH
         + std::time::Duration::from_secs(cutoff_time.timestamp().max(0) as u64);
@@ -867,7 +940,7 @@ pub fn scan_usage(
                                                     }
                                                 }
                                             } else if let Some(dt) =
-                                                parse_am_pm_reset(Utc::now(), text)
+                                                parse_am_pm_reset(now_utc(), text)
                                             {
                                                 if latest_reset.map(|x| dt > x).unwrap_or(true) {
                                                     latest_reset = Some(dt);
@@ -907,7 +980,7 @@ pub fn scan_usage(
                                             latest_reset = Some(dt);
                                         }
                                     }
-                                } else if let Some(dt) = parse_am_pm_reset(Utc::now(), text) {
+                                } else if let Some(dt) = parse_am_pm_reset(now_utc(), text) {
                                     if latest_reset.map(|x| dt > x).unwrap_or(true) {
                                         latest_reset = Some(dt);
                                     }
@@ -968,7 +1041,15 @@ pub fn scan_usage(
                             let cache_read = json_number_as_u64(usage.get("cacheReadInputTokens"));
                             let web_search_requests =
                                 json_number_as_u64(usage.get("webSearchRequests"));
-                            let cost = json_number_as_f64(usage.get("costUSD"));
+                            let cost = if is_free_model(model_name) {
+                                0.0
+                            } else {
+                                apply_proxy_surcharge(
+                                    json_number_as_f64(usage.get("costUSD"))
+                                        * project_cost_multiplier(proj_name.as_deref()),
+                                    input + output + cache_create + cache_read,
+                                )
+                            };
                             if input == 0
                                 && output == 0
                                 && cache_create == 0
@@ -985,7 +1066,10 @@ pub fn scan_usage(
                                 output,
                                 cache_create,
                                 cache_read,
-                                web_search_requests,
+                                server_tools: ServerToolUse::single(
+                                    "web_search",
+                                    web_search_requests,
+                                ),
                                 speed: None,
                                 service_tier: None,
                                 cost,
@@ -1212,7 +1296,7 @@ pub fn scan_usage(
                                                 }
                                             }
                                         }
-                                    } else if let Some(dt) = parse_am_pm_reset(Utc::now(), text) {
+                                    } else if let Some(dt) = parse_am_pm_reset(now_utc(), text) {
                                         if latest_reset.map(|x| dt > x).unwrap_or(true) {
                                             latest_reset = Some(dt);
                                         }
@@ -1268,12 +1352,8 @@ pub fn scan_usage(
                     .and_then(|n| n.as_u64())
                     .unwrap_or(0);
                 let mut cost = 0.0f64;
-                // Web search request charges
-                let web_search_reqs = usage
-                    .get("server_tool_use")
-                    .and_then(|o| o.get("web_search_requests"))
-                    .and_then(|n| n.as_u64())
-                    .unwrap_or(0);
+                // Server tool requests, charged per request on top of tokens
+                let server_tools = ServerToolUse::from_usage(usage);
                 let model = msg
                     .get("model")
                     .and_then(|s| s.as_str())
@@ -1335,7 +1415,8 @@ pub fn scan_usage(
                     sid_by_rid.insert(r.clone(), s.clone());
                 }
                 if let Some(ref mdl) = model {
-                    cost = calculate_cost_for_usage_with_speed(mdl, usage, speed.as_deref());
+                    cost =
+                        calculate_project_cost(proj_name.as_deref(), mdl, usage, speed.as_deref());
                 }
                 // Decide whether updates for this key are cumulative totals or per-chunk deltas
                 let key_clone = agg_key.clone();
@@ -1372,7 +1453,7 @@ pub fn scan_usage(
                     output,
                     cache_create,
                     cache_read,
-                    web_search_requests: web_search_reqs,
+                    server_tools: server_tools.clone(),
                     speed: speed.clone(),
                     service_tier: service_tier.clone(),
                     cost,
@@ -1392,7 +1473,7 @@ pub fn scan_usage(
                     e.output = e.output.saturating_add(output);
                     e.cache_create = e.cache_create.saturating_add(cache_create);
                     e.cache_read = e.cache_read.saturating_add(cache_read);
-                    e.web_search_requests = e.web_search_requests.saturating_add(web_search_reqs);
+                    e.server_tools.accumulate(&server_tools);
                     e.cost += cost;
                     if e.service_tier.is_none() {
                         e.service_tier = service_tier.clone();
@@ -1423,9 +1504,7 @@ pub fn scan_usage(
                     if cache_read > e.cache_read {
                         e.cache_read = cache_read;
                     }
-                    if web_search_reqs > e.web_search_requests {
-                        e.web_search_requests = web_search_reqs;
-                    }
+                    e.server_tools.merge_max(&server_tools);
                     if cost > e.cost {
                         e.cost = cost;
                     }
@@ -1442,9 +1521,7 @@ pub fn scan_usage(
                         e.project = proj_name.clone();
                     }
                 }
-                if web_search_reqs > e.web_search_requests {
-                    e.web_search_requests = web_search_reqs;
-                }
+                e.server_tools.merge_max(&server_tools);
                 if e.service_tier.is_none() {
                     e.service_tier = service_tier.clone();
                 }
@@ -1462,7 +1539,7 @@ pub fn scan_usage(
     }
 
     // Offline-only: never perform API probes. If a persisted reset exists in the future, use it.
-    let now = Utc::now();
+    let now = now_utc();
     let rl_info: Option<RateLimitInfo> = None;
     if latest_reset.is_none() {
         if let Some(state) = read_persisted_reset_state() {
@@ -1561,7 +1638,7 @@ pub fn scan_usage(
 // Some providers emit an absolute epoch (e.g., 172xxxxxxx). Others may emit seconds-until-reset (e.g., 5400).
 // Heuristic: treat values >= 1_000_000_000 as epoch seconds; otherwise as seconds-from-now.
 fn normalize_reset_anchor(n: i64) -> i64 {
-    let now = Utc::now().timestamp();
+    let now = now_utc().timestamp();
     if n >= 1_000_000_000 { n } else { now + n }
 }
 
@@ -1614,13 +1691,88 @@ pub fn calculate_session_complexity(entries: &[Entry], session_id: &str) -> Mess
     complexity
 }
 
+/// Group entries into 5-hour blocks, ccusage-style.
+///
+/// A block starts at the floored hour of its first entry and closes once an
+/// entry lands more than 5 hours after the block start or after the previous
+/// entry. Idle stretches longer than 5 hours between blocks become gap blocks
+/// (`is_gap`, no entries, zero cost). A block is active while `now` is inside
+/// it and its last entry is less than 5 hours old. Blocks are oldest first.
+pub fn identify_blocks(entries: &[Entry], now: DateTime<Utc>) -> Vec<Block> {
+    let mut sorted: Vec<&Entry> = entries.iter().collect();
+    sorted.sort_by_key(|e| e.ts);
+
+    let session_duration = Duration::hours(WINDOW_DURATION_HOURS);
+    let mut groups: Vec<Vec<&Entry>> = Vec::new();
+    for entry in sorted {
+        let starts_new = match groups.last() {
+            Some(group) => {
+                let start = floor_to_hour(group[0].ts);
+                let last = group[group.len() - 1].ts;
+                entry.ts - start > session_duration || entry.ts - last > session_duration
+            }
+            None => true,
+        };
+        if starts_new {
+            groups.push(vec![entry]);
+        } else if let Some(group) = groups.last_mut() {
+            group.push(entry);
+        }
+    }
+
+    let mut blocks: Vec<Block> = Vec::new();
+    for group in groups {
+        let start = floor_to_hour(group[0].ts);
+        let end = start + session_duration;
+        let actual_end = group[group.len() - 1].ts;
+
+        if let Some(prev) = blocks.last() {
+            let gap_start = prev.actual_end + session_duration;
+            if gap_start < group[0].ts {
+                blocks.push(Block {
+                    start: gap_start,
+                    end: group[0].ts,
+                    actual_end: group[0].ts,
+                    is_active: false,
+                    is_gap: true,
+                    entries: Vec::new(),
+                    tokens: TokenCounts::default(),
+                    cost: 0.0,
+                });
+            }
+        }
+
+        let mut tokens = TokenCounts::default();
+        let mut cost = 0.0;
+        for e in &group {
+            tokens.input += e.input;
+            tokens.output += e.output;
+            tokens.cache_create += e.cache_create;
+            tokens.cache_read += e.cache_read;
+            cost += e.cost;
+        }
+        blocks.push(Block {
+            start,
+            end,
+            actual_end,
+            is_active: now - actual_end < session_duration && now < end,
+            is_gap: false,
+            entries: group.into_iter().cloned().collect(),
+            tokens,
+            cost,
+        });
+    }
+
+    blocks
+}
+
 // Detect rapid message exchange patterns for burn rate calculation
 pub fn detect_rapid_exchange(
     entries: &[Entry],
     session_id: &str,
     window_minutes: i64,
+    now: DateTime<Utc>,
 ) -> (bool, f64) {
-    let now = Utc::now();
     let window_start = now - Duration::minutes(window_minutes);
 
     // Filter entries for this session within the window
@@ -1850,7 +2002,7 @@ mod tests {
             output: 9000,
             cache_create: 2000,
             cache_read: 3000,
-            web_search_requests: 0,
+            server_tools: ServerToolUse::default(),
             speed: None,
             service_tier: None,
             cost: 0.0,
@@ -2388,7 +2540,7 @@ mod tests {
         assert_eq!(entries[0].output, 200);
         assert_eq!(entries[0].cache_read, 300);
         assert_eq!(entries[0].cache_create, 400);
-        assert_eq!(entries[0].web_search_requests, 2);
+        assert_eq!(entries[0].server_tools.requests("web_search"), 2);
         assert_eq!(entries[0].model.as_deref(), Some("claude-sonnet-4-6"));
         assert!((session_cost - 1.5).abs() < 1e-10);
         assert!((session_today_cost - 1.5).abs() < 1e-10);
@@ -2837,4 +2989,118 @@ mod tests {
         );
         Ok(())
     }
+
+    fn block_entry(ts: DateTime<Utc>, input: u64, cost: f64) -> Entry {
+        Entry {
+            ts,
+            input,
+            output: 0,
+            cache_create: 0,
+            cache_read: 0,
+            server_tools: ServerToolUse::default(),
+            speed: None,
+            service_tier: None,
+            cost,
+            model: None,
+            session_id: Some("s1".to_string()),
+            msg_id: None,
+            req_id: None,
+            project: None,
+            agent_id: None,
+        }
+    }
+
+    #[test]
+    fn identify_blocks_splits_on_block_length_and_inserts_gaps() {
+        let base = Utc.with_ymd_and_hms(2025, 6, 2, 9, 20, 0).unwrap();
+        let entries = vec![
+            // Out of order on purpose: blocks are built from sorted entries.
+            block_entry(base + Duration::minutes(260), 20, 0.2),
+            block_entry(base, 10, 0.1),
+            // 09:00 floor + 5h = 14:00 is the block end, so 14:30 opens a new block.
+            block_entry(base + Duration::minutes(310), 30, 0.3),
+            // 14:30 + 5h idle -> gap block until the next entry.
+            block_entry(base + Duration::hours(12), 40, 0.4),
+        ];
+        let now = base + Duration::hours(12) + Duration::minutes(5);
+
+        let blocks = identify_blocks(&entries, now);
+
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(
+            blocks[0].start,
+            Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap()
+        );
+        assert_eq!(blocks[0].entries.len(), 2);
+        assert_eq!(blocks[0].tokens.input, 30);
+        assert!((blocks[0].cost - 0.3).abs() < 1e-9);
+        assert_eq!(
+            blocks[1].start,
+            Utc.with_ymd_and_hms(2025, 6, 2, 14, 0, 0).unwrap()
+        );
+        assert!(!blocks[1].is_gap);
+        assert!(blocks[2].is_gap);
+        assert_eq!(
+            blocks[2].start,
+            base + Duration::minutes(310) + Duration::hours(5)
+        );
+        assert_eq!(blocks[2].end, base + Duration::hours(12));
+        assert!(blocks[2].entries.is_empty());
+        assert!(blocks[3].is_active);
+        assert!(blocks.iter().filter(|b| b.is_active).count() == 1);
+    }
+
+    #[test]
+    fn identify_blocks_marks_stale_block_inactive() {
+        let base = Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap();
+        let entries = vec![block_entry(base, 10, 0.1)];
+
+        assert!(identify_blocks(&entries, base + Duration::hours(1))[0].is_active);
+        assert!(!identify_blocks(&entries, base + Duration::hours(6))[0].is_active);
+        assert!(identify_blocks(&[], base).is_empty());
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    #[serial_test::serial]
+    fn get_cached_usage_reuses_recent_scan_across_calls() -> Result<()> {
+        let session_id = format!(
+            "cached-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        );
+        let line = json!({
+            "type": "assistant",
+            "sessionId": session_id,
+            "timestamp": Local::now().to_rfc3339(),
+            "message": {
+                "role": "assistant",
+                "id": "msg-cached",
+                "model": "claude-sonnet-4-6",
+                "usage": { "input_tokens": 1000, "output_tokens": 1000 }
+            }
+        });
+        let dir = write_transcript_line(&session_id, line)?;
+        let db_dir = tempdir()?;
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_dir.path().join("scan.db")) };
+        let base = dir.path().to_path_buf();
+
+        let first = get_cached_usage(std::slice::from_ref(&base), &session_id, None, None)?;
+        assert_eq!(first.3.len(), 1);
+        fs::remove_dir_all(base.join("projects"))?;
+        let cached = get_cached_usage(std::slice::from_ref(&base), &session_id, None, None)?;
+        assert_eq!(cached.3.len(), 1);
+        assert!((cached.2 - first.2).abs() < 1e-12);
+        // Another session never sees this one's result
+        let other = get_cached_usage(std::slice::from_ref(&base), "other-session", None, None)?;
+        assert!(other.3.is_empty());
+        // Nor does a scan under a different lookback
+        unsafe { env::set_var("CLAUDE_SCAN_LOOKBACK_HOURS", "24") };
+        let narrower = get_cached_usage(&[base], &session_id, None, None)?;
+        assert!(narrower.3.is_empty());
+        unsafe { env::remove_var("CLAUDE_SCAN_LOOKBACK_HOURS") };
+
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+        Ok(())
+    }
 }
@@ -1,14 +1,43 @@
 use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread::JoinHandle;
 use std::time::Duration;
 
+use crate::cli::SubscriptionArg;
+use crate::provenance::{CacheLayer, record_cache};
+use crate::utils::{key_fingerprint, run_with_timeout};
+
 const USAGE_ENDPOINT: &str = "https://api.anthropic.com/api/oauth/usage";
+const PROFILE_ENDPOINT: &str = "https://api.anthropic.com/api/oauth/profile";
 const ANTHROPIC_API_HOST: &str = "api.anthropic.com";
-const CACHE_TTL_SECONDS: i64 = 300;
 const NEGATIVE_CACHE_TTL_SECONDS: i64 = 120;
+/// Each consecutive failed refresh doubles the negative TTL up to this cap
+const MAX_NEGATIVE_CACHE_TTL_SECONDS: i64 = 1800;
+/// Attempts per refresh; only transport errors and 5xx are retried
+const FETCH_ATTEMPTS: u32 = 3;
+/// First retry waits about this long, doubling per attempt, plus jitter
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+/// A refresh, retries included, never runs longer than this; each attempt
+/// gets what is left of it as its timeout
+const FETCH_BUDGET: Duration = Duration::from_secs(8);
+/// A locked Secret Service collection can prompt; never wait on it for long.
+/// PowerShell compiles the Credential Manager shim on each start. The macOS
+/// Keychain's "allow access" prompt waits on the user, so it is never cut off.
+const CREDENTIAL_STORE_TIMEOUT: Option<Duration> = if cfg!(target_os = "macos") {
+    None
+} else if cfg!(windows) {
+    Some(Duration::from_secs(5))
+} else {
+    Some(Duration::from_secs(2))
+};
+/// Outlasts [`FETCH_BUDGET`], so the lock holds until the refresh settles
 const FETCH_LOCK_TTL_SECONDS: i64 = 10;
 const ANTHROPIC_BETA: &str = "oauth-2025-04-20";
 /// Extra CA bundle path, matching Claude Code's proxy CA env var. When set, the
@@ -17,6 +46,134 @@ const ANTHROPIC_BETA: &str = "oauth-2025-04-20";
 const EXTRA_CA_ENV: &str = "NODE_EXTRA_CA_CERTS";
 const API_CACHE_KEY: &str = "oauth_usage_summary";
 const NEGATIVE_CACHE_KEY: &str = "oauth_usage_negative";
+/// Metadata key counting refreshes failed in a row, the circuit breaker state
+const FAILURES_KEY: &str = "usage_api_consecutive_failures";
+/// Metadata key holding `<status>:<token fingerprint>` for the token last
+/// rejected; emptied by the next successful refresh
+const TOKEN_INVALID_KEY: &str = "usage_api_token_invalid";
+/// A rejected token is not retried for this long; a different token (after
+/// logging in again) is tried right away
+const TOKEN_INVALID_COOLDOWN_SECONDS: i64 = 900;
+const PROFILE_CACHE_KEY: &str = "oauth_profile";
+/// An organization's plan rarely changes; the profile is refetched daily
+const PROFILE_CACHE_TTL_SECONDS: i64 = 86_400;
+/// A failed profile fetch is retried no sooner than this
+const PROFILE_RETRY_SECONDS: i64 = 600;
+
+/// `--usage-api-proxy`, taking precedence over the proxy environment
+static PROXY: OnceCell<ureq::Proxy> = OnceCell::new();
+
+/// Refetches started when a render served an expired cache entry
+static BACKGROUND: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// A Claude account from the config `[accounts]` section: a label and the
+/// config dir (`CLAUDE_CONFIG_DIR`) its credentials live under.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageAccount {
+    pub label: String,
+    pub claude_dir: PathBuf,
+}
+
+/// The account whose usage is fetched: `selected` by label, else the one whose
+/// dir is among the Claude data paths in use. `None` without `[accounts]`, or
+/// when nothing matches, which keeps the single-account behavior.
+pub fn resolve_account(
+    accounts: &[(String, PathBuf)],
+    selected: Option<&str>,
+    claude_paths: &[PathBuf],
+) -> Option<UsageAccount> {
+    let same_dir = |a: &Path, b: &Path| {
+        a == b || matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+    };
+    let (label, claude_dir) = match selected {
+        Some(selected) => accounts.iter().find(|(label, _)| label == selected)?,
+        None => claude_paths.iter().find_map(|path| {
+            accounts
+                .iter()
+                .find(|(_, claude_dir)| same_dir(claude_dir, path))
+        })?,
+    };
+    Some(UsageAccount {
+        label: label.clone(),
+        claude_dir: claude_dir.clone(),
+    })
+}
+
+/// Where the OAuth token is read from, and which cache entries hold the
+/// usage it fetches. Built once per render: the token and its rejection are
+/// looked up on first use and shared by every caller after that.
+#[derive(Debug, Clone)]
+pub struct TokenSource {
+    claude_paths: Vec<PathBuf>,
+    account: Option<UsageAccount>,
+    token: OnceCell<Option<String>>,
+    rejection: OnceCell<Option<(u16, i64)>>,
+}
+
+impl TokenSource {
+    /// Credentials of `account` when given, else whichever the environment
+    /// and Claude data paths lead to
+    pub fn new(claude_paths: &[PathBuf], account: Option<&UsageAccount>) -> Self {
+        TokenSource {
+            claude_paths: claude_paths.to_vec(),
+            account: account.cloned(),
+            token: OnceCell::new(),
+            rejection: OnceCell::new(),
+        }
+    }
+
+    /// The OAuth token, read from the environment or credential store once
+    fn token(&self) -> Option<&str> {
+        self.token.get_or_init(|| find_oauth_token(self)).as_deref()
+    }
+
+    /// Status the token in use was rejected with and when, while the
+    /// cooldown lasts. The token is only read once a recent rejection is on
+    /// record.
+    fn rejection(&self) -> Option<(u16, i64)> {
+        *self.rejection.get_or_init(|| {
+            let entry = crate::db::load_metadata(&self.key(TOKEN_INVALID_KEY))
+                .ok()
+                .flatten()?;
+            let (status, fingerprint) = entry.value.split_once(':')?;
+            let rejected_at = entry.updated_at?;
+            if Utc::now().timestamp() - rejected_at >= TOKEN_INVALID_COOLDOWN_SECONDS {
+                return None;
+            }
+            (key_fingerprint(self.token()?) == fingerprint)
+                .then_some((status.parse().ok()?, rejected_at))
+        })
+    }
+
+    /// `base`, suffixed with the account label so accounts never share entries
+    fn key(&self, base: &str) -> String {
+        match &self.account {
+            Some(account) => format!("{base}:{}", account.label),
+            None => base.to_string(),
+        }
+    }
+
+    /// Credential files to try: the account's dir, else every Claude data path
+    fn credential_dirs(&self) -> Vec<PathBuf> {
+        match &self.account {
+            Some(account) => vec![account.claude_dir.clone()],
+            None => self.claude_paths.clone(),
+        }
+    }
+
+    /// Config dir Claude Code keyed its credential store entry by; `None`
+    /// for the default `~/.claude`, whose entry has no suffix
+    fn config_dir(&self) -> Option<String> {
+        match &self.account {
+            Some(account) => {
+                let default = directories::BaseDirs::new().map(|b| b.home_dir().join(".claude"));
+                (default.as_deref() != Some(account.claude_dir.as_path()))
+                    .then(|| account.claude_dir.display().to_string())
+            }
+            None => env::var("CLAUDE_CONFIG_DIR").ok(),
+        }
+    }
+}
 
 /// Check if we're using direct Anthropic API with a Claude model.
 /// Returns false if:
@@ -46,17 +203,31 @@ pub fn is_direct_claude_api(model_id: Option<&str>) -> bool {
     true
 }
 
+/// Install the `--usage-api-proxy` URL (`http://`, `https://`, `socks4://`,
+/// `socks4a://`, `socks5://`, or `socks5h://`) for this process.
+pub fn set_proxy(url: &str) -> Result<(), ureq::Error> {
+    let _ = PROXY.set(ureq::Proxy::new(url)?);
+    Ok(())
+}
+
+/// Proxy the usage call goes through: the configured one, else the first of
+/// `ALL_PROXY`, `HTTPS_PROXY`, and `HTTP_PROXY` (any case) that parses.
+fn usage_proxy() -> Option<ureq::Proxy> {
+    PROXY.get().cloned().or_else(ureq::Proxy::try_from_env)
+}
+
 /// Where the OAuth usage ("stats") API request egresses: straight to Anthropic,
 /// or through an HTTP/HTTPS proxy resolved from the environment.
 ///
-/// Resolution mirrors the real request path. It uses ureq's own
-/// `Proxy::try_from_env` (the same value the request agent is built from) and
-/// `NO_PROXY` matching, so the reported route is exactly what the call takes.
+/// Resolution mirrors the real request path. It uses the same proxy the
+/// request agent is built from (`--usage-api-proxy`, else ureq's
+/// `Proxy::try_from_env`) and `NO_PROXY` matching, so the reported route is
+/// exactly what the call takes.
 /// Proxy credentials are never included in any field.
 #[derive(Debug, Clone, Serialize)]
 pub struct UsageEgress {
-    /// Human-readable route with credentials masked, e.g. `direct` or
-    /// `proxy http://127.0.0.1:8080 (auth)`.
+    /// Human-readable route with credentials masked, e.g. `direct`,
+    /// `proxy http://127.0.0.1:8080 (auth)`, or `proxy socks5://127.0.0.1:1080 (config)`.
     pub route: String,
     /// True when an environment proxy carries the request.
     pub via_proxy: bool,
@@ -79,6 +250,10 @@ fn extra_ca_path() -> Option<String> {
 
 /// Resolve the egress route for the usage endpoint from the current environment.
 pub fn resolve_usage_egress() -> UsageEgress {
+    egress_for(usage_proxy())
+}
+
+fn egress_for(proxy: Option<ureq::Proxy>) -> UsageEgress {
     let extra_ca = extra_ca_path();
     let direct = |route: &str, bypass: bool| UsageEgress {
         route: route.to_string(),
@@ -92,7 +267,7 @@ pub fn resolve_usage_egress() -> UsageEgress {
         return direct("direct", false);
     };
 
-    match ureq::Proxy::try_from_env() {
+    match proxy {
         None => direct("direct", false),
         Some(proxy) if proxy.is_no_proxy(&endpoint) => direct("direct (NO_PROXY bypass)", true),
         Some(proxy) => {
@@ -103,8 +278,9 @@ pub fn resolve_usage_egress() -> UsageEgress {
             } else {
                 ""
             };
+            let source = if proxy.is_from_env() { "" } else { " (config)" };
             UsageEgress {
-                route: format!("proxy {scheme}://{origin}{auth}"),
+                route: format!("proxy {scheme}://{origin}{auth}{source}"),
                 via_proxy: true,
                 proxy_origin: Some(origin),
                 no_proxy_bypass: false,
@@ -164,30 +340,44 @@ fn usage_root_certs() -> Option<ureq::tls::RootCerts> {
 
 #[derive(Debug, Clone, Serialize)]
 pub struct UsageApiHealth {
+    /// `[accounts]` label the checks ran for
+    pub account: Option<String>,
     pub direct_claude_api: bool,
     pub oauth_token_present: bool,
     pub fresh_cache_present: bool,
     pub stale_cache_present: bool,
     pub negative_cache_active: bool,
+    /// Refreshes failed in a row; each one doubles the negative cache TTL
+    pub consecutive_failures: u32,
+    /// HTTP status the token was rejected with, during the retry cooldown
+    pub token_rejected: Option<u16>,
     pub egress: UsageEgress,
 }
 
-pub fn inspect_usage_api(claude_paths: &[PathBuf], model_id: Option<&str>) -> UsageApiHealth {
+pub fn inspect_usage_api(
+    claude_paths: &[PathBuf],
+    model_id: Option<&str>,
+    account: Option<&UsageAccount>,
+) -> UsageApiHealth {
+    let source = TokenSource::new(claude_paths, account);
     UsageApiHealth {
+        account: account.map(|a| a.label.clone()),
         direct_claude_api: is_direct_claude_api(model_id),
-        oauth_token_present: find_oauth_token(claude_paths).is_some(),
-        fresh_cache_present: crate::db::get_api_cache(API_CACHE_KEY)
+        oauth_token_present: source.token().is_some(),
+        fresh_cache_present: crate::db::get_api_cache(&source.key(API_CACHE_KEY))
             .ok()
             .flatten()
             .is_some(),
-        stale_cache_present: crate::db::get_stale_api_cache(API_CACHE_KEY)
+        stale_cache_present: crate::db::get_stale_api_cache(&source.key(API_CACHE_KEY))
             .ok()
             .flatten()
             .is_some(),
-        negative_cache_active: crate::db::get_api_cache(NEGATIVE_CACHE_KEY)
+        negative_cache_active: crate::db::get_api_cache(&source.key(NEGATIVE_CACHE_KEY))
             .ok()
             .flatten()
             .is_some(),
+        consecutive_failures: consecutive_failures(&source),
+        token_rejected: source.rejection().map(|(status, _)| status),
         egress: resolve_usage_egress(),
     }
 }
@@ -267,94 +457,346 @@ struct UsageResponseDto {
     extra_usage: Option<ExtraUsageDto>,
 }
 
-pub fn get_usage_summary(claude_paths: &[PathBuf], model_id: Option<&str>) -> Option<UsageSummary> {
+/// Usage limits for the credentials `source` leads to
+pub fn get_usage_summary(source: &TokenSource, model_id: Option<&str>) -> Option<UsageSummary> {
     // Subsystem-level disable now lives at main.rs (subsystems.usage_api). We
     // keep the direct-API guard here because it depends on env/model details
     // that the gate caller doesn't know.
     if !is_direct_claude_api(model_id) {
         return None;
     }
+    cached_usage_summary(source, fetch_usage_summary)
+}
 
+/// Serve the summary from the db cache. Once it expires, the render that wins
+/// the fetch lock serves the expired summary and refetches on a worker it
+/// waits for only after printing (see [`finish_background`]); a render with
+/// nothing cached fetches inline, with a single attempt.
+fn cached_usage_summary(
+    source: &TokenSource,
+    fetch: fn(&TokenSource, u32) -> Option<UsageSummary>,
+) -> Option<UsageSummary> {
+    let negative_key = source.key(NEGATIVE_CACHE_KEY);
     // Try to get from persistent SQLite cache first
-    if let Ok(Some(cached_json)) = crate::db::get_api_cache(API_CACHE_KEY) {
+    if let Ok(Some((cached_json, fetched_at))) =
+        crate::db::get_api_cache_entry(&source.key(API_CACHE_KEY))
+    {
         if let Ok(summary) = serde_json::from_str::<UsageSummary>(&cached_json) {
+            let age = Utc::now().timestamp() - fetched_at;
+            record_cache("usage_api", CacheLayer::Sqlite, Some(age));
             return Some(summary);
         }
     }
 
     // If API recently failed (429/error), don't retry -- serve stale data
-    if let Ok(Some(_)) = crate::db::get_api_cache(NEGATIVE_CACHE_KEY) {
-        return stale_fallback();
+    if let Ok(Some(_)) = crate::db::get_api_cache(&negative_key) {
+        return stale_fallback(source);
+    }
+    // A rejected token stays rejected until the user logs in again
+    if source.rejection().is_some() {
+        return stale_fallback(source);
     }
 
     // Acquire fetch lock to prevent concurrent API calls across sessions.
     // Only the first process wins; others get stale data instead of racing.
-    let got_lock = crate::db::try_set_api_cache(NEGATIVE_CACHE_KEY, "f", FETCH_LOCK_TTL_SECONDS)
-        .unwrap_or(false);
+    let got_lock =
+        crate::db::try_set_api_cache(&negative_key, "f", FETCH_LOCK_TTL_SECONDS).unwrap_or(false);
     if !got_lock {
-        return stale_fallback();
+        return stale_fallback(source);
+    }
+
+    // Expired but still cached: never keep the render waiting on the network
+    if let Some(summary) = expired_summary(source) {
+        let source = source.clone();
+        spawn_background(move || {
+            let _ = refresh_usage_summary(&source, FETCH_ATTEMPTS, fetch);
+        });
+        return Some(summary);
     }
 
-    // Cache miss or invalid - fetch from API
-    let summary = fetch_usage_summary(claude_paths);
+    refresh_usage_summary(source, 1, fetch).or_else(|| stale_fallback(source))
+}
 
-    match summary {
+/// Fetch from the API in up to `attempts` tries and store the answer,
+/// releasing the fetch lock. A failure upgrades the lock to a negative entry
+/// instead.
+fn refresh_usage_summary(
+    source: &TokenSource,
+    attempts: u32,
+    fetch: fn(&TokenSource, u32) -> Option<UsageSummary>,
+) -> Option<UsageSummary> {
+    let negative_key = source.key(NEGATIVE_CACHE_KEY);
+    let failures_key = source.key(FAILURES_KEY);
+    match fetch(source, attempts) {
         Some(s) => {
             // Store in persistent cache; clear the fetch lock
             if let Ok(json) = serde_json::to_string(&s) {
-                let _ = crate::db::set_api_cache(API_CACHE_KEY, &json, CACHE_TTL_SECONDS);
+                let ttl = crate::db::cache_ttls().usage_api_seconds;
+                let _ = crate::db::set_api_cache(&source.key(API_CACHE_KEY), &json, ttl);
+            }
+            let account = source.account.as_ref().map_or("", |a| a.label.as_str());
+            let _ = crate::db::record_usage_snapshot(&s.snapshot(account, Utc::now()));
+            let _ = crate::db::set_api_cache(&negative_key, "", 0);
+            if consecutive_failures(source) > 0 {
+                let _ = crate::db::store_metadata(&failures_key, "0");
+            }
+            let rejected_key = source.key(TOKEN_INVALID_KEY);
+            if crate::db::load_metadata(&rejected_key)
+                .ok()
+                .flatten()
+                .is_some_and(|entry| !entry.value.is_empty())
+            {
+                let _ = crate::db::store_metadata(&rejected_key, "");
             }
-            let _ = crate::db::set_api_cache(NEGATIVE_CACHE_KEY, "", 0);
+            record_cache("usage_api", CacheLayer::Api, None);
             Some(s)
         }
         None => {
-            // Upgrade fetch lock to full negative cache to prevent retry storm
-            let _ = crate::db::set_api_cache(NEGATIVE_CACHE_KEY, "1", NEGATIVE_CACHE_TTL_SECONDS);
-            stale_fallback()
+            // Upgrade fetch lock to full negative cache to prevent retry storm,
+            // backing off further while the API keeps failing
+            let failures = consecutive_failures(source).saturating_add(1);
+            let _ = crate::db::store_metadata(&failures_key, &failures.to_string());
+            let _ = crate::db::set_api_cache(&negative_key, "1", negative_ttl(failures));
+            None
         }
     }
 }
 
-/// Return the last cached API data (even if expired), marked as stale
-fn stale_fallback() -> Option<UsageSummary> {
-    if let Ok(Some(json)) = crate::db::get_stale_api_cache(API_CACHE_KEY) {
-        if let Ok(mut summary) = serde_json::from_str::<UsageSummary>(&json) {
-            summary.stale = true;
-            return Some(summary);
+/// Refreshes failed in a row, from the db metadata
+fn consecutive_failures(source: &TokenSource) -> u32 {
+    crate::db::load_metadata(&source.key(FAILURES_KEY))
+        .ok()
+        .flatten()
+        .and_then(|entry| entry.value.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Remember that the endpoint rejected `token` with `status`
+fn store_token_rejection(source: &TokenSource, token: &str, status: u16) {
+    let marker = format!("{status}:{}", key_fingerprint(token));
+    let _ = crate::db::store_metadata(&source.key(TOKEN_INVALID_KEY), &marker);
+}
+
+/// Why the usage limits are missing or stale
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageApiError {
+    /// `token_invalid`: the endpoint answered 401 or 403
+    pub kind: &'static str,
+    pub status: u16,
+    /// When the endpoint is tried again
+    pub retry_at: Option<DateTime<Utc>>,
+}
+
+/// The usage endpoint's last rejection of the token in use, while retries
+/// are held off. `None` when the token was accepted or nothing was tried.
+pub fn usage_api_error(source: &TokenSource, model_id: Option<&str>) -> Option<UsageApiError> {
+    if !is_direct_claude_api(model_id) {
+        return None;
+    }
+    let (status, rejected_at) = source.rejection()?;
+    Some(UsageApiError {
+        kind: "token_invalid",
+        status,
+        retry_at: DateTime::from_timestamp(rejected_at + TOKEN_INVALID_COOLDOWN_SECONDS, 0),
+    })
+}
+
+/// Negative cache TTL after `failures` consecutive failed refreshes
+fn negative_ttl(failures: u32) -> i64 {
+    let doublings = failures.saturating_sub(1).min(16);
+    (NEGATIVE_CACHE_TTL_SECONDS << doublings).min(MAX_NEGATIVE_CACHE_TTL_SECONDS)
+}
+
+/// Wait before retry `attempt` (1-based): the doubled base delay plus up to
+/// half of it again as jitter, so sessions that failed together spread out.
+fn retry_delay(attempt: u32, jitter_seed: u32) -> Duration {
+    let base = RETRY_BASE_DELAY * (1 << attempt.saturating_sub(1).min(8));
+    let jitter_ms = u64::from(jitter_seed) % (base.as_millis() as u64 / 2 + 1);
+    base + Duration::from_millis(jitter_ms)
+}
+
+fn spawn_background(work: impl FnOnce() + Send + 'static) {
+    let handle = std::thread::spawn(work);
+    BACKGROUND
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(handle);
+}
+
+/// Wait for refetches started behind expired cache entries so their results
+/// reach the cache for the next render. Call after the statusline has been
+/// printed and stdout released ([`crate::utils::release_stdout`]); with
+/// retries a refetch can take several seconds.
+pub fn finish_background() {
+    let handles = std::mem::take(&mut *BACKGROUND.lock().unwrap_or_else(|e| e.into_inner()));
+    for handle in handles {
+        let _ = handle.join();
+    }
+}
+
+/// One API's answers in the db cache, for [`cached_fetch`]
+pub(crate) struct CachedApi {
+    /// Provenance name, e.g. `admin_api`
+    pub name: &'static str,
+    pub key: String,
+    pub ttl_seconds: i64,
+    /// How long a failed fetch holds off the next one
+    pub retry_seconds: i64,
+}
+
+impl CachedApi {
+    /// Fetch lock, upgraded to the retry hold-off when a fetch fails
+    fn lock_key(&self) -> String {
+        format!("{}:lock", self.key)
+    }
+
+    /// The last cached answer, even if expired
+    fn expired<T: DeserializeOwned>(&self) -> Option<T> {
+        let (json, fetched_at) = crate::db::get_stale_api_cache_entry(&self.key).ok()??;
+        let value = serde_json::from_str(&json).ok()?;
+        let age = Utc::now().timestamp() - fetched_at;
+        record_cache(self.name, CacheLayer::SqliteStale, Some(age));
+        Some(value)
+    }
+
+    /// Fetch and store the answer, releasing the fetch lock; a failure keeps
+    /// the lock for `retry_seconds` instead
+    fn refresh<T: Serialize>(&self, fetch: impl FnOnce() -> Option<T>) -> Option<T> {
+        let Some(value) = fetch() else {
+            let _ = crate::db::set_api_cache(&self.lock_key(), "1", self.retry_seconds);
+            return None;
+        };
+        if let Ok(json) = serde_json::to_string(&value) {
+            let _ = crate::db::set_api_cache(&self.key, &json, self.ttl_seconds);
         }
+        let _ = crate::db::set_api_cache(&self.lock_key(), "", 0);
+        record_cache(self.name, CacheLayer::Api, None);
+        Some(value)
     }
-    None
 }
 
-fn fetch_usage_summary(claude_paths: &[PathBuf]) -> Option<UsageSummary> {
-    let token = find_oauth_token(claude_paths)?;
-    let mut config = ureq::Agent::config_builder().timeout_global(Some(Duration::from_secs(5)));
+/// Serve `api` from the db cache. Once it expires, the render that wins the
+/// fetch lock serves the expired answer and refetches on a worker it waits
+/// for only after printing (see [`finish_background`]); a render with nothing
+/// cached fetches inline. The rest keep the expired answer meanwhile.
+pub(crate) fn cached_fetch<T>(
+    api: CachedApi,
+    fetch: impl FnOnce() -> Option<T> + Send + 'static,
+) -> Option<T>
+where
+    T: Serialize + DeserializeOwned + Send + 'static,
+{
+    if let Ok(Some((json, fetched_at))) = crate::db::get_api_cache_entry(&api.key)
+        && let Ok(value) = serde_json::from_str::<T>(&json)
+    {
+        let age = Utc::now().timestamp() - fetched_at;
+        record_cache(api.name, CacheLayer::Sqlite, Some(age));
+        return Some(value);
+    }
+    let got_lock =
+        crate::db::try_set_api_cache(&api.lock_key(), "1", FETCH_LOCK_TTL_SECONDS).unwrap_or(false);
+    if !got_lock {
+        return api.expired();
+    }
+    if let Some(value) = api.expired() {
+        spawn_background(move || {
+            let _ = api.refresh(fetch);
+        });
+        return Some(value);
+    }
+    api.refresh(fetch)
+}
+
+impl UsageSummary {
+    /// The utilizations kept in the db's limit history for `account` (the
+    /// `[accounts]` label, or `""` without accounts).
+    pub fn snapshot(&self, account: &str, fetched_at: DateTime<Utc>) -> crate::db::UsageSnapshot {
+        crate::db::UsageSnapshot {
+            account: account.to_string(),
+            fetched_at: fetched_at.timestamp(),
+            five_hour: self.window.utilization,
+            five_hour_resets_at: self.window.resets_at.map(|t| t.timestamp()),
+            seven_day: self.seven_day.utilization,
+            seven_day_resets_at: self.seven_day.resets_at.map(|t| t.timestamp()),
+            seven_day_opus: self.seven_day_opus.utilization,
+            seven_day_sonnet: self.seven_day_sonnet.utilization,
+        }
+    }
+}
+
+/// Return the last cached API data (even if expired), marked as stale
+fn stale_fallback(source: &TokenSource) -> Option<UsageSummary> {
+    let mut summary = expired_summary(source)?;
+    summary.stale = true;
+    Some(summary)
+}
+
+/// The last cached API data, even if expired
+fn expired_summary(source: &TokenSource) -> Option<UsageSummary> {
+    let (json, fetched_at) =
+        crate::db::get_stale_api_cache_entry(&source.key(API_CACHE_KEY)).ok()??;
+    let summary = serde_json::from_str::<UsageSummary>(&json).ok()?;
+    let age = Utc::now().timestamp() - fetched_at;
+    record_cache("usage_api", CacheLayer::SqliteStale, Some(age));
+    Some(summary)
+}
+
+/// Agent for the statusline's API calls, through the configured proxy and CA
+pub(crate) fn api_agent() -> ureq::Agent {
+    let mut config = ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(5)))
+        .proxy(usage_proxy());
     // Honor NODE_EXTRA_CA_CERTS so the call works behind a TLS-intercepting proxy.
     if let Some(roots) = usage_root_certs() {
         config = config.tls_config(ureq::tls::TlsConfig::builder().root_certs(roots).build());
     }
-    let agent: ureq::Agent = config.build().into();
-
-    let response = agent
-        .get(USAGE_ENDPOINT)
-        .header("Authorization", &format!("Bearer {}", token))
-        .header("Accept", "application/json")
-        .header("anthropic-beta", ANTHROPIC_BETA)
-        .call();
+    config.build().into()
+}
 
-    let mut response = match response {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("Usage API error: {}", e);
+/// Up to `attempts` tries, together never longer than [`FETCH_BUDGET`]
+fn fetch_usage_summary(source: &TokenSource, attempts: u32) -> Option<UsageSummary> {
+    let token = source.token()?;
+    let agent = api_agent();
+
+    let started = std::time::Instant::now();
+    let mut attempt = 1;
+    let mut response = loop {
+        let remaining = FETCH_BUDGET.saturating_sub(started.elapsed());
+        let response = agent
+            .get(USAGE_ENDPOINT)
+            .header("Authorization", &format!("Bearer {}", token))
+            .header("Accept", "application/json")
+            .header("anthropic-beta", ANTHROPIC_BETA)
+            .config()
+            .timeout_global(Some(remaining))
+            .build()
+            .call();
+
+        let error = match response {
+            Ok(r) if r.status() == 200 => break r,
+            Ok(r) => {
+                eprintln!("Usage API HTTP {}", r.status());
+                return None;
+            }
+            Err(e) => e,
+        };
+        eprintln!("Usage API error: {}", error);
+        if let ureq::Error::StatusCode(status @ (401 | 403)) = error {
+            store_token_rejection(source, token, status);
+            return None;
+        }
+        // 4xx (expired token, 429) will not clear up within a render
+        if matches!(error, ureq::Error::StatusCode(code) if code < 500) || attempt >= attempts {
+            return None;
+        }
+        let delay = retry_delay(attempt, started.elapsed().subsec_nanos());
+        if started.elapsed() + delay >= FETCH_BUDGET {
             return None;
         }
+        std::thread::sleep(delay);
+        attempt += 1;
     };
 
-    if response.status() != 200 {
-        eprintln!("Usage API HTTP {}", response.status());
-        return None;
-    }
-
     let dto: UsageResponseDto = response.body_mut().read_json().ok()?;
     Some(UsageSummary {
         window: dto.five_hour.map(UsageLimit::from).unwrap_or_default(),
@@ -397,9 +839,114 @@ impl From<UsageLimitDto> for UsageLimit {
     }
 }
 
-fn find_oauth_token(claude_paths: &[PathBuf]) -> Option<String> {
-    // Check environment variables first
-    for env in ["CLAUDE_CODE_OAUTH_TOKEN", "ANTHROPIC_AUTH_TOKEN"] {
+/// The organization behind the OAuth token, from the profile endpoint
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OAuthProfile {
+    /// e.g. `claude_pro`, `claude_max`, `claude_team`, `claude_enterprise`
+    pub organization_type: Option<String>,
+    /// e.g. `default_claude_max_5x`, `default_claude_max_20x`
+    pub rate_limit_tier: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileResponseDto {
+    #[serde(default)]
+    organization: Option<OAuthProfile>,
+}
+
+impl OAuthProfile {
+    /// The subscription plan, for `--subscription` when it is not set. Team and
+    /// Enterprise seats have no list price and resolve to `None`.
+    pub fn subscription(&self) -> Option<SubscriptionArg> {
+        let tier = self.rate_limit_tier.as_deref().unwrap_or("");
+        if tier.contains("max_20x") {
+            Some(SubscriptionArg::Max20x)
+        } else if tier.contains("max_5x") {
+            Some(SubscriptionArg::Max5x)
+        } else if self.organization_type.as_deref() == Some("claude_pro") {
+            Some(SubscriptionArg::Pro)
+        } else {
+            None
+        }
+    }
+
+    /// Short plan name for the provider segment
+    pub fn plan_label(&self) -> Option<&'static str> {
+        Some(match self.organization_type.as_deref()? {
+            "claude_pro" => "Pro",
+            "claude_max" => match self.subscription() {
+                Some(SubscriptionArg::Max20x) => "Max 20x",
+                Some(SubscriptionArg::Max5x) => "Max 5x",
+                _ => "Max",
+            },
+            "claude_team" => "Team",
+            "claude_enterprise" => "Enterprise",
+            _ => return None,
+        })
+    }
+}
+
+/// Organization type and rate-limit tier for the credentials `source` leads
+/// to. Cached for a day; an expired profile is served while it is refetched.
+pub fn get_oauth_profile(source: &TokenSource, model_id: Option<&str>) -> Option<OAuthProfile> {
+    if !is_direct_claude_api(model_id) {
+        return None;
+    }
+    cached_oauth_profile(source, fetch_oauth_profile)
+}
+
+fn cached_oauth_profile(
+    source: &TokenSource,
+    fetch: fn(&TokenSource) -> Option<OAuthProfile>,
+) -> Option<OAuthProfile> {
+    let api = CachedApi {
+        name: "oauth_profile",
+        key: source.key(PROFILE_CACHE_KEY),
+        ttl_seconds: PROFILE_CACHE_TTL_SECONDS,
+        retry_seconds: PROFILE_RETRY_SECONDS,
+    };
+    let source = source.clone();
+    cached_fetch(api, move || fetch(&source))
+}
+
+/// One attempt per retry interval; the profile only labels the statusline
+fn fetch_oauth_profile(source: &TokenSource) -> Option<OAuthProfile> {
+    let token = source.token()?;
+    let response = api_agent()
+        .get(PROFILE_ENDPOINT)
+        .header("Authorization", &format!("Bearer {}", token))
+        .header("Accept", "application/json")
+        .header("anthropic-beta", ANTHROPIC_BETA)
+        .call();
+    let mut response = match response {
+        Ok(r) if r.status() == 200 => r,
+        Ok(r) => {
+            eprintln!("Profile API HTTP {}", r.status());
+            return None;
+        }
+        Err(e) => {
+            eprintln!("Profile API error: {}", e);
+            return None;
+        }
+    };
+    parse_profile(&response.body_mut().read_to_string().ok()?)
+}
+
+fn parse_profile(body: &str) -> Option<OAuthProfile> {
+    serde_json::from_str::<ProfileResponseDto>(body)
+        .ok()?
+        .organization
+}
+
+fn find_oauth_token(source: &TokenSource) -> Option<String> {
+    // Check environment variables first; they carry no account, so a
+    // configured account reads only its own credentials
+    let env_vars: &[&str] = if source.account.is_some() {
+        &[]
+    } else {
+        &["CLAUDE_CODE_OAUTH_TOKEN", "ANTHROPIC_AUTH_TOKEN"]
+    };
+    for env in env_vars {
         if let Ok(val) = std::env::var(env) {
             let trimmed = val.trim().to_string();
             if !trimmed.is_empty() {
@@ -411,95 +958,150 @@ fn find_oauth_token(claude_paths: &[PathBuf]) -> Option<String> {
     // macOS: Try Keychain first (credentials stored in Keychain, not file)
     #[cfg(target_os = "macos")]
     {
-        if let Some(token) = read_from_macos_keychain() {
+        if let Some(token) = read_credential_store(source.config_dir().as_deref()) {
             return Some(token);
         }
     }
 
     // Search through all provided claude paths for .credentials.json (Linux/Windows)
-    for base_path in claude_paths {
+    for base_path in source.credential_dirs() {
         let credentials_path = base_path.join(".credentials.json");
-        if let Ok(raw) = fs::read_to_string(&credentials_path) {
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&raw) {
-                if let Some(access) = json
-                    .get("claudeAiOauth")
-                    .and_then(|v| v.get("accessToken"))
-                    .and_then(|v| v.as_str())
-                {
-                    let trimmed = access.trim().to_string();
-                    if !trimmed.is_empty() {
-                        return Some(trimmed);
-                    }
-                }
-            }
+        if crate::utils::known_absent("credentials", &credentials_path) {
+            continue;
+        }
+        let raw = fs::read_to_string(&credentials_path);
+        if matches!(&raw, Err(e) if e.kind() == std::io::ErrorKind::NotFound) {
+            crate::utils::remember_absent("credentials", &credentials_path);
+        }
+        if let Some(token) = raw.ok().as_deref().and_then(access_token) {
+            return Some(token);
+        }
+    }
+
+    // Linux/Windows: installs that keep no .credentials.json use the OS store
+    #[cfg(not(target_os = "macos"))]
+    {
+        if let Some(token) = read_credential_store(source.config_dir().as_deref()) {
+            return Some(token);
         }
     }
 
     None
 }
 
-#[cfg(target_os = "macos")]
-fn read_from_macos_keychain() -> Option<String> {
-    use sha2::{Digest, Sha256};
-    use std::process::Command;
+/// `claudeAiOauth.accessToken` from a stored credentials JSON
+fn access_token(credentials_json: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(credentials_json).ok()?;
+    let token = json
+        .get("claudeAiOauth")
+        .and_then(|v| v.get("accessToken"))
+        .and_then(|v| v.as_str())?
+        .trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
 
-    // Get current username for account field
-    let username = env::var("USER").ok()?;
+/// Service name Claude Code stores its credentials JSON under:
+/// "Claude Code-credentials", plus an 8-char SHA-256 suffix of the config
+/// dir for a non-default one (CLAUDE_CONFIG_DIR)
+fn credential_service_name(config_dir: Option<&str>) -> String {
+    use sha2::{Digest, Sha256};
 
-    // Build service name: "Claude Code-credentials"
-    // If CLAUDE_CONFIG_DIR is set, append 8-char SHA256 suffix
     let mut service_name = "Claude Code-credentials".to_string();
-
-    if let Ok(config_dir) = env::var("CLAUDE_CONFIG_DIR") {
-        let mut hasher = Sha256::new();
-        hasher.update(config_dir.as_bytes());
-        let hash = hasher.finalize();
-        let mut suffix = String::with_capacity(8);
+    if let Some(config_dir) = config_dir {
+        let hash = Sha256::digest(config_dir.as_bytes());
+        service_name.push('-');
         for byte in hash.iter().take(4) {
-            suffix.push_str(&format!("{:02x}", byte));
+            service_name.push_str(&format!("{:02x}", byte));
         }
-        service_name.push('-');
-        service_name.push_str(&suffix);
-    }
-
-    // Query macOS Keychain for the credentials JSON
-    let output = Command::new("security")
-        .args(&[
-            "find-generic-password",
-            "-a",
-            &username, // Account name
-            "-s",
-            &service_name, // Service name
-            "-w",          // Output password only
-        ])
-        .output()
-        .ok()?;
+    }
+    service_name
+}
 
+/// Token from the credentials JSON in the OS credential store: the macOS
+/// Keychain, the Secret Service (GNOME Keyring, KWallet) through
+/// `secret-tool`, or the Windows Credential Manager through PowerShell.
+fn read_credential_store(config_dir: Option<&str>) -> Option<String> {
+    let service_name = credential_service_name(config_dir);
+    let mut cmd = credential_store_command(&service_name)?;
+    let output = match CREDENTIAL_STORE_TIMEOUT {
+        Some(timeout) => run_with_timeout(cmd, timeout)?,
+        None => cmd.output().ok()?,
+    };
     if !output.status.success() {
         return None;
     }
+    access_token(String::from_utf8_lossy(&output.stdout).trim())
+}
 
-    // Parse the JSON payload stored in Keychain
-    let json_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if json_str.is_empty() {
-        return None;
-    }
+#[cfg(target_os = "macos")]
+fn credential_store_command(service_name: &str) -> Option<Command> {
+    let mut cmd = Command::new("security");
+    cmd.args([
+        "find-generic-password",
+        "-a",
+        &env::var("USER").ok()?, // Account name
+        "-s",
+        service_name,
+        "-w", // Output password only
+    ]);
+    Some(cmd)
+}
 
-    // The stored value is the full credentials JSON
-    let json: serde_json::Value = serde_json::from_str(&json_str).ok()?;
-    let access_token = json
-        .get("claudeAiOauth")
-        .and_then(|v| v.get("accessToken"))
-        .and_then(|v| v.as_str())?
-        .trim()
-        .to_string();
+#[cfg(all(unix, not(target_os = "macos")))]
+fn credential_store_command(service_name: &str) -> Option<Command> {
+    let mut cmd = Command::new("secret-tool");
+    cmd.args(["lookup", "service", service_name, "account"])
+        .arg(env::var("USER").ok()?);
+    Some(cmd)
+}
 
-    if access_token.is_empty() {
-        None
-    } else {
-        Some(access_token)
+#[cfg(windows)]
+fn credential_store_command(service_name: &str) -> Option<Command> {
+    // CredReadW through P/Invoke; PowerShell has no built-in cmdlet that
+    // reveals a generic credential's secret
+    const SCRIPT: &str = r#"
+Add-Type -TypeDefinition @'
+using System;
+using System.Runtime.InteropServices;
+public static class ClaudeCredential {
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    struct CREDENTIAL {
+        public int Flags; public int Type; public string TargetName; public string Comment;
+        public System.Runtime.InteropServices.ComTypes.FILETIME LastWritten;
+        public int CredentialBlobSize; public IntPtr CredentialBlob; public int Persist;
+        public int AttributeCount; public IntPtr Attributes; public string TargetAlias;
+        public string UserName;
+    }
+    [DllImport("advapi32.dll", CharSet = CharSet.Unicode, SetLastError = true)]
+    static extern bool CredReadW(string target, int type, int flags, out IntPtr credential);
+    [DllImport("advapi32.dll")]
+    static extern void CredFree(IntPtr credential);
+    public static string Read(string target) {
+        IntPtr ptr;
+        if (!CredReadW(target, 1, 0, out ptr)) { return null; }
+        try {
+            var cred = (CREDENTIAL)Marshal.PtrToStructure(ptr, typeof(CREDENTIAL));
+            var blob = new byte[cred.CredentialBlobSize];
+            Marshal.Copy(cred.CredentialBlob, blob, 0, blob.Length);
+            return System.Text.Encoding.UTF8.GetString(blob);
+        } finally { CredFree(ptr); }
     }
 }
+'@
+$value = [ClaudeCredential]::Read($env:CLAUDE_STATUSLINE_CREDENTIAL_TARGET)
+if ($value -eq $null) { exit 1 }
+[Console]::Out.Write($value)
+"#;
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("CLAUDE_STATUSLINE_CREDENTIAL_TARGET", service_name);
+    Some(cmd)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn credential_store_command(_service_name: &str) -> Option<Command> {
+    None
+}
 
 fn deserialize_optional_datetime<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
 where
@@ -595,6 +1197,28 @@ mod tests {
         assert_eq!(egress.route, "direct (NO_PROXY bypass)");
     }
 
+    #[test]
+    #[serial]
+    fn egress_prefers_configured_socks_proxy() {
+        clear_proxy_env();
+        unsafe { env::set_var("HTTPS_PROXY", "http://127.0.0.1:8080") };
+        let proxy = ureq::Proxy::new("socks5://user:pw@127.0.0.1:1080").expect("socks proxy");
+        let egress = egress_for(Some(proxy));
+        clear_proxy_env();
+
+        assert!(egress.via_proxy);
+        assert_eq!(egress.proxy_origin.as_deref(), Some("127.0.0.1:1080"));
+        assert_eq!(
+            egress.route,
+            "proxy socks5://127.0.0.1:1080 (auth) (config)"
+        );
+
+        unsafe { env::set_var("ALL_PROXY", "socks5h://127.0.0.1:1080") };
+        let egress = resolve_usage_egress();
+        clear_proxy_env();
+        assert_eq!(egress.route, "proxy socks5h://127.0.0.1:1080");
+    }
+
     /// A throwaway self-signed CA (generic `example.com` subject) used to verify
     /// PEM parsing without depending on the host trust store.
     const TEST_CA_PEM: &[u8] = b"-----BEGIN CERTIFICATE-----
@@ -661,6 +1285,244 @@ KYWlso+DPM561Zw=
         }
     }
 
+    #[cfg(feature = "db")]
+    #[test]
+    #[serial]
+    fn expired_summary_is_served_while_refetching() {
+        fn fetch(_: &TokenSource, attempts: u32) -> Option<UsageSummary> {
+            assert_eq!(attempts, FETCH_ATTEMPTS, "refetches behind a summary retry");
+            let mut summary = UsageSummary::default();
+            summary.window.utilization = Some(50.0);
+            Some(summary)
+        }
+        let db_dir = tempfile::tempdir().unwrap();
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_dir.path().join("usage.db")) };
+
+        let mut old = UsageSummary::default();
+        old.window.utilization = Some(10.0);
+        let json = serde_json::to_string(&old).unwrap();
+        crate::db::set_api_cache(API_CACHE_KEY, &json, -1).unwrap();
+
+        let source = TokenSource::new(&[], None);
+        let served = cached_usage_summary(&source, fetch).expect("expired summary");
+        assert_eq!(served.window.utilization, Some(10.0));
+        assert!(!served.stale);
+        finish_background();
+        let fresh = cached_usage_summary(&source, fetch).expect("refetched summary");
+        assert_eq!(fresh.window.utilization, Some(50.0));
+
+        // Another account never sees this one's cache
+        let work = UsageAccount {
+            label: "work".to_string(),
+            claude_dir: PathBuf::from("/nonexistent/.claude-work"),
+        };
+        let work = TokenSource::new(&[], Some(&work));
+        assert_eq!(work.key(API_CACHE_KEY), "oauth_usage_summary:work");
+        assert!(
+            cached_usage_summary(&work, |_, attempts| {
+                assert_eq!(attempts, 1, "a render waiting on the fetch tries once");
+                None
+            })
+            .is_none()
+        );
+
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[test]
+    fn profile_resolves_the_plan() {
+        let profile = parse_profile(
+            r#"{"account": {"uuid": "a1", "email": "dev@example.com"},
+            "organization": {"uuid": "o1", "name": "Example",
+            "organization_type": "claude_max", "rate_limit_tier": "default_claude_max_20x",
+            "billing_type": "stripe_subscription"}}"#,
+        )
+        .expect("organization");
+        assert_eq!(profile.subscription(), Some(SubscriptionArg::Max20x));
+        assert_eq!(profile.plan_label(), Some("Max 20x"));
+
+        let team = OAuthProfile {
+            organization_type: Some("claude_team".to_string()),
+            rate_limit_tier: Some("default_claude_team".to_string()),
+        };
+        assert_eq!(
+            (team.subscription(), team.plan_label()),
+            (None, Some("Team"))
+        );
+        let pro = OAuthProfile {
+            organization_type: Some("claude_pro".to_string()),
+            rate_limit_tier: None,
+        };
+        assert_eq!(pro.subscription(), Some(SubscriptionArg::Pro));
+        assert_eq!(parse_profile(r#"{"account": {}}"#), None);
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    #[serial]
+    fn expired_profile_is_served_while_refetching() {
+        fn fetch(_: &TokenSource) -> Option<OAuthProfile> {
+            Some(OAuthProfile {
+                organization_type: Some("claude_team".to_string()),
+                rate_limit_tier: None,
+            })
+        }
+        let db_dir = tempfile::tempdir().unwrap();
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_dir.path().join("usage.db")) };
+
+        let old = OAuthProfile {
+            organization_type: Some("claude_pro".to_string()),
+            rate_limit_tier: None,
+        };
+        let json = serde_json::to_string(&old).unwrap();
+        crate::db::set_api_cache(PROFILE_CACHE_KEY, &json, -1).unwrap();
+
+        let source = TokenSource::new(&[], None);
+        assert_eq!(cached_oauth_profile(&source, fetch), Some(old));
+        finish_background();
+        let fresh = cached_oauth_profile(&source, |_| None).expect("refetched profile");
+        assert_eq!(fresh.plan_label(), Some("Team"));
+
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    #[serial]
+    fn failed_fetch_is_held_off_for_the_retry_interval() {
+        let db_dir = tempfile::tempdir().unwrap();
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_dir.path().join("usage.db")) };
+        let api = || CachedApi {
+            name: "test_api",
+            key: "test_api:key".to_string(),
+            ttl_seconds: 60,
+            retry_seconds: 600,
+        };
+
+        crate::db::set_api_cache("test_api:key", "1", -1).unwrap();
+        assert_eq!(cached_fetch::<u32>(api(), || None), Some(1));
+        finish_background();
+        // The failure holds further fetches off; the expired answer stays
+        assert_eq!(
+            cached_fetch::<u32>(api(), || panic!("fetched during the hold-off")),
+            Some(1)
+        );
+
+        crate::db::set_api_cache("test_api:key:lock", "", 0).unwrap();
+        assert_eq!(cached_fetch(api(), || Some(2u32)), Some(1));
+        finish_background();
+        assert_eq!(cached_fetch::<u32>(api(), || None), Some(2));
+
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    #[serial]
+    fn rejected_token_is_not_retried_during_cooldown() {
+        fn rejected(_: &TokenSource, _: u32) -> Option<UsageSummary> {
+            panic!("a rejected token must not be retried");
+        }
+        fn accepted(_: &TokenSource, _: u32) -> Option<UsageSummary> {
+            Some(UsageSummary::default())
+        }
+        let db_dir = tempfile::tempdir().unwrap();
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe {
+            env::set_var("CLAUDE_STATUSLINE_DB_PATH", db_dir.path().join("usage.db"));
+            env::set_var("CLAUDE_CODE_OAUTH_TOKEN", "old-token");
+            env::remove_var("ANTHROPIC_BASE_URL");
+        }
+
+        let mut old = UsageSummary::default();
+        old.window.utilization = Some(10.0);
+        let json = serde_json::to_string(&old).unwrap();
+        crate::db::set_api_cache(API_CACHE_KEY, &json, -1).unwrap();
+        let source = TokenSource::new(&[], None);
+        store_token_rejection(&source, "old-token", 401);
+
+        let served = cached_usage_summary(&source, rejected).expect("stale summary");
+        assert!(served.stale);
+        let error = usage_api_error(&source, Some("claude-sonnet-4-6")).expect("error");
+        assert_eq!((error.kind, error.status), ("token_invalid", 401));
+
+        // Logging in again brings a new token, which the next render tries at
+        // once and clears the marker when accepted
+        unsafe { env::set_var("CLAUDE_CODE_OAUTH_TOKEN", "new-token") };
+        let source = TokenSource::new(&[], None);
+        assert_eq!(usage_api_error(&source, Some("claude-sonnet-4-6")), None);
+        assert!(cached_usage_summary(&source, accepted).is_some());
+        finish_background();
+        unsafe { env::set_var("CLAUDE_CODE_OAUTH_TOKEN", "old-token") };
+        let source = TokenSource::new(&[], None);
+        assert_eq!(usage_api_error(&source, Some("claude-sonnet-4-6")), None);
+
+        unsafe {
+            env::remove_var("CLAUDE_STATUSLINE_DB_PATH");
+            env::remove_var("CLAUDE_CODE_OAUTH_TOKEN");
+        }
+    }
+
+    #[test]
+    fn account_resolves_by_label_then_by_claude_dir() {
+        let accounts = vec![
+            ("personal".to_string(), PathBuf::from("/home/u/.claude")),
+            ("work".to_string(), PathBuf::from("/home/u/.claude-work")),
+        ];
+        let paths = [PathBuf::from("/home/u/.claude-work")];
+
+        let selected = resolve_account(&accounts, Some("personal"), &paths).expect("by label");
+        assert_eq!(selected.claude_dir, PathBuf::from("/home/u/.claude"));
+        let matched = resolve_account(&accounts, None, &paths).expect("by dir");
+        assert_eq!(matched.label, "work");
+
+        assert_eq!(resolve_account(&accounts, Some("other"), &paths), None);
+        assert_eq!(
+            resolve_account(&accounts, None, &[PathBuf::from("/srv")]),
+            None
+        );
+        assert_eq!(resolve_account(&[], None, &paths), None);
+    }
+
+    #[test]
+    fn credential_store_entries_match_claude_code() {
+        assert_eq!(credential_service_name(None), "Claude Code-credentials");
+        let suffixed = credential_service_name(Some("/home/u/.claude-work"));
+        assert!(suffixed.starts_with("Claude Code-credentials-"));
+        assert_eq!(suffixed.len(), "Claude Code-credentials-".len() + 8);
+        assert_ne!(
+            suffixed,
+            credential_service_name(Some("/home/u/.claude-home"))
+        );
+
+        assert_eq!(
+            access_token(r#"{"claudeAiOauth":{"accessToken":" sk-ant-oat01 ","expiresAt":1}}"#)
+                .as_deref(),
+            Some("sk-ant-oat01")
+        );
+        assert_eq!(
+            access_token(r#"{"claudeAiOauth":{"accessToken":""}}"#),
+            None
+        );
+        assert_eq!(access_token("not json"), None);
+    }
+
+    #[test]
+    fn failed_refreshes_back_off_to_a_cap() {
+        assert_eq!(negative_ttl(1), NEGATIVE_CACHE_TTL_SECONDS);
+        assert_eq!(negative_ttl(2), NEGATIVE_CACHE_TTL_SECONDS * 2);
+        assert_eq!(negative_ttl(3), NEGATIVE_CACHE_TTL_SECONDS * 4);
+        assert_eq!(negative_ttl(40), MAX_NEGATIVE_CACHE_TTL_SECONDS);
+
+        assert_eq!(retry_delay(1, 0), RETRY_BASE_DELAY);
+        assert_eq!(retry_delay(2, 0), RETRY_BASE_DELAY * 2);
+        let jittered = retry_delay(1, u32::MAX);
+        assert!(jittered > RETRY_BASE_DELAY && jittered <= RETRY_BASE_DELAY * 3 / 2);
+    }
+
     #[test]
     fn usage_response_parses_raw_api_shape() {
         // Mirrors the live /api/oauth/usage body, including null codename
@@ -1,7 +1,13 @@
-use chrono::{DateTime, Local, NaiveDate, Utc};
+use chrono::{DateTime, Local, NaiveDate, Timelike, Utc};
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
 use std::env;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, Instant};
 
 pub const WINDOW_DURATION_HOURS: i64 = 5;
 pub const WINDOW_DURATION_SECONDS: i64 = WINDOW_DURATION_HOURS * 60 * 60;
@@ -44,12 +50,190 @@ pub fn claude_paths(override_env: Option<&str>) -> Vec<PathBuf> {
     paths
 }
 
+/// Run `cmd` with captured output, killing it once `timeout` passes.
+/// Stdout is drained while waiting, so output larger than the pipe buffer
+/// cannot stall the child until the timeout. A grandchild left holding stdout
+/// open counts against the same timeout rather than blocking the caller.
+pub fn run_with_timeout(cmd: Command, timeout: Duration) -> Option<Output> {
+    run_with_input(cmd, None, timeout)
+}
+
+/// [`run_with_timeout`], writing `input` to the child's stdin first; used for
+/// secrets that must not appear in argv.
+pub fn run_with_input(mut cmd: Command, input: Option<&[u8]>, timeout: Duration) -> Option<Output> {
+    let stdin = if input.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    };
+    let mut child = cmd
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    if let (Some(input), Some(mut pipe)) = (input, child.stdin.take()) {
+        // Dropping the pipe afterwards closes stdin
+        let _ = std::io::Write::write_all(&mut pipe, input);
+    }
+    let mut stdout_pipe = child.stdout.take()?;
+    let (sender, receiver) = std::sync::mpsc::channel();
+    // Detached: a reader stuck on a pipe a grandchild still holds is left to
+    // finish on its own once the timeout gives up on it
+    std::thread::spawn(move || {
+        let mut stdout = Vec::new();
+        let read = std::io::Read::read_to_end(&mut stdout_pipe, &mut stdout);
+        let _ = sender.send(read.map(|_| stdout));
+    });
+    let started = Instant::now();
+    loop {
+        match child.try_wait().ok()? {
+            Some(status) => {
+                let stdout = receiver
+                    .recv_timeout(timeout.saturating_sub(started.elapsed()))
+                    .ok()?
+                    .ok()?;
+                return Some(Output {
+                    status,
+                    stdout,
+                    stderr: Vec::new(),
+                });
+            }
+            None if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            None => std::thread::sleep(Duration::from_millis(20)),
+        }
+    }
+}
+
+/// Flush the printed statusline and point stdout at `/dev/null`, so a caller
+/// reading until EOF gets the line now rather than when work left to finish
+/// after printing is done. A no-op off Unix.
+pub fn release_stdout() {
+    let _ = std::io::Write::flush(&mut std::io::stdout());
+    #[cfg(unix)]
+    if let Ok(null) = std::fs::OpenOptions::new().write(true).open("/dev/null") {
+        use std::os::fd::AsRawFd;
+        // SAFETY: dup2 onto fd 1 only swaps what stdout refers to; `null`
+        // stays open until after the call
+        unsafe {
+            libc::dup2(null.as_raw_fd(), libc::STDOUT_FILENO);
+        }
+    }
+}
+
+/// Short digest telling API keys apart in cache keys without storing the key
+/// itself
+pub fn key_fingerprint(key: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(key.as_bytes());
+    digest[..4].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Jira and Linear (`PROJ-123`, `ENG-456`) and GitHub (`#789`) keys. Only
+/// upper case prefixes count, so `fix-2` or `release-2024` are not keys.
+const DEFAULT_ISSUE_PATTERNS: &[&str] = &[r"\b[A-Z][A-Z0-9]{1,9}-[0-9]+\b", r"#[0-9]+"];
+
+/// Issue keys in `branch`, in the order they appear, each once. Uses the
+/// built-in Jira/Linear/GitHub patterns when `patterns` is empty; patterns
+/// that fail to compile are skipped.
+pub fn issue_keys(branch: &str, patterns: &[String]) -> Vec<String> {
+    let sources: Vec<&str> = if patterns.is_empty() {
+        DEFAULT_ISSUE_PATTERNS.to_vec()
+    } else {
+        patterns.iter().map(String::as_str).collect()
+    };
+    let mut found: Vec<(usize, &str)> = sources
+        .iter()
+        .filter_map(|source| regex::Regex::new(source).ok())
+        .flat_map(|re| {
+            re.find_iter(branch)
+                .map(|m| (m.start(), m.as_str()))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    found.sort_by_key(|(start, _)| *start);
+    let mut keys: Vec<String> = Vec::new();
+    for (_, key) in found {
+        if !keys.iter().any(|k| k == key) {
+            keys.push(key.to_string());
+        }
+    }
+    keys
+}
+
+/// Branch globs protected when `--git-protected-branch` is unset
+const DEFAULT_PROTECTED_BRANCHES: &[&str] = &["main", "master"];
+
+/// Whether committing on `branch` deserves a warning: it is the remote
+/// default branch or matches one of `patterns` (`main`/`master` when empty).
+pub fn is_protected_branch(
+    branch: &str,
+    default_branch: Option<&str>,
+    patterns: &[String],
+) -> bool {
+    if default_branch == Some(branch) {
+        return true;
+    }
+    if patterns.is_empty() {
+        DEFAULT_PROTECTED_BRANCHES
+            .iter()
+            .any(|p| glob_match(p, branch))
+    } else {
+        patterns.iter().any(|p| glob_match(p, branch))
+    }
+}
+
+/// Expand `{key}` in an issue URL template; `#789` expands as `789`.
+pub fn issue_url(template: &str, key: &str) -> String {
+    template.replace("{key}", key.trim_start_matches('#'))
+}
+
+/// Files that mark a package root inside a monorepo
+const PACKAGE_ROOT_MARKERS: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "go.mod",
+    "pyproject.toml",
+    "BUILD",
+    "BUILD.bazel",
+];
+
+/// Nearest package root at or above `start` but below the repository `root`,
+/// as a `/`-separated path relative to `root`. `None` when `start` is
+/// outside `root` or no package root sits between them.
+pub fn subproject_dir(root: &Path, start: &Path) -> Option<String> {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+    start.strip_prefix(&root).ok()?;
+    let package = start
+        .ancestors()
+        .take_while(|dir| *dir != root)
+        .find(|dir| PACKAGE_ROOT_MARKERS.iter().any(|m| dir.join(m).is_file()))?;
+    let rel = package.strip_prefix(&root).ok()?;
+    Some(
+        rel.components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
 pub fn deduce_provider_from_model(model_id: &str) -> &'static str {
     let m = model_id.to_lowercase();
+    if m.starts_with("openrouter/") {
+        return "openrouter";
+    }
     if m.contains('@') {
         return "vertex";
     }
-    if m.contains("anthropic") && (m.contains(":") || m.contains("us.")) {
+    if m.starts_with("arn:aws:bedrock:")
+        || (m.contains("anthropic")
+            && (m.contains(":") || m.contains("us.") || m.contains("anthropic.")))
+    {
         return "bedrock";
     }
     "anthropic"
@@ -71,6 +255,17 @@ pub fn format_path(p: &str) -> String {
     p.to_owned()
 }
 
+/// Inverse of `format_path`: expand a leading `~` to the home directory.
+pub fn expand_home(p: &str) -> String {
+    if p == "~" || p.starts_with("~/") {
+        if let Some(b) = directories::BaseDirs::new() {
+            return format!("{}{}", b.home_dir().to_string_lossy(), &p[1..]);
+        }
+    }
+    p.to_owned()
+}
+
+/// Two-decimal amount, already converted (see [`crate::currency::convert`]).
 pub fn format_currency(v: f64) -> String {
     format!("{v:.2}")
 }
@@ -102,12 +297,73 @@ pub fn format_tokens(n: u64) -> String {
     }
 }
 
+/// Floor a timestamp to the beginning of the hour
+pub fn floor_to_hour(ts: DateTime<Utc>) -> DateTime<Utc> {
+    ts.with_minute(0)
+        .and_then(|d| d.with_second(0))
+        .and_then(|d| d.with_nanosecond(0))
+        .unwrap_or(ts)
+}
+
+static NOW_OVERRIDE: OnceCell<DateTime<Utc>> = OnceCell::new();
+
+/// Pin the clock used for window math, daily boundaries, and reset parsing
+/// (`--now`). Only the first call takes effect.
+pub fn set_now_override(now: DateTime<Utc>) {
+    let _ = NOW_OVERRIDE.set(now);
+}
+
+/// Current time, honoring a `--now` override so bug reports about wrong
+/// windows can be replayed deterministically.
+pub fn now_utc() -> DateTime<Utc> {
+    NOW_OVERRIDE.get().copied().unwrap_or_else(Utc::now)
+}
+
+/// Current time in the display timezone (see `--timezone`).
+pub fn now_local() -> DateTime<Local> {
+    now_utc().with_timezone(&Local)
+}
+
 pub fn parse_iso_date(s: &str) -> Option<NaiveDate> {
     let dt: DateTime<Utc> = DateTime::parse_from_rfc3339(s).ok()?.with_timezone(&Utc);
     // compare in local date, like ccusage tables typically show
     Some(dt.with_timezone(&Local).date_naive())
 }
 
+// Zone directories chrono searches when `TZ` names an IANA zone.
+const ZONEINFO_DIRS: [&str; 4] = [
+    "/usr/share/zoneinfo",
+    "/share/zoneinfo",
+    "/etc/zoneinfo",
+    "/usr/share/lib/zoneinfo",
+];
+
+/// Validate a `--timezone` value. Accepts `UTC`/`GMT` and IANA names that
+/// exist in the system zoneinfo database (e.g. `Europe/Berlin`).
+pub fn resolve_timezone(name: &str) -> Option<String> {
+    let name = name.trim();
+    if name.is_empty() || name.contains("..") || name.starts_with('/') {
+        return None;
+    }
+    if name.eq_ignore_ascii_case("utc") || name.eq_ignore_ascii_case("gmt") {
+        return Some("UTC".to_string());
+    }
+    ZONEINFO_DIRS
+        .iter()
+        .any(|dir| Path::new(dir).join(name).is_file())
+        .then(|| name.to_string())
+}
+
+/// Override the timezone used for every `Local` conversion (reset clocks,
+/// daily boundaries, window anchors). chrono's `Local` honors `TZ` on Unix,
+/// so the override is applied once at startup before any time is computed.
+pub fn apply_timezone_override(name: &str) -> anyhow::Result<()> {
+    let tz = resolve_timezone(name).ok_or_else(|| anyhow::anyhow!("unknown timezone: {name}"))?;
+    // SAFETY: called from main before any threads are spawned.
+    unsafe { env::set_var("TZ", tz) };
+    Ok(())
+}
+
 pub(crate) fn static_context_limit_lookup(model_id: &str) -> Option<u64> {
     let m = model_id.to_lowercase();
     // Known variants; newer 1M-capable models must be listed before family fallbacks.
@@ -344,12 +600,204 @@ pub fn sanitized_project_name(project_dir: &str) -> String {
         .collect()
 }
 
+/// Sanitize a configured project directory (`~` allowed) like the names
+/// transcripts are stored under, keeping `*`/`?` glob wildcards.
+pub fn project_dir_pattern(dir: &str) -> String {
+    expand_home(dir)
+        .split_inclusive(['*', '?'])
+        .map(|part| match part.strip_suffix(['*', '?']) {
+            Some(literal) => sanitized_project_name(literal) + &part[literal.len()..],
+            None => sanitized_project_name(part),
+        })
+        .collect()
+}
+
+/// Match `text` against a glob where `*` is any run of characters and `?` is
+/// exactly one.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character and retry.
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Seconds a missing artifact is remembered; 0, the default, probes every time
+static ABSENT_TTL: AtomicI64 = AtomicI64::new(0);
+/// Known-absent entries (`kind:path` to expiry) and the marker file they were
+/// read from, loaded once per process
+static ABSENT: Mutex<Option<(PathBuf, HashMap<String, i64>)>> = Mutex::new(None);
+
+/// Install how long [`remember_absent`] entries last. Renders without the db
+/// cache, and `--now` replays, leave it at 0.
+pub fn set_absent_ttl(seconds: i64) {
+    ABSENT_TTL.store(seconds, Ordering::Relaxed);
+}
+
+/// One small JSON file beside the database, so a probe costs at most one read
+/// per render rather than a db open
+fn absent_marker_path() -> Option<PathBuf> {
+    let dir = match env::var("CLAUDE_STATUSLINE_DB_PATH") {
+        Ok(db_path) => Path::new(&db_path).parent()?.to_path_buf(),
+        Err(_) => directories::BaseDirs::new()?.home_dir().join(".claude"),
+    };
+    Some(dir.join("statusline-absent.json"))
+}
+
+/// Run `f` on the known-absent entries, loading them on first use
+fn with_absent<T>(f: impl FnOnce(&Path, &mut HashMap<String, i64>) -> T) -> Option<T> {
+    let marker = absent_marker_path()?;
+    let mut absent = ABSENT.lock().unwrap_or_else(|e| e.into_inner());
+    if absent.as_ref().is_none_or(|(path, _)| *path != marker) {
+        let entries = std::fs::read_to_string(&marker)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        *absent = Some((marker, entries));
+    }
+    let (marker, entries) = absent.as_mut()?;
+    Some(f(marker, entries))
+}
+
+fn absent_key(kind: &str, path: &Path) -> String {
+    format!("{kind}:{}", path.display())
+}
+
+/// Whether a recent render already found no `kind` artifact (credentials,
+/// `.beads`, Gas Town markers) for `path`, so the filesystem probe can be
+/// skipped until the entry expires.
+pub fn known_absent(kind: &str, path: &Path) -> bool {
+    if ABSENT_TTL.load(Ordering::Relaxed) <= 0 {
+        return false;
+    }
+    let now = Utc::now().timestamp();
+    with_absent(|_, entries| {
+        entries
+            .get(&absent_key(kind, path))
+            .is_some_and(|&expires| expires > now)
+    })
+    .unwrap_or(false)
+}
+
+/// Record that probing for `kind` under `path` found nothing.
+pub fn remember_absent(kind: &str, path: &Path) {
+    let ttl = ABSENT_TTL.load(Ordering::Relaxed);
+    if ttl <= 0 {
+        return;
+    }
+    let now = Utc::now().timestamp();
+    with_absent(|marker, entries| {
+        entries.retain(|_, expires| *expires > now);
+        entries.insert(absent_key(kind, path), now + ttl);
+        if let Ok(json) = serde_json::to_string(entries) {
+            let _ = std::fs::write(marker, json);
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serial_test::serial;
     use std::env;
 
+    #[test]
+    #[cfg(unix)]
+    fn run_with_timeout_reads_output_past_the_pipe_buffer() {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "head -c 300000 /dev/zero"]);
+        let output = run_with_timeout(cmd, Duration::from_secs(5)).expect("finished in time");
+        assert!(output.status.success());
+        assert_eq!(output.stdout.len(), 300_000);
+
+        let mut cmd = Command::new("sleep");
+        cmd.arg("5");
+        assert!(run_with_timeout(cmd, Duration::from_millis(100)).is_none());
+
+        // The shell exits at once, but its background child keeps stdout open
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "echo hi; sleep 5 &"]);
+        let started = Instant::now();
+        assert!(run_with_timeout(cmd, Duration::from_millis(200)).is_none());
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn issue_keys_come_from_branch_names() {
+        assert_eq!(issue_keys("feature/PROJ-123-login", &[]), ["PROJ-123"]);
+        assert_eq!(issue_keys("ENG-456/fix-#789", &[]), ["ENG-456", "#789"]);
+        assert!(issue_keys("main", &[]).is_empty());
+        assert!(issue_keys("release-2024/fix-2", &[]).is_empty());
+        assert_eq!(
+            issue_keys("team/ABC-1-and-ABC-1", &["ABC-[0-9]+".to_string()]),
+            ["ABC-1"]
+        );
+        assert_eq!(
+            issue_url("https://github.com/o/r/issues/{key}", "#789"),
+            "https://github.com/o/r/issues/789"
+        );
+    }
+
+    #[test]
+    fn protected_branches_cover_default_and_globs() {
+        assert!(is_protected_branch("main", None, &[]));
+        assert!(is_protected_branch("trunk", Some("trunk"), &[]));
+        assert!(!is_protected_branch("feature/x", Some("main"), &[]));
+        let release = vec!["release/*".to_string()];
+        assert!(is_protected_branch("release/1.2", None, &release));
+        assert!(!is_protected_branch("master", None, &release));
+    }
+
+    #[test]
+    fn subproject_dir_finds_nearest_package_root() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let root = repo.path();
+        std::fs::write(root.join("Cargo.toml"), "").unwrap();
+        let api = root.join("crates/api");
+        std::fs::create_dir_all(api.join("src/handlers")).unwrap();
+        std::fs::write(api.join("Cargo.toml"), "").unwrap();
+
+        assert_eq!(
+            subproject_dir(root, &api.join("src/handlers")).as_deref(),
+            Some("crates/api")
+        );
+        assert_eq!(subproject_dir(root, &root.join("crates")), None);
+        assert_eq!(subproject_dir(root, root), None);
+        assert_eq!(subproject_dir(&api, root), None);
+    }
+
+    #[test]
+    fn glob_match_handles_wildcards() {
+        assert!(glob_match("-work-acme*", "-work-acme-api"));
+        assert!(glob_match("-work-acme*", "-work-acme"));
+        assert!(glob_match("*-api", "-work-acme-api"));
+        assert!(glob_match("-work-a?me*", "-work-acme-web"));
+        assert!(glob_match("*acme*web", "-work-acme-api-web"));
+        assert!(!glob_match("-work-acme*", "-work-globex"));
+        assert!(!glob_match("-work-acme", "-work-acme-api"));
+    }
+
     #[test]
     #[serial]
     fn test_context_limit_for_model_display() {
@@ -571,4 +1019,43 @@ mod tests {
             "gemini-2.5-pro"
         );
     }
+
+    #[test]
+    fn test_resolve_timezone() {
+        assert_eq!(resolve_timezone("utc").as_deref(), Some("UTC"));
+        assert_eq!(resolve_timezone(""), None);
+        assert_eq!(resolve_timezone("../etc/passwd"), None);
+        assert_eq!(resolve_timezone("Not/AZone"), None);
+        if Path::new("/usr/share/zoneinfo/Europe/Berlin").is_file() {
+            assert_eq!(
+                resolve_timezone("Europe/Berlin").as_deref(),
+                Some("Europe/Berlin")
+            );
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn remembered_absence_is_scoped_by_kind_and_path() {
+        let dir = tempfile::tempdir().unwrap();
+        // SAFETY: Test runs serially, no concurrent env access
+        unsafe { env::set_var("CLAUDE_STATUSLINE_DB_PATH", dir.path().join("absent.db")) };
+        let project = dir.path().join("project");
+
+        // Off until a TTL is installed
+        remember_absent("beads", &project);
+        assert!(!known_absent("beads", &project));
+
+        set_absent_ttl(30);
+        assert!(!known_absent("beads", &project));
+        remember_absent("beads", &project);
+        assert!(known_absent("beads", &project));
+        assert!(!known_absent("gastown", &project));
+        assert!(dir.path().join("statusline-absent.json").exists());
+        assert!(!dir.path().join("absent.db").exists());
+
+        set_absent_ttl(0);
+        assert!(!known_absent("beads", &project));
+        unsafe { env::remove_var("CLAUDE_STATUSLINE_DB_PATH") };
+    }
 }
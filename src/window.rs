@@ -2,9 +2,12 @@
 //!
 //! Handles 5-hour window calculations for usage tracking
 
-use crate::models::Entry;
-use crate::usage::{calculate_session_complexity, detect_rapid_exchange};
-use crate::utils::{WINDOW_DURATION_HOURS, WINDOW_DURATION_SECONDS, sanitized_project_name};
+use crate::models::{Block, Entry, ServerToolUse};
+use crate::pricing::service_tier_adjustment;
+use crate::usage::{calculate_session_complexity, detect_rapid_exchange, identify_blocks};
+use crate::utils::{
+    WINDOW_DURATION_HOURS, WINDOW_DURATION_SECONDS, floor_to_hour, sanitized_project_name,
+};
 use chrono::{DateTime, Duration, Local, Timelike, Utc};
 
 // Session reset hours in local time: 1am, 7am, 1pm, 7pm
@@ -109,27 +112,68 @@ pub struct WindowMetrics {
     pub session_tokens_output: u64,
     pub session_tokens_cache_create: u64,
     pub session_tokens_cache_read: u64,
-    pub web_search_requests: u64,
+    /// Server tool requests within the window, per tool
+    pub server_tools: ServerToolUse,
     pub service_tier: Option<String>,
+    /// Share of `total_cost` billed at batch rates
+    pub batch_cost: f64,
+    /// Cost difference from list price for entries on the active `service_tier`
+    pub tier_adjustment: f64,
     pub tpm: f64,
     pub tpm_indicator: f64,
     pub session_nc_tpm: f64,
     pub global_nc_tpm: f64,
     pub cost_per_hour: f64,
     pub remaining_minutes: f64,
+    pub efficiency: Efficiency,
 }
 
-/// Scope for window calculations
+/// Ratios derived from window token totals and cost. Each is `None` when its
+/// denominator is zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Efficiency {
+    /// Output tokens per non-cache input token
+    pub output_input_ratio: Option<f64>,
+    /// Cost in USD per 1K output tokens
+    pub cost_per_1k_output: Option<f64>,
+    /// Share of prompt tokens served from cache reads, 0.0..=1.0
+    pub cache_read_ratio: Option<f64>,
+    /// All tokens (including cache) per USD
+    pub tokens_per_dollar: Option<f64>,
+}
+
+impl Efficiency {
+    pub fn from_totals(
+        input: u64,
+        output: u64,
+        cache_create: u64,
+        cache_read: u64,
+        cost: f64,
+    ) -> Self {
+        let ratio = |num: f64, den: f64| (den > 0.0).then(|| num / den);
+        let prompt = (input + cache_create + cache_read) as f64;
+        Self {
+            output_input_ratio: ratio(output as f64, input as f64),
+            cost_per_1k_output: ratio(cost * 1000.0, output as f64),
+            cache_read_ratio: ratio(cache_read as f64, prompt),
+            tokens_per_dollar: ratio(prompt + output as f64, cost),
+        }
+    }
+}
+
+/// Scope for window cost/token totals
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WindowScope {
     Global,
     Project,
+    Session,
 }
 
 /// Scope for burn rate calculations
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BurnScope {
     Session,
+    Project,
     Global,
 }
 
@@ -175,12 +219,19 @@ pub fn calculate_window_metrics(
         .collect();
     global_entries.sort_by_key(|e| e.ts);
 
-    // Optional project-scoped view retained only for display/burn by scope
+    let project_name = project_dir.map(sanitized_project_name);
+    let in_project = |e: &Entry| match project_name.as_deref() {
+        Some(proj) => e.project.as_deref() == Some(proj),
+        None => true,
+    };
+
+    // Optional project/session-scoped view retained only for display/burn by scope
     let mut window_entries: Vec<&Entry> = global_entries.clone();
-    if let WindowScope::Project = window_scope {
-        if let Some(pd) = project_dir {
-            let proj = sanitized_project_name(pd);
-            window_entries.retain(|e| e.project.as_deref() == Some(&proj));
+    match window_scope {
+        WindowScope::Global => {}
+        WindowScope::Project => window_entries.retain(|e| in_project(e)),
+        WindowScope::Session => {
+            window_entries.retain(|e| e.session_id.as_deref() == Some(session_id))
         }
     }
 
@@ -194,28 +245,48 @@ pub fn calculate_window_metrics(
     let mut tokens_cache_create: u64 = 0;
     let mut tokens_cache_read: u64 = 0;
     let mut total_cost: f64 = 0.0;
+    let mut batch_cost: f64 = 0.0;
     for e in &global_entries {
         tokens_input += e.input;
         tokens_output += e.output;
         tokens_cache_create += e.cache_create;
         tokens_cache_read += e.cache_read;
         total_cost += e.cost;
+        if e.is_batch() {
+            batch_cost += e.cost;
+        }
     }
     // Cost is already computed per entry in usage.rs (including web_search when recomputed);
     // do not add web_search again here to avoid double counting.
 
-    if matches!(window_scope, WindowScope::Project) {
+    if !matches!(window_scope, WindowScope::Global) {
         tokens_input = window_entries.iter().map(|e| e.input).sum();
         tokens_output = window_entries.iter().map(|e| e.output).sum();
         tokens_cache_create = window_entries.iter().map(|e| e.cache_create).sum();
         tokens_cache_read = window_entries.iter().map(|e| e.cache_read).sum();
         total_cost = window_entries.iter().map(|e| e.cost).sum();
+        batch_cost = window_entries
+            .iter()
+            .filter(|e| e.is_batch())
+            .map(|e| e.cost)
+            .sum();
     }
 
+    // Premium (or discount) the active tier added over list price; scoped
+    // entries equal the global set under global scope.
+    let tier_adjustment: f64 = window_entries
+        .iter()
+        .filter(|e| e.service_tier == service_tier)
+        .map(|e| service_tier_adjustment(e.cost, &e.server_tools, e.service_tier.as_deref()))
+        .sum();
+
     let total_tokens =
         (tokens_input + tokens_output + tokens_cache_create + tokens_cache_read) as f64;
     let noncache_tokens = (tokens_input + tokens_output) as f64;
-    let web_search_requests: u64 = window_entries.iter().map(|e| e.web_search_requests).sum();
+    let mut server_tools = ServerToolUse::default();
+    for e in &window_entries {
+        server_tools.accumulate(&e.server_tools);
+    }
 
     // Calculate session-specific burn rate
     let mut session_input: u64 = 0;
@@ -272,7 +343,7 @@ pub fn calculate_window_metrics(
     };
 
     // Enhanced burn rate with rapid exchange detection
-    let (is_rapid, enhanced_burn_rate) = detect_rapid_exchange(entries, session_id, 15);
+    let (is_rapid, enhanced_burn_rate) = detect_rapid_exchange(entries, session_id, 15, now_utc);
 
     // Adjust burn rate if rapid exchange detected (indicates active development)
     let adjusted_session_tpm = if is_rapid && enhanced_burn_rate > session_nc_tpm {
@@ -342,6 +413,14 @@ pub fn calculate_window_metrics(
     // Usage percent reflects account-level (global) consumption against cap.
     let tpm_indicator = match burn_scope {
         BurnScope::Session => complexity_adjusted_tpm,
+        BurnScope::Project => {
+            let project_entries: Vec<&Entry> = global_entries
+                .iter()
+                .copied()
+                .filter(|e| in_project(e))
+                .collect();
+            noncache_tpm(&project_entries)
+        }
         BurnScope::Global => blended_nc_tpm,
     };
 
@@ -359,14 +438,35 @@ pub fn calculate_window_metrics(
         session_tokens_output: session_output,
         session_tokens_cache_create: session_cache_create,
         session_tokens_cache_read: session_cache_read,
-        web_search_requests,
+        server_tools,
         service_tier,
+        batch_cost,
+        tier_adjustment,
         tpm,
         tpm_indicator,
         session_nc_tpm,
         global_nc_tpm,
         cost_per_hour,
         remaining_minutes,
+        efficiency: Efficiency::from_totals(
+            tokens_input,
+            tokens_output,
+            tokens_cache_create,
+            tokens_cache_read,
+            total_cost,
+        ),
+    }
+}
+
+/// Non-cache (input+output) tokens per minute across time-sorted entries.
+fn noncache_tpm(entries: &[&Entry]) -> f64 {
+    match (entries.first(), entries.last()) {
+        (Some(first), Some(last)) if entries.len() >= 2 => {
+            let minutes = ((last.ts - first.ts).num_seconds().max(60) as f64) / 60.0;
+            let tokens: u64 = entries.iter().map(|e| e.input + e.output).sum();
+            tokens as f64 / minutes
+        }
+        _ => 0.0,
     }
 }
 
@@ -394,14 +494,6 @@ pub fn window_bounds(
     }
 }
 
-/// Floor a timestamp to the beginning of the hour
-fn floor_to_hour(ts: DateTime<Utc>) -> DateTime<Utc> {
-    ts.with_minute(0)
-        .and_then(|d| d.with_second(0))
-        .and_then(|d| d.with_nanosecond(0))
-        .unwrap_or(ts)
-}
-
 /// Find session boundaries by detecting gaps in activity
 #[allow(dead_code)]
 fn find_session_boundaries(entries: &[Entry], gap_threshold: Duration) -> Vec<DateTime<Utc>> {
@@ -495,71 +587,13 @@ fn heuristic_active_block_bounds(
         return None;
     }
 
-    // Sort entries by timestamp
-    let mut sorted_entries = entries.to_vec();
-    sorted_entries.sort_by_key(|e| e.ts);
-
-    let session_duration = chrono::TimeDelta::hours(WINDOW_DURATION_HOURS);
-    let session_duration_ms = session_duration.num_milliseconds();
-
-    // Identify session blocks using the same algorithm as claude-powerline-rust
-    let mut blocks: Vec<Vec<DateTime<Utc>>> = Vec::new();
-    let mut current_block: Vec<DateTime<Utc>> = Vec::new();
-    let mut current_block_start: Option<DateTime<Utc>> = None;
-
-    for entry in &sorted_entries {
-        let entry_time = entry.ts;
-
-        match current_block_start {
-            None => {
-                // Start first block - floor to the hour
-                current_block_start = Some(floor_to_hour(entry_time));
-                current_block.push(entry_time);
-            }
-            Some(block_start) => {
-                let time_since_block_start = (entry_time - block_start).num_milliseconds();
-                let time_since_last_entry = if let Some(last) = current_block.last() {
-                    (entry_time - *last).num_milliseconds()
-                } else {
-                    0
-                };
-
-                // New block starts if: time since block start > 5 hours OR time since last entry > 5 hours
-                if time_since_block_start > session_duration_ms
-                    || time_since_last_entry > session_duration_ms
-                {
-                    // Finalize current block
-                    if !current_block.is_empty() {
-                        blocks.push(current_block.clone());
-                    }
-
-                    // Start new block
-                    current_block_start = Some(floor_to_hour(entry_time));
-                    current_block = vec![entry_time];
-                } else {
-                    current_block.push(entry_time);
-                }
-            }
-        }
-    }
-
-    // Don't forget the last block
-    if !current_block.is_empty() {
-        blocks.push(current_block);
-    }
-
-    // Find the active block (most recent that's still within session duration)
-    for block in blocks.iter().rev() {
-        if let (Some(first), Some(last)) = (block.first(), block.last()) {
-            let block_start = floor_to_hour(*first);
-            let block_end = block_start + session_duration;
-
-            // Check if block is active: current time within 5 hours of last entry AND before theoretical end
-            let time_since_last = now_utc - *last;
-            if time_since_last < session_duration && now_utc < block_end {
-                return Some((block_start, block_end));
-            }
-        }
+    // Active block from the shared block grouping (floored hour, 5h splits)
+    if let Some(block) = identify_blocks(entries, now_utc)
+        .into_iter()
+        .rev()
+        .find(|b| !b.is_gap && b.is_active)
+    {
+        return Some((block.start, block.end));
     }
 
     // Fallback: Use progressive lookback if the block-based approach fails
@@ -568,6 +602,13 @@ fn heuristic_active_block_bounds(
     }
 
     // Last resort: rolling 5-hour window ending at 'now'
-    let start = now_utc - session_duration;
+    let start = now_utc - Duration::hours(WINDOW_DURATION_HOURS);
     Some((start, now_utc))
 }
+
+/// The most recent `limit` blocks from [`identify_blocks`], oldest first.
+pub fn recent_blocks(entries: &[Entry], now_utc: DateTime<Utc>, limit: usize) -> Vec<Block> {
+    let mut blocks = identify_blocks(entries, now_utc);
+    let skip = blocks.len().saturating_sub(limit);
+    blocks.split_off(skip)
+}
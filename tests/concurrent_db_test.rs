@@ -1,3 +1,6 @@
+// Exercises the SQLite cache, which only exists with the `db` feature
+#![cfg(feature = "db")]
+
 use claude_statusline::db::get_global_usage;
 use serial_test::serial;
 use std::path::PathBuf;
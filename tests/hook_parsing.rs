@@ -64,6 +64,19 @@ fn parses_null_context_percentages_between_api_calls() {
     assert_eq!(hook.context_window.remaining_percentage, 0);
 }
 
+#[test]
+fn parses_hook_without_line_counts() {
+    let payload = MINIMUM_HOOK.replace(
+        ",\n    \"total_lines_added\": 0,\n    \"total_lines_removed\": 0",
+        "",
+    );
+    assert!(!payload.contains("total_lines_added"));
+    let hook: HookJson = serde_json::from_str(&payload).expect("older hook should parse");
+
+    assert_eq!(hook.cost.total_lines_added, None);
+    assert_eq!(hook.cost.total_lines_removed, None);
+}
+
 #[test]
 fn parses_modern_hook_workspace_and_remote_extras() {
     let hook: HookJson = serde_json::from_str(
@@ -164,7 +177,7 @@ fn parses_modern_hook_reporting_fields() {
 
     assert!(hook.fast_mode);
     assert_eq!(hook.cost.total_cost_usd, 1.23);
-    assert_eq!(hook.cost.total_lines_added, 5);
+    assert_eq!(hook.cost.total_lines_added, Some(5));
     assert_eq!(
         hook.effort.as_ref().map(|effort| effort.level.as_str()),
         Some("xhigh")
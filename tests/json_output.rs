@@ -13,8 +13,8 @@ fn default_hook_cost() -> HookCost {
         total_cost_usd: 0.0,
         total_duration_ms: 0,
         total_api_duration_ms: 0,
-        total_lines_added: 0,
-        total_lines_removed: 0,
+        total_lines_added: Some(0),
+        total_lines_removed: Some(0),
     }
 }
 
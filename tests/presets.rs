@@ -68,9 +68,9 @@ fn preset_minimal_hides_secondary_tokens_and_skips_expensive_subsystems() {
     assert!(args.no_subsystem_beads);
     assert!(args.no_subsystem_gastown);
     assert!(args.no_subsystem_usage_api);
-    // git + db_cache stay on (cheap / essential)
+    // git + db_cache stay on (cheap / essential); no-db builds have no cache
     assert!(!args.no_subsystem_git);
-    assert!(!args.no_subsystem_db_cache);
+    assert_eq!(args.no_subsystem_db_cache, !cfg!(feature = "db"));
 }
 
 #[test]
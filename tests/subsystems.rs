@@ -6,8 +6,9 @@ fn subsystem_flags_default_to_enabled() {
     assert!(!args.no_subsystem_git);
     assert!(!args.no_subsystem_beads);
     assert!(!args.no_subsystem_gastown);
-    assert!(!args.no_subsystem_db_cache);
     assert!(!args.no_subsystem_usage_api);
+    // Builds without the `db` feature have no cache to enable
+    assert_eq!(args.no_subsystem_db_cache, !cfg!(feature = "db"));
 }
 
 #[test]
@@ -48,7 +49,7 @@ fn config_file_disables_subsystem_via_subsystems_section() {
     assert!(args.no_subsystem_beads);
     assert!(args.no_subsystem_usage_api);
     assert!(!args.no_subsystem_gastown);
-    assert!(!args.no_subsystem_db_cache);
+    assert_eq!(args.no_subsystem_db_cache, !cfg!(feature = "db"));
 }
 
 #[test]
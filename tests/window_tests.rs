@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use claude_statusline::models::Entry;
-use claude_statusline::window::{BurnScope, WindowAnchor, WindowScope, calculate_window_metrics};
+use claude_statusline::window::{
+    BurnScope, Efficiency, WindowAnchor, WindowScope, calculate_window_metrics,
+};
 
 fn create_test_entry(
     ts: DateTime<Utc>,
@@ -15,7 +17,7 @@ fn create_test_entry(
         output,
         cache_create: 0,
         cache_read: 0,
-        web_search_requests: 0,
+        server_tools: Default::default(),
         speed: None,
         service_tier: None,
         cost,
@@ -77,7 +79,7 @@ fn test_window_scope_project_filtering() {
     let now = Utc::now();
     let mut first_project =
         create_test_entry(now - chrono::Duration::hours(3), 1000, 500, 1.0, "session1");
-    first_project.web_search_requests = 1;
+    first_project.server_tools.add("web_search", 1);
     let mut entries = vec![first_project];
 
     // Add entry with different project
@@ -89,7 +91,7 @@ fn test_window_scope_project_filtering() {
         "session1",
     );
     other_entry.project = Some("other-project".to_string());
-    other_entry.web_search_requests = 7;
+    other_entry.server_tools.add("web_search", 7);
     entries.push(other_entry);
 
     let mut second_project = create_test_entry(
@@ -99,7 +101,7 @@ fn test_window_scope_project_filtering() {
         3.0,
         "session1",
     );
-    second_project.web_search_requests = 2;
+    second_project.server_tools.add("web_search", 2);
     entries.push(second_project);
 
     let metrics = calculate_window_metrics(
@@ -116,11 +118,48 @@ fn test_window_scope_project_filtering() {
     // Should only include test-project entries for scoped totals and rates.
     assert_eq!(metrics.tokens_input, 4000);
     assert_eq!(metrics.tokens_output, 2000);
-    assert_eq!(metrics.web_search_requests, 3);
+    assert_eq!(metrics.server_tools.requests("web_search"), 3);
     assert_eq!(metrics.total_cost, 4.0);
     assert_eq!(metrics.cost_per_hour, 2.0);
 }
 
+#[test]
+fn test_batch_cost_counts_only_batch_tier_entries_in_scope() {
+    let now = Utc::now();
+    let standard = create_test_entry(now - chrono::Duration::hours(3), 1000, 500, 1.0, "session1");
+    let mut batch = create_test_entry(
+        now - chrono::Duration::hours(2),
+        2000,
+        1000,
+        3.0,
+        "session1",
+    );
+    batch.service_tier = Some("batch".to_string());
+    let mut other_batch = create_test_entry(
+        now - chrono::Duration::hours(1),
+        2000,
+        1000,
+        50.0,
+        "session1",
+    );
+    other_batch.project = Some("other-project".to_string());
+    other_batch.service_tier = Some("batch".to_string());
+
+    let metrics = calculate_window_metrics(
+        &[standard, batch, other_batch],
+        "session1",
+        Some("test-project"),
+        now,
+        None,
+        WindowScope::Project,
+        BurnScope::Session,
+        WindowAnchor::Provider,
+    );
+
+    assert_eq!(metrics.total_cost, 4.0);
+    assert_eq!(metrics.batch_cost, 3.0);
+}
+
 #[test]
 fn test_burn_scope_session_vs_global() {
     let now = Utc::now();
@@ -170,6 +209,116 @@ fn test_burn_scope_session_vs_global() {
     assert_eq!(session_metrics.total_tokens, global_metrics.total_tokens);
 }
 
+#[test]
+fn test_window_efficiency_ratios() {
+    let now = Utc::now();
+    let mut entry = create_test_entry(now - chrono::Duration::hours(1), 1000, 500, 2.0, "s1");
+    entry.cache_create = 1000;
+    entry.cache_read = 8000;
+    let metrics = calculate_window_metrics(
+        &[entry],
+        "s1",
+        None,
+        now,
+        None,
+        WindowScope::Global,
+        BurnScope::Session,
+        WindowAnchor::Provider,
+    );
+
+    let eff = metrics.efficiency;
+    assert_eq!(eff.output_input_ratio, Some(0.5));
+    assert_eq!(eff.cost_per_1k_output, Some(4.0));
+    assert_eq!(eff.cache_read_ratio, Some(0.8));
+    assert_eq!(eff.tokens_per_dollar, Some(5250.0));
+
+    let empty = Efficiency::from_totals(0, 0, 0, 0, 0.0);
+    assert_eq!(empty, Efficiency::default());
+}
+
+#[test]
+fn test_window_scope_session_filtering() {
+    let now = Utc::now();
+    let entries = vec![
+        create_test_entry(now - chrono::Duration::hours(3), 1000, 500, 1.0, "session1"),
+        create_test_entry(
+            now - chrono::Duration::hours(2),
+            2000,
+            1000,
+            5.0,
+            "session2",
+        ),
+        create_test_entry(
+            now - chrono::Duration::hours(1),
+            3000,
+            1500,
+            3.0,
+            "session1",
+        ),
+    ];
+
+    let metrics = calculate_window_metrics(
+        &entries,
+        "session1",
+        Some("test-project"),
+        now,
+        None,
+        WindowScope::Session,
+        BurnScope::Session,
+        WindowAnchor::Provider,
+    );
+
+    assert_eq!(metrics.tokens_input, 4000);
+    assert_eq!(metrics.tokens_output, 2000);
+    assert_eq!(metrics.total_cost, 4.0);
+}
+
+#[test]
+fn test_burn_scope_project_ignores_other_projects() {
+    let now = Utc::now();
+    let mut other = create_test_entry(
+        now - chrono::Duration::minutes(20),
+        50_000,
+        50_000,
+        1.0,
+        "session2",
+    );
+    other.project = Some("other-project".to_string());
+    let entries = vec![
+        create_test_entry(
+            now - chrono::Duration::minutes(40),
+            1000,
+            0,
+            0.1,
+            "session1",
+        ),
+        other,
+        create_test_entry(
+            now - chrono::Duration::minutes(30),
+            0,
+            1000,
+            0.1,
+            "session2",
+        ),
+    ];
+
+    let metrics = calculate_window_metrics(
+        &entries,
+        "session1",
+        Some("test-project"),
+        now,
+        None,
+        WindowScope::Global,
+        BurnScope::Project,
+        WindowAnchor::Provider,
+    );
+
+    // 2000 project tokens across 10 minutes; the other project is excluded.
+    assert!((metrics.tpm_indicator - 200.0).abs() < 1e-6);
+    // Window totals stay global.
+    assert_eq!(metrics.tokens_input, 51_000);
+}
+
 #[test]
 fn test_reset_anchor_window_calculation() {
     let now = Utc::now();
@@ -222,3 +371,70 @@ fn test_empty_entries() {
     assert_eq!(metrics.total_tokens, 0.0);
     assert_eq!(metrics.tpm, 0.0);
 }
+
+#[test]
+fn test_pinned_now_makes_burn_rate_deterministic() {
+    // US DST start: a replayed "now" must drive the window, not the wall clock.
+    let now = DateTime::parse_from_rfc3339("2025-03-09T07:30:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let entries = vec![
+        create_test_entry(now - chrono::Duration::minutes(9), 4000, 2000, 0.4, "s1"),
+        create_test_entry(now - chrono::Duration::minutes(6), 4000, 2000, 0.4, "s1"),
+        create_test_entry(now - chrono::Duration::minutes(3), 4000, 2000, 0.4, "s1"),
+    ];
+
+    let (is_rapid, burn) = claude_statusline::usage::detect_rapid_exchange(&entries, "s1", 15, now);
+    assert!(is_rapid);
+    assert_eq!(burn, 3000.0);
+
+    let metrics = calculate_window_metrics(
+        &entries,
+        "s1",
+        None,
+        now,
+        None,
+        WindowScope::Global,
+        BurnScope::Session,
+        WindowAnchor::Log,
+    );
+    // The window floors the first entry (07:21) to the hour and runs five
+    // hours from there, measured against the pinned now, not the wall clock
+    let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+    assert_eq!(metrics.start, at("2025-03-09T07:00:00Z"));
+    assert_eq!(metrics.end, at("2025-03-09T12:00:00Z"));
+    assert_eq!(metrics.remaining_minutes, 270.0);
+    assert_eq!(metrics.tpm, 3000.0);
+    assert_eq!(metrics.tpm_indicator, 15000.0);
+    assert!((metrics.cost_per_hour - 12.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_recent_blocks_emit_gap_between_idle_stretches() {
+    let now = DateTime::parse_from_rfc3339("2025-06-02T20:30:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let at = |h: i64, m: i64| now - chrono::Duration::hours(h) + chrono::Duration::minutes(m);
+    let entries = vec![
+        create_test_entry(at(14, 0), 1000, 100, 0.5, "s1"),
+        create_test_entry(at(13, 0), 1000, 100, 0.5, "s1"),
+        create_test_entry(at(1, 0), 2000, 200, 1.0, "s1"),
+        create_test_entry(at(0, -10), 2000, 200, 1.0, "s1"),
+    ];
+
+    let blocks = claude_statusline::window::recent_blocks(&entries, now, 8);
+
+    assert_eq!(blocks.len(), 3);
+    assert!(!blocks[0].is_gap && !blocks[0].is_active);
+    assert_eq!(blocks[0].entries.len(), 2);
+    assert!(blocks[1].is_gap);
+    assert_eq!(blocks[1].start, at(13, 0) + chrono::Duration::hours(5));
+    assert_eq!(blocks[1].end, at(1, 0));
+    assert!(blocks[2].is_active);
+    assert_eq!(blocks[2].cost, 2.0);
+    assert_eq!(blocks[2].tokens.input, 4000);
+
+    let last_two = claude_statusline::window::recent_blocks(&entries, now, 2);
+    assert_eq!(last_two.len(), 2);
+    assert!(last_two[0].is_gap);
+}